@@ -0,0 +1,88 @@
+//! Runs the machines as Actix actors. The frontend is the actor, every event is sent to it wrapped in an
+//! `FsmMessage`, and the timers are polled on an interval of the actor's context.
+//!
+//! Example : `let addr = fsm.start_actor(); addr.send(FsmMessage(Connect)).await??;`
+
+use actix::{Actor, AsyncContext, Addr, Context, Handler, Message, MessageResult};
+
+use crate::lib::*;
+use crate::{FsmBackend, FsmCurrentState, FsmEventQueue, FsmFrontend, FsmResult, FsmStates, FsmTimers, Inspect};
+
+type FsmCurrentStates<F> = <<F as FsmBackend>::States as FsmStates<F>>::CurrentState;
+
+/// How often the actor dispatches the triggered timers.
+pub const FSM_ACTOR_TIMERS_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An event for the machine's actor. It's dispatched and run to completition, with the result of its dispatch.
+#[derive(Debug, Clone)]
+pub struct FsmMessage<E>(pub E);
+
+impl<E: 'static> Message for FsmMessage<E> {
+    type Result = FsmResult<()>;
+}
+
+/// Ask the machine's actor for its current states.
+pub struct FsmGetCurrentStates<F>(PhantomData<F>);
+
+impl<F> FsmGetCurrentStates<F> {
+    pub fn new() -> Self {
+        FsmGetCurrentStates(PhantomData)
+    }
+}
+
+impl<F> Default for FsmGetCurrentStates<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend + 'static> Message for FsmGetCurrentStates<F> {
+    type Result = FsmCurrentStates<F>;
+}
+
+impl<F, Q, I, T> Actor for FsmFrontend<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, Self: Unpin + 'static
+{
+    type Context = Context<Self>;
+
+    /// Starts the machine if it wasn't started yet, and the polling of its timers.
+    fn started(&mut self, ctx: &mut Self::Context) {
+        if FsmCurrentState::all_stopped(self.get_current_states().as_ref()) {
+            let _ = self.start();
+        }
+
+        ctx.run_interval(FSM_ACTOR_TIMERS_INTERVAL, |fsm, _ctx| {
+            let _ = fsm.dispatch_timer_events();
+        });
+    }
+}
+
+impl<F, Q, I, T, E> Handler<FsmMessage<E>> for FsmFrontend<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, Self: Unpin + 'static,
+        E: Into<<F as FsmBackend>::Events> + 'static
+{
+    type Result = FsmResult<()>;
+
+    fn handle(&mut self, msg: FsmMessage<E>, _ctx: &mut Self::Context) -> Self::Result {
+        self.dispatch(msg.0)
+    }
+}
+
+impl<F, Q, I, T> Handler<FsmGetCurrentStates<F>> for FsmFrontend<F, Q, I, T>
+    where F: FsmBackend + 'static, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, Self: Unpin + 'static
+{
+    type Result = MessageResult<FsmGetCurrentStates<F>>;
+
+    fn handle(&mut self, _msg: FsmGetCurrentStates<F>, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.get_current_states())
+    }
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, Self: Unpin + 'static
+{
+    /// Start the machine's actor on the current Actix system.
+    pub fn start_actor(self) -> Addr<Self> {
+        Actor::start(self)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::lib::*;
 
-use crate::{FsmBackend, fsm::EventContext};
+use crate::{FsmBackend, FsmEntryPoint, FsmResult, FsmState, fsm::EventContext};
 use super::{FsmQueueMock, FsmStateBuilder};
 
 pub struct FsmEventBuilderState<'a, TFsm, TContext, TEvent, TState> {
@@ -41,15 +41,95 @@ impl<'a, TFsm, TContext, TEvent, TState> FsmEventBuilderTransition<'a, TFsm, TCo
     where TFsm: FsmBackend
 {
     /// An action that happens when the currently active state receives this event. No transitions.
+    /// Like the guards, it can be a function that is shared between the transitions.
     pub fn action<TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TState)>(&mut self, _action: TAction) -> &mut Self {
         self
     }
     
-    /// A guard for executing this action.
+    /// A guard for executing this action. Can be a closure or a function that is shared between the
+    /// transitions, the function's name is then used by the inspectors and the exported diagrams.
     pub fn guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
         self
     }
 
+    /// A fallible action. A failed action aborts the dispatch and the error is returned by it. In a self
+    /// transition the state was already exited, so it's entered again with its entry actions and timers.
+    /// Can't be combined with `action`.
+    pub fn try_action<TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TState) -> FsmResult<()>>(&mut self, _action: TAction) -> &mut Self {
+        self
+    }
+
+    /// Both this guard and the previously declared ones have to accept the event. The terms are evaluated from
+    /// the left with short-circuiting, each evaluated term is reported to the inspection under its own name and
+    /// the exports label them separately, like `[has_credit] && ![is_blocked]`. The closures are named after
+    /// their position, like `guard_2`.
+    pub fn and_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// Either this guard or the previously declared ones have to accept the event.
+    pub fn or_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// Negates the previously declared guards.
+    pub fn not(&mut self) -> &mut Self {
+        self
+    }
+
+    /// A fallible guard for executing this action. A failed guard aborts the dispatch and the error is
+    /// returned by it. Can't be combined with `guard`.
+    pub fn try_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> FsmResult<bool>>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// An asynchronous action, awaited after the synchronous `action`. The machine can then only be
+    /// dispatched with `dispatch_async`, the sync dispatch fails with `FsmError::AsyncRequired`.
+    pub fn action_async<TAction, TFut>(&mut self, _action: TAction) -> &mut Self
+        where
+            TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TState) -> TFut,
+            TFut: Future<Output = ()>
+    {
+        self
+    }
+
+    /// An asynchronous guard for executing this action. The machine can then only be dispatched with
+    /// `dispatch_async`, the sync dispatch fails with `FsmError::AsyncRequired`, and `peek_dispatch`
+    /// treats it as a rejecting guard. Can't be combined with `guard`.
+    pub fn guard_async<TGuard, TFut>(&mut self, _guard: TGuard) -> &mut Self
+        where
+            TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> TFut,
+            TFut: Future<Output = bool>
+    {
+        self
+    }
+
+    /// Take the transition on the first event, then only once the event was quiet for the window, like for
+    /// a bouncing button. The repetitions within the window are ignored like by a rejecting guard, and extend
+    /// it. Measured with the timers' clock, the events aren't filtered without one.
+    pub fn debounce(&mut self, _window: Duration) -> &mut Self {
+        self
+    }
+
+    /// Take the transition at most once per window, like for a chattering sensor. The repetitions are ignored
+    /// like by a rejecting guard. Measured with the timers' clock, the events aren't filtered without one.
+    pub fn throttle(&mut self, _window: Duration) -> &mut Self {
+        self
+    }
+
+    /// Take the transition at most `max` times in a row, then treat it like a rejecting guard, so that the
+    /// following transition on the event is taken as the fallback. The region counts the retries, readable
+    /// with `ctx.attempts()`, until it enters a state that's neither the source nor the target of one of its
+    /// retries, like the fallback's target. The retry is counted before the transition's actions, a failed
+    /// action still uses it up, unless the machine is `transactional` and rolls the count back. Without the
+    /// `std` feature, only the first `FSM_TIMER_STATUS_CAPACITY` regions can retry, the others fail the dispatch
+    /// with `FsmTimerError::RetriesOverCapacity`.
+    ///
+    /// Example : `.on_event::<Failed>().transition_to::<Backoff>().retry(3).transition_to::<Offline>()`
+    pub fn retry(&mut self, _max: u32) -> &mut Self {
+        self
+    }
+
     /// A type for this transition. The struct for the transition will be generated.
     pub fn with_transition_ty<TTransition>(&mut self) -> &mut Self {
         self
@@ -65,18 +145,147 @@ pub struct FsmEventBuilderTransitionFull<'a, TFsm, TContext, TEvent, TStateFrom,
 impl<'a, TFsm, TContext, TEvent, TStateFrom, TStateTo> FsmEventBuilderTransitionFull<'a, TFsm, TContext, TEvent, TStateFrom, TStateTo> 
     where TFsm: FsmBackend
 {
-    /// An action that happens between the transitions from the two states.
+    /// An action that happens between the transitions from the two states. Like the guards, it can be
+    /// a function that is shared between the transitions.
     pub fn action<TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TStateFrom, &mut TStateTo)>(&mut self, _action: TAction) -> &mut Self {
         self
     }
 
     /// A guard for starting this transition from one state to another, including executing the action.
+    /// Can be a closure or a function that is shared between the transitions, the function's name is then
+    /// used by the inspectors and the exported diagrams. The states of all the regions can be read with
+    /// `states.get::<TState>()`.
+    ///
+    /// Example : `fn is_paid<Q>(ev: &Vend, ctx: &EventContext<Vending, Q>, states: &VendingStates) -> bool`
     pub fn guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
         self
     }
 
+    /// A fallible action that happens between the transitions from the two states. A failed action aborts
+    /// the transition, the second state isn't entered and the error is returned by the dispatch. The first
+    /// state was already exited, so it's entered again with its entry actions and timers and stays the
+    /// current state, the next dispatch is handled by it. Can't be combined with `action`.
+    pub fn try_action<TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TStateFrom, &mut TStateTo) -> FsmResult<()>>(&mut self, _action: TAction) -> &mut Self {
+        self
+    }
+
+    /// Both this guard and the previously declared ones have to accept the event. The terms are evaluated from
+    /// the left with short-circuiting, each evaluated term is reported to the inspection under its own name and
+    /// the exports label them separately, like `[has_credit] && ![is_blocked]`. The closures are named after
+    /// their position, like `guard_2`.
+    pub fn and_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// Either this guard or the previously declared ones have to accept the event.
+    pub fn or_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> bool>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// Negates the previously declared guards.
+    pub fn not(&mut self) -> &mut Self {
+        self
+    }
+
+    /// A fallible guard for this transition. A failed guard aborts the dispatch and the error is returned
+    /// by it. Can't be combined with `guard`.
+    pub fn try_guard<TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> FsmResult<bool>>(&mut self, _guard: TGuard) -> &mut Self {
+        self
+    }
+
+    /// An asynchronous action that happens between the transitions from the two states, awaited after the
+    /// synchronous `action`. The machine can then only be dispatched with `dispatch_async`, the sync
+    /// dispatch fails with `FsmError::AsyncRequired`.
+    pub fn action_async<TAction, TFut>(&mut self, _action: TAction) -> &mut Self
+        where
+            TAction: Fn(&TEvent, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &mut TStateFrom, &mut TStateTo) -> TFut,
+            TFut: Future<Output = ()>
+    {
+        self
+    }
+
+    /// An asynchronous guard for this transition. The machine can then only be dispatched with
+    /// `dispatch_async`, the sync dispatch fails with `FsmError::AsyncRequired`, and `peek_dispatch`
+    /// treats it as a rejecting guard. Can't be combined with `guard`.
+    pub fn guard_async<TGuard, TFut>(&mut self, _guard: TGuard) -> &mut Self
+        where
+            TGuard: Fn(&TEvent, &EventContext<'a, TFsm, FsmQueueMock<TFsm>>, &<TFsm as FsmBackend>::States) -> TFut,
+            TFut: Future<Output = bool>
+    {
+        self
+    }
+
+    /// Take the transition on the first event, then only once the event was quiet for the window, like for
+    /// a bouncing button. The repetitions within the window are ignored like by a rejecting guard, and extend
+    /// it. Measured with the timers' clock, the events aren't filtered without one.
+    pub fn debounce(&mut self, _window: Duration) -> &mut Self {
+        self
+    }
+
+    /// Take the transition at most once per window, like for a chattering sensor. The repetitions are ignored
+    /// like by a rejecting guard. Measured with the timers' clock, the events aren't filtered without one.
+    pub fn throttle(&mut self, _window: Duration) -> &mut Self {
+        self
+    }
+
+    /// Take the transition at most `max` times in a row, then treat it like a rejecting guard, so that the
+    /// following transition on the event is taken as the fallback. The region counts the retries, readable
+    /// with `ctx.attempts()`, until it enters a state that's neither the source nor the target of one of its
+    /// retries, like the fallback's target. The retry is counted before the transition's actions, a failed
+    /// action still uses it up, unless the machine is `transactional` and rolls the count back. Without the
+    /// `std` feature, only the first `FSM_TIMER_STATUS_CAPACITY` regions can retry, the others fail the dispatch
+    /// with `FsmTimerError::RetriesOverCapacity`.
+    ///
+    /// Example : `.on_event::<Failed>().transition_to::<Backoff>().retry(3).transition_to::<Offline>()`
+    pub fn retry(&mut self, _max: u32) -> &mut Self {
+        self
+    }
+
     /// A type for this transition. The struct for the transition will be generated.
     pub fn with_transition_ty<TTransition>(&mut self) -> &mut Self {
         self
-    }    
+    }
+
+    /// Enter the target sub-machine at this state instead of its initial states, like an UML entry point.
+    /// The sub-machine has to declare the state as its `entry_point`, its other regions are entered at their
+    /// initial states.
+    pub fn entry_point<TSubState>(&mut self) -> &mut Self
+        where TStateTo: FsmBackend, TSubState: FsmEntryPoint<TStateTo>
+    {
+        self
+    }
+
+    /// A fork, also enter this state of another region, after exiting that region's current state. The forked
+    /// region doesn't handle this event again. Can be declared once for each of the other regions.
+    pub fn fork<TStateOther>(&mut self) -> &mut Self
+        where TStateOther: FsmState<TFsm>
+    {
+        self
+    }
+
+    /// A join, the transition only fires while the other region is in this state. Combine it with `fork`
+    /// to also move the joined region into its next state.
+    pub fn join<TStateOther>(&mut self) -> &mut Self
+        where TStateOther: FsmState<TFsm>
+    {
+        self
+    }
+
+    /// Another guarded transition for the same event, evaluated if the guard of this one rejects it.
+    /// The transitions are evaluated in the order of declaration.
+    pub fn transition_to<TStateOther>(&self) -> FsmEventBuilderTransitionFull<'a, TFsm, TContext, TEvent, TStateFrom, TStateOther> {
+        FsmEventBuilderTransitionFull {
+            _transition_from: self._transition_from,
+            _state_to: PhantomData::default()
+        }
+    }
+
+    /// The fallback transition for the same event, taken when all of the previous guards rejected it.
+    /// Has to be the last one in the chain and can't have a guard.
+    pub fn otherwise<TStateOther>(&self) -> FsmEventBuilderTransitionFull<'a, TFsm, TContext, TEvent, TStateFrom, TStateOther> {
+        FsmEventBuilderTransitionFull {
+            _transition_from: self._transition_from,
+            _state_to: PhantomData::default()
+        }
+    }
 }
\ No newline at end of file
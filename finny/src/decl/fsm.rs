@@ -1,7 +1,7 @@
 use crate::{lib::*};
 
-use crate::FsmBackend;
-use super::{FsmStateBuilder, FsmSubMachineBuilder};
+use crate::{EventContext, FsmBackend, FsmUnhandledEventPolicy};
+use super::{FsmAnyStateBuilder, FsmQueueMock, FsmStateBuilder, FsmStateGroupBuilder, FsmSubMachineBuilder};
 
 /// The main builder-API for defining your Finny state machine.
 #[derive(Default)]
@@ -13,6 +13,19 @@ pub struct FsmBuilder<TFsm, TContext> {
 /// The consumed struct of the FSM, ensures that all of the builder's references are released.
 pub struct BuiltFsm;
 
+/// Declares the fault state of the machine.
+pub struct FsmErrorBuilder<TFsm, TContext> {
+	_fsm: PhantomData<TFsm>,
+	_context: PhantomData<TContext>
+}
+
+impl<TFsm, TContext> FsmErrorBuilder<TFsm, TContext> {
+	/// The state that is entered when a fallible guard or action fails.
+	pub fn transition_to<TState>(&self) {
+
+	}
+}
+
 impl<TFsm, TContext> FsmBuilder<TFsm, TContext>
 	where TFsm: FsmBackend<Context = TContext>
 {
@@ -34,6 +47,161 @@ impl<TFsm, TContext> FsmBuilder<TFsm, TContext>
 		
 	}
 
+	/// Require the `Debug` trait on the States, and implement it for the generated states struct.
+	pub fn states_debug(&mut self) {
+
+	}
+
+	/// Derive `Serialize` and `Deserialize` for the generated events enum, so the events can be received
+	/// over the wire and dispatched directly. Requires the `serde` feature, and all of the events, including
+	/// the events of the submachines, have to be serializable. Implied by `derive_serde`.
+	pub fn events_serde(&mut self) {
+
+	}
+
+	/// Implement `arbitrary::Arbitrary` for the generated events enum, so the sequences of the events can be
+	/// generated by the fuzzers, see `FsmFuzz::arbitrary_events`. Requires the `fuzz_arbitrary` feature, and all
+	/// of the events, including the events of the submachines, have to implement `Arbitrary`.
+	pub fn events_arbitrary(&mut self) {
+
+	}
+
+	/// Don't require the `Clone` trait on the Events. The events are moved through the queue and only borrowed
+	/// by the guards and the actions, so large events are never copied. Not supported with submachines or deferred events.
+	pub fn events_without_clone(&mut self) {
+		
+	}
+
+	/// Declares the type of the external resources that are lent to the guards and actions by
+	/// `FsmFrontend::dispatch_with`, as `EventContext::resources`. Useful for the peripherals or the
+	/// connections that can't be owned by the context. The resources aren't lent to the submachines, so
+	/// the machines with resources can't have any.
+	///
+	/// Example : `fsm.resources::<Peripherals>()`
+	pub fn resources<TResources>(&mut self) {
+
+	}
+
+	/// Declares the type of the commands that the actions push into `EventContext::outputs`, like the frames
+	/// to send or the LEDs to set. The caller drains them after the dispatch with `FsmFrontend::drain_outputs`
+	/// and performs the I/O, so the actions stay free of it. Usually an enum of the commands.
+	///
+	/// Example : `fsm.outputs::<Command>()`
+	pub fn outputs<TOutputs>(&mut self) {
+
+	}
+
+	/// Fail the dispatch with `FsmError::NoTransition` whenever an event isn't handled in the current
+	/// state of a region, even if the other regions handled it. Without it, an event has to be handled by
+	/// one of the regions and the unhandled ones are treated by the unhandled event policy.
+	pub fn strict_events(&mut self) {
+
+	}
+
+	/// Roll back the step of an event whose guard or action failed: the context, the states, the current states,
+	/// the events enqueued by the step, the released deferred events and the bookkeeping of the timer requests
+	/// (the times in the states, the rate limits and the retries) are restored to what they were before it. With
+	/// `on_error`, the fault state is then entered from the restored states. The snapshot is taken once the event
+	/// matches a transition, so the unhandled events don't clone anything, and the other errors, like an unhandled
+	/// event in the strict mode, aren't rolled back. Requires the context and the states to implement `Clone`.
+	/// The running timers and the outputs aren't rolled back, and the machine can't have submachines. Its queue
+	/// has to be able to drop the step's events, the `spsc` queue fails to compile with it.
+	pub fn transactional(&mut self) {
+
+	}
+
+	/// Generate an `extern "C"` API for the machine, for embedding it in a C codebase: `{fsm}_create`,
+	/// `{fsm}_destroy`, `{fsm}_dispatch` with the event's stable id and a byte payload, and
+	/// `{fsm}_current_state` with the stable id of a region's state. The functions return an `FsmFfiStatus`.
+	/// Requires the `alloc` feature, a `Default` context, and all of the events have to implement
+	/// `FsmFfiEvent`. The machine runs without timers, see `FsmFfiFrontend`.
+	pub fn ffi(&mut self) {
+
+	}
+
+	/// Derive `Serialize` and `Deserialize` for the generated states, events and current state types,
+	/// so the machine can be snapshotted and restored. Requires the `serde` feature, and all of
+	/// the states, events, submachines and the context have to be serializable.
+	pub fn derive_serde(&mut self) {
+		
+	}
+
+	/// Declares an event that none of the states handle, like the events that a sub-machine enqueues
+	/// for its parent machine, see `lift_event`.
+	pub fn event<TEvent>(&mut self) {
+
+	}
+
+	/// Sets the priority of an event, used by the `FsmEventQueuePriority` queue to dispatch the events with
+	/// a higher priority first. The events without a declared priority have the priority of 0.
+	pub fn event_priority<TEvent>(&mut self, _priority: u8) {
+
+	}
+
+	/// Sets the stable id of an event, its discriminant in the generated `EventKind` enum. The events without a
+	/// declared id have the hash of their name. The id has to be an integer literal.
+	///
+	/// Example : `fsm.event_id::<Heartbeat>(3)`
+	pub fn event_id<TEvent>(&mut self, _id: u32) {
+
+	}
+
+	/// Sets the name of an event, used by the inspectors, the diagrams and the generated `event_name`
+	/// instead of the name of its type. The name has to be a string literal.
+	///
+	/// Example : `fsm.event_name::<ButtonPressedEvent>("Press")`
+	pub fn event_name<TEvent>(&mut self, _name: &'static str) {
+
+	}
+
+	/// Sets the stable id of a state, its discriminant in the generated `CurrentState` enum, see `FsmId`. The
+	/// states without a declared id have the hash of their name. The id has to be an integer literal.
+	///
+	/// Example : `fsm.state_id::<Connected>(7)`
+	pub fn state_id<TState>(&mut self, _id: u32) {
+
+	}
+
+	/// Sets the priority of a region, referred to by its initial state. The regions with a higher priority see
+	/// every dispatched event first, including the start of the machine. The regions without a declared priority
+	/// have the priority of 0, and the regions with the same priority see the events in the order of `initial_states`.
+	/// The order is fixed by the generated code, so the priority has to be an integer literal.
+	///
+	/// Example : `fsm.region_priority::<Sensors>(10)`
+	pub fn region_priority<TInitialState>(&mut self, _priority: u8) {
+
+	}
+
+	/// Declares the trace context carried by the event, like the `tracing::Span` of the request that
+	/// caused it. The dispatches of the event are linked to this context by the inspectors, see
+	/// `FsmEventTraceContext`.
+	pub fn event_trace_context<TEvent>(&mut self, _trace_context: impl Fn(&TEvent) -> &dyn Any) {
+
+	}
+
+	/// Execute this closure when an event isn't handled by any of the regions of the machine.
+	pub fn on_unhandled_event<'a, THandler: Fn(&<TFsm as FsmBackend>::Events, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>)>(&mut self, _handler: THandler) {
+
+	}
+
+	/// Sets what happens with the events that aren't handled by any of the regions. By default, the
+	/// dispatch fails with `FsmError::NoTransition`.
+	pub fn unhandled_event_policy(&mut self, _policy: FsmUnhandledEventPolicy) {
+
+	}
+
+	/// Enter a dedicated fault state whenever a fallible guard or action fails, instead of failing the
+	/// dispatch. The active state of the fault state's region is exited, unless the failed transition
+	/// already exited it. The error is stored in the machine, see `FsmBackendImpl::take_error`.
+	///
+	/// Example : `fsm.on_error().transition_to::<Fault>()`
+	pub fn on_error(&mut self) -> FsmErrorBuilder<TFsm, TContext> {
+		FsmErrorBuilder {
+			_fsm: PhantomData::default(),
+			_context: PhantomData::default()
+		}
+	}
+
 	/// Adds some information about a state.
 	pub fn state<TState>(&mut self) -> FsmStateBuilder<TFsm, TContext, TState> {
 		FsmStateBuilder {
@@ -43,6 +211,36 @@ impl<TFsm, TContext> FsmBuilder<TFsm, TContext>
 		}
 	}
 
+	/// Adds the transitions on an event from every declared state, except the excluded ones. The state's own
+	/// transitions on the event are evaluated first, and a state isn't given the transition if it is its target,
+	/// if it defers the event or if one of its own transitions on the event has no guard. Meant for machines
+	/// with a single region, the states of any other regions have to be excluded.
+	///
+	/// Example : `fsm.any_state().except::<Off>().on_event::<Shutdown>().transition_to::<Off>()`
+	pub fn any_state(&mut self) -> FsmAnyStateBuilder<TFsm, TContext> {
+		FsmAnyStateBuilder {
+			_state_builder: FsmStateBuilder {
+				_context: PhantomData::default(),
+				_fsm: PhantomData::default(),
+				_state: PhantomData::default()
+			}
+		}
+	}
+
+	/// Adds the transitions on an event from each state of the group, the type has to be a tuple of the states.
+	/// The transitions are validated as if they were declared on each of the states.
+	///
+	/// Example : `fsm.state_group::<(Connecting, Connected)>().on_event::<LinkDown>().transition_to::<Offline>()`
+	pub fn state_group<TStates>(&mut self) -> FsmStateGroupBuilder<TFsm, TContext> {
+		FsmStateGroupBuilder {
+			_state_builder: FsmStateBuilder {
+				_context: PhantomData::default(),
+				_fsm: PhantomData::default(),
+				_state: PhantomData::default()
+			}
+		}
+	}
+
 	/// Adds a sub machine
 	pub fn sub_machine<TSubFsm>(&mut self) -> FsmSubMachineBuilder<TFsm, TContext, TSubFsm>
 		where TSubFsm: FsmBackend
@@ -1,6 +1,6 @@
 use crate::{TimerFsmSettings, lib::*};
 
-use crate::{EventContext, FsmBackend};
+use crate::{EventContext, FsmBackend, FsmEvent};
 use super::{FsmQueueMock, event::FsmEventBuilderState};
 
 pub struct FsmStateBuilder<TFsm, TContext, TState> {
@@ -9,6 +9,44 @@ pub struct FsmStateBuilder<TFsm, TContext, TState> {
 	pub (crate) _context: PhantomData<TContext>
 }
 
+/// The placeholder for the source state of the transitions declared using `FsmBuilder::any_state` or
+/// `FsmBuilder::state_group`. The transitions are generated for each of the states, so the actions can't
+/// access the source state.
+pub struct FsmAnyState;
+
+/// Declares the transitions from all of the states of the machine.
+pub struct FsmAnyStateBuilder<TFsm, TContext> {
+	pub (crate) _state_builder: FsmStateBuilder<TFsm, TContext, FsmAnyState>
+}
+
+impl<TFsm, TContext> FsmAnyStateBuilder<TFsm, TContext>
+	where TFsm: FsmBackend
+{
+	/// Don't generate the transitions from this state. Multiple states can be excluded using a tuple.
+	pub fn except<TState>(&self) -> &Self {
+		self
+	}
+
+	/// What happens if we receive this event, regardless of the current state?
+	pub fn on_event<TEvent>(&self) -> FsmEventBuilderState<TFsm, TContext, TEvent, FsmAnyState> {
+		self._state_builder.on_event()
+	}
+}
+
+/// Declares the transitions from each state of a group.
+pub struct FsmStateGroupBuilder<TFsm, TContext> {
+	pub (crate) _state_builder: FsmStateBuilder<TFsm, TContext, FsmAnyState>
+}
+
+impl<TFsm, TContext> FsmStateGroupBuilder<TFsm, TContext>
+	where TFsm: FsmBackend
+{
+	/// What happens if we receive this event while in any of the states of the group?
+	pub fn on_event<TEvent>(&self) -> FsmEventBuilderState<TFsm, TContext, TEvent, FsmAnyState> {
+		self._state_builder.on_event()
+	}
+}
+
 impl<TFsm, TContext, TState> FsmStateBuilder<TFsm, TContext, TState>
 	where TFsm: FsmBackend
 {
@@ -17,12 +55,95 @@ impl<TFsm, TContext, TState> FsmStateBuilder<TFsm, TContext, TState>
 		self
 	}
 
+	/// Exposes this state as an entry point, so a parent machine can enter this machine at it instead of
+	/// its initial state. See `entry_point` on the parent's transition.
+	pub fn entry_point(&self) -> &Self {
+		self
+	}
+
+	/// Sets the name of the state, used by the inspectors, the diagrams and the generated `state_name`
+	/// instead of the name of its type. The name has to be a string literal.
+	///
+	/// Example : `fsm.state::<WaitingForCredentials>().named("Login")`
+	pub fn named(&self, _name: &'static str) -> &Self {
+		self
+	}
+
+	/// Marks this state as a final state of its region. The machine is completed once all of its regions
+	/// are in their final states, see `FsmBackendImpl::is_completed`.
+	pub fn final_state(&self) -> &Self {
+		self
+	}
+
 	/// Execute this action when exiting the state.
 	pub fn on_exit<'a, TAction: Fn(&mut TState, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>)>(&self, _action: TAction) -> &Self {
 		self
 	}
 
+	/// Execute this action when entering the state, after `on_entry`. The action receives the event that
+	/// triggered the transition, or `None` when the state is re-entered by resuming a submachine's history.
+	pub fn on_entry_with_event<'a, TAction>(&self, _action: TAction) -> &Self
+		where TAction: Fn(&mut TState, Option<&FsmEvent<<TFsm as FsmBackend>::Events, <TFsm as FsmBackend>::Timers>>, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>)
+	{
+		self
+	}
+
+	/// Execute this action when exiting the state, after `on_exit`. The action receives the event that
+	/// triggered the transition.
+	pub fn on_exit_with_event<'a, TAction>(&self, _action: TAction) -> &Self
+		where TAction: Fn(&mut TState, Option<&FsmEvent<<TFsm as FsmBackend>::Events, <TFsm as FsmBackend>::Timers>>, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>)
+	{
+		self
+	}
+
+	/// Execute this asynchronous action when entering the state, awaited after the synchronous `on_entry`
+	/// action. The machine can then only be started and dispatched with `start_async` and `dispatch_async`,
+	/// the sync path fails with `FsmError::AsyncRequired`.
+	pub fn on_entry_async<'a, TAction, TFut>(&self, _action: TAction) -> &Self
+		where
+			TAction: Fn(&mut TState, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>) -> TFut,
+			TFut: Future<Output = ()>
+	{
+		self
+	}
+
+	/// Execute this asynchronous action when exiting the state, awaited after the synchronous `on_exit`
+	/// action. The machine can then only be started and dispatched with `start_async` and `dispatch_async`,
+	/// the sync path fails with `FsmError::AsyncRequired`.
+	pub fn on_exit_async<'a, TAction, TFut>(&self, _action: TAction) -> &Self
+		where
+			TAction: Fn(&mut TState, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>) -> TFut,
+			TFut: Future<Output = ()>
+	{
+		self
+	}
+
+	/// Re-entering this submachine state resumes its last active states instead of the initial ones.
+	/// Only supported on submachine states.
+	pub fn history_shallow(&self) -> &Self {
+		self
+	}
+
+	/// Re-entering this submachine state restores its entire last active configuration, including
+	/// the states of the nested submachines. Only supported on submachine states.
+	pub fn history_deep(&self) -> &Self {
+		self
+	}
+
+	/// Defer this event while the machine is in this state. The event is stored and dispatched again,
+	/// in the order of arrival and before the queued events, once this state is exited. The first error of
+	/// the released events is returned by the dispatch that exited the state.
+	pub fn defer<TEvent>(&self) -> &Self {
+		self
+	}
+
 	/// What happens if we receive this event and we are in this state right now?
+	///
+	/// An event that borrows its data is declared with its lifetime, like `on_event::<Packet<'_>>()`. It's
+	/// dispatched with `dispatch_borrowed` and can't be queued, deferred or forwarded to the submachines.
+	///
+	/// A generic event is declared with its type arguments, like `on_event::<Received<u32>>()`. Each of the
+	/// instantiations is a separate event, its variant is named after the arguments, like `ReceivedU32`.
 	pub fn on_event<TEvent>(&self) -> FsmEventBuilderState<TFsm, TContext, TEvent, TState> {
 		FsmEventBuilderState {
 			_state_builder: self,
@@ -42,6 +163,42 @@ impl<TFsm, TContext, TState> FsmStateBuilder<TFsm, TContext, TState>
 			_state: self
 		}
 	}
+
+	/// Start a timer when entering this state, with the timeout computed from the context and
+	/// the state at the time of the entry. The trigger closure returns the event to be enqueued
+	/// once the timer expires.
+	pub fn on_timer<FTimeout, FTrigger>(&self, _timeout: FTimeout, _trigger: FTrigger) -> FsmStateTimerBuilder<TFsm, TContext, TState>
+		where 
+			FTimeout: Fn(&TContext, &TState) -> Duration,
+			FTrigger: Fn(&TContext, &TState) -> Option< <TFsm as FsmBackend>::Events >
+	{
+		FsmStateTimerBuilder {
+			_state: self
+		}
+	}
+
+	/// Start a timer when entering this state, like `on_timer`, that enqueues the event returned by the closure
+	/// once it expires. The event is declared for the machine, so it's handled by the transitions of any of its
+	/// states like the events dispatched from the outside.
+	///
+	/// Example : `.on_timer_event::<Timeout>(|ctx, _state| ctx.timeout, |_ctx, _state| Timeout)`
+	pub fn on_timer_event<TEvent>(&self, _timeout: impl Fn(&TContext, &TState) -> Duration, _event: impl Fn(&TContext, &TState) -> TEvent) -> FsmStateTimerBuilder<TFsm, TContext, TState> {
+		FsmStateTimerBuilder {
+			_state: self
+		}
+	}
+
+	/// Start a periodic timer when entering this state. The trigger closure is called on every tick of
+	/// the interval and the event it returns is enqueued in the FSM. The timer is cancelled when the
+	/// state is exited.
+	pub fn on_timer_interval<FTrigger>(&self, _interval: Duration, _trigger: FTrigger) -> FsmStateTimerBuilder<TFsm, TContext, TState>
+		where 
+			FTrigger: Fn(&TContext, &TState) -> Option< <TFsm as FsmBackend>::Events >
+	{
+		FsmStateTimerBuilder {
+			_state: self
+		}
+	}
 }
 
 pub struct FsmStateTimerBuilder<'a, TFsm, TContext, TState> {
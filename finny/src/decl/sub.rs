@@ -1,4 +1,4 @@
-use crate::{EventContext, FsmBackend, lib::*};
+use crate::{EventContext, FsmBackend, FsmState, lib::*};
 
 use super::{FsmEventBuilderState, FsmQueueMock, FsmStateBuilder};
 
@@ -19,6 +19,47 @@ impl<TFsm, TContext, TSubMachine> FsmSubMachineBuilder<TFsm, TContext, TSubMachi
 		self
 	}
 
+	/// Enqueue the event created by this closure in the parent machine when the sub-machine reaches
+	/// its final states, so the parent can transition further.
+	pub fn on_completion<TEvent, TCompletion>(&self, _completion: TCompletion) -> &Self
+		where
+			TCompletion: Fn(&<TSubMachine as FsmBackend>::Context) -> TEvent,
+			TEvent: Into<<TFsm as FsmBackend>::Events>
+	{
+		self
+	}
+
+	/// Enqueue the event created by this closure in the parent machine when the sub-machine enters this
+	/// exit point state, so a sub-machine can have several exits that lead to different parent transitions.
+	///
+	/// Example: `on_exit_point::<Rejected, _, _>(|ctx| CallRejected)`
+	pub fn on_exit_point<TExitState, TEvent, TExit>(&self, _exit: TExit) -> &Self
+		where
+			TExitState: FsmState<TSubMachine>,
+			TExit: Fn(&<TSubMachine as FsmBackend>::Context) -> TEvent,
+			TEvent: Into<<TFsm as FsmBackend>::Events>
+	{
+		self
+	}
+
+	/// Enqueue the event created by this closure in the parent machine when the sub-machine enters this
+	/// state. Like `on_exit_point`, the closure also receives the entered state of the sub-machine.
+	///
+	/// Example: `on_child_state::<Done, _, _>(|ctx, done| Finished { code: done.code })`
+	pub fn on_child_state<TChildState, TEvent, TNotify>(&self, _notify: TNotify) -> &Self
+		where
+			TChildState: FsmState<TSubMachine>,
+			TNotify: Fn(&<TSubMachine as FsmBackend>::Context, &TChildState) -> TEvent,
+			TEvent: Into<<TFsm as FsmBackend>::Events>
+	{
+		self
+	}
+
+
+	pub fn final_state(&self) -> &Self {
+		self
+	}
+
 	/// Execute this action when entering the sub-machine state.
 	pub fn on_entry<'a, TAction: Fn(&mut TSubMachine, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>)>(&self, _action: TAction) -> &Self {
 		self
@@ -29,6 +70,44 @@ impl<TFsm, TContext, TSubMachine> FsmSubMachineBuilder<TFsm, TContext, TSubMachi
 		self
 	}	
 
+	/// Execute this asynchronous action when entering the sub-machine state. The machine can then only be
+	/// started and dispatched with `start_async` and `dispatch_async`, the sync path fails with `FsmError::AsyncRequired`.
+	pub fn on_entry_async<'a, TAction, TFut>(&self, _action: TAction) -> &Self
+		where
+			TAction: Fn(&mut TSubMachine, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>) -> TFut,
+			TFut: Future<Output = ()>
+	{
+		self
+	}
+
+	/// Execute this asynchronous action when exiting the sub-machine state. The machine can then only be
+	/// started and dispatched with `start_async` and `dispatch_async`, the sync path fails with `FsmError::AsyncRequired`.
+	pub fn on_exit_async<'a, TAction, TFut>(&self, _action: TAction) -> &Self
+		where
+			TAction: Fn(&mut TSubMachine, &mut EventContext<'a, TFsm, FsmQueueMock<TFsm>>) -> TFut,
+			TFut: Future<Output = ()>
+	{
+		self
+	}
+
+	/// Re-entering the sub-machine state resumes its last active states instead of the initial ones.
+	/// Nested sub-machines of the resumed states are started from their initial states.
+	pub fn history_shallow(&self) -> &Self {
+		self
+	}
+
+	/// Re-entering the sub-machine state restores its entire last active configuration, including
+	/// the states of the nested sub-machines.
+	pub fn history_deep(&self) -> &Self {
+		self
+	}
+
+	/// Defer this event while the machine is in this sub-machine state. The event is dispatched
+	/// again once the sub-machine state is exited.
+	pub fn defer<TEvent>(&self) -> &Self {
+		self
+	}
+
 	/// What happens if we receive this event and we are in this submachine's state right now?
 	pub fn on_event<TEvent>(&self) -> FsmEventBuilderState<TFsm, TContext, TEvent, TSubMachine> {
 		FsmEventBuilderState {
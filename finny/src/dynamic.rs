@@ -0,0 +1,397 @@
+//! Machines that are built at runtime from data, like the workflow definitions loaded from a database. The states
+//! and the events are named by strings and the guards and actions are boxed closures, so the definition can change
+//! without recompiling, at the cost of the static dispatch and the compile-time validation of the `finny_fsm` macro.
+//! The dynamic machines have a single region, without submachines and timers.
+
+use std::collections::HashMap;
+
+use crate::lib::*;
+use crate::FsmError;
+
+pub type FsmDynResult<T> = Result<T, FsmDynError>;
+
+/// The events of a dynamic machine, matched to the transitions by their names.
+pub trait FsmDynEvent {
+    fn event_name(&self) -> &str;
+}
+
+impl FsmDynEvent for String {
+    fn event_name(&self) -> &str {
+        self
+    }
+}
+
+impl FsmDynEvent for &str {
+    fn event_name(&self) -> &str {
+        self
+    }
+}
+
+/// The errors of the dynamic machines. The names of the states and events are owned, unlike in `FsmError`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsmDynError {
+    /// The definition refers to a state that wasn't declared.
+    StateNotFound(String),
+    /// The definition doesn't have an initial state.
+    NoInitialState,
+    /// The event isn't handled in the current state. The state is `Stopped` before the machine is started.
+    NoTransition { state: String, event: String },
+    /// A fallible action failed.
+    Fsm(FsmError)
+}
+
+impl From<FsmError> for FsmDynError {
+    fn from(err: FsmError) -> Self {
+        FsmDynError::Fsm(err)
+    }
+}
+
+impl fmt::Display for FsmDynError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmDynError::StateNotFound(state) => write!(f, "The state '{}' isn't declared", state),
+            FsmDynError::NoInitialState => f.write_str("The machine doesn't have an initial state"),
+            FsmDynError::NoTransition { state, event } => write!(f, "The event '{}' isn't handled in the state '{}'", event, state),
+            FsmDynError::Fsm(err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl std::error::Error for FsmDynError {}
+
+/// The context of the dynamic machine's actions. Derefs into the machine's context, the events enqueued
+/// here are dispatched after the current one.
+pub struct FsmDynEventContext<'a, C, E> {
+    pub context: &'a mut C,
+    pub queue: &'a mut VecDeque<E>
+}
+
+impl<'a, C, E> Deref for FsmDynEventContext<'a, C, E> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        self.context
+    }
+}
+
+impl<'a, C, E> DerefMut for FsmDynEventContext<'a, C, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.context
+    }
+}
+
+type FsmDynStateAction<C, E> = Box<dyn Fn(&mut FsmDynEventContext<C, E>)>;
+type FsmDynGuard<C, E> = Box<dyn Fn(&E, &C) -> bool>;
+type FsmDynAction<C, E> = Box<dyn Fn(&E, &mut FsmDynEventContext<C, E>) -> Result<(), FsmError>>;
+
+struct FsmDynState<C, E> {
+    name: String,
+    on_entry: Vec<FsmDynStateAction<C, E>>,
+    on_exit: Vec<FsmDynStateAction<C, E>>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FsmDynTransitionKind {
+    Normal,
+    SelfTransition,
+    Internal
+}
+
+struct FsmDynTransition<C, E> {
+    from: String,
+    to: String,
+    event: String,
+    kind: FsmDynTransitionKind,
+    guard: Option<FsmDynGuard<C, E>>,
+    action: Option<FsmDynAction<C, E>>
+}
+
+/// Builds a dynamic machine, with the same structure as the `finny_fsm` builder.
+///
+/// ```rust
+/// use finny::dynamic::FsmDynBuilder;
+///
+/// let mut fsm = FsmDynBuilder::<usize, String>::new();
+/// fsm.initial_state("Idle");
+/// fsm.state("Idle")
+///     .on_event("Go")
+///     .transition_to("Running")
+///     .action(|_ev, ctx| { **ctx += 1; });
+/// fsm.state("Running");
+///
+/// let mut fsm = fsm.build(0).unwrap();
+/// fsm.start().unwrap();
+/// fsm.dispatch("Go".to_string()).unwrap();
+/// assert_eq!(Some("Running"), fsm.current_state());
+/// assert_eq!(1, *fsm);
+/// ```
+pub struct FsmDynBuilder<C, E> {
+    initial_state: Option<String>,
+    states: Vec<FsmDynState<C, E>>,
+    transitions: Vec<FsmDynTransition<C, E>>
+}
+
+impl<C, E: FsmDynEvent> FsmDynBuilder<C, E> {
+    pub fn new() -> Self {
+        FsmDynBuilder {
+            initial_state: None,
+            states: Vec::new(),
+            transitions: Vec::new()
+        }
+    }
+
+    pub fn initial_state(&mut self, state: &str) {
+        self.initial_state = Some(state.to_string());
+    }
+
+    /// Declare a state, or add to the declaration of an existing one.
+    pub fn state(&mut self, state: &str) -> FsmDynStateBuilder<'_, C, E> {
+        let index = match self.states.iter().position(|s| s.name == state) {
+            Some(index) => index,
+            None => {
+                self.states.push(FsmDynState { name: state.to_string(), on_entry: Vec::new(), on_exit: Vec::new() });
+                self.states.len() - 1
+            }
+        };
+        FsmDynStateBuilder { builder: self, state: index }
+    }
+
+    /// Validate the definition and build the machine, which then has to be started.
+    pub fn build(self, context: C) -> FsmDynResult<FsmDyn<C, E>> {
+        let FsmDynBuilder { initial_state, states, transitions: declared } = self;
+        let find_state = |name: &str| states.iter().position(|s| s.name == name).ok_or_else(|| FsmDynError::StateNotFound(name.to_string()));
+
+        let initial_state = find_state(initial_state.as_deref().ok_or(FsmDynError::NoInitialState)?)?;
+
+        let mut transitions: HashMap<(usize, String), Vec<FsmDynTransitionEntry<C, E>>> = HashMap::new();
+        for transition in declared {
+            let from = find_state(&transition.from)?;
+            let to = find_state(&transition.to)?;
+            transitions.entry((from, transition.event)).or_default().push(FsmDynTransitionEntry {
+                to,
+                kind: transition.kind,
+                guard: transition.guard,
+                action: transition.action
+            });
+        }
+
+        Ok(FsmDyn {
+            context,
+            states,
+            transitions,
+            initial_state,
+            current_state: None,
+            queue: VecDeque::new()
+        })
+    }
+}
+
+impl<C, E: FsmDynEvent> Default for FsmDynBuilder<C, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct FsmDynStateBuilder<'a, C, E> {
+    builder: &'a mut FsmDynBuilder<C, E>,
+    state: usize
+}
+
+impl<'a, C, E> FsmDynStateBuilder<'a, C, E> {
+    /// Executed when the state is entered.
+    pub fn on_entry<A: Fn(&mut FsmDynEventContext<C, E>) + 'static>(self, action: A) -> Self {
+        self.builder.states[self.state].on_entry.push(Box::new(action));
+        self
+    }
+
+    /// Executed when the state is exited.
+    pub fn on_exit<A: Fn(&mut FsmDynEventContext<C, E>) + 'static>(self, action: A) -> Self {
+        self.builder.states[self.state].on_exit.push(Box::new(action));
+        self
+    }
+
+    /// The transitions from this state, triggered by the events with this name.
+    pub fn on_event(self, event: &str) -> FsmDynEventBuilder<'a, C, E> {
+        FsmDynEventBuilder { builder: self.builder, state: self.state, event: event.to_string() }
+    }
+}
+
+pub struct FsmDynEventBuilder<'a, C, E> {
+    builder: &'a mut FsmDynBuilder<C, E>,
+    state: usize,
+    event: String
+}
+
+impl<'a, C, E> FsmDynEventBuilder<'a, C, E> {
+    /// Transition into this state. The exit actions of the current state are executed, then the transition's
+    /// action and the entry actions of the new state.
+    pub fn transition_to(self, state: &str) -> FsmDynTransitionBuilder<'a, C, E> {
+        let to = state.to_string();
+        self.add(to, FsmDynTransitionKind::Normal)
+    }
+
+    /// A transition that exits and re-enters the current state.
+    pub fn self_transition(self) -> FsmDynTransitionBuilder<'a, C, E> {
+        let to = self.builder.states[self.state].name.clone();
+        self.add(to, FsmDynTransitionKind::SelfTransition)
+    }
+
+    /// A transition that only executes its action, the state isn't exited.
+    pub fn internal_transition(self) -> FsmDynTransitionBuilder<'a, C, E> {
+        let to = self.builder.states[self.state].name.clone();
+        self.add(to, FsmDynTransitionKind::Internal)
+    }
+
+    fn add(self, to: String, kind: FsmDynTransitionKind) -> FsmDynTransitionBuilder<'a, C, E> {
+        let from = self.builder.states[self.state].name.clone();
+        self.builder.transitions.push(FsmDynTransition { from, to, event: self.event, kind, guard: None, action: None });
+        let transition = self.builder.transitions.len() - 1;
+        FsmDynTransitionBuilder { builder: self.builder, transition }
+    }
+}
+
+pub struct FsmDynTransitionBuilder<'a, C, E> {
+    builder: &'a mut FsmDynBuilder<C, E>,
+    transition: usize
+}
+
+impl<'a, C, E> FsmDynTransitionBuilder<'a, C, E> {
+    /// The transition is only taken if the guard passes. The transitions for the same event are evaluated
+    /// in the order of their declaration.
+    pub fn guard<G: Fn(&E, &C) -> bool + 'static>(self, guard: G) -> Self {
+        self.builder.transitions[self.transition].guard = Some(Box::new(guard));
+        self
+    }
+
+    pub fn action<A: Fn(&E, &mut FsmDynEventContext<C, E>) + 'static>(self, action: A) -> Self {
+        self.try_action(move |ev, ctx| {
+            action(ev, ctx);
+            Ok(())
+        })
+    }
+
+    /// A fallible action. A failed action aborts the dispatch and the error is returned by it.
+    pub fn try_action<A: Fn(&E, &mut FsmDynEventContext<C, E>) -> Result<(), FsmError> + 'static>(self, action: A) -> Self {
+        self.builder.transitions[self.transition].action = Some(Box::new(action));
+        self
+    }
+}
+
+struct FsmDynTransitionEntry<C, E> {
+    to: usize,
+    kind: FsmDynTransitionKind,
+    guard: Option<FsmDynGuard<C, E>>,
+    action: Option<FsmDynAction<C, E>>
+}
+
+/// A machine built at runtime by the `FsmDynBuilder`. Derefs into its context.
+pub struct FsmDyn<C, E> {
+    pub context: C,
+    states: Vec<FsmDynState<C, E>>,
+    transitions: HashMap<(usize, String), Vec<FsmDynTransitionEntry<C, E>>>,
+    initial_state: usize,
+    current_state: Option<usize>,
+    queue: VecDeque<E>
+}
+
+impl<C, E: FsmDynEvent> FsmDyn<C, E> {
+    /// Start the machine, entering the initial state.
+    pub fn start(&mut self) -> FsmDynResult<()> {
+        if self.current_state.is_none() {
+            self.enter(self.initial_state);
+        }
+
+        self.dispatch_queue()
+    }
+
+    /// Dispatch this event and run the queued events to completion.
+    pub fn dispatch(&mut self, event: E) -> FsmDynResult<()> {
+        self.dispatch_single(event)?;
+        self.dispatch_queue()
+    }
+
+    /// The name of the current state, `None` if the machine wasn't started.
+    pub fn current_state(&self) -> Option<&str> {
+        self.current_state.map(|s| self.states[s].name.as_str())
+    }
+
+    /// The names of all the declared states.
+    pub fn states(&self) -> impl Iterator<Item = &str> {
+        self.states.iter().map(|s| s.name.as_str())
+    }
+
+    /// Enqueue an event, it is dispatched with the next event or with `dispatch_queue`.
+    pub fn enqueue(&mut self, event: E) {
+        self.queue.push_back(event);
+    }
+
+    /// Dispatch the queued events until the queue is empty.
+    pub fn dispatch_queue(&mut self) -> FsmDynResult<()> {
+        while let Some(event) = self.queue.pop_front() {
+            self.dispatch_single(event)?;
+        }
+
+        Ok(())
+    }
+
+    fn dispatch_single(&mut self, event: E) -> FsmDynResult<()> {
+        let no_transition = |state: &str| FsmDynError::NoTransition { state: state.to_string(), event: event.event_name().to_string() };
+
+        let current = match self.current_state {
+            Some(current) => current,
+            None => return Err(no_transition("Stopped"))
+        };
+
+        let transitions = match self.transitions.get(&(current, event.event_name().to_string())) {
+            Some(transitions) => transitions,
+            None => return Err(no_transition(&self.states[current].name))
+        };
+
+        let transition = match transitions.iter().position(|t| t.guard.as_ref().map(|g| g(&event, &self.context)).unwrap_or(true)) {
+            Some(transition) => transition,
+            None => return Err(no_transition(&self.states[current].name))
+        };
+
+        let (to, kind) = (transitions[transition].to, transitions[transition].kind);
+
+        if kind != FsmDynTransitionKind::Internal {
+            let mut ctx = FsmDynEventContext { context: &mut self.context, queue: &mut self.queue };
+            for on_exit in &self.states[current].on_exit {
+                on_exit(&mut ctx);
+            }
+        }
+
+        if let Some(ref action) = self.transitions[&(current, event.event_name().to_string())][transition].action {
+            let mut ctx = FsmDynEventContext { context: &mut self.context, queue: &mut self.queue };
+            action(&event, &mut ctx)?;
+        }
+
+        if kind != FsmDynTransitionKind::Internal {
+            self.enter(to);
+        }
+
+        Ok(())
+    }
+
+    fn enter(&mut self, state: usize) {
+        let mut ctx = FsmDynEventContext { context: &mut self.context, queue: &mut self.queue };
+        for on_entry in &self.states[state].on_entry {
+            on_entry(&mut ctx);
+        }
+        self.current_state = Some(state);
+    }
+}
+
+impl<C, E> Deref for FsmDyn<C, E> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.context
+    }
+}
+
+impl<C, E> DerefMut for FsmDyn<C, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.context
+    }
+}
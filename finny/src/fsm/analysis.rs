@@ -0,0 +1,150 @@
+//! Reachability and path queries over the machine's description, for the model-level assertions in the
+//! tests. The guards are assumed to pass, so a state is reachable if there is some sequence of events that
+//! could lead to it. The submachines are single states of their parent, the queries don't descend into them.
+
+use crate::{FsmInfo, FsmInfoEvent, FsmInfoTransitionKind};
+use std::collections::{HashMap, VecDeque};
+
+/// The pseudo state before the machine is started, the source of the regions' start transitions.
+pub const FSM_INFO_STOPPED: &str = "Stopped";
+
+/// A transition that changes the region's state, one step of a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FsmPathStep {
+    pub transition_id: &'static str,
+    pub event: FsmInfoEvent,
+    pub from_state: &'static str,
+    pub to_state: &'static str,
+    pub guarded: bool
+}
+
+/// A sequence of transitions through the states of a region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmPath {
+    pub steps: Vec<FsmPathStep>
+}
+
+impl FsmPath {
+    /// The events to dispatch to follow the path, without the start of the machine.
+    pub fn events(&self) -> Vec<&'static str> {
+        self.steps.iter().filter_map(|s| match s.event {
+            FsmInfoEvent::Event(ev) => Some(ev),
+            _ => None
+        }).collect()
+    }
+
+    /// The visited states, including the first one.
+    pub fn states(&self) -> Vec<&'static str> {
+        let mut states: Vec<_> = self.steps.first().map(|s| s.from_state).into_iter().collect();
+        states.extend(self.steps.iter().map(|s| s.to_state));
+        states
+    }
+
+    /// Does the path rely on any guard passing?
+    pub fn is_guarded(&self) -> bool {
+        self.steps.iter().any(|s| s.guarded)
+    }
+}
+
+/// Queries over the transition graph of the machine's description. The states are identified by their
+/// names, the state before the machine is started is `FSM_INFO_STOPPED`.
+///
+/// Example : `FsmAnalysis::new(MyFsm::fsm_info()).shortest_path_from_start("Failed")`
+#[derive(Debug, Clone, Copy)]
+pub struct FsmAnalysis {
+    info: &'static FsmInfo
+}
+
+impl FsmAnalysis {
+    pub fn new(info: &'static FsmInfo) -> Self {
+        FsmAnalysis { info }
+    }
+
+    /// The transitions that leave the state, in the order in which they are evaluated. The self and
+    /// internal transitions don't change the state and are skipped.
+    fn steps_from(&self, state_id: &str) -> impl Iterator<Item = FsmPathStep> + '_ {
+        let state_id = state_id.to_string();
+        self.info.transitions().filter_map(move |t| match t.kind {
+            FsmInfoTransitionKind::NormalTransition { from_state, to_state } if from_state == state_id => Some(FsmPathStep {
+                transition_id: t.transition_id,
+                event: t.event,
+                from_state,
+                to_state,
+                guarded: t.guard.is_some()
+            }),
+            _ => None
+        })
+    }
+
+    /// Breadth-first search from the state, remembering the step that first reached each state.
+    fn search(&self, from: &str) -> HashMap<&'static str, Option<FsmPathStep>> {
+        let mut reached: HashMap<&'static str, Option<FsmPathStep>> = HashMap::new();
+        let mut pending = VecDeque::new();
+
+        if let Some(from) = self.state_name(from) {
+            reached.insert(from, None);
+            pending.push_back(from);
+        }
+
+        while let Some(state) = pending.pop_front() {
+            for step in self.steps_from(state) {
+                if !reached.contains_key(step.to_state) {
+                    reached.insert(step.to_state, Some(step));
+                    pending.push_back(step.to_state);
+                }
+            }
+        }
+
+        reached
+    }
+
+    fn state_name(&self, state_id: &str) -> Option<&'static str> {
+        if state_id == FSM_INFO_STOPPED {
+            return Some(FSM_INFO_STOPPED);
+        }
+        self.info.get_state(state_id).map(|s| s.state_id)
+    }
+
+    /// The states that can be reached from the state with any sequence of events, sorted by their names.
+    /// Includes the state itself.
+    pub fn reachable_states(&self, from: &str) -> Vec<&'static str> {
+        let mut states: Vec<_> = self.search(from).into_keys().collect();
+        states.sort_unstable();
+        states
+    }
+
+    /// Can the state `to` be reached from the state `from`? A state is always reachable from itself.
+    pub fn is_reachable(&self, from: &str, to: &str) -> bool {
+        self.search(from).contains_key(to)
+    }
+
+    /// The states without any transitions that leave them, other than the final states.
+    pub fn dead_end_states(&self) -> Vec<&'static str> {
+        self.info.regions.iter()
+            .flat_map(|r| r.states.iter())
+            .filter(|s| !s.is_final && self.steps_from(s.state_id).next().is_none())
+            .map(|s| s.state_id)
+            .collect()
+    }
+
+    /// A shortest sequence of transitions from the state `from` to the state `to`. Among the shortest
+    /// paths, the transitions that are evaluated first are preferred. The path is empty if both are the same state.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<FsmPath> {
+        let reached = self.search(from);
+        let mut state = self.state_name(to)?;
+        let mut steps = vec![];
+
+        while let Some(step) = reached.get(state)? {
+            steps.push(*step);
+            state = step.from_state;
+        }
+
+        steps.reverse();
+        Some(FsmPath { steps })
+    }
+
+    /// A shortest sequence of transitions from the start of the machine to the state, including the start transition.
+    pub fn shortest_path_from_start(&self, to: &str) -> Option<FsmPath> {
+        self.shortest_path(FSM_INFO_STOPPED, to)
+    }
+}
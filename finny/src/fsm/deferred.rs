@@ -0,0 +1,178 @@
+use crate::lib::*;
+use crate::{FsmBackend, FsmResult};
+
+#[cfg(not(feature = "std"))]
+use arraydeque::ArrayDeque;
+
+/// The maximum number of deferred events that can be stored by a single machine without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub const FSM_DEFERRED_EVENTS_CAPACITY: usize = 16;
+
+/// The side queue for the events that were deferred by the current states. The events are released,
+/// in their original order, after the deferring state is exited.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "<F as FsmBackend>::Events: serde::Serialize",
+    deserialize = "<F as FsmBackend>::Events: serde::Deserialize<'de>"
+)))]
+pub struct FsmDeferredEvents<F: FsmBackend> {
+    #[cfg(feature = "std")]
+    events: VecDeque<<F as FsmBackend>::Events>,
+    #[cfg(not(feature = "std"))]
+    #[cfg_attr(feature = "serde", serde(with = "serde_events"))]
+    events: ArrayDeque<[<F as FsmBackend>::Events; FSM_DEFERRED_EVENTS_CAPACITY]>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    release: Option<FsmDeferredRelease>
+}
+
+/// The released deferred events before a step of a transactional machine, see `FsmDeferredEvents::checkpoint`.
+#[derive(Copy, Clone, Debug)]
+pub struct FsmDeferredCheckpoint {
+    release: Option<FsmDeferredRelease>
+}
+
+/// Progress of re-dispatching the released events.
+#[derive(Copy, Clone, Debug, Default)]
+struct FsmDeferredRelease {
+    /// Index of the next event to be re-dispatched.
+    next: usize,
+    /// Index of the event that is being re-dispatched right now.
+    retrying: Option<usize>
+}
+
+impl<F: FsmBackend> FsmDeferredEvents<F> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            events: VecDeque::new(),
+            #[cfg(not(feature = "std"))]
+            events: ArrayDeque::new(),
+            release: None
+        }
+    }
+
+    /// Park this event until the deferring state is exited. An event that is deferred again while
+    /// it is being re-dispatched keeps its place in the queue.
+    pub fn defer(&mut self, event: <F as FsmBackend>::Events) -> FsmResult<()> {
+        let retried_pos = self.release.as_mut().and_then(|release| {
+            let pos = release.retrying.take()?;
+            release.next = pos + 1;
+            Some(pos)
+        });
+
+        let index = retried_pos.unwrap_or(self.events.len());
+        self.insert(index, event)
+    }
+
+    /// Mark all the deferred events for re-dispatching, starting with the oldest one.
+    pub fn release(&mut self) {
+        if !self.events.is_empty() {
+            self.release = Some(FsmDeferredRelease::default());
+        }
+    }
+
+    /// Take the next released event to be re-dispatched.
+    pub fn next_released(&mut self) -> Option<<F as FsmBackend>::Events> {
+        let release = self.release.as_mut()?;
+
+        // the previous event was consumed by the machine
+        if let Some(pos) = release.retrying.take() {
+            release.next = pos;
+        }
+
+        if release.next < self.events.len() {
+            release.retrying = Some(release.next);
+            self.events.remove(release.next)
+        } else {
+            self.release = None;
+            None
+        }
+    }
+
+    /// Remember which of the deferred events are released, before a step of a transactional machine. The
+    /// step that defers its event doesn't execute any actions, so the deferred events themselves can't
+    /// change in a step that fails.
+    pub fn checkpoint(&self) -> FsmDeferredCheckpoint {
+        FsmDeferredCheckpoint { release: self.release }
+    }
+
+    /// Undo the release of the deferred events by the failed step of a transactional machine.
+    pub fn rollback(&mut self, checkpoint: FsmDeferredCheckpoint) {
+        self.release = checkpoint.release;
+    }
+
+    /// Drop all the deferred events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.release = None;
+    }
+
+    /// Number of the deferred events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    #[cfg(feature = "std")]
+    fn insert(&mut self, index: usize, event: <F as FsmBackend>::Events) -> FsmResult<()> {
+        self.events.insert(index, event);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn insert(&mut self, index: usize, event: <F as FsmBackend>::Events) -> FsmResult<()> {
+        self.events.insert(index, event).map_err(|_| crate::FsmError::QueueOverCapacity { capacity: FSM_DEFERRED_EVENTS_CAPACITY })
+    }
+}
+
+/// Serializes the fixed capacity deferred events as a sequence, in the same format as the `VecDeque` of the `std` builds.
+#[cfg(all(feature = "serde", not(feature = "std")))]
+mod serde_events {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::{Error, SeqAccess, Visitor}};
+
+    type Events<E> = ArrayDeque<[E; FSM_DEFERRED_EVENTS_CAPACITY]>;
+
+    pub fn serialize<E: Serialize, S: Serializer>(events: &Events<E>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(events.iter())
+    }
+
+    pub fn deserialize<'de, E: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Events<E>, D::Error> {
+        struct EventsVisitor<E>(PhantomData<E>);
+
+        impl<'de, E: Deserialize<'de>> Visitor<'de> for EventsVisitor<E> {
+            type Value = Events<E>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "at most {} deferred events", FSM_DEFERRED_EVENTS_CAPACITY)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut events: Events<E> = ArrayDeque::new();
+                while let Some(event) = seq.next_element()? {
+                    if events.push_back(event).is_err() {
+                        return Err(A::Error::invalid_length(FSM_DEFERRED_EVENTS_CAPACITY + 1, &self));
+                    }
+                }
+                Ok(events)
+            }
+        }
+
+        deserializer.deserialize_seq(EventsVisitor(PhantomData))
+    }
+}
+
+impl<F: FsmBackend> Default for FsmDeferredEvents<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend> Debug for FsmDeferredEvents<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsmDeferredEvents").field("len", &self.events.len()).finish()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{FsmTimers, FsmTimersSub, lib::*};
-use crate::{EventContext, FsmBackend, FsmBackendImpl, FsmEvent, FsmEventQueue, FsmEventQueueSub, FsmRegionId, FsmResult, Inspect};
+use crate::{EventContext, FsmBackend, FsmBackendAsync, FsmBackendBorrowed, FsmBackendHistory, FsmBackendImpl, FsmBackendPeek, FsmBackendStop, FsmInfoTransition, FsmDispatchResult, FsmEvent, FsmEventQueue, FsmEventQueueSub, FsmRegionId, FsmEntryPoint, FsmResult, Inspect};
 
 pub struct DispatchContext<'a, 'b, 'c, F, Q, I, T>
     where F: FsmBackend,
@@ -10,7 +10,9 @@ pub struct DispatchContext<'a, 'b, 'c, F, Q, I, T>
     pub queue: &'a mut Q,
     pub inspect: &'b mut I,
     pub backend: &'c mut FsmBackendImpl<F>,
-    pub timers: &'a mut T
+    pub timers: &'a mut T,
+    /// The external resources lent for this dispatch, see `FsmFrontend::dispatch_with`.
+    pub resources: Option<&'a mut <F as FsmBackend>::Resources>
 }
 
 impl<'a, 'b, 'c, F, Q, I, T> DispatchContext<'a, 'b, 'c, F, Q, I, T>
@@ -20,16 +22,220 @@ where F: FsmBackend,
     T: FsmTimers<F>
 {
 
+    /// The same context, with the inspection of a transition in place of the dispatch's. The states that
+    /// the transition exits and enters are reported to it.
+    pub fn with_inspect<'i>(&'i mut self, inspect: &'i mut I) -> DispatchContext<'i, 'i, 'i, F, Q, I, T> {
+        DispatchContext {
+            queue: &mut *self.queue,
+            inspect,
+            backend: &mut *self.backend,
+            timers: &mut *self.timers,
+            resources: self.resources.as_deref_mut()
+        }
+    }
+
     pub fn to_event_context(&'a mut self, region: FsmRegionId) -> EventContext<'a, F, Q>
     {
         EventContext {
             context: &mut self.backend.context,
+            timers: &mut self.backend.timer_requests,
+            outputs: &mut self.backend.outputs,
             queue: self.queue,
-            region
+            region,
+            resources: self.resources.as_deref_mut()
         }
     } 
 }
 
+/// Dispatch the event, then re-dispatch the deferred events that were released by exiting
+/// their deferring states, in the order in which they were originally received. Returns the error of
+/// the event or, if it was dispatched, the first error of the released events, like a failed action or an
+/// event that isn't handled by the new states. All of the released events are re-dispatched either way.
+pub fn dispatch_with_deferred<F, Q, I, T>(ctx: DispatchContext<F, Q, I, T>, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>) -> FsmDispatchResult
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    let DispatchContext { queue, inspect, backend, timers, mut resources } = ctx;
+
+    #[cfg(feature = "std")]
+    let event = match crate::FsmMiddlewares::before_dispatch(backend, event)? {
+        Some(event) => event,
+        None => return Ok(())
+    };
+
+    #[cfg(feature = "std")]
+    let observed = backend.observers.before_dispatch(event.as_ref(), backend.current_states);
+
+    let mut result = F::dispatch_event(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, event);
+
+    #[cfg(feature = "std")]
+    backend.observers.after_dispatch(observed, backend.current_states);
+
+    #[cfg(feature = "std")]
+    crate::FsmMiddlewares::after_dispatch(backend, &result);
+
+    while let Some(ev) = backend.deferred.next_released() {
+        let ev = FsmEvent::Event(ev);
+
+        #[cfg(feature = "std")]
+        let observed = backend.observers.before_dispatch(ev.as_ref(), backend.current_states);
+
+        let released = F::dispatch_event(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, ev);
+
+        #[cfg(feature = "std")]
+        backend.observers.after_dispatch(observed, backend.current_states);
+
+        if result.is_ok() {
+            result = released;
+        }
+    }
+
+    result
+}
+
+/// Dispatch the borrowed event, then re-dispatch the released deferred events like `dispatch_with_deferred`.
+/// The middlewares only receive the owned events, so the borrowed event is refused with `FsmError::NotSupported`
+/// while any of them are registered, rather than bypassing them.
+pub fn dispatch_borrowed_with_deferred<F, Q, I, T>(ctx: DispatchContext<F, Q, I, T>, event: FsmEvent<<F as FsmBackendBorrowed>::BorrowedEvents<'_>, <F as FsmBackend>::Timers>) -> FsmDispatchResult
+    where F: FsmBackendBorrowed, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    let DispatchContext { queue, inspect, backend, timers, mut resources } = ctx;
+
+    #[cfg(feature = "std")]
+    if !backend.middlewares.is_empty() {
+        return Err(crate::FsmError::NotSupported);
+    }
+
+    #[cfg(feature = "std")]
+    let observed = backend.observers.before_dispatch(event.as_ref(), backend.current_states);
+
+    let mut result = F::dispatch_borrowed_event(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, event);
+
+    #[cfg(feature = "std")]
+    backend.observers.after_dispatch(observed, backend.current_states);
+
+    while let Some(ev) = backend.deferred.next_released() {
+        let ev = FsmEvent::Event(ev);
+
+        #[cfg(feature = "std")]
+        let observed = backend.observers.before_dispatch(ev.as_ref(), backend.current_states);
+
+        let released = F::dispatch_event(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, ev);
+
+        #[cfg(feature = "std")]
+        backend.observers.after_dispatch(observed, backend.current_states);
+
+        if result.is_ok() {
+            result = released;
+        }
+    }
+
+    result
+}
+
+/// Dispatch the event and the released deferred events, for the async dispatch path.
+pub async fn dispatch_with_deferred_async<F, Q, I, T>(ctx: DispatchContext<'_, '_, '_, F, Q, I, T>, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>) -> FsmDispatchResult
+    where F: FsmBackendAsync, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    let DispatchContext { queue, inspect, backend, timers, mut resources } = ctx;
+
+    #[cfg(feature = "std")]
+    let event = match crate::FsmMiddlewares::before_dispatch(backend, event)? {
+        Some(event) => event,
+        None => return Ok(())
+    };
+
+    #[cfg(feature = "std")]
+    let observed = backend.observers.before_dispatch(event.as_ref(), backend.current_states);
+
+    let mut result = F::dispatch_event_async(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, event).await;
+
+    #[cfg(feature = "std")]
+    backend.observers.after_dispatch(observed, backend.current_states);
+
+    #[cfg(feature = "std")]
+    crate::FsmMiddlewares::after_dispatch(backend, &result);
+
+    while let Some(ev) = backend.deferred.next_released() {
+        let ev = FsmEvent::Event(ev);
+
+        #[cfg(feature = "std")]
+        let observed = backend.observers.before_dispatch(ev.as_ref(), backend.current_states);
+
+        let released = F::dispatch_event_async(DispatchContext { queue: &mut *queue, inspect: &mut *inspect, backend: &mut *backend, timers: &mut *timers, resources: resources.as_deref_mut() }, ev).await;
+
+        #[cfg(feature = "std")]
+        backend.observers.after_dispatch(observed, backend.current_states);
+
+        if result.is_ok() {
+            result = released;
+        }
+    }
+
+    result
+}
+
+/// The dispatch context of a sub-machine, as a part of its parent's dispatch.
+pub type FsmSubDispatchContext<'a, 'p, TFsm, TSubMachine, Q, I, T> =
+    DispatchContext<'a, 'a, 'a, TSubMachine, FsmEventQueueSub<'p, Q, TFsm, TSubMachine>, I, FsmTimersSub<'p, T, TFsm, TSubMachine>>;
+
+/// The sub-machine of a parent's dispatch, with the parent's queue, timers and inspection adapted to it.
+pub struct FsmSubDispatch<'p, TFsm, TSubMachine, Q, I, T>
+    where
+        TFsm: FsmBackend,
+        Q: FsmEventQueue<TFsm>,
+        T: FsmTimers<TFsm>
+{
+    pub backend: &'p mut TSubMachine,
+    queue: FsmEventQueueSub<'p, Q, TFsm, TSubMachine>,
+    timers: FsmTimersSub<'p, T, TFsm, TSubMachine>,
+    inspect: I
+}
+
+impl<'p, TFsm, TSubMachine, Q, I, T> FsmSubDispatch<'p, TFsm, TSubMachine, Q, I, T>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackend + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    /// Borrows the sub-machine from the parent's context, as a part of the parent's dispatch.
+    pub fn new(ctx: &'p mut DispatchContext<'_, '_, '_, TFsm, Q, I, T>, inspect_event_ctx: &I) -> Self {
+        let backend: &mut TSubMachine = ctx.backend.states.as_mut();
+        backend.dispatch_id = ctx.backend.dispatch_id;
+
+        FsmSubDispatch {
+            backend,
+            queue: FsmEventQueueSub {
+                parent: &mut *ctx.queue,
+                _parent_fsm: PhantomData::<TFsm>,
+                _sub_fsm: PhantomData::<TSubMachine>
+            },
+            timers: FsmTimersSub {
+                parent: &mut *ctx.timers,
+                _parent_fsm: PhantomData::<TFsm>,
+                _sub_fsm: PhantomData::<TSubMachine>
+            },
+            inspect: inspect_event_ctx.for_sub_machine::<TSubMachine>()
+        }
+    }
+
+    /// The dispatch context of the sub-machine.
+    pub fn context(&mut self) -> FsmSubDispatchContext<'_, 'p, TFsm, TSubMachine, Q, I, T> {
+        DispatchContext {
+            backend: &mut *self.backend,
+            inspect: &mut self.inspect,
+            queue: &mut self.queue,
+            timers: &mut self.timers,
+            // the machines with resources can't have submachines
+            resources: None
+        }
+    }
+}
+
 /// Used to funnel the event down to the sub-machine.
 pub fn dispatch_to_submachine<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>,
         ev: FsmEvent<<TSubMachine as FsmBackend>::Events, <TSubMachine as FsmBackend>::Timers>, inspect_event_ctx: &mut I)
@@ -44,28 +250,148 @@ pub fn dispatch_to_submachine<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut
         I: Inspect,
         T: FsmTimers<TFsm>,
 {
-    let sub_fsm: &mut TSubMachine = ctx.backend.states.as_mut();
+    let mut sub = FsmSubDispatch::<TFsm, TSubMachine, Q, I, T>::new(ctx, inspect_event_ctx);
+
+    dispatch_with_deferred(sub.context(), ev)
+}
+
+/// Stops the sub-machine while its parent is being stopped. See `FsmBackendStop`.
+pub fn stop_submachine<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>, inspect_event_ctx: &mut I)
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendStop + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    let mut sub = FsmSubDispatch::<TFsm, TSubMachine, Q, I, T>::new(ctx, inspect_event_ctx);
+
+    TSubMachine::stop_states(sub.context());
+    sub.backend.deferred.clear();
+}
+
+/// Evaluates the event in the sub-machine, without dispatching it. See `FsmBackendPeek`.
+pub fn peek_submachine<TFsm, TSubMachine, Q>(backend: &mut FsmBackendImpl<TFsm>, queue: &mut Q,
+        ev: FsmEvent<<TSubMachine as FsmBackend>::Events, <TSubMachine as FsmBackend>::Timers>)
+    -> FsmResult<Option<&'static FsmInfoTransition>>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        TSubMachine: FsmBackendPeek + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>
+{
+    let sub_fsm: &mut TSubMachine = backend.states.as_mut();
 
     let mut queue_adapter = FsmEventQueueSub {
-        parent: ctx.queue,
-        _parent_fsm: core::marker::PhantomData::<TFsm>::default(),
-        _sub_fsm: core::marker::PhantomData::<TSubMachine>::default()
+        parent: queue,
+        _parent_fsm: core::marker::PhantomData::<TFsm>,
+        _sub_fsm: core::marker::PhantomData::<TSubMachine>
     };
 
-    let mut timers_adapter = FsmTimersSub {
-        parent: ctx.timers,
-        _parent_fsm: core::marker::PhantomData::<TFsm>::default(),
-        _sub_fsm: core::marker::PhantomData::<TSubMachine>::default()
-    };
+    TSubMachine::peek_event(sub_fsm, &mut queue_adapter, &ev)
+}
 
-    let mut inspect = inspect_event_ctx.for_sub_machine::<TSubMachine>();
+/// Used to funnel the event down to the sub-machine, for the async dispatch path.
+pub async fn dispatch_to_submachine_async<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>,
+        ev: FsmEvent<<TSubMachine as FsmBackend>::Events, <TSubMachine as FsmBackend>::Timers>, inspect_event_ctx: &mut I)
+    -> FsmResult<()>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendAsync + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    let mut sub = FsmSubDispatch::<TFsm, TSubMachine, Q, I, T>::new(ctx, inspect_event_ctx);
 
-    let sub_dispatch_ctx = DispatchContext {
-        backend: sub_fsm,
-        inspect: &mut inspect,
-        queue: &mut queue_adapter,
-        timers: &mut timers_adapter
-    };
-    
-    <TSubMachine>::dispatch_event(sub_dispatch_ctx, ev)
-}
\ No newline at end of file
+    dispatch_with_deferred_async(sub.context(), ev).await
+}
+
+/// Re-enters the previously active states of the sub-machine, used for sub-machine states with history.
+pub fn resume_submachine<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>, deep: bool, inspect_event_ctx: &mut I)
+    -> FsmResult<()>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendHistory + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    let mut sub = FsmSubDispatch::<TFsm, TSubMachine, Q, I, T>::new(ctx, inspect_event_ctx);
+
+    <TSubMachine>::resume(sub.context(), deep)
+}
+
+/// Enters the sub-machine at its entry point instead of its initial states.
+pub fn enter_submachine<'a, 'b, 'c, TFsm, TSubMachine, TEntryPoint, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>, inspect_event_ctx: &mut I)
+    -> FsmResult<()>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendHistory + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        TEntryPoint: FsmEntryPoint<TSubMachine>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    inspect_event_ctx.info("Entering the sub-machine at its entry point.");
+
+    let sub_fsm: &mut TSubMachine = ctx.backend.states.as_mut();
+    sub_fsm.dispatch_id = ctx.backend.dispatch_id;
+    sub_fsm.current_states = <TSubMachine>::entry_point_states(<TEntryPoint>::fsm_state());
+
+    resume_submachine(ctx, false, inspect_event_ctx)
+}
+
+/// Enters the sub-machine at its entry point, for the async dispatch path.
+pub async fn enter_submachine_async<'a, 'b, 'c, TFsm, TSubMachine, TEntryPoint, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>, inspect_event_ctx: &mut I)
+    -> FsmResult<()>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendAsync + FsmBackendHistory + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        TEntryPoint: FsmEntryPoint<TSubMachine>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    inspect_event_ctx.info("Entering the sub-machine at its entry point.");
+
+    let sub_fsm: &mut TSubMachine = ctx.backend.states.as_mut();
+    sub_fsm.dispatch_id = ctx.backend.dispatch_id;
+    sub_fsm.current_states = <TSubMachine>::entry_point_states(<TEntryPoint>::fsm_state());
+
+    resume_submachine_async(ctx, false, inspect_event_ctx).await
+}
+
+/// Re-enters the previously active states of the sub-machine, for the async dispatch path.
+pub async fn resume_submachine_async<'a, 'b, 'c, TFsm, TSubMachine, Q, I, T>(ctx: &mut DispatchContext<'a, 'b, 'c, TFsm, Q, I, T>, deep: bool, inspect_event_ctx: &mut I)
+    -> FsmResult<()>
+    where
+        TFsm: FsmBackend,
+        <TFsm as FsmBackend>::States: AsMut<TSubMachine>,
+        <TFsm as FsmBackend>::Events: From<<TSubMachine as FsmBackend>::Events>,
+        <TFsm as FsmBackend>::Timers: From<<TSubMachine as FsmBackend>::Timers>,
+        TSubMachine: FsmBackendAsync + DerefMut<Target = FsmBackendImpl<TSubMachine>>,
+        Q: FsmEventQueue<TFsm>,
+        I: Inspect,
+        T: FsmTimers<TFsm>,
+{
+    let mut sub = FsmSubDispatch::<TFsm, TSubMachine, Q, I, T>::new(ctx, inspect_event_ctx);
+
+    <TSubMachine>::resume_async(sub.context(), deep).await
+}
@@ -0,0 +1,123 @@
+use crate::lib::*;
+
+pub type FsmResult<T> = Result<T, FsmError>;
+
+/// The lib-level error type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmError {
+    /// The event isn't handled in the current state. With multiple regions, this is the state of the
+    /// first region that didn't handle it. The state is `Stopped` before the machine is started.
+    NoTransition { state: &'static str, event: &'static str },
+    /// The event queue was full, with `capacity` events, and the new event was rejected. Reported to the
+    /// inspector with `Inspect::on_queue_full` when the machine enqueued it, as are the events that the
+    /// queues with the `DropOldest` and `DropNewest` policies dropped without failing. See `FsmFrontend::try_dispatch` and `FsmHandle::try_dispatch`
+    /// for the backpressure against a busy machine.
+    QueueFull { capacity: usize, dropped: FsmQueueDropped },
+    /// The outputs or the deferred events are full and the new one wasn't kept.
+    QueueOverCapacity { capacity: usize },
+    /// The shared queue can't be locked, another thread panicked while holding it.
+    QueueUnavailable,
+    NotSupported,
+    /// The machine declares async actions or guards, it has to be started and dispatched with
+    /// `start_async` and `dispatch_async`.
+    AsyncRequired,
+    Timer(FsmTimerError),
+    /// A fallible guard or action failed. Implement `From` for your own error types to use them with
+    /// the `?` operator in `try_guard` and `try_action`.
+    ActionFailed(&'static str),
+    /// A middleware rejected the event before it was dispatched, see `FsmMiddleware`.
+    EventRejected(&'static str),
+    /// A guard or an action panicked, caught by an `FsmSupervisor`.
+    Panicked,
+    /// The supervisor restarted the machine and waits for the backoff before dispatching again, see `FsmSupervisor`.
+    SupervisorBackoff { remaining: Duration },
+    /// The snapshot was taken with another version of the machine, and none of the migrations lead from
+    /// it to this one. See `FsmSnapshotMigrations`.
+    SnapshotVersion { expected: u64, found: u64 },
+    /// The snapshot couldn't be migrated or deserialized.
+    SnapshotInvalid(&'static str),
+    /// The machine was despawned from its `FsmPool`, or the id is from another pool.
+    PoolInstanceNotFound,
+    /// The machine's runner thread was shut down or panicked, see `FsmHandle`.
+    RunnerStopped,
+    /// The awaited state wasn't entered in time, see `FsmTokioHandle::wait_for_state`.
+    WaitTimeout
+}
+
+/// Which event was dropped by a full queue, see `FsmError::QueueFull` and `Inspect::on_queue_full`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsmQueueDropped {
+    /// The event that was being enqueued.
+    Newest,
+    /// The oldest queued event, to make room for the new one.
+    Oldest
+}
+
+/// The errors of the timers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmTimerError {
+    /// The timer triggered, but the machine didn't start it.
+    NotStarted,
+    /// The timers implementation doesn't support the timers.
+    NotSupported,
+    /// The requests of the timers are full.
+    RequestsOverCapacity { capacity: usize },
+    /// The region's retries can't be counted, without the `std` feature only the first regions up to the
+    /// capacity are tracked.
+    RetriesOverCapacity { capacity: usize }
+}
+
+impl From<FsmTimerError> for FsmError {
+    fn from(err: FsmTimerError) -> Self {
+        FsmError::Timer(err)
+    }
+}
+
+impl fmt::Display for FsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmError::NoTransition { state, event } => write!(f, "The event '{}' isn't handled in the state '{}'", event, state),
+            FsmError::QueueFull { capacity, dropped } => write!(f, "The event queue is full, with {} events, {} was dropped", capacity, dropped),
+            FsmError::QueueOverCapacity { capacity } => write!(f, "The queue is full, with {} entries", capacity),
+            FsmError::QueueUnavailable => f.write_str("The event queue is unavailable"),
+            FsmError::NotSupported => f.write_str("Not supported"),
+            FsmError::AsyncRequired => f.write_str("The machine has async actions or guards, dispatch it with dispatch_async"),
+            FsmError::Timer(err) => write!(f, "Timer error: {}", err),
+            FsmError::ActionFailed(reason) => write!(f, "The guard or the action failed: {}", reason),
+            FsmError::EventRejected(reason) => write!(f, "The event was rejected: {}", reason),
+            FsmError::Panicked => f.write_str("The guard or the action panicked"),
+            FsmError::SupervisorBackoff { remaining } => write!(f, "The machine was restarted, dispatching again in {:?}", remaining),
+            FsmError::SnapshotVersion { expected, found } => write!(f, "The snapshot's version {:x} can't be migrated to the version {:x}", found, expected),
+            FsmError::SnapshotInvalid(reason) => write!(f, "The snapshot is invalid: {}", reason),
+            FsmError::PoolInstanceNotFound => f.write_str("The machine isn't in the pool"),
+            FsmError::RunnerStopped => f.write_str("The machine's runner was stopped"),
+            FsmError::WaitTimeout => f.write_str("The state wasn't entered in time")
+        }
+    }
+}
+
+impl fmt::Display for FsmQueueDropped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmQueueDropped::Newest => f.write_str("the new event"),
+            FsmQueueDropped::Oldest => f.write_str("the oldest event")
+        }
+    }
+}
+
+impl fmt::Display for FsmTimerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmTimerError::NotStarted => f.write_str("the timer hasn't been started"),
+            FsmTimerError::NotSupported => f.write_str("the timers aren't supported"),
+            FsmTimerError::RequestsOverCapacity { capacity } => write!(f, "the timer requests are full, with {} requests", capacity),
+            FsmTimerError::RetriesOverCapacity { capacity } => write!(f, "the retries are counted only for the first {} regions", capacity)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FsmError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FsmTimerError {}
@@ -1,7 +1,8 @@
-use crate::{FsmBackend, FsmEventQueueSender, lib::*};
+use crate::{FsmBackend, FsmEventQueueSender, FsmOutputs, FsmTimerRequests, lib::*};
 
 /// The internal event type that also allows stopping or starting the machine.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FsmEvent<E, T> {
     Start,
     Stop,
@@ -9,6 +10,25 @@ pub enum FsmEvent<E, T> {
     Event(E)
 }
 
+/// The trace context carried by an event, implemented by the generated events enum. The context is declared
+/// with `fsm.event_trace_context::<TEvent>(|ev| &ev.span)` and is downcast by the inspectors that understand
+/// it, like `InspectOpenTelemetry` with a `tracing::Span`. The other events don't carry a context.
+pub trait FsmEventTraceContext {
+    fn trace_context(&self) -> Option<&dyn Any> {
+        None
+    }
+}
+
+impl<E, T> FsmEvent<E, T> where E: FsmEventTraceContext {
+    /// The trace context of the wrapped event.
+    pub fn trace_context(&self) -> Option<&dyn Any> {
+        match self {
+            FsmEvent::Event(ev) => ev.trace_context(),
+            _ => None
+        }
+    }
+}
+
 impl<E, T> From<E> for FsmEvent<E, T> {
     fn from(event: E) -> Self {
         FsmEvent::Event(event)
@@ -59,11 +79,45 @@ impl<E, T> FsmEvent<E, T> {
 
 pub type FsmRegionId = usize;
 
+/// What the machine does with the events that don't have a transition in any of the regions.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum FsmUnhandledEventPolicy {
+    /// The event is discarded and the dispatch succeeds.
+    Ignore,
+    /// The event is reported to the inspector and the dispatch succeeds.
+    Inspect,
+    /// The event is reported to the inspector and the dispatch fails with `FsmError::NoTransition`. The default.
+    #[default]
+    Error
+}
+
 /// The context that is given to all of the guards and actions.
 pub struct EventContext<'a, TFsm, Q> where TFsm: FsmBackend, Q: FsmEventQueueSender<TFsm> {
     pub context: &'a mut TFsm::Context,
     pub queue: &'a mut Q,
-    pub region: FsmRegionId
+    pub region: FsmRegionId,
+    /// Cancel or restart the timers of the active states, applied after the current event is processed.
+    pub timers: &'a mut FsmTimerRequests<TFsm>,
+    /// The commands for the caller, like the frames to send, drained after the dispatch.
+    pub outputs: &'a mut FsmOutputs<TFsm>,
+    /// The external resources that were lent to this dispatch with `FsmFrontend::dispatch_with`, `None`
+    /// for the other dispatches and inside of the submachines.
+    pub resources: Option<&'a mut TFsm::Resources>
+}
+
+impl<'a, TFsm, Q> EventContext<'a, TFsm, Q> where TFsm: FsmBackend, Q: FsmEventQueueSender<TFsm>
+{
+    /// For how long has the current state of this region been active, measured at the start of the current
+    /// dispatch. In the exit actions and the transitions' actions that's the state being left, in the entry
+    /// actions it's zero. `None` if the timers don't have a clock, see `FsmTimersWithClock`.
+    pub fn time_in_state(&self) -> Option<Duration> {
+        self.timers.time_in_state(self.region)
+    }
+
+    /// The retries taken by this region since it entered its retry loop, see `retry` in the builder.
+    pub fn attempts(&self) -> u32 {
+        self.timers.attempts(self.region)
+    }
 }
 
 impl<'a, TFsm, Q> Deref for EventContext<'a, TFsm, Q> where TFsm: FsmBackend, Q: FsmEventQueueSender<TFsm>
@@ -0,0 +1,64 @@
+//! The support of the `extern "C"` API that is generated for the machines declared with `fsm.ffi()`.
+
+use crate::inspect::null::InspectNull;
+use crate::lib::*;
+use crate::{FsmBackend, FsmError, FsmEventQueueVec, FsmFrontend, FsmResult, FsmTimersNull};
+
+/// The frontend behind the opaque pointer of the C API. It's heap allocated and doesn't have the timers.
+pub type FsmFfiFrontend<F> = FsmFrontend<F, FsmEventQueueVec<F>, InspectNull, FsmTimersNull>;
+
+/// An event that can be dispatched through the C API, decoded from the byte payload that is passed with
+/// its id. Implemented by all of the machine's events.
+///
+/// Example : `impl FsmFfiEvent for SetSpeed { fn from_ffi_bytes(p: &[u8]) -> Option<Self> { Some(SetSpeed(*p.first()?)) } }`
+pub trait FsmFfiEvent: Sized {
+    /// Decode the event, or `None` if the payload isn't valid.
+    fn from_ffi_bytes(payload: &[u8]) -> Option<Self>;
+}
+
+/// The status codes returned by the functions of the C API.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsmFfiStatus {
+    Ok = 0,
+    /// The machine's pointer is null.
+    NullPointer = -1,
+    /// None of the machine's events have the id.
+    UnknownEvent = -2,
+    /// The event couldn't be decoded from the payload.
+    InvalidPayload = -3,
+    /// The event isn't handled in the current state.
+    NoTransition = -4,
+    /// The machine or the region is stopped, or the region doesn't exist.
+    Stopped = -5,
+    /// Any of the other errors of the dispatch.
+    Failed = -6
+}
+
+impl FsmFfiStatus {
+    pub fn from_result(result: FsmResult<()>) -> Self {
+        match result {
+            Ok(()) => FsmFfiStatus::Ok,
+            Err(FsmError::NoTransition { .. }) => FsmFfiStatus::NoTransition,
+            Err(_) => FsmFfiStatus::Failed
+        }
+    }
+}
+
+impl<F: FsmBackend> FsmFfiFrontend<F> {
+    /// Move the machine to the heap, for the opaque pointer of the C API.
+    pub fn into_ffi_ptr(self) -> *mut Self {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Drop the machine behind the pointer. Null pointers are ignored.
+    ///
+    /// # Safety
+    ///
+    /// The pointer has to be created by `into_ffi_ptr` and can't be used afterwards.
+    pub unsafe fn drop_ffi_ptr(fsm: *mut Self) {
+        if !fsm.is_null() {
+            drop(Box::from_raw(fsm));
+        }
+    }
+}
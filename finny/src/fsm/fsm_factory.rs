@@ -1,7 +1,18 @@
 use crate::{FsmBackend, FsmBackendImpl, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect};
 
 #[cfg(feature="std")]
-use crate::{FsmEventQueueVec, timers::std::TimersStd};
+use crate::{DispatchContext, FsmEventQueueBounded, FsmEventQueueVec, FsmTimersNull, inspect::recorder::FsmEventLog, timers::std::TimersStd};
+
+#[cfg(feature="serde")]
+use crate::{FsmError, FsmSnapshot, FsmSnapshotVersion};
+
+/// The frontend of a replayed machine, see `FsmFactory::replay`.
+#[cfg(feature="std")]
+pub type FsmReplayFrontend<F, I> = FsmFrontend<F, FsmEventQueueVec<F>, I, FsmTimersNull>;
+
+/// The frontend with an inline queue, see `FsmFactory::new_with_capacity`.
+#[cfg(feature="std")]
+pub type FsmInlineFrontend<F, const N: usize> = FsmFrontend<F, FsmEventQueueBounded<F, N>, crate::inspect::null::InspectNull, TimersStd<F>>;
 
 /// Builds a frontend for running your FSM.
 pub trait FsmFactory {
@@ -38,4 +49,83 @@ pub trait FsmFactory {
 
         Ok(frontend)
     }
-}
\ No newline at end of file
+
+    /// Build a new frontend for the FSM with an inline `FsmEventQueueBounded` queue of `N` events, `TimersStd` for
+    /// timers and no logging. The queue doesn't allocate, it rejects the events once it's full.
+    #[cfg(feature="std")]
+    fn new_with_capacity<const N: usize>(context: <Self::Fsm as FsmBackend>::Context) -> FsmResult<FsmInlineFrontend<Self::Fsm, N>> {
+        use crate::inspect::null::InspectNull;
+
+        Self::new_with(context, FsmEventQueueBounded::new(), InspectNull::new(), TimersStd::new())
+    }
+
+    /// Re-drive a new machine with the events of the log, in their recorded order. The events enqueued by the
+    /// actions are discarded, as they are a part of the log, and the timer events are dispatched as recorded,
+    /// so no timers are started. The machine is returned in its final state.
+    #[cfg(feature="std")]
+    fn replay_with<I>(context: <Self::Fsm as FsmBackend>::Context, log: &FsmEventLog<Self::Fsm>, inspect: I) -> FsmResult<FsmReplayFrontend<Self::Fsm, I>>
+        where I: Inspect, <Self::Fsm as FsmBackend>::Events: Clone
+    {
+        let mut frontend = Self::new_with(context, FsmEventQueueVec::new(), inspect, FsmTimersNull)?;
+
+        for event in &log.events {
+            let dispatch_ctx = DispatchContext {
+                backend: &mut frontend.backend,
+                inspect: &mut frontend.inspect,
+                queue: &mut frontend.queue,
+                timers: &mut frontend.timers,
+                resources: None
+            };
+
+            // the errors are a part of the replayed behaviour
+            let _ = <Self::Fsm as FsmBackend>::dispatch_event(dispatch_ctx, event.clone());
+
+            while frontend.queue.dequeue().is_some() { }
+        }
+
+        Ok(frontend)
+    }
+
+    /// Re-drive a new machine with the events of the log, without logging.
+    #[cfg(feature="std")]
+    fn replay(context: <Self::Fsm as FsmBackend>::Context, log: &FsmEventLog<Self::Fsm>) -> FsmResult<FsmReplayFrontend<Self::Fsm, crate::inspect::null::InspectNull>>
+        where <Self::Fsm as FsmBackend>::Events: Clone
+    {
+        use crate::inspect::null::InspectNull;
+
+        Self::replay_with(context, log, InspectNull::new())
+    }
+
+    /// Restore a frontend from a snapshot, with all the environmental services provided by the caller. The
+    /// queue is taken from the snapshot. No actions are executed and the state timers are not restarted.
+    /// Fails if the snapshot was taken with another version of the machine, see `FsmSnapshotMigrations`.
+    #[cfg(feature="serde")]
+    fn restore_with<Q, I, T>(context: <Self::Fsm as FsmBackend>::Context, snapshot: FsmSnapshot<Self::Fsm, Q>, inspect: I, timers: T) -> FsmResult<FsmFrontend<Self::Fsm, Q, I, T>>
+        where Q: FsmEventQueue<Self::Fsm>, I: Inspect, T: FsmTimers<Self::Fsm>, Self::Fsm: FsmSnapshotVersion
+    {
+        if snapshot.version != <Self::Fsm as FsmSnapshotVersion>::SNAPSHOT_VERSION {
+            return Err(FsmError::SnapshotVersion { expected: <Self::Fsm as FsmSnapshotVersion>::SNAPSHOT_VERSION, found: snapshot.version });
+        }
+
+        let (backend, queue) = FsmBackendImpl::from_snapshot(context, snapshot);
+
+        let frontend = FsmFrontend {
+            queue,
+            inspect,
+            backend,
+            timers
+        };
+
+        Ok(frontend)
+    }
+
+    /// Restore a frontend from a snapshot, with a `FsmEventQueueVec` queue, `TimersStd` for timers and no logging.
+    #[cfg(all(feature="std", feature="serde"))]
+    fn restore(context: <Self::Fsm as FsmBackend>::Context, snapshot: FsmSnapshot<Self::Fsm, FsmEventQueueVec<Self::Fsm>>) -> FsmResult<FsmFrontend<Self::Fsm, FsmEventQueueVec<Self::Fsm>, crate::inspect::null::InspectNull, TimersStd<Self::Fsm>>>
+        where Self::Fsm: FsmSnapshotVersion
+    {
+        use crate::inspect::null::InspectNull;
+
+        Self::restore_with(context, snapshot, InspectNull::new(), TimersStd::new())
+    }
+}
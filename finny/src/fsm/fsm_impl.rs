@@ -1,14 +1,44 @@
-use crate::{DispatchContext, FsmTimers, Inspect, lib::*};
-use crate::{FsmBackend, FsmEvent, FsmEventQueue, FsmResult, FsmStates};
+use crate::{DispatchContext, FsmBackendAsync, FsmBackendBorrowed, FsmBackendCancelTimers, FsmBackendPeek, FsmBackendStatePath, FsmBackendStop, FsmInfoTransition, FsmDeferredEvents, FsmOutputs, FsmOutputsDrain, FsmResetStates, FsmTimerRequests, FsmTimers, Inspect, lib::*};
+use crate::{FsmBackend, FsmCurrentState, FsmError, FsmEvent, FsmEventQueue, FsmEventSender, FsmResult, FsmState, FsmStates};
 
 use super::FsmStateFactory;
 
+#[cfg(feature = "std")]
+use crate::{FsmScheduledEventId, FsmTimerError};
+
 /// The struct that holds the core context and state of the given Finny FSM. Doesn't include
 /// environmental traits that can be changed at runtime.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "<F as FsmBackend>::Context: serde::Serialize, <F as FsmBackend>::States: serde::Serialize, <<F as FsmBackend>::States as FsmStates<F>>::CurrentState: serde::Serialize, <F as FsmBackend>::Events: serde::Serialize",
+    deserialize = "<F as FsmBackend>::Context: serde::Deserialize<'de>, <F as FsmBackend>::States: serde::Deserialize<'de>, <<F as FsmBackend>::States as FsmStates<F>>::CurrentState: serde::Deserialize<'de>, <F as FsmBackend>::Events: serde::Deserialize<'de>"
+)))]
 pub struct FsmBackendImpl<F: FsmBackend> {
     pub context: <F as FsmBackend>::Context,
     pub states: <F as FsmBackend>::States,
-    pub current_states: <<F as FsmBackend>::States as FsmStates<F>>::CurrentState
+    pub current_states: <<F as FsmBackend>::States as FsmStates<F>>::CurrentState,
+    pub deferred: FsmDeferredEvents<F>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub timer_requests: FsmTimerRequests<F>,
+    /// The commands pushed by the actions, to be drained by the caller.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub outputs: FsmOutputs<F>,
+    /// The error that moved the machine into its fault state.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub error: Option<FsmError>,
+    /// Notified about the state changes after every event.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub observers: crate::FsmObservers<F>,
+    /// Wrap the dispatch of every event.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub middlewares: crate::FsmMiddlewares<F>,
+    /// The id of the current or the last top-level dispatch of the frontend, like `dispatch` or `process`.
+    /// The inspection of all the events that it runs to completition, including the queued events and
+    /// the submachines, can be correlated with it. Increases with every top-level dispatch.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub dispatch_id: u64
 }
 
 impl<F: FsmBackend> FsmBackendImpl<F> {
@@ -20,7 +50,16 @@ impl<F: FsmBackend> FsmBackendImpl<F> {
         let backend = FsmBackendImpl::<F> {
             context,
             states,
-            current_states
+            current_states,
+            deferred: FsmDeferredEvents::new(),
+            timer_requests: FsmTimerRequests::new(),
+            outputs: FsmOutputs::new(),
+            error: None,
+            #[cfg(feature = "std")]
+            observers: crate::FsmObservers::new(),
+            #[cfg(feature = "std")]
+            middlewares: crate::FsmMiddlewares::new(),
+            dispatch_id: 0
         };
 
         Ok(backend)
@@ -39,6 +78,63 @@ impl<F: FsmBackend> FsmBackendImpl<F> {
     {
         self.states.as_ref()
     }
+
+    /// The current states of the submachine, as seen from this machine. They are `Stopped` until this machine
+    /// first enters the submachine's state. After it exits the state, the submachine keeps its last states, for
+    /// the history to resume them, the submachines without history restart from their initial states instead.
+    pub fn get_sub_current_states<TSub>(&self) -> <<TSub as FsmBackend>::States as FsmStates<TSub>>::CurrentState
+        where <F as FsmBackend>::States : AsRef<TSub>, TSub: FsmBackend + Deref<Target = FsmBackendImpl<TSub>>
+    {
+        self.get_state::<TSub>().get_current_states()
+    }
+
+    /// A state of the submachine, as seen from this machine.
+    pub fn get_sub_state<'a, TSub, S>(&'a self) -> &'a S
+        where <F as FsmBackend>::States : AsRef<TSub>, TSub: FsmBackend + Deref<Target = FsmBackendImpl<TSub>> + 'a,
+            <TSub as FsmBackend>::States : AsRef<S>
+    {
+        self.get_state::<TSub>().get_state()
+    }
+
+    /// Are all of the regions in their final states? A stopped machine isn't completed.
+    pub fn is_completed(&self) -> bool {
+        self.current_states.as_ref().iter().all(|s| match s {
+            FsmCurrentState::State(s) => <<F as FsmBackend>::States as FsmStates<F>>::is_final_state(*s),
+            FsmCurrentState::Stopped => false
+        })
+    }
+
+    /// Is the machine stopped, not started yet or stopped with `FsmFrontend::stop`?
+    pub fn is_stopped(&self) -> bool {
+        FsmCurrentState::all_stopped(self.current_states.as_ref())
+    }
+
+    /// The error that moved the machine into its fault state, if any.
+    pub fn get_error(&self) -> Option<&FsmError> {
+        self.error.as_ref()
+    }
+
+    /// Take the error that moved the machine into its fault state, clearing it.
+    pub fn take_error(&mut self) -> Option<FsmError> {
+        self.error.take()
+    }
+}
+
+impl<F: FsmBackendStatePath> FsmBackendImpl<F> {
+    /// Write the path of the current states, like `Connected/Authenticating`, without allocating. The states
+    /// of the submachines follow theirs after a `/`, the regions are separated by `, ` and the regions of a
+    /// submachine are grouped in parentheses.
+    pub fn write_current_state_name<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        F::write_state_path(self, w)
+    }
+
+    /// The path of the current states, see `write_current_state_name`.
+    #[cfg(feature = "std")]
+    pub fn current_state_name(&self) -> String {
+        let mut name = String::new();
+        let _ = self.write_current_state_name(&mut name);
+        name
+    }
 }
 
 impl<F: FsmBackend> Deref for FsmBackendImpl<F> {
@@ -76,21 +172,121 @@ impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
 {
     /// Start the FSM, initiates the transition to the initial state.
     pub fn start(&mut self) -> FsmResult<()> {
-        Self::dispatch_single_event(self, FsmEvent::Start)
+        self.begin_dispatch();
+        self.dispatch_single_event_with(FsmEvent::Start, None)
+    }
+
+    /// Stop the machine. The current states of all the regions are exited, the states of the submachines
+    /// before the submachines themselves, so that the exit actions can release what the entry actions acquired.
+    /// The timers are cancelled and the triggered ones drained, and the queued, deferred and scheduled events
+    /// are dropped. The stopped machine can be started again with `start`. Only the synchronous exit actions
+    /// are executed.
+    pub fn stop(&mut self)
+        where F: FsmBackendStop + FsmBackendCancelTimers
+    {
+        self.begin_dispatch();
+
+        let dispatch_ctx = DispatchContext {
+            backend: &mut self.backend,
+            inspect: &mut self.inspect,
+            queue: &mut self.queue,
+            timers: &mut self.timers,
+            resources: None
+        };
+        F::stop_states(dispatch_ctx);
+
+        F::cancel_timers(&mut self.backend, &mut self.inspect, &mut self.timers);
+        while self.timers.get_triggered_timer().is_some() { }
+        self.backend.deferred.clear();
+        self.backend.timer_requests = FsmTimerRequests::new();
+        self.queue.clear();
+    }
+
+    /// Return the machine to its initial states, keeping its context. The machine is stopped, with the exit
+    /// actions of its current states, then the states are kept or created again, the outputs and the error are
+    /// dropped along with the events, and the machine is started again, with the entry actions of the initial
+    /// states. See `stop`.
+    pub fn reset(&mut self, states: FsmResetStates) -> FsmResult<()>
+        where F: FsmBackendStop + FsmBackendCancelTimers
+    {
+        self.stop();
+        if states == FsmResetStates::Recreate {
+            self.backend.states = <<F as FsmBackend>::States>::new_state(&self.backend.context)?;
+        }
+        self.backend.outputs.clear();
+        self.backend.error = None;
+
+        self.start()
     }
 
-    /// Dispatch any pending timer events into the queue, then run all the
-    /// events from the queue until completition.
+    /// Reset the machine with new states, see `reset`. See `FsmSupervisor` for restarting the failed machines.
+    pub fn restart(&mut self) -> FsmResult<()>
+        where F: FsmBackendStop + FsmBackendCancelTimers
+    {
+        self.reset(FsmResetStates::Recreate)
+    }
+
+    /// A new top-level dispatch, see `FsmBackendImpl::dispatch_id`.
+    fn begin_dispatch(&mut self) {
+        self.backend.dispatch_id = self.backend.dispatch_id.wrapping_add(1);
+    }
+
+    /// Dispatch any pending timer events into the queue, then run all the events from the queue until
+    /// completition. A failed timer event doesn't hold back the others, its error is returned afterwards.
     pub fn dispatch_timer_events(&mut self) -> FsmResult<()> {
-        loop {
-            if let Some(timer_id) = self.timers.get_triggered_timer() {
-                self.dispatch_single_event(FsmEvent::Timer(timer_id))?;
-            } else {
-                break;
+        self.begin_dispatch();
+
+        let mut result = Ok(());
+        while let Some(timer_id) = self.timers.get_triggered_timer() {
+            let dispatched = self.dispatch_single_event_with(FsmEvent::Timer(timer_id), None);
+            if result.is_ok() {
+                result = dispatched;
+            }
+        }
+
+        #[cfg(feature = "std")]
+        self.release_scheduled_events()?;
+
+        self.run_queue()?;
+        result
+    }
+
+    /// Enqueue the event once the delay has passed on the timers' clock. See `FsmTimerRequests::enqueue_after`
+    /// for scheduling the events from the actions.
+    #[cfg(feature = "std")]
+    pub fn enqueue_after<E>(&mut self, event: E, delay: Duration) -> FsmResult<FsmScheduledEventId>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let now = self.timers.now().ok_or(FsmTimerError::NotSupported)?;
+        Ok(self.backend.timer_requests.schedule_at(event.into(), now + delay))
+    }
+
+    /// Cancel the event that was scheduled with `enqueue_after`. Returns `false` if it was already released.
+    #[cfg(feature = "std")]
+    pub fn cancel_scheduled(&mut self, id: FsmScheduledEventId) -> bool {
+        self.backend.timer_requests.cancel_scheduled(id)
+    }
+
+    /// For how long the earliest scheduled event still waits, zero if it is already due. `None` if no events are
+    /// scheduled or the timers don't have a clock. The scheduled events are released by `dispatch_timer_events`,
+    /// the drivers wait for this long along with the timers, see `TimersTokio`.
+    #[cfg(feature = "std")]
+    pub fn next_scheduled_in(&self) -> Option<Duration> {
+        let due = self.backend.timer_requests.next_scheduled_at()?;
+        let now = self.timers.now()?;
+        Some(due.checked_sub(now).unwrap_or_default())
+    }
+
+    /// Move the scheduled events that are due into the queue.
+    #[cfg(feature = "std")]
+    fn release_scheduled_events(&mut self) -> FsmResult<()> {
+        if let Some(now) = self.timers.now() {
+            while let Some(ev) = self.backend.timer_requests.take_due(now) {
+                self.enqueue(ev)?;
             }
         }
 
-        self.dispatch_queue()
+        Ok(())
     }
 
     /// Dispatch this event and run it to completition.
@@ -99,29 +295,323 @@ impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
     {
         let ev = event.into();
         let ev = FsmEvent::Event(ev);
-        Self::dispatch_single_event(self, ev)?;
+        self.begin_dispatch();
+        self.dispatch_single_event_with(ev, None)?;
+
+        self.run_queue()
+    }
+
+    /// Enqueue the event, to be dispatched with the queue. A full queue is reported to the inspector. The
+    /// rejected event is returned as `FsmError::QueueFull`, the queues that drop their oldest event instead
+    /// accept the new one.
+    pub fn enqueue<E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        crate::enqueue_inspected::<F, _, _, _>(&mut self.queue, &self.inspect, event)
+    }
+
+    /// Dispatch the event like `dispatch` if the machine has no pending events. Otherwise the machine is busy,
+    /// the event is enqueued behind the pending ones and the queue is run to completition. A full queue rejects
+    /// the event with `FsmError::QueueFull`, reported to the inspector, and nothing is dispatched, so the
+    /// producers can back off and try again later.
+    pub fn try_dispatch<E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        if self.queue.len() == 0 {
+            return self.dispatch(event);
+        }
+
+        self.enqueue(event)?;
+        self.begin_dispatch();
+        self.run_queue()
+    }
+
+    /// A cloneable handle for enqueueing the events from other threads, see `FsmEventSender`.
+    pub fn event_sender(&self) -> FsmEventSender<F, Q> where Q: Clone {
+        FsmEventSender::new(&self.queue)
+    }
+
+    /// Dispatch the triggered timers and the events that were enqueued by the event senders, until completition.
+    pub fn process(&mut self) -> FsmResult<()> {
+        self.dispatch_timer_events()
+    }
+
+    /// Dispatch a batch of events in their order, then run the queue to completition once. The events that
+    /// were enqueued by the actions are dispatched after the entire batch. Returns the result of every event
+    /// of the batch, collected into any `Default + Extend` collection, like a `Vec`.
+    pub fn dispatch_all<It, E, R>(&mut self, events: It) -> R
+        where It: IntoIterator<Item = E>, E: Into<<F as FsmBackend>::Events>, R: Default + Extend<FsmResult<()>>
+    {
+        self.begin_dispatch();
+
+        let mut results = R::default();
+        for event in events {
+            let result = self.dispatch_single_event_with(FsmEvent::Event(event.into()), None);
+            results.extend(core::iter::once(result));
+        }
+
+        let _ = self.run_queue();
+
+        results
+    }
 
-        self.dispatch_queue()
+    /// Dispatch only this event, the events it enqueues are left in the queue. See `process_one`.
+    pub fn dispatch_single<E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        self.dispatch_single_event(FsmEvent::Event(event.into()))
+    }
+
+    /// Dispatch a single step: the oldest triggered timer or, if none were triggered, the oldest queued
+    /// event, after the scheduled events that are due were enqueued. The events it enqueues are left in the queue. Returns `false` if there was nothing to dispatch,
+    /// and the dispatch's error if the event wasn't handled, in which case the event is consumed.
+    pub fn process_one(&mut self) -> FsmResult<bool> {
+        if let Some(timer_id) = self.timers.get_triggered_timer() {
+            self.dispatch_single_event(FsmEvent::Timer(timer_id))?;
+            return Ok(true);
+        }
+
+        #[cfg(feature = "std")]
+        self.release_scheduled_events()?;
+
+        match self.queue.dequeue() {
+            Some(ev) => {
+                self.dispatch_single_event(FsmEvent::Event(ev))?;
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+
+    /// The number of the events in the queue, waiting to be dispatched.
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// The queue of the events waiting to be dispatched, see `FsmEventQueueIter` for inspecting them.
+    pub fn queue(&self) -> &Q {
+        &self.queue
+    }
+
+    /// The queue of the events waiting to be dispatched, to drop the stale events with `clear` or `retain`.
+    pub fn queue_mut(&mut self) -> &mut Q {
+        &mut self.queue
+    }
+
+    /// Take the commands that the actions pushed into `EventContext::outputs`, in their order.
+    pub fn drain_outputs(&mut self) -> FsmOutputsDrain<'_, F> {
+        self.backend.outputs.drain()
+    }
+
+    /// Dispatch this event and run it to completition, lending the resources to the guards and actions
+    /// of this event and of the events that it enqueues, as `EventContext::resources`. The machines that
+    /// declare their resources can't have submachines, they wouldn't receive them.
+    pub fn dispatch_with<E>(&mut self, resources: &mut <F as FsmBackend>::Resources, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        self.begin_dispatch();
+        self.dispatch_single_event_with(FsmEvent::Event(event.into()), Some(&mut *resources))?;
+
+        while let Some(ev) = self.queue.dequeue() {
+            let _ = self.dispatch_single_event_with(FsmEvent::Event(ev), Some(&mut *resources));
+        }
+
+        Ok(())
     }
 
     /// Dispatch only this event, do not run it to completition.
     pub fn dispatch_single_event(&mut self, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>) -> FsmResult<()> {
+        self.begin_dispatch();
+        self.dispatch_single_event_with(event, None)
+    }
+
+    fn dispatch_single_event_with(&mut self, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, resources: Option<&mut <F as FsmBackend>::Resources>) -> FsmResult<()> {
         let dispatch_ctx = DispatchContext {
             backend: &mut self.backend,
             inspect: &mut self.inspect,
             queue: &mut self.queue,
-            timers: &mut self.timers
+            timers: &mut self.timers,
+            resources
         };
 
-        F::dispatch_event(dispatch_ctx, event)
+        crate::dispatch_with_deferred(dispatch_ctx, event)
     }
 
     /// Dispatch the entire event queue and run it to completition.
     pub fn dispatch_queue(&mut self) -> FsmResult<()> {
+        self.begin_dispatch();
+        self.run_queue()
+    }
+
+    /// Dispatch the queue as a part of the current top-level dispatch.
+    fn run_queue(&mut self) -> FsmResult<()> {
         while let Some(ev) = self.queue.dequeue() {
-            let ev: <F as FsmBackend>::Events = ev.into();
             // todo: log?
-            let _ = Self::dispatch(self, ev);
+            let _ = self.dispatch_single_event_with(FsmEvent::Event(ev), None);
+        }
+
+        Ok(())
+    }
+
+    /// The total time spent in the state, including the current visit if the state is active.
+    /// Measured with the timers' clock, `None` if the timers don't have one.
+    pub fn time_in_state<S>(&self) -> Option<Duration>
+        where S: FsmState<F>, <F as FsmBackend>::States: AsMut<S>
+    {
+        let now = self.timers.now()?;
+        let kind = <S>::fsm_state();
+        let requests = &self.backend.timer_requests;
+
+        let current = self.backend.current_states.as_ref().iter().enumerate()
+            .find(|(_, s)| **s == FsmCurrentState::State(kind))
+            .and_then(|(region, _)| requests.state_entered_at(region))
+            .map(|entered_at| now.checked_sub(entered_at).unwrap_or_default())
+            .unwrap_or_default();
+
+        Some(requests.state_total_time(kind) + current)
+    }
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmBackendBorrowed, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// Dispatch an event that borrows its data and run the queue to completition. The borrowed event
+    /// only lives for this call, the events that its actions enqueue are owned. Fails with `FsmError::NotSupported`
+    /// while the machine has any middlewares, they can't intercept the borrowed events.
+    pub fn dispatch_borrowed<'e, E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackendBorrowed>::BorrowedEvents<'e>>
+    {
+        self.begin_dispatch();
+
+        let dispatch_ctx = DispatchContext {
+            backend: &mut self.backend,
+            inspect: &mut self.inspect,
+            queue: &mut self.queue,
+            timers: &mut self.timers,
+            resources: None
+        };
+
+        crate::dispatch_borrowed_with_deferred(dispatch_ctx, FsmEvent::Event(event.into()))?;
+
+        self.run_queue()
+    }
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmBackendPeek, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// The transition that this event would execute, or `None` if it wouldn't be handled. Only the guards
+    /// are evaluated, the actions aren't executed and the states aren't changed. See `FsmBackendPeek`.
+    pub fn peek_dispatch<E>(&mut self, event: &E) -> FsmResult<Option<&'static FsmInfoTransition>>
+        where E: Clone + Into<<F as FsmBackend>::Events>
+    {
+        F::peek_event(&mut self.backend, &mut self.queue, &FsmEvent::Event(event.clone().into()))
+    }
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmBackendAsync, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// Start the FSM using the async dispatch path, awaiting the initial state's entry actions.
+    pub async fn start_async(&mut self) -> FsmResult<()> {
+        self.dispatch_single_event_async(FsmEvent::Start).await
+    }
+
+    /// Dispatch any pending timer events into the queue, then run all the events from the queue until
+    /// completition, using the async dispatch path. Returns the first error like `dispatch_timer_events`.
+    pub async fn dispatch_timer_events_async(&mut self) -> FsmResult<()> {
+        self.begin_dispatch();
+
+        let mut result = Ok(());
+        while let Some(timer_id) = self.timers.get_triggered_timer() {
+            let dispatched = self.dispatch_event_async(FsmEvent::Timer(timer_id)).await;
+            if result.is_ok() {
+                result = dispatched;
+            }
+        }
+
+        #[cfg(feature = "std")]
+        self.release_scheduled_events()?;
+
+        self.run_queue_async().await?;
+        result
+    }
+
+    /// Dispatch this event and run it to completition, awaiting all the async actions and guards.
+    pub async fn dispatch_async<E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let ev = event.into();
+        let ev = FsmEvent::Event(ev);
+        self.begin_dispatch();
+        self.dispatch_event_async(ev).await?;
+
+        self.run_queue_async().await
+    }
+
+    /// Dispatch a batch of events using the async dispatch path, then run the queue to completition once.
+    pub async fn dispatch_all_async<It, E, R>(&mut self, events: It) -> R
+        where It: IntoIterator<Item = E>, E: Into<<F as FsmBackend>::Events>, R: Default + Extend<FsmResult<()>>
+    {
+        self.begin_dispatch();
+
+        let mut results = R::default();
+        for event in events {
+            let result = self.dispatch_event_async(FsmEvent::Event(event.into())).await;
+            results.extend(core::iter::once(result));
+        }
+
+        let _ = self.run_queue_async().await;
+
+        results
+    }
+
+    /// Dispatch a single step using the async dispatch path, see `process_one`.
+    pub async fn process_one_async(&mut self) -> FsmResult<bool> {
+        if let Some(timer_id) = self.timers.get_triggered_timer() {
+            self.dispatch_single_event_async(FsmEvent::Timer(timer_id)).await?;
+            return Ok(true);
+        }
+
+        #[cfg(feature = "std")]
+        self.release_scheduled_events()?;
+
+        match self.queue.dequeue() {
+            Some(ev) => {
+                self.dispatch_single_event_async(FsmEvent::Event(ev)).await?;
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+
+    /// Dispatch only this event using the async dispatch path, do not run it to completition.
+    pub async fn dispatch_single_event_async(&mut self, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>) -> FsmResult<()> {
+        self.begin_dispatch();
+        self.dispatch_event_async(event).await
+    }
+
+    async fn dispatch_event_async(&mut self, event: FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>) -> FsmResult<()> {
+        let dispatch_ctx = DispatchContext {
+            backend: &mut self.backend,
+            inspect: &mut self.inspect,
+            queue: &mut self.queue,
+            timers: &mut self.timers,
+            resources: None
+        };
+
+        crate::dispatch_with_deferred_async(dispatch_ctx, event).await
+    }
+
+    /// Dispatch the entire event queue and run it to completition, using the async dispatch path.
+    pub async fn dispatch_queue_async(&mut self) -> FsmResult<()> {
+        self.begin_dispatch();
+        self.run_queue_async().await
+    }
+
+    async fn run_queue_async(&mut self) -> FsmResult<()> {
+        while let Some(ev) = self.queue.dequeue() {
+            let _ = self.dispatch_event_async(FsmEvent::Event(ev)).await;
         }
 
         Ok(())
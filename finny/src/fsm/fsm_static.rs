@@ -0,0 +1,71 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::FsmBackend;
+use crate::spsc::{self, FsmEventQueueSpsc, FsmEventQueueSpscProducer, FsmEventQueueSpscStorage};
+
+/// A `static` place for a value that can only be created at runtime, like the machine's frontend or the ring
+/// buffer of its queue. Initialized once, it hands out the only `&'static mut` reference to the value, for the
+/// RTIC resources and the interrupt handlers, without the heap or a `static mut`. The value is never dropped.
+///
+/// Example : `static FSM: FsmStatic<MyFrontend> = FsmStatic::new(); let fsm = FSM.init(MyFsm::new_with(..)?);`
+pub struct FsmStatic<T> {
+    initialized: AtomicBool,
+    value: UnsafeCell<MaybeUninit<T>>
+}
+
+// the value is only reachable through the single reference handed out by `try_init`
+unsafe impl<T: Send> Sync for FsmStatic<T> {}
+
+impl<T> FsmStatic<T> {
+    pub const fn new() -> Self {
+        Self {
+            initialized: AtomicBool::new(false),
+            value: UnsafeCell::new(MaybeUninit::uninit())
+        }
+    }
+
+    /// Move the value into its place and return the only reference to it. Returns `None` if it was
+    /// already initialized.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_init(&'static self, value: T) -> Option<&'static mut T> {
+        if self.initialized.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+
+        // safe, the flag was only just set, so there are no other references
+        let slot = unsafe { &mut *self.value.get() };
+        Some(slot.write(value))
+    }
+
+    /// Move the value into its place and return the only reference to it. Panics if it was already initialized.
+    #[allow(clippy::mut_from_ref)]
+    pub fn init(&'static self, value: T) -> &'static mut T {
+        match self.try_init(value) {
+            Some(value) => value,
+            None => panic!("The FsmStatic was already initialized.")
+        }
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Default for FsmStatic<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `static` ring buffer of a lock-free queue, see `spsc::split`.
+pub type FsmStaticQueue<F, const N: usize> = FsmStatic<FsmEventQueueSpscStorage<F, N>>;
+
+impl<E, const N: usize> FsmStatic<heapless::spsc::Queue<E, N>> {
+    /// Initialize the ring buffer and split it into the producer, for the tasks and the interrupt handlers,
+    /// and the consumer, the queue of the frontend. Returns `None` if it was already split.
+    pub fn split<F: FsmBackend<Events = E>, const L: usize>(&'static self) -> Option<(FsmEventQueueSpscProducer<'static, F, N>, FsmEventQueueSpsc<'static, F, N, L>)> {
+        self.try_init(heapless::spsc::Queue::new()).map(spsc::split)
+    }
+}
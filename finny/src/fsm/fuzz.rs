@@ -0,0 +1,279 @@
+//! Randomized testing of the machines with generated event sequences, checking the declared invariants after
+//! every dispatched event. The failing sequences are shrunk to a minimal one and can be replayed with their seed.
+
+use crate::{FsmBackend, FsmCurrentState, FsmEventQueue, FsmFrontend, FsmResult, FsmState, FsmTimers, Inspect};
+use std::panic::{self, AssertUnwindSafe};
+use std::fmt;
+
+/// A small, seeded pseudo random number generator. Given to the event generators for the events' fields.
+#[derive(Debug, Clone)]
+pub struct FsmFuzzRng {
+    state: u64
+}
+
+impl FsmFuzzRng {
+    pub fn new(seed: u64) -> Self {
+        FsmFuzzRng { state: seed ^ 0x9E37_79B9_7F4A_7C15 }.or_nonzero()
+    }
+
+    /// The generator would be stuck at the zero state.
+    fn or_nonzero(mut self) -> Self {
+        if self.state == 0 {
+            self.state = 0x2545_F491_4F6C_DD1D;
+        }
+        self
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// A number in `0..bound`, or zero for a zero bound.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.next_u64().checked_rem(bound as u64).unwrap_or(0) as usize
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// A failed run, with the shrunk sequence of the events that reproduces it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmFuzzFailure {
+    /// The seed of the failed run.
+    pub seed: u64,
+    /// The names of the dispatched events, the last one caused the failure.
+    pub events: Vec<String>,
+    pub reason: String
+}
+
+impl fmt::Display for FsmFuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (seed {}, events [{}])", self.reason, self.seed, self.events.join(", "))
+    }
+}
+
+type FsmFuzzGenerator<F> = Box<dyn Fn(&mut FsmFuzzRng) -> <F as FsmBackend>::Events>;
+type FsmFuzzInvariant<F, Q, I, T> = (&'static str, Box<dyn Fn(&FsmFrontend<F, Q, I, T>) -> bool>);
+
+/// Dispatches random sequences of the registered events to fresh machines and checks the invariants after
+/// every event. The unhandled events are part of the exploration and aren't failures, unless the dispatch
+/// errors are declared as failures with `fail_on_error`. A panic in any of the machine's closures is a failure.
+///
+/// Example : `FsmFuzz::new(|| Door::new(DoorContext::default())).event(|_| Push).invariant::<Open>("unlocked", |fsm| !fsm.locked).check()`
+pub struct FsmFuzz<F, Q, I, T, N>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, N: Fn() -> FsmResult<FsmFrontend<F, Q, I, T>>
+{
+    new_fsm: N,
+    events: Vec<FsmFuzzGenerator<F>>,
+    invariants: Vec<FsmFuzzInvariant<F, Q, I, T>>,
+    seed: u64,
+    runs: usize,
+    length: usize,
+    fail_on_error: bool
+}
+
+/// One event of a generated sequence, generated again from its own seed when the sequence is replayed.
+#[derive(Debug, Clone, Copy)]
+struct FsmFuzzStep {
+    generator: usize,
+    seed: u64
+}
+
+impl<F, Q, I, T, N> FsmFuzz<F, Q, I, T, N>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, N: Fn() -> FsmResult<FsmFrontend<F, Q, I, T>>
+{
+    /// The machines are created with this closure, one for every run. They are started by the fuzzer.
+    pub fn new(new_fsm: N) -> Self {
+        FsmFuzz {
+            new_fsm,
+            events: vec![],
+            invariants: vec![],
+            seed: 0,
+            runs: 100,
+            length: 32,
+            fail_on_error: false
+        }
+    }
+
+    /// Adds an event to the generated sequences.
+    pub fn event<E, G>(mut self, generate: G) -> Self
+        where E: Into<<F as FsmBackend>::Events>, G: Fn(&mut FsmFuzzRng) -> E + 'static
+    {
+        self.events.push(Box::new(move |rng| generate(rng).into()));
+        self
+    }
+
+    /// Adds all the events of the machine to the generated sequences, generated by their `Arbitrary`
+    /// implementation from the seeded bytes, see `events_arbitrary` in the builder. The events that can't
+    /// be generated from the bytes fail the run.
+    #[cfg(feature = "fuzz_arbitrary")]
+    pub fn arbitrary_events(self) -> Self
+        where <F as FsmBackend>::Events: for<'a> arbitrary::Arbitrary<'a>
+    {
+        self.event(|rng| {
+            let mut bytes = [0; 256];
+            rng.fill_bytes(&mut bytes);
+            let mut data = arbitrary::Unstructured::new(&bytes);
+            match <<F as FsmBackend>::Events as arbitrary::Arbitrary>::arbitrary(&mut data) {
+                Ok(event) => event,
+                Err(e) => panic!("Failed to generate the event: {}", e)
+            }
+        })
+    }
+
+    /// An invariant that has to hold after every event while the state is active.
+    pub fn invariant<S>(mut self, name: &'static str, check: impl Fn(&FsmFrontend<F, Q, I, T>) -> bool + 'static) -> Self
+        where S: FsmState<F>
+    {
+        let state = FsmCurrentState::State(<S>::fsm_state());
+        self.invariants.push((name, Box::new(move |fsm| {
+            !fsm.get_current_states().as_ref().contains(&state) || check(fsm)
+        })));
+        self
+    }
+
+    /// An invariant that has to hold after every event, regardless of the current states.
+    pub fn invariant_all<C>(mut self, name: &'static str, check: C) -> Self
+        where C: Fn(&FsmFrontend<F, Q, I, T>) -> bool + 'static
+    {
+        self.invariants.push((name, Box::new(check)));
+        self
+    }
+
+    /// The seed of the first run. The following runs use the consecutive seeds.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// How many sequences are dispatched.
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// The number of events in each sequence.
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Treat the failed dispatches, including the unhandled events, as failures.
+    pub fn fail_on_error(mut self) -> Self {
+        self.fail_on_error = true;
+        self
+    }
+
+    fn generate(&self, seed: u64) -> Vec<FsmFuzzStep> {
+        let mut rng = FsmFuzzRng::new(seed);
+        (0..self.length).map(|_| FsmFuzzStep {
+            generator: rng.below(self.events.len()),
+            seed: rng.next_u64()
+        }).collect()
+    }
+
+    /// Dispatch the sequence to a new machine. Returns the dispatched events and the reason of the failure.
+    fn execute(&self, steps: &[FsmFuzzStep]) -> Result<(), (Vec<String>, String)> {
+        let mut dispatched = vec![];
+        let r = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+            let mut fsm = (self.new_fsm)().map_err(|e| format!("Failed to create the machine: {:?}", e))?;
+            fsm.start().map_err(|e| format!("Failed to start the machine: {:?}", e))?;
+            self.check_invariants(&fsm)?;
+
+            for step in steps {
+                let mut rng = FsmFuzzRng::new(step.seed);
+                let event = (self.events[step.generator])(&mut rng);
+                dispatched.push(event.as_ref().to_string());
+
+                let result = fsm.dispatch(event);
+                if let (true, Err(e)) = (self.fail_on_error, result) {
+                    return Err(format!("The dispatch failed: {:?}", e));
+                }
+                self.check_invariants(&fsm)?;
+            }
+
+            Ok(())
+        }));
+
+        match r {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(reason)) => Err((dispatched, reason)),
+            Err(panic) => {
+                let msg = panic.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Err((dispatched, format!("Panicked: {}", msg)))
+            }
+        }
+    }
+
+    fn check_invariants(&self, fsm: &FsmFrontend<F, Q, I, T>) -> Result<(), String> {
+        match self.invariants.iter().find(|(_, check)| !check(fsm)) {
+            Some((name, _)) => Err(format!("The invariant '{}' doesn't hold in the states {:?}", name, fsm.get_current_states())),
+            None => Ok(())
+        }
+    }
+
+    /// Remove the events from the failing sequence, one at a time, until none of them can be removed
+    /// without the sequence passing.
+    fn shrink(&self, mut steps: Vec<FsmFuzzStep>) -> Vec<FsmFuzzStep> {
+        let mut shrunk = true;
+        while shrunk {
+            shrunk = false;
+            let mut idx = steps.len();
+            while idx > 0 {
+                idx -= 1;
+                let mut candidate = steps.clone();
+                candidate.remove(idx);
+                if self.execute(&candidate).is_err() {
+                    steps = candidate;
+                    shrunk = true;
+                }
+            }
+        }
+        steps
+    }
+
+    /// Dispatch all the runs, stopping at the first failure.
+    pub fn run(&self) -> Result<(), FsmFuzzFailure> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+
+        for run in 0..self.runs {
+            let seed = self.seed.wrapping_add(run as u64);
+            let steps = self.generate(seed);
+            if let Err(failure) = self.execute(&steps) {
+                let shrunk = self.shrink(steps);
+                let (events, reason) = self.execute(&shrunk).err().unwrap_or(failure);
+                return Err(FsmFuzzFailure { seed, events, reason });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dispatch all the runs and panic with the minimal failing sequence, for use in the tests.
+    pub fn check(&self) {
+        if let Err(failure) = self.run() {
+            panic!("The machine failed the fuzzing: {}", failure);
+        }
+    }
+}
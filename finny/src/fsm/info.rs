@@ -0,0 +1,98 @@
+//! A static description of the machine's structure, generated by the procedural macro. Returned by the
+//! generated `fsm_info()` function of every machine, to be used by tooling without parsing the builder.
+
+/// The description of the machine.
+#[derive(Debug, Clone, Copy)]
+pub struct FsmInfo {
+    pub fsm_id: &'static str,
+    pub context_id: &'static str,
+    /// All of the events that the machine accepts, including the ones only handled by the submachines. The
+    /// events are referred to by their names, declared with `event_name` or the names of their types.
+    pub events: &'static [&'static str],
+    pub regions: &'static [FsmInfoRegion]
+}
+
+/// An orthogonal region of the machine, with its own current state.
+#[derive(Debug, Clone, Copy)]
+pub struct FsmInfoRegion {
+    pub region_id: usize,
+    /// The regions with a higher priority see the dispatched events first, see `FsmBuilder::region_priority`.
+    pub priority: u8,
+    pub initial_state: &'static str,
+    pub states: &'static [FsmInfoState],
+    pub transitions: &'static [FsmInfoTransition]
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FsmInfoState {
+    pub state_id: &'static str,
+    /// The name declared with `named`, or the `state_id`.
+    pub name: &'static str,
+    pub kind: FsmInfoStateKind,
+    pub timers: &'static [FsmInfoTimer],
+    pub deferred_events: &'static [&'static str],
+    pub is_final: bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmInfoStateKind {
+    State,
+    /// A submachine state. Its own structure is described by the submachine's `fsm_info()`.
+    SubMachine { fsm_id: &'static str }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FsmInfoTimer {
+    pub timer_id: &'static str
+}
+
+/// A transition, in the order in which the transitions for the event are evaluated.
+#[derive(Debug, Clone, Copy)]
+pub struct FsmInfoTransition {
+    pub transition_id: &'static str,
+    pub event: FsmInfoEvent,
+    pub kind: FsmInfoTransitionKind,
+    /// The name of the guard's function, or the source of its closure, if the transition is guarded. The
+    /// combined guards are named by the expression of their terms, like `!overloaded && door_closed`.
+    pub guard: Option<&'static str>,
+    /// The terms of a guard combined with `and_guard`, `or_guard` and `not`.
+    pub guard_expr: Option<FsmInfoGuard>,
+    /// The name of the action's function, or the source of its closure.
+    pub action: Option<&'static str>
+}
+
+/// A combined guard, evaluated from the left with short-circuiting. The terms are named after the guard
+/// functions, the closures after their position in the expression, like `guard_2`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmInfoGuard {
+    Guard(&'static str),
+    Not(&'static FsmInfoGuard),
+    And(&'static FsmInfoGuard, &'static FsmInfoGuard),
+    Or(&'static FsmInfoGuard, &'static FsmInfoGuard)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmInfoEvent {
+    Start,
+    Stop,
+    Event(&'static str)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmInfoTransitionKind {
+    SelfTransition { state_id: &'static str },
+    InternalTransition { state_id: &'static str },
+    NormalTransition { from_state: &'static str, to_state: &'static str }
+}
+
+impl FsmInfo {
+    /// Find the state with this id in any of the regions.
+    pub fn get_state(&self, state_id: &str) -> Option<&'static FsmInfoState> {
+        self.regions.iter().flat_map(|r| r.states.iter()).find(|s| s.state_id == state_id)
+    }
+
+    /// All the transitions of the machine, region by region.
+    pub fn transitions(&self) -> impl Iterator<Item = &'static FsmInfoTransition> {
+        self.regions.iter().flat_map(|r| r.transitions.iter())
+    }
+}
@@ -1,7 +1,8 @@
 use core::fmt::Debug;
 use core::any::Any;
+use core::time::Duration;
 
-use crate::{FsmBackend, FsmBackendImpl, FsmEvent};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped};
 
 #[derive(Debug, Clone)]
 pub enum InspectFsmEvent<S> where S: Debug + Clone {
@@ -12,21 +13,58 @@ pub enum InspectFsmEvent<S> where S: Debug + Clone {
 pub trait Inspect: InspectEvent {
     
     fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self;
+    /// The dispatch of an event that borrows its data, see `FsmBackendBorrowed`. Only its name is known.
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm: &FsmBackendImpl<F>) -> Self;
     fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>);
 
     fn for_transition<T>(&self) -> Self;
     fn for_sub_machine<FSub: FsmBackend>(&self) -> Self;
     fn for_timer<F>(&self, timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend;
 
-    fn on_guard<T>(&self, guard_result: bool);
-    fn on_state_enter<S>(&self);
-    fn on_state_exit<S>(&self);
-    fn on_action<S>(&self);
+    /// The guard is named after the function that was given to the transitions, otherwise after the transition's type.
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool);
+    /// The state is named with `named` in the builder, otherwise after its type, see `FsmState::fsm_state_name`.
+    fn on_state_enter<S>(&self, state: &'static str);
+    fn on_state_exit<S>(&self, state: &'static str);
+    /// The state was exited after being active for this long, as measured by the timers' clock.
+    /// Not called if the timers don't have a clock.
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration);
+    /// The action of a transition, named like the guards.
+    fn on_action<S>(&self, action: &'static str);
+    /// The action returned, whether or not it failed. Follows every `on_action`.
+    fn on_action_done<S>(&self, action: &'static str);
+    /// None of the regions had a transition for the dispatched event.
+    fn on_unhandled_event(&self);
+    /// The machine's event queue was full when the machine enqueued an event, like the events of the timers
+    /// or of `FsmFrontend::enqueue`. See `FsmError::QueueFull`.
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped);
 
     fn on_error<E>(&self, msg: &str, error: &E) where E: core::fmt::Debug;
     fn info(&self, msg: &str);    
 }
 
+/// The name of the type without its module path, the type arguments are kept. Used by the inspectors
+/// for the machines, transitions and states that aren't named otherwise.
+///
+/// Example : `short_type_name::<app::Door<app::Wood>>()` is `Door<app::Wood>`
+pub fn short_type_name<T: ?Sized>() -> &'static str {
+    let ty = core::any::type_name::<T>();
+    let path_end = ty.find('<').unwrap_or(ty.len());
+    match ty[..path_end].rfind("::") {
+        Some(i) => &ty[i + 2..],
+        None => ty
+    }
+}
+
+/// The identifier of the type's name, without its module path and its type arguments. Used to match the
+/// type names with the identifiers in the generated descriptions of the machines, `FsmInfo`.
+///
+/// Example : `type_name_ident("app::Door<app::Wood>")` is `Door`
+pub fn type_name_ident(ty: &str) -> &str {
+    let ty = ty.split('<').next().unwrap_or(ty).trim();
+    ty.rsplit("::").next().unwrap_or(ty).trim()
+}
+
 pub trait InspectEvent {
     fn on_event<S: Any + Debug + Clone>(&self, event: &InspectFsmEvent<S>);
 }
\ No newline at end of file
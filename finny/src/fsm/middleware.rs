@@ -0,0 +1,81 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmDispatchResult, FsmEvent, FsmResult};
+
+type FsmMiddlewareEvent<F> = FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>;
+
+/// Wraps the dispatch of every event, including the queued events and the timers, for the behavior that cuts
+/// across the machine, like authenticating the events or rate limiting them. The middlewares are called in
+/// the order in which they were added, each one receives the event returned by the previous one. The released
+/// deferred events aren't intercepted, and the borrowed events are refused while any middlewares are registered.
+pub trait FsmMiddleware<F: FsmBackend> {
+    /// Called before the event is dispatched. Return the event, possibly replaced by another one, to dispatch
+    /// it, `None` to drop it silently, or an error to fail its dispatch, like `FsmError::EventRejected`.
+    fn before_dispatch(&mut self, event: FsmMiddlewareEvent<F>, backend: &FsmBackendImpl<F>) -> FsmResult<Option<FsmMiddlewareEvent<F>>>;
+
+    /// Called after the event was dispatched, with the result of its dispatch.
+    fn after_dispatch(&mut self, _result: &FsmDispatchResult, _backend: &FsmBackendImpl<F>) {
+
+    }
+}
+
+/// The middlewares of the machine, see `FsmMiddleware`. Like the observers, they can be added at runtime.
+pub struct FsmMiddlewares<F: FsmBackend> {
+    middlewares: Vec<Box<dyn FsmMiddleware<F> + Send + Sync>>
+}
+
+impl<F: FsmBackend> FsmMiddlewares<F> {
+    pub fn new() -> Self {
+        FsmMiddlewares {
+            middlewares: Vec::new()
+        }
+    }
+
+    pub fn add<M>(&mut self, middleware: M) where M: FsmMiddleware<F> + Send + Sync + 'static {
+        self.middlewares.push(Box::new(middleware));
+    }
+
+    pub fn clear(&mut self) {
+        self.middlewares.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.middlewares.is_empty()
+    }
+
+    /// Pass the event through all of the middlewares. They are taken out of the backend for the duration
+    /// of the call, so they can read it.
+    pub(crate) fn before_dispatch(backend: &mut FsmBackendImpl<F>, event: FsmMiddlewareEvent<F>) -> FsmResult<Option<FsmMiddlewareEvent<F>>> {
+        if backend.middlewares.is_empty() {
+            return Ok(Some(event));
+        }
+
+        let mut middlewares = core::mem::take(&mut backend.middlewares);
+        let mut result = Ok(Some(event));
+        for middleware in middlewares.middlewares.iter_mut() {
+            result = match result {
+                Ok(Some(event)) => middleware.before_dispatch(event, backend),
+                _ => break
+            };
+        }
+        backend.middlewares = middlewares;
+
+        result
+    }
+
+    pub(crate) fn after_dispatch(backend: &mut FsmBackendImpl<F>, result: &FsmDispatchResult) {
+        if backend.middlewares.is_empty() {
+            return;
+        }
+
+        let mut middlewares = core::mem::take(&mut backend.middlewares);
+        for middleware in middlewares.middlewares.iter_mut() {
+            middleware.after_dispatch(result, backend);
+        }
+        backend.middlewares = middlewares;
+    }
+}
+
+impl<F: FsmBackend> Default for FsmMiddlewares<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,7 @@
 //! The public Finite State Machine traits. The derive macros will implement these for your particular
 //! state machines.
 
+mod error;
 mod events;
 mod fsm_impl;
 mod fsm_factory;
@@ -11,7 +12,34 @@ mod tests_fsm;
 mod dispatch;
 mod timers;
 mod inspect;
+mod deferred;
+mod outputs;
+mod timer_requests;
+mod info;
+mod fsm_static;
+mod supervisor;
+#[cfg(feature = "alloc")]
+mod ffi;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "std")]
+mod observers;
+#[cfg(feature = "std")]
+mod middleware;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "std")]
+mod runner;
+#[cfg(feature = "timers_tokio")]
+mod runner_tokio;
+#[cfg(feature = "analysis")]
+mod analysis;
+#[cfg(feature = "fuzz")]
+mod fuzz;
 
+pub use self::error::*;
 pub use self::events::*;
 pub use self::fsm_factory::*;
 pub use self::fsm_impl::*;
@@ -21,20 +49,35 @@ pub use self::transitions::*;
 pub use self::inspect::*;
 pub use self::dispatch::*;
 pub use self::timers::*;
+pub use self::deferred::*;
+pub use self::outputs::*;
+pub use self::timer_requests::*;
+pub use self::info::*;
+pub use self::fsm_static::*;
+pub use self::supervisor::*;
+#[cfg(feature = "alloc")]
+pub use self::ffi::*;
+#[cfg(feature = "serde")]
+pub use self::snapshot::*;
+#[cfg(feature = "futures")]
+pub use self::stream::*;
+#[cfg(feature = "std")]
+pub use self::observers::*;
+#[cfg(feature = "std")]
+pub use self::middleware::*;
+#[cfg(feature = "std")]
+pub use self::pool::*;
+#[cfg(feature = "std")]
+pub use self::runner::*;
+#[cfg(feature = "timers_tokio")]
+pub use self::runner_tokio::*;
+#[cfg(feature = "analysis")]
+pub use self::analysis::*;
+#[cfg(feature = "fuzz")]
+pub use self::fuzz::*;
 
 use crate::lib::*;
 
-pub type FsmResult<T> = Result<T, FsmError>;
-
-/// The lib-level error type.
-#[derive(Debug, PartialEq)]
-pub enum FsmError {
-    NoTransition,
-    QueueOverCapacity,
-    NotSupported,
-    TimerNotStarted
-}
-
 pub type FsmDispatchResult = FsmResult<()>;
 
 /// Finite State Machine backend. Handles the dispatching, the types are
@@ -42,16 +85,97 @@ pub type FsmDispatchResult = FsmResult<()>;
 pub trait FsmBackend where Self: Sized + Debug {
     /// The machine's context that is shared between its constructors and actions.
     type Context;
+    /// The external resources, like peripherals or connections, that are lent to the guards and actions
+    /// for the duration of a dispatch instead of being owned by the context. Declared with `fsm.resources`.
+    type Resources;
+    /// The commands that the actions produce for the caller, drained after the dispatch. Declared with
+    /// `fsm.outputs`, `()` if not declared.
+    type Outputs;
     /// The type that holds the states of the machine.
     type States: FsmStates<Self>;
     /// A tagged union type with all the supported events. This type has to support cloning to facilitate
     /// the dispatch into sub-machines and into multiple regions.
-    type Events: AsRef<str> + Clone;
+    type Events: AsRef<str> + FsmEventTraceContext + 'static;
     /// An enum with variants for all the possible timer instances, with support for submachines.
-    type Timers: Debug + Clone + PartialEq + AllVariants;
+    type Timers: Debug + Clone + PartialEq + AllVariants + 'static;
 
     fn dispatch_event<Q, I, T>(ctx: DispatchContext<Self, Q, I, T>, event: FsmEvent<Self::Events, Self::Timers>) -> FsmDispatchResult
         where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
+
+    /// The static description of the machine, the generated `fsm_info()`. Used by the inspectors that
+    /// label the transitions with their states.
+    fn info() -> Option<&'static FsmInfo> {
+        None
+    }
+}
+
+/// The asynchronous dispatch path of the backend. Awaits the `async` entry, exit, guard and action
+/// closures declared in the builder. Implemented by the code generator for every machine.
+pub trait FsmBackendAsync: FsmBackend {
+    #[allow(async_fn_in_trait)]
+    async fn dispatch_event_async<Q, I, T>(ctx: DispatchContext<'_, '_, '_, Self, Q, I, T>, event: FsmEvent<Self::Events, Self::Timers>) -> FsmDispatchResult
+        where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
+
+    /// Re-enter the previously active states, awaiting the async entry actions.
+    #[allow(async_fn_in_trait)]
+    async fn resume_async<Q, I, T>(ctx: DispatchContext<'_, '_, '_, Self, Q, I, T>, deep: bool) -> FsmDispatchResult
+        where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
+}
+
+/// The dispatch of the events that borrow their data, declared with a lifetime like `on_event::<Packet<'_>>()`.
+/// Implemented by the code generator for the machines with such events.
+pub trait FsmBackendBorrowed: FsmBackend {
+    /// A tagged union of the borrowed events. Unlike `Events`, these can't be queued, deferred or
+    /// dispatched to the submachines, they only live for the duration of their dispatch.
+    type BorrowedEvents<'e>: AsRef<str>;
+
+    fn dispatch_borrowed_event<Q, I, T>(ctx: DispatchContext<Self, Q, I, T>, event: FsmEvent<Self::BorrowedEvents<'_>, Self::Timers>) -> FsmDispatchResult
+        where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
+}
+
+/// Re-enters the previously active states of the machine instead of starting from the initial states.
+/// Used when re-entering a submachine state with history. Implemented by the code generator for every machine.
+pub trait FsmBackendHistory: FsmBackend {
+    /// Executes the entry actions of the last active states. Starts the machine if it was never started.
+    /// With `deep`, the nested submachines are resumed as well, otherwise they follow their own history settings.
+    fn resume<Q, I, T>(ctx: DispatchContext<Self, Q, I, T>, deep: bool) -> FsmDispatchResult
+        where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
+
+    /// The states that are entered through the entry point at this state. Its region is entered at it, the
+    /// other regions at their initial states.
+    fn entry_point_states(state: <Self::States as FsmStates<Self>>::StateKind) -> <Self::States as FsmStates<Self>>::CurrentState;
+}
+
+/// Evaluates the transitions for an event without dispatching it. Implemented by the code generator for every machine.
+pub trait FsmBackendPeek: FsmBackend {
+    /// The transition that would be executed for this event, in the first region by the region priorities that
+    /// handles it. Only the guards are evaluated, the actions aren't executed and the states aren't changed. The
+    /// events handled by a submachine report the submachine's transition. The timers, the deferred events and
+    /// the transitions with only an async guard report no transition.
+    fn peek_event<Q>(backend: &mut FsmBackendImpl<Self>, queue: &mut Q, event: &FsmEvent<Self::Events, Self::Timers>) -> FsmResult<Option<&'static FsmInfoTransition>>
+        where Q: FsmEventQueue<Self>;
+}
+
+/// Writes the names of the current states, see `FsmBackendImpl::current_state_name`. Implemented by the code
+/// generator for every machine.
+pub trait FsmBackendStatePath: FsmBackend {
+    fn write_state_path<W: fmt::Write>(backend: &FsmBackendImpl<Self>, w: &mut W) -> fmt::Result;
+}
+
+/// Cancels the started timers of all the states, including the nested submachines. Called when the parent
+/// machine exits the submachine, so that the timers of its states don't trigger after it was left.
+/// Implemented by the code generator for every machine.
+pub trait FsmBackendCancelTimers: FsmBackend {
+    fn cancel_timers<I, T>(backend: &mut FsmBackendImpl<Self>, inspect: &mut I, timers: &mut T)
+        where I: Inspect, T: FsmTimers<Self>;
+}
+
+/// Exits the current states of all the regions with `FsmEvent::Stop`, the states of the nested submachines
+/// before the submachine itself, and leaves the regions stopped. See `FsmFrontend::stop`. Implemented by the
+/// code generator for every machine.
+pub trait FsmBackendStop: FsmBackend {
+    fn stop_states<Q, I, T>(ctx: DispatchContext<Self, Q, I, T>)
+        where Q: FsmEventQueue<Self>, I: Inspect, T: FsmTimers<Self>;
 }
 
 /// Enumerates all the possible variants of a simple enum.
@@ -0,0 +1,112 @@
+use std::sync::mpsc::{Receiver, channel};
+
+use crate::{FsmBackend, FsmCurrentState, FsmRegionId, FsmStates};
+use crate::lib::*;
+
+type FsmStateKind<F> = <<F as FsmBackend>::States as FsmStates<F>>::StateKind;
+type FsmCurrentStates<F> = <<F as FsmBackend>::States as FsmStates<F>>::CurrentState;
+type FsmObserver<F> = Box<dyn FnMut(&FsmStateChange<F>) + Send + Sync>;
+
+/// A change of the current state of one of the machine's regions.
+pub struct FsmStateChange<F: FsmBackend> {
+    pub region: FsmRegionId,
+    pub from: FsmCurrentState<FsmStateKind<F>>,
+    /// The name of the event that caused the change.
+    pub event: String,
+    pub to: FsmCurrentState<FsmStateKind<F>>
+}
+
+impl<F: FsmBackend> Clone for FsmStateChange<F> {
+    fn clone(&self) -> Self {
+        FsmStateChange {
+            region: self.region,
+            from: self.from,
+            event: self.event.clone(),
+            to: self.to
+        }
+    }
+}
+
+impl<F: FsmBackend> Debug for FsmStateChange<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsmStateChange")
+            .field("region", &self.region)
+            .field("from", &self.from)
+            .field("event", &self.event)
+            .field("to", &self.to)
+            .finish()
+    }
+}
+
+/// The subscribers that are notified about the changes of the current states, after every dispatched event.
+/// Unlike `Inspect`, they can be added at runtime. The internal and the self transitions don't change the
+/// current state, so they aren't reported.
+pub struct FsmObservers<F: FsmBackend> {
+    observers: Vec<FsmObserver<F>>
+}
+
+impl<F: FsmBackend> FsmObservers<F> {
+    pub fn new() -> Self {
+        FsmObservers {
+            observers: Vec::new()
+        }
+    }
+
+    pub fn subscribe<O>(&mut self, observer: O) where O: FnMut(&FsmStateChange<F>) + Send + Sync + 'static {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Receive the changes through a channel, for example on another thread. The changes are dropped
+    /// once the receiver is gone.
+    pub fn subscribe_channel(&mut self) -> Receiver<FsmStateChange<F>> where F: 'static, FsmStateKind<F>: Send {
+        let (sender, receiver) = channel();
+        let sender = std::sync::Mutex::new(sender);
+        self.subscribe(move |change| {
+            if let Ok(sender) = sender.lock() {
+                let _ = sender.send(change.clone());
+            }
+        });
+        receiver
+    }
+
+    pub fn clear(&mut self) {
+        self.observers.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    pub(crate) fn before_dispatch(&self, event: &str, states: FsmCurrentStates<F>) -> Option<(String, FsmCurrentStates<F>)> {
+        if self.observers.is_empty() {
+            None
+        } else {
+            Some((event.to_string(), states))
+        }
+    }
+
+    pub(crate) fn after_dispatch(&mut self, before: Option<(String, FsmCurrentStates<F>)>, states: FsmCurrentStates<F>) {
+        if let Some((event, states_before)) = before {
+            for (region, (from, to)) in states_before.as_ref().iter().zip(states.as_ref().iter()).enumerate() {
+                if from == to { continue; }
+
+                let change = FsmStateChange {
+                    region,
+                    from: *from,
+                    event: event.clone(),
+                    to: *to
+                };
+
+                for observer in self.observers.iter_mut() {
+                    observer(&change);
+                }
+            }
+        }
+    }
+}
+
+impl<F: FsmBackend> Default for FsmObservers<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,95 @@
+use crate::lib::*;
+use crate::{FsmBackend, FsmResult};
+
+#[cfg(not(feature = "std"))]
+use arraydeque::ArrayDeque;
+
+/// The maximum number of outputs that can be stored by a single machine without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub const FSM_OUTPUTS_CAPACITY: usize = 16;
+
+/// The commands that the actions produced for the caller, like the frames to send or the LEDs to set.
+/// The actions only push them, the caller drains them after the dispatch and performs the I/O. The
+/// type of the outputs is declared with `fsm.outputs`, the outputs of a submachine are kept by it.
+pub struct FsmOutputs<F: FsmBackend> {
+    #[cfg(feature = "std")]
+    outputs: VecDeque<<F as FsmBackend>::Outputs>,
+    #[cfg(not(feature = "std"))]
+    outputs: ArrayDeque<[<F as FsmBackend>::Outputs; FSM_OUTPUTS_CAPACITY]>
+}
+
+impl<F: FsmBackend> FsmOutputs<F> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            outputs: VecDeque::new(),
+            #[cfg(not(feature = "std"))]
+            outputs: ArrayDeque::new()
+        }
+    }
+
+    /// Push an output for the caller.
+    #[cfg(feature = "std")]
+    pub fn push<O: Into<<F as FsmBackend>::Outputs>>(&mut self, output: O) -> FsmResult<()> {
+        self.outputs.push_back(output.into());
+        Ok(())
+    }
+
+    /// Push an output for the caller. Fails once the outputs are full.
+    #[cfg(not(feature = "std"))]
+    pub fn push<O: Into<<F as FsmBackend>::Outputs>>(&mut self, output: O) -> FsmResult<()> {
+        self.outputs.push_back(output.into()).map_err(|_| crate::FsmError::QueueOverCapacity { capacity: FSM_OUTPUTS_CAPACITY })
+    }
+
+    /// Take the oldest output.
+    pub fn take(&mut self) -> Option<<F as FsmBackend>::Outputs> {
+        self.outputs.pop_front()
+    }
+
+    /// Take all of the outputs, in the order in which they were pushed.
+    pub fn drain(&mut self) -> FsmOutputsDrain<'_, F> {
+        FsmOutputsDrain { outputs: self }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Outputs> {
+        self.outputs.iter()
+    }
+
+    /// Drop all of the outputs.
+    pub fn clear(&mut self) {
+        self.outputs.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+}
+
+impl<F: FsmBackend> Default for FsmOutputs<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend> Debug for FsmOutputs<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsmOutputs").field("len", &self.outputs.len()).finish()
+    }
+}
+
+/// Takes the outputs, in the order in which they were pushed. See `FsmOutputs::drain`.
+pub struct FsmOutputsDrain<'a, F: FsmBackend> {
+    outputs: &'a mut FsmOutputs<F>
+}
+
+impl<'a, F: FsmBackend> Iterator for FsmOutputsDrain<'a, F> {
+    type Item = <F as FsmBackend>::Outputs;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.outputs.take()
+    }
+}
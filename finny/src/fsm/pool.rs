@@ -0,0 +1,125 @@
+use crate::{FsmBackend, FsmError, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect};
+
+/// The id of a machine in a `FsmPool`. The slots of the despawned machines are reused, the generation
+/// makes sure that the ids of the despawned machines don't refer to the new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FsmPoolId {
+    index: u32,
+    generation: u32
+}
+
+impl FsmPoolId {
+    /// The index of the machine's slot in the pool.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+struct FsmPoolSlot<T> {
+    generation: u32,
+    frontend: Option<T>
+}
+
+/// Many instances of the same machine, stored next to each other in a single allocation, with their
+/// states, contexts and queues. Spawning and despawning a machine is O(1), the slots of the despawned
+/// machines are reused. Build the frontends with `FsmFactory::new_with_capacity` so that even their
+/// queues are stored inline.
+pub struct FsmPool<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    slots: Vec<FsmPoolSlot<FsmFrontend<F, Q, I, T>>>,
+    free: Vec<u32>,
+    len: usize
+}
+
+impl<F, Q, I, T> FsmPool<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// A pool with the room for this many machines, before it has to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        FsmPool {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0
+        }
+    }
+
+    /// Add a machine to the pool. It isn't started.
+    pub fn spawn(&mut self, frontend: FsmFrontend<F, Q, I, T>) -> FsmPoolId {
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.frontend = Some(frontend);
+            return FsmPoolId { index, generation: slot.generation };
+        }
+
+        let index = self.slots.len() as u32;
+        self.slots.push(FsmPoolSlot { generation: 0, frontend: Some(frontend) });
+        FsmPoolId { index, generation: 0 }
+    }
+
+    /// Remove the machine from the pool and return it.
+    pub fn despawn(&mut self, id: FsmPoolId) -> Option<FsmFrontend<F, Q, I, T>> {
+        let slot = self.slots.get_mut(id.index as usize).filter(|s| s.generation == id.generation)?;
+        let frontend = slot.frontend.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(frontend)
+    }
+
+    pub fn get(&self, id: FsmPoolId) -> Option<&FsmFrontend<F, Q, I, T>> {
+        self.slots.get(id.index as usize).filter(|s| s.generation == id.generation)?.frontend.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: FsmPoolId) -> Option<&mut FsmFrontend<F, Q, I, T>> {
+        self.slots.get_mut(id.index as usize).filter(|s| s.generation == id.generation)?.frontend.as_mut()
+    }
+
+    pub fn contains(&self, id: FsmPoolId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Dispatch the event to the machine and run its queue to completion.
+    pub fn dispatch<E>(&mut self, id: FsmPoolId, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        self.get_mut(id).ok_or(FsmError::PoolInstanceNotFound)?.dispatch(event)
+    }
+
+    /// The number of machines in the pool.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The machines in the pool, in the order of their slots.
+    pub fn iter(&self) -> impl Iterator<Item = (FsmPoolId, &FsmFrontend<F, Q, I, T>)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.frontend.as_ref().map(|frontend| (FsmPoolId { index: index as u32, generation: slot.generation }, frontend))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (FsmPoolId, &mut FsmFrontend<F, Q, I, T>)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+            let generation = slot.generation;
+            slot.frontend.as_mut().map(|frontend| (FsmPoolId { index: index as u32, generation }, frontend))
+        })
+    }
+}
+
+impl<F, Q, I, T> Default for FsmPool<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::lib::*;
-use crate::{FsmBackend, FsmResult};
+use crate::{FsmBackend, FsmError, FsmQueueDropped, FsmResult, Inspect};
 
 /// The event queueing trait for FSMs. Can be used from outside or from within the actions of the FSM.
 pub trait FsmEventQueue<F: FsmBackend>: FsmEventQueueSender<F> {
@@ -7,18 +7,73 @@ pub trait FsmEventQueue<F: FsmBackend>: FsmEventQueueSender<F> {
     fn dequeue(&mut self) -> Option<<F as FsmBackend>::Events>;
     /// Number of messages to be dequeued.
     fn len(&self) -> usize;
+
+    /// Drop all the queued events.
+    fn clear(&mut self) {
+        while self.dequeue().is_some() { }
+    }
+
+    /// Drop the events enqueued after the queue had this length. By default, the oldest events are kept.
+    fn truncate(&mut self, len: usize) {
+        let mut kept = 0;
+        self.retain(|_| {
+            kept += 1;
+            kept <= len
+        });
+    }
+
+    /// Keep only the queued events for which the predicate returns true, in their order. By default,
+    /// the kept events are dequeued and enqueued again.
+    fn retain<P: FnMut(&<F as FsmBackend>::Events) -> bool>(&mut self, mut keep: P) {
+        for _ in 0..self.len() {
+            match self.dequeue() {
+                Some(ev) if keep(&ev) => { let _ = self.enqueue(ev); },
+                Some(_) => (),
+                None => break
+            }
+        }
+    }
+
+    /// Whether the queue can be used by the transactional machines, which drop the events of a failed step
+    /// with `truncate`. Checked when the dispatch of a transactional machine is compiled.
+    const TRANSACTIONAL: bool = true;
+}
+
+/// A queue whose events can be inspected without dequeueing them.
+pub trait FsmEventQueueIter<F: FsmBackend>: FsmEventQueue<F> {
+    /// The queued events, in the order in which they will be dequeued.
+    fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Events>;
 }
 
 pub trait FsmEventQueueSender<F: FsmBackend> {
     /// Try to enqueue an event.
     fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()>;
+
+    /// The capacity of the queue and the dropped event, if the last enqueued event was accepted by a full
+    /// queue that dropped the new event or the oldest one. Clears the note, used for reporting the dropped
+    /// event to the inspector.
+    fn take_dropped(&mut self) -> Option<(usize, FsmQueueDropped)> {
+        None
+    }
 }
 
-#[cfg(feature = "std")]
+/// The priority of an event, implemented by the generated events enum. The priorities are declared
+/// with `fsm.event_priority::<TEvent>(priority)`, the undeclared events have the priority of 0.
+pub trait FsmEventPriority {
+    fn priority(&self) -> u8;
+}
+
+#[cfg(feature = "alloc")]
 mod queue_vec {
     use super::*;
 
-    /// An unbound event queue that uses `VecDeque`.
+    /// An unbound event queue that uses `VecDeque`. Available on the `no_std` targets with an allocator
+    /// under the `alloc` feature.
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(bound(
+        serialize = "<F as FsmBackend>::Events: serde::Serialize",
+        deserialize = "<F as FsmBackend>::Events: serde::Deserialize<'de>"
+    )))]
     pub struct FsmEventQueueVec<F: FsmBackend> {
         queue: VecDeque<<F as FsmBackend>::Events>
     }
@@ -40,6 +95,20 @@ mod queue_vec {
         fn len(&self) -> usize {
             self.queue.len()
         }
+
+        fn clear(&mut self) {
+            self.queue.clear();
+        }
+
+        fn retain<P: FnMut(&<F as FsmBackend>::Events) -> bool>(&mut self, keep: P) {
+            self.queue.retain(keep);
+        }
+    }
+
+    impl<F: FsmBackend> FsmEventQueueIter<F> for FsmEventQueueVec<F> {
+        fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Events> {
+            self.queue.iter()
+        }
     }
 
     impl<F: FsmBackend> FsmEventQueueSender<F> for FsmEventQueueVec<F> {
@@ -50,7 +119,7 @@ mod queue_vec {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 pub use self::queue_vec::*;
 
 #[cfg(feature = "std")]
@@ -112,7 +181,7 @@ mod queue_vec_shared {
                 q.push_back(event.into());
                 Ok(())
             } else {
-                Err(FsmError::QueueOverCapacity)
+                Err(FsmError::QueueUnavailable)
             }
         }
     }
@@ -121,6 +190,109 @@ mod queue_vec_shared {
 #[cfg(feature = "std")]
 pub use self::queue_vec_shared::*;
 
+#[cfg(feature = "std")]
+mod queue_priority {
+    use std::collections::BinaryHeap;
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    /// An unbound event queue that dequeues the events with a higher priority first. Events with
+    /// the same priority are dequeued in the order in which they were enqueued.
+    pub struct FsmEventQueuePriority<F: FsmBackend> where <F as FsmBackend>::Events: FsmEventPriority {
+        queue: BinaryHeap<PriorityEvent<<F as FsmBackend>::Events>>,
+        sequence: u64
+    }
+
+    struct PriorityEvent<E> {
+        priority: u8,
+        sequence: u64,
+        event: E
+    }
+
+    impl<E> PartialEq for PriorityEvent<E> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl<E> Eq for PriorityEvent<E> { }
+
+    impl<E> PartialOrd for PriorityEvent<E> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl<E> Ord for PriorityEvent<E> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            // the heap is a max-heap, the older events have to come first
+            self.priority.cmp(&other.priority).then_with(|| other.sequence.cmp(&self.sequence))
+        }
+    }
+
+    impl<F: FsmBackend> FsmEventQueuePriority<F> where <F as FsmBackend>::Events: FsmEventPriority {
+        pub fn new() -> Self {
+            FsmEventQueuePriority {
+                queue: BinaryHeap::new(),
+                sequence: 0
+            }
+        }
+    }
+
+    impl<F: FsmBackend> Default for FsmEventQueuePriority<F> where <F as FsmBackend>::Events: FsmEventPriority {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<F: FsmBackend> FsmEventQueue<F> for FsmEventQueuePriority<F> where <F as FsmBackend>::Events: FsmEventPriority {
+        fn dequeue(&mut self) -> Option<<F as FsmBackend>::Events> {
+            self.queue.pop().map(|e| e.event)
+        }
+
+        fn len(&self) -> usize {
+            self.queue.len()
+        }
+
+        fn clear(&mut self) {
+            self.queue.clear();
+        }
+
+        fn retain<P: FnMut(&<F as FsmBackend>::Events) -> bool>(&mut self, mut keep: P) {
+            self.queue.retain(|e| keep(&e.event));
+        }
+
+        fn truncate(&mut self, len: usize) {
+            // the newest events have the highest sequence numbers
+            let dropped = self.queue.len().saturating_sub(len) as u64;
+            let first_dropped = self.sequence - dropped;
+            self.queue.retain(|e| e.sequence < first_dropped);
+        }
+    }
+
+    impl<F: FsmBackend> FsmEventQueueIter<F> for FsmEventQueuePriority<F> where <F as FsmBackend>::Events: FsmEventPriority {
+        fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Events> {
+            let mut events: Vec<_> = self.queue.iter().collect();
+            events.sort_by(|a, b| b.cmp(a));
+            events.into_iter().map(|e| &e.event)
+        }
+    }
+
+    impl<F: FsmBackend> FsmEventQueueSender<F> for FsmEventQueuePriority<F> where <F as FsmBackend>::Events: FsmEventPriority {
+        fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+            let event = event.into();
+            let priority = event.priority();
+            self.queue.push(PriorityEvent { priority, sequence: self.sequence, event });
+            self.sequence += 1;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::queue_priority::*;
+
 mod queue_array {
     use arraydeque::{Array, ArrayDeque};
 
@@ -155,6 +327,22 @@ mod queue_array {
         fn len(&self) -> usize {
             self.dequeue.len()
         }
+
+        fn clear(&mut self) {
+            self.dequeue.clear();
+        }
+
+        fn retain<P: FnMut(&<F as FsmBackend>::Events) -> bool>(&mut self, keep: P) {
+            self.dequeue.retain(keep);
+        }
+    }
+
+    impl<F, A> FsmEventQueueIter<F> for FsmEventQueueArray<F, A>
+        where F: FsmBackend, A: Array<Item = <F as FsmBackend>::Events>
+    {
+        fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Events> {
+            self.dequeue.iter()
+        }
     }
 
     impl<F, A> FsmEventQueueSender<F> for FsmEventQueueArray<F, A> 
@@ -163,7 +351,7 @@ mod queue_array {
         fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
             match self.dequeue.push_back(event.into()) {
                 Ok(_) => Ok(()),
-                Err(_) => Err(crate::FsmError::QueueOverCapacity)
+                Err(_) => Err(crate::FsmError::QueueFull { capacity: self.dequeue.capacity(), dropped: crate::FsmQueueDropped::Newest })
             }
         }
     }
@@ -171,13 +359,125 @@ mod queue_array {
 
 pub use self::queue_array::*;
 
+mod queue_bounded {
+    use heapless::Deque;
+
+    use crate::{FsmError, FsmQueueDropped};
+
+    use super::*;
+
+    /// What happens when an event is enqueued into a full bounded queue.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub enum FsmQueueOverflowPolicy {
+        /// The new event isn't enqueued, `FsmError::QueueFull` is returned with `FsmQueueDropped::Newest`.
+        Reject,
+        /// The oldest event in the queue is dropped to make room for the new one, which is accepted. The
+        /// dropped event is reported to the inspector with `FsmQueueDropped::Oldest`.
+        DropOldest,
+        /// The new event is quietly dropped and the enqueueing succeeds. The dropped event is reported to the
+        /// inspector with `FsmQueueDropped::Newest`.
+        DropNewest
+    }
+
+    /// A heapless queue with a fixed capacity of `N` events and a configurable overflow policy.
+    pub struct FsmEventQueueBounded<F: FsmBackend, const N: usize> {
+        queue: Deque<<F as FsmBackend>::Events, N>,
+        policy: FsmQueueOverflowPolicy,
+        dropped: usize,
+        last_dropped: Option<FsmQueueDropped>
+    }
+
+    impl<F: FsmBackend, const N: usize> FsmEventQueueBounded<F, N> {
+        /// A queue that rejects the events once it's full.
+        pub fn new() -> Self {
+            Self::with_policy(FsmQueueOverflowPolicy::Reject)
+        }
+
+        pub fn with_policy(policy: FsmQueueOverflowPolicy) -> Self {
+            Self {
+                queue: Deque::new(),
+                policy,
+                dropped: 0,
+                last_dropped: None
+            }
+        }
+
+        pub fn policy(&self) -> FsmQueueOverflowPolicy {
+            self.policy
+        }
+
+        /// The number of events that were dropped or rejected because the queue was full.
+        pub fn dropped(&self) -> usize {
+            self.dropped
+        }
+    }
+
+    impl<F: FsmBackend, const N: usize> Default for FsmEventQueueBounded<F, N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<F: FsmBackend, const N: usize> FsmEventQueue<F> for FsmEventQueueBounded<F, N> {
+        fn dequeue(&mut self) -> Option<<F as FsmBackend>::Events> {
+            self.queue.pop_front()
+        }
+
+        fn len(&self) -> usize {
+            self.queue.len()
+        }
+
+        fn clear(&mut self) {
+            self.queue.clear();
+        }
+    }
+
+    impl<F: FsmBackend, const N: usize> FsmEventQueueIter<F> for FsmEventQueueBounded<F, N> {
+        fn iter(&self) -> impl Iterator<Item = &<F as FsmBackend>::Events> {
+            self.queue.iter()
+        }
+    }
+
+    impl<F: FsmBackend, const N: usize> FsmEventQueueSender<F> for FsmEventQueueBounded<F, N> {
+        fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+            let event = match self.queue.push_back(event.into()) {
+                Ok(_) => return Ok(()),
+                Err(event) => event
+            };
+
+            self.dropped += 1;
+
+            match self.policy {
+                FsmQueueOverflowPolicy::Reject => Err(FsmError::QueueFull { capacity: N, dropped: FsmQueueDropped::Newest }),
+                FsmQueueOverflowPolicy::DropOldest => {
+                    self.queue.pop_front();
+                    // there's room for the event now
+                    let _ = self.queue.push_back(event);
+                    self.last_dropped = Some(FsmQueueDropped::Oldest);
+                    Ok(())
+                },
+                FsmQueueOverflowPolicy::DropNewest => {
+                    self.last_dropped = Some(FsmQueueDropped::Newest);
+                    Ok(())
+                }
+            }
+        }
+
+        fn take_dropped(&mut self) -> Option<(usize, FsmQueueDropped)> {
+            self.last_dropped.take().map(|dropped| (N, dropped))
+        }
+    }
+}
+
+pub use self::queue_bounded::*;
+
 
 pub mod heapless_shared {
     //! A heapless queue with Clone and Arc support.
     
     use core::sync::atomic::{AtomicUsize, Ordering};
 
-    use crate::FsmError;
+    use crate::{FsmError, FsmQueueDropped};
 
     use super::*;
 
@@ -237,11 +537,177 @@ pub mod heapless_shared {
                     self.inner.len.fetch_add(1, Ordering::SeqCst);
                     Ok(())
                 },
-                Err(_) => Err(FsmError::QueueOverCapacity) 
+                Err(_) => Err(FsmError::QueueFull { capacity: 64, dropped: FsmQueueDropped::Newest })
+            }
+        }
+    }
+
+}
+
+pub mod spsc {
+    //! A lock-free single-producer single-consumer queue, for enqueueing the events from an interrupt
+    //! handler while the main loop dispatches them.
+
+    use heapless::Deque;
+    use heapless::spsc::{Consumer, Producer, Queue};
+
+    use crate::{FsmError, FsmQueueDropped};
+
+    use super::*;
+
+    /// The ring buffer shared by the two sides of the queue, usually a `static`. Holds up to `N - 1` events.
+    pub type FsmEventQueueSpscStorage<F, const N: usize> = Queue<<F as FsmBackend>::Events, N>;
+
+    /// Split the storage into the producer, for the interrupt handler, and the consumer, to be used as the
+    /// queue of the frontend.
+    pub fn split<F: FsmBackend, const N: usize, const L: usize>(storage: &mut FsmEventQueueSpscStorage<F, N>) -> (FsmEventQueueSpscProducer<'_, F, N>, FsmEventQueueSpsc<'_, F, N, L>) {
+        let (producer, consumer) = storage.split();
+
+        let producer = FsmEventQueueSpscProducer {
+            producer,
+            _fsm: PhantomData
+        };
+
+        let consumer = FsmEventQueueSpsc {
+            consumer,
+            local: Deque::new()
+        };
+
+        (producer, consumer)
+    }
+
+    /// The consumer side of the queue. The ring buffer can only have a single producer, so the events
+    /// enqueued by the machine's own actions are kept in a separate local queue of up to `L` events.
+    /// The local events are dispatched first. Can't be used by the transactional machines.
+    pub struct FsmEventQueueSpsc<'a, F: FsmBackend, const N: usize, const L: usize> {
+        consumer: Consumer<'a, <F as FsmBackend>::Events, N>,
+        local: Deque<<F as FsmBackend>::Events, L>
+    }
+
+    impl<'a, F: FsmBackend, const N: usize, const L: usize> FsmEventQueue<F> for FsmEventQueueSpsc<'a, F, N, L> {
+        fn dequeue(&mut self) -> Option<<F as FsmBackend>::Events> {
+            match self.local.pop_front() {
+                Some(e) => Some(e),
+                None => self.consumer.dequeue()
+            }
+        }
+
+        fn len(&self) -> usize {
+            self.local.len() + self.consumer.len()
+        }
+
+        /// The ring buffer can only be read from its front, so its kept events are moved into the local
+        /// queue, in front of the events that the producer enqueues meanwhile. Once the local queue is full,
+        /// the rest of the ring buffer's events are kept without being examined.
+        fn retain<P: FnMut(&<F as FsmBackend>::Events) -> bool>(&mut self, mut keep: P) {
+            for _ in 0..self.local.len() {
+                match self.local.pop_front() {
+                    Some(ev) if keep(&ev) => { let _ = self.local.push_back(ev); },
+                    Some(_) => (),
+                    None => break
+                }
             }
+
+            for _ in 0..self.consumer.len() {
+                let kept = match self.consumer.peek() {
+                    Some(ev) => keep(ev),
+                    None => break
+                };
+
+                if !kept {
+                    self.consumer.dequeue();
+                } else if self.local.is_full() {
+                    break;
+                } else if let Some(ev) = self.consumer.dequeue() {
+                    let _ = self.local.push_back(ev);
+                }
+            }
+        }
+
+        /// The local events are dispatched first, so the failed step's events can't be told apart from
+        /// the ones that the producer enqueued meanwhile.
+        const TRANSACTIONAL: bool = false;
+    }
+
+    impl<'a, F: FsmBackend, const N: usize, const L: usize> FsmEventQueueSender<F> for FsmEventQueueSpsc<'a, F, N, L> {
+        fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+            self.local.push_back(event.into()).map_err(|_| FsmError::QueueFull { capacity: L, dropped: FsmQueueDropped::Newest })
+        }
+    }
+
+    /// The producer side of the queue, can be moved into an interrupt handler or another thread.
+    pub struct FsmEventQueueSpscProducer<'a, F: FsmBackend, const N: usize> {
+        producer: Producer<'a, <F as FsmBackend>::Events, N>,
+        _fsm: PhantomData<fn() -> F>
+    }
+
+    impl<'a, F: FsmBackend, const N: usize> FsmEventQueueSpscProducer<'a, F, N> {
+        /// Returns true if there's room for another event.
+        pub fn ready(&self) -> bool {
+            self.producer.ready()
+        }
+    }
+
+    impl<'a, F: FsmBackend, const N: usize> FsmEventQueueSender<F> for FsmEventQueueSpscProducer<'a, F, N> {
+        fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+            self.producer.enqueue(event.into()).map_err(|_| FsmError::QueueFull { capacity: N - 1, dropped: FsmQueueDropped::Newest })
+        }
+    }
+}
+
+/// Enqueue an event on behalf of the machine, reporting the full queue to the inspector.
+pub fn enqueue_inspected<F, Q, I, E>(queue: &mut Q, inspect: &I, event: E) -> FsmResult<()>
+    where F: FsmBackend, Q: FsmEventQueueSender<F>, I: Inspect, E: Into<<F as FsmBackend>::Events>
+{
+    let result = queue.enqueue(event);
+    match result {
+        Err(FsmError::QueueFull { capacity, dropped }) => inspect.on_queue_full(capacity, dropped),
+        Ok(()) => if let Some((capacity, dropped)) = queue.take_dropped() {
+            inspect.on_queue_full(capacity, dropped);
+        },
+        Err(_) => ()
+    }
+    result
+}
+
+/// A handle for enqueueing the events into a running machine from other threads or interrupts, obtained
+/// with `FsmFrontend::event_sender`. Requires a shared queue, like `FsmEventQueueVecShared` or
+/// `FsmEventQueueHeaplessShared`. The events are dispatched once the owner of the frontend calls `process`.
+pub struct FsmEventSender<F, Q> {
+    queue: Q,
+    _fsm: PhantomData<fn() -> F>
+}
+
+impl<F, Q> FsmEventSender<F, Q> where F: FsmBackend, Q: FsmEventQueueSender<F> + Clone {
+    pub fn new(queue: &Q) -> Self {
+        FsmEventSender {
+            queue: queue.clone(),
+            _fsm: PhantomData
+        }
+    }
+
+    pub fn send<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+        self.queue.enqueue(event)
+    }
+}
+
+impl<F, Q> Clone for FsmEventSender<F, Q> where Q: Clone {
+    fn clone(&self) -> Self {
+        FsmEventSender {
+            queue: self.queue.clone(),
+            _fsm: PhantomData
         }
     }
+}
+
+impl<F, Q> FsmEventQueueSender<F> for FsmEventSender<F, Q> where F: FsmBackend, Q: FsmEventQueueSender<F> {
+    fn enqueue<E: Into<<F as FsmBackend>::Events>>(&mut self, event: E) -> FsmResult<()> {
+        self.queue.enqueue(event)
+    }
 
+    fn take_dropped(&mut self) -> Option<(usize, FsmQueueDropped)> {
+        self.queue.take_dropped()
+    }
 }
 
 pub struct FsmEventQueueNull<F> {
@@ -307,6 +773,10 @@ impl<'a, Q, F, FSub> FsmEventQueueSender<FSub> for FsmEventQueueSub<'a, Q, F, FS
     {
         self.parent.enqueue(event.into())
     }
+
+    fn take_dropped(&mut self) -> Option<(usize, FsmQueueDropped)> {
+        self.parent.take_dropped()
+    }
 }
 
 
@@ -333,6 +803,48 @@ fn test_dequeue_vec_shared() {
     test_queue(queue);
 }
 
+#[test]
+fn test_priority() {
+    let queue = FsmEventQueuePriority::<TestFsm>::new();
+    test_queue(queue);
+}
+
+#[test]
+fn test_bounded() {
+    let queue = FsmEventQueueBounded::<TestFsm, 16>::new();
+    test_queue(queue);
+}
+
+#[test]
+fn test_bounded_overflow() {
+    use super::tests_fsm::{Events, EventA};
+
+    let mut queue = FsmEventQueueBounded::<TestFsm, 2>::new();
+    queue.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    assert_eq!(Err(crate::FsmError::QueueFull { capacity: 2, dropped: crate::FsmQueueDropped::Newest }), queue.enqueue(EventA { n: 2 }));
+    assert_eq!(Some(Events::EventA(EventA { n: 0 })), queue.dequeue());
+
+    let mut queue = FsmEventQueueBounded::<TestFsm, 2>::with_policy(FsmQueueOverflowPolicy::DropOldest);
+    queue.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    assert_eq!(Ok(()), queue.enqueue(EventA { n: 2 }));
+    assert_eq!(Some((2, crate::FsmQueueDropped::Oldest)), queue.take_dropped());
+    assert_eq!(None, queue.take_dropped());
+    assert_eq!(Some(Events::EventA(EventA { n: 1 })), queue.dequeue());
+    assert_eq!(Some(Events::EventA(EventA { n: 2 })), queue.dequeue());
+
+    let mut queue = FsmEventQueueBounded::<TestFsm, 2>::with_policy(FsmQueueOverflowPolicy::DropNewest);
+    queue.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    assert_eq!(Ok(()), queue.enqueue(EventA { n: 2 }));
+    assert_eq!(Some((2, crate::FsmQueueDropped::Newest)), queue.take_dropped());
+    assert_eq!(Some(Events::EventA(EventA { n: 0 })), queue.dequeue());
+    assert_eq!(Some(Events::EventA(EventA { n: 1 })), queue.dequeue());
+    assert_eq!(None, queue.dequeue());
+    assert_eq!(1, queue.dropped());
+}
+
 #[test]
 fn test_heapless_shared() {
     use self::heapless_shared::FsmEventQueueHeaplessShared;
@@ -340,6 +852,68 @@ fn test_heapless_shared() {
     test_queue(queue);
 }
 
+#[test]
+fn test_spsc() {
+    use self::spsc::FsmEventQueueSpscStorage;
+    use super::tests_fsm::{Events, EventA};
+
+    let mut storage = FsmEventQueueSpscStorage::<TestFsm, 16>::new();
+    let (_, queue) = spsc::split::<TestFsm, 16, 16>(&mut storage);
+    test_queue(queue);
+
+    // the machine's own events first
+    let mut storage = FsmEventQueueSpscStorage::<TestFsm, 4>::new();
+    let (mut producer, mut queue) = spsc::split::<TestFsm, 4, 4>(&mut storage);
+    producer.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    producer.enqueue(EventA { n: 2 }).unwrap();
+    producer.enqueue(EventA { n: 3 }).unwrap();
+    assert_eq!(Err(crate::FsmError::QueueFull { capacity: 3, dropped: crate::FsmQueueDropped::Newest }), producer.enqueue(EventA { n: 4 }));
+    assert_eq!(4, queue.len());
+    assert_eq!(Some(Events::EventA(EventA { n: 1 })), queue.dequeue());
+    assert_eq!(Some(Events::EventA(EventA { n: 0 })), queue.dequeue());
+
+    // the events of both the local queue and the ring buffer are examined once, in their order
+    let mut storage = FsmEventQueueSpscStorage::<TestFsm, 8>::new();
+    let (mut producer, mut queue) = spsc::split::<TestFsm, 8, 4>(&mut storage);
+    queue.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    for n in 2..6 {
+        producer.enqueue(EventA { n }).unwrap();
+    }
+    let mut examined = vec![];
+    queue.retain(|Events::EventA(EventA { n })| {
+        examined.push(*n);
+        n % 2 == 0
+    });
+    assert_eq!(vec![0, 1, 2, 3, 4, 5], examined);
+    producer.enqueue(EventA { n: 6 }).unwrap();
+    queue.truncate(2);
+    assert_eq!(2, queue.len());
+    assert_eq!(Some(Events::EventA(EventA { n: 0 })), queue.dequeue());
+    assert_eq!(Some(Events::EventA(EventA { n: 2 })), queue.dequeue());
+    assert_eq!(None, queue.dequeue());
+
+    // the truncated queue keeps the oldest events of the ring buffer
+    producer.enqueue(EventA { n: 7 }).unwrap();
+    producer.enqueue(EventA { n: 8 }).unwrap();
+    queue.truncate(1);
+    assert_eq!(Some(Events::EventA(EventA { n: 7 })), queue.dequeue());
+    assert_eq!(None, queue.dequeue());
+}
+
+#[test]
+fn test_iter() {
+    use super::tests_fsm::{Events, EventA};
+
+    let mut queue = FsmEventQueueVec::<TestFsm>::new();
+    queue.enqueue(EventA { n: 0 }).unwrap();
+    queue.enqueue(EventA { n: 1 }).unwrap();
+    let events: Vec<_> = queue.iter().cloned().collect();
+    assert_eq!(vec![Events::EventA(EventA { n: 0 }), Events::EventA(EventA { n: 1 })], events);
+    assert_eq!(2, queue.len());
+}
+
 #[cfg(test)]
 fn test_queue<Q: FsmEventQueue<TestFsm>>(mut queue: Q) {
     use super::tests_fsm::{Events, EventA};
@@ -380,4 +954,21 @@ fn test_queue<Q: FsmEventQueue<TestFsm>>(mut queue: Q) {
             assert_eq!(queue.len(), x);
         }
     }
+
+    // retain and clear
+    {
+        queue.clear();
+        assert_eq!(0, queue.len());
+
+        for n in 0..6 {
+            queue.enqueue(EventA { n }).unwrap();
+        }
+        queue.retain(|Events::EventA(EventA { n })| n % 2 == 1);
+        assert_eq!(3, queue.len());
+        assert_eq!(Some(Events::EventA(EventA { n: 1 })), queue.dequeue());
+
+        queue.clear();
+        assert_eq!(0, queue.len());
+        assert_eq!(None, queue.dequeue());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use std::sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}, mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError}};
+use std::thread::JoinHandle;
+
+use crate::lib::*;
+use crate::{FsmBackend, FsmCurrentState, FsmError, FsmEventQueue, FsmFrontend, FsmQueueDropped, FsmResult, FsmStates, FsmTimers, Inspect};
+
+type FsmCurrentStates<F> = <<F as FsmBackend>::States as FsmStates<F>>::CurrentState;
+
+/// How often the runner's thread polls the timers while it waits for the events.
+pub const FSM_RUNNER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+enum FsmRunnerCommand<F: FsmBackend> {
+    Event(<F as FsmBackend>::Events),
+    Shutdown
+}
+
+enum FsmRunnerSender<F: FsmBackend> {
+    Unbounded(Sender<FsmRunnerCommand<F>>),
+    Bounded(SyncSender<FsmRunnerCommand<F>>, usize)
+}
+
+impl<F: FsmBackend> Clone for FsmRunnerSender<F> {
+    fn clone(&self) -> Self {
+        match self {
+            FsmRunnerSender::Unbounded(sender) => FsmRunnerSender::Unbounded(sender.clone()),
+            FsmRunnerSender::Bounded(sender, capacity) => FsmRunnerSender::Bounded(sender.clone(), *capacity)
+        }
+    }
+}
+
+/// A cheap, cloneable handle to a machine that runs on its own thread, see `FsmFrontend::spawn_runner`. The
+/// thread dispatches the sent events in their order and the timers as they trigger. The failed dispatches
+/// are only reported to the machine's inspector.
+pub struct FsmHandle<F: FsmBackend> {
+    sender: FsmRunnerSender<F>,
+    rejected: Arc<AtomicUsize>,
+    current_states: Arc<Mutex<FsmCurrentStates<F>>>,
+    thread: Arc<Mutex<Option<JoinHandle<()>>>>
+}
+
+impl<F: FsmBackend> FsmHandle<F> {
+    /// Send the event to the machine's thread. Blocks while the channel of a bounded runner is full, see
+    /// `try_dispatch`. Fails with `FsmError::RunnerStopped` once the runner was shut down.
+    pub fn send<E>(&self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let command = FsmRunnerCommand::Event(event.into());
+        let sent = match &self.sender {
+            FsmRunnerSender::Unbounded(sender) => sender.send(command).is_ok(),
+            FsmRunnerSender::Bounded(sender, _) => sender.send(command).is_ok()
+        };
+
+        if sent { Ok(()) } else { Err(FsmError::RunnerStopped) }
+    }
+
+    /// Send the event without blocking. Fails with `FsmError::QueueFull` if the channel of a bounded runner
+    /// is full, the machine is busy and the event wasn't sent. The producers can back off and try again later.
+    /// The rejected events are reported to the machine's inspector by its thread.
+    pub fn try_dispatch<E>(&self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let command = FsmRunnerCommand::Event(event.into());
+        match &self.sender {
+            FsmRunnerSender::Unbounded(sender) => sender.send(command).map_err(|_| FsmError::RunnerStopped),
+            FsmRunnerSender::Bounded(sender, capacity) => match sender.try_send(command) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.rejected.fetch_add(1, Ordering::SeqCst);
+                    Err(FsmError::QueueFull { capacity: *capacity, dropped: FsmQueueDropped::Newest })
+                },
+                Err(TrySendError::Disconnected(_)) => Err(FsmError::RunnerStopped)
+            }
+        }
+    }
+
+    /// The current states, as of the last event or timer that the thread dispatched.
+    pub fn current_state(&self) -> FsmCurrentStates<F> {
+        match self.current_states.lock() {
+            Ok(states) => *states,
+            Err(poisoned) => *poisoned.into_inner()
+        }
+    }
+
+    /// Stop the thread after it dispatched the events that were sent before, and wait for it to finish.
+    /// The machine is dropped with the thread.
+    pub fn shutdown(&self) -> FsmResult<()> {
+        let _ = match &self.sender {
+            FsmRunnerSender::Unbounded(sender) => sender.send(FsmRunnerCommand::Shutdown).is_ok(),
+            FsmRunnerSender::Bounded(sender, _) => sender.send(FsmRunnerCommand::Shutdown).is_ok()
+        };
+
+        let thread = self.thread.lock().ok().and_then(|mut t| t.take());
+        match thread {
+            Some(thread) => thread.join().map_err(|_| FsmError::RunnerStopped),
+            None => Ok(())
+        }
+    }
+
+    /// Is the machine's thread still running?
+    pub fn is_running(&self) -> bool {
+        self.thread.lock().map(|t| t.as_ref().is_some_and(|t| !t.is_finished())).unwrap_or(false)
+    }
+}
+
+impl<F: FsmBackend> Clone for FsmHandle<F> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            rejected: self.rejected.clone(),
+            current_states: self.current_states.clone(),
+            thread: self.thread.clone()
+        }
+    }
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where
+        F: FsmBackend,
+        Q: FsmEventQueue<F>,
+        I: Inspect,
+        T: FsmTimers<F>,
+        Self: Send + 'static,
+        <F as FsmBackend>::Events: Send,
+        FsmCurrentStates<F>: Send
+{
+    /// Move the machine onto its own thread, which dispatches the events sent through the returned handle
+    /// and polls the timers every `FSM_RUNNER_POLL_INTERVAL`. Starts the machine if it wasn't started yet, and
+    /// fails with the error of its start without spawning the thread.
+    pub fn spawn_runner(self) -> FsmResult<FsmHandle<F>> {
+        self.spawn_runner_with_interval(FSM_RUNNER_POLL_INTERVAL)
+    }
+
+    /// Like `spawn_runner`, polling the timers at this interval.
+    pub fn spawn_runner_with_interval(self, poll_interval: Duration) -> FsmResult<FsmHandle<F>> {
+        let (sender, receiver) = channel::<FsmRunnerCommand<F>>();
+        self.run_on_thread(FsmRunnerSender::Unbounded(sender), receiver, poll_interval)
+    }
+
+    /// Like `spawn_runner`, with a channel of up to `capacity` events that weren't dispatched yet. Once it's
+    /// full, `FsmHandle::send` blocks and `FsmHandle::try_dispatch` fails with `FsmError::QueueFull`.
+    pub fn spawn_runner_bounded(self, capacity: usize) -> FsmResult<FsmHandle<F>> {
+        let (sender, receiver) = sync_channel::<FsmRunnerCommand<F>>(capacity);
+        self.run_on_thread(FsmRunnerSender::Bounded(sender, capacity), receiver, FSM_RUNNER_POLL_INTERVAL)
+    }
+
+    fn run_on_thread(mut self, sender: FsmRunnerSender<F>, receiver: Receiver<FsmRunnerCommand<F>>, poll_interval: Duration) -> FsmResult<FsmHandle<F>> {
+        let capacity = match sender {
+            FsmRunnerSender::Bounded(_, capacity) => capacity,
+            FsmRunnerSender::Unbounded(_) => 0
+        };
+        let rejected = Arc::new(AtomicUsize::new(0));
+
+        if FsmCurrentState::all_stopped(self.get_current_states().as_ref()) {
+            self.start()?;
+        }
+        let current_states = Arc::new(Mutex::new(self.get_current_states()));
+
+        let states = current_states.clone();
+        let rejected_events = rejected.clone();
+        let thread = std::thread::spawn(move || {
+            let mut fsm = self;
+            loop {
+                match receiver.recv_timeout(poll_interval) {
+                    Ok(FsmRunnerCommand::Event(ev)) => {
+                        let _ = fsm.dispatch(ev);
+                    },
+                    Ok(FsmRunnerCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => ()
+                }
+
+                let _ = fsm.dispatch_timer_events();
+
+                for _ in 0..rejected_events.swap(0, Ordering::SeqCst) {
+                    fsm.inspect.on_queue_full(capacity, FsmQueueDropped::Newest);
+                }
+
+                if let Ok(mut states) = states.lock() {
+                    *states = fsm.get_current_states();
+                }
+            }
+        });
+
+        Ok(FsmHandle {
+            sender,
+            rejected,
+            current_states,
+            thread: Arc::new(Mutex::new(Some(thread)))
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::{sync::{mpsc::{UnboundedSender, unbounded_channel}, oneshot, watch}, task::JoinHandle};
+
+use crate::{FsmBackend, FsmCurrentState, FsmError, FsmEvent, FsmEventQueue, FsmFrontend, FsmResult, FsmState, FsmStates, Inspect, timers::tokio::TimersTokio};
+
+type FsmCurrentStates<F> = <<F as FsmBackend>::States as FsmStates<F>>::CurrentState;
+
+enum FsmTokioCommand<F: FsmBackend> {
+    Dispatch(<F as FsmBackend>::Events, oneshot::Sender<FsmResult<()>>),
+    Shutdown
+}
+
+/// A cheap, cloneable handle to a machine that is owned by a Tokio task, see `FsmFrontend::spawn_tokio_runner`.
+/// The task dispatches the events in the order in which they were sent, the timers as they trigger and the
+/// events scheduled with `enqueue_after` once they are due.
+pub struct FsmTokioHandle<F: FsmBackend> {
+    sender: UnboundedSender<FsmTokioCommand<F>>,
+    current_states: watch::Receiver<FsmCurrentStates<F>>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>
+}
+
+impl<F: FsmBackend> FsmTokioHandle<F> {
+    /// Dispatch the event and wait until it was run to completition, with the result of its dispatch.
+    /// Fails with `FsmError::RunnerStopped` once the task was shut down.
+    pub async fn dispatch<E>(&self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let (reply, result) = oneshot::channel();
+        self.sender.send(FsmTokioCommand::Dispatch(event.into(), reply)).map_err(|_| FsmError::RunnerStopped)?;
+        result.await.map_err(|_| FsmError::RunnerStopped)?
+    }
+
+    /// The current states, as of the last event or timer that the task dispatched.
+    pub fn current_state(&self) -> FsmCurrentStates<F> {
+        *self.current_states.borrow()
+    }
+
+    /// Wait until any of the regions is in the state, resolves at once if it already is. Fails with
+    /// `FsmError::WaitTimeout` if the state wasn't entered in time, and with `FsmError::RunnerStopped`
+    /// if the task was shut down.
+    ///
+    /// The states are published once the task dispatched an event or a timer to completition, with the
+    /// events that it queued. A state that is entered and left again within a single dispatch is never
+    /// seen, so wait for the state that the machine settles in. Only the states of this machine's regions
+    /// are published, the states of its submachines can't be waited on.
+    pub async fn wait_for_state<S>(&self, timeout: Duration) -> FsmResult<()>
+        where S: FsmState<F>
+    {
+        let state = FsmCurrentState::State(<S>::fsm_state());
+        let mut current_states = self.current_states.clone();
+        let entered = async move {
+            current_states.wait_for(|states| states.as_ref().contains(&state)).await.map(|_| ())
+        };
+
+        match tokio::time::timeout(timeout, entered).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(_)) => Err(FsmError::RunnerStopped),
+            Err(_) => Err(FsmError::WaitTimeout)
+        }
+    }
+
+    /// Stop the task after it dispatched the events that were sent before, and wait for it to finish.
+    pub async fn shutdown(&self) -> FsmResult<()> {
+        let _ = self.sender.send(FsmTokioCommand::Shutdown);
+
+        let task = self.task.lock().ok().and_then(|mut t| t.take());
+        match task {
+            Some(task) => task.await.map_err(|_| FsmError::RunnerStopped),
+            None => Ok(())
+        }
+    }
+}
+
+impl<F: FsmBackend> Clone for FsmTokioHandle<F> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            current_states: self.current_states.clone(),
+            task: self.task.clone()
+        }
+    }
+}
+
+impl<F, Q, I> FsmFrontend<F, Q, I, TimersTokio<F>>
+    where
+        F: FsmBackend,
+        Q: FsmEventQueue<F>,
+        I: Inspect,
+        Self: Send + 'static,
+        <F as FsmBackend>::Events: Send,
+        <F as FsmBackend>::Timers: Send + 'static,
+        FsmCurrentStates<F>: Send + Sync
+{
+    /// Move the machine into a task on the current Tokio runtime, which dispatches the events of the returned
+    /// handle, the timers as they trigger and the scheduled events once they are due. Starts the machine if it
+    /// wasn't started yet, and fails with the error of its start without spawning the task. Has to be called
+    /// from within a runtime context.
+    pub fn spawn_tokio_runner(mut self) -> FsmResult<FsmTokioHandle<F>> {
+        let (sender, mut receiver) = unbounded_channel::<FsmTokioCommand<F>>();
+
+        if FsmCurrentState::all_stopped(self.get_current_states().as_ref()) {
+            self.start()?;
+        }
+        let (states, current_states) = watch::channel(self.get_current_states());
+
+        let task = tokio::spawn(async move {
+            let mut fsm = self;
+            loop {
+                tokio::select! {
+                    command = receiver.recv() => match command {
+                        Some(FsmTokioCommand::Dispatch(ev, reply)) => {
+                            let result = fsm.dispatch(ev);
+                            // the scheduled events that became due while the event was dispatched
+                            let _ = fsm.dispatch_timer_events();
+                            states.send_replace(fsm.get_current_states());
+                            let _ = reply.send(result);
+                        },
+                        Some(FsmTokioCommand::Shutdown) | None => break
+                    },
+                    timer_id = fsm.wait_for_timer_or_scheduled() => {
+                        if let Some(timer_id) = timer_id {
+                            let _ = fsm.dispatch_single_event(FsmEvent::Timer(timer_id));
+                        }
+                        let _ = fsm.dispatch_timer_events();
+                        states.send_replace(fsm.get_current_states());
+                    }
+                }
+            }
+        });
+
+        Ok(FsmTokioHandle {
+            sender,
+            current_states,
+            task: Arc::new(Mutex::new(Some(task)))
+        })
+    }
+}
@@ -0,0 +1,246 @@
+//! Snapshots of the FSM's runtime state, for persisting long-running machines.
+
+use crate::{FsmBackend, FsmBackendImpl, FsmDeferredEvents, FsmError, FsmEventQueue, FsmFrontend, FsmResult, FsmStates, FsmTimers, Inspect};
+
+/// The version of the machine that its snapshots are tagged with. Implemented by the code generator for the machines
+/// with `derive_serde`, as a hash of the names of the states, events and regions, and of the versions of the submachines.
+/// The changes to the fields of the states' own types aren't a part of it.
+pub trait FsmSnapshotVersion: FsmBackend {
+    const SNAPSHOT_VERSION: u64;
+}
+
+/// Combines the version of the machine with the versions of its submachines, used by the generated code.
+pub const fn fsm_snapshot_version(version: u64, submachines: &[u64]) -> u64 {
+    let mut version = version;
+    let mut i = 0;
+    while i < submachines.len() {
+        version = (version ^ submachines[i]).wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    version
+}
+
+/// An owned snapshot of the machine's states, the current states of the regions, the deferred events and the event queue.
+/// Use it to deserialize a snapshot that was created with `FsmFrontend::snapshot`.
+///
+/// The timers are not a part of the snapshot, they are started again when their states are re-entered. The snapshots
+/// taken with another version of the machine can be restored by migrating them, see `FsmSnapshotMigrations`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "<F as FsmBackend>::States: serde::Serialize, <<F as FsmBackend>::States as FsmStates<F>>::CurrentState: serde::Serialize, <F as FsmBackend>::Events: serde::Serialize, Q: serde::Serialize",
+    deserialize = "<F as FsmBackend>::States: serde::Deserialize<'de>, <<F as FsmBackend>::States as FsmStates<F>>::CurrentState: serde::Deserialize<'de>, <F as FsmBackend>::Events: serde::Deserialize<'de>, Q: serde::Deserialize<'de>"
+))]
+pub struct FsmSnapshot<F, Q>
+    where F: FsmBackend
+{
+    /// The `FsmSnapshotVersion` of the machine that took the snapshot, 0 for the snapshots taken before the versions.
+    #[serde(default)]
+    pub version: u64,
+    pub states: <F as FsmBackend>::States,
+    pub current_states: <<F as FsmBackend>::States as FsmStates<F>>::CurrentState,
+    pub deferred: FsmDeferredEvents<F>,
+    pub queue: Q
+}
+
+/// A borrowed snapshot of the running machine, serializes into the same format as `FsmSnapshot`.
+#[derive(serde::Serialize)]
+#[serde(bound(
+    serialize = "<F as FsmBackend>::States: serde::Serialize, <<F as FsmBackend>::States as FsmStates<F>>::CurrentState: serde::Serialize, <F as FsmBackend>::Events: serde::Serialize, Q: serde::Serialize"
+))]
+pub struct FsmSnapshotRef<'a, F, Q>
+    where F: FsmBackend
+{
+    pub version: u64,
+    pub states: &'a <F as FsmBackend>::States,
+    pub current_states: &'a <<F as FsmBackend>::States as FsmStates<F>>::CurrentState,
+    pub deferred: &'a FsmDeferredEvents<F>,
+    pub queue: &'a Q
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmSnapshotVersion, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// Take a serializable snapshot of the machine's states, current states and the pending and deferred events.
+    pub fn snapshot(&self) -> FsmSnapshotRef<'_, F, Q> {
+        FsmSnapshotRef {
+            version: F::SNAPSHOT_VERSION,
+            states: &self.backend.states,
+            current_states: &self.backend.current_states,
+            deferred: &self.backend.deferred,
+            queue: &self.queue
+        }
+    }
+}
+
+impl<F> FsmBackendImpl<F>
+    where F: FsmBackend
+{
+    /// Rebuild the backend from a context and a snapshot's states, without executing any actions.
+    pub fn from_snapshot<Q>(context: <F as FsmBackend>::Context, snapshot: FsmSnapshot<F, Q>) -> (Self, Q) {
+        let backend = FsmBackendImpl {
+            context,
+            states: snapshot.states,
+            current_states: snapshot.current_states,
+            deferred: snapshot.deferred,
+            timer_requests: Default::default(),
+            outputs: Default::default(),
+            error: None,
+            #[cfg(feature = "std")]
+            observers: Default::default(),
+            #[cfg(feature = "std")]
+            middlewares: Default::default(),
+            dispatch_id: 0
+        };
+
+        (backend, snapshot.queue)
+    }
+}
+
+/// The compact binary snapshots, for persisting the machines of the embedded devices to the EEPROM or the flash.
+/// The snapshot is encoded with `postcard` into a caller's buffer, without allocating, and followed by the CRC-32
+/// of the encoded bytes, so a snapshot that was torn by a power loss is rejected when it's decoded.
+#[cfg(feature = "snapshot_postcard")]
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmSnapshotVersion, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// Encode the snapshot into the buffer and return the used part of it, fails if the buffer is too small.
+    pub fn snapshot_to_slice<'b>(&self, buf: &'b mut [u8]) -> FsmResult<&'b mut [u8]>
+        where for<'a> FsmSnapshotRef<'a, F, Q>: serde::Serialize
+    {
+        let len = buf.len().checked_sub(FSM_SNAPSHOT_CRC_LEN).ok_or(FsmError::SnapshotInvalid("The buffer is too small for the snapshot"))?;
+        let encoded = postcard::to_slice(&self.snapshot(), &mut buf[..len])
+            .map_err(|_| FsmError::SnapshotInvalid("The buffer is too small for the snapshot"))?
+            .len();
+
+        let crc = fsm_snapshot_crc(&buf[..encoded]);
+        buf[encoded..encoded + FSM_SNAPSHOT_CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+
+        Ok(&mut buf[..encoded + FSM_SNAPSHOT_CRC_LEN])
+    }
+}
+
+/// The length of the checksum that follows the encoded binary snapshots.
+#[cfg(feature = "snapshot_postcard")]
+pub const FSM_SNAPSHOT_CRC_LEN: usize = 4;
+
+/// Decode a binary snapshot taken with `FsmFrontend::snapshot_to_slice`. Trailing bytes after the snapshot, like
+/// the erased bytes of its flash page, are not allowed. Restore the machine with `FsmFactory::restore_with`.
+#[cfg(feature = "snapshot_postcard")]
+pub fn fsm_snapshot_from_slice<F, Q>(bytes: &[u8]) -> FsmResult<FsmSnapshot<F, Q>>
+    where F: FsmBackend, FsmSnapshot<F, Q>: serde::de::DeserializeOwned
+{
+    let len = bytes.len().checked_sub(FSM_SNAPSHOT_CRC_LEN).ok_or(FsmError::SnapshotInvalid("The snapshot is truncated"))?;
+    let (encoded, crc) = bytes.split_at(len);
+
+    if fsm_snapshot_crc(encoded).to_le_bytes() != crc {
+        return Err(FsmError::SnapshotInvalid("The checksum of the snapshot doesn't match"));
+    }
+
+    postcard::from_bytes(encoded).map_err(|_| FsmError::SnapshotInvalid("The snapshot doesn't match the machine"))
+}
+
+/// The CRC-32 (IEEE) of the encoded snapshot.
+#[cfg(feature = "snapshot_postcard")]
+fn fsm_snapshot_crc(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// A snapshot that was deserialized into a self-describing format, like `serde_json::Value`, so the migrations
+/// can rename its states and edit their fields before it's deserialized into a `FsmSnapshot`.
+pub trait FsmSnapshotDocument: Sized {
+    /// The version of the machine that took the snapshot, see `FsmSnapshot::version`.
+    fn version(&self) -> u64;
+
+    fn set_version(&mut self, version: u64);
+
+    fn into_snapshot<F, Q>(self) -> FsmResult<FsmSnapshot<F, Q>>
+        where F: FsmBackend, FsmSnapshot<F, Q>: serde::de::DeserializeOwned;
+}
+
+#[cfg(feature = "serde_json")]
+impl FsmSnapshotDocument for serde_json::Value {
+    fn version(&self) -> u64 {
+        self.get("version").and_then(|v| v.as_u64()).unwrap_or(0)
+    }
+
+    fn set_version(&mut self, version: u64) {
+        if let Some(snapshot) = self.as_object_mut() {
+            snapshot.insert("version".into(), version.into());
+        }
+    }
+
+    fn into_snapshot<F, Q>(self) -> FsmResult<FsmSnapshot<F, Q>>
+        where F: FsmBackend, FsmSnapshot<F, Q>: serde::de::DeserializeOwned
+    {
+        serde_json::from_value(self).map_err(|_| FsmError::SnapshotInvalid("The migrated snapshot doesn't match the machine"))
+    }
+}
+
+/// The migrations of the snapshots taken with the previous versions of the machine. Each migration edits the
+/// document of a snapshot from one version to the next one, for example renaming a state in the `current_states`
+/// and moving its field in the `states`. The migrations are chained until the snapshot reaches the machine's version.
+#[cfg(feature = "std")]
+pub struct FsmSnapshotMigrations<D> {
+    migrations: Vec<FsmSnapshotMigration<D>>
+}
+
+#[cfg(feature = "std")]
+type FsmSnapshotMigrationFn<D> = Box<dyn Fn(&mut D) -> FsmResult<()>>;
+
+#[cfg(feature = "std")]
+struct FsmSnapshotMigration<D> {
+    from: u64,
+    to: u64,
+    migrate: FsmSnapshotMigrationFn<D>
+}
+
+#[cfg(feature = "std")]
+impl<D: FsmSnapshotDocument> FsmSnapshotMigrations<D> {
+    pub fn new() -> Self {
+        FsmSnapshotMigrations {
+            migrations: Vec::new()
+        }
+    }
+
+    /// Register the migration of the snapshots with the version `from` to the version `to`.
+    pub fn add<M>(&mut self, from: u64, to: u64, migration: M) -> &mut Self
+        where M: Fn(&mut D) -> FsmResult<()> + 'static
+    {
+        self.migrations.push(FsmSnapshotMigration { from, to, migrate: Box::new(migration) });
+        self
+    }
+
+    /// Migrate the snapshot to the current version of the machine and deserialize it.
+    pub fn migrate<F, Q>(&self, mut document: D) -> FsmResult<FsmSnapshot<F, Q>>
+        where F: FsmSnapshotVersion, FsmSnapshot<F, Q>: serde::de::DeserializeOwned
+    {
+        let mut version = document.version();
+        let mut applied = 0;
+
+        while version != F::SNAPSHOT_VERSION {
+            let migration = self.migrations.iter().find(|m| m.from == version).filter(|_| applied < self.migrations.len());
+            let migration = migration.ok_or(FsmError::SnapshotVersion { expected: F::SNAPSHOT_VERSION, found: version })?;
+
+            (migration.migrate)(&mut document)?;
+            version = migration.to;
+            document.set_version(version);
+            applied += 1;
+        }
+
+        document.into_snapshot()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: FsmSnapshotDocument> Default for FsmSnapshotMigrations<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,13 +5,19 @@ use crate::FsmResult;
 /// The implementation should hold all of the FSM's states as fields.
 pub trait FsmStates<TFsm>: FsmStateFactory<TFsm> where TFsm: FsmBackend {
     /// The enum type for all states that's used as the "current state" field in the FSM's backend.
-    type StateKind: Clone + Copy + Debug + PartialEq + 'static;
+    type StateKind: Clone + Copy + Debug + PartialEq + FsmId + 'static;
     /// An array of current states for the machine, one for each region.
     type CurrentState: Clone + Copy + Debug + Default + AsRef<[FsmCurrentState<Self::StateKind>]> + AsMut<[FsmCurrentState<Self::StateKind>]> + 'static;
+
+    /// Was this state declared as a final state of its region?
+    fn is_final_state(_state: Self::StateKind) -> bool {
+        false
+    }
 }
 
 /// The current state of the FSM.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FsmCurrentState<S> where S: Clone + Copy {
     /// The FSM is halted and has to be started using the `start()` method.
     Stopped,
@@ -28,6 +34,24 @@ impl<S> FsmCurrentState<S> where S: Clone + Copy {
     }
 }
 
+impl<S> FsmCurrentState<S> where S: FsmId + Copy {
+    /// The id of the current state, `None` if the FSM is stopped.
+    pub fn id(&self) -> Option<u32> {
+        match self {
+            FsmCurrentState::Stopped => None,
+            FsmCurrentState::State(s) => Some(s.to_id())
+        }
+    }
+}
+
+/// The stable numeric ids of the states and the events, implemented by the generated `CurrentState` and
+/// `EventKind` enums. The ids are the hashes of the names, unless they were declared with `fsm.state_id`
+/// and `fsm.event_id`, so they don't change when the builder is reordered.
+pub trait FsmId: Sized {
+    fn to_id(&self) -> u32;
+    fn from_id(id: u32) -> Option<Self>;
+}
+
 impl<S> Debug for FsmCurrentState<S> where S: Debug + Copy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -37,12 +61,30 @@ impl<S> Debug for FsmCurrentState<S> where S: Debug + Copy {
     }
 }
 
+impl<S> fmt::Display for FsmCurrentState<S> where S: fmt::Display + Copy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmCurrentState::Stopped => f.write_str("Stopped"),
+            FsmCurrentState::State(s) => s.fmt(f)
+        }
+    }
+}
+
 impl<S> Default for FsmCurrentState<S> where S: Clone + Copy {
     fn default() -> Self {
         Self::Stopped
     }
 }
 
+/// What `FsmFrontend::reset` does with the data of the states.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FsmResetStates {
+    /// Keep the data of all the states, including the submachines, only the current states are reset.
+    Keep,
+    /// Create all the states again with their `FsmStateFactory`, usually with `Default`.
+    Recreate
+}
+
 /// Create a new state from the shared global context.
 pub trait FsmStateFactory<TFsm> where Self: Sized, TFsm: FsmBackend {
     /// Constructor for building this state from the shared global context.
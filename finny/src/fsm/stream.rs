@@ -0,0 +1,111 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::Stream;
+
+use crate::{FsmBackend, FsmEventQueue, FsmFrontend, FsmResult, FsmStates, FsmTimers, Inspect};
+
+/// The outcome of a single event that was dispatched by the `FsmStreamDriver`.
+pub struct FsmStreamTransition<F: FsmBackend> {
+    pub event: String,
+    pub states_before: <<F as FsmBackend>::States as FsmStates<F>>::CurrentState,
+    pub states_after: <<F as FsmBackend>::States as FsmStates<F>>::CurrentState,
+    /// The result of the event's dispatch. The driver keeps running after the failed events.
+    pub result: FsmResult<()>
+}
+
+impl<F: FsmBackend> FsmStreamTransition<F> {
+    /// Did the event change the current state of any of the regions?
+    pub fn is_state_changed(&self) -> bool {
+        self.states_before.as_ref() != self.states_after.as_ref()
+    }
+}
+
+/// Drives the machine with the events of a stream until the stream ends or the machine is completed,
+/// yielding the outcome of every event. The events are dispatched using the synchronous dispatch path,
+/// running the queue to completition after each of them.
+pub struct FsmStreamDriver<F, Q, I, T, S>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    frontend: FsmFrontend<F, Q, I, T>,
+    events: S,
+    done: bool
+}
+
+impl<F, Q, I, T, S, E> FsmStreamDriver<F, Q, I, T, S>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, S: Stream<Item = E> + Unpin, E: Into<<F as FsmBackend>::Events>
+{
+    /// The machine should already be started.
+    pub fn new(frontend: FsmFrontend<F, Q, I, T>, events: S) -> Self {
+        FsmStreamDriver {
+            frontend,
+            events,
+            done: false
+        }
+    }
+
+    pub fn frontend(&self) -> &FsmFrontend<F, Q, I, T> {
+        &self.frontend
+    }
+
+    /// Stop driving the machine and return it.
+    pub fn into_frontend(self) -> FsmFrontend<F, Q, I, T> {
+        self.frontend
+    }
+}
+
+impl<F, Q, I, T, S, E> Stream for FsmStreamDriver<F, Q, I, T, S>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, S: Stream<Item = E> + Unpin, E: Into<<F as FsmBackend>::Events>
+{
+    type Item = FsmStreamTransition<F>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let driver = self.get_mut();
+
+        if driver.done {
+            return Poll::Ready(None);
+        }
+
+        match Pin::new(&mut driver.events).poll_next(cx) {
+            Poll::Ready(Some(event)) => {
+                let event: <F as FsmBackend>::Events = event.into();
+                let event_name = event.as_ref().to_string();
+                let states_before = driver.frontend.get_current_states();
+                let result = driver.frontend.dispatch(event);
+
+                if driver.frontend.is_completed() {
+                    driver.done = true;
+                }
+
+                Poll::Ready(Some(FsmStreamTransition {
+                    event: event_name,
+                    states_before,
+                    states_after: driver.frontend.get_current_states(),
+                    result
+                }))
+            },
+            Poll::Ready(None) => {
+                driver.done = true;
+                Poll::Ready(None)
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+// the frontend is never pinned, only the stream of the events is polled
+impl<F, Q, I, T, S> Unpin for FsmStreamDriver<F, Q, I, T, S>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>, S: Unpin
+{
+
+}
+
+impl<F, Q, I, T> FsmFrontend<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    /// Drive the started machine with a stream of events, see `FsmStreamDriver`.
+    pub fn drive_stream<S, E>(self, events: S) -> FsmStreamDriver<F, Q, I, T, S>
+        where S: Stream<Item = E> + Unpin, E: Into<<F as FsmBackend>::Events>
+    {
+        FsmStreamDriver::new(self, events)
+    }
+}
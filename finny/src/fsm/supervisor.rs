@@ -0,0 +1,209 @@
+use crate::lib::*;
+use crate::{FsmBackend, FsmBackendCancelTimers, FsmBackendStop, FsmError, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect};
+
+/// What the supervisor does once the supervised machine fails.
+pub enum FsmSupervisorPolicy<F: FsmBackend> {
+    /// Stop the machine and restart it from its initial states, see `FsmFrontend::restart`.
+    Restart,
+    /// Dispatch the event that's built by the function, to move the machine into its fault state. Usually
+    /// handled with `fsm.any_state().on_event::<TEvent>().transition_to::<TFaultState>()`.
+    Fault(fn() -> <F as FsmBackend>::Events),
+    /// Return the failure to the caller, the machine is left as it is.
+    Escalate
+}
+
+impl<F: FsmBackend> Clone for FsmSupervisorPolicy<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: FsmBackend> Copy for FsmSupervisorPolicy<F> { }
+
+/// Supervises a frontend, Erlang-style. The guards and actions that fail with `FsmError::ActionFailed` and,
+/// if enabled with `catch_panics`, those that panic, are handled by the policy instead of being returned to
+/// the caller. Once the machine was restarted more than the allowed number of times within the period, the
+/// failures are escalated. The failures that the machine handles with its own fault state, declared with
+/// `fsm.on_error()`, aren't supervised.
+///
+/// The period and the backoff are measured with the timers' clock. Without a clock or a period the restarts
+/// are never forgotten, and without a clock there's no backoff.
+pub struct FsmSupervisor<F, Q, I, T>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    frontend: FsmFrontend<F, Q, I, T>,
+    policy: FsmSupervisorPolicy<F>,
+    max_restarts: Option<(u32, Duration)>,
+    backoff: Option<(Duration, Duration)>,
+    #[cfg(feature = "std")]
+    catch_panics: bool,
+    restarts: u32,
+    failures: u32,
+    last_error: Option<FsmError>,
+    /// The start of the restart period and the restarts within it.
+    period: Option<(Duration, u32)>,
+    backoff_until: Option<Duration>
+}
+
+impl<F, Q, I, T> FsmSupervisor<F, Q, I, T>
+    where F: FsmBackendStop + FsmBackendCancelTimers, Q: FsmEventQueue<F>, I: Inspect, T: FsmTimers<F>
+{
+    pub fn new(frontend: FsmFrontend<F, Q, I, T>, policy: FsmSupervisorPolicy<F>) -> Self {
+        FsmSupervisor {
+            frontend,
+            policy,
+            max_restarts: None,
+            backoff: None,
+            #[cfg(feature = "std")]
+            catch_panics: false,
+            restarts: 0,
+            failures: 0,
+            last_error: None,
+            period: None,
+            backoff_until: None
+        }
+    }
+
+    /// Escalate the failures once the machine was restarted `max` times within the period.
+    pub fn with_max_restarts(mut self, max: u32, period: Duration) -> Self {
+        self.max_restarts = Some((max, period));
+        self
+    }
+
+    /// After a restart, reject the events with `FsmError::SupervisorBackoff` for the backoff, doubled with every
+    /// restart within the period, up to the maximum.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = Some((initial, max));
+        self
+    }
+
+    /// Also supervise the guards and actions that panic, as `FsmError::Panicked`. The machine is likely left
+    /// in an inconsistent state, so `FsmSupervisorPolicy::Restart` is the right policy for the panics.
+    #[cfg(feature = "std")]
+    pub fn catch_panics(mut self) -> Self {
+        self.catch_panics = true;
+        self
+    }
+
+    pub fn frontend(&self) -> &FsmFrontend<F, Q, I, T> {
+        &self.frontend
+    }
+
+    pub fn frontend_mut(&mut self) -> &mut FsmFrontend<F, Q, I, T> {
+        &mut self.frontend
+    }
+
+    pub fn into_inner(self) -> FsmFrontend<F, Q, I, T> {
+        self.frontend
+    }
+
+    /// How many times the machine was restarted by the supervisor.
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// How many failures the supervisor handled or escalated.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The last failure of the machine.
+    pub fn last_error(&self) -> Option<&FsmError> {
+        self.last_error.as_ref()
+    }
+
+    /// Start the supervised machine.
+    pub fn start(&mut self) -> FsmResult<()> {
+        self.supervise(|fsm| fsm.start())
+    }
+
+    /// Dispatch the event and run it to completition, see `FsmFrontend::dispatch`.
+    pub fn dispatch<E>(&mut self, event: E) -> FsmResult<()>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        self.supervise(|fsm| fsm.dispatch(event))
+    }
+
+    /// Dispatch the triggered timers and the queued events, see `FsmFrontend::process`.
+    pub fn process(&mut self) -> FsmResult<()> {
+        self.supervise(|fsm| fsm.process())
+    }
+
+    fn supervise<O>(&mut self, op: O) -> FsmResult<()>
+        where O: FnOnce(&mut FsmFrontend<F, Q, I, T>) -> FsmResult<()>
+    {
+        if let (Some(until), Some(now)) = (self.backoff_until, self.frontend.timers.now()) {
+            if now < until {
+                return Err(FsmError::SupervisorBackoff { remaining: until - now });
+            }
+            self.backoff_until = None;
+        }
+
+        match self.run(op) {
+            Err(e @ FsmError::ActionFailed(_)) | Err(e @ FsmError::Panicked) => self.on_failure(e),
+            result => result
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn run<O>(&mut self, op: O) -> FsmResult<()>
+        where O: FnOnce(&mut FsmFrontend<F, Q, I, T>) -> FsmResult<()>
+    {
+        if !self.catch_panics {
+            return op(&mut self.frontend);
+        }
+
+        let frontend = &mut self.frontend;
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| op(frontend))).unwrap_or(Err(FsmError::Panicked))
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn run<O>(&mut self, op: O) -> FsmResult<()>
+        where O: FnOnce(&mut FsmFrontend<F, Q, I, T>) -> FsmResult<()>
+    {
+        op(&mut self.frontend)
+    }
+
+    fn on_failure(&mut self, error: FsmError) -> FsmResult<()> {
+        self.failures += 1;
+        self.last_error = Some(error);
+        self.frontend.inspect.on_error("The supervised machine failed", &error);
+
+        match self.policy {
+            FsmSupervisorPolicy::Escalate => Err(error),
+            FsmSupervisorPolicy::Fault(event) => {
+                self.frontend.inspect.info("Moving the supervised machine into its fault state.");
+                self.run(|fsm| fsm.dispatch(event()))
+            },
+            FsmSupervisorPolicy::Restart => {
+                // the restarts within the current period, without a clock or a period they're never forgotten
+                let now = self.frontend.timers.now();
+                let (start, restarts) = match (self.period, self.max_restarts, now) {
+                    (Some((start, _)), Some((_, period)), Some(now)) if now.saturating_sub(start) > period => (now, 0),
+                    (Some(current), _, _) => current,
+                    (None, _, now) => (now.unwrap_or_default(), 0)
+                };
+
+                if let Some((max, _)) = self.max_restarts {
+                    if restarts >= max {
+                        self.frontend.inspect.info("The supervised machine was restarted too many times, escalating.");
+                        return Err(error);
+                    }
+                }
+
+                self.period = Some((start, restarts + 1));
+                self.restarts += 1;
+
+                self.frontend.inspect.info("Restarting the supervised machine.");
+                self.frontend.restart()?;
+
+                if let (Some((initial, max)), Some(now)) = (self.backoff, now) {
+                    let backoff = initial.checked_mul(1 << restarts.min(31)).unwrap_or(max).min(max);
+                    self.backoff_until = Some(now + backoff);
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! A minimal, internal FSM for unit tests, manually written.
 
-use crate::{AllVariants, FsmBackend, FsmCurrentState, FsmStates};
+use crate::{AllVariants, FsmBackend, FsmCurrentState, FsmEventPriority, FsmEventTraceContext, FsmId, FsmStates};
 use derive_more::From;
 
 #[derive(Default)]
@@ -21,6 +21,19 @@ pub enum StateKind {
     StateA
 }
 
+impl FsmId for StateKind {
+    fn to_id(&self) -> u32 {
+        0
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(StateKind::StateA),
+            _ => None
+        }
+    }
+}
+
 impl FsmStates<TestFsm> for States {
     type StateKind = StateKind;
     type CurrentState = [FsmCurrentState<StateKind>; 1];
@@ -36,6 +49,14 @@ impl AsRef<str> for Events {
         todo!()
     }
 }
+
+impl FsmEventTraceContext for Events {}
+
+impl FsmEventPriority for Events {
+    fn priority(&self) -> u8 {
+        0
+    }
+}
 #[derive(Debug, Clone, PartialEq)]
 pub enum FsmBackendTimers {
 
@@ -51,6 +72,8 @@ impl AllVariants for FsmBackendTimers {
 
 impl FsmBackend for TestFsm {
     type Context = ();
+    type Resources = ();
+    type Outputs = ();
     type States = States;
     type Events = Events;
     type Timers = FsmBackendTimers;
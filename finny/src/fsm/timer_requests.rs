@@ -0,0 +1,431 @@
+use crate::lib::*;
+use crate::{AllVariants, FsmBackend, FsmRegionId, FsmResult, FsmStates, FsmTimerId, FsmTimers};
+
+#[cfg(not(feature = "std"))]
+use arraydeque::ArrayDeque;
+
+/// The maximum number of pending timer requests of a single dispatch without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub const FSM_TIMER_REQUESTS_CAPACITY: usize = 8;
+
+/// The maximum number of regions and running timers that are tracked for the guards, without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub const FSM_TIMER_STATUS_CAPACITY: usize = 8;
+
+/// The maximum number of states whose total time is tracked, without the `std` feature.
+#[cfg(not(feature = "std"))]
+pub const FSM_STATE_TIMES_CAPACITY: usize = 16;
+
+/// The maximum number of transitions with a rate limit, without the `std` feature. The transitions beyond it aren't filtered.
+#[cfg(not(feature = "std"))]
+pub const FSM_RATE_LIMITS_CAPACITY: usize = 8;
+
+type FsmStateKind<F> = <<F as FsmBackend>::States as FsmStates<F>>::StateKind;
+
+/// The handle of an event scheduled with `FsmTimerRequests::enqueue_after`, for cancelling it.
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FsmScheduledEventId(u64);
+
+/// Filters the rapid repetitions of a transition's event, declared with `debounce` or `throttle`. The filtered
+/// events are treated like the ones rejected by a guard.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FsmRateLimit {
+    /// The transition is taken on the first event, then only once the event was quiet for the window. The
+    /// repetitions within the window extend it.
+    Debounce(Duration),
+    /// The transition is taken at most once per window.
+    Throttle(Duration)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FsmTimerRequest {
+    Cancel,
+    Restart
+}
+
+/// Cancellations and restarts of the state timers, requested by the actions. The requests are
+/// applied by the machine after the current event is processed. Also tells the guards which timers
+/// are running and for how long the current states have been active, and keeps the total time
+/// spent in each state, the last events of the transitions with a rate limit and the attempts of the retries. With the `std` feature, it also holds the events that were scheduled for a later
+/// dispatch.
+pub struct FsmTimerRequests<F: FsmBackend> {
+    #[cfg(feature = "std")]
+    requests: VecDeque<(<F as FsmBackend>::Timers, FsmTimerRequest)>,
+    #[cfg(not(feature = "std"))]
+    requests: ArrayDeque<[(<F as FsmBackend>::Timers, FsmTimerRequest); FSM_TIMER_REQUESTS_CAPACITY]>,
+    /// The timers' clock, as read at the start of the current dispatch.
+    now: Option<Duration>,
+    /// The clock at the time the current state of each region was entered.
+    #[cfg(feature = "std")]
+    entered_at: Vec<Option<Duration>>,
+    #[cfg(not(feature = "std"))]
+    entered_at: [Option<Duration>; FSM_TIMER_STATUS_CAPACITY],
+    /// The timers that were running at the start of the current dispatch.
+    #[cfg(feature = "std")]
+    running: Vec<<F as FsmBackend>::Timers>,
+    #[cfg(not(feature = "std"))]
+    running: ArrayDeque<[<F as FsmBackend>::Timers; FSM_TIMER_STATUS_CAPACITY]>,
+    /// The time spent in each state, over all of its completed visits.
+    #[cfg(feature = "std")]
+    state_times: Vec<(FsmStateKind<F>, Duration)>,
+    #[cfg(not(feature = "std"))]
+    state_times: ArrayDeque<[(FsmStateKind<F>, Duration); FSM_STATE_TIMES_CAPACITY]>,
+    /// The clock at the last event of the transitions with a rate limit, by their index.
+    #[cfg(feature = "std")]
+    rate_limits: Vec<Option<Duration>>,
+    #[cfg(not(feature = "std"))]
+    rate_limits: [Option<Duration>; FSM_RATE_LIMITS_CAPACITY],
+    /// The retries taken by each region since it entered its retry loop.
+    #[cfg(feature = "std")]
+    attempts: Vec<u32>,
+    #[cfg(not(feature = "std"))]
+    attempts: [u32; FSM_TIMER_STATUS_CAPACITY],
+    /// The scheduled events and the time on the timers' clock at which they are due.
+    #[cfg(feature = "std")]
+    scheduled: Vec<(FsmScheduledEventId, Duration, <F as FsmBackend>::Events)>,
+    #[cfg(feature = "std")]
+    next_scheduled_id: u64
+}
+
+/// The bookkeeping of the timer requests before a step of a transactional machine, see `FsmTimerRequests::checkpoint`.
+pub struct FsmTimerRequestsCheckpoint<F: FsmBackend> {
+    requests: usize,
+    #[cfg(feature = "std")]
+    entered_at: Vec<Option<Duration>>,
+    #[cfg(not(feature = "std"))]
+    entered_at: [Option<Duration>; FSM_TIMER_STATUS_CAPACITY],
+    #[cfg(feature = "std")]
+    state_times: Vec<(FsmStateKind<F>, Duration)>,
+    #[cfg(not(feature = "std"))]
+    state_times: ArrayDeque<[(FsmStateKind<F>, Duration); FSM_STATE_TIMES_CAPACITY]>,
+    #[cfg(feature = "std")]
+    rate_limits: Vec<Option<Duration>>,
+    #[cfg(not(feature = "std"))]
+    rate_limits: [Option<Duration>; FSM_RATE_LIMITS_CAPACITY],
+    #[cfg(feature = "std")]
+    attempts: Vec<u32>,
+    #[cfg(not(feature = "std"))]
+    attempts: [u32; FSM_TIMER_STATUS_CAPACITY],
+    #[cfg(feature = "std")]
+    next_scheduled_id: u64
+}
+
+impl<F: FsmBackend> FsmTimerRequests<F> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            requests: VecDeque::new(),
+            #[cfg(not(feature = "std"))]
+            requests: ArrayDeque::new(),
+            now: None,
+            #[cfg(feature = "std")]
+            entered_at: Vec::new(),
+            #[cfg(not(feature = "std"))]
+            entered_at: [None; FSM_TIMER_STATUS_CAPACITY],
+            #[cfg(feature = "std")]
+            running: Vec::new(),
+            #[cfg(not(feature = "std"))]
+            running: ArrayDeque::new(),
+            #[cfg(feature = "std")]
+            state_times: Vec::new(),
+            #[cfg(not(feature = "std"))]
+            state_times: ArrayDeque::new(),
+            #[cfg(feature = "std")]
+            rate_limits: Vec::new(),
+            #[cfg(not(feature = "std"))]
+            rate_limits: [None; FSM_RATE_LIMITS_CAPACITY],
+            #[cfg(feature = "std")]
+            attempts: Vec::new(),
+            #[cfg(not(feature = "std"))]
+            attempts: [0; FSM_TIMER_STATUS_CAPACITY],
+            #[cfg(feature = "std")]
+            scheduled: Vec::new(),
+            #[cfg(feature = "std")]
+            next_scheduled_id: 0
+        }
+    }
+
+    /// Cancel the timer. It won't trigger again until its state is re-entered or the timer is restarted.
+    pub fn cancel<TTimer: FsmTimerId<F>>(&mut self) -> FsmResult<()> {
+        self.request(TTimer::timer_id(), FsmTimerRequest::Cancel)
+    }
+
+    /// Start the timer from the beginning, with its setup evaluated again. Ignored if the timer's
+    /// state isn't active.
+    pub fn restart<TTimer: FsmTimerId<F>>(&mut self) -> FsmResult<()> {
+        self.request(TTimer::timer_id(), FsmTimerRequest::Restart)
+    }
+
+    pub fn request(&mut self, timer_id: <F as FsmBackend>::Timers, request: FsmTimerRequest) -> FsmResult<()> {
+        #[cfg(feature = "std")]
+        {
+            self.requests.push_back((timer_id, request));
+            Ok(())
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            self.requests.push_back((timer_id, request))
+                .map_err(|_| crate::FsmTimerError::RequestsOverCapacity { capacity: FSM_TIMER_REQUESTS_CAPACITY }.into())
+        }
+    }
+
+    /// Enqueue the event once the delay has passed on the timers' clock, as read at the start of the
+    /// current dispatch. The event is released into the queue when the frontend dispatches the timer
+    /// events, the drivers of the timers wait for it with `FsmFrontend::next_scheduled_in`, like the ones of
+    /// `TimersTokio`. Fails with `FsmTimerError::NotSupported` if the timers don't have a clock.
+    #[cfg(feature = "std")]
+    pub fn enqueue_after<E>(&mut self, event: E, delay: Duration) -> FsmResult<FsmScheduledEventId>
+        where E: Into<<F as FsmBackend>::Events>
+    {
+        let now = self.now.ok_or(crate::FsmTimerError::NotSupported)?;
+        Ok(self.schedule_at(event.into(), now + delay))
+    }
+
+    /// Schedule the event for the time on the timers' clock.
+    #[cfg(feature = "std")]
+    pub fn schedule_at(&mut self, event: <F as FsmBackend>::Events, due: Duration) -> FsmScheduledEventId {
+        let id = FsmScheduledEventId(self.next_scheduled_id);
+        self.next_scheduled_id += 1;
+        self.scheduled.push((id, due, event));
+        id
+    }
+
+    /// Cancel the scheduled event. Returns `false` if it was already released or cancelled.
+    #[cfg(feature = "std")]
+    pub fn cancel_scheduled(&mut self, id: FsmScheduledEventId) -> bool {
+        match self.scheduled.iter().position(|(i, _, _)| *i == id) {
+            Some(idx) => {
+                self.scheduled.remove(idx);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Is the event still waiting for its time?
+    #[cfg(feature = "std")]
+    pub fn is_scheduled(&self, id: FsmScheduledEventId) -> bool {
+        self.scheduled.iter().any(|(i, _, _)| *i == id)
+    }
+
+    /// The earliest time at which a scheduled event is due.
+    #[cfg(feature = "std")]
+    pub fn next_scheduled_at(&self) -> Option<Duration> {
+        self.scheduled.iter().map(|(_, due, _)| *due).min()
+    }
+
+    /// Take the earliest scheduled event that is due at this time. The events that are due at the same
+    /// time are taken in the order in which they were scheduled.
+    #[cfg(feature = "std")]
+    pub fn take_due(&mut self, now: Duration) -> Option<<F as FsmBackend>::Events> {
+        let idx = self.scheduled.iter().enumerate()
+            .filter(|(_, (_, due, _))| *due <= now)
+            .min_by_key(|(_, (_, due, _))| *due)
+            .map(|(idx, _)| idx)?;
+        Some(self.scheduled.remove(idx).2)
+    }
+
+    /// Take the oldest pending request.
+    pub fn take(&mut self) -> Option<(<F as FsmBackend>::Timers, FsmTimerRequest)> {
+        self.requests.pop_front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Was the timer running at the start of the current dispatch? Always false with timers
+    /// that don't keep track of this.
+    pub fn is_running<TTimer: FsmTimerId<F>>(&self) -> bool {
+        let id = TTimer::timer_id();
+        self.running.contains(&id)
+    }
+
+    /// For how long has the current state of the region been active, measured at the start of the
+    /// current dispatch. `None` if the timers don't have a clock.
+    pub fn time_in_state(&self, region: FsmRegionId) -> Option<Duration> {
+        let entered_at = self.entered_at.get(region).copied().flatten()?;
+        let now = self.now?;
+        Some(now.checked_sub(entered_at).unwrap_or_default())
+    }
+
+    /// Read the clock and the running timers, before the event is dispatched.
+    pub fn refresh<T: FsmTimers<F>>(&mut self, timers: &T) {
+        self.now = timers.now();
+        self.running.clear();
+        for id in <F as FsmBackend>::Timers::iter() {
+            if timers.is_running(id.clone()) {
+                #[cfg(feature = "std")]
+                self.running.push(id);
+                #[cfg(not(feature = "std"))]
+                let _ = self.running.push_back(id);
+            }
+        }
+    }
+
+    /// Remember the time at which the region's state was entered.
+    pub fn state_entered(&mut self, region: FsmRegionId, now: Option<Duration>) {
+        #[cfg(feature = "std")]
+        if self.entered_at.len() <= region {
+            self.entered_at.resize(region + 1, None);
+        }
+
+        if let Some(entered_at) = self.entered_at.get_mut(region) {
+            *entered_at = now;
+        }
+    }
+
+    /// Add the time since the region's state was entered to the state's total. Returns the time of
+    /// this visit, or `None` if the timers don't have a clock.
+    pub fn state_exited(&mut self, region: FsmRegionId, state: FsmStateKind<F>, now: Option<Duration>) -> Option<Duration> {
+        let entered_at = self.state_entered_at(region)?;
+        let elapsed = now?.checked_sub(entered_at).unwrap_or_default();
+
+        match self.state_times.iter_mut().find(|(s, _)| *s == state) {
+            Some((_, total)) => *total += elapsed,
+            None => {
+                #[cfg(feature = "std")]
+                self.state_times.push((state, elapsed));
+                #[cfg(not(feature = "std"))]
+                let _ = self.state_times.push_back((state, elapsed));
+            }
+        }
+
+        Some(elapsed)
+    }
+
+    /// The time spent in the state over all of its completed visits, not including the current one.
+    pub fn state_total_time(&self, state: FsmStateKind<F>) -> Duration {
+        self.state_times.iter().find(|(s, _)| *s == state).map(|(_, t)| *t).unwrap_or_default()
+    }
+
+    /// The time at which the region's current state was entered.
+    pub fn state_entered_at(&self, region: FsmRegionId) -> Option<Duration> {
+        self.entered_at.get(region).copied().flatten()
+    }
+
+    /// Does the rate limit of the transition let the event through? Measured at the start of the current
+    /// dispatch, the events always pass without a clock. Doesn't record the event, see `rate_limit`.
+    pub fn rate_limit_passes(&self, index: usize, limit: FsmRateLimit) -> bool {
+        let (now, last) = match (self.now, self.rate_limits.get(index).copied().flatten()) {
+            (Some(now), Some(last)) => (now, last),
+            _ => return true
+        };
+
+        let window = match limit {
+            FsmRateLimit::Debounce(window) | FsmRateLimit::Throttle(window) => window
+        };
+        now.checked_sub(last).unwrap_or_default() >= window
+    }
+
+    /// Filter the event by the transition's rate limit, the index is assigned by the code generator. The
+    /// debounced transitions record all of their events, the throttled ones only those that pass.
+    pub fn rate_limit(&mut self, index: usize, limit: FsmRateLimit) -> bool {
+        let passes = self.rate_limit_passes(index, limit);
+        let record = match limit {
+            FsmRateLimit::Debounce(_) => true,
+            FsmRateLimit::Throttle(_) => passes
+        };
+
+        if let (true, Some(now)) = (record, self.now) {
+            #[cfg(feature = "std")]
+            if self.rate_limits.len() <= index {
+                self.rate_limits.resize(index + 1, None);
+            }
+
+            if let Some(last) = self.rate_limits.get_mut(index) {
+                *last = Some(now);
+            }
+        }
+
+        passes
+    }
+
+    /// The retries taken by the region since it entered its retry loop, the source and target states of its
+    /// transitions declared with `retry`.
+    pub fn attempts(&self, region: FsmRegionId) -> u32 {
+        self.attempts.get(region).copied().unwrap_or_default()
+    }
+
+    /// Can the region take another retry?
+    pub fn can_retry(&self, region: FsmRegionId, max: u32) -> bool {
+        self.attempts(region) < max
+    }
+
+    /// Count the retry, if the region can still take it. Fails with `FsmTimerError::RetriesOverCapacity` without
+    /// the `std` feature for the regions past the `FSM_TIMER_STATUS_CAPACITY`, their retries can't be counted.
+    pub fn retry(&mut self, region: FsmRegionId, max: u32) -> FsmResult<bool> {
+        if !self.can_retry(region, max) {
+            return Ok(false);
+        }
+
+        #[cfg(feature = "std")]
+        if self.attempts.len() <= region {
+            self.attempts.resize(region + 1, 0);
+        }
+
+        #[cfg(not(feature = "std"))]
+        if region >= FSM_TIMER_STATUS_CAPACITY {
+            return Err(crate::FsmTimerError::RetriesOverCapacity { capacity: FSM_TIMER_STATUS_CAPACITY }.into());
+        }
+
+        if let Some(attempts) = self.attempts.get_mut(region) {
+            *attempts += 1;
+        }
+
+        Ok(true)
+    }
+
+    /// The region left its retry loop.
+    pub fn reset_attempts(&mut self, region: FsmRegionId) {
+        if let Some(attempts) = self.attempts.get_mut(region) {
+            *attempts = 0;
+        }
+    }
+
+    /// Remember the timer requests, the times of the states, the rate limits and the retries, before a step of
+    /// a transactional machine.
+    pub fn checkpoint(&self) -> FsmTimerRequestsCheckpoint<F> {
+        FsmTimerRequestsCheckpoint {
+            requests: self.requests.len(),
+            entered_at: self.entered_at.clone(),
+            state_times: self.state_times.clone(),
+            rate_limits: self.rate_limits.clone(),
+            attempts: self.attempts.clone(),
+            #[cfg(feature = "std")]
+            next_scheduled_id: self.next_scheduled_id
+        }
+    }
+
+    /// Undo the failed step of a transactional machine. The timer requests and the scheduled events of the
+    /// step are dropped, the scheduled events that it cancelled stay cancelled.
+    pub fn rollback(&mut self, checkpoint: FsmTimerRequestsCheckpoint<F>) {
+        while self.requests.len() > checkpoint.requests {
+            self.requests.pop_back();
+        }
+        self.entered_at = checkpoint.entered_at;
+        self.state_times = checkpoint.state_times;
+        self.rate_limits = checkpoint.rate_limits;
+        self.attempts = checkpoint.attempts;
+
+        #[cfg(feature = "std")]
+        {
+            let next_scheduled_id = checkpoint.next_scheduled_id;
+            self.scheduled.retain(|(id, _, _)| id.0 < next_scheduled_id);
+        }
+    }
+}
+
+impl<F: FsmBackend> Default for FsmTimerRequests<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend> Debug for FsmTimerRequests<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.requests.iter()).finish()
+    }
+}
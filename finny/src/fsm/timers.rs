@@ -1,10 +1,11 @@
-use crate::{AllVariants, DispatchContext, FsmError, FsmEvent, FsmEventQueue, Inspect, lib::*};
+use crate::{AllVariants, DispatchContext, FsmError, FsmEventQueue, FsmTimerError, Inspect, lib::*};
 use crate::{FsmBackend, FsmResult};
 
 /// Associate some data with a specific timer ID.
 pub trait TimersStorage<FT, T> : Default
     where FT: AllVariants
 {
+    fn get_timer_storage(&self, id: &FT) -> &Option<T>;
     fn get_timer_storage_mut(&mut self, id: &FT) -> &mut Option<T>;
 }
 
@@ -19,16 +20,16 @@ pub struct TimerInstance<F>
 pub trait FsmTimer<F, S>
     where F: FsmBackend, Self: Default
 {
-    fn setup(ctx: &mut <F as FsmBackend>::Context, settings: &mut TimerFsmSettings);
+    fn setup(ctx: &mut <F as FsmBackend>::Context, state: &S, settings: &mut TimerFsmSettings);
     fn trigger(ctx: &<F as FsmBackend>::Context, state: &S) -> Option< <F as FsmBackend>::Events >;
 
     fn get_instance(&self) -> &Option<TimerInstance<F>>;
     fn get_instance_mut(&mut self) -> &mut Option<TimerInstance<F>>;
 
-    fn execute_on_enter<I: Inspect, T: FsmTimers<F>>(&mut self, id: F::Timers, ctx: &mut <F as FsmBackend>::Context, inspect: &mut I, timers: &mut T) {
+    fn execute_on_enter<I: Inspect, T: FsmTimers<F>>(&mut self, id: F::Timers, ctx: &mut <F as FsmBackend>::Context, state: &S, inspect: &mut I, timers: &mut T) {
         let log = inspect.for_timer::<F>(id.clone());
         let mut settings = TimerFsmSettings::default();
-        Self::setup(ctx, &mut settings);
+        Self::setup(ctx, state, &mut settings);
         if settings.enabled {
             match timers.create(id.clone(), &settings.to_timer_settings()) {
                 Ok(_) => {
@@ -65,6 +66,39 @@ pub trait FsmTimer<F, S>
         }
     }
 
+    /// Cancel the timer regardless of its settings, as requested by an action.
+    fn execute_cancel<I: Inspect, T: FsmTimers<F>>(&mut self, id: F::Timers, inspect: &mut I, timers: &mut T) {
+        let log = inspect.for_timer::<F>(id.clone());
+        match timers.cancel(id) {
+            Ok(_) => {
+                *self.get_instance_mut() = None;
+                log.info("Cancelled the timer.");
+            },
+            Err(ref e) => {
+                log.on_error("Failed to cancel the timer", e);
+            }
+        }
+    }
+
+    /// Cancel the timer if it was started, regardless of its settings, because the submachine of its state
+    /// was exited.
+    fn execute_cancel_nested<I: Inspect, T: FsmTimers<F>>(&mut self, id: F::Timers, inspect: &mut I, timers: &mut T) {
+        if self.get_instance().is_none() {
+            return;
+        }
+
+        let log = inspect.for_timer::<F>(id.clone());
+        match timers.cancel(id) {
+            Ok(_) => {
+                *self.get_instance_mut() = None;
+                log.info("Cancelled the timer, its submachine was exited.");
+            },
+            Err(ref e) => {
+                log.on_error("Failed to cancel the timer", e);
+            }
+        }
+    }
+
     fn execute_trigger<'a, 'b, 'c, 'd, Q, I, T>(id: F::Timers, context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, inspect: &mut I)
         where 
             Q: FsmEventQueue<F>,
@@ -79,8 +113,7 @@ pub trait FsmTimer<F, S>
             Some(_) => {                
                 match Self::trigger(&context.backend.context, context.backend.states.as_ref()) {
                     Some(ev) => {
-                        let inspect = inspect.new_event::<F>(&FsmEvent::Event(ev.clone()), &context.backend);
-                        match context.queue.enqueue(ev) {
+                        match crate::enqueue_inspected::<F, _, _, _>(context.queue, &inspect, ev) {
                             Ok(_) => {
                                 inspect.info("The event triggered by the timer was enqueued.");
                             },
@@ -94,13 +127,19 @@ pub trait FsmTimer<F, S>
 
             },
             None => {
-                let error = FsmError::TimerNotStarted;
+                let error = FsmError::Timer(FsmTimerError::NotStarted);
                 inspect.on_error("Timer hasn't been started.", &error);
             }
         }
     }
 }
 
+/// Maps the generated timer types to the timer IDs of the machine.
+pub trait FsmTimerId<F> where F: FsmBackend
+{
+    fn timer_id() -> <F as FsmBackend>::Timers;
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TimerFsmSettings {
     pub enabled: bool,
@@ -139,6 +178,12 @@ pub struct TimerSettings
     pub renew: bool
 }
 
+/// A monotonic clock, the time elapsed since an arbitrary, but fixed point in time. Drives the timers that
+/// are created with it and measures the time spent in the states. See `FsmClockStd` and `FsmClockManual`.
+pub trait FsmClock {
+    fn now(&self) -> Duration;
+}
+
 pub trait FsmTimers<F>
     where F: FsmBackend
 {
@@ -148,6 +193,17 @@ pub trait FsmTimers<F>
     /// Return the timer that was triggered. Poll this until it returns None. The events
     /// should be dequeued in a FIFO manner.
     fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers>;
+
+    /// Is the timer going to trigger? Timers that don't keep track of this report all of them as stopped.
+    fn is_running(&self, _id: <F as FsmBackend>::Timers) -> bool {
+        false
+    }
+
+    /// The time elapsed since an arbitrary, but fixed point in time, as measured by the timers. Used for
+    /// measuring how long the states have been active. `None` if the timers don't have a clock.
+    fn now(&self) -> Option<Duration> {
+        None
+    }
 }
 
 
@@ -163,11 +219,11 @@ impl<F> FsmTimers<F> for FsmTimersNull
     where F: FsmBackend
 {
     fn create(&mut self, _id: <F as FsmBackend>::Timers, _settings: &TimerSettings) -> FsmResult<()> {
-        Err(FsmError::NotSupported)
+        Err(FsmTimerError::NotSupported.into())
     }
 
     fn cancel(&mut self, _id: <F as FsmBackend>::Timers) -> FsmResult<()> {
-        Err(FsmError::NotSupported)
+        Err(FsmTimerError::NotSupported.into())
     }
 
     fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
@@ -176,6 +232,47 @@ impl<F> FsmTimers<F> for FsmTimersNull
 }
 
 
+/// Measures the time spent in the states with the clock, for the timers that don't have one, like
+/// `FsmTimersNull`. Available without `std`, with a clock like the microcontroller's tick counter.
+///
+/// Example : `Machine::new_with(context, queue, inspect, FsmTimersWithClock::new(FsmTimersNull, TickClock))`
+#[derive(Debug, Default, Clone)]
+pub struct FsmTimersWithClock<T, C> {
+    pub timers: T,
+    pub clock: C
+}
+
+impl<T, C> FsmTimersWithClock<T, C> {
+    pub fn new(timers: T, clock: C) -> Self {
+        FsmTimersWithClock { timers, clock }
+    }
+}
+
+impl<F, T, C> FsmTimers<F> for FsmTimersWithClock<T, C>
+    where F: FsmBackend, T: FsmTimers<F>, C: FsmClock
+{
+    fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &TimerSettings) -> FsmResult<()> {
+        self.timers.create(id, settings)
+    }
+
+    fn cancel(&mut self, id: <F as FsmBackend>::Timers) -> FsmResult<()> {
+        self.timers.cancel(id)
+    }
+
+    fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        self.timers.get_triggered_timer()
+    }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.is_running(id)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.clock.now())
+    }
+}
+
+
 pub struct FsmTimersSub<'a, T, F, FSub>
     where
         F: FsmBackend,
@@ -205,4 +302,12 @@ impl<'a, T, F, FSub> FsmTimers<FSub> for FsmTimersSub<'a, T, F, FSub>
         // todo: not needed, split the trait
         None
     }
+
+    fn is_running(&self, id: <FSub as FsmBackend>::Timers) -> bool {
+        self.parent.is_running(id.into())
+    }
+
+    fn now(&self) -> Option<Duration> {
+        self.parent.now()
+    }
 }
\ No newline at end of file
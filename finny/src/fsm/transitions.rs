@@ -1,6 +1,6 @@
 //! All of these traits will be implemented by the procedural code generator.
 
-use crate::{FsmBackendImpl, FsmDispatchResult, FsmEventQueueSub, FsmTimers, FsmTimersSub, lib::*};
+use crate::{FsmBackendAsync, FsmBackendImpl, FsmDispatchResult, FsmResult, FsmSubDispatch, FsmTimers, dispatch_to_submachine_async, lib::*};
 use crate::{DispatchContext, EventContext, FsmBackend, FsmCurrentState, FsmEvent, FsmEventQueue, FsmRegionId, FsmStateTransitionAsMut, FsmStates, Inspect};
 
 use super::inspect::InspectFsmEvent;
@@ -11,19 +11,39 @@ pub trait FsmState<F: FsmBackend> where Self: Sized {
     fn on_entry<'a, Q: FsmEventQueue<F>>(&mut self, context: &mut EventContext<'a, F, Q>);
     /// Action that is executed whenever this state is being exited.
     fn on_exit<'a, Q: FsmEventQueue<F>>(&mut self, context: &mut EventContext<'a, F, Q>);
+    /// Asynchronous entry action, awaited by the async dispatch path after the synchronous one.
+    #[allow(async_fn_in_trait)]
+    async fn on_entry_async<'a, Q: FsmEventQueue<F>>(&mut self, context: &mut EventContext<'a, F, Q>) {
+        self.on_entry(context);
+    }
+    /// Asynchronous exit action, awaited by the async dispatch path after the synchronous one.
+    #[allow(async_fn_in_trait)]
+    async fn on_exit_async<'a, Q: FsmEventQueue<F>>(&mut self, context: &mut EventContext<'a, F, Q>) {
+        self.on_exit(context);
+    }
+    /// Entry action that also receives the event that triggered the transition, executed after `on_entry`. There is
+    /// no event when the state is re-entered by resuming a submachine's history.
+    fn on_entry_with_event<'a, Q: FsmEventQueue<F>>(&mut self, _event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, _context: &mut EventContext<'a, F, Q>) { }
+    /// Exit action that also receives the event that triggered the transition, executed after `on_exit`.
+    fn on_exit_with_event<'a, Q: FsmEventQueue<F>>(&mut self, _event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, _context: &mut EventContext<'a, F, Q>) { }
 
-    fn execute_on_entry<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId) 
+    fn execute_on_entry<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId, event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>) 
         where Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::States: AsMut<Self>, T: FsmTimers<F>
     {
+        context.backend.timer_requests.state_entered(region, context.timers.now());
+
         let mut event_context = EventContext {
             context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
             region,
             queue: context.queue
         };
 
         // inspection
         {
-            context.inspect.on_state_enter::<Self>();
+            context.inspect.on_state_enter::<Self>(<Self>::fsm_state_name());
 
             let kind = <Self>::fsm_state();
             let ev = InspectFsmEvent::StateEnter(kind);
@@ -32,23 +52,33 @@ pub trait FsmState<F: FsmBackend> where Self: Sized {
 
         let state: &mut Self = context.backend.states.as_mut();
         state.on_entry(&mut event_context);
+        state.on_entry_with_event(event, &mut event_context);
     }
 
-    fn execute_on_exit<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId) 
+    fn execute_on_exit<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId, event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>) 
         where Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::States: AsMut<Self>, T: FsmTimers<F>
     {
         let mut event_context = EventContext {
             context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
             queue: context.queue,
             region
         };
 
         let state: &mut Self = context.backend.states.as_mut();
         state.on_exit(&mut event_context);
+        state.on_exit_with_event(event, &mut event_context);
+
+        let elapsed = context.backend.timer_requests.state_exited(region, <Self>::fsm_state(), context.timers.now());
 
         // inspection
         {
-            context.inspect.on_state_exit::<Self>();
+            context.inspect.on_state_exit::<Self>(<Self>::fsm_state_name());
+            if let Some(elapsed) = elapsed {
+                context.inspect.on_state_time::<F, Self>(<Self>::fsm_state_name(), elapsed);
+            }
 
             let kind = <Self>::fsm_state();
             let ev = InspectFsmEvent::StateExit(kind);
@@ -56,27 +86,153 @@ pub trait FsmState<F: FsmBackend> where Self: Sized {
         }        
     }
 
+    #[allow(async_fn_in_trait)]
+    async fn execute_on_entry_async<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId, event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>)
+        where Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::States: AsMut<Self>, T: FsmTimers<F>
+    {
+        context.backend.timer_requests.state_entered(region, context.timers.now());
+
+        let mut event_context = EventContext {
+            context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
+            region,
+            queue: context.queue
+        };
+
+        // inspection
+        {
+            context.inspect.on_state_enter::<Self>(<Self>::fsm_state_name());
+
+            let kind = <Self>::fsm_state();
+            let ev = InspectFsmEvent::StateEnter(kind);
+            context.inspect.on_event(&ev);
+        }
+
+        let state: &mut Self = context.backend.states.as_mut();
+        state.on_entry_async(&mut event_context).await;
+        state.on_entry_with_event(event, &mut event_context);
+    }
+
+    #[allow(async_fn_in_trait)]
+    async fn execute_on_exit_async<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, region: FsmRegionId, event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>)
+        where Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::States: AsMut<Self>, T: FsmTimers<F>
+    {
+        let mut event_context = EventContext {
+            context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
+            queue: context.queue,
+            region
+        };
+
+        let state: &mut Self = context.backend.states.as_mut();
+        state.on_exit_async(&mut event_context).await;
+        state.on_exit_with_event(event, &mut event_context);
+
+        let elapsed = context.backend.timer_requests.state_exited(region, <Self>::fsm_state(), context.timers.now());
+
+        // inspection
+        {
+            context.inspect.on_state_exit::<Self>(<Self>::fsm_state_name());
+            if let Some(elapsed) = elapsed {
+                context.inspect.on_state_time::<F, Self>(<Self>::fsm_state_name(), elapsed);
+            }
+
+            let kind = <Self>::fsm_state();
+            let ev = InspectFsmEvent::StateExit(kind);
+            context.inspect.on_event(&ev);
+        }
+    }
+
     fn fsm_state() -> <<F as FsmBackend>::States as FsmStates<F>>::StateKind;
+
+    /// The name of the state for the inspection, declared with `named` in the builder. Otherwise the name of
+    /// its type, without the module path.
+    fn fsm_state_name() -> &'static str {
+        crate::short_type_name::<Self>()
+    }
 }
 
+/// A state that the submachine exposes as its entry point, declared with `entry_point`. The parent machine
+/// can enter the submachine at it instead of the initial state.
+pub trait FsmEntryPoint<F: FsmBackend>: FsmState<F> { }
+
 /// Check if this transition is allowed to be entered.
 pub trait FsmTransitionGuard<F: FsmBackend, E> {
     /// Return a boolean value whether this transition is usable at the moment. The check shouln't mutate any structures.
-    fn guard<'a, Q: FsmEventQueue<F>>(event: &E, context: &EventContext<'a, F, Q>, states: &'a <F as FsmBackend>::States) -> bool;
+    /// A failed guard aborts the dispatch of the event.
+    fn guard<'a, Q: FsmEventQueue<F>>(event: &E, context: &EventContext<'a, F, Q>, states: &'a <F as FsmBackend>::States) -> FsmResult<bool>;
 
-    fn execute_guard<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId, inspect_event_ctx: &mut I) -> bool
+    /// The name of the shared guard function, or the transition's type for the closures.
+    fn guard_name() -> &'static str {
+        crate::short_type_name::<Self>()
+    }
+
+    /// Evaluate the guard and report its result to the inspection. The guards combined with `and_guard`,
+    /// `or_guard` and `not` report each of their evaluated terms instead, under the terms' names.
+    fn guard_inspected<'a, Q: FsmEventQueue<F>, I: Inspect>(event: &E, context: &EventContext<'a, F, Q>, states: &'a <F as FsmBackend>::States, inspect: &I) -> FsmResult<bool>
+        where Self: Sized
+    {
+        let guard_result = Self::guard(event, context, states)?;
+        inspect.on_guard::<Self>(Self::guard_name(), guard_result);
+        Ok(guard_result)
+    }
+
+    fn execute_guard<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmResult<bool>
         where I: Inspect, Self: Sized, T: FsmTimers<F>
     {
         let event_context = EventContext {
             context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
             queue: context.queue,
             region
         };
 
-        let guard_result = Self::guard(event, &event_context, &context.backend.states);
+        let guard_result = Self::guard_inspected(event, &event_context, &context.backend.states, inspect_event_ctx);
+        if let Err(ref e) = guard_result {
+            inspect_event_ctx.on_error("The guard failed", e);
+        }
+        guard_result
+    }
 
-        inspect_event_ctx.on_guard::<Self>(guard_result);
+    /// Asynchronous variant of the guard, used by the async dispatch path.
+    #[allow(async_fn_in_trait)]
+    async fn guard_async<'a, Q: FsmEventQueue<F>>(event: &E, context: &EventContext<'a, F, Q>, states: &'a <F as FsmBackend>::States) -> FsmResult<bool> {
+        Self::guard(event, context, states)
+    }
 
+    /// Asynchronous variant of `guard_inspected`.
+    #[allow(async_fn_in_trait)]
+    async fn guard_async_inspected<'a, Q: FsmEventQueue<F>, I: Inspect>(event: &E, context: &EventContext<'a, F, Q>, states: &'a <F as FsmBackend>::States, inspect: &I) -> FsmResult<bool>
+        where Self: Sized
+    {
+        let guard_result = Self::guard_async(event, context, states).await?;
+        inspect.on_guard::<Self>(Self::guard_name(), guard_result);
+        Ok(guard_result)
+    }
+
+    #[allow(async_fn_in_trait)]
+    async fn execute_guard_async<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmResult<bool>
+        where I: Inspect, Self: Sized, T: FsmTimers<F>
+    {
+        let event_context = EventContext {
+            context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
+            queue: context.queue,
+            region
+        };
+
+        let guard_result = Self::guard_async_inspected(event, &event_context, &context.backend.states, inspect_event_ctx).await;
+        if let Err(ref e) = guard_result {
+            inspect_event_ctx.on_error("The guard failed", e);
+        }
         guard_result
     }
 }
@@ -85,9 +241,9 @@ pub trait FsmTransitionGuard<F: FsmBackend, E> {
 /// The transition that starts the machine, triggered using the `start()` method.
 pub trait FsmTransitionFsmStart<F: FsmBackend, TInitialState> {
     fn execute_transition<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, 
-        _fsm_event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>,
+        fsm_event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>,
         region: FsmRegionId,
-        inspect_event_ctx: &mut I)
+        inspect_event_ctx: &mut I) -> FsmDispatchResult
         where
             I: Inspect,
             TInitialState: FsmState<F>,
@@ -96,13 +252,14 @@ pub trait FsmTransitionFsmStart<F: FsmBackend, TInitialState> {
             Self: Sized,
             T: FsmTimers<F>
     {
-        let ctx = inspect_event_ctx.for_transition::<Self>();
-        ctx.on_state_enter::<TInitialState>();
-        
-        <TInitialState>::execute_on_entry(context, region);
+        let mut ctx = inspect_event_ctx.for_transition::<Self>();
+
+        <TInitialState>::execute_on_entry(&mut context.with_inspect(&mut ctx), region, Some(fsm_event));
         
         let cs = context.backend.current_states.as_mut();
         cs[region] = FsmCurrentState::State(<TInitialState>::fsm_state());
+
+        Ok(())
     }
 
     /// Executed after the transition on the parent FSM (F) and triggers the first `start()` call if necessary. Subsequent
@@ -119,31 +276,56 @@ pub trait FsmTransitionFsmStart<F: FsmBackend, TInitialState> {
             T: FsmTimers<F>,
             <F as FsmBackend>::Timers: From<<TInitialState as FsmBackend>::Timers>
     {
-        let sub_backend: &mut TInitialState = context.backend.states.as_mut();
-        let states = sub_backend.get_current_states();
+        let mut sub = FsmSubDispatch::<F, TInitialState, Q, I, T>::new(context, inspect_event_ctx);
+        let states = sub.backend.get_current_states();
         if FsmCurrentState::all_stopped(states.as_ref()) {
-            let mut queue_adapter = FsmEventQueueSub {
-                parent: context.queue,
-                _parent_fsm: PhantomData::<F>::default(),
-                _sub_fsm: PhantomData::<TInitialState>::default()
-            };
+            return TInitialState::dispatch_event(sub.context(), FsmEvent::Start);
+        }
 
-            let mut timers_adapter = FsmTimersSub {
-                parent: context.timers,
-                _parent_fsm: core::marker::PhantomData::<F>::default(),
-                _sub_fsm: core::marker::PhantomData::<TInitialState>::default()
-            };
+        Ok(())
+    }
+
+    #[allow(async_fn_in_trait)]
+    async fn execute_transition_async<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>,
+        fsm_event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>,
+        region: FsmRegionId,
+        inspect_event_ctx: &mut I) -> FsmDispatchResult
+        where
+            I: Inspect,
+            TInitialState: FsmState<F>,
+            <F as FsmBackend>::States: AsMut<TInitialState>,
+            <F as FsmBackend>::States: AsRef<TInitialState>,
+            Self: Sized,
+            T: FsmTimers<F>
+    {
+        let mut ctx = inspect_event_ctx.for_transition::<Self>();
 
-            let mut inspect = inspect_event_ctx.for_sub_machine::<TInitialState>();
+        <TInitialState>::execute_on_entry_async(&mut context.with_inspect(&mut ctx), region, Some(fsm_event)).await;
 
-            let sub_dispatch_context = DispatchContext {
-                backend: sub_backend,
-                inspect: &mut inspect,
-                queue: &mut queue_adapter,
-                timers: &mut timers_adapter
-            };
+        let cs = context.backend.current_states.as_mut();
+        cs[region] = FsmCurrentState::State(<TInitialState>::fsm_state());
+
+        Ok(())
+    }
 
-            return TInitialState::dispatch_event(sub_dispatch_context, FsmEvent::Start);
+    #[allow(async_fn_in_trait)]
+    async fn execute_on_sub_entry_async<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, _region: FsmRegionId, inspect_event_ctx: &mut I)
+        -> FsmDispatchResult
+        where
+            TInitialState: FsmBackendAsync,
+            Q: FsmEventQueue<F>,
+            I: Inspect,
+            <F as FsmBackend>::Events: From<<TInitialState as FsmBackend>::Events>,
+            <F as FsmBackend>::States: AsMut<TInitialState>,
+            TInitialState: DerefMut<Target = FsmBackendImpl<TInitialState>>,
+            T: FsmTimers<F>,
+            <F as FsmBackend>::Timers: From<<TInitialState as FsmBackend>::Timers>
+    {
+        let sub_backend: &mut TInitialState = context.backend.states.as_mut();
+        sub_backend.dispatch_id = context.backend.dispatch_id;
+        let states = sub_backend.get_current_states();
+        if FsmCurrentState::all_stopped(states.as_ref()) {
+            return dispatch_to_submachine_async::<_, TInitialState, _, _, _>(context, FsmEvent::Start, inspect_event_ctx).await;
         }
 
         Ok(())
@@ -153,9 +335,15 @@ pub trait FsmTransitionFsmStart<F: FsmBackend, TInitialState> {
 /// A transition's action that operates on both the exit and entry states.
 pub trait FsmTransitionAction<F: FsmBackend, E, TStateFrom, TStateTo> {
     /// This action is executed after the first state's exit event, and just before the second event's entry action. It can mutate both states.
-    fn action<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, from: &mut TStateFrom, to: &mut TStateTo);
+    /// A failed action aborts the transition, the first state is entered again and stays the current state, the second state isn't entered.
+    fn action<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, from: &mut TStateFrom, to: &mut TStateTo) -> FsmDispatchResult;
+
+    /// The name of the shared action function, or the transition's type for the closures.
+    fn action_name() -> &'static str {
+        crate::short_type_name::<Self>()
+    }
 
-    fn execute_transition<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId, inspect_event_ctx: &mut I)
+    fn execute_transition<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, fsm_event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmDispatchResult
         where 
             I: Inspect,
             <F as FsmBackend>::States: FsmStateTransitionAsMut<TStateFrom, TStateTo>,
@@ -165,28 +353,40 @@ pub trait FsmTransitionAction<F: FsmBackend, E, TStateFrom, TStateTo> {
             TStateTo: FsmState<F>, Self: Sized,
             T: FsmTimers<F>
     {
-        let inspect_ctx = inspect_event_ctx.for_transition::<Self>();
+        let mut inspect_ctx = inspect_event_ctx.for_transition::<Self>();
 
-        <TStateFrom>::execute_on_exit(context, region);
+        <TStateFrom>::execute_on_exit(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event);
         
         // transition action
         {
-            inspect_ctx.on_action::<Self>();
+            inspect_ctx.on_action::<Self>(Self::action_name());
 
             let mut event_context = EventContext {
                 context: &mut context.backend.context,
+                timers: &mut context.backend.timer_requests,
+                outputs: &mut context.backend.outputs,
+                resources: context.resources.as_deref_mut(),
                 queue: context.queue,
                 region
             };        
             let states: (&mut TStateFrom, &mut TStateTo) = context.backend.states.as_state_transition_mut();
-            Self::action(event, &mut event_context, states.0, states.1);
+            let result = Self::action(event, &mut event_context, states.0, states.1);
+            inspect_ctx.on_action_done::<Self>(Self::action_name());
+            if let Err(e) = result {
+                inspect_ctx.on_error("The transition's action failed", &e);
+                // the exited state stays the current one
+                <TStateFrom>::execute_on_entry(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event);
+                return Err(e);
+            }
         }
         
 
-        <TStateTo>::execute_on_entry(context, region);
+        <TStateTo>::execute_on_entry(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event);
 
         let cs = context.backend.current_states.as_mut();
         cs[region] = FsmCurrentState::State(<TStateTo>::fsm_state());
+
+        Ok(())
     }
 
     /// Executed after the transition on the parent FSM (F) and triggers the first `start()` call if necessary. Subsequent
@@ -203,31 +403,85 @@ pub trait FsmTransitionAction<F: FsmBackend, E, TStateFrom, TStateTo> {
             T: FsmTimers<F>,
             <F as FsmBackend>::Timers: From<<TStateTo as FsmBackend>::Timers>
     {
-        let sub_backend: &mut TStateTo = context.backend.states.as_mut();
-        let states = sub_backend.get_current_states();
+        let mut sub = FsmSubDispatch::<F, TStateTo, Q, I, T>::new(context, inspect_event_ctx);
+        let states = sub.backend.get_current_states();
         if FsmCurrentState::all_stopped(states.as_ref()) {
-            let mut queue_adapter = FsmEventQueueSub {
-                parent: context.queue,
-                _parent_fsm: PhantomData::<F>::default(),
-                _sub_fsm: PhantomData::<TStateTo>::default()
-            };
+            return TStateTo::dispatch_event(sub.context(), FsmEvent::Start);
+        }
 
-            let mut timers_adapter = FsmTimersSub {
-                parent: context.timers,
-                _parent_fsm: core::marker::PhantomData::<F>::default(),
-                _sub_fsm: core::marker::PhantomData::<TStateTo>::default()
-            };
+        Ok(())
+    }
 
-            let mut inspect = inspect_event_ctx.for_sub_machine::<TStateTo>();
+    /// Asynchronous variant of the action, awaited by the async dispatch path.
+    #[allow(async_fn_in_trait)]
+    async fn action_async<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, from: &mut TStateFrom, to: &mut TStateTo) -> FsmDispatchResult {
+        Self::action(event, context, from, to)
+    }
 
-            let sub_dispatch_context = DispatchContext {
-                backend: sub_backend,
-                inspect: &mut inspect,
-                queue: &mut queue_adapter,
-                timers: &mut timers_adapter
+    #[allow(async_fn_in_trait)]
+    async fn execute_transition_async<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, fsm_event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmDispatchResult
+        where
+            I: Inspect,
+            <F as FsmBackend>::States: FsmStateTransitionAsMut<TStateFrom, TStateTo>,
+            <F as FsmBackend>::States: AsMut<TStateFrom>,
+            <F as FsmBackend>::States: AsMut<TStateTo>,
+            TStateFrom: FsmState<F>,
+            TStateTo: FsmState<F>, Self: Sized,
+            T: FsmTimers<F>
+    {
+        let mut inspect_ctx = inspect_event_ctx.for_transition::<Self>();
+
+        <TStateFrom>::execute_on_exit_async(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event).await;
+
+        // transition action
+        {
+            inspect_ctx.on_action::<Self>(Self::action_name());
+
+            let mut event_context = EventContext {
+                context: &mut context.backend.context,
+                timers: &mut context.backend.timer_requests,
+                outputs: &mut context.backend.outputs,
+                resources: context.resources.as_deref_mut(),
+                queue: context.queue,
+                region
             };
+            let states: (&mut TStateFrom, &mut TStateTo) = context.backend.states.as_state_transition_mut();
+            let result = Self::action_async(event, &mut event_context, states.0, states.1).await;
+            inspect_ctx.on_action_done::<Self>(Self::action_name());
+            if let Err(e) = result {
+                inspect_ctx.on_error("The transition's action failed", &e);
+                // the exited state stays the current one
+                <TStateFrom>::execute_on_entry_async(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event).await;
+                return Err(e);
+            }
+        }
+
+        <TStateTo>::execute_on_entry_async(&mut context.with_inspect(&mut inspect_ctx), region, fsm_event).await;
+
+        let cs = context.backend.current_states.as_mut();
+        cs[region] = FsmCurrentState::State(<TStateTo>::fsm_state());
+
+        Ok(())
+    }
 
-            return TStateTo::dispatch_event(sub_dispatch_context, FsmEvent::Start);
+    #[allow(async_fn_in_trait)]
+    async fn execute_on_sub_entry_async<'a, 'b, 'c, 'd, Q, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, _region: FsmRegionId, inspect_event_ctx: &mut I)
+        -> FsmDispatchResult
+        where
+            TStateTo: FsmBackendAsync,
+            Q: FsmEventQueue<F>,
+            I: Inspect,
+            <F as FsmBackend>::Events: From<<TStateTo as FsmBackend>::Events>,
+            <F as FsmBackend>::States: AsMut<TStateTo>,
+            TStateTo: DerefMut<Target = FsmBackendImpl<TStateTo>>,
+            T: FsmTimers<F>,
+            <F as FsmBackend>::Timers: From<<TStateTo as FsmBackend>::Timers>
+    {
+        let sub_backend: &mut TStateTo = context.backend.states.as_mut();
+        sub_backend.dispatch_id = context.backend.dispatch_id;
+        let states = sub_backend.get_current_states();
+        if FsmCurrentState::all_stopped(states.as_ref()) {
+            return dispatch_to_submachine_async::<_, TStateTo, _, _, _>(context, FsmEvent::Start, inspect_event_ctx).await;
         }
 
         Ok(())
@@ -236,41 +490,118 @@ pub trait FsmTransitionAction<F: FsmBackend, E, TStateFrom, TStateTo> {
 
 /// An internal or self action can only mutate itself.
 pub trait FsmAction<F: FsmBackend, E, State> {
-    /// This action is executed as part of an internal or self transition.
-    fn action<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, state: &mut State);
+    /// This action is executed as part of an internal or self transition. A failed action of a self transition
+    /// aborts it, the state isn't re-entered.
+    fn action<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, state: &mut State) -> FsmDispatchResult;
     /// Is this a self transition which should trigger the state's exit and entry actions?
     fn should_trigger_state_actions() -> bool;
 
-    fn execute_action<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId)
+    /// The name of the shared action function, or the transition's type for the closures.
+    fn action_name() -> &'static str {
+        crate::short_type_name::<Self>()
+    }
+
+    fn execute_action<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId) -> FsmDispatchResult
         where <F as FsmBackend>::States: AsMut<State>, I: Inspect, T: FsmTimers<F>
     {
         let mut event_context = EventContext {
             context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
             queue: context.queue,
             region
         };
 
         let state: &mut State = context.backend.states.as_mut();
 
-        Self::action(event, &mut event_context, state);
+        Self::action(event, &mut event_context, state)
     }
 
-    fn execute_transition<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId, inspect_event_ctx: &mut I)
+    fn execute_transition<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, fsm_event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmDispatchResult
         where I: Inspect,
             State: FsmState<F>,
             <F as FsmBackend>::States: AsMut<State>, Self: Sized,
             T: FsmTimers<F>
     {
-        let _ctx = inspect_event_ctx.for_transition::<Self>();
+        let mut ctx = inspect_event_ctx.for_transition::<Self>();
 
         if Self::should_trigger_state_actions() {
-            <State>::execute_on_exit(context, region);
+            <State>::execute_on_exit(&mut context.with_inspect(&mut ctx), region, fsm_event);
         }
 
-        Self::execute_action(context, event, region);
+        ctx.on_action::<Self>(Self::action_name());
+        let result = Self::execute_action(context, event, region);
+        ctx.on_action_done::<Self>(Self::action_name());
+        if let Err(e) = result {
+            ctx.on_error("The action failed", &e);
+            // the exited state stays the current one
+            if Self::should_trigger_state_actions() {
+                <State>::execute_on_entry(&mut context.with_inspect(&mut ctx), region, fsm_event);
+            }
+            return Err(e);
+        }
 
         if Self::should_trigger_state_actions() {
-            <State>::execute_on_entry(context, region);
+            <State>::execute_on_entry(&mut context.with_inspect(&mut ctx), region, fsm_event);
         }
+
+        Ok(())
+    }
+
+    /// Asynchronous variant of the action, awaited by the async dispatch path.
+    #[allow(async_fn_in_trait)]
+    async fn action_async<'a, Q: FsmEventQueue<F>>(event: &E, context: &mut EventContext<'a, F, Q>, state: &mut State) -> FsmDispatchResult {
+        Self::action(event, context, state)
     }
-}
\ No newline at end of file
+
+    #[allow(async_fn_in_trait)]
+    async fn execute_action_async<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, region: FsmRegionId) -> FsmDispatchResult
+        where <F as FsmBackend>::States: AsMut<State>, I: Inspect, T: FsmTimers<F>
+    {
+        let mut event_context = EventContext {
+            context: &mut context.backend.context,
+            timers: &mut context.backend.timer_requests,
+            outputs: &mut context.backend.outputs,
+            resources: context.resources.as_deref_mut(),
+            queue: context.queue,
+            region
+        };
+
+        let state: &mut State = context.backend.states.as_mut();
+
+        Self::action_async(event, &mut event_context, state).await
+    }
+
+    #[allow(async_fn_in_trait)]
+    async fn execute_transition_async<'a, 'b, 'c, 'd, Q: FsmEventQueue<F>, I, T>(context: &'d mut DispatchContext<'a, 'b, 'c, F, Q, I, T>, event: &E, fsm_event: Option<&FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>, region: FsmRegionId, inspect_event_ctx: &mut I) -> FsmDispatchResult
+        where I: Inspect,
+            State: FsmState<F>,
+            <F as FsmBackend>::States: AsMut<State>, Self: Sized,
+            T: FsmTimers<F>
+    {
+        let mut ctx = inspect_event_ctx.for_transition::<Self>();
+
+        if Self::should_trigger_state_actions() {
+            <State>::execute_on_exit_async(&mut context.with_inspect(&mut ctx), region, fsm_event).await;
+        }
+
+        ctx.on_action::<Self>(Self::action_name());
+        let result = Self::execute_action_async(context, event, region).await;
+        ctx.on_action_done::<Self>(Self::action_name());
+        if let Err(e) = result {
+            ctx.on_error("The action failed", &e);
+            // the exited state stays the current one
+            if Self::should_trigger_state_actions() {
+                <State>::execute_on_entry_async(&mut context.with_inspect(&mut ctx), region, fsm_event).await;
+            }
+            return Err(e);
+        }
+
+        if Self::should_trigger_state_actions() {
+            <State>::execute_on_entry_async(&mut context.with_inspect(&mut ctx), region, fsm_event).await;
+        }
+
+        Ok(())
+    }
+}
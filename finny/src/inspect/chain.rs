@@ -1,9 +1,11 @@
-use crate::{FsmBackend, FsmBackendImpl, FsmEvent, Inspect, InspectEvent, InspectFsmEvent};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent};
 use core::any::Any;
 use core::fmt::Debug;
+use core::time::Duration;
 use super::{null::InspectNull};
 
-
+/// Runs two inspectors side by side, every inspection call is forwarded to both of them. Longer chains
+/// can be built with `add_inspect`. Pairs of inspectors, `(A, B)`, can also be used directly.
 pub struct InspectChain<A, B>
 where A: Inspect, B: Inspect
 {
@@ -51,6 +53,23 @@ impl<A, B> Inspect for InspectChain<A, B>
         }
     }
 
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm: &FsmBackendImpl<F>) -> Self {
+        Self {
+            a: self.a.new_borrowed_event(event, fsm),
+            b: self.b.new_borrowed_event(event, fsm)
+        }
+    }
+
+    fn on_unhandled_event(&self) {
+        self.a.on_unhandled_event();
+        self.b.on_unhandled_event();
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        self.a.on_queue_full(capacity, dropped);
+        self.b.on_queue_full(capacity, dropped);
+    }
+
     fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
         self.a.event_done(fsm);
         self.b.event_done(fsm);
@@ -77,24 +96,34 @@ impl<A, B> Inspect for InspectChain<A, B>
         }
     }
 
-    fn on_guard<T>(&self, guard_result: bool) {
-        self.a.on_guard::<T>(guard_result);
-        self.b.on_guard::<T>(guard_result);
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        self.a.on_guard::<T>(guard, guard_result);
+        self.b.on_guard::<T>(guard, guard_result);
     }
 
-    fn on_state_enter<S>(&self) {
-        self.a.on_state_enter::<S>();
-        self.b.on_state_enter::<S>();
+    fn on_state_enter<S>(&self, state: &'static str) {
+        self.a.on_state_enter::<S>(state);
+        self.b.on_state_enter::<S>(state);
     }
 
-    fn on_state_exit<S>(&self) {
-        self.a.on_state_exit::<S>();
-        self.b.on_state_exit::<S>();
+    fn on_state_exit<S>(&self, state: &'static str) {
+        self.a.on_state_exit::<S>(state);
+        self.b.on_state_exit::<S>(state);
     }
 
-    fn on_action<S>(&self) {
-        self.a.on_action::<S>();
-        self.b.on_action::<S>();
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        self.a.on_state_time::<F, S>(state, elapsed);
+        self.b.on_state_time::<F, S>(state, elapsed);
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        self.a.on_action::<S>(action);
+        self.b.on_action::<S>(action);
+    }
+
+    fn on_action_done<S>(&self, action: &'static str) {
+        self.a.on_action_done::<S>(action);
+        self.b.on_action_done::<S>(action);
     }
 
     fn on_error<E>(&self, msg: &str, error: &E) where E: core::fmt::Debug {
@@ -115,4 +144,100 @@ impl<A, B> InspectEvent for InspectChain<A, B>
         self.a.on_event(event);
         self.b.on_event(event);
     }
-}
\ No newline at end of file
+}
+
+impl<A, B> From<(A, B)> for InspectChain<A, B>
+    where A: Inspect, B: Inspect
+{
+    fn from((a, b): (A, B)) -> Self {
+        InspectChain::new_pair(a, b)
+    }
+}
+
+impl<A, B> Inspect for (A, B)
+    where A: Inspect, B: Inspect
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        (self.0.new_event(event, fsm), self.1.new_event(event, fsm))
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm: &FsmBackendImpl<F>) -> Self {
+        (self.0.new_borrowed_event(event, fsm), self.1.new_borrowed_event(event, fsm))
+    }
+
+    fn on_unhandled_event(&self) {
+        self.0.on_unhandled_event();
+        self.1.on_unhandled_event();
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        self.0.on_queue_full(capacity, dropped);
+        self.1.on_queue_full(capacity, dropped);
+    }
+
+    fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
+        self.0.event_done(fsm);
+        self.1.event_done(fsm);
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        (self.0.for_transition::<T>(), self.1.for_transition::<T>())
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        (self.0.for_sub_machine::<FSub>(), self.1.for_sub_machine::<FSub>())
+    }
+
+    fn for_timer<F>(&self, timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        (self.0.for_timer::<F>(timer_id.clone()), self.1.for_timer::<F>(timer_id))
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        self.0.on_guard::<T>(guard, guard_result);
+        self.1.on_guard::<T>(guard, guard_result);
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        self.0.on_state_enter::<S>(state);
+        self.1.on_state_enter::<S>(state);
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        self.0.on_state_exit::<S>(state);
+        self.1.on_state_exit::<S>(state);
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        self.0.on_state_time::<F, S>(state, elapsed);
+        self.1.on_state_time::<F, S>(state, elapsed);
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        self.0.on_action::<S>(action);
+        self.1.on_action::<S>(action);
+    }
+
+    fn on_action_done<S>(&self, action: &'static str) {
+        self.0.on_action_done::<S>(action);
+        self.1.on_action_done::<S>(action);
+    }
+
+    fn on_error<E>(&self, msg: &str, error: &E) where E: core::fmt::Debug {
+        self.0.on_error(msg, error);
+        self.1.on_error(msg, error);
+    }
+
+    fn info(&self, msg: &str) {
+        self.0.info(msg);
+        self.1.info(msg);
+    }
+}
+
+impl<A, B> InspectEvent for (A, B)
+    where A: Inspect, B: Inspect
+{
+    fn on_event<S: Any + Debug + Clone>(&self, event: &InspectFsmEvent<S>) {
+        self.0.on_event(event);
+        self.1.on_event(event);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmInfo, FsmInfoTransition, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, type_name_ident};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// How many times a transition was taken and how its guard was evaluated.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct FsmTransitionHits {
+    pub fired: usize,
+    pub guard_accepted: usize,
+    pub guard_rejected: usize
+}
+
+/// Collects the transitions that were taken and the branches of their guards, for all the machines
+/// and submachines it inspects. The clones share the collected data, so a single collector can be
+/// used by multiple machines and tests. Compare the collected data with the machine's structure using
+/// `report`.
+#[derive(Clone)]
+pub struct InspectCoverage {
+    hits: Arc<Mutex<HashMap<(String, String), FsmTransitionHits>>>,
+    fsm: &'static str
+}
+
+impl InspectCoverage {
+    pub fn new() -> Self {
+        InspectCoverage {
+            hits: Arc::new(Mutex::new(HashMap::new())),
+            fsm: ""
+        }
+    }
+
+    fn hit<T, H: FnOnce(&mut FsmTransitionHits)>(&self, update: H) {
+        if let Ok(mut hits) = self.hits.lock() {
+            let key = (type_name_ident(self.fsm).to_string(), type_name_ident(type_name::<T>()).to_string());
+            update(hits.entry(key).or_default());
+        }
+    }
+
+    /// The coverage of the transitions of the machine described by `info`, usually obtained with
+    /// the machine's generated `fsm_info()`.
+    pub fn report(&self, info: &FsmInfo) -> FsmCoverageReport {
+        let hits = self.hits.lock().map(|h| h.clone()).unwrap_or_default();
+        let fsm = type_name_ident(info.fsm_id).to_string();
+
+        let transitions = info.transitions().map(|transition| {
+            let key = (fsm.clone(), type_name_ident(transition.transition_id).to_string());
+            FsmTransitionCoverage {
+                transition,
+                hits: hits.get(&key).copied().unwrap_or_default()
+            }
+        }).collect();
+
+        FsmCoverageReport {
+            fsm_id: info.fsm_id,
+            transitions
+        }
+    }
+}
+
+impl Default for InspectCoverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FsmTransitionCoverage {
+    pub transition: &'static FsmInfoTransition,
+    pub hits: FsmTransitionHits
+}
+
+impl FsmTransitionCoverage {
+    /// The transition was taken at least once and, if guarded, its guard was both accepted and rejected.
+    pub fn is_covered(&self) -> bool {
+        self.hits.fired > 0 && (self.transition.guard.is_none() || (self.hits.guard_accepted > 0 && self.hits.guard_rejected > 0))
+    }
+}
+
+/// The coverage of a single machine's transitions, in the order of its `FsmInfo`.
+#[derive(Debug, Clone)]
+pub struct FsmCoverageReport {
+    pub fsm_id: &'static str,
+    pub transitions: Vec<FsmTransitionCoverage>
+}
+
+impl FsmCoverageReport {
+    /// The transitions that were never taken.
+    pub fn unexercised(&self) -> impl Iterator<Item = &FsmTransitionCoverage> {
+        self.transitions.iter().filter(|t| t.hits.fired == 0)
+    }
+
+    /// The guarded transitions with a guard that was never accepted or never rejected.
+    pub fn unexercised_guards(&self) -> impl Iterator<Item = &FsmTransitionCoverage> {
+        self.transitions.iter().filter(|t| t.transition.guard.is_some() && (t.hits.guard_accepted == 0 || t.hits.guard_rejected == 0))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.transitions.iter().all(|t| t.is_covered())
+    }
+}
+
+impl fmt::Display for FsmCoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let covered = self.transitions.iter().filter(|t| t.is_covered()).count();
+        writeln!(f, "{}: {} of {} transitions covered", self.fsm_id, covered, self.transitions.len())?;
+
+        for t in self.unexercised() {
+            writeln!(f, "  never taken: {} on {:?} ({:?})", t.transition.transition_id, t.transition.event, t.transition.kind)?;
+        }
+
+        for t in self.unexercised_guards() {
+            let branch = if t.hits.guard_accepted == 0 { "accepted" } else { "rejected" };
+            writeln!(f, "  guard never {}: {}", branch, t.transition.transition_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Inspect for InspectCoverage
+{
+    fn new_event<F: FsmBackend>(&self, _event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        self.new_borrowed_event("", fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, _event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        InspectCoverage {
+            hits: self.hits.clone(),
+            fsm: type_name::<F>()
+        }
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        self.hit::<T, _>(|h| h.fired += 1);
+        self.clone()
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.clone()
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.clone()
+    }
+
+    fn on_guard<T>(&self, _guard: &'static str, guard_result: bool) {
+        self.hit::<T, _>(|h| if guard_result { h.guard_accepted += 1 } else { h.guard_rejected += 1 });
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, _elapsed: Duration) {
+
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+
+    }
+
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
+
+    }
+
+    fn event_done<F: FsmBackend>(self, _fsm: &FsmBackendImpl<F>) {
+
+    }
+
+    fn on_error<E>(&self, _msg: &str, _error: &E) where E: Debug {
+
+    }
+
+    fn info(&self, _msg: &str) {
+
+    }
+}
+
+impl InspectEvent for InspectCoverage
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
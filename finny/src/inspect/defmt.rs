@@ -0,0 +1,120 @@
+use defmt::{debug, error, info, trace, warn};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+
+/// Inspection for embedded targets using `defmt`. Doesn't require `std` or allocations, the messages are
+/// interned by `defmt` and only the names of the machine, the events, the transitions and the states are
+/// transmitted as strings. The levels are filtered at compile time, using the `DEFMT_LOG` environment variable.
+#[derive(Copy, Clone)]
+pub struct InspectDefmt {
+    fsm: &'static str
+}
+
+impl InspectDefmt {
+    pub fn new() -> Self {
+        InspectDefmt {
+            fsm: ""
+        }
+    }
+}
+
+impl Default for InspectDefmt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inspect for InspectDefmt
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm_impl: &FsmBackendImpl<F>) -> Self {
+        let fsm = short_type_name::<F>();
+        match event {
+            FsmEvent::Timer(_) => debug!("[{=str}] #{=u64} Dispatching a timer event", fsm, fsm_impl.dispatch_id),
+            _ => debug!("[{=str}] #{=u64} Dispatching {=str}", fsm, fsm_impl.dispatch_id, event.as_ref())
+        }
+
+        InspectDefmt {
+            fsm
+        }
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm_impl: &FsmBackendImpl<F>) -> Self {
+        let fsm = short_type_name::<F>();
+        debug!("[{=str}] #{=u64} Dispatching {=str}", fsm, fsm_impl.dispatch_id, event);
+
+        InspectDefmt {
+            fsm
+        }
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        info!("[{=str}] Matched transition {=str}", self.fsm, short_type_name::<T>());
+        *self
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        debug!("[{=str}] Dispatching to the submachine {=str}", self.fsm, short_type_name::<FSub>());
+        *self
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        debug!("[{=str}] Timer triggered", self.fsm);
+        *self
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        debug!("[{=str}] Guard {=str} evaluated to {=bool}", self.fsm, guard, guard_result);
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        debug!("[{=str}] Entering {=str}", self.fsm, state);
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        debug!("[{=str}] Exiting {=str}", self.fsm, state);
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        debug!("[{=str}] Spent {=u64} us in {=str}", self.fsm, elapsed.as_micros() as u64, state);
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        trace!("[{=str}] Executing {=str}", self.fsm, action);
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        warn!("[{=str}] No transition for the event", self.fsm);
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        warn!("[{=str}] The event queue is full, with {=usize} events, dropped the {=str} event", self.fsm, capacity, match dropped {
+            FsmQueueDropped::Newest => "new",
+            FsmQueueDropped::Oldest => "oldest"
+        });
+    }
+
+    fn event_done<F: FsmBackend>(self, _fsm: &FsmBackendImpl<F>) {
+        trace!("[{=str}] Dispatch done", self.fsm);
+    }
+
+    fn on_error<E>(&self, msg: &str, _error: &E) where E: Debug {
+        error!("[{=str}] {=str}", self.fsm, msg);
+    }
+
+    fn info(&self, msg: &str) {
+        debug!("[{=str}] {=str}", self.fsm, msg);
+    }
+}
+
+impl InspectEvent for InspectDefmt
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
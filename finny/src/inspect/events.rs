@@ -1,6 +1,7 @@
-use crate::{FsmBackend, FsmBackendImpl, FsmEvent, Inspect, InspectEvent, InspectFsmEvent};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent};
 use core::any::Any;
 use core::fmt::Debug;
+use core::time::Duration;
 
 #[derive(Clone)]
 pub struct EventInspector<T>
@@ -26,6 +27,10 @@ impl<TI> Inspect for EventInspector<TI>
         self.clone()
     }
 
+    fn new_borrowed_event<F: FsmBackend>(&self, _event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        self.clone()
+    }
+
     fn for_transition<T>(&self) -> Self {
         self.clone()
     }
@@ -38,19 +43,35 @@ impl<TI> Inspect for EventInspector<TI>
         self.clone()
     }    
 
-    fn on_guard<T>(&self, _guard_result: bool) {
+    fn on_guard<T>(&self, _guard: &'static str, _guard_result: bool) {
+        
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+        
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+        
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, _elapsed: Duration) {
+        
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
         
     }
 
-    fn on_state_enter<S>(&self) {
+    fn on_action_done<S>(&self, _action: &'static str) {
         
     }
 
-    fn on_state_exit<S>(&self) {
+    fn on_unhandled_event(&self) {
         
     }
 
-    fn on_action<S>(&self) {
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
         
     }
 
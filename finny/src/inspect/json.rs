@@ -0,0 +1,203 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A single inspection record, written as a line of JSON.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct InspectJsonRecord {
+    /// Microseconds since the Unix epoch.
+    pub timestamp_us: u64,
+    /// The id of the top-level dispatch, see `FsmBackendImpl::dispatch_id`. All of the records of the dispatch
+    /// share it, including the records of the queued events and of the submachines.
+    pub dispatch_id: u64,
+    /// The machine that emitted the record.
+    pub fsm: String,
+    /// The event that is being dispatched.
+    pub event: String,
+    #[serde(flatten)]
+    pub kind: InspectJsonKind
+}
+
+/// What happened, tagged by the `kind` field of the record.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InspectJsonKind {
+    /// The event was taken from the queue or dispatched directly.
+    Dispatch { current_states: String },
+    Transition { transition: String },
+    SubMachine { sub_machine: String },
+    Timer { timer: String },
+    Guard { guard: String, result: bool },
+    StateEnter { state: String },
+    StateExit { state: String },
+    StateTime { state: String, elapsed_us: u64 },
+    Action { action: String },
+    Unhandled,
+    QueueFull { capacity: usize, dropped: String },
+    Error { message: String, error: String },
+    Info { message: String },
+    Done { current_states: String }
+}
+
+type InspectJsonSink = Arc<Mutex<dyn FnMut(&InspectJsonRecord) + Send>>;
+
+/// The dispatch id, the machine and the event of the last dispatch.
+type InspectJsonDispatch = Arc<Mutex<(u64, String, String)>>;
+
+/// Inspection that emits every inspection event as an `InspectJsonRecord`, either as the JSON lines of a
+/// writer or to a function, for the log pipelines. The clones share the sink, a machine that is
+/// dispatched from multiple threads should get its own instance.
+///
+/// Example : `InspectJson::new(std::io::stdout())`
+#[derive(Clone)]
+pub struct InspectJson {
+    sink: InspectJsonSink,
+    // the entries and exits of the states are reported to the machine's own inspector, outside of the dispatch.
+    last_dispatch: InspectJsonDispatch,
+    dispatch_id: Option<u64>,
+    fsm: String,
+    event: String
+}
+
+impl InspectJson {
+    /// Write the records as the lines of JSON. The errors of the writer are ignored.
+    pub fn new<W: Write + Send + 'static>(mut writer: W) -> Self {
+        Self::with_sink(move |record| {
+            if let Ok(line) = serde_json::to_string(record) {
+                let _ = writeln!(writer, "{}", line);
+            }
+        })
+    }
+
+    /// Pass the records to the function.
+    pub fn with_sink<S: FnMut(&InspectJsonRecord) + Send + 'static>(sink: S) -> Self {
+        InspectJson {
+            sink: Arc::new(Mutex::new(sink)),
+            last_dispatch: Arc::new(Mutex::new((0, String::new(), String::new()))),
+            dispatch_id: None,
+            fsm: String::new(),
+            event: String::new()
+        }
+    }
+
+    fn emit(&self, kind: InspectJsonKind) {
+        let timestamp_us = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_micros() as u64).unwrap_or(0);
+        let (dispatch_id, fsm, event) = match self.dispatch_id {
+            Some(dispatch_id) => (dispatch_id, self.fsm.clone(), self.event.clone()),
+            None => self.last_dispatch.lock().map(|last| last.clone()).unwrap_or_default()
+        };
+        let record = InspectJsonRecord {
+            timestamp_us,
+            dispatch_id,
+            fsm,
+            event,
+            kind
+        };
+
+        if let Ok(mut sink) = self.sink.lock() {
+            (*sink)(&record);
+        }
+    }
+}
+
+impl Inspect for InspectJson
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        let event_display = match event {
+            FsmEvent::Timer(t) => format!("Fsm::Timer({:?})", t),
+            _ => event.as_ref().to_string()
+        };
+
+        self.new_borrowed_event(&event_display, fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm: &FsmBackendImpl<F>) -> Self {
+        // the submachines are a part of their parent's dispatch
+        let dispatch_id = fsm.dispatch_id;
+        if self.dispatch_id.is_none() {
+            if let Ok(mut last) = self.last_dispatch.lock() {
+                *last = (dispatch_id, short_type_name::<F>().to_string(), event.to_string());
+            }
+        }
+
+        let inspect = InspectJson {
+            sink: self.sink.clone(),
+            last_dispatch: self.last_dispatch.clone(),
+            dispatch_id: Some(dispatch_id),
+            fsm: short_type_name::<F>().to_string(),
+            event: event.to_string()
+        };
+        inspect.emit(InspectJsonKind::Dispatch { current_states: format!("{:?}", fsm.get_current_states()) });
+        inspect
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        self.emit(InspectJsonKind::Transition { transition: short_type_name::<T>().to_string() });
+        self.clone()
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.emit(InspectJsonKind::SubMachine { sub_machine: short_type_name::<FSub>().to_string() });
+        self.clone()
+    }
+
+    fn for_timer<F>(&self, timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.emit(InspectJsonKind::Timer { timer: format!("{:?}", timer_id) });
+        self.clone()
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        self.emit(InspectJsonKind::Guard { guard: guard.to_string(), result: guard_result });
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        self.emit(InspectJsonKind::StateEnter { state: state.to_string() });
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        self.emit(InspectJsonKind::StateExit { state: state.to_string() });
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        self.emit(InspectJsonKind::StateTime { state: state.to_string(), elapsed_us: elapsed.as_micros() as u64 });
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        self.emit(InspectJsonKind::Action { action: action.to_string() });
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        self.emit(InspectJsonKind::Unhandled);
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        self.emit(InspectJsonKind::QueueFull { capacity, dropped: format!("{:?}", dropped) });
+    }
+
+    fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
+        self.emit(InspectJsonKind::Done { current_states: format!("{:?}", fsm.get_current_states()) });
+    }
+
+    fn on_error<E>(&self, msg: &str, error: &E) where E: Debug {
+        self.emit(InspectJsonKind::Error { message: msg.to_string(), error: format!("{:?}", error) });
+    }
+
+    fn info(&self, msg: &str) {
+        self.emit(InspectJsonKind::Info { message: msg.to_string() });
+    }
+}
+
+impl InspectEvent for InspectJson
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
@@ -0,0 +1,157 @@
+extern crate alloc;
+
+use log::{Level, log};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use alloc::string::{String, ToString};
+use alloc::format;
+
+/// The levels at which the different kinds of inspection messages are logged.
+#[derive(Debug, Clone, Copy)]
+pub struct InspectLogLevels {
+    /// The start and the end of the event's dispatch.
+    pub dispatch: Level,
+    /// Matched transitions, evaluated guards and executed actions.
+    pub transitions: Level,
+    /// Entry and exit of the states.
+    pub states: Level,
+    /// Events without a transition in any of the regions.
+    pub unhandled: Level,
+    /// Errors reported by the machine.
+    pub errors: Level
+}
+
+impl Default for InspectLogLevels {
+    fn default() -> Self {
+        Self {
+            dispatch: Level::Debug,
+            transitions: Level::Info,
+            states: Level::Debug,
+            unhandled: Level::Warn,
+            errors: Level::Error
+        }
+    }
+}
+
+/// Inspection using the `log` facade. Every message is prefixed with the machine and the event that
+/// is being dispatched.
+#[derive(Clone)]
+pub struct InspectLog {
+    pub levels: InspectLogLevels,
+    prefix: String
+}
+
+impl InspectLog {
+    pub fn new() -> Self {
+        Self::with_levels(InspectLogLevels::default())
+    }
+
+    pub fn with_levels(levels: InspectLogLevels) -> Self {
+        InspectLog {
+            levels,
+            prefix: String::new()
+        }
+    }
+}
+
+impl Default for InspectLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inspect for InspectLog
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        let event_display = match event {
+            FsmEvent::Timer(t) => format!("Fsm::Timer({:?})", t),
+            _ => event.as_ref().to_string()
+        };
+
+        self.new_borrowed_event(&event_display, fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event_display: &str, fsm: &FsmBackendImpl<F>) -> Self {
+
+        // the submachines are a part of their parent's dispatch
+        let prefix = if self.prefix.is_empty() {
+            format!("[{}] #{} {}", short_type_name::<F>(), fsm.dispatch_id, event_display)
+        } else {
+            format!("{}[{}] {}", self.prefix, short_type_name::<F>(), event_display)
+        };
+        log!(self.levels.dispatch, "{}: Dispatching, the current states are {:?}", prefix, fsm.get_current_states());
+
+        InspectLog {
+            levels: self.levels,
+            prefix: format!("{}: ", prefix)
+        }
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        log!(self.levels.transitions, "{}Matched transition {}", self.prefix, short_type_name::<T>());
+        self.clone()
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        log!(self.levels.dispatch, "{}Dispatching to the submachine {}", self.prefix, short_type_name::<FSub>());
+        self.clone()
+    }
+
+    fn for_timer<F>(&self, timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        log!(self.levels.transitions, "{}Timer {:?} triggered", self.prefix, timer_id);
+        self.clone()
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        log!(self.levels.transitions, "{}Guard {} evaluated to {}", self.prefix, guard, guard_result);
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        log!(self.levels.states, "{}Entering {}", self.prefix, state);
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        log!(self.levels.states, "{}Exiting {}", self.prefix, state);
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        log!(self.levels.states, "{}Spent {:?} in {}", self.prefix, elapsed, state);
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        log!(self.levels.transitions, "{}Executing {}", self.prefix, action);
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        log!(self.levels.unhandled, "{}No transition for the event", self.prefix);
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        log!(self.levels.errors, "{}The event queue is full, with {} events, {} was dropped", self.prefix, capacity, dropped);
+    }
+
+    fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
+        log!(self.levels.dispatch, "{}Dispatch done, the current states are {:?}", self.prefix, fsm.get_current_states());
+    }
+
+    fn on_error<E>(&self, msg: &str, error: &E) where E: Debug {
+        log!(self.levels.errors, "{}{}: {:?}", self.prefix, msg, error);
+    }
+
+    fn info(&self, msg: &str) {
+        log!(self.levels.dispatch, "{}{}", self.prefix, msg);
+    }
+}
+
+impl InspectEvent for InspectLog
+{
+    fn on_event<S: Any + Debug + Clone>(&self, event: &InspectFsmEvent<S>) {
+        log!(Level::Trace, "{}Inspection event {:?}", self.prefix, event);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmInfo, FsmInfoTransitionKind, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, type_name_ident};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default upper bounds of the dispatch latency histogram's buckets, in seconds.
+pub const FSM_METRICS_DEFAULT_BUCKETS: &[f64] = &[0.000_001, 0.000_01, 0.000_1, 0.001, 0.01, 0.1, 1.0];
+
+/// The distribution of the dispatch latency of a machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmLatencyHistogram {
+    /// The upper bounds of the buckets, in seconds, with the cumulative count of the dispatches within each.
+    pub buckets: Vec<(f64, u64)>,
+    /// The total time spent in the dispatches, in seconds.
+    pub sum: f64,
+    pub count: u64
+}
+
+impl FsmLatencyHistogram {
+    fn new(bounds: &[f64]) -> Self {
+        FsmLatencyHistogram {
+            buckets: bounds.iter().map(|b| (*b, 0)).collect(),
+            sum: 0.0,
+            count: 0
+        }
+    }
+
+    fn observe(&mut self, seconds: f64) {
+        for (bound, count) in self.buckets.iter_mut() {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// A metrics library that the metrics are forwarded to as they are observed, like the `metrics` facade's
+/// `counter!` and `histogram!` or a collector of a `prometheus::Registry`. The metrics are named and labeled
+/// like the rendered ones, except for the time spent in the states, which is recorded for every visit as the
+/// `finny_state_duration_seconds` histogram. The durations are in seconds.
+///
+/// Example : `fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]) { metrics::counter!(name, &to_labels(labels)).increment(1) }`
+pub trait FsmMetricsRecorder: Send {
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]);
+    fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64);
+}
+
+struct FsmMetricsData {
+    infos: Vec<&'static FsmInfo>,
+    recorders: Vec<Box<dyn FsmMetricsRecorder>>,
+    buckets: Vec<f64>,
+    /// Keyed by the machine and the event.
+    dispatched: HashMap<(String, String), u64>,
+    unhandled: HashMap<(String, String), u64>,
+    /// Keyed by the machine, the transition and the event.
+    transitions: HashMap<(String, String, String), u64>,
+    latency: HashMap<String, FsmLatencyHistogram>,
+    /// Keyed by the machine and the state.
+    state_time: HashMap<(String, String), Duration>
+}
+
+/// Counts the dispatched events, the taken transitions and the unhandled events, and measures the
+/// latency of the dispatches and the time spent in each state, for all the machines and submachines it inspects. The clones share the
+/// collected data, so a single collector can be used by a fleet of machines. `render` exposes the
+/// metrics in the Prometheus text format, to be served by the scrape endpoint or forwarded to the
+/// service's registry.
+///
+/// The transitions are labeled with their source and target states, from the descriptions of the
+/// inspected machines. Use `with_recorder` to forward the metrics to the service's metrics library.
+#[derive(Clone)]
+pub struct InspectMetrics {
+    data: Arc<Mutex<FsmMetricsData>>,
+    fsm: &'static str,
+    event: Option<String>,
+    started: Option<Instant>
+}
+
+impl InspectMetrics {
+    pub fn new() -> Self {
+        InspectMetrics {
+            data: Arc::new(Mutex::new(FsmMetricsData {
+                infos: vec![],
+                recorders: vec![],
+                buckets: FSM_METRICS_DEFAULT_BUCKETS.to_vec(),
+                dispatched: HashMap::new(),
+                unhandled: HashMap::new(),
+                transitions: HashMap::new(),
+                latency: HashMap::new(),
+                state_time: HashMap::new()
+            })),
+            fsm: "",
+            event: None,
+            started: None
+        }
+    }
+
+    /// Register the description of a machine, usually obtained with the machine's generated `fsm_info()`,
+    /// used for the labels of its transitions. The descriptions of the inspected machines are registered
+    /// on their first event, so this is only needed for rendering them before that.
+    pub fn with_info(self, info: &'static FsmInfo) -> Self {
+        if let Ok(mut data) = self.data.lock() {
+            data.register(Some(info));
+        }
+        self
+    }
+
+    /// Forward the metrics to the recorder as they are observed, in addition to collecting them.
+    pub fn with_recorder<R: FsmMetricsRecorder + 'static>(self, recorder: R) -> Self {
+        if let Ok(mut data) = self.data.lock() {
+            data.recorders.push(Box::new(recorder));
+        }
+        self
+    }
+
+    /// Replace the upper bounds of the dispatch latency histogram's buckets, in seconds. Only affects the
+    /// machines that weren't observed yet.
+    pub fn with_buckets(self, buckets: &[f64]) -> Self {
+        if let Ok(mut data) = self.data.lock() {
+            data.buckets = buckets.to_vec();
+        }
+        self
+    }
+
+    fn with_data<R: Default, U: FnOnce(&mut FsmMetricsData) -> R>(&self, update: U) -> R {
+        self.data.lock().map(|mut d| update(&mut d)).unwrap_or_default()
+    }
+
+    /// How many times the event was dispatched to the machine.
+    pub fn events_dispatched(&self, fsm_id: &str, event: &str) -> u64 {
+        self.with_data(|d| d.dispatched.get(&(fsm_id.to_string(), event.to_string())).copied().unwrap_or(0))
+    }
+
+    /// How many times the event wasn't handled by any of the machine's regions.
+    pub fn events_unhandled(&self, fsm_id: &str, event: &str) -> u64 {
+        self.with_data(|d| d.unhandled.get(&(fsm_id.to_string(), event.to_string())).copied().unwrap_or(0))
+    }
+
+    /// How many times the machine transitioned between the states on the event. The self and internal
+    /// transitions have the same source and target state.
+    pub fn transitions(&self, fsm_id: &str, from: &str, to: &str, event: &str) -> u64 {
+        self.with_data(|d| {
+            d.transition_counts()
+                .filter(|(key, _)| key.0 == fsm_id && key.1 == from && key.2 == to && key.3 == event)
+                .map(|(_, count)| count)
+                .sum()
+        })
+    }
+
+    pub fn dispatch_latency(&self, fsm_id: &str) -> Option<FsmLatencyHistogram> {
+        self.with_data(|d| d.latency.get(fsm_id).cloned())
+    }
+
+    /// The total time spent in the state, over all of its completed visits. Measured by the timers' clock.
+    pub fn time_in_state(&self, fsm_id: &str, state: &str) -> Duration {
+        self.with_data(|d| d.state_time.get(&(fsm_id.to_string(), state.to_string())).copied().unwrap_or_default())
+    }
+
+    /// All the collected metrics, in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        self.with_data(|d| d.render().unwrap_or_default())
+    }
+}
+
+impl Default for InspectMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsmMetricsData {
+    fn register(&mut self, info: Option<&'static FsmInfo>) {
+        if let Some(info) = info {
+            if !self.infos.iter().any(|i| i.fsm_id == info.fsm_id) {
+                self.infos.push(info);
+            }
+        }
+    }
+
+    /// The source and target states of the transition. Empty for the machines without a description.
+    fn transition_states(&self, fsm: &str, transition_id: &str) -> (&'static str, &'static str) {
+        let kind = self.infos.iter()
+            .filter(|i| i.fsm_id == fsm)
+            .flat_map(|i| i.transitions())
+            .find(|t| t.transition_id == transition_id)
+            .map(|t| t.kind);
+
+        match kind {
+            Some(FsmInfoTransitionKind::NormalTransition { from_state, to_state }) => (from_state, to_state),
+            Some(FsmInfoTransitionKind::SelfTransition { state_id }) |
+            Some(FsmInfoTransitionKind::InternalTransition { state_id }) => (state_id, state_id),
+            None => ("", "")
+        }
+    }
+
+    /// The transition counts, labeled with the machine, the source and target states and the event.
+    fn transition_counts(&self) -> impl Iterator<Item = ((&str, &str, &str, &str), u64)> {
+        self.transitions.iter().map(move |((fsm, transition_id, event), count)| {
+            let (from, to) = self.transition_states(fsm, transition_id);
+            ((fsm.as_str(), from, to, event.as_str()), *count)
+        })
+    }
+
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]) {
+        for recorder in &self.recorders {
+            recorder.increment_counter(name, labels);
+        }
+    }
+
+    fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        for recorder in &self.recorders {
+            recorder.record_histogram(name, labels, value);
+        }
+    }
+
+    fn render(&self) -> Result<String, fmt::Error> {
+        use core::fmt::Write;
+
+        let mut output = String::new();
+
+        writeln!(output, "# HELP finny_events_dispatched_total The events dispatched to the machine.")?;
+        writeln!(output, "# TYPE finny_events_dispatched_total counter")?;
+        let mut dispatched: Vec<_> = self.dispatched.iter().collect();
+        dispatched.sort();
+        for ((fsm, event), count) in dispatched {
+            writeln!(output, "finny_events_dispatched_total{{fsm=\"{}\",event=\"{}\"}} {}", escape(fsm), escape(event), count)?;
+        }
+
+        writeln!(output, "# HELP finny_transitions_total The transitions taken by the machine.")?;
+        writeln!(output, "# TYPE finny_transitions_total counter")?;
+        let mut transitions: HashMap<(&str, &str, &str, &str), u64> = HashMap::new();
+        for (key, count) in self.transition_counts() {
+            *transitions.entry(key).or_default() += count;
+        }
+        let mut transitions: Vec<_> = transitions.into_iter().collect();
+        transitions.sort();
+        for ((fsm, from, to, event), count) in transitions {
+            writeln!(output, "finny_transitions_total{{fsm=\"{}\",from=\"{}\",to=\"{}\",event=\"{}\"}} {}",
+                escape(fsm), escape(from), escape(to), escape(event), count)?;
+        }
+
+        writeln!(output, "# HELP finny_events_unhandled_total The events that weren't handled by any of the machine's regions.")?;
+        writeln!(output, "# TYPE finny_events_unhandled_total counter")?;
+        let mut unhandled: Vec<_> = self.unhandled.iter().collect();
+        unhandled.sort();
+        for ((fsm, event), count) in unhandled {
+            writeln!(output, "finny_events_unhandled_total{{fsm=\"{}\",event=\"{}\"}} {}", escape(fsm), escape(event), count)?;
+        }
+
+        writeln!(output, "# HELP finny_dispatch_duration_seconds The latency of the event dispatches.")?;
+        writeln!(output, "# TYPE finny_dispatch_duration_seconds histogram")?;
+        let mut latency: Vec<_> = self.latency.iter().collect();
+        latency.sort_by(|a, b| a.0.cmp(b.0));
+        for (fsm, histogram) in latency {
+            let fsm = escape(fsm);
+            for (bound, count) in &histogram.buckets {
+                writeln!(output, "finny_dispatch_duration_seconds_bucket{{fsm=\"{}\",le=\"{}\"}} {}", fsm, bound, count)?;
+            }
+            writeln!(output, "finny_dispatch_duration_seconds_bucket{{fsm=\"{}\",le=\"+Inf\"}} {}", fsm, histogram.count)?;
+            writeln!(output, "finny_dispatch_duration_seconds_sum{{fsm=\"{}\"}} {}", fsm, histogram.sum)?;
+            writeln!(output, "finny_dispatch_duration_seconds_count{{fsm=\"{}\"}} {}", fsm, histogram.count)?;
+        }
+
+        writeln!(output, "# HELP finny_state_duration_seconds_total The time spent in the state, over all of its completed visits.")?;
+        writeln!(output, "# TYPE finny_state_duration_seconds_total counter")?;
+        let mut state_time: Vec<_> = self.state_time.iter().collect();
+        state_time.sort();
+        for ((fsm, state), elapsed) in state_time {
+            writeln!(output, "finny_state_duration_seconds_total{{fsm=\"{}\",state=\"{}\"}} {}", escape(fsm), escape(state), elapsed.as_secs_f64())?;
+        }
+
+        Ok(output)
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl Inspect for InspectMetrics
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        self.new_borrowed_event(event.as_ref(), fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        let fsm = type_name::<F>();
+        let event = event.to_string();
+
+        self.with_data(|d| {
+            d.register(F::info());
+            d.increment_counter("finny_events_dispatched_total", &[("fsm", type_name_ident(fsm)), ("event", &event)]);
+            *d.dispatched.entry((type_name_ident(fsm).to_string(), event.clone())).or_default() += 1;
+        });
+
+        InspectMetrics {
+            data: self.data.clone(),
+            fsm,
+            event: Some(event),
+            started: Some(Instant::now())
+        }
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        if let Some(ref event) = self.event {
+            let (fsm, transition_id) = (type_name_ident(self.fsm), type_name_ident(type_name::<T>()));
+            self.with_data(|d| {
+                let (from, to) = d.transition_states(fsm, transition_id);
+                d.increment_counter("finny_transitions_total", &[("fsm", fsm), ("from", from), ("to", to), ("event", event)]);
+                *d.transitions.entry((fsm.to_string(), transition_id.to_string(), event.clone())).or_default() += 1;
+            });
+        }
+        self.clone()
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.with_data(|d| d.register(FSub::info()));
+        self.clone()
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.clone()
+    }
+
+    fn on_guard<T>(&self, _guard: &'static str, _guard_result: bool) {
+
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, elapsed: Duration) {
+        let (fsm, state) = (type_name_ident(type_name::<F>()), type_name_ident(type_name::<S>()));
+        self.with_data(|d| {
+            d.record_histogram("finny_state_duration_seconds", &[("fsm", fsm), ("state", state)], elapsed.as_secs_f64());
+            *d.state_time.entry((fsm.to_string(), state.to_string())).or_default() += elapsed;
+        });
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        if let Some(ref event) = self.event {
+            let fsm = type_name_ident(self.fsm);
+            self.with_data(|d| {
+                d.increment_counter("finny_events_unhandled_total", &[("fsm", fsm), ("event", event)]);
+                *d.unhandled.entry((fsm.to_string(), event.clone())).or_default() += 1;
+            });
+        }
+    }
+
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
+
+    }
+
+    fn event_done<F: FsmBackend>(self, _fsm: &FsmBackendImpl<F>) {
+        if let Some(started) = self.started {
+            let seconds = started.elapsed().as_secs_f64();
+            let fsm = type_name_ident(self.fsm);
+            self.with_data(|d| {
+                d.record_histogram("finny_dispatch_duration_seconds", &[("fsm", fsm)], seconds);
+                let FsmMetricsData { latency, buckets, .. } = d;
+                latency.entry(fsm.to_string()).or_insert_with(|| FsmLatencyHistogram::new(buckets)).observe(seconds);
+            });
+        }
+    }
+
+    fn on_error<E>(&self, _msg: &str, _error: &E) where E: Debug {
+
+    }
+
+    fn info(&self, _msg: &str) {
+
+    }
+}
+
+impl InspectEvent for InspectMetrics
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
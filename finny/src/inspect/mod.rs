@@ -3,5 +3,32 @@ pub mod chain;
 pub mod events;
 
 
+#[cfg(feature="std")]
+pub mod recorder;
+
 #[cfg(feature="inspect_slog")]
-pub mod slog;
\ No newline at end of file
+pub mod slog;
+
+#[cfg(feature="inspect_tracing")]
+pub mod tracing;
+
+#[cfg(feature="inspect_opentelemetry")]
+pub mod opentelemetry;
+
+#[cfg(feature="inspect_log")]
+pub mod log;
+
+#[cfg(feature="inspect_defmt")]
+pub mod defmt;
+
+#[cfg(feature="inspect_coverage")]
+pub mod coverage;
+
+#[cfg(feature="inspect_metrics")]
+pub mod metrics;
+
+#[cfg(feature="inspect_timing")]
+pub mod timing;
+
+#[cfg(feature="inspect_json")]
+pub mod json;
@@ -1,5 +1,6 @@
-use crate::{FsmBackend, FsmBackendImpl, FsmEvent, Inspect, InspectEvent, InspectFsmEvent};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent};
 use core::fmt::Debug;
+use core::time::Duration;
 use core::any::Any;
 
 #[derive(Default)]
@@ -16,6 +17,10 @@ impl Inspect for InspectNull {
         Self::default()
     }
 
+    fn new_borrowed_event<F: FsmBackend>(&self, _event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        Self::default()
+    }
+
     fn for_transition<T>(&self) -> Self {
         Self::default()
     }
@@ -28,19 +33,35 @@ impl Inspect for InspectNull {
         Self::default()
     }    
 
-    fn on_guard<T>(&self, _guard_result: bool) {
+    fn on_guard<T>(&self, _guard: &'static str, _guard_result: bool) {
+        
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+        
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+        
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, _elapsed: Duration) {
+        
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
         
     }
 
-    fn on_state_enter<S>(&self) {
+    fn on_action_done<S>(&self, _action: &'static str) {
         
     }
 
-    fn on_state_exit<S>(&self) {
+    fn on_unhandled_event(&self) {
         
     }
 
-    fn on_action<S>(&self) {
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
         
     }
 
@@ -0,0 +1,177 @@
+extern crate alloc;
+
+use tracing::{Span, error, field, info, info_span, warn};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use alloc::string::{String, ToString};
+use alloc::format;
+
+/// Inspection with the OpenTelemetry spans, created with the `tracing` crate and following the OpenTelemetry
+/// conventions of the `otel.*` fields. Every dispatch is a `fsm_dispatch` span and every taken transition
+/// a nested `fsm_transition` span, named with the `otel.name` field. Submachine dispatches are nested
+/// in their parent's dispatch.
+///
+/// Finny doesn't depend on the `opentelemetry` crates. The spans are exported by the application, that
+/// adds the `tracing-opentelemetry` layer with its exporter to the `tracing` subscriber, for example
+/// `tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer))`. Without the
+/// layer, these are plain `tracing` spans.
+///
+/// The events can carry the `tracing::Span` of the request that caused them, declared with
+/// `fsm.event_trace_context::<TEvent>(|ev| &ev.span)`. Their dispatch is then a child of that span,
+/// even if the event was queued and dispatched later, which links the machine's work to the request's
+/// distributed trace. The other dispatches are children of the span that is current at the time.
+///
+/// The guards, the entered and exited states, the actions and the messages are the events of the
+/// spans, exported as the OpenTelemetry span events. An error sets the `ERROR` status of its span and
+/// of the dispatch's span.
+pub struct InspectOpenTelemetry {
+    pub span: Span,
+    dispatch: Span
+}
+
+impl InspectOpenTelemetry {
+    pub fn new() -> Self {
+        InspectOpenTelemetry {
+            span: Span::none(),
+            dispatch: Span::none()
+        }
+    }
+
+    fn with_span(&self, span: Span) -> Self {
+        InspectOpenTelemetry {
+            span,
+            dispatch: self.dispatch.clone()
+        }
+    }
+
+    fn new_dispatch<F: FsmBackend>(&self, parent: &Span, event: &str, event_display: &str, fsm: &FsmBackendImpl<F>) -> Self {
+        let fsm_name = short_type_name::<F>();
+        let otel_name = format!("{} {}", fsm_name, event);
+        let start_state = format!("{:?}", fsm.get_current_states());
+
+        let span = parent.in_scope(|| {
+            info_span!("fsm_dispatch",
+                otel.name = %otel_name,
+                otel.kind = "internal",
+                otel.status_code = field::Empty,
+                otel.status_message = field::Empty,
+                fsm = fsm_name,
+                event = %event_display,
+                start_state = %start_state,
+                dispatch_id = fsm.dispatch_id,
+                stop_state = field::Empty,
+                unhandled = field::Empty
+            )
+        });
+
+        InspectOpenTelemetry {
+            dispatch: span.clone(),
+            span
+        }
+    }
+}
+
+impl Default for InspectOpenTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inspect for InspectOpenTelemetry
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        let event_display = match event {
+            FsmEvent::Timer(t) => format!("Fsm::Timer({:?})", t),
+            _ => event.as_ref().to_string()
+        };
+
+        let parent = event.trace_context().and_then(|c| c.downcast_ref::<Span>()).unwrap_or(&self.span);
+
+        self.new_dispatch(parent, event.as_ref(), &event_display, fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event: &str, fsm: &FsmBackendImpl<F>) -> Self {
+        self.new_dispatch(&self.span, event, event, fsm)
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        let transition = short_type_name::<T>();
+        let span = self.span.in_scope(|| {
+            info_span!("fsm_transition",
+                otel.name = transition,
+                otel.kind = "internal",
+                otel.status_code = field::Empty,
+                otel.status_message = field::Empty,
+                transition
+            )
+        });
+
+        self.with_span(span)
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.with_span(self.span.clone())
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.with_span(self.span.clone())
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        self.span.in_scope(|| info!(guard, result = guard_result, "Guard evaluated"));
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        self.span.in_scope(|| info!(state, "Entering state"));
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        self.span.in_scope(|| info!(state, "Exiting state"));
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        self.span.in_scope(|| info!(state, elapsed_seconds = elapsed.as_secs_f64(), "Time spent in state"));
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        self.span.in_scope(|| info!(action, "Executing action"));
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        self.span.record("unhandled", true);
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        self.span.in_scope(|| warn!(capacity, dropped = ?dropped, "The event queue is full"));
+    }
+
+    fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
+        let stop_state: String = format!("{:?}", fsm.get_current_states());
+        self.span.record("stop_state", field::display(&stop_state));
+    }
+
+    fn on_error<E>(&self, msg: &str, error: &E) where E: Debug {
+        for span in [&self.span, &self.dispatch] {
+            span.record("otel.status_code", "ERROR");
+            span.record("otel.status_message", msg);
+        }
+        self.span.in_scope(|| error!(error = ?error, "{}", msg));
+    }
+
+    fn info(&self, msg: &str) {
+        self.span.in_scope(|| info!("{}", msg));
+    }
+}
+
+impl InspectEvent for InspectOpenTelemetry
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent};
+use core::fmt::Debug;
+use core::time::Duration;
+use core::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// The events that were dispatched to the machine, in their order, including the timer events and the
+/// events that were enqueued by the actions. Replay it using `FsmFactory::replay`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "<F as FsmBackend>::Events: serde::Serialize, <F as FsmBackend>::Timers: serde::Serialize",
+    deserialize = "<F as FsmBackend>::Events: serde::Deserialize<'de>, <F as FsmBackend>::Timers: serde::Deserialize<'de>"
+)))]
+pub struct FsmEventLog<F: FsmBackend> {
+    pub events: Vec<FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>>
+}
+
+impl<F: FsmBackend> FsmEventLog<F> {
+    pub fn new() -> Self {
+        FsmEventLog {
+            events: Vec::new()
+        }
+    }
+}
+
+impl<F: FsmBackend> Default for FsmEventLog<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend> Clone for FsmEventLog<F> where <F as FsmBackend>::Events: Clone {
+    fn clone(&self) -> Self {
+        FsmEventLog {
+            events: self.events.clone()
+        }
+    }
+}
+
+/// Records the events that are dispatched to the machine `F` into a shared `FsmEventLog`. The events
+/// dispatched to its submachines are a part of the parent's events, so they are not recorded separately.
+pub struct InspectRecorder<F: FsmBackend> {
+    log: Arc<Mutex<FsmEventLog<F>>>
+}
+
+impl<F: FsmBackend> InspectRecorder<F> {
+    pub fn new() -> Self {
+        InspectRecorder {
+            log: Arc::new(Mutex::new(FsmEventLog::new()))
+        }
+    }
+}
+
+impl<F: FsmBackend> InspectRecorder<F> where <F as FsmBackend>::Events: Clone {
+    /// A copy of the events recorded so far.
+    pub fn log(&self) -> FsmEventLog<F> {
+        self.log.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+}
+
+impl<F: FsmBackend> Default for InspectRecorder<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FsmBackend> Clone for InspectRecorder<F> {
+    fn clone(&self) -> Self {
+        InspectRecorder {
+            log: self.log.clone()
+        }
+    }
+}
+
+impl<R: FsmBackend> Inspect for InspectRecorder<R> where <R as FsmBackend>::Events: Clone
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, _fsm: &FsmBackendImpl<F>) -> Self {
+        let event: &dyn Any = event;
+        if let Some(event) = event.downcast_ref::<FsmEvent<<R as FsmBackend>::Events, <R as FsmBackend>::Timers>>() {
+            if let Ok(mut log) = self.log.lock() {
+                log.events.push(event.clone());
+            }
+        }
+
+        self.clone()
+    }
+
+    /// The borrowed events can't be recorded, only their transitions and states are.
+    fn new_borrowed_event<F: FsmBackend>(&self, _event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        self.clone()
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        self.clone()
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.clone()
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.clone()
+    }
+
+    fn on_guard<T>(&self, _guard: &'static str, _guard_result: bool) {
+
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, _elapsed: Duration) {
+
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+
+    }
+
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
+
+    }
+
+    fn event_done<F: FsmBackend>(self, _fsm: &FsmBackendImpl<F>) {
+
+    }
+
+    fn on_error<E>(&self, _msg: &str, _error: &E) where E: Debug {
+
+    }
+
+    fn info(&self, _msg: &str) {
+
+    }
+}
+
+impl<R: FsmBackend> InspectEvent for InspectRecorder<R> where <R as FsmBackend>::Events: Clone
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
@@ -1,7 +1,7 @@
 extern crate alloc;
 
-use slog::{info, o, error};
-use crate::{FsmBackend, FsmBackendImpl, FsmEvent, Inspect, InspectEvent, InspectFsmEvent};
+use slog::{info, o, error, warn};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
 use crate::lib::*;
 use AsRef;
 use core::fmt::Debug;
@@ -29,9 +29,14 @@ impl Inspect for InspectSlog
             _ => event.as_ref().to_string()
         };
 
+        self.new_borrowed_event(&event_display, fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event_display: &str, fsm: &FsmBackendImpl<F>) -> Self {
+
         let current_state = format!("{:?}", fsm.get_current_states());
 
-        let kv = o!("event" => event_display, "start_state" => current_state);
+        let kv = o!("dispatch_id" => fsm.dispatch_id, "event" => event_display.to_string(), "start_state" => current_state);
         info!(self.logger, "Dispatching"; &kv);
         InspectSlog {
             logger: self.logger.new(kv)
@@ -39,7 +44,7 @@ impl Inspect for InspectSlog
     }
 
     fn for_transition<T>(&self) -> Self {
-        let transition = type_name::<T>();
+        let transition = short_type_name::<T>();
         let kv = o!("transition" => transition);
         info!(self.logger, "Matched transition"; &kv);
         InspectSlog {
@@ -48,7 +53,7 @@ impl Inspect for InspectSlog
     }
  
     fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
-        let sub_fsm = type_name::<FSub>();
+        let sub_fsm = short_type_name::<FSub>();
         let kv = o!("sub_fsm" => sub_fsm);
         info!(self.logger, "Dispatching to a submachine"; &kv);
         InspectSlog {
@@ -63,26 +68,40 @@ impl Inspect for InspectSlog
         }
     }    
 
-    fn on_guard<T>(&self, guard_result: bool) {
-        let guard = type_name::<T>();
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
         info!(self.logger, "Guard {guard} evaluated to {guard_result}", guard = guard, guard_result = guard_result);
     }
 
-    fn on_state_enter<S>(&self) {
-        let state = type_name::<S>();
+    fn on_state_enter<S>(&self, state: &'static str) {
         info!(self.logger, "Entering {state}", state = state);
     }
 
-    fn on_state_exit<S>(&self) {
-        let state = type_name::<S>();
+    fn on_state_exit<S>(&self, state: &'static str) {
         info!(self.logger, "Exiting {state}", state = state);
     }
 
-    fn on_action<S>(&self) {
-        let action = type_name::<S>();
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        let elapsed = format!("{:?}", elapsed);
+        info!(self.logger, "Time spent in {state}", state = state; "elapsed" => elapsed);
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
         info!(self.logger, "Executing {action}", action = action);
     }
 
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        info!(self.logger, "No transition for the event");
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        let kv = o!("capacity" => capacity, "dropped" => format!("{:?}", dropped));
+        warn!(self.logger, "The event queue is full"; kv);
+    }
+
     fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
         let states = format!("{:?}", fsm.get_current_states());
         info!(self.logger, "Dispatch done"; "stop_state" => states);
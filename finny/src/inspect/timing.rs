@@ -0,0 +1,272 @@
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use core::cell::{Cell, RefCell};
+use core::fmt::Debug;
+use core::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default upper bounds of the timing histogram's buckets.
+pub const FSM_TIMING_DEFAULT_BUCKETS: &[Duration] = &[
+    Duration::from_micros(10),
+    Duration::from_micros(50),
+    Duration::from_micros(100),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(100)
+];
+
+/// The distribution of the measured durations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmTimingHistogram {
+    /// The upper bounds of the buckets, with the cumulative count of the measurements within each.
+    pub buckets: Vec<(Duration, u64)>,
+    pub sum: Duration,
+    /// The longest measurement.
+    pub max: Duration,
+    pub count: u64
+}
+
+impl FsmTimingHistogram {
+    fn new(bounds: &[Duration]) -> Self {
+        FsmTimingHistogram {
+            buckets: bounds.iter().map(|b| (*b, 0)).collect(),
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+            count: 0
+        }
+    }
+
+    fn observe(&mut self, elapsed: Duration) {
+        for (bound, count) in self.buckets.iter_mut() {
+            if elapsed <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += elapsed;
+        self.max = self.max.max(elapsed);
+        self.count += 1;
+    }
+
+    /// The number of the measurements that took longer than the budget. Exact if the budget is one of the
+    /// bounds of the buckets, otherwise counted from the next smaller bound.
+    pub fn over_budget(&self, budget: Duration) -> u64 {
+        let within = self.buckets.iter()
+            .rev()
+            .find(|(bound, _)| *bound <= budget)
+            .map(|(_, count)| *count)
+            .unwrap_or(0);
+        self.count - within
+    }
+}
+
+/// The measurements of a transition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmTimingEntry {
+    pub fsm: String,
+    /// The transition's type name, see `FsmInfoTransition::transition_id`.
+    pub transition: String,
+    pub histogram: FsmTimingHistogram
+}
+
+struct FsmTimingData {
+    buckets: Vec<Duration>,
+    /// Keyed by the machine and the transition.
+    dispatches: HashMap<(String, String), FsmTimingHistogram>,
+    actions: HashMap<(String, String), FsmTimingHistogram>
+}
+
+impl FsmTimingData {
+    fn observe(&mut self, actions: bool, fsm: &str, transition: &str, elapsed: Duration) {
+        let FsmTimingData { buckets, dispatches, actions: action_times } = self;
+        let histograms = if actions { action_times } else { dispatches };
+        histograms.entry((fsm.to_string(), transition.to_string()))
+            .or_insert_with(|| FsmTimingHistogram::new(buckets))
+            .observe(elapsed);
+    }
+
+    fn entries(histograms: &HashMap<(String, String), FsmTimingHistogram>) -> Vec<FsmTimingEntry> {
+        let mut entries: Vec<_> = histograms.iter().map(|((fsm, transition), histogram)| FsmTimingEntry {
+            fsm: fsm.clone(),
+            transition: transition.clone(),
+            histogram: histogram.clone()
+        }).collect();
+        entries.sort_by(|a, b| (&a.fsm, &a.transition).cmp(&(&b.fsm, &b.transition)));
+        entries
+    }
+}
+
+/// Measures the monotonic time of the dispatches and of the transitions' actions, aggregated into a histogram
+/// for every transition, to find the transitions that blow the latency budget of a control loop. A dispatch is
+/// attributed to all of the transitions that it took, the dispatches that didn't take any aren't measured.
+/// The clones share the collected data, like with `InspectMetrics`.
+///
+/// Example : `timing.actions().iter().filter(|t| t.histogram.over_budget(Duration::from_millis(1)) > 0)`
+pub struct InspectTiming {
+    data: Arc<Mutex<FsmTimingData>>,
+    fsm: &'static str,
+    started: Option<Instant>,
+    // the transitions taken by the dispatch, only in the event's context
+    taken: RefCell<Vec<&'static str>>,
+    transition: Option<&'static str>,
+    action_started: Cell<Option<Instant>>
+}
+
+impl InspectTiming {
+    pub fn new() -> Self {
+        Self::with_buckets(FSM_TIMING_DEFAULT_BUCKETS)
+    }
+
+    /// Use these upper bounds of the histogram's buckets, the budget should be one of them.
+    pub fn with_buckets(buckets: &[Duration]) -> Self {
+        InspectTiming {
+            data: Arc::new(Mutex::new(FsmTimingData {
+                buckets: buckets.to_vec(),
+                dispatches: HashMap::new(),
+                actions: HashMap::new()
+            })),
+            fsm: "",
+            started: None,
+            taken: RefCell::new(vec![]),
+            transition: None,
+            action_started: Cell::new(None)
+        }
+    }
+
+    fn with_data<R: Default, U: FnOnce(&mut FsmTimingData) -> R>(&self, update: U) -> R {
+        self.data.lock().map(|mut d| update(&mut d)).unwrap_or_default()
+    }
+
+    fn context(&self, fsm: &'static str, started: Option<Instant>, transition: Option<&'static str>) -> Self {
+        InspectTiming {
+            data: self.data.clone(),
+            fsm,
+            started,
+            taken: RefCell::new(vec![]),
+            transition,
+            action_started: Cell::new(None)
+        }
+    }
+
+    /// The time of the dispatches that took the transition.
+    pub fn dispatch_time(&self, fsm: &str, transition: &str) -> Option<FsmTimingHistogram> {
+        self.with_data(|d| d.dispatches.get(&(fsm.to_string(), transition.to_string())).cloned())
+    }
+
+    /// The time of the transition's action.
+    pub fn action_time(&self, fsm: &str, transition: &str) -> Option<FsmTimingHistogram> {
+        self.with_data(|d| d.actions.get(&(fsm.to_string(), transition.to_string())).cloned())
+    }
+
+    /// The time of the dispatches of all the measured transitions, ordered by the machine and the transition.
+    pub fn dispatches(&self) -> Vec<FsmTimingEntry> {
+        self.with_data(|d| FsmTimingData::entries(&d.dispatches))
+    }
+
+    /// The time of the actions of all the measured transitions, ordered by the machine and the transition.
+    pub fn actions(&self) -> Vec<FsmTimingEntry> {
+        self.with_data(|d| FsmTimingData::entries(&d.actions))
+    }
+}
+
+impl Default for InspectTiming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for InspectTiming {
+    fn clone(&self) -> Self {
+        self.context(self.fsm, self.started, self.transition)
+    }
+}
+
+impl Inspect for InspectTiming
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        self.new_borrowed_event(event.as_ref(), fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, _event: &str, _fsm: &FsmBackendImpl<F>) -> Self {
+        self.context(short_type_name::<F>(), Some(Instant::now()), None)
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        let transition = short_type_name::<T>();
+        self.taken.borrow_mut().push(transition);
+        self.context(self.fsm, None, Some(transition))
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.context(self.fsm, None, None)
+    }
+
+    fn for_timer<F>(&self, _timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.context(self.fsm, None, None)
+    }
+
+    fn on_guard<T>(&self, _guard: &'static str, _guard_result: bool) {
+
+    }
+
+    fn on_state_enter<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_exit<S>(&self, _state: &'static str) {
+
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, _state: &'static str, _elapsed: Duration) {
+
+    }
+
+    fn on_action<S>(&self, _action: &'static str) {
+        self.action_started.set(Some(Instant::now()));
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+        if let (Some(started), Some(transition)) = (self.action_started.take(), self.transition) {
+            let elapsed = started.elapsed();
+            self.with_data(|d| d.observe(true, self.fsm, transition, elapsed));
+        }
+    }
+
+    fn on_unhandled_event(&self) {
+
+    }
+
+    fn on_queue_full(&self, _capacity: usize, _dropped: FsmQueueDropped) {
+
+    }
+
+    fn event_done<F: FsmBackend>(self, _fsm: &FsmBackendImpl<F>) {
+        if let Some(started) = self.started {
+            let elapsed = started.elapsed();
+            let mut taken = self.taken.take();
+            taken.dedup();
+            self.with_data(|d| {
+                for transition in taken {
+                    d.observe(false, self.fsm, transition, elapsed);
+                }
+            });
+        }
+    }
+
+    fn on_error<E>(&self, _msg: &str, _error: &E) where E: Debug {
+
+    }
+
+    fn info(&self, _msg: &str) {
+
+    }
+}
+
+impl InspectEvent for InspectTiming
+{
+    fn on_event<S: Any + Debug + Clone>(&self, _event: &InspectFsmEvent<S>) {
+
+    }
+}
@@ -0,0 +1,135 @@
+extern crate alloc;
+
+use tracing::{Level, Span, debug, error, event, field, info, info_span, warn};
+use crate::{FsmBackend, FsmBackendImpl, FsmEvent, FsmQueueDropped, Inspect, InspectEvent, InspectFsmEvent, short_type_name};
+use crate::lib::*;
+use core::fmt::Debug;
+use core::any::Any;
+use alloc::string::{String, ToString};
+use alloc::format;
+
+/// Inspection using the `tracing` crate. Every dispatch is wrapped in a `fsm_dispatch` span with the
+/// machine, the event, the current states and the dispatch id as fields, while the transitions, guards and state changes
+/// are emitted as events within it. The spans of the submachines are nested in their parent's span.
+pub struct InspectTracing {
+    pub span: Span
+}
+
+impl InspectTracing {
+    /// The dispatch spans will be children of the span that is current at the time of the dispatch.
+    pub fn new() -> Self {
+        InspectTracing {
+            span: Span::none()
+        }
+    }
+
+    fn with_span(&self, span: Span) -> Self {
+        InspectTracing {
+            span
+        }
+    }
+}
+
+impl Default for InspectTracing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inspect for InspectTracing
+{
+    fn new_event<F: FsmBackend>(&self, event: &FsmEvent<<F as FsmBackend>::Events, <F as FsmBackend>::Timers>, fsm: &FsmBackendImpl<F>) -> Self {
+        let event_display = match event {
+            FsmEvent::Timer(t) => format!("Fsm::Timer({:?})", t),
+            _ => event.as_ref().to_string()
+        };
+
+        self.new_borrowed_event(&event_display, fsm)
+    }
+
+    fn new_borrowed_event<F: FsmBackend>(&self, event_display: &str, fsm: &FsmBackendImpl<F>) -> Self {
+
+        let start_state = format!("{:?}", fsm.get_current_states());
+        let fsm_name = short_type_name::<F>();
+
+        let span = self.span.in_scope(|| {
+            info_span!("fsm_dispatch", fsm = fsm_name, event = %event_display, start_state = %start_state, dispatch_id = fsm.dispatch_id, stop_state = field::Empty)
+        });
+
+        span.in_scope(|| debug!("Dispatching"));
+
+        self.with_span(span)
+    }
+
+    fn for_transition<T>(&self) -> Self {
+        self.span.in_scope(|| info!(transition = short_type_name::<T>(), "Matched transition"));
+        self.with_span(self.span.clone())
+    }
+
+    fn for_sub_machine<FSub: FsmBackend>(&self) -> Self {
+        self.span.in_scope(|| debug!(sub_fsm = short_type_name::<FSub>(), "Dispatching to a submachine"));
+        self.with_span(self.span.clone())
+    }
+
+    fn for_timer<F>(&self, timer_id: <F as FsmBackend>::Timers) -> Self where F: FsmBackend {
+        self.span.in_scope(|| debug!(timer_id = ?timer_id, "Timer triggered"));
+        self.with_span(self.span.clone())
+    }
+
+    fn on_guard<T>(&self, guard: &'static str, guard_result: bool) {
+        if guard_result {
+            self.span.in_scope(|| debug!(guard, "Guard accepted"));
+        } else {
+            self.span.in_scope(|| info!(guard, "Guard rejected"));
+        }
+    }
+
+    fn on_state_enter<S>(&self, state: &'static str) {
+        self.span.in_scope(|| debug!(state = state, "Entering state"));
+    }
+
+    fn on_state_exit<S>(&self, state: &'static str) {
+        self.span.in_scope(|| debug!(state = state, "Exiting state"));
+    }
+
+    fn on_state_time<F: FsmBackend, S>(&self, state: &'static str, elapsed: Duration) {
+        self.span.in_scope(|| debug!(state = state, elapsed = ?elapsed, "Time spent in state"));
+    }
+
+    fn on_action<S>(&self, action: &'static str) {
+        self.span.in_scope(|| debug!(action, "Executing action"));
+    }
+
+    fn on_action_done<S>(&self, _action: &'static str) {
+
+    }
+
+    fn on_unhandled_event(&self) {
+        self.span.in_scope(|| info!("No transition for the event"));
+    }
+
+    fn on_queue_full(&self, capacity: usize, dropped: FsmQueueDropped) {
+        self.span.in_scope(|| warn!(capacity, dropped = ?dropped, "The event queue is full"));
+    }
+
+    fn event_done<F: FsmBackend>(self, fsm: &FsmBackendImpl<F>) {
+        let stop_state: String = format!("{:?}", fsm.get_current_states());
+        self.span.record("stop_state", field::display(&stop_state));
+        self.span.in_scope(|| debug!("Dispatch done"));
+    }
+
+    fn on_error<E>(&self, msg: &str, error: &E) where E: Debug {
+        self.span.in_scope(|| error!(error = ?error, "{}", msg));
+    }
+
+    fn info(&self, msg: &str) {
+        self.span.in_scope(|| info!("{}", msg));
+    }
+}
+
+impl InspectEvent for InspectTracing
+{
+    fn on_event<S: Any + Debug + Clone>(&self, event: &InspectFsmEvent<S>) {
+        self.span.in_scope(|| event!(Level::TRACE, event = ?event, "Inspection event"));
+    }
+}
@@ -9,13 +9,15 @@
 //! ## Features
 //! * Declarative, builder API with a procedural function macro that generate the dispatcher
 //! * Compile-time transition graph validation
-//! * No run-time allocations required, `no_std` support
+//! * No run-time allocations required, `no_std` support, with the heap-allocated queue under the `alloc` feature
 //! * Support for generics within the shared context
 //! * Transition guards and actions
-//! * State regions, also known as orthogonal states
+//! * State regions, also known as orthogonal states, with fork and join transitions
 //! * Event queueing and run-to-completition execution
-//! * Submachines, also known as Hierarchical State Machines
-//! * Timers on states
+//! * Submachines, also known as Hierarchical State Machines, nested to any depth
+//! * Timers on states, also driven by Tokio or, for async embedded, by Embassy under the `embassy` feature
+//! * Machines built at runtime from their definitions, in the `dynamic` module
+//! * A generated `extern "C"` API for embedding the machines in C codebases, with `fsm.ffi()`
 //!
 //! ## Example
 //!
@@ -83,21 +85,45 @@ mod fsm;
 
 pub mod inspect;
 pub mod timers;
+#[cfg(feature="std")]
+pub mod dynamic;
+#[cfg(feature="actor_actix")]
+pub mod actor;
 
 pub use fsm::*;
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+extern crate alloc;
 extern crate finny_derive;
 extern crate derive_more;
 
 /// The procedural macro that will transform the builder function into the FSM.
 pub use finny_derive::finny_fsm;
 
+/// Generates the FSM from an SCXML statechart document: `finny_scxml!(Machine, MachineContext, "charts/machine.scxml");`.
+/// The states and events become unit structs, the delayed `<send>` elements of `<onentry>` become the state timers and
+/// the transitions' conditions are the methods of the generated `MachineGuards` trait, implemented by the context.
+/// The path is relative to the crate's manifest.
+pub use finny_derive::finny_scxml;
+
 /// External bundled libraries to be used by the procedural macros.
 pub mod bundled {
     /// Derive_more crate for deriving the enum conversions.
     pub mod derive_more {
         pub use crate::derive_more::From;
     }
+
+    /// Serde crate for deriving the serialization of the snapshots.
+    #[cfg(feature="serde")]
+    pub mod serde {
+        pub use ::serde::*;
+    }
+
+    /// Arbitrary crate for generating the events in the fuzzers.
+    #[cfg(feature="arbitrary")]
+    pub mod arbitrary {
+        pub use ::arbitrary::*;
+    }
 }
 
 mod lib {
@@ -113,9 +139,17 @@ mod lib {
    pub use self::core::fmt::Debug;
    pub use self::core::result::Result;
    pub use self::core::fmt;
-   pub use self::core::any::type_name;
+   pub use self::core::any::{Any, type_name};
    pub use self::core::time::Duration;
+   pub use self::core::future::Future;
 
    #[cfg(feature="std")]
    pub use std::collections::VecDeque;
+   #[cfg(all(feature="alloc", not(feature="std")))]
+   pub use alloc::collections::VecDeque;
+
+   #[cfg(feature="std")]
+   pub use std::boxed::Box;
+   #[cfg(all(feature="alloc", not(feature="std")))]
+   pub use alloc::boxed::Box;
 }
\ No newline at end of file
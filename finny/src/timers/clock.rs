@@ -0,0 +1,56 @@
+//! Clocks for the timers and for measuring the time spent in the states.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::FsmClock;
+
+/// The standard library's monotonic clock, measured from the clock's creation.
+#[derive(Debug, Clone, Copy)]
+pub struct FsmClockStd {
+    created_at: Instant
+}
+
+impl FsmClockStd {
+    pub fn new() -> Self {
+        Self {
+            created_at: Instant::now()
+        }
+    }
+}
+
+impl Default for FsmClockStd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsmClock for FsmClockStd {
+    fn now(&self) -> Duration {
+        self.created_at.elapsed()
+    }
+}
+
+/// A clock that only moves when it's advanced, for the simulations and tests. The clones share the time,
+/// so the clock can be advanced after it was handed to the timers.
+#[derive(Debug, Clone, Default)]
+pub struct FsmClockManual {
+    now: Arc<Mutex<Duration>>
+}
+
+impl FsmClockManual {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, elapsed: Duration) {
+        if let Ok(mut now) = self.now.lock() {
+            *now += elapsed;
+        }
+    }
+}
+
+impl FsmClock for FsmClockManual {
+    fn now(&self) -> Duration {
+        self.now.lock().map(|now| *now).unwrap_or_default()
+    }
+}
@@ -13,6 +13,8 @@ pub struct TimersCore<F, S, Q>
 {
     timers: S,
     pending_events: ArrayDeque<Q>,
+    /// The sum of all the ticks, the clock of these timers.
+    elapsed: Duration,
     _fsm: PhantomData<F>
 }
 
@@ -31,11 +33,14 @@ impl<F, S, Q> TimersCore<F, S, Q>
         Self {
             timers,
             pending_events: ArrayDeque::new(),
+            elapsed: Duration::from_secs(0),
             _fsm: PhantomData::default()
         }
     }
 
     pub fn tick(&mut self, elapsed_since_last_tick: Duration) {
+        self.elapsed += elapsed_since_last_tick;
+
         let iter = <F as FsmBackend>::Timers::iter();
         for id in iter {
             let timer = self.timers.get_timer_storage_mut(&id);
@@ -93,4 +98,12 @@ impl<F, S, Q> FsmTimers<F> for TimersCore<F, S, Q>
     fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
         self.pending_events.pop_back()
     }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.get_timer_storage(&id).is_some()
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.elapsed)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+//! Timers on the `embassy_time` clock, and the async loop that drives the machine with the events of an
+//! `embassy_sync` channel. Doesn't allocate, the timers are kept in the machine's timers storage.
+
+use embassy_futures::select::{Either, select};
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel::Receiver};
+use embassy_time::{Instant, Timer};
+
+use crate::{AllVariants, FsmBackend, FsmEvent, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect, TimerSettings, TimersStorage};
+use crate::lib::*;
+
+/// Timers driven by the `embassy_time` driver, with their deadlines kept in the storage that is generated
+/// for the machine, like `MyFsmTimersStorage<EmbassyTimer>`.
+///
+/// Example : `TimersEmbassy::new(MyFsmTimersStorage::default())`
+pub struct TimersEmbassy<F, S>
+    where F: FsmBackend, S: TimersStorage<<F as FsmBackend>::Timers, EmbassyTimer>
+{
+    timers: S,
+    _fsm: PhantomData<F>
+}
+
+/// A running timer, with its deadline on the `embassy_time` clock.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbassyTimer {
+    deadline: Instant,
+    interval: Option<embassy_time::Duration>
+}
+
+impl<F, S> TimersEmbassy<F, S>
+    where F: FsmBackend, S: TimersStorage<<F as FsmBackend>::Timers, EmbassyTimer>
+{
+    pub fn new(timers: S) -> Self {
+        Self {
+            timers,
+            _fsm: PhantomData
+        }
+    }
+
+    /// The earliest deadline of the running timers.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        <F as FsmBackend>::Timers::iter()
+            .filter_map(|id| self.timers.get_timer_storage(&id).map(|t| t.deadline))
+            .min()
+    }
+
+    /// Wait until one of the running timers is triggered. Never completes if there are no running timers.
+    pub async fn wait_for_triggered_timer(&mut self) -> <F as FsmBackend>::Timers {
+        loop {
+            if let Some(id) = self.get_triggered_timer() {
+                return id;
+            }
+
+            match self.next_deadline() {
+                Some(deadline) => Timer::at(deadline).await,
+                None => core::future::pending::<()>().await
+            }
+        }
+    }
+}
+
+impl<F, S> FsmTimers<F> for TimersEmbassy<F, S>
+    where F: FsmBackend, S: TimersStorage<<F as FsmBackend>::Timers, EmbassyTimer>
+{
+    fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &TimerSettings) -> FsmResult<()> {
+        let _ = self.cancel(id.clone());
+
+        if settings.enabled {
+            let timeout = embassy_time::Duration::from_micros(settings.timeout.as_micros() as u64);
+            // a zero interval would trigger forever
+            let interval = if settings.renew && timeout > embassy_time::Duration::from_ticks(0) { Some(timeout) } else { None };
+            *self.timers.get_timer_storage_mut(&id) = Some(EmbassyTimer { deadline: Instant::now() + timeout, interval });
+        }
+
+        Ok(())
+    }
+
+    fn cancel(&mut self, id: <F as FsmBackend>::Timers) -> FsmResult<()> {
+        *self.timers.get_timer_storage_mut(&id) = None;
+        Ok(())
+    }
+
+    /// The missed intervals trigger on the following polls, one by one.
+    fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        let now = Instant::now();
+        for id in <F as FsmBackend>::Timers::iter() {
+            let timer = self.timers.get_timer_storage_mut(&id);
+            match timer {
+                Some(EmbassyTimer { deadline, interval: Some(interval) }) if *deadline <= now => {
+                    *deadline += *interval;
+                    return Some(id);
+                },
+                Some(EmbassyTimer { deadline, interval: None }) if *deadline <= now => {
+                    *timer = None;
+                    return Some(id);
+                },
+                _ => ()
+            }
+        }
+
+        None
+    }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.get_timer_storage(&id).is_some()
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(Duration::from_micros(Instant::now().as_micros()))
+    }
+}
+
+impl<F, Q, I, S> FsmFrontend<F, Q, I, TimersEmbassy<F, S>>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, S: TimersStorage<<F as FsmBackend>::Timers, EmbassyTimer>
+{
+    /// Dispatch the events of the channel and the timers as they trigger, forever. Meant to be awaited by an
+    /// executor's task that owns the machine, or raced against other futures. The machine should already
+    /// be started. The failed dispatches are only reported to the machine's inspector.
+    pub async fn run_embassy<M, E, const N: usize>(&mut self, events: Receiver<'_, M, E, N>)
+        where M: RawMutex, E: Into<<F as FsmBackend>::Events>
+    {
+        loop {
+            match select(events.receive(), self.timers.wait_for_triggered_timer()).await {
+                Either::First(ev) => {
+                    let _ = self.dispatch(ev);
+                },
+                Either::Second(timer_id) => {
+                    let _ = self.dispatch_single_event(FsmEvent::Timer(timer_id));
+                    let _ = self.dispatch_timer_events();
+                }
+            }
+        }
+    }
+}
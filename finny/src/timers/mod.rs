@@ -1,7 +1,22 @@
+#[cfg(feature="std")]
+pub mod clock;
+
+#[cfg(feature="std")]
+pub mod test;
+
 #[cfg(feature="timers_std")]
 pub mod std_noalloc;
 
 #[cfg(feature="timers_std")]
 pub mod std;
 
+#[cfg(feature="timers_std")]
+pub mod wheel;
+
+#[cfg(feature="timers_tokio")]
+pub mod tokio;
+
+#[cfg(feature="embassy")]
+pub mod embassy;
+
 pub mod core;
\ No newline at end of file
@@ -1,43 +1,62 @@
 //! Standard library timers with dynamic allocation of the timer's storage.
 
-use std::{time::{Duration, Instant}};
-use crate::{FsmBackend, FsmTimers};
+use std::time::Duration;
+use crate::{FsmBackend, FsmClock, FsmTimers, timers::clock::FsmClockStd};
 
-pub struct TimersStd<F>
+pub struct TimersStd<F, C = FsmClockStd>
     where F: FsmBackend
 {
     timers: Vec<(<F as FsmBackend>::Timers, StdTimer)>,
-    pending_intervals: Option<(<F as FsmBackend>::Timers, usize)>
+    pending_intervals: Option<(<F as FsmBackend>::Timers, usize)>,
+    clock: C
 }
 
 #[derive(Debug)]
 enum StdTimer {
-    Timeout { started_at: Instant, duration: Duration },
-    Interval { started_at: Instant, interval: Duration }
+    Timeout { started_at: Duration, duration: Duration },
+    Interval { started_at: Duration, interval: Duration }
 }
 
-impl<F> TimersStd<F>
+impl<F> TimersStd<F, FsmClockStd>
     where F: FsmBackend
 {
     pub fn new() -> Self {
+        Self::with_clock(FsmClockStd::new())
+    }
+}
+
+impl<F> Default for TimersStd<F, FsmClockStd>
+    where F: FsmBackend
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, C> TimersStd<F, C>
+    where F: FsmBackend, C: FsmClock
+{
+    /// Timers driven by a custom clock.
+    pub fn with_clock(clock: C) -> Self {
         Self {
             timers: vec![],
-            pending_intervals: None
+            pending_intervals: None,
+            clock
         }
     }
 }
 
-impl<F> FsmTimers<F> for TimersStd<F>
-    where F: FsmBackend
+impl<F, C> FsmTimers<F> for TimersStd<F, C>
+    where F: FsmBackend, C: FsmClock
 {
     fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &crate::TimerSettings) -> crate::FsmResult<()> {
         // try to cancel any existing ones
         self.cancel(id.clone())?;
 
         if settings.renew {
-            self.timers.push((id, StdTimer::Interval { started_at: Instant::now(), interval: settings.timeout }));
+            self.timers.push((id, StdTimer::Interval { started_at: self.clock.now(), interval: settings.timeout }));
         } else {
-            self.timers.push((id, StdTimer::Timeout { started_at: Instant::now(), duration: settings.timeout }));
+            self.timers.push((id, StdTimer::Timeout { started_at: self.clock.now(), duration: settings.timeout }));
         }
 
         Ok(())
@@ -59,15 +78,15 @@ impl<F> FsmTimers<F> for TimersStd<F>
         }
 
         let mut timed_out_idx = None;
-        let now = Instant::now();
+        let now = self.clock.now();
         for (idx, (timer_id, timer)) in self.timers.iter_mut().enumerate() {
             match timer {
-                StdTimer::Timeout { started_at, duration } if now.duration_since(*started_at) >= *duration => {
+                StdTimer::Timeout { started_at, duration } if now.saturating_sub(*started_at) >= *duration => {
                     timed_out_idx = Some(idx);
                     break;
                 },
-                StdTimer::Interval { ref mut started_at, interval } if now.duration_since(*started_at) >= *interval => {
-                    let t = now.duration_since(*started_at);
+                StdTimer::Interval { ref mut started_at, interval } if now.saturating_sub(*started_at) >= *interval => {
+                    let t = now.saturating_sub(*started_at);
                     let times = ((t.as_secs_f32() / interval.as_secs_f32()).floor() as usize) - 1;
                     if times > 0 {
                         self.pending_intervals = Some((timer_id.clone(), times));
@@ -86,4 +105,12 @@ impl<F> FsmTimers<F> for TimersStd<F>
 
         None
     }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.iter().any(|(timer_id, _)| *timer_id == id)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.clock.now())
+    }
 }
\ No newline at end of file
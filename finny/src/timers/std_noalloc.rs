@@ -1,37 +1,52 @@
-//! A naive timers implementation based on standard libraries' `Instant` time provider and no runtime allocations.
+//! A naive timers implementation based on a monotonic clock, the standard library's `Instant` by default, and no runtime allocations.
 //! Type system has to be setup manually.
 
-use std::{time::{Duration, Instant}};
-use crate::{FsmBackend, FsmTimers, TimersStorage, AllVariants};
+use std::time::Duration;
+use crate::{FsmBackend, FsmClock, FsmTimers, TimersStorage, AllVariants, timers::clock::FsmClockStd};
 
-pub struct TimersStdNoAlloc<F, S>
+pub struct TimersStdNoAlloc<F, S, C = FsmClockStd>
     where F: FsmBackend
 {
     timers: S,
-    pending_intervals: Option<(<F as FsmBackend>::Timers, usize)>
+    pending_intervals: Option<(<F as FsmBackend>::Timers, usize)>,
+    clock: C
 }
 
+/// A timer, with the times as measured by the timers' clock.
 #[derive(Debug)]
 pub enum StdTimer {
-    Timeout { started_at: Instant, duration: Duration },
-    Interval { started_at: Instant, interval: Duration }
+    Timeout { started_at: Duration, duration: Duration },
+    Interval { started_at: Duration, interval: Duration }
 }
 
-impl<F, S> TimersStdNoAlloc<F, S>
+impl<F, S> TimersStdNoAlloc<F, S, FsmClockStd>
     where F: FsmBackend,
     S: TimersStorage<<F as FsmBackend>::Timers, StdTimer>,
 {
     pub fn new(timers: S) -> Self {
+        Self::with_clock(timers, FsmClockStd::new())
+    }
+}
+
+impl<F, S, C> TimersStdNoAlloc<F, S, C>
+    where F: FsmBackend,
+    S: TimersStorage<<F as FsmBackend>::Timers, StdTimer>,
+    C: FsmClock
+{
+    /// Timers driven by a custom clock.
+    pub fn with_clock(timers: S, clock: C) -> Self {
         Self {
             timers,
-            pending_intervals: None
+            pending_intervals: None,
+            clock
         }
     }
 }
 
-impl<F, S> FsmTimers<F> for TimersStdNoAlloc<F, S>
+impl<F, S, C> FsmTimers<F> for TimersStdNoAlloc<F, S, C>
     where F: FsmBackend,
-    S: TimersStorage<<F as FsmBackend>::Timers, StdTimer>
+    S: TimersStorage<<F as FsmBackend>::Timers, StdTimer>,
+    C: FsmClock
 {
     fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &crate::TimerSettings) -> crate::FsmResult<()> {
         // try to cancel any existing ones
@@ -40,9 +55,9 @@ impl<F, S> FsmTimers<F> for TimersStdNoAlloc<F, S>
         let t = self.timers.get_timer_storage_mut(&id);
 
         if settings.renew {
-            *t = Some(StdTimer::Interval { started_at: Instant::now(), interval: settings.timeout });
+            *t = Some(StdTimer::Interval { started_at: self.clock.now(), interval: settings.timeout });
         } else {
-            *t = Some(StdTimer::Timeout { started_at: Instant::now(), duration: settings.timeout });
+            *t = Some(StdTimer::Timeout { started_at: self.clock.now(), duration: settings.timeout });
         }
 
         Ok(())
@@ -65,17 +80,17 @@ impl<F, S> FsmTimers<F> for TimersStdNoAlloc<F, S>
         }
 
         let mut timed_out_id = None;
-        let now = Instant::now();
+        let now = self.clock.now();
 
         for timer_id in <F as FsmBackend>::Timers::iter() {
             let timer = self.timers.get_timer_storage_mut(&timer_id);
             match timer {
-                Some(StdTimer::Timeout { started_at, duration }) if now.duration_since(*started_at) >= *duration => {
+                Some(StdTimer::Timeout { started_at, duration }) if now.saturating_sub(*started_at) >= *duration => {
                     timed_out_id = Some(timer_id);
                     break;
                 },
-                Some(StdTimer::Interval { ref mut started_at, interval }) if now.duration_since(*started_at) >= *interval => {
-                    let t = now.duration_since(*started_at);
+                Some(StdTimer::Interval { ref mut started_at, interval }) if now.saturating_sub(*started_at) >= *interval => {
+                    let t = now.saturating_sub(*started_at);
                     let times = ((t.as_secs_f32() / interval.as_secs_f32()).floor() as usize) - 1;
                     if times > 0 {
                         self.pending_intervals = Some((timer_id.clone(), times));
@@ -95,4 +110,12 @@ impl<F, S> FsmTimers<F> for TimersStdNoAlloc<F, S>
         
         None
     }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.get_timer_storage(&id).is_some()
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.clock.now())
+    }
 }
\ No newline at end of file
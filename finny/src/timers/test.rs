@@ -0,0 +1,150 @@
+//! Timers with a simulated clock for the tests, which fire deterministically as the time is advanced.
+
+use crate::{FsmBackend, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect, TimerSettings};
+use crate::lib::*;
+
+/// Timers whose time only moves with `advance`, for testing the machines with timers without sleeping.
+/// Advancing the machine's frontend fires the timers in the order of their deadlines, at the time
+/// of their deadlines, so the timers that were started or cancelled by the triggered transitions
+/// behave as they would in real time.
+///
+/// Example : `fsm.advance(Duration::from_secs(5))?`
+pub struct TimersTest<F>
+    where F: FsmBackend
+{
+    timers: Vec<(<F as FsmBackend>::Timers, TestTimer)>,
+    triggered: VecDeque<<F as FsmBackend>::Timers>,
+    now: Duration
+}
+
+#[derive(Debug)]
+struct TestTimer {
+    deadline: Duration,
+    interval: Option<Duration>
+}
+
+impl<F> TimersTest<F>
+    where F: FsmBackend
+{
+    pub fn new() -> Self {
+        Self {
+            timers: vec![],
+            triggered: VecDeque::new(),
+            now: Duration::from_secs(0)
+        }
+    }
+
+    /// The simulated time, since the creation of the timers.
+    pub fn elapsed(&self) -> Duration {
+        self.now
+    }
+
+    /// The earliest deadline of the running timers.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.timers.iter().map(|(_, t)| t.deadline).min()
+    }
+
+    /// Move the time forward and queue the expired timers, in the order of their deadlines. The timers
+    /// are only dispatched by the machine; use the frontend's `advance` to also fire the timers that
+    /// are started by the triggered transitions.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let until = self.now + elapsed;
+        while let Some(deadline) = self.next_deadline().filter(|d| *d <= until) {
+            self.advance_to(deadline);
+        }
+        self.now = until;
+    }
+
+    /// Move the time to the deadline and queue the timers that expire at it.
+    fn advance_to(&mut self, deadline: Duration) {
+        self.now = self.now.max(deadline);
+
+        let mut idx = 0;
+        while idx < self.timers.len() {
+            let (id, timer) = &mut self.timers[idx];
+            if timer.deadline > deadline {
+                idx += 1;
+                continue;
+            }
+
+            self.triggered.push_back(id.clone());
+            match timer.interval {
+                Some(interval) => {
+                    timer.deadline += interval;
+                    idx += 1;
+                },
+                None => {
+                    self.timers.remove(idx);
+                }
+            }
+        }
+    }
+}
+
+impl<F> Default for TimersTest<F>
+    where F: FsmBackend
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> FsmTimers<F> for TimersTest<F>
+    where F: FsmBackend
+{
+    fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &TimerSettings) -> FsmResult<()> {
+        self.cancel(id.clone())?;
+
+        if settings.enabled {
+            // a zero interval would never let the time move forward
+            let interval = if settings.renew && settings.timeout > Duration::from_secs(0) { Some(settings.timeout) } else { None };
+            self.timers.push((id, TestTimer { deadline: self.now + settings.timeout, interval }));
+        }
+
+        Ok(())
+    }
+
+    fn cancel(&mut self, id: <F as FsmBackend>::Timers) -> FsmResult<()> {
+        self.timers.retain(|(timer_id, _)| *timer_id != id);
+        self.triggered.retain(|timer_id| *timer_id != id);
+        Ok(())
+    }
+
+    fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        self.triggered.pop_front()
+    }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.iter().any(|(timer_id, _)| *timer_id == id)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.now)
+    }
+}
+
+impl<F, Q, I> FsmFrontend<F, Q, I, TimersTest<F>>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect
+{
+    /// Move the simulated time forward, dispatching each of the timers and the scheduled events at its
+    /// deadline, including the ones that were started by the dispatched events.
+    pub fn advance(&mut self, elapsed: Duration) -> FsmResult<()> {
+        let until = self.timers.now + elapsed;
+        loop {
+            let deadline = match (self.timers.next_deadline(), self.backend.timer_requests.next_scheduled_at()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b)
+            };
+            match deadline.filter(|d| *d <= until) {
+                Some(deadline) => {
+                    self.timers.advance_to(deadline);
+                    self.dispatch_timer_events()?;
+                },
+                None => break
+            }
+        }
+        self.timers.now = until;
+
+        self.dispatch_timer_events()
+    }
+}
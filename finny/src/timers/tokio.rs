@@ -0,0 +1,202 @@
+//! Timers driven by the Tokio runtime. Every started timer is a spawned task that sleeps
+//! and reports back through a channel, so the FSM can simply await the next triggered timer.
+//!
+//! The triggered timers aren't fed into the machine's event queue by themselves, `dispatch` doesn't
+//! look at them. Drive them with `run_timers` or a loop over `wait_and_dispatch_timer_events`, raced
+//! against the other events of the machine, or use the Tokio runner that does both.
+
+use std::time::Duration;
+use tokio::{sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel}, task::JoinHandle, time::{Instant, interval_at, sleep}};
+use crate::{FsmBackend, FsmBackendAsync, FsmError, FsmEvent, FsmEventQueue, FsmFrontend, FsmResult, FsmTimers, Inspect, TimerSettings};
+
+/// Timers that are scheduled as tasks on the current Tokio runtime. Timers have to be created
+/// from within a runtime context, meaning the FSM has to be started and driven from a runtime.
+pub struct TimersTokio<F>
+    where F: FsmBackend
+{
+    timers: Vec<TokioTimer<F>>,
+    generation: usize,
+    created_at: Instant,
+    sender: UnboundedSender<(<F as FsmBackend>::Timers, usize)>,
+    receiver: UnboundedReceiver<(<F as FsmBackend>::Timers, usize)>
+}
+
+struct TokioTimer<F>
+    where F: FsmBackend
+{
+    id: <F as FsmBackend>::Timers,
+    generation: usize,
+    renew: bool,
+    task: JoinHandle<()>
+}
+
+impl<F> TimersTokio<F>
+    where F: FsmBackend
+{
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+        Self {
+            timers: vec![],
+            generation: 0,
+            created_at: Instant::now(),
+            sender,
+            receiver
+        }
+    }
+
+    /// Wait until one of the running timers is triggered. Never completes if there are no running timers.
+    pub async fn wait_for_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        loop {
+            let (id, generation) = self.receiver.recv().await?;
+            if let Some(id) = self.accept_triggered(id, generation) {
+                return Some(id);
+            }
+        }
+    }
+
+    /// Triggers of cancelled or restarted timers might still be in the channel, skip those.
+    fn accept_triggered(&mut self, id: <F as FsmBackend>::Timers, generation: usize) -> Option<<F as FsmBackend>::Timers> {
+        let idx = self.timers.iter().position(|t| t.id == id && t.generation == generation)?;
+        if !self.timers[idx].renew {
+            self.timers.remove(idx);
+        }
+        Some(id)
+    }
+}
+
+impl<F> FsmTimers<F> for TimersTokio<F>
+    where F: FsmBackend, <F as FsmBackend>::Timers: Send + 'static
+{
+    fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &TimerSettings) -> FsmResult<()> {
+        // try to cancel any existing ones
+        self.cancel(id.clone())?;
+
+        self.generation = self.generation.wrapping_add(1);
+        let generation = self.generation;
+        let sender = self.sender.clone();
+        let timeout = settings.timeout;
+        let timer_id = id.clone();
+
+        let task = if settings.renew {
+            tokio::spawn(async move {
+                let mut interval = interval_at(Instant::now() + timeout, timeout);
+                loop {
+                    interval.tick().await;
+                    if sender.send((timer_id.clone(), generation)).is_err() {
+                        break;
+                    }
+                }
+            })
+        } else {
+            tokio::spawn(async move {
+                sleep(timeout).await;
+                let _ = sender.send((timer_id, generation));
+            })
+        };
+
+        self.timers.push(TokioTimer { id, generation, renew: settings.renew, task });
+
+        Ok(())
+    }
+
+    fn cancel(&mut self, id: <F as FsmBackend>::Timers) -> FsmResult<()> {
+        self.timers.retain(|timer| {
+            if timer.id == id {
+                timer.task.abort();
+                false
+            } else {
+                true
+            }
+        });
+        Ok(())
+    }
+
+    fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        while let Ok((id, generation)) = self.receiver.try_recv() {
+            if let Some(id) = self.accept_triggered(id, generation) {
+                return Some(id);
+            }
+        }
+
+        None
+    }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.iter().any(|timer| timer.id == id)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.created_at.elapsed())
+    }
+}
+
+impl<F> Default for TimersTokio<F>
+    where F: FsmBackend
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Drop for TimersTokio<F>
+    where F: FsmBackend
+{
+    fn drop(&mut self) {
+        for timer in &self.timers {
+            timer.task.abort();
+        }
+    }
+}
+
+impl<F, Q, I> FsmFrontend<F, Q, I, TimersTokio<F>>
+    where F: FsmBackend, Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::Timers: Send + 'static
+{
+    /// Wait until the next timer triggers or the earliest event scheduled with `enqueue_after` is due.
+    /// Returns the triggered timer.
+    pub async fn wait_for_timer_or_scheduled(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        match self.next_scheduled_in() {
+            Some(due_in) => tokio::select! {
+                timer_id = self.timers.wait_for_triggered_timer() => timer_id,
+                _ = sleep(due_in) => None
+            },
+            None => self.timers.wait_for_triggered_timer().await
+        }
+    }
+
+    /// Wait for the next timer to trigger or for the next scheduled event, then dispatch it along with all
+    /// the other pending timer events and the due scheduled events, and run the queue until completition.
+    pub async fn wait_and_dispatch_timer_events(&mut self) -> FsmResult<()> {
+        let triggered = match self.wait_for_timer_or_scheduled().await {
+            Some(timer_id) => self.dispatch_single_event(FsmEvent::Timer(timer_id)),
+            None => Ok(())
+        };
+
+        triggered.and(self.dispatch_timer_events())
+    }
+
+    /// Keep dispatching the timer events as they are triggered. The events that aren't handled by the current
+    /// states are skipped, only completes on the other dispatch errors. Meant to be raced against other futures.
+    pub async fn run_timers(&mut self) -> FsmResult<()> {
+        loop {
+            match self.wait_and_dispatch_timer_events().await {
+                Ok(()) | Err(FsmError::NoTransition { .. }) => (),
+                Err(e) => return Err(e)
+            }
+        }
+    }
+}
+
+impl<F, Q, I> FsmFrontend<F, Q, I, TimersTokio<F>>
+    where F: FsmBackendAsync, Q: FsmEventQueue<F>, I: Inspect, <F as FsmBackend>::Timers: Send + 'static
+{
+    /// Wait for the next timer to trigger or for the next scheduled event, then dispatch the pending timer events
+    /// and the due scheduled events using the async dispatch path.
+    pub async fn wait_and_dispatch_timer_events_async(&mut self) -> FsmResult<()> {
+        let triggered = match self.wait_for_timer_or_scheduled().await {
+            Some(timer_id) => self.dispatch_single_event_async(FsmEvent::Timer(timer_id)).await,
+            None => Ok(())
+        };
+
+        triggered.and(self.dispatch_timer_events_async().await)
+    }
+}
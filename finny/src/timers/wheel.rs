@@ -0,0 +1,211 @@
+//! Hashed timer wheel, for the machines with many running timers.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::{AllVariants, FsmBackend, FsmClock, FsmResult, FsmTimers, TimerSettings, timers::clock::FsmClockStd};
+
+/// The default duration of a tick of the wheel.
+pub const FSM_TIMERS_WHEEL_TICK: Duration = Duration::from_millis(1);
+
+/// The default number of the wheel's slots.
+pub const FSM_TIMERS_WHEEL_SLOTS: usize = 256;
+
+/// Timers kept in a hashed wheel of slots, each slot holding the timers whose deadline falls on it, modulo
+/// the size of the wheel. Starting a timer pushes it into its slot and cancelling it only invalidates it,
+/// so both are constant time, and polling only visits the slots of the ticks that passed since the
+/// previous poll. The deadlines are rounded up to the wheel's tick.
+///
+/// Example : `TimersWheel::with_resolution(FsmClockStd::new(), Duration::from_millis(10), 1024)`
+pub struct TimersWheel<F, C = FsmClockStd>
+    where F: FsmBackend
+{
+    slots: Vec<Vec<WheelTimer<F>>>,
+    /// The machine's timer ids, with the generation of their current instance and whether it's running.
+    timers: Vec<(<F as FsmBackend>::Timers, u64, bool)>,
+    triggered: VecDeque<<F as FsmBackend>::Timers>,
+    /// The last tick that was polled.
+    tick: u64,
+    resolution: Duration,
+    clock: C
+}
+
+struct WheelTimer<F: FsmBackend> {
+    id: <F as FsmBackend>::Timers,
+    generation: u64,
+    deadline: u64,
+    interval: Option<u64>
+}
+
+impl<F> TimersWheel<F, FsmClockStd>
+    where F: FsmBackend
+{
+    pub fn new() -> Self {
+        Self::with_clock(FsmClockStd::new())
+    }
+}
+
+impl<F> Default for TimersWheel<F, FsmClockStd>
+    where F: FsmBackend
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, C> TimersWheel<F, C>
+    where F: FsmBackend, C: FsmClock
+{
+    /// Timers driven by a custom clock, with the default tick and number of slots.
+    pub fn with_clock(clock: C) -> Self {
+        Self::with_resolution(clock, FSM_TIMERS_WHEEL_TICK, FSM_TIMERS_WHEEL_SLOTS)
+    }
+
+    /// Timers with the duration of the wheel's tick and its number of slots. The timers whose timeouts
+    /// are longer than a turn of the wheel wait in their slot for the later turns.
+    pub fn with_resolution(clock: C, tick: Duration, slots: usize) -> Self {
+        let resolution = if tick.is_zero() { FSM_TIMERS_WHEEL_TICK } else { tick };
+        let mut wheel = Self {
+            slots: (0..slots.max(1)).map(|_| Vec::new()).collect(),
+            timers: <F as FsmBackend>::Timers::iter().map(|id| (id, 0, false)).collect(),
+            triggered: VecDeque::new(),
+            tick: 0,
+            resolution,
+            clock
+        };
+        wheel.tick = wheel.current_tick();
+        wheel
+    }
+
+    /// The number of the started timers that are waiting in the wheel, including the cancelled ones
+    /// whose slot wasn't visited yet.
+    pub fn len(&self) -> usize {
+        self.slots.iter().map(|s| s.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|s| s.is_empty())
+    }
+
+    fn current_tick(&self) -> u64 {
+        (self.clock.now().as_nanos() / self.resolution.as_nanos()) as u64
+    }
+
+    /// The duration in ticks, rounded up.
+    fn to_ticks(&self, duration: Duration) -> u64 {
+        let resolution = self.resolution.as_nanos();
+        duration.as_nanos().div_ceil(resolution) as u64
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+
+    fn timer_mut(&mut self, id: &<F as FsmBackend>::Timers) -> Option<&mut (<F as FsmBackend>::Timers, u64, bool)> {
+        self.timers.iter_mut().find(|(timer_id, _, _)| timer_id == id)
+    }
+
+    fn is_current(&self, timer: &WheelTimer<F>) -> bool {
+        self.timers.iter().any(|(id, generation, running)| *running && *generation == timer.generation && *id == timer.id)
+    }
+
+    /// Visit the slots of the ticks that passed since the last poll and queue the expired timers. After
+    /// a full turn of the wheel, every slot is visited once.
+    fn poll(&mut self) {
+        let now = self.current_tick();
+        if now <= self.tick {
+            return;
+        }
+
+        let ticks = (now - self.tick).min(self.slots.len() as u64);
+        for tick in (self.tick + 1)..=(self.tick + ticks) {
+            let slot = self.slot(tick);
+            let timers = core::mem::take(&mut self.slots[slot]);
+            for mut timer in timers {
+                if !self.is_current(&timer) {
+                    continue;
+                }
+
+                if timer.deadline > now {
+                    self.slots[slot].push(timer);
+                    continue;
+                }
+
+                match timer.interval {
+                    Some(interval) => {
+                        // the missed intervals are triggered as well
+                        while timer.deadline <= now {
+                            self.triggered.push_back(timer.id.clone());
+                            timer.deadline += interval;
+                        }
+                        let next = self.slot(timer.deadline);
+                        self.slots[next].push(timer);
+                    },
+                    None => {
+                        self.triggered.push_back(timer.id.clone());
+                        if let Some(entry) = self.timer_mut(&timer.id) {
+                            entry.2 = false;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.tick = now;
+    }
+}
+
+impl<F, C> FsmTimers<F> for TimersWheel<F, C>
+    where F: FsmBackend, C: FsmClock
+{
+    fn create(&mut self, id: <F as FsmBackend>::Timers, settings: &TimerSettings) -> FsmResult<()> {
+        self.cancel(id.clone())?;
+
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        // a zero interval would trigger forever
+        let timeout = self.to_ticks(settings.timeout).max(1);
+        let interval = if settings.renew { Some(timeout) } else { None };
+        let deadline = self.current_tick().max(self.tick) + timeout;
+
+        let generation = match self.timer_mut(&id) {
+            Some(entry) => {
+                entry.1 += 1;
+                entry.2 = true;
+                entry.1
+            },
+            None => return Err(crate::FsmTimerError::NotSupported.into())
+        };
+
+        let slot = self.slot(deadline);
+        self.slots[slot].push(WheelTimer { id, generation, deadline, interval });
+
+        Ok(())
+    }
+
+    fn cancel(&mut self, id: <F as FsmBackend>::Timers) -> FsmResult<()> {
+        // the cancelled timer is dropped once its slot is visited
+        if let Some(entry) = self.timer_mut(&id) {
+            entry.2 = false;
+        }
+        self.triggered.retain(|timer_id| *timer_id != id);
+        Ok(())
+    }
+
+    fn get_triggered_timer(&mut self) -> Option<<F as FsmBackend>::Timers> {
+        if self.triggered.is_empty() {
+            self.poll();
+        }
+
+        self.triggered.pop_front()
+    }
+
+    fn is_running(&self, id: <F as FsmBackend>::Timers) -> bool {
+        self.timers.iter().any(|(timer_id, _, running)| *running && *timer_id == id)
+    }
+
+    fn now(&self) -> Option<Duration> {
+        Some(self.clock.now())
+    }
+}
@@ -2,9 +2,11 @@ use std::collections::HashSet;
 
 use proc_macro2::{TokenStream};
 use quote::{TokenStreamExt, quote};
-use crate::{codegen_meta::generate_fsm_meta, fsm::FsmTypes, parse::{FsmState, FsmStateAction, FsmStateKind}, utils::{remap_closure_inputs, to_field_name, tokens_to_string}};
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use crate::{codegen_ffi::generate_fsm_ffi, codegen_meta::generate_fsm_meta, fsm::FsmTypes, parse::{EventGuardAction, FsmRegion, FsmState, FsmStateAction, FsmStateHistory, FsmStateKind, FsmSubMachineOptions}, utils::{event_variant, remap_closure_inputs, to_field_name, tokens_to_string, with_lifetime}};
 
-use crate::{parse::{FsmFnInput, FsmStateTransition, FsmTransitionState, FsmTransitionType}, utils::ty_append};
+use crate::{parse::{FsmFnInput, FsmStateTransition, FsmTransition, FsmTransitionRateLimit, FsmTransitionState, FsmTransitionType}, utils::ty_append};
 
 pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStream) -> syn::Result<TokenStream> {
     let fsm_ty = &fsm.base.fsm_ty;
@@ -18,16 +20,30 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
     let timers_enum_iter_ty = fsm_types.get_fsm_timers_iter_ty();
     let timers_storage_ty = fsm_types.get_fsm_timers_storage_ty();
     let event_enum_ty = fsm_types.get_fsm_events_ty();
+    let borrowed_event_enum_ty = fsm_types.get_fsm_borrowed_events_ty();
 
     let region_count = fsm.fsm.regions.len();
 
     let (fsm_generics_impl, fsm_generics_type, fsm_generics_where) = fsm.base.fsm_generics.split_for_impl();
 
+    let (serde_derives, serde_skip) = if fsm.fsm.codegen_options.derive_serde {
+        (quote! {
+            #[derive(finny::bundled::serde::Serialize, finny::bundled::serde::Deserialize)]
+            #[serde(crate = "finny::bundled::serde")]
+        }, quote! {
+            #[serde(skip)]
+        })
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    };
+
     let states_store = {
 
         let mut code_fields = TokenStream::new();
         let mut new_state_fields = TokenStream::new();
         let mut state_variants = TokenStream::new();
+        let mut state_names = TokenStream::new();
+        let mut state_from_ids = TokenStream::new();
         let mut state_accessors = TokenStream::new();
 
 
@@ -41,7 +57,7 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                 let timer_ty = timer.get_ty(&fsm.base);
                 let timer_field = timer.get_field(&fsm.base);
 
-                code_fields.append_all(quote! { #timer_field: #timer_ty #fsm_generics_type, });
+                code_fields.append_all(quote! { #serde_skip #timer_field: #timer_ty #fsm_generics_type, });
                 new_state_fields.append_all(quote! { #timer_field: #timer_ty::default(), });
 
                 state_accessors.append_all(quote! {
@@ -60,7 +76,11 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
             }
 
             code_fields.append_all(quote! { #name: #ty, });
-            state_variants.append_all(quote!{ #ty_name, });
+            let state_id = state.stable_id();
+            state_variants.append_all(quote!{ #ty_name = #state_id, });
+            let ty_name_str = state.display_name();
+            state_names.append_all(quote!{ #states_enum_ty :: #ty_name => #ty_name_str, });
+            state_from_ids.append_all(quote!{ #state_id => Some(#states_enum_ty :: #ty_name), });
 
             let new_state_field = match state.kind {
                 FsmStateKind::Normal => {
@@ -157,10 +177,51 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
             }
         }
 
+        let is_final_state = {
+            let final_states: Vec<_> = fsm.fsm.states.values().filter(|s| s.is_final).map(|s| {
+                let state_ty = FsmTypes::new(&s.ty, &fsm.base.fsm_generics);
+                let variant = state_ty.get_fsm_no_generics_ty().clone();
+                quote! { #states_enum_ty :: #variant }
+            }).collect();
+
+            if final_states.is_empty() {
+                TokenStream::new()
+            } else {
+                quote! {
+                    fn is_final_state(state: Self::StateKind) -> bool {
+                        matches!(state, #(#final_states)|*)
+                    }
+                }
+            }
+        };
+
+        let states_debug = if fsm.fsm.codegen_options.states_debug {
+            let fields = fsm.fsm.states.values().map(|state| {
+                let field = &state.state_storage_field;
+                let name = field.to_string();
+                quote! { .field(#name, &self. #field) }
+            });
+            let name = tokens_to_string(&states_store_ty);
+
+            quote! {
+                impl #fsm_generics_impl core::fmt::Debug for #states_store_ty #fsm_generics_type #fsm_generics_where {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        f.debug_struct(#name)
+                            #(#fields)*
+                            .finish()
+                    }
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
         quote! {
             /// States storage struct for the state machine.
+            #serde_derives
             pub struct #states_store_ty #fsm_generics_type #fsm_generics_where {
                 #code_fields
+                #serde_skip
                 _fsm: core::marker::PhantomData< #fsm_ty #fsm_generics_type >
             }
             
@@ -173,15 +234,59 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                     Ok(s)
                 }
             }
+
+            impl #fsm_generics_impl #states_store_ty #fsm_generics_type #fsm_generics_where {
+                /// Read any of the states, including the states of the other regions, like from a guard. The states
+                /// keep their data while they aren't active.
+                pub fn get<S>(&self) -> &S
+                    where Self: core::convert::AsRef<S>
+                {
+                    self.as_ref()
+                }
+            }
             
-            #[derive(Copy, Clone, Debug, PartialEq)]
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+            #serde_derives
+            #[repr(u32)]
             pub enum #states_enum_ty {
                 #state_variants
             }
 
+            impl #states_enum_ty {
+                /// The name of the state.
+                pub fn state_name(&self) -> &'static str {
+                    match self {
+                        #state_names
+                    }
+                }
+            }
+
+            impl core::fmt::Display for #states_enum_ty {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(self.state_name())
+                }
+            }
+
+            #states_debug
+
+            impl finny::FsmId for #states_enum_ty {
+                fn to_id(&self) -> u32 {
+                    *self as u32
+                }
+
+                fn from_id(id: u32) -> Option<Self> {
+                    match id {
+                        #state_from_ids
+                        _ => None
+                    }
+                }
+            }
+
             impl #fsm_generics_impl finny::FsmStates< #fsm_ty #fsm_generics_type > for #states_store_ty #fsm_generics_type #fsm_generics_where {
                 type StateKind = #states_enum_ty;
                 type CurrentState = [finny::FsmCurrentState<Self::StateKind>; #region_count];
+
+                #is_final_state
             }
 
             #state_accessors
@@ -204,38 +309,158 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
         let mut variants = TokenStream::new();
         let mut as_ref_str = TokenStream::new();
+        let mut priorities = TokenStream::new();
+        let mut trace_contexts = TokenStream::new();
+        let mut arbitrary_variants = TokenStream::new();
         let mut i = 0;
 
-        for (ty, _ev) in  fsm.fsm.events.iter() {
-            let ty_str = crate::utils::tokens_to_string(ty);
+        let event_kind_ty = ty_append(&fsm.base.fsm_ty, "EventKind");
+        let mut kind_variants = TokenStream::new();
+        let mut kind_names = TokenStream::new();
+        let mut kind_from_ids = TokenStream::new();
+        let mut event_kinds = TokenStream::new();
+        let mut event_ids = HashSet::new();
+
+        let mut borrowed_variants = TokenStream::new();
+        let mut borrowed_names = TokenStream::new();
+        let mut borrowed_ids = TokenStream::new();
+        let mut borrowed_from = TokenStream::new();
+
+        for (ty, ev) in  fsm.fsm.events.iter() {
+            if ev.is_borrowed() {
+                let variant = ev.variant();
+                let variant_str = ev.display_name();
+                let ty_e = with_lifetime(ty.clone(), &syn::Lifetime::new("'e", Span::call_site()));
+
+                borrowed_variants.append_all(quote! { #variant ( #ty_e ), });
+                borrowed_names.append_all(quote! { #borrowed_event_enum_ty :: #variant(_) => #variant_str, });
+                let id = ev.stable_id();
+                borrowed_ids.append_all(quote! { #borrowed_event_enum_ty :: #variant(_) => #id, });
+                borrowed_from.append_all(quote! {
+                    impl<'e> From< #ty_e > for #borrowed_event_enum_ty <'e> {
+                        fn from(ev: #ty_e) -> Self {
+                            #borrowed_event_enum_ty :: #variant(ev)
+                        }
+                    }
+                });
+                continue;
+            }
 
-            variants.append_all(quote! { #ty ( #ty ),  });            
-            as_ref_str.append_all(quote! { #event_enum_ty:: #ty(_) => #ty_str, });
+            let variant = ev.variant();
+            let ty_str = ev.display_name();
+
+            variants.append_all(quote! { #variant ( #ty ),  });            
+            as_ref_str.append_all(quote! { #event_enum_ty:: #variant(_) => #ty_str, });
+            let idx = i as usize;
+            arbitrary_variants.append_all(quote! { #idx => #event_enum_ty:: #variant(finny::bundled::arbitrary::Arbitrary::arbitrary(u)?), });
+            let id = ev.stable_id();
+            event_ids.insert(id);
+            kind_variants.append_all(quote! { #variant = #id, });
+            kind_names.append_all(quote! { #event_kind_ty:: #variant => #ty_str, });
+            kind_from_ids.append_all(quote! { #id => Some(#event_kind_ty:: #variant), });
+            event_kinds.append_all(quote! { #event_enum_ty:: #variant(_) => #event_kind_ty:: #variant, });
+            if let Some(ref priority) = ev.priority {
+                priorities.append_all(quote! { #event_enum_ty:: #variant(_) => #priority, });
+            }
+            if let Some(ref closure) = ev.trace_context {
+                let remap = remap_closure_inputs(&closure.inputs, &[quote! { ev }])?;
+                let body = &closure.body;
+                trace_contexts.append_all(quote! {
+                    #event_enum_ty:: #variant(ev) => {
+                        #remap
+                        let trace_context: &dyn core::any::Any = #body;
+                        Some(trace_context)
+                    },
+                });
+            }
             i += 1;
         }
 
-        for (_sub, state) in submachines {
+        let mut lifted_from = TokenStream::new();
+
+        for (sub, state) in submachines {
             let sub_fsm = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
             let sub_fsm_event_ty = sub_fsm.get_fsm_events_ty();
             let sub_fsm_ty = sub_fsm.get_fsm_no_generics_ty();            
 
             let sub_fsm_event_ty_str = crate::utils::tokens_to_string(&sub_fsm_event_ty);
 
-            variants.append_all(quote! {
-                #sub_fsm_ty ( #sub_fsm_event_ty ),
-            });
+            if sub.lifted_events.is_empty() {
+                variants.append_all(quote! {
+                    #sub_fsm_ty ( #sub_fsm_event_ty ),
+                });
+            } else {
+                // the lifted events of the submachine become the parent's events as they are enqueued
+                let mut lifted = TokenStream::new();
+                for (ty, closure) in &sub.lifted_events {
+                    let variant = event_variant(ty);
+                    let remap = remap_closure_inputs(&closure.inputs, &[quote! { ev }])?;
+                    let body = &closure.body;
+
+                    lifted.append_all(quote! {
+                        #sub_fsm_event_ty :: #variant(ev) => {
+                            #remap
+                            { #body }.into()
+                        },
+                    });
+                }
+
+                variants.append_all(quote! {
+                    #[from(ignore)]
+                    #sub_fsm_ty ( #sub_fsm_event_ty ),
+                });
+                lifted_from.append_all(quote! {
+                    impl From<#sub_fsm_event_ty> for #event_enum_ty {
+                        fn from(ev: #sub_fsm_event_ty) -> Self {
+                            #[allow(unreachable_patterns)]
+                            match ev {
+                                #lifted
+                                ev => #event_enum_ty :: #sub_fsm_ty(ev)
+                            }
+                        }
+                    }
+                });
+            }
             as_ref_str.append_all(quote! {
                 #event_enum_ty :: #sub_fsm_ty(_) => #sub_fsm_event_ty_str ,
             });
+            let idx = i as usize;
+            arbitrary_variants.append_all(quote! { #idx => #event_enum_ty :: #sub_fsm_ty(finny::bundled::arbitrary::Arbitrary::arbitrary(u)?), });
+            // the events of a submachine share its id
+            let id = crate::utils::stable_id(&sub_fsm_event_ty_str);
+            if !event_ids.insert(id) {
+                return Err(syn::Error::new(state.ty.span(), "The events of the submachine have the same id as one of the events, declare the event's id with 'event_id'."));
+            }
+            kind_variants.append_all(quote! { #sub_fsm_ty = #id, });
+            kind_names.append_all(quote! { #event_kind_ty :: #sub_fsm_ty => #sub_fsm_event_ty_str, });
+            kind_from_ids.append_all(quote! { #id => Some(#event_kind_ty :: #sub_fsm_ty), });
+            event_kinds.append_all(quote! { #event_enum_ty :: #sub_fsm_ty(_) => #event_kind_ty :: #sub_fsm_ty, });
+            priorities.append_all(quote! {
+                #event_enum_ty :: #sub_fsm_ty(ev) => finny::FsmEventPriority::priority(ev) ,
+            });
+            trace_contexts.append_all(quote! {
+                #event_enum_ty :: #sub_fsm_ty(ev) => finny::FsmEventTraceContext::trace_context(ev) ,
+            });
             i += 1;
         }
 
         let mut derives = TokenStream::new();
+        if fsm.fsm.codegen_options.event_clone {
+            derives.append_all(quote! {
+                #[derive(Clone)]
+            });
+        }
         if fsm.fsm.codegen_options.event_debug {
             derives.append_all(quote! {
                 #[derive(Debug)]
             });
         }
+        if fsm.fsm.codegen_options.event_serde && !fsm.fsm.codegen_options.derive_serde {
+            derives.append_all(quote! {
+                #[derive(finny::bundled::serde::Serialize, finny::bundled::serde::Deserialize)]
+                #[serde(crate = "finny::bundled::serde")]
+            });
+        }
 
         let as_ref_str = match i {
             0 => {
@@ -252,22 +477,182 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
             }
         };
         
+        let priority = if priorities.is_empty() {
+            quote! { 0 }
+        } else {
+            quote! {
+                match self {
+                    #priorities
+                    _ => 0
+                }
+            }
+        };
+
+        let trace_context = if trace_contexts.is_empty() {
+            quote! { None }
+        } else {
+            quote! {
+                match self {
+                    #trace_contexts
+                    _ => None
+                }
+            }
+        };
+
+        // the variant is chosen first, then its event is generated from the rest of the data
+        let arbitrary = if fsm.fsm.codegen_options.event_arbitrary {
+            let count = i as usize;
+            quote! {
+                impl<'a> finny::bundled::arbitrary::Arbitrary<'a> for #event_enum_ty {
+                    fn arbitrary(u: &mut finny::bundled::arbitrary::Unstructured<'a>) -> finny::bundled::arbitrary::Result<Self> {
+                        if #count == 0 {
+                            return Err(finny::bundled::arbitrary::Error::IncorrectFormat);
+                        }
+
+                        #[allow(unreachable_code)]
+                        Ok(match u.choose_index(#count)? {
+                            #arbitrary_variants
+                            _ => unreachable!()
+                        })
+                    }
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        // an enum without variants can't have a representation
+        let (kind_repr, kind_to_id, event_kind) = match i {
+            0 => (TokenStream::new(), quote! { match *self {} }, quote! { match *self {} }),
+            _ => (quote! { #[repr(u32)] }, quote! { *self as u32 }, quote! { match self { #event_kinds } })
+        };
+
         let evs = quote! {
             #[derive(finny::bundled::derive_more::From)]
-            #[derive(Clone)]
             #derives
+            #serde_derives
             pub enum #event_enum_ty {
                 #variants
             }
 
-            impl core::convert::AsRef<str> for #event_enum_ty {
-                fn as_ref(&self) -> &'static str {
+            impl #event_enum_ty {
+                /// The name of the event.
+                pub fn event_name(&self) -> &'static str {
                     #as_ref_str
                 }
+
+                /// The kind of the event, without its data.
+                pub fn event_kind(&self) -> #event_kind_ty {
+                    #event_kind
+                }
+
+                /// The stable id of the event, see `FsmId`.
+                pub fn event_id(&self) -> u32 {
+                    finny::FsmId::to_id(&self.event_kind())
+                }
+            }
+
+            /// The kinds of the events, with their stable ids as the discriminants.
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+            #kind_repr
+            pub enum #event_kind_ty {
+                #kind_variants
+            }
+
+            impl #event_kind_ty {
+                /// The name of the event.
+                pub fn event_name(&self) -> &'static str {
+                    match *self {
+                        #kind_names
+                    }
+                }
+            }
+
+            impl finny::FsmId for #event_kind_ty {
+                fn to_id(&self) -> u32 {
+                    #kind_to_id
+                }
+
+                fn from_id(id: u32) -> Option<Self> {
+                    match id {
+                        #kind_from_ids
+                        _ => None
+                    }
+                }
+            }
+
+            #lifted_from
+
+            #arbitrary
+
+            impl core::convert::AsRef<str> for #event_enum_ty {
+                fn as_ref(&self) -> &str {
+                    self.event_name()
+                }
+            }
+
+            impl finny::FsmEventPriority for #event_enum_ty {
+                #[allow(unreachable_patterns)]
+                fn priority(&self) -> u8 {
+                    #priority
+                }
+            }
+
+            impl finny::FsmEventTraceContext for #event_enum_ty {
+                #[allow(unreachable_patterns)]
+                fn trace_context(&self) -> Option<&dyn core::any::Any> {
+                    #trace_context
+                }
+            }
+        };
+
+        let borrowed_derives = if fsm.fsm.codegen_options.event_debug {
+            quote! { #[derive(Debug)] }
+        } else {
+            TokenStream::new()
+        };
+
+        let borrowed_evs = if borrowed_variants.is_empty() {
+            TokenStream::new()
+        } else {
+            quote! {
+                /// The events that borrow their data, dispatched with `dispatch_borrowed`.
+                #borrowed_derives
+                pub enum #borrowed_event_enum_ty <'e> {
+                    #borrowed_variants
+                }
+
+                impl<'e> #borrowed_event_enum_ty <'e> {
+                    /// The name of the event.
+                    pub fn event_name(&self) -> &'static str {
+                        match self {
+                            #borrowed_names
+                        }
+                    }
+
+                    /// The stable id of the event, see `FsmId`.
+                    pub fn event_id(&self) -> u32 {
+                        match self {
+                            #borrowed_ids
+                        }
+                    }
+                }
+
+                impl<'e> core::convert::AsRef<str> for #borrowed_event_enum_ty <'e> {
+                    fn as_ref(&self) -> &str {
+                        self.event_name()
+                    }
+                }
+
+                #borrowed_from
             }
         };
 
-        evs
+        quote! {
+            #evs
+
+            #borrowed_evs
+        }
     };
     
     let transition_types = {
@@ -297,28 +682,24 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                             tokens_to_string(event_ty)
                         ));
 
-                        if let Some(ref guard) = s.action.guard {
-                            let remap = remap_closure_inputs(&guard.inputs, vec![
-                                quote! { event }, quote! { context }, quote! { states }
-                            ].as_slice())?;
-
-                            let body = &guard.body;
+                        if s.action.has_guard() {
+                            match s.action.guard_name {
+                                Some(ref name) => transition_doc.push_str(&format!(" Guarded by `{}`.", name)),
+                                None => transition_doc.push_str(" Guarded.")
+                            }
 
-                            transition_doc.push_str(" Guarded.");
+                            let g = generate_transition_guard(fsm, ty, event_ty, &s.action)?;
+                            q.append_all(g);
+                        }
 
-                            let g = quote! {
-                                impl #fsm_generics_impl finny::FsmTransitionGuard<#fsm_ty #fsm_generics_type, #event_ty> for #ty #fsm_generics_where {
-                                    fn guard<'fsm_event, Q>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: & #states_store_ty #fsm_generics_type ) -> bool
-                                        where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
-                                    {
-                                        #remap
-                                        let result = { #body };
-                                        result
-                                    }
-                                }
-                            };
+                        match s.action.rate_limit {
+                            Some(FsmTransitionRateLimit::Debounce(_)) => transition_doc.push_str(" Debounced."),
+                            Some(FsmTransitionRateLimit::Throttle(_)) => transition_doc.push_str(" Throttled."),
+                            None => ()
+                        }
 
-                            q.append_all(g);
+                        if let Some(ref max) = s.action.retry {
+                            transition_doc.push_str(&format!(" Retried at most {} times.", tokens_to_string(max)));
                         }
                         
                         let action_body = if let Some(ref action) = s.action.action {
@@ -328,25 +709,57 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
                             transition_doc.push_str(" Executes an action.");
 
-                            let body = &action.body;
-                            
-                            quote! { 
-                                #remap
-                                { #body }
+                            action_result_body(&remap, &action.body, s.action.action_fallible)
+                        } else {
+                            quote! { Ok(()) }
+                        };
+
+                        let state_ty = &state.ty;
+
+                        let action_async = if let Some(ref action) = s.action.action_async {
+                            let remap = remap_closure_inputs(&action.inputs, vec![
+                                quote! { event }, quote! { context }, quote! { state }
+                            ].as_slice())?;
+
+                            transition_doc.push_str(" Executes an async action.");
+
+                            let body = async_closure_body(action);
+
+                            quote! {
+                                async fn action_async<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, state: &mut #state_ty) -> finny::FsmDispatchResult
+                                    where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
+                                {
+                                    Self::action(event, context, state)?;
+                                    #remap
+                                    #body;
+                                    Ok(())
+                                }
                             }
                         } else {
                             TokenStream::new()
                         };
 
-                        let state_ty = &state.ty;
+                        let action_name = match s.action.action_name {
+                            Some(ref name) => quote! {
+                                fn action_name() -> &'static str {
+                                    #name
+                                }
+                            },
+                            None => TokenStream::new()
+                        };
+
                         q.append_all(quote! {
                             impl #fsm_generics_impl finny::FsmAction<#fsm_ty #fsm_generics_type, #event_ty, #state_ty > for #ty #fsm_generics_where {
-                                fn action<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, state: &mut #state_ty)
+                                fn action<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, state: &mut #state_ty) -> finny::FsmDispatchResult
                                     where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
                                 {
                                     #action_body
                                 }
 
+                                #action_name
+
+                                #action_async
+
                                 fn should_trigger_state_actions() -> bool {
                                     #is_self_transition
                                 }
@@ -381,30 +794,24 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                             tokens_to_string(&event_ty)
                         ));
 
-                        if let Some(ref guard) = s.action.guard {
-                            let event_ty = &s.event.get_event()?.ty;
-
-                            transition_doc.push_str(" Guarded.");
-
-                            let remap = remap_closure_inputs(&guard.inputs, vec![
-                                quote! { event }, quote! { context }, quote! { states }
-                            ].as_slice())?;
+                        if s.action.has_guard() {
+                            match s.action.guard_name {
+                                Some(ref name) => transition_doc.push_str(&format!(" Guarded by `{}`.", name)),
+                                None => transition_doc.push_str(" Guarded.")
+                            }
 
-                            let body = &guard.body;
+                            let g = generate_transition_guard(fsm, ty, event_ty, &s.action)?;
+                            q.append_all(g);
+                        }
 
-                            let g = quote! {
-                                impl #fsm_generics_impl finny::FsmTransitionGuard<#fsm_ty #fsm_generics_type, #event_ty> for #ty #fsm_generics_where {
-                                    fn guard<'fsm_event, Q>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: & #states_store_ty #fsm_generics_type) -> bool
-                                        where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
-                                    {
-                                        #remap
-                                        let result = { #body };
-                                        result
-                                    }
-                                }
-                            };
+                        match s.action.rate_limit {
+                            Some(FsmTransitionRateLimit::Debounce(_)) => transition_doc.push_str(" Debounced."),
+                            Some(FsmTransitionRateLimit::Throttle(_)) => transition_doc.push_str(" Throttled."),
+                            None => ()
+                        }
 
-                            q.append_all(g);
+                        if let Some(ref max) = s.action.retry {
+                            transition_doc.push_str(&format!(" Retried at most {} times.", tokens_to_string(max)));
                         }
 
                         let action_body = if let Some(ref action) = s.action.action {
@@ -414,26 +821,57 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                                 quote! { event }, quote! { context }, quote! { from }, quote! { to }
                             ].as_slice())?;
 
-                            let body = &action.body;
+                            action_result_body(&remap, &action.body, s.action.action_fallible)
+                        } else {
+                            quote! { Ok(()) }
+                        };
+                        
+                        let state_from_ty = &state_from.ty;
+                        let state_to_ty = &state_to.ty;
+
+                        let action_async = if let Some(ref action) = s.action.action_async {
+                            transition_doc.push_str(" Executes an async action.");
+
+                            let remap = remap_closure_inputs(&action.inputs, vec![
+                                quote! { event }, quote! { context }, quote! { from }, quote! { to }
+                            ].as_slice())?;
+
+                            let body = async_closure_body(action);
 
                             quote! {
-                                #remap
-                                { #body }
+                                async fn action_async<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, from: &mut #state_from_ty, to: &mut #state_to_ty) -> finny::FsmDispatchResult
+                                    where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
+                                {
+                                    Self::action(event, context, from, to)?;
+                                    #remap
+                                    #body;
+                                    Ok(())
+                                }
                             }
                         } else {
                             TokenStream::new()
                         };
-                        
-                        let state_from_ty = &state_from.ty;
-                        let state_to_ty = &state_to.ty;
+
+                        let action_name = match s.action.action_name {
+                            Some(ref name) => quote! {
+                                fn action_name() -> &'static str {
+                                    #name
+                                }
+                            },
+                            None => TokenStream::new()
+                        };
 
                         let a = quote! {
                             impl #fsm_generics_impl finny::FsmTransitionAction<#fsm_ty #fsm_generics_type, #event_ty, #state_from_ty, #state_to_ty> for #ty #fsm_generics_where {
-                                fn action<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, from: &mut #state_from_ty, to: &mut #state_to_ty)
+                                fn action<'fsm_event, Q>(event: & #event_ty , context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q >, from: &mut #state_from_ty, to: &mut #state_to_ty) -> finny::FsmDispatchResult
                                     where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
                                 {
                                     #action_body
                                 }
+
+                                #action_name
+
+                                #action_async
                             }
                         };
 
@@ -456,255 +894,1349 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
     };
 
     let dispatch = {        
-        
+        // the error of an event that isn't handled in the current state of the region
+        let no_transition = |region_id: usize| -> TokenStream {
+            quote! {
+                finny::FsmError::NoTransition {
+                    state: match ctx.backend.current_states[#region_id] {
+                        finny::FsmCurrentState::State(s) => s.state_name(),
+                        finny::FsmCurrentState::Stopped => "Stopped"
+                    },
+                    event: match event {
+                        finny::FsmEvent::Event(ref ev) => ev.event_name(),
+                        finny::FsmEvent::Start => "Fsm::Start",
+                        finny::FsmEvent::Stop => "Fsm::Stop",
+                        finny::FsmEvent::Timer(_) => "Fsm::Timer"
+                    }
+                }
+            }
+        };
 
-        let mut regions = TokenStream::new();
-        for region in &fsm.fsm.regions {
-            let mut region_transitions = TokenStream::new();
+        // the patterns of the transition's source state and of its event
+        let match_transition_state = |transition: &FsmTransition| -> TokenStream {
+            let state_from = match &transition.ty {
+                FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => {
+                    &s.state
+                }
+                FsmTransitionType::StateTransition(s) => &s.state_from
+            };
 
-            let region_id = region.region_id;
-            for transition in &region.transitions {
+            match state_from {
+                FsmTransitionState::None => quote! { finny::FsmCurrentState::Stopped },
+                FsmTransitionState::State(st) => {
+                    let state_ty = FsmTypes::new(&st.ty, &fsm.base.fsm_generics);
+                    let variant = state_ty.get_fsm_no_generics_ty();
+                    quote! { finny::FsmCurrentState::State(#states_enum_ty :: #variant) }
+                }
+            }
+        };
 
-                let transition_ty = &transition.transition_ty;
-                
-                let match_state = {
-                    let state_from = match &transition.ty {
-                        FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => {
-                            &s.state
-                        }
-                        FsmTransitionType::StateTransition(s) => &s.state_from
-                    };
+        let match_transition_event = |transition: &FsmTransition, borrowed: bool| -> TokenStream {
+            let event = match &transition.ty {
+                FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => &s.event,
+                FsmTransitionType::StateTransition(s) => &s.event
+            };
 
-                    match state_from {
-                        FsmTransitionState::None => quote! { finny::FsmCurrentState::Stopped },
-                        FsmTransitionState::State(st) => {
-                            let state_ty = FsmTypes::new(&st.ty, &fsm.base.fsm_generics);
-                            let variant = state_ty.get_fsm_no_generics_ty();
-                            quote! { finny::FsmCurrentState::State(#states_enum_ty :: #variant) }
-                        }
-                    }
-                };
-                
-                let match_event = {                
-                    let event = match &transition.ty {
-                        FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => &s.event,
-                        FsmTransitionType::StateTransition(s) => &s.event
-                    };
+            match event {
+                crate::parse::FsmTransitionEvent::Start => quote! { ev @ finny::FsmEvent::Start },
+                crate::parse::FsmTransitionEvent::Stop => quote ! { ev @ finny::FsmEvent::Stop },
+                crate::parse::FsmTransitionEvent::Event(ref ev) if borrowed => {
+                    let kind = ev.variant();
+                    quote! { finny::FsmEvent::Event(#borrowed_event_enum_ty::#kind(ref ev)) }
+                },
+                crate::parse::FsmTransitionEvent::Event(ref ev) => {
+                    let kind = ev.variant();
+                    quote! { finny::FsmEvent::Event(#event_enum_ty::#kind(ref ev)) }
+                }
+            }
+        };
 
-                    match event {
-                        crate::parse::FsmTransitionEvent::Start => quote! { ev @ finny::FsmEvent::Start },
-                        crate::parse::FsmTransitionEvent::Stop => quote ! { ev @ finny::FsmEvent::Stop },
-                        crate::parse::FsmTransitionEvent::Event(ref ev) => {
-                            let kind = &ev.ty;
-                            quote! { finny::FsmEvent::Event(#event_enum_ty::#kind(ref ev)) }
-                        }
-                    }
-                };
+        // the transitions with a rate limit are numbered for their bookkeeping in the timer requests
+        let rate_limited: Vec<_> = fsm.fsm.regions.iter()
+            .flat_map(|r| r.transitions.iter())
+            .filter(|t| t.ty.get_action().rate_limit.is_some())
+            .map(|t| t.transition_ty.clone())
+            .collect();
+        let rate_limit = |transition: &FsmTransition| -> Option<(usize, TokenStream)> {
+            let index = rate_limited.iter().position(|ty| *ty == transition.transition_ty)?;
+            let limit = match transition.ty.get_action().rate_limit.as_ref()? {
+                FsmTransitionRateLimit::Debounce(window) => quote! { finny::FsmRateLimit::Debounce(#window) },
+                FsmTransitionRateLimit::Throttle(window) => quote! { finny::FsmRateLimit::Throttle(#window) }
+            };
+            Some((index, limit))
+        };
+
+        // the region counts its retries until it enters a state outside of its retry loop
+        let reset_attempts = |region: &FsmRegion, entered: &syn::Type| -> TokenStream {
+            let retries: Vec<_> = region.transitions.iter().filter(|t| t.ty.get_action().retry.is_some()).collect();
+            if retries.is_empty() || retries.iter().any(|t| t.ty.get_states().contains(entered)) {
+                return TokenStream::new();
+            }
+
+            let region_id = region.region_id;
+            quote! {
+                ctx.backend.timer_requests.reset_attempts(#region_id);
+            }
+        };
+
+        // the transactional machines remember the context and the states once an event matches a transition,
+        // and restore them if one of the step's guards or actions fails
+        let transactional = fsm.fsm.codegen_options.transactional;
+        let (begin_transaction, rollback_transaction) = if transactional {
+            let fields: Vec<_> = fsm.fsm.states.values().map(|s| &s.state_storage_field).collect();
+            let saved: Vec<_> = fields.iter().map(|f| syn::Ident::new(&format!("transaction_{}", f), Span::call_site())).collect();
+
+            let begin = quote! {
+                {
+                    if transaction.is_none() {
+                        transaction = Some((
+                            ctx.backend.context.clone(),
+                            ( #( ctx.backend.states. #fields .clone(), )* ),
+                            ctx.backend.current_states,
+                            ctx.queue.len(),
+                            ctx.backend.deferred.checkpoint(),
+                            ctx.backend.timer_requests.checkpoint()
+                        ));
+                    }
+                    true
+                }
+            };
+            let rollback = quote! {
+                if let Some((transaction_context, ( #( #saved, )* ), transaction_states, transaction_queue, transaction_deferred, transaction_timers)) = transaction.take() {
+                    inspect_event_ctx.info("Rolling back the failed step.");
+                    ctx.backend.context = transaction_context;
+                    #( ctx.backend.states. #fields = #saved; )*
+                    ctx.backend.current_states = transaction_states;
+                    ctx.queue.truncate(transaction_queue);
+                    ctx.backend.deferred.rollback(transaction_deferred);
+                    ctx.backend.timer_requests.rollback(transaction_timers);
+                }
+            };
+            (begin, rollback)
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+
+        let is_borrowed = |transition: &FsmTransition| match &transition.ty {
+            FsmTransitionType::InternalTransition(FsmStateAction { event: crate::parse::FsmTransitionEvent::Event(ev), .. }) |
+            FsmTransitionType::SelfTransition(FsmStateAction { event: crate::parse::FsmTransitionEvent::Event(ev), .. }) |
+            FsmTransitionType::StateTransition(FsmStateTransition { event: crate::parse::FsmTransitionEvent::Event(ev), .. }) => ev.is_borrowed(),
+            _ => false
+        };
 
-                let guard = {
-                    let has_guard = match &transition.ty {
-                        FsmTransitionType::StateTransition(s) => {
-                            s.action.guard.is_some()
+        // the timers of the submachine's states would otherwise keep running after it was exited
+        let cancel_sub_timers = |state: &FsmState| -> TokenStream {
+            match state.kind {
+                FsmStateKind::SubMachine(_) => {
+                    let sub_ty = &state.ty;
+                    quote! {
+                        {
+                            use finny::FsmBackendCancelTimers;
+                            let sub_fsm: &mut #sub_ty = ctx.backend.states.as_mut();
+                            let mut timers_adapter = finny::FsmTimersSub {
+                                parent: &mut *ctx.timers,
+                                _parent_fsm: core::marker::PhantomData,
+                                _sub_fsm: core::marker::PhantomData
+                            };
+                            let mut inspect = inspect_event_ctx.for_sub_machine::<#sub_ty>();
+                            <#sub_ty>::cancel_timers(&mut **sub_fsm, &mut inspect, &mut timers_adapter);
                         }
-                        FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => {
-                            s.action.guard.is_some()
+                    }
+                },
+                FsmStateKind::Normal => TokenStream::new()
+            }
+        };
+
+        let start_timers = |state: &FsmState| -> TokenStream {
+            let mut timers_enter = TokenStream::new();
+            for timer in &state.timers {
+                let timer_field = timer.get_field(&fsm.base);
+                let timer_ty = timer.get_ty(&fsm.base);
+                let state_field = &state.state_storage_field;
+
+                timers_enter.append_all(quote! {
+                    {
+                        use finny::FsmTimer;
+                        ctx.backend.states. #timer_field . execute_on_enter( #timers_enum_ty :: #timer_ty , &mut ctx.backend.context, &ctx.backend.states. #state_field , &mut inspect_event_ctx, ctx.timers );
+                    }
+                });
+            }
+            timers_enter
+        };
+
+        // the borrowed events are dispatched separately, without their machine event
+        let generate_regions = |is_async: bool, borrowed: bool| -> syn::Result<TokenStream> {
+            let fsm_event = if borrowed { quote! { None } } else { quote! { Some(&event) } };
+            let awaited = if is_async { quote! { .await } } else { TokenStream::new() };
+            let execute_guard = dispatch_fn_ident("execute_guard", is_async);
+            let execute_transition = dispatch_fn_ident("execute_transition", is_async);
+            let execute_on_sub_entry = dispatch_fn_ident("execute_on_sub_entry", is_async);
+            let dispatch_to_submachine = dispatch_fn_ident("dispatch_to_submachine", is_async);
+            let execute_on_entry = dispatch_fn_ident("execute_on_entry", is_async);
+            let execute_on_exit = dispatch_fn_ident("execute_on_exit", is_async);
+
+            // exit the current state of the region and enter this one
+            let enter_region_state = |region: &FsmRegion, state: &FsmState| -> TokenStream {
+                let region_id = region.region_id;
+
+                let mut exits = TokenStream::new();
+                for exited in region.states.iter().filter(|s| s.ty != state.ty) {
+                    let exited_ty = &exited.ty;
+                    let exited_types = FsmTypes::new(&exited.ty, &fsm.base.fsm_generics);
+                    let variant = exited_types.get_fsm_no_generics_ty();
+
+                    let mut timers_exit = TokenStream::new();
+                    for timer in &exited.timers {
+                        let timer_field = timer.get_field(&fsm.base);
+                        let timer_ty = timer.get_ty(&fsm.base);
+
+                        timers_exit.append_all(quote! {
+                            {
+                                use finny::FsmTimer;
+                                ctx.backend.states. #timer_field . execute_on_exit( #timers_enum_ty :: #timer_ty , &mut inspect_event_ctx, ctx.timers );
+                            }
+                        });
+                    }
+                    timers_exit.append_all(cancel_sub_timers(exited));
+
+                    exits.append_all(quote! {
+                        finny::FsmCurrentState::State(#states_enum_ty :: #variant) => {
+                            #timers_exit
+                            <#exited_ty>::#execute_on_exit(&mut ctx, #region_id, #fsm_event) #awaited;
+                        },
+                    });
+                }
+
+                let timers_enter = start_timers(state);
+
+                let state_ty = &state.ty;
+                let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                let variant = state_types.get_fsm_no_generics_ty();
+                let reset = reset_attempts(region, &state.ty);
+
+                quote! {
+                    #[allow(unreachable_patterns)]
+                    match ctx.backend.current_states[#region_id] {
+                        #exits
+                        _ => ()
+                    }
+
+                    <#state_ty>::#execute_on_entry(&mut ctx, #region_id, #fsm_event) #awaited;
+                    ctx.backend.current_states[#region_id] = finny::FsmCurrentState::State(#states_enum_ty :: #variant);
+                    #reset
+
+                    #timers_enter
+                }
+            };
+
+            // a failed guard or action, either enters the fault state or fails the dispatch. The source state of
+            // a failed action was entered again, so it's exited like any other state.
+            let on_failure = || -> TokenStream {
+                let fault = fsm.fsm.error_state.as_ref().and_then(|ty| {
+                    fsm.fsm.regions.iter().find_map(|r| r.states.iter().find(|s| &s.ty == ty).map(|s| (r, s)))
+                });
+
+                let (fault_region, fault_state) = match fault {
+                    Some(fault) => fault,
+                    None => {
+                        return quote! {
+                            #rollback_transaction
+                            inspect_event_ctx.event_done(&ctx.backend);
+                            return Err(e);
+                        };
+                    }
+                };
+
+                let enter_fault = enter_region_state(fault_region, fault_state);
+
+                quote! {
+                    #rollback_transaction
+                    inspect_event_ctx.info("Entering the fault state.");
+                    ctx.backend.error = Some(e);
+
+                    #enter_fault
+
+                    inspect_event_ctx.event_done(&ctx.backend);
+                    return Ok(());
+                }
+            };
+
+            // dispatching to a submachine, notifying the parent once the submachine completes
+            let sub_dispatch = |sub: &FsmState, ev: TokenStream| -> syn::Result<TokenStream> {
+                let sub_ty = &sub.ty;
+                let (completion, state_events) = match sub.kind {
+                    FsmStateKind::SubMachine(FsmSubMachineOptions { ref completion_event, ref state_events, .. }) if completion_event.is_some() || !state_events.is_empty() => (completion_event, state_events),
+                    _ => {
+                        return Ok(quote! {
+                            return finny::#dispatch_to_submachine::<_, #sub_ty, _, _, _>(&mut ctx, #ev, &mut inspect_event_ctx) #awaited;
+                        });
+                    }
+                };
+
+                let mut completion_events = TokenStream::new();
+                if let Some(completion) = completion {
+                    let remap = remap_closure_inputs(&completion.inputs, &[quote! { &sub.context }])?;
+                    let body = &completion.body;
+
+                    completion_events.append_all(quote! {
+                        if !completed_before && sub.is_completed() {
+                            let ev = { #remap #body };
+                            inspect_event_ctx.info("The submachine completed, enqueuing the completion event.");
+                            if let Err(e) = finny::enqueue_inspected::<Self, _, _, _>(ctx.queue, &inspect_event_ctx, ev) {
+                                inspect_event_ctx.on_error("The submachine's completion event couldn't be enqueued.", &e);
+                            }
                         }
+                    });
+                }
+
+                // the exit points and the observed states notify the parent once they are entered
+                for state_event in state_events {
+                    let state_ty = &state_event.state;
+                    let (remap, entered_state) = if state_event.with_state {
+                        (remap_closure_inputs(&state_event.closure.inputs, &[quote! { &sub.context }, quote! { entered_state }])?,
+                            quote! { let entered_state: &#state_ty = sub.get_state(); })
+                    } else {
+                        (remap_closure_inputs(&state_event.closure.inputs, &[quote! { &sub.context }])?, TokenStream::new())
                     };
+                    let body = &state_event.closure.body;
 
-                    if has_guard {
-                        quote! {
-                            if <#transition_ty>::execute_guard(&mut ctx, &ev, #region_id, &mut inspect_event_ctx)
+                    completion_events.append_all(quote! {
+                        {
+                            let observed = finny::FsmCurrentState::State(<#state_ty as finny::FsmState<#sub_ty>>::fsm_state());
+                            if !states_before.as_ref().contains(&observed) && sub.get_current_states().as_ref().contains(&observed) {
+                                #entered_state
+                                let ev = { #remap #body };
+                                inspect_event_ctx.info("The submachine entered the observed state, enqueuing the event.");
+                                if let Err(e) = finny::enqueue_inspected::<Self, _, _, _>(ctx.queue, &inspect_event_ctx, ev) {
+                                    inspect_event_ctx.on_error("The submachine's event couldn't be enqueued.", &e);
+                                }
+                            }
                         }
-                    } else {
-                        TokenStream::new()
+                    });
+                }
+
+                Ok(quote! {
+                    let (completed_before, states_before) = {
+                        let sub: &#sub_ty = ctx.backend.states.as_ref();
+                        (sub.is_completed(), sub.get_current_states())
+                    };
+
+                    let result = finny::#dispatch_to_submachine::<_, #sub_ty, _, _, _>(&mut ctx, #ev, &mut inspect_event_ctx) #awaited;
+
+                    if result.is_ok() {
+                        let sub: &#sub_ty = ctx.backend.states.as_ref();
+                        #completion_events
                     }
-                };
-                
-                let fsm_sub_entry = match &transition.ty {
-                    FsmTransitionType::StateTransition(FsmStateTransition {state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), .. }) => {
 
-                        let sub_ty = &s.ty;
+                    return result;
+                })
+            };
 
-                        quote! {
+            // the regions that were entered by a fork don't handle the forking event again
+            let mut forked_region_ids = HashSet::new();
+            for transition in fsm.fsm.regions.iter().flat_map(|r| r.transitions.iter()).filter(|t| is_borrowed(t) == borrowed) {
+                if let FsmTransitionType::StateTransition(FsmStateTransition { action, .. }) = &transition.ty {
+                    for fork in &action.forks {
+                        forked_region_ids.insert(fsm.fsm.find_state(fork)?.0.region_id);
+                    }
+                }
+            }
 
-                            // reset
-                            {
-                                use finny::FsmBackendResetSubmachine;
-                                <Self as FsmBackendResetSubmachine<_, #sub_ty >>::reset(ctx.backend, &mut inspect_event_ctx);
+            let mut regions = TokenStream::new();
+            if !forked_region_ids.is_empty() {
+                let region_count = fsm.fsm.regions.len();
+                regions.append_all(quote! {
+                    let mut forked_regions = [false; #region_count];
+                });
+            }
+
+            for region in fsm.fsm.regions_by_priority() {
+                let mut region_transitions = TokenStream::new();
+
+                let region_id = region.region_id;
+                for transition in &region.transitions {
+
+                    let transition_ty = &transition.transition_ty;
+
+                    if is_borrowed(transition) != borrowed {
+                        continue;
+                    }
+                
+                    let match_state = match_transition_state(transition);
+                    let match_event = match_transition_event(transition, borrowed);
+
+                    let guard = {
+                        let has_guard = match &transition.ty {
+                            FsmTransitionType::StateTransition(s) => {
+                                s.action.has_guard()
                             }
-                            {
-                                <#transition_ty>::execute_on_sub_entry(&mut ctx, #region_id, &mut inspect_event_ctx);
+                            FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => {
+                                s.action.has_guard()
+                            }
+                        };
+
+                        // the joined regions have to be in their states before the guard is evaluated
+                        let mut conditions = vec![];
+                        if transactional {
+                            conditions.push(begin_transaction.clone());
+                        }
+                        if let FsmTransitionType::StateTransition(FsmStateTransition { action, .. }) = &transition.ty {
+                            for join in &action.joins {
+                                let (joined_region, joined_state) = fsm.fsm.find_state(join)?;
+                                let joined_region_id = joined_region.region_id;
+                                let joined_types = FsmTypes::new(&joined_state.ty, &fsm.base.fsm_generics);
+                                let variant = joined_types.get_fsm_no_generics_ty();
+
+                                conditions.push(quote! {
+                                    ctx.backend.current_states[#joined_region_id] == finny::FsmCurrentState::State(#states_enum_ty :: #variant)
+                                });
                             }
                         }
-                    },
-                    _ => TokenStream::new()
-                };
 
-                let timers_enter = {
-                    let mut timers_enter = TokenStream::new();
+                        if has_guard {
+                            let guard_failure = on_failure();
+
+                            conditions.push(quote! {
+                                match <#transition_ty>::#execute_guard(&mut ctx, &ev, #region_id, &mut inspect_event_ctx) #awaited {
+                                    Ok(guard_result) => guard_result,
+                                    Err(e) => {
+                                        #guard_failure
+                                    }
+                                }
+                            });
+                        }
+
+                        // only the events that would be taken count against the rate limit
+                        if let Some((index, limit)) = rate_limit(transition) {
+                            conditions.push(quote! {
+                                {
+                                    let passes = ctx.backend.timer_requests.rate_limit(#index, #limit);
+                                    if !passes {
+                                        inspect_event_ctx.info("The event was filtered by the transition's rate limit.");
+                                    }
+                                    passes
+                                }
+                            });
+                        }
+
+                        if let Some(max) = &transition.ty.get_action().retry {
+                            let retry_failure = on_failure();
+
+                            conditions.push(quote! {
+                                match ctx.backend.timer_requests.retry(#region_id, #max) {
+                                    Ok(true) => true,
+                                    Ok(false) => {
+                                        inspect_event_ctx.info("The retries of the transition are exhausted.");
+                                        false
+                                    },
+                                    Err(e) => {
+                                        #retry_failure
+                                    }
+                                }
+                            });
+                        }
+
+                        if conditions.is_empty() {
+                            TokenStream::new()
+                        } else {
+                            quote! { if #(#conditions)&&* }
+                        }
+                    };
+
+                    // the forked regions are entered after the transition, and skipped for the rest of this event
+                    let forks = {
+                        let mut forks = TokenStream::new();
+                        if let FsmTransitionType::StateTransition(FsmStateTransition { action, .. }) = &transition.ty {
+                            for fork in &action.forks {
+                                let (forked_region, forked_state) = fsm.fsm.find_state(fork)?;
+                                let forked_region_id = forked_region.region_id;
+                                let enter = enter_region_state(forked_region, forked_state);
+
+                                forks.append_all(quote! {
+                                    inspect_event_ctx.info("Forking into another region.");
+                                    #enter
+                                    forked_regions[#forked_region_id] = true;
+                                });
+                            }
+                        }
+                        forks
+                    };
+                
+                    // the submachine's failed start fails the transition like its action
+                    let sub_failure = on_failure();
+                    let fsm_sub_entry = match &transition.ty {
+                        FsmTransitionType::StateTransition(FsmStateTransition {state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), action: EventGuardAction { entry_point: Some(entry_point), .. }, .. }) => {
 
-                    let state = match &transition.ty {
-                        FsmTransitionType::SelfTransition(FsmStateAction { state: FsmTransitionState::State(st @ FsmState { .. }), .. }) => {
-                            Some(st)
+                            let sub_ty = &s.ty;
+                            let enter_submachine = dispatch_fn_ident("enter_submachine", is_async);
+
+                            quote! {
+                                {
+                                    use finny::FsmBackendResetSubmachine;
+                                    <Self as FsmBackendResetSubmachine<_, #sub_ty >>::reset(ctx.backend, &mut inspect_event_ctx);
+                                    if let Err(e) = finny::#enter_submachine::<_, #sub_ty, #entry_point, _, _, _>(&mut ctx, &mut inspect_event_ctx) #awaited {
+                                        #sub_failure
+                                    }
+                                }
+                            }
                         },
-                        FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(st @ FsmState { .. }), .. }) => {
-                            Some(st)
+                        FsmTransitionType::StateTransition(FsmStateTransition {state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), .. }) if s.history != FsmStateHistory::None => {
+
+                            let sub_ty = &s.ty;
+                            let resume_submachine = dispatch_fn_ident("resume_submachine", is_async);
+                            let deep = s.history == FsmStateHistory::Deep;
+
+                            quote! {
+                                if let Err(e) = finny::#resume_submachine::<_, #sub_ty, _, _, _>(&mut ctx, #deep, &mut inspect_event_ctx) #awaited {
+                                    #sub_failure
+                                }
+                            }
                         },
-                        _ => None
-                    };
+                        FsmTransitionType::StateTransition(FsmStateTransition {state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), .. }) => {
 
-                    if let Some(state) = state {
-                        for timer in &state.timers {
-                            let timer_field = timer.get_field(&fsm.base);
-                            let timer_ty = timer.get_ty(&fsm.base);
+                            let sub_ty = &s.ty;
 
-                            timers_enter.append_all(quote! {
+                            quote! {
+
+                                // reset
                                 {
-                                    use finny::FsmTimer;
-                                    ctx.backend.states. #timer_field . execute_on_enter( #timers_enum_ty :: #timer_ty , &mut ctx.backend.context, &mut inspect_event_ctx, ctx.timers );
+                                    use finny::FsmBackendResetSubmachine;
+                                    <Self as FsmBackendResetSubmachine<_, #sub_ty >>::reset(ctx.backend, &mut inspect_event_ctx);
                                 }
-                            });
+                                if let Err(e) = <#transition_ty>::#execute_on_sub_entry(&mut ctx, #region_id, &mut inspect_event_ctx) #awaited {
+                                    #sub_failure
+                                }
+                            }
+                        },
+                        _ => TokenStream::new()
+                    };
+
+                    let timers_enter = match &transition.ty {
+                        FsmTransitionType::SelfTransition(FsmStateAction { state: FsmTransitionState::State(st @ FsmState { .. }), .. }) |
+                        FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(st @ FsmState { .. }), .. }) => start_timers(st),
+                        _ => TokenStream::new()
+                    };
+
+                    let state_from = match &transition.ty {
+                        FsmTransitionType::SelfTransition(FsmStateAction { state: FsmTransitionState::State(st @ FsmState { .. }), .. }) |
+                        FsmTransitionType::StateTransition(FsmStateTransition { state_from: FsmTransitionState::State(st @ FsmState { .. }), .. }) => Some(st),
+                        _ => None
+                    };
+
+                    // the source state is entered again if the action fails, see `FsmTransitionAction::action`
+                    let timers_reenter = state_from.map(&start_timers).unwrap_or_default();
+
+                    let timers_exit = {
+                        let mut timers_exit = TokenStream::new();
+
+                        let state = state_from;
+
+                        if let Some(state) = state {
+                            for timer in &state.timers {
+                                let timer_field = timer.get_field(&fsm.base);
+                                let timer_ty = timer.get_ty(&fsm.base);
+
+                                timers_exit.append_all(quote! {
+                                    {
+                                        use finny::FsmTimer;
+                                        ctx.backend.states. #timer_field . execute_on_exit( #timers_enum_ty :: #timer_ty , &mut inspect_event_ctx, ctx.timers );
+                                    }
+                                });
+                            }
+                            timers_exit.append_all(cancel_sub_timers(state));
                         }
-                    }
 
-                    timers_enter
-                };
+                        timers_exit
+                    };
 
-                let timers_exit = {
-                    let mut timers_exit = TokenStream::new();
+                    let action_failure = on_failure();
+
+                    // the start transition only receives the machine's event
+                    let transition_event = match &transition.ty {
+                        FsmTransitionType::StateTransition(FsmStateTransition { state_from: FsmTransitionState::None, .. }) => quote! { &ev },
+                        _ => quote! { &ev, #fsm_event }
+                    };
+
+                    let reset = match &transition.ty {
+                        FsmTransitionType::SelfTransition(FsmStateAction { state: FsmTransitionState::State(st), .. }) |
+                        FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(st), .. }) => reset_attempts(region, &st.ty),
+                        _ => TokenStream::new()
+                    };
+
+                    let m = quote! {
+                        ( #match_state , #match_event ) #guard => {
+
+                            #timers_exit
+
+                            if let Err(e) = <#transition_ty>::#execute_transition(&mut ctx, #transition_event, #region_id, &mut inspect_event_ctx) #awaited {
+                                #timers_reenter
+                                #action_failure
+                            }
+
+                            #reset
+
+                            #fsm_sub_entry
+                        
+                            #timers_enter
 
-                    let state = match &transition.ty {
-                        FsmTransitionType::SelfTransition(FsmStateAction { state: FsmTransitionState::State(st @ FsmState { .. }), .. }) => {
-                            Some(st)
+                            #forks
                         },
-                        FsmTransitionType::StateTransition(FsmStateTransition { state_from: FsmTransitionState::State(st @ FsmState { .. }), .. }) => {
-                            Some(st)
+                    };
+
+                    region_transitions.append_all(m);
+                }
+
+                // match and dispatch to submachines
+                let region_submachines = {
+
+                    let mut sub_matches = TokenStream::new();
+
+                    let submachines: Vec<_> = region.transitions.iter().filter_map(|t| match &t.ty {
+                        FsmTransitionType::InternalTransition(_) => None,
+                        FsmTransitionType::SelfTransition(_) => None,
+                        FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), .. }) => {
+                            Some(s)
                         },
                         _ => None
-                    };
+                    }).collect();
 
-                    if let Some(state) = state {
+                    for submachine in submachines.into_iter().filter(|_| !borrowed) {
+                        let fsm_sub = FsmTypes::new(&submachine.ty, &fsm.base.fsm_generics);
+                        let kind_variant = fsm_sub.get_fsm_no_generics_ty();
+                        let dispatch = sub_dispatch(submachine, quote! { finny::FsmEvent::Event(ev.clone()) })?;
+
+                        let sub = quote! {
+                            ( finny::FsmCurrentState::State(#states_enum_ty :: #kind_variant), finny::FsmEvent::Event(#event_enum_ty::#kind_variant(ev))  ) => {
+                                #dispatch
+                            },
+                        };
+
+                        sub_matches.append_all(sub);
+                    }
+
+                    sub_matches
+                };
+
+                // the parent's events that are mapped into the submachine's events, unless a transition handled them
+                let region_mapped = {
+                    let mut mapped_matches = TokenStream::new();
+
+                    for state in region.states.iter().filter(|_| !borrowed) {
+                        let mapped_events = match state.kind {
+                            FsmStateKind::SubMachine(FsmSubMachineOptions { ref mapped_events, .. }) => mapped_events,
+                            FsmStateKind::Normal => continue
+                        };
+
+                        let fsm_sub = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                        let kind_variant = fsm_sub.get_fsm_no_generics_ty();
+                        let sub_events_ty = fsm_sub.get_fsm_events_ty();
+
+                        for (ty, closure) in mapped_events {
+                            let variant = event_variant(ty);
+                            let remap = remap_closure_inputs(&closure.inputs, &[quote! { ev }])?;
+                            let body = &closure.body;
+                            let dispatch = sub_dispatch(state, quote! { finny::FsmEvent::Event(sub_ev) })?;
+
+                            mapped_matches.append_all(quote! {
+                                ( finny::FsmCurrentState::State(#states_enum_ty :: #kind_variant), finny::FsmEvent::Event(#event_enum_ty::#variant(ev)) ) => {
+                                    let sub_ev: #sub_events_ty = {
+                                        #remap
+                                        { #body }.into()
+                                    };
+                                    #dispatch
+                                },
+                            });
+                        }
+                    }
+
+                    mapped_matches
+                };
+
+                // match and dispatch timer events
+                let timers = {
+                    let mut timer_dispatch = TokenStream::new();
+
+                    // our timers
+                    for state in region.states.iter().filter(|_| !borrowed) {
                         for timer in &state.timers {
-                            let timer_field = timer.get_field(&fsm.base);
                             let timer_ty = timer.get_ty(&fsm.base);
 
-                            timers_exit.append_all(quote! {
+                            timer_dispatch.append_all(quote! {
+                                (_, finny::FsmEvent::Timer( timer_id @ #timers_enum_ty :: #timer_ty )) => {
+                                    {
+                                        use finny::FsmTimer;
+                                        < #timer_ty #fsm_generics_type > :: execute_trigger(*timer_id, &mut ctx, &mut inspect_event_ctx);
+                                    }
+                                },
+                            });
+                        }
+                    }
+
+                    // sub machines
+                    for state in region.states.iter().filter(|s| !borrowed && matches!(s.kind, FsmStateKind::SubMachine(_)))
+                    {
+                        let sub_ty = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                        let sub_variant = sub_ty.get_fsm_no_generics_ty();
+                        let dispatch = sub_dispatch(state, quote! { finny::FsmEvent::Timer(*timer_id) })?;
+
+                        timer_dispatch.append_all(quote! {
+                            (_, finny::FsmEvent::Timer( #timers_enum_ty :: #sub_variant (timer_id))) => {
                                 {
-                                    use finny::FsmTimer;
-                                    ctx.backend.states. #timer_field . execute_on_exit( #timers_enum_ty :: #timer_ty , &mut inspect_event_ctx, ctx.timers );
+                                    #dispatch
                                 }
-                            });
+                            },
+                        });
+                    }
+
+                    timer_dispatch
+                };
+
+                // the first region that didn't handle the event fails the dispatch
+                let strict_miss = if fsm.fsm.codegen_options.strict_events {
+                    let no_transition = no_transition(region_id);
+                    quote! {
+                        if let (None, finny::FsmEvent::Event(_)) = (&unexpected_event, &event) {
+                            unexpected_event = Some(#no_transition);
                         }
                     }
+                } else {
+                    TokenStream::new()
+                };
+
+                let region_match = quote! {
+                    match (ctx.backend.current_states[#region_id], &event) {
+
+                        #region_submachines
+                    
+                        #region_transitions
+
+                        #region_mapped
 
-                    timers_exit
+                        // do not dispatch timers if the machine is stopped
+                        (finny::FsmCurrentState::Stopped, finny::FsmEvent::Timer(_)) => (),
+
+                        #timers
+
+                        _ => {
+                            transition_misses += 1;
+                            #strict_miss
+                        }
+                    }
                 };
 
-                let m = quote! {
-                    ( #match_state , #match_event ) #guard => {
+                if forked_region_ids.contains(&region_id) {
+                    regions.append_all(quote! {
+                        if !forked_regions[#region_id] {
+                            #region_match
+                        }
+                    });
+                } else {
+                    regions.append_all(region_match);
+                }
+            }
 
-                        #timers_exit
+            Ok(regions)
+        };
 
-                        <#transition_ty>::execute_transition(&mut ctx, &ev, #region_id, &mut inspect_event_ctx);
+        let regions = generate_regions(false, false)?;
+        let regions_async = generate_regions(true, false)?;
+        let regions_borrowed = generate_regions(false, true)?;
 
-                        #fsm_sub_entry
-                        
-                        #timers_enter                        
-                    },
+        // parking the deferred events and releasing them once their deferring states are exited
+        let (defer_check, defer_release, peek_deferred) = {
+            let mut deferring = TokenStream::new();
+            let mut release = TokenStream::new();
+
+            for region in &fsm.fsm.regions {
+                let region_id = region.region_id;
+                let mut deferring_states = vec![];
+
+                for state in region.states.iter().filter(|s| !s.deferred_events.is_empty()) {
+                    let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                    let variant = state_types.get_fsm_no_generics_ty();
+
+                    let events: Vec<_> = state.deferred_events.iter().map(|ev| { let ev = event_variant(ev); quote! { #event_enum_ty :: #ev (_) } }).collect();
+
+                    deferring.append_all(quote! {
+                        (#region_id, finny::FsmCurrentState::State(#states_enum_ty :: #variant), #(#events)|*) => true,
+                    });
+
+                    deferring_states.push(quote! { finny::FsmCurrentState::State(#states_enum_ty :: #variant) });
+                }
+
+                if !deferring_states.is_empty() {
+                    release.append_all(quote! {
+                        if ctx.backend.current_states[#region_id] != states_before[#region_id] && matches!(states_before[#region_id], #(#deferring_states)|*) {
+                            ctx.backend.deferred.release();
+                        }
+                    });
+                }
+            }
+
+            if deferring.is_empty() {
+                (TokenStream::new(), TokenStream::new(), TokenStream::new())
+            } else {
+                let check = quote! {
+                    if let finny::FsmEvent::Event(ref ev) = event {
+                        let is_deferred = ctx.backend.current_states.iter().enumerate().any(|(region, state)| match (region, *state, ev) {
+                            #deferring
+                            _ => false
+                        });
+
+                        if is_deferred {
+                            let inspect_event_ctx = ctx.inspect.new_event::<Self>(&event, &ctx.backend);
+                            inspect_event_ctx.info("Deferring the event.");
+                            let result = ctx.backend.deferred.defer(ev.clone());
+                            inspect_event_ctx.event_done(&ctx.backend);
+                            return result;
+                        }
+                    }
+
+                    let states_before = ctx.backend.current_states;
                 };
 
-                region_transitions.append_all(m);
+                let peek = quote! {
+                    if let finny::FsmEvent::Event(ref ev) = event {
+                        if backend.current_states.iter().enumerate().any(|(region, state)| match (region, *state, ev) {
+                            #deferring
+                            _ => false
+                        }) {
+                            return Ok(None);
+                        }
+                    }
+                };
+
+                (check, release, peek)
             }
+        };
 
-            // match and dispatch to submachines
-            let region_submachines = {
+        // evaluating the guards of the transitions for the event, in the order of the dispatch
+        let peek = {
+            let mut regions = TokenStream::new();
 
-                let mut sub_matches = TokenStream::new();
+            for region in fsm.fsm.regions_by_priority() {
+                let region_id = region.region_id;
+                let mut region_matches = TokenStream::new();
+                let mut region_mapped = TokenStream::new();
 
-                let submachines: Vec<_> = region.transitions.iter().filter_map(|t| match &t.ty {
-                    FsmTransitionType::InternalTransition(_) => None,
-                    FsmTransitionType::SelfTransition(_) => None,
-                    FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }), .. }) => {
-                        Some(s)
-                    },
-                    _ => None
-                }).collect();
+                for state in region.states.iter() {
+                    let submachine = match state.kind {
+                        FsmStateKind::SubMachine(ref sub) => sub,
+                        FsmStateKind::Normal => continue
+                    };
 
-                for submachine in submachines {
-                    let kind = &submachine.ty;
-                    let fsm_sub = FsmTypes::new(&submachine.ty, &fsm.base.fsm_generics);
+                    let sub_ty = &state.ty;
+                    let fsm_sub = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
                     let kind_variant = fsm_sub.get_fsm_no_generics_ty();
+                    let sub_events_ty = fsm_sub.get_fsm_events_ty();
+
+                    // only the submachines that are entered by a transition receive the parent's events
+                    let entered = region.transitions.iter().any(|t| matches!(&t.ty, FsmTransitionType::StateTransition(FsmStateTransition { state_to: FsmTransitionState::State(s), .. }) if s.ty == state.ty));
+                    if entered {
+                        region_matches.append_all(quote! {
+                            ( finny::FsmCurrentState::State(#states_enum_ty :: #kind_variant), finny::FsmEvent::Event(#event_enum_ty::#kind_variant(ev)) ) => {
+                                return finny::peek_submachine::<_, #sub_ty, _>(backend, queue, finny::FsmEvent::Event(ev.clone()));
+                            },
+                        });
+                    }
 
-                    let sub = quote! {
-                        ( finny::FsmCurrentState::State(#states_enum_ty :: #kind_variant), finny::FsmEvent::Event(#event_enum_ty::#kind_variant(ev))  ) => {
-                            return finny::dispatch_to_submachine::<_, #kind, _, _, _>(&mut ctx, finny::FsmEvent::Event(ev.clone()), &mut inspect_event_ctx);
-                        },
+                    for (ty, closure) in &submachine.mapped_events {
+                        let variant = event_variant(ty);
+                        let remap = remap_closure_inputs(&closure.inputs, &[quote! { ev }])?;
+                        let body = &closure.body;
+
+                        region_mapped.append_all(quote! {
+                            ( finny::FsmCurrentState::State(#states_enum_ty :: #kind_variant), finny::FsmEvent::Event(#event_enum_ty::#variant(ev)) ) => {
+                                let sub_ev: #sub_events_ty = {
+                                    #remap
+                                    { #body }.into()
+                                };
+                                return finny::peek_submachine::<_, #sub_ty, _>(backend, queue, finny::FsmEvent::Event(sub_ev));
+                            },
+                        });
+                    }
+                }
+
+                for transition in region.transitions.iter().filter(|t| !is_borrowed(t)) {
+                    let transition_ty = &transition.transition_ty;
+                    let transition_id = tokens_to_string(transition_ty);
+                    let match_state = match_transition_state(transition);
+                    let match_event = match_transition_event(transition, false);
+
+                    let action = match &transition.ty {
+                        FsmTransitionType::StateTransition(s) => &s.action,
+                        FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => &s.action
+                    };
+
+                    let mut conditions = vec![];
+                    for join in &action.joins {
+                        let (joined_region, joined_state) = fsm.fsm.find_state(join)?;
+                        let joined_region_id = joined_region.region_id;
+                        let joined_types = FsmTypes::new(&joined_state.ty, &fsm.base.fsm_generics);
+                        let variant = joined_types.get_fsm_no_generics_ty();
+
+                        conditions.push(quote! {
+                            backend.current_states[#joined_region_id] == finny::FsmCurrentState::State(#states_enum_ty :: #variant)
+                        });
+                    }
+
+                    if action.guard.is_some() {
+                        conditions.push(quote! {
+                            {
+                                let event_context = finny::EventContext {
+                                    context: &mut backend.context,
+                                    queue: &mut *queue,
+                                    region: #region_id,
+                                    timers: &mut backend.timer_requests,
+                                    outputs: &mut backend.outputs,
+                                    resources: None
+                                };
+
+                                <#transition_ty>::guard(&ev, &event_context, &backend.states)?
+                            }
+                        });
+                    } else if action.guard_async.is_some() {
+                        conditions.push(quote! { false });
+                    }
+
+                    if let Some((index, limit)) = rate_limit(transition) {
+                        conditions.push(quote! { backend.timer_requests.rate_limit_passes(#index, #limit) });
+                    }
+
+                    if let Some(max) = &action.retry {
+                        conditions.push(quote! { backend.timer_requests.can_retry(#region_id, #max) });
+                    }
+
+                    let guard = if conditions.is_empty() {
+                        TokenStream::new()
+                    } else {
+                        quote! { if #(#conditions)&&* }
                     };
 
-                    sub_matches.append_all(sub);
+                    region_matches.append_all(quote! {
+                        ( #match_state , #match_event ) #guard => {
+                            return Ok(Self::fsm_info().regions[#region_id].transitions.iter().find(|t| t.transition_id == #transition_id));
+                        },
+                    });
                 }
 
-                sub_matches
-            };
+                regions.append_all(quote! {
+                    #[allow(unreachable_patterns)]
+                    match (backend.current_states[#region_id], event) {
+                        #region_matches
+                        #region_mapped
+                        _ => ()
+                    }
+                });
+            }
 
-            // match and dispatch timer events
-            let timers = {
-                let mut timer_dispatch = TokenStream::new();
+            regions
+        };
+
+        // cancelling and restarting the timers, as requested by the actions
+        let timer_requests = {
+            let mut timer_matches = TokenStream::new();
+
+            for region in &fsm.fsm.regions {
+                let region_id = region.region_id;
 
-                // our timers
                 for state in &region.states {
+                    let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                    let variant = state_types.get_fsm_no_generics_ty();
+
+                    let state_field = &state.state_storage_field;
+
                     for timer in &state.timers {
+                        let timer_field = timer.get_field(&fsm.base);
                         let timer_ty = timer.get_ty(&fsm.base);
 
-                        timer_dispatch.append_all(quote! {
-                            (_, finny::FsmEvent::Timer( timer_id @ #timers_enum_ty :: #timer_ty )) => {
-                                {
-                                    use finny::FsmTimer;
-                                    < #timer_ty #fsm_generics_type > :: execute_trigger(*timer_id, &mut ctx, &mut inspect_event_ctx);
+                        timer_matches.append_all(quote! {
+                            #timers_enum_ty :: #timer_ty => {
+                                use finny::FsmTimer;
+                                if ctx.backend.current_states[#region_id] == finny::FsmCurrentState::State(#states_enum_ty :: #variant) {
+                                    match request {
+                                        finny::FsmTimerRequest::Cancel => ctx.backend.states. #timer_field . execute_cancel( timer_id, &mut inspect_event_ctx, ctx.timers ),
+                                        finny::FsmTimerRequest::Restart => ctx.backend.states. #timer_field . execute_on_enter( timer_id, &mut ctx.backend.context, &ctx.backend.states. #state_field , &mut inspect_event_ctx, ctx.timers )
+                                    }
+                                } else {
+                                    inspect_event_ctx.info("The timer's state isn't active, ignoring the timer request.");
                                 }
                             },
                         });
                     }
                 }
+            }
 
-                // sub machines
-                for state in region.states.iter().filter(|s| if let FsmStateKind::SubMachine(_) = s.kind { true } else { false })
-                {
-                    let sub = &state.ty;
-                    let sub_ty = FsmTypes::new(sub, &fsm.base.fsm_generics);
-                    let sub_variant = sub_ty.get_fsm_no_generics_ty();
+            if timer_matches.is_empty() {
+                TokenStream::new()
+            } else {
+                quote! {
+                    while let Some((timer_id, request)) = ctx.backend.timer_requests.take() {
+                        #[allow(unreachable_patterns)]
+                        match timer_id {
+                            #timer_matches
+                            _ => ()
+                        }
+                    }
+                }
+            }
+        };
+
+        // reacting to the events that weren't handled by any of the regions
+        let (unhandled_handler, unhandled_result) = {
+            let no_transition = no_transition(0);
+            let handler = match fsm.fsm.unhandled_event.handler {
+                Some(ref closure) => {
+                    let remap = remap_closure_inputs(&closure.inputs, &[quote! { ev }, quote! { &mut event_context }])?;
+                    let body = &closure.body;
 
-                    timer_dispatch.append_all(quote! {
-                        (_, finny::FsmEvent::Timer( #timers_enum_ty :: #sub_variant (timer_id))) => {
+                    quote! {
+                        if transition_misses == #region_count {
+                            if let finny::FsmEvent::Event(ref ev) = event {
+                                let mut event_context = finny::EventContext {
+                                    context: &mut ctx.backend.context,
+                                    queue: &mut *ctx.queue,
+                                    region: 0,
+                                    timers: &mut ctx.backend.timer_requests,
+                                    outputs: &mut ctx.backend.outputs,
+                                    resources: ctx.resources.as_deref_mut()
+                                };
+
+                                {
+                                    #remap
+                                    #body
+                                }
+                            }
+                        }
+                    }
+                },
+                None => TokenStream::new()
+            };
+
+            let result = match fsm.fsm.unhandled_event.policy {
+                Some(ref policy) => quote! {
+                    match #policy {
+                        finny::FsmUnhandledEventPolicy::Ignore => Ok(()),
+                        finny::FsmUnhandledEventPolicy::Inspect => {
+                            inspect_event_ctx.on_unhandled_event();
+                            Ok(())
+                        },
+                        finny::FsmUnhandledEventPolicy::Error => {
+                            inspect_event_ctx.on_unhandled_event();
+                            Err(#no_transition)
+                        }
+                    }
+                },
+                None => quote! {
+                    inspect_event_ctx.on_unhandled_event();
+                    Err(#no_transition)
+                }
+            };
+
+            (handler, result)
+        };
+
+        // in the strict mode, the event has to be handled by all of the regions
+        let (unexpected_event, result) = if fsm.fsm.codegen_options.strict_events {
+            (quote! {
+                let mut unexpected_event: Option<finny::FsmError> = None;
+            }, quote! {
+                if let Some(err) = unexpected_event {
+                    inspect_event_ctx.on_unhandled_event();
+                    Err(err)
+                } else if transition_misses == #region_count {
+                    #unhandled_result
+                } else {
+                    Ok(())
+                }
+            })
+        } else {
+            (TokenStream::new(), quote! {
+                if transition_misses == #region_count {
+                    #unhandled_result
+                } else {
+                    Ok(())
+                }
+            })
+        };
+
+        // re-entering the last active states, for submachines with history
+        let generate_resume = |is_async: bool| -> TokenStream {
+            let awaited = if is_async { quote! { .await } } else { TokenStream::new() };
+            let execute_on_entry = dispatch_fn_ident("execute_on_entry", is_async);
+            let dispatch_to_submachine = dispatch_fn_ident("dispatch_to_submachine", is_async);
+            let resume_submachine = dispatch_fn_ident("resume_submachine", is_async);
+
+            // the submachine's failure ends the resuming
+            let sub_failure = quote! {
+                inspect_event_ctx.event_done(&ctx.backend);
+                return Err(e);
+            };
+
+            let mut resume = TokenStream::new();
+            for region in &fsm.fsm.regions {
+                let region_id = region.region_id;
+                let mut state_matches = TokenStream::new();
+
+                for state in &region.states {
+                    let state_ty = &state.ty;
+                    let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                    let variant = state_types.get_fsm_no_generics_ty();
+
+                    let mut timers_enter = TokenStream::new();
+                    for timer in &state.timers {
+                        let timer_field = timer.get_field(&fsm.base);
+                        let timer_ty = timer.get_ty(&fsm.base);
+                        let state_field = &state.state_storage_field;
+
+                        timers_enter.append_all(quote! {
                             {
-                                let ev = finny::FsmEvent::Timer(*timer_id);
-                                return finny::dispatch_to_submachine::<_, #sub, _, _, _>(&mut ctx, ev, &mut inspect_event_ctx);
+                                use finny::FsmTimer;
+                                ctx.backend.states. #timer_field . execute_on_enter( #timers_enum_ty :: #timer_ty , &mut ctx.backend.context, &ctx.backend.states. #state_field , &mut inspect_event_ctx, ctx.timers );
+                            }
+                        });
+                    }
+
+                    let sub_entry = match state.kind {
+                        FsmStateKind::SubMachine(_) if state.history != FsmStateHistory::None => {
+                            let sub_deep = state.history == FsmStateHistory::Deep;
+                            quote! {
+                                if let Err(e) = finny::#resume_submachine::<_, #state_ty, _, _, _>(&mut ctx, deep || #sub_deep, &mut inspect_event_ctx) #awaited {
+                                    #sub_failure
+                                }
                             }
                         },
+                        FsmStateKind::SubMachine(_) => quote! {
+                            if deep {
+                                if let Err(e) = finny::#resume_submachine::<_, #state_ty, _, _, _>(&mut ctx, true, &mut inspect_event_ctx) #awaited {
+                                    #sub_failure
+                                }
+                            } else {
+                                {
+                                    use finny::FsmBackendResetSubmachine;
+                                    <Self as FsmBackendResetSubmachine<_, #state_ty >>::reset(ctx.backend, &mut inspect_event_ctx);
+                                }
+                                if let Err(e) = finny::#dispatch_to_submachine::<_, #state_ty, _, _, _>(&mut ctx, finny::FsmEvent::Start, &mut inspect_event_ctx) #awaited {
+                                    #sub_failure
+                                }
+                            }
+                        },
+                        FsmStateKind::Normal => TokenStream::new()
+                    };
+
+                    state_matches.append_all(quote! {
+                        finny::FsmCurrentState::State(#states_enum_ty :: #variant) => {
+                            < #state_ty >::#execute_on_entry(&mut ctx, #region_id, None) #awaited;
+
+                            #sub_entry
+
+                            #timers_enter
+                        },
                     });
                 }
 
-                timer_dispatch
+                resume.append_all(quote! {
+                    match history.as_ref()[#region_id] {
+                        #state_matches
+                        _ => ()
+                    }
+                });
+            }
+
+            resume
+        };
+
+        let resources_ty = match fsm.fsm.resources {
+            Some(ref ty) => quote! { #ty },
+            None => quote! { () }
+        };
+
+        let outputs_ty = match fsm.fsm.outputs {
+            Some(ref ty) => quote! { #ty },
+            None => quote! { () }
+        };
+
+        // the machines with async actions or guards fail on the sync dispatch path, instead of skipping them
+        let async_required = fsm.fsm.states.values().any(|s| s.on_entry_async_closure.is_some() || s.on_exit_async_closure.is_some())
+            || fsm.fsm.events.values().flat_map(|ev| ev.transitions.iter()).map(|t| t.get_action()).any(|a| a.guard_async.is_some() || a.action_async.is_some());
+        let sync_dispatch = |args: TokenStream, body: TokenStream| -> (TokenStream, TokenStream) {
+            if async_required {
+                (quote! { ctx }, quote! {
+                    let _ = (ctx, #args);
+                    Err(finny::FsmError::AsyncRequired)
+                })
+            } else {
+                (quote! { mut ctx }, body)
+            }
+        };
+
+        // the transactions of the steps, see `begin_transaction`
+        let with_transaction = |body: TokenStream| -> TokenStream {
+            if !transactional {
+                return body;
+            }
+
+            quote! {
+                const { assert!(<Q as finny::FsmEventQueue<Self>>::TRANSACTIONAL, "The queue can't be used by a transactional machine, it can't drop the events of a failed step."); }
+                let mut transaction = None;
+
+                #body
+            }
+        };
+
+        let generate_dispatch_body = |regions: &TokenStream| -> TokenStream {
+            quote! {
+                #defer_check
+
+                ctx.backend.timer_requests.refresh(&*ctx.timers);
+
+                let mut transition_misses = 0;
+                #unexpected_event
+
+                let mut inspect_event_ctx = ctx.inspect.new_event::<Self>(&event, &ctx.backend);
+
+                #regions
+
+                #unhandled_handler
+
+                #timer_requests
+
+                #defer_release
+
+                let result = #result;
+
+                inspect_event_ctx.event_done(&ctx.backend);
+
+                result
+            }
+        };
+        let (dispatch_ctx, dispatch_body) = sync_dispatch(quote! { event }, {
+            let body = with_transaction(generate_dispatch_body(&regions));
+            quote! {
+                use finny::{FsmTransitionGuard, FsmTransitionAction, FsmAction, FsmState, FsmTransitionFsmStart};
+
+                #body
+            }
+        });
+        let dispatch_body_async = with_transaction(generate_dispatch_body(&regions_async));
+
+        // the events that borrow their data have their own dispatch, they can't be queued
+        let borrowed_dispatch = if fsm.fsm.events.values().any(|ev| ev.is_borrowed()) {
+            let states_before = if defer_release.is_empty() {
+                TokenStream::new()
+            } else {
+                quote! { let states_before = ctx.backend.current_states; }
             };
 
-            regions.append_all(quote! {
-                match (ctx.backend.current_states[#region_id], &event) {
+            let dispatch_body_borrowed = with_transaction(quote! {
+                #states_before
 
-                    #region_submachines
-                    
-                    #region_transitions
+                ctx.backend.timer_requests.refresh(&*ctx.timers);
 
-                    // do not dispatch timers if the machine is stopped
-                    (finny::FsmCurrentState::Stopped, finny::FsmEvent::Timer(_)) => (),
+                let mut transition_misses = 0;
+                #unexpected_event
 
-                    #timers
+                let mut inspect_event_ctx = ctx.inspect.new_borrowed_event::<Self>(event.as_ref(), &ctx.backend);
 
-                    _ => {
-                        transition_misses += 1;
+                #regions_borrowed
+
+                #timer_requests
+
+                #defer_release
+
+                let result = #result;
+
+                inspect_event_ctx.event_done(&ctx.backend);
+
+                result
+            });
+            let (dispatch_ctx, dispatch_body_borrowed) = sync_dispatch(quote! { event }, quote! {
+                use finny::{FsmTransitionGuard, FsmTransitionAction, FsmAction, FsmState};
+
+                #dispatch_body_borrowed
+            });
+
+            quote! {
+                impl #fsm_generics_impl finny::FsmBackendBorrowed for #fsm_ty #fsm_generics_type
+                    #fsm_generics_where
+                {
+                    type BorrowedEvents<'e> = #borrowed_event_enum_ty <'e>;
+
+                    fn dispatch_borrowed_event<Q, I, T>(#dispatch_ctx: finny::DispatchContext<Self, Q, I, T>, event: finny::FsmEvent<Self::BorrowedEvents<'_>, Self::Timers>) -> finny::FsmDispatchResult
+                        where Q: finny::FsmEventQueue<Self>,
+                        I: finny::Inspect, T: finny::FsmTimers<Self>
+                    {
+                        #dispatch_body_borrowed
                     }
                 }
-            });
-        }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let resume_deep_arg = if fsm.fsm.states.values().any(|s| matches!(s.kind, FsmStateKind::SubMachine(_))) {
+            quote! { deep }
+        } else {
+            quote! { _deep }
+        };
+        let resume = generate_resume(false);
+        let (resume_ctx, resume) = sync_dispatch(resume_deep_arg.clone(), quote! {
+            use finny::FsmState;
+
+            let history = ctx.backend.current_states;
+            if finny::FsmCurrentState::all_stopped(history.as_ref()) {
+                return <Self as finny::FsmBackend>::dispatch_event(ctx, finny::FsmEvent::Start);
+            }
+
+            let event = finny::FsmEvent::Start;
+            let mut inspect_event_ctx = ctx.inspect.new_event::<Self>(&event, &ctx.backend);
+            inspect_event_ctx.info("Resuming the states from history.");
+
+            #resume
+
+            #timer_requests
+
+            inspect_event_ctx.event_done(&ctx.backend);
+
+            Ok(())
+        });
+        let resume_async = generate_resume(true);
+
+        // entering a submachine at an entry point, the other regions start at their initial states
+        let entry_point_states = {
+            let mut initial = TokenStream::new();
+            let mut entries = TokenStream::new();
+
+            for region in &fsm.fsm.regions {
+                let region_id = region.region_id;
+                let initial_types = FsmTypes::new(&region.initial_state, &fsm.base.fsm_generics);
+                let initial_variant = initial_types.get_fsm_no_generics_ty();
+                initial.append_all(quote! {
+                    states.as_mut()[#region_id] = finny::FsmCurrentState::State(#states_enum_ty :: #initial_variant);
+                });
+
+                for state in &region.states {
+                    let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                    let variant = state_types.get_fsm_no_generics_ty();
+                    entries.append_all(quote! {
+                        #states_enum_ty :: #variant => { states.as_mut()[#region_id] = finny::FsmCurrentState::State(state); },
+                    });
+                }
+            }
+
+            quote! {
+                let mut states = <<<Self as finny::FsmBackend>::States as finny::FsmStates<Self>>::CurrentState>::default();
+                #initial
+
+                #[allow(unreachable_patterns)]
+                match state {
+                    #entries
+                    _ => ()
+                }
+
+                states
+            }
+        };
 
         quote! {
               
@@ -712,31 +2244,92 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                 #fsm_generics_where
             {
                 type Context = #ctx_ty;
+                type Resources = #resources_ty;
+                type Outputs = #outputs_ty;
                 type States = #states_store_ty #fsm_generics_type;
                 type Events = #event_enum_ty;
                 type Timers = #timers_enum_ty;
 
-                fn dispatch_event<Q, I, T>(mut ctx: finny::DispatchContext<Self, Q, I, T>, event: finny::FsmEvent<Self::Events, Self::Timers>) -> finny::FsmDispatchResult
+                fn dispatch_event<Q, I, T>(#dispatch_ctx: finny::DispatchContext<Self, Q, I, T>, event: finny::FsmEvent<Self::Events, Self::Timers>) -> finny::FsmDispatchResult
+                    where Q: finny::FsmEventQueue<Self>,
+                    I: finny::Inspect, T: finny::FsmTimers<Self>
+                {
+                    #dispatch_body
+                }
+
+                fn info() -> Option<&'static finny::FsmInfo> {
+                    Some(Self::fsm_info())
+                }
+            }
+
+            impl #fsm_generics_impl finny::FsmBackendAsync for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                async fn dispatch_event_async<Q, I, T>(mut ctx: finny::DispatchContext<'_, '_, '_, Self, Q, I, T>, event: finny::FsmEvent<Self::Events, Self::Timers>) -> finny::FsmDispatchResult
                     where Q: finny::FsmEventQueue<Self>,
                     I: finny::Inspect, T: finny::FsmTimers<Self>
                 {
                     use finny::{FsmTransitionGuard, FsmTransitionAction, FsmAction, FsmState, FsmTransitionFsmStart};
 
-                    let mut transition_misses = 0;
+                    #dispatch_body_async
+                }
+
+                async fn resume_async<Q, I, T>(mut ctx: finny::DispatchContext<'_, '_, '_, Self, Q, I, T>, #resume_deep_arg: bool) -> finny::FsmDispatchResult
+                    where Q: finny::FsmEventQueue<Self>,
+                    I: finny::Inspect, T: finny::FsmTimers<Self>
+                {
+                    use finny::FsmState;
+
+                    let history = ctx.backend.current_states;
+                    if finny::FsmCurrentState::all_stopped(history.as_ref()) {
+                        return <Self as finny::FsmBackendAsync>::dispatch_event_async(ctx, finny::FsmEvent::Start).await;
+                    }
 
+                    let event = finny::FsmEvent::Start;
                     let mut inspect_event_ctx = ctx.inspect.new_event::<Self>(&event, &ctx.backend);
+                    inspect_event_ctx.info("Resuming the states from history.");
 
-                    #regions
+                    #resume_async
 
-                    let result = if transition_misses == #region_count {
-                        Err(finny::FsmError::NoTransition)
-                    } else {
-                        Ok(())
-                    };
+                    #timer_requests
 
                     inspect_event_ctx.event_done(&ctx.backend);
 
-                    result
+                    Ok(())
+                }
+            }
+
+            #borrowed_dispatch
+
+            impl #fsm_generics_impl finny::FsmBackendHistory for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                fn resume<Q, I, T>(#resume_ctx: finny::DispatchContext<Self, Q, I, T>, #resume_deep_arg: bool) -> finny::FsmDispatchResult
+                    where Q: finny::FsmEventQueue<Self>,
+                    I: finny::Inspect, T: finny::FsmTimers<Self>
+                {
+                    #resume
+                }
+
+                fn entry_point_states(state: #states_enum_ty) -> <<Self as finny::FsmBackend>::States as finny::FsmStates<Self>>::CurrentState {
+                    #entry_point_states
+                }
+            }
+
+            impl #fsm_generics_impl finny::FsmBackendPeek for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                #[allow(unused_variables)]
+                fn peek_event<Q>(backend: &mut finny::FsmBackendImpl<Self>, queue: &mut Q, event: &finny::FsmEvent<Self::Events, Self::Timers>) -> finny::FsmResult<Option<&'static finny::FsmInfoTransition>>
+                    where Q: finny::FsmEventQueue<Self>
+                {
+                    use finny::FsmTransitionGuard;
+
+                    #peek_deferred
+
+                    #peek
+
+                    Ok(None)
                 }
             }
 
@@ -754,10 +2347,11 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
         let mut states = TokenStream::new();
         for (ty, state) in fsm.fsm.states.iter() {
+            let state_is_entry_point = state.is_entry_point;
 
             let remap_closure = |c: &Option<syn::ExprClosure>| -> syn::Result<TokenStream> {
                 if let Some(c) = &c {
-                    let remap = remap_closure_inputs(&c.inputs, &vec![ quote! { self }, quote! { context } ])?;
+                    let remap = remap_closure_inputs(&c.inputs, &[ quote! { self }, quote! { context } ])?;
                     let b = &c.body;
                     
                     let q = quote! {                                        
@@ -773,8 +2367,53 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
             let on_entry = remap_closure(&state.on_entry_closure)?;
             let on_exit = remap_closure(&state.on_exit_closure)?;
 
+            let remap_async_closure = |c: &Option<syn::ExprClosure>, name: &str, sync_name: &str| -> syn::Result<TokenStream> {
+                if let Some(c) = &c {
+                    let remap = remap_closure_inputs(&c.inputs, &[ quote! { self }, quote! { context } ])?;
+                    let b = async_closure_body(c);
+                    let name = syn::Ident::new(name, c.span());
+                    let sync_name = syn::Ident::new(sync_name, c.span());
+
+                    let q = quote! {
+                        async fn #name<'fsm_event, Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>>(&mut self, context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>) {
+                            self. #sync_name (context);
+                            #remap
+                            #b;
+                        }
+                    };
+                    Ok(q)
+                } else {
+                    Ok(TokenStream::new())
+                }
+            };
+
+            let on_entry_async = remap_async_closure(&state.on_entry_async_closure, "on_entry_async", "on_entry")?;
+            let on_exit_async = remap_async_closure(&state.on_exit_async_closure, "on_exit_async", "on_exit")?;
+
+            let remap_event_closure = |c: &Option<syn::ExprClosure>, name: &str| -> syn::Result<TokenStream> {
+                if let Some(c) = &c {
+                    let remap = remap_closure_inputs(&c.inputs, &[ quote! { self }, quote! { event }, quote! { context } ])?;
+                    let b = &c.body;
+                    let name = syn::Ident::new(name, c.span());
+
+                    let q = quote! {
+                        fn #name<'fsm_event, Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>>(&mut self, event: Option<&finny::FsmEvent<#event_enum_ty, #timers_enum_ty>>, context: &mut finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>) {
+                            #remap
+                            { #b }
+                        }
+                    };
+                    Ok(q)
+                } else {
+                    Ok(TokenStream::new())
+                }
+            };
+
+            let on_entry_with_event = remap_event_closure(&state.on_entry_event_closure, "on_entry_with_event")?;
+            let on_exit_with_event = remap_event_closure(&state.on_exit_event_closure, "on_exit_with_event")?;
+
             let state_ty = FsmTypes::new(&ty, &fsm.base.fsm_generics);
-            let variant = state_ty.get_fsm_no_generics_ty();            
+            let variant = state_ty.get_fsm_no_generics_ty();
+            let display_name = state.display_name();
 
             let state = quote! {
 
@@ -787,15 +2426,33 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                         #on_exit
                     }
 
+                    #on_entry_async
+
+                    #on_exit_async
+
+                    #on_entry_with_event
+
+                    #on_exit_with_event
+
                     fn fsm_state() -> #states_enum_ty {
                         #states_enum_ty :: #variant
                     }
+
+                    fn fsm_state_name() -> &'static str {
+                        #display_name
+                    }
                 }
 
             };
 
             states.append_all(state);
 
+            if state_is_entry_point {
+                states.append_all(quote! {
+                    impl #fsm_generics_impl finny::FsmEntryPoint<#fsm_ty #fsm_generics_type> for #ty #fsm_generics_where { }
+                });
+            }
+
         }
 
         states
@@ -806,6 +2463,7 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
         quote! {
 
             /// A Finny Finite State Machine.
+            #serde_derives
             pub struct #fsm_ty #fsm_generics_type #fsm_generics_where {
                 backend: finny::FsmBackendImpl<#fsm_ty #fsm_generics_type >
             }
@@ -873,7 +2531,12 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                 enum_variants.push(quote! { #timer_ty });
                 our_timers.push(timer_ty.clone());
 
-                let setup = remap_closure_inputs(&timer.setup.inputs, &[quote! { ctx }, quote! { settings }])?;
+                // the timers with a computed timeout also have access to the state
+                let setup = if timer.setup.inputs.len() == 3 {
+                    remap_closure_inputs(&timer.setup.inputs, &[quote! { ctx }, quote! { state }, quote! { settings }])?
+                } else {
+                    remap_closure_inputs(&timer.setup.inputs, &[quote! { ctx }, quote! { settings }])?
+                };
                 let setup_body = &timer.setup.body;
 
                 let trigger = remap_closure_inputs(&timer.trigger.inputs, &[quote! { ctx }, quote! { state }])?;
@@ -898,7 +2561,7 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                     }
 
                     impl #fsm_generics_impl finny::FsmTimer< #fsm_ty #fsm_generics_type , #state_ty > for #timer_ty #fsm_generics_type #fsm_generics_where {
-                        fn setup(ctx: &mut #ctx_ty, settings: &mut finny::TimerFsmSettings) {
+                        fn setup(ctx: &mut #ctx_ty, state: & #state_ty, settings: &mut finny::TimerFsmSettings) {
                             #setup
                             {
                                 #setup_body
@@ -922,6 +2585,12 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                         }
                     }
 
+                    impl #fsm_generics_impl finny::FsmTimerId< #fsm_ty #fsm_generics_type > for #timer_ty #fsm_generics_type #fsm_generics_where {
+                        fn timer_id() -> #timers_enum_ty {
+                            #timers_enum_ty :: #timer_ty
+                        }
+                    }
+
                 });
             }
         }
@@ -935,6 +2604,7 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
         code.append_all(quote! {
             #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            #serde_derives
             pub enum #timers_enum_ty {
                 #variants
             }
@@ -1053,6 +2723,38 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
         new_fields.append_separated(new_fields_vec, quote! { , });
 
         
+        let mut timers_storage_ref_matches = vec![];
+        timers_storage_ref_matches.extend(our_timers_storage.iter().map(|(field, ty)| {
+            quote! {
+                #timers_enum_ty :: #ty => &self. #field
+            }
+        }));
+        timers_storage_ref_matches.extend(submachines.iter().map(|s| {
+            let ty = s.get_fsm_timers_storage_ty();
+            let t = s.get_fsm_no_generics_ty();
+            let field = to_field_name(&ty);
+            quote! {
+                #timers_enum_ty :: #t (ref sub) => {
+                    self. #field .get_timer_storage(sub)
+                }
+            }
+        }));
+
+        let ref_matches = if timers_storage_ref_matches.is_empty() {
+            quote! {
+                panic!("Not supported in this FSM.");
+            }
+        } else {
+            let mut m = TokenStream::new();
+            m.append_separated(timers_storage_ref_matches, quote! { , });
+
+            quote! {
+                match *id {
+                    #m
+                }
+            }
+        };
+
         let mut timers_storage_matches = vec![];
         timers_storage_matches.extend(our_timers_storage.iter().map(|(field, ty)| {
             quote! {
@@ -1104,6 +2806,10 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
             impl<TTimerStorage> finny::TimersStorage<#timers_enum_ty , TTimerStorage> for #timers_storage_ty<TTimerStorage>
             {
+                fn get_timer_storage(&self, id: & #timers_enum_ty ) -> &Option<TTimerStorage> {
+                    #ref_matches
+                }
+
                 fn get_timer_storage_mut(&mut self, id: & #timers_enum_ty ) -> &mut Option<TTimerStorage> {
                     #matches
                 }
@@ -1145,6 +2851,7 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
                         {
                             let sub_fsm: &mut #sub_ty = backend.states.as_mut();
                             sub_fsm.backend.current_states = Default::default();
+                            sub_fsm.backend.deferred.clear();
                             inspect_event_ctx.info("Setting the state of the submachine to Start.");
                         }
                     }
@@ -1157,8 +2864,203 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
         }
     };
 
+    let cancel_timers = {
+        let mut cancels = TokenStream::new();
+        for state in fsm.fsm.states.values() {
+            for timer in &state.timers {
+                let timer_field = timer.get_field(&fsm.base);
+                let timer_ty = timer.get_ty(&fsm.base);
+
+                cancels.append_all(quote! {
+                    backend.states. #timer_field . execute_cancel_nested( #timers_enum_ty :: #timer_ty , inspect, timers );
+                });
+            }
+
+            if let FsmStateKind::SubMachine(_) = state.kind {
+                let sub_ty = &state.ty;
+                cancels.append_all(quote! {
+                    {
+                        let sub_fsm: &mut #sub_ty = backend.states.as_mut();
+                        let mut timers_adapter = finny::FsmTimersSub {
+                            parent: &mut *timers,
+                            _parent_fsm: core::marker::PhantomData,
+                            _sub_fsm: core::marker::PhantomData
+                        };
+                        let mut inspect = inspect.for_sub_machine::<#sub_ty>();
+                        <#sub_ty as finny::FsmBackendCancelTimers>::cancel_timers(&mut **sub_fsm, &mut inspect, &mut timers_adapter);
+                    }
+                });
+            }
+        }
+
+        quote! {
+            impl #fsm_generics_impl finny::FsmBackendCancelTimers for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                #[allow(unused_variables)]
+                fn cancel_timers<I, T>(backend: &mut finny::FsmBackendImpl<Self>, inspect: &mut I, timers: &mut T)
+                    where I: finny::Inspect, T: finny::FsmTimers<Self>
+                {
+                    #[allow(unused_imports)]
+                    use finny::FsmTimer;
+
+                    #cancels
+                }
+            }
+        }
+    };
+
+    let stop_states = {
+        let mut regions = TokenStream::new();
+        for region in &fsm.fsm.regions {
+            let region_id = region.region_id;
+
+            let mut exits = TokenStream::new();
+            for state in &region.states {
+                let state_ty = &state.ty;
+                let state_types = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+                let variant = state_types.get_fsm_no_generics_ty();
+
+                // the submachine's states are exited before the submachine itself
+                let mut exit = match state.kind {
+                    FsmStateKind::SubMachine(_) => quote! {
+                        finny::stop_submachine::<Self, #state_ty, _, _, _>(&mut ctx, &mut inspect_event_ctx);
+                    },
+                    FsmStateKind::Normal => TokenStream::new()
+                };
+
+                for timer in &state.timers {
+                    let timer_field = timer.get_field(&fsm.base);
+                    let timer_ty = timer.get_ty(&fsm.base);
+
+                    exit.append_all(quote! {
+                        {
+                            use finny::FsmTimer;
+                            ctx.backend.states. #timer_field . execute_on_exit( #timers_enum_ty :: #timer_ty , &mut inspect_event_ctx, ctx.timers );
+                        }
+                    });
+                }
+
+                exits.append_all(quote! {
+                    finny::FsmCurrentState::State(#states_enum_ty :: #variant) => {
+                        #exit
+                        <#state_ty>::execute_on_exit(&mut ctx, #region_id, Some(&event));
+                    },
+                });
+            }
+
+            regions.append_all(quote! {
+                #[allow(unreachable_patterns)]
+                match ctx.backend.current_states[#region_id] {
+                    #exits
+                    _ => ()
+                }
+                ctx.backend.current_states[#region_id] = finny::FsmCurrentState::Stopped;
+            });
+        }
+
+        quote! {
+            impl #fsm_generics_impl finny::FsmBackendStop for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                #[allow(unused_mut, unused_variables)]
+                fn stop_states<Q, I, T>(mut ctx: finny::DispatchContext<Self, Q, I, T>)
+                    where Q: finny::FsmEventQueue<Self>, I: finny::Inspect, T: finny::FsmTimers<Self>
+                {
+                    #[allow(unused_imports)]
+                    use finny::FsmState;
+
+                    // the exit actions measure the time in their states up to now
+                    ctx.backend.timer_requests.refresh(&*ctx.timers);
+
+                    let event = finny::FsmEvent::Stop;
+                    let mut inspect_event_ctx = ctx.inspect.new_event::<Self>(&event, ctx.backend);
+
+                    #regions
+
+                    inspect_event_ctx.event_done(ctx.backend);
+                }
+            }
+        }
+    };
+
     let fsm_meta = generate_fsm_meta(&fsm);
 
+    let fsm_ffi = generate_fsm_ffi(fsm)?;
+
+    // the snapshots are tagged with a hash of their shape, the names of the states, events and regions
+    let snapshot_version = if fsm.fsm.codegen_options.derive_serde {
+        let mut states: Vec<_> = fsm.fsm.states.values().map(|s| {
+            let kind = match s.kind {
+                FsmStateKind::Normal => "state",
+                FsmStateKind::SubMachine(_) => "submachine"
+            };
+            format!("{}:{}", s.state_storage_field, kind)
+        }).collect();
+        states.sort();
+        let mut events: Vec<_> = fsm.fsm.events.values().map(|e| e.name()).collect();
+        events.sort();
+        let regions: Vec<_> = fsm.fsm.regions.iter().map(|r| tokens_to_string(&r.initial_state)).collect();
+
+        let shape = format!("states={};events={};regions={}", states.join(","), events.join(","), regions.join(","));
+        let version = shape.bytes().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3));
+
+        let submachines = fsm.fsm.states.values().filter(|s| matches!(s.kind, FsmStateKind::SubMachine(_))).map(|s| {
+            let ty = &s.ty;
+            quote! { <#ty as finny::FsmSnapshotVersion>::SNAPSHOT_VERSION }
+        });
+
+        quote! {
+            impl #fsm_generics_impl finny::FsmSnapshotVersion for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                const SNAPSHOT_VERSION: u64 = finny::fsm_snapshot_version(#version, &[ #(#submachines),* ]);
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let state_path = {
+        let mut submachine_arms = TokenStream::new();
+        for state in fsm.fsm.states.values().filter(|s| matches!(s.kind, FsmStateKind::SubMachine(_))) {
+            let ty = &state.ty;
+            let state_ty = FsmTypes::new(&state.ty, &fsm.base.fsm_generics);
+            let variant = state_ty.get_fsm_no_generics_ty();
+            let prefix = format!("{}/", state.display_name());
+
+            submachine_arms.append_all(quote! {
+                finny::FsmCurrentState::State(#states_enum_ty :: #variant) => {
+                    w.write_str(#prefix)?;
+                    let sub: & #ty = backend.states.as_ref();
+                    // the regions of a submachine are grouped
+                    let grouped = sub.backend.current_states.as_ref().len() > 1;
+                    if grouped { w.write_str("(")?; }
+                    <#ty as finny::FsmBackendStatePath>::write_state_path(&sub.backend, w)?;
+                    if grouped { w.write_str(")")?; }
+                },
+            });
+        }
+
+        quote! {
+            impl #fsm_generics_impl finny::FsmBackendStatePath for #fsm_ty #fsm_generics_type
+                #fsm_generics_where
+            {
+                fn write_state_path<W: core::fmt::Write>(backend: &finny::FsmBackendImpl<Self>, w: &mut W) -> core::fmt::Result {
+                    for (i, state) in backend.current_states.as_ref().iter().enumerate() {
+                        if i > 0 { w.write_str(", ")?; }
+                        match state {
+                            finny::FsmCurrentState::Stopped => w.write_str("Stopped")?,
+                            #submachine_arms
+                            finny::FsmCurrentState::State(state) => w.write_str(state.state_name())?
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        }
+    };
+
     let q = quote! {
         #states_store
 
@@ -1176,7 +3078,17 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
 
         #sub_restart
 
+        #cancel_timers
+
+        #stop_states
+
+        #snapshot_version
+
+        #state_path
+
         #fsm_meta
+
+        #fsm_ffi
     };
 
     /*
@@ -1190,4 +3102,146 @@ pub fn generate_fsm_code(fsm: &FsmFnInput, _attr: TokenStream, _input: TokenStre
     */
 
     Ok(q.into())
-}
\ No newline at end of file
+}
+
+/// The guard's trait implementation. A guard that is only declared as async will reject the
+/// transition when peeked, the sync dispatch of such machines fails.
+fn generate_transition_guard(fsm: &FsmFnInput, ty: &syn::Type, event_ty: &syn::Type, action: &EventGuardAction) -> syn::Result<TokenStream> {
+    let fsm_ty = &fsm.base.fsm_ty;
+    let states_store_ty = ty_append(&fsm.base.fsm_ty, "States");
+    let (fsm_generics_impl, fsm_generics_type, fsm_generics_where) = fsm.base.fsm_generics.split_for_impl();
+
+    let guard_body = if let Some(ref guard) = action.guard {
+        let remap = remap_closure_inputs(&guard.inputs, vec![
+            quote! { event }, quote! { context }, quote! { states }
+        ].as_slice())?;
+
+        let body = &guard.body;
+
+        if action.guard_fallible {
+            quote! {
+                #remap
+                let result = { #body };
+                result
+            }
+        } else {
+            quote! {
+                #remap
+                let result = { #body };
+                Ok(result)
+            }
+        }
+    } else {
+        quote! {
+            let _ = (event, context, states);
+            Ok(false)
+        }
+    };
+
+    let guard_async = if let Some(ref guard) = action.guard_async {
+        let remap = remap_closure_inputs(&guard.inputs, vec![
+            quote! { event }, quote! { context }, quote! { states }
+        ].as_slice())?;
+
+        let body = async_closure_body(guard);
+
+        quote! {
+            async fn guard_async<'fsm_event, Q>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: &'fsm_event #states_store_ty #fsm_generics_type) -> finny::FsmResult<bool>
+                where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
+            {
+                #remap
+                let result = #body;
+                Ok(result)
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let guard_name = match action.guard_name {
+        Some(ref name) => quote! {
+            fn guard_name() -> &'static str {
+                #name
+            }
+        },
+        None => TokenStream::new()
+    };
+
+    // the combined guards report each of their evaluated terms
+    let guard_inspected = if let Some(ref expr) = action.guard_expr {
+        let expr = expr.expand(&[quote! { event }, quote! { context }, quote! { states }], &|name, term| quote! {
+            {
+                let result = #term;
+                inspect.on_guard::<Self>(#name, result);
+                result
+            }
+        })?;
+
+        quote! {
+            fn guard_inspected<'fsm_event, Q, I>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: &'fsm_event #states_store_ty #fsm_generics_type, inspect: &I) -> finny::FsmResult<bool>
+                where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>, I: finny::Inspect
+            {
+                Ok(#expr)
+            }
+
+            async fn guard_async_inspected<'fsm_event, Q, I>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: &'fsm_event #states_store_ty #fsm_generics_type, inspect: &I) -> finny::FsmResult<bool>
+                where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>, I: finny::Inspect
+            {
+                Self::guard_inspected(event, context, states, inspect)
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        impl #fsm_generics_impl finny::FsmTransitionGuard<#fsm_ty #fsm_generics_type, #event_ty> for #ty #fsm_generics_where {
+            fn guard<'fsm_event, Q>(event: & #event_ty, context: &finny::EventContext<'fsm_event, #fsm_ty #fsm_generics_type, Q>, states: & #states_store_ty #fsm_generics_type) -> finny::FsmResult<bool>
+                where Q: finny::FsmEventQueue<#fsm_ty #fsm_generics_type>
+            {
+                #guard_body
+            }
+
+            #guard_name
+
+            #guard_inspected
+
+            #guard_async
+        }
+    })
+}
+
+/// The body of a transition's action. The result of a fallible action is returned as is.
+fn action_result_body(remap: &TokenStream, body: &syn::Expr, fallible: bool) -> TokenStream {
+    if fallible {
+        quote! {
+            #remap
+            { #body }
+        }
+    } else {
+        quote! {
+            #remap
+            { #body }
+            Ok(())
+        }
+    }
+}
+
+/// The awaited body of an async closure. Supports both the `async |..| { }` and the `|..| async move { }` forms.
+fn async_closure_body(closure: &syn::ExprClosure) -> TokenStream {
+    let body = &closure.body;
+    if closure.asyncness.is_some() {
+        quote! { { #body } }
+    } else {
+        quote! { ( #body ).await }
+    }
+}
+
+/// Name of the dispatch helper, suffixed for the async dispatch path.
+fn dispatch_fn_ident(name: &str, is_async: bool) -> syn::Ident {
+    if is_async {
+        syn::Ident::new(&format!("{}_async", name), Span::call_site())
+    } else {
+        syn::Ident::new(name, Span::call_site())
+    }
+}
@@ -0,0 +1,143 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{TokenStreamExt, quote};
+use syn::spanned::Spanned;
+
+use crate::{fsm::FsmTypes, parse::FsmFnInput, utils::{to_snake_case, tokens_to_string, ty_append}};
+
+/// The `extern "C"` functions of a machine declared with `fsm.ffi()`, and its C header.
+pub fn generate_fsm_ffi(fsm: &FsmFnInput) -> syn::Result<TokenStream> {
+    if !fsm.fsm.codegen_options.ffi {
+        return Ok(TokenStream::new());
+    }
+
+    if !fsm.base.fsm_generics.params.is_empty() {
+        return Err(syn::Error::new(fsm.base.fsm_generics.span(), "The C API can't be generated for a generic machine!"));
+    }
+
+    let fsm_ty = &fsm.base.fsm_ty;
+    let fsm_types = FsmTypes::new(&fsm.base.fsm_ty, &fsm.base.fsm_generics);
+    let event_enum_ty = fsm_types.get_fsm_events_ty();
+    let event_kind_ty = ty_append(&fsm.base.fsm_ty, "EventKind");
+
+    let prefix = to_snake_case(&tokens_to_string(fsm_ty));
+    let fn_ident = |name: &str| syn::Ident::new(&format!("{}_{}", prefix, name), Span::call_site());
+    let create_fn = fn_ident("create");
+    let destroy_fn = fn_ident("destroy");
+    let dispatch_fn = fn_ident("dispatch");
+    let current_state_fn = fn_ident("current_state");
+
+    let mut event_arms = TokenStream::new();
+    let mut defines = String::new();
+
+    for (ty, ev) in fsm.fsm.events.iter().filter(|(_, ev)| !ev.is_borrowed()) {
+        let variant = ev.variant();
+        event_arms.append_all(quote! {
+            Some(#event_kind_ty :: #variant) => <#ty as finny::FsmFfiEvent>::from_ffi_bytes(payload).map(#event_enum_ty :: #variant),
+        });
+        defines.push_str(&format!("#define {}_EVENT_{} {}u\n", prefix.to_uppercase(), to_snake_case(&variant.to_string()).to_uppercase(), ev.stable_id()));
+    }
+    for state in fsm.fsm.states.values() {
+        defines.push_str(&format!("#define {}_STATE_{} {}u\n", prefix.to_uppercase(), state.state_storage_field.to_string().to_uppercase(), state.stable_id()));
+    }
+
+    let header = format!(concat!(
+        "#include <stddef.h>\n",
+        "#include <stdint.h>\n\n",
+        "#ifndef FINNY_FFI_STATUS\n",
+        "#define FINNY_FFI_STATUS\n",
+        "#define FINNY_FFI_OK 0\n",
+        "#define FINNY_FFI_NULL_POINTER -1\n",
+        "#define FINNY_FFI_UNKNOWN_EVENT -2\n",
+        "#define FINNY_FFI_INVALID_PAYLOAD -3\n",
+        "#define FINNY_FFI_NO_TRANSITION -4\n",
+        "#define FINNY_FFI_STOPPED -5\n",
+        "#define FINNY_FFI_FAILED -6\n",
+        "#endif\n\n",
+        "{defines}\n",
+        "typedef struct {p} {p}_t;\n\n",
+        "{p}_t *{p}_create(void);\n",
+        "void {p}_destroy({p}_t *fsm);\n",
+        "int32_t {p}_dispatch({p}_t *fsm, uint32_t event_id, const uint8_t *payload, size_t payload_len);\n",
+        "int32_t {p}_current_state(const {p}_t *fsm, size_t region, uint32_t *state_id);\n"
+    ), defines = defines, p = prefix);
+
+    let create_doc = format!("Create the machine with the default context and start it. Returns null if it couldn't be started. Free it with `{}`.", destroy_fn);
+
+    Ok(quote! {
+        impl #fsm_ty {
+            /// The C header of the machine's `extern "C"` API, with the stable ids of its events and states.
+            pub const FFI_HEADER: &'static str = #header;
+        }
+
+        #[doc = #create_doc]
+        #[no_mangle]
+        pub extern "C" fn #create_fn() -> *mut finny::FsmFfiFrontend<#fsm_ty> {
+            let fsm = <#fsm_ty as finny::FsmFactory>::new_with(Default::default(), finny::FsmEventQueueVec::new(), finny::inspect::null::InspectNull::new(), finny::FsmTimersNull);
+            match fsm.and_then(|mut fsm| fsm.start().map(|_| fsm)) {
+                Ok(fsm) => fsm.into_ffi_ptr(),
+                Err(_) => core::ptr::null_mut()
+            }
+        }
+
+        /// Free the machine. Null pointers are ignored.
+        ///
+        /// # Safety
+        ///
+        /// The pointer has to be created by the machine's `create` function and can't be used afterwards.
+        #[no_mangle]
+        pub unsafe extern "C" fn #destroy_fn(fsm: *mut finny::FsmFfiFrontend<#fsm_ty>) {
+            finny::FsmFfiFrontend::<#fsm_ty>::drop_ffi_ptr(fsm)
+        }
+
+        /// Decode the event with the stable id from the payload and dispatch it, returns an `FsmFfiStatus`.
+        ///
+        /// # Safety
+        ///
+        /// The machine's pointer has to be valid, and the payload has to point to `payload_len` bytes, or be null.
+        #[no_mangle]
+        #[allow(unreachable_code, unreachable_patterns)]
+        pub unsafe extern "C" fn #dispatch_fn(fsm: *mut finny::FsmFfiFrontend<#fsm_ty>, event_id: u32, payload: *const u8, payload_len: usize) -> i32 {
+            let fsm = match fsm.as_mut() {
+                Some(fsm) => fsm,
+                None => return finny::FsmFfiStatus::NullPointer as i32
+            };
+            let payload: &[u8] = if payload.is_null() || payload_len == 0 {
+                &[]
+            } else {
+                core::slice::from_raw_parts(payload, payload_len)
+            };
+
+            let event: Option<#event_enum_ty> = match <#event_kind_ty as finny::FsmId>::from_id(event_id) {
+                #event_arms
+                _ => return finny::FsmFfiStatus::UnknownEvent as i32
+            };
+
+            match event {
+                Some(event) => finny::FsmFfiStatus::from_result(fsm.dispatch(event)) as i32,
+                None => finny::FsmFfiStatus::InvalidPayload as i32
+            }
+        }
+
+        /// Write the stable id of the region's current state, returns an `FsmFfiStatus`.
+        ///
+        /// # Safety
+        ///
+        /// The machine's pointer has to be valid, and the state's pointer has to be writable.
+        #[no_mangle]
+        pub unsafe extern "C" fn #current_state_fn(fsm: *const finny::FsmFfiFrontend<#fsm_ty>, region: usize, state_id: *mut u32) -> i32 {
+            let fsm = match fsm.as_ref() {
+                Some(fsm) => fsm,
+                None => return finny::FsmFfiStatus::NullPointer as i32
+            };
+
+            match fsm.get_current_states().as_ref().get(region).and_then(|state| state.id()) {
+                Some(id) if !state_id.is_null() => {
+                    *state_id = id;
+                    finny::FsmFfiStatus::Ok as i32
+                },
+                Some(_) => finny::FsmFfiStatus::NullPointer as i32,
+                None => finny::FsmFfiStatus::Stopped as i32
+            }
+        }
+    })
+}
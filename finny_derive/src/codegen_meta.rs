@@ -2,9 +2,9 @@
 use proc_macro2::TokenStream;
 
 use crate::{meta::{
-        FinnyEvent, FinnyFsm, FinnyRegion, FinnyState, FinnyStateKind, FinnyTimer, FinnyTransition,
+        FinnyEvent, FinnyFsm, FinnyGuard, FinnyRegion, FinnyState, FinnyStateKind, FinnyTimer, FinnyTransition,
         FinnyTransitionKind, FinnyTransitionNormal,
-    }, parse::{FsmFnInput, FsmState, FsmStateKind, FsmTransitionState}, utils::{strip_generics, tokens_to_string}};
+    }, parse::{FsmFnInput, FsmGuardExpr, FsmState, FsmStateKind, FsmTransitionEvent, FsmTransitionState, FsmTransitionType}, utils::{event_name, strip_generics, tokens_to_string}};
 use quote::quote;
 
 fn ty_to_string(ty: &syn::Type) -> String {
@@ -13,11 +13,17 @@ fn ty_to_string(ty: &syn::Type) -> String {
     tokens_to_string(&ty)
 }
 
+/// The name of the event, as in the generated event enums.
+fn event_display_name(fsm: &FsmFnInput, ty: &syn::Type) -> String {
+    fsm.fsm.events.get(ty).map(|e| e.display_name()).unwrap_or_else(|| event_name(ty))
+}
+
 fn to_info_state(s: &FsmTransitionState, fsm: &FsmFnInput) -> FinnyStateKind {
     match s {
         FsmTransitionState::None => FinnyStateKind::Stopped,
         FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::Normal, .. }) => FinnyStateKind::State(FinnyState {
             state_id: ty_to_string(&s.ty),
+            name: s.display_name(),
             timers: s
                 .timers
                 .iter()
@@ -25,11 +31,40 @@ fn to_info_state(s: &FsmTransitionState, fsm: &FsmFnInput) -> FinnyStateKind {
                     timer_id: tokens_to_string(&t.get_ty(&fsm.base)),
                 })
                 .collect(),
+            is_final: s.is_final
         }),
         FsmTransitionState::State(s @ FsmState { kind: FsmStateKind::SubMachine(_), .. }) => FinnyStateKind::SubMachine(ty_to_string(&s.ty))
     }
 }
 
+fn to_info_guard(expr: &FsmGuardExpr) -> FinnyGuard {
+    match expr {
+        FsmGuardExpr::Term { name, .. } => FinnyGuard::Guard(name.clone()),
+        FsmGuardExpr::Not(e) => FinnyGuard::Not(Box::new(to_info_guard(e))),
+        FsmGuardExpr::And(a, b) => FinnyGuard::And(Box::new(to_info_guard(a)), Box::new(to_info_guard(b))),
+        FsmGuardExpr::Or(a, b) => FinnyGuard::Or(Box::new(to_info_guard(a)), Box::new(to_info_guard(b)))
+    }
+}
+
+/// The `FsmInfoGuard` tree, promoted to a static.
+fn to_fsm_info_guard(expr: &FsmGuardExpr) -> TokenStream {
+    match expr {
+        FsmGuardExpr::Term { name, .. } => quote! { finny::FsmInfoGuard::Guard(#name) },
+        FsmGuardExpr::Not(e) => {
+            let e = to_fsm_info_guard(e);
+            quote! { finny::FsmInfoGuard::Not(&#e) }
+        },
+        FsmGuardExpr::And(a, b) => {
+            let (a, b) = (to_fsm_info_guard(a), to_fsm_info_guard(b));
+            quote! { finny::FsmInfoGuard::And(&#a, &#b) }
+        },
+        FsmGuardExpr::Or(a, b) => {
+            let (a, b) = (to_fsm_info_guard(a), to_fsm_info_guard(b));
+            quote! { finny::FsmInfoGuard::Or(&#a, &#b) }
+        }
+    }
+}
+
 fn to_info(fsm: &FsmFnInput) -> FinnyFsm {
     let stopped_state = FinnyStateKind::Stopped;
 
@@ -59,9 +94,19 @@ fn to_info(fsm: &FsmFnInput) -> FinnyFsm {
                         transitions: region
                             .transitions
                             .iter()
-                            .map(|transition| {
+                            .enumerate()
+                            .map(|(order, transition)| {
                                 let transition_id = tokens_to_string(&transition.transition_ty);
 
+                                let action = match transition.ty {
+                                    crate::parse::FsmTransitionType::InternalTransition(ref s) | crate::parse::FsmTransitionType::SelfTransition(ref s) => &s.action,
+                                    crate::parse::FsmTransitionType::StateTransition(ref s) => &s.action
+                                };
+                                let guarded = action.has_guard();
+                                let guard = action.guard_name.clone();
+                                let guard_expr = action.guard_expr.as_ref().map(to_info_guard);
+                                let action_name = action.action_name.clone();
+
                                 let (event, transition_ty) = match transition.ty {
                                     crate::parse::FsmTransitionType::InternalTransition(
                                         ref internal,
@@ -92,7 +137,7 @@ fn to_info(fsm: &FsmFnInput) -> FinnyFsm {
                                     crate::parse::FsmTransitionEvent::Stop => FinnyEvent::Stop,
                                     crate::parse::FsmTransitionEvent::Start => FinnyEvent::Start,
                                     crate::parse::FsmTransitionEvent::Event(ev) => {
-                                        FinnyEvent::Event(tokens_to_string(&ev.ty))
+                                        FinnyEvent::Event(event_display_name(fsm, &ev.ty))
                                     }
                                 };
 
@@ -102,6 +147,11 @@ fn to_info(fsm: &FsmFnInput) -> FinnyFsm {
                                         transition_id,
                                         event,
                                         transition: transition_ty,
+                                        guarded,
+                                        guard,
+                                        guard_expr,
+                                        action: action_name,
+                                        order
                                     },
                                 )
                             })
@@ -115,6 +165,139 @@ fn to_info(fsm: &FsmFnInput) -> FinnyFsm {
     finny_fsm
 }
 
+/// The static description of the machine, returned by the generated `fsm_info()`.
+fn generate_fsm_info(fsm: &FsmFnInput) -> TokenStream {
+    let state_id = |s: &FsmTransitionState| match s {
+        FsmTransitionState::None => "Stopped".to_string(),
+        FsmTransitionState::State(s) => ty_to_string(&s.ty)
+    };
+
+    let mut events: Vec<_> = fsm.fsm.events.values().map(|e| e.display_name()).collect();
+    events.sort();
+
+    let regions = fsm.fsm.regions.iter().map(|region| {
+        let region_id = region.region_id;
+        let priority = region.priority;
+        let initial_state = ty_to_string(&region.initial_state);
+
+        let mut states: Vec<_> = region.states.iter().collect();
+        states.sort_by_key(|s| ty_to_string(&s.ty));
+        let states = states.into_iter().map(|state| {
+            let state_id = ty_to_string(&state.ty);
+            let name = state.display_name();
+            let kind = match state.kind {
+                FsmStateKind::Normal => quote! { finny::FsmInfoStateKind::State },
+                FsmStateKind::SubMachine(_) => quote! { finny::FsmInfoStateKind::SubMachine { fsm_id: #state_id } }
+            };
+            let timers = state.timers.iter().map(|t| tokens_to_string(&t.get_ty(&fsm.base)));
+            let deferred_events = state.deferred_events.iter().map(|ty| event_display_name(fsm, ty));
+            let is_final = state.is_final;
+
+            quote! {
+                finny::FsmInfoState {
+                    state_id: #state_id,
+                    name: #name,
+                    kind: #kind,
+                    timers: &[ #( finny::FsmInfoTimer { timer_id: #timers } ),* ],
+                    deferred_events: &[ #( #deferred_events ),* ],
+                    is_final: #is_final
+                }
+            }
+        });
+
+        // the transitions for the same event keep their evaluation order
+        let mut transitions: Vec<_> = region.transitions.iter().map(|transition| {
+            let (event, kind, action) = match &transition.ty {
+                FsmTransitionType::InternalTransition(s) => {
+                    let state_id = state_id(&s.state);
+                    (&s.event, quote! { finny::FsmInfoTransitionKind::InternalTransition { state_id: #state_id } }, &s.action)
+                },
+                FsmTransitionType::SelfTransition(s) => {
+                    let state_id = state_id(&s.state);
+                    (&s.event, quote! { finny::FsmInfoTransitionKind::SelfTransition { state_id: #state_id } }, &s.action)
+                },
+                FsmTransitionType::StateTransition(s) => {
+                    let from_state = state_id(&s.state_from);
+                    let to_state = state_id(&s.state_to);
+                    (&s.event, quote! { finny::FsmInfoTransitionKind::NormalTransition { from_state: #from_state, to_state: #to_state } }, &s.action)
+                }
+            };
+
+            let (event_id, event) = match event {
+                FsmTransitionEvent::Start => (String::new(), quote! { finny::FsmInfoEvent::Start }),
+                FsmTransitionEvent::Stop => (String::new(), quote! { finny::FsmInfoEvent::Stop }),
+                FsmTransitionEvent::Event(ev) => {
+                    let ev = event_display_name(fsm, &ev.ty);
+                    (ev.clone(), quote! { finny::FsmInfoEvent::Event(#ev) })
+                }
+            };
+
+            let guard = match (&action.guard_name, action.guard.as_ref().or(action.guard_async.as_ref())) {
+                (Some(name), _) => quote! { Some(#name) },
+                (None, Some(guard)) => {
+                    let guard = tokens_to_string(guard);
+                    quote! { Some(#guard) }
+                },
+                (None, None) => quote! { None }
+            };
+
+            let guard_expr = match action.guard_expr {
+                Some(ref expr) => {
+                    let expr = to_fsm_info_guard(expr);
+                    quote! { Some(#expr) }
+                },
+                None => quote! { None }
+            };
+
+            let action = match (&action.action_name, action.action.as_ref().or(action.action_async.as_ref())) {
+                (Some(name), _) => quote! { Some(#name) },
+                (None, Some(action)) => {
+                    let action = tokens_to_string(action);
+                    quote! { Some(#action) }
+                },
+                (None, None) => quote! { None }
+            };
+
+            let transition_id = tokens_to_string(&transition.transition_ty);
+
+            (event_id, quote! {
+                finny::FsmInfoTransition {
+                    transition_id: #transition_id,
+                    event: #event,
+                    kind: #kind,
+                    guard: #guard,
+                    guard_expr: #guard_expr,
+                    action: #action
+                }
+            })
+        }).collect();
+        transitions.sort_by(|a, b| a.0.cmp(&b.0));
+        let transitions = transitions.into_iter().map(|(_, t)| t);
+
+        quote! {
+            finny::FsmInfoRegion {
+                region_id: #region_id,
+                priority: #priority,
+                initial_state: #initial_state,
+                states: &[ #( #states ),* ],
+                transitions: &[ #( #transitions ),* ]
+            }
+        }
+    });
+
+    let fsm_id = ty_to_string(&fsm.base.fsm_ty);
+    let context_id = tokens_to_string(&fsm.base.context_ty);
+
+    quote! {
+        finny::FsmInfo {
+            fsm_id: #fsm_id,
+            context_id: #context_id,
+            events: &[ #( #events ),* ],
+            regions: &[ #( #regions ),* ]
+        }
+    }
+}
+
 pub fn generate_fsm_meta(fsm: &FsmFnInput) -> TokenStream {
     let info = to_info(fsm);
 
@@ -132,7 +315,8 @@ pub fn generate_fsm_meta(fsm: &FsmFnInput) -> TokenStream {
         { TokenStream::new() }
         #[cfg(feature="generate_plantuml")]
         {
-            let (plant_uml_str, additional) = crate::meta::plantuml::to_plant_uml(&info).expect("PlantUML syntax generation error!");
+            let plant_uml = crate::meta::plantuml::to_plant_uml(&info).expect("PlantUML syntax generation error!");
+            let regions_count = info.regions.len();
 
             let test_fn_name = crate::utils::to_field_name(&crate::utils::ty_append(&fsm_ty, "_plantuml"));
             
@@ -145,34 +329,42 @@ pub fn generate_fsm_meta(fsm: &FsmFnInput) -> TokenStream {
 
                     let contents = < #fsm_info_ty > :: plantuml();
 
-                    let mut f = fs::File::create(&format!("{}.plantuml", #fsm_ty_name_snake )).unwrap();
+                    // keep the rendered diagram out of the source tree
+                    let path = std::env::temp_dir().join(format!("{}.plantuml", #fsm_ty_name_snake ));
+                    let mut f = fs::File::create(&path).unwrap();
                     f.write_all(contents.as_bytes()).unwrap();
                 }
 
-                #[derive(Default)]
-                pub struct #fsm_info_ty;
-
                 impl #fsm_info_ty {
+                    /// The states and transitions of the FSM, including the regions and submachines, without the diagram's header.
                     pub fn plantuml_inner() -> String {
                         use std::fmt::Write;
 
-                        let mut output = ( #plant_uml_str ).to_string();
+                        let mut output = String::new();
 
-                        #additional
+                        #plant_uml
 
                         output
                     }
 
+                    /// The PlantUML state diagram of the FSM. Machines with multiple regions are wrapped
+                    /// into a composite state, as the regions have to be rendered as concurrent states.
                     pub fn plantuml() -> String {
                         use std::fmt::Write;
 
                         let mut output = String::new();
 
-                        writeln!(&mut output, "@startuml {}", #fsm_ty_name );
+                        let _ = writeln!(&mut output, "@startuml {}", #fsm_ty_name );
 
-                        writeln!(&mut output, "{}", Self::plantuml_inner());
+                        if #regions_count > 1 {
+                            let _ = writeln!(&mut output, "state {} {{", #fsm_ty_name );
+                            let _ = writeln!(&mut output, "{}", Self::plantuml_inner());
+                            let _ = writeln!(&mut output, "}}");
+                        } else {
+                            let _ = writeln!(&mut output, "{}", Self::plantuml_inner());
+                        }
 
-                        writeln!(&mut output, "@enduml");
+                        let _ = writeln!(&mut output, "@enduml");
 
                         output
                     }
@@ -181,31 +373,151 @@ pub fn generate_fsm_meta(fsm: &FsmFnInput) -> TokenStream {
         }
     };
 
-    
-    
+    let dot_build = {
+        #[cfg(not(feature="generate_dot"))]
+        { TokenStream::new() }
+        #[cfg(feature="generate_dot")]
+        {
+            let (dot_str, additional) = crate::meta::dot::to_dot(&info).expect("DOT syntax generation error!");
 
+            quote! {
+                impl #fsm_info_ty {
+                    pub fn dot_inner() -> String {
+                        use std::fmt::Write;
 
+                        let mut output = ( #dot_str ).to_string();
 
-    /*
-    quote! {
+                        #additional
 
-        #[derive(Default)]
-        pub struct #fsm_meta_ty;
+                        output
+                    }
+
+                    /// The transition graph of the FSM in the Graphviz DOT syntax.
+                    pub fn dot() -> String {
+                        use std::fmt::Write;
+
+                        let mut output = String::new();
+
+                        let _ = writeln!(&mut output, "digraph \"{}\" {{", #fsm_ty_name );
+                        let _ = write!(&mut output, "{}", Self::dot_inner());
+                        let _ = writeln!(&mut output, "}}");
 
-        impl finny::FsmMetaJson for #fsm_info_ty {
-            fn get_json_meta() -> &'static str {
-                #json
+                        output
+                    }
+                }
             }
+        }
+    };
 
-            fn get_plantuml_meta() -> &'static str {
+    let xstate_build = {
+        #[cfg(not(feature="generate_xstate"))]
+        { TokenStream::new() }
+        #[cfg(feature="generate_xstate")]
+        {
+            let (xstate_str, additional) = crate::meta::xstate::to_xstate(&info).expect("xstate JSON generation error!");
+
+            quote! {
+                impl #fsm_info_ty {
+                    /// The members of the machine's xstate node, without the enclosing braces.
+                    pub fn xstate_inner() -> String {
+                        let mut output = ( #xstate_str ).to_string();
 
+                        #additional
+
+                        output
+                    }
+
+                    /// The machine's definition in the xstate JSON machine format, for the Stately editor
+                    /// and visualizer. The guards are named after their functions, otherwise after their transitions.
+                    pub fn xstate() -> String {
+                        format!("{{\"id\":\"{}\",{}}}", #fsm_ty_name, Self::xstate_inner())
+                    }
+                }
             }
         }
+    };
 
-    }
-    */
+    let mermaid_build = {
+        #[cfg(not(feature="generate_mermaid"))]
+        { TokenStream::new() }
+        #[cfg(feature="generate_mermaid")]
+        {
+            let (mermaid_str, additional) = crate::meta::mermaid::to_mermaid(&info).expect("Mermaid syntax generation error!");
+            let regions_count = info.regions.len();
+
+            quote! {
+                impl #fsm_info_ty {
+                    /// The states and transitions of the FSM, including the regions and submachines, without the diagram's header.
+                    pub fn mermaid_inner() -> String {
+                        let mut output = ( #mermaid_str ).to_string();
+
+                        #additional
+
+                        output
+                    }
+
+                    /// The Mermaid `stateDiagram-v2` of the FSM. Machines with multiple regions are wrapped
+                    /// into a composite state, as the regions can only be separated within one.
+                    pub fn mermaid() -> String {
+                        use std::fmt::Write;
+
+                        let mut output = String::new();
+
+                        let _ = writeln!(&mut output, "stateDiagram-v2");
+
+                        if #regions_count > 1 {
+                            let _ = writeln!(&mut output, "state \"{}\" as {} {{", #fsm_ty_name, #fsm_ty_name );
+                            let _ = write!(&mut output, "{}", Self::mermaid_inner());
+                            let _ = writeln!(&mut output, "}}");
+                        } else {
+                            let _ = write!(&mut output, "{}", Self::mermaid_inner());
+                        }
+
+                        output
+                    }
+                }
+
+                impl #fsm_generics_impl #fsm_ty #fsm_generics_type #fsm_generics_where {
+                    /// The Mermaid `stateDiagram-v2` of this machine, to be rendered inline in the Markdown documents.
+                    pub fn to_mermaid() -> String {
+                        < #fsm_info_ty > :: mermaid()
+                    }
+                }
+            }
+        }
+    };
+
+    let info_struct = if cfg!(any(feature="generate_plantuml", feature="generate_dot", feature="generate_xstate", feature="generate_mermaid")) {
+        quote! {
+            #[derive(Default)]
+            pub struct #fsm_info_ty;
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let fsm_info = generate_fsm_info(fsm);
+
+    let fsm_info_build = quote! {
+        impl #fsm_generics_impl #fsm_ty #fsm_generics_type #fsm_generics_where {
+            /// The static description of the states, events, transitions and regions of this machine.
+            pub fn fsm_info() -> &'static finny::FsmInfo {
+                &#fsm_info
+            }
+        }
+    };
 
     quote! {
+        #info_struct
+
+        #fsm_info_build
+
         #plant_uml_test_build
+
+        #dot_build
+
+        #xstate_build
+
+        #mermaid_build
     }
 }
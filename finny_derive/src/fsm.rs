@@ -28,6 +28,10 @@ impl FsmTypes {
         ty_append(&self.fsm_no_generics, "Events")
     }
 
+    pub fn get_fsm_borrowed_events_ty(&self) -> syn::Type {
+        ty_append(&self.fsm_no_generics, "BorrowedEvents")
+    }
+
     pub fn get_fsm_timers_ty(&self) -> syn::Type {
         ty_append(&self.fsm_no_generics, "Timers")
     }
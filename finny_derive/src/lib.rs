@@ -10,10 +10,12 @@ use proc_macro::TokenStream;
 
 mod codegen;
 mod codegen_meta;
+mod codegen_ffi;
 mod meta;
 mod parse;
 mod parse_blocks;
 mod parse_fsm;
+mod scxml;
 mod utils;
 mod validation;
 mod fsm;
@@ -34,3 +36,13 @@ pub fn finny_fsm(attr: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into()
     }
 }
+
+#[proc_macro]
+pub fn finny_scxml(input: TokenStream) -> TokenStream {
+    let parsed = syn::parse_macro_input!(input as scxml::ScxmlInput);
+
+    match scxml::generate_scxml_fsm(parsed) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
@@ -0,0 +1,92 @@
+use proc_macro2::TokenStream;
+use quote::{quote, TokenStreamExt};
+
+use super::FinnyFsm;
+use std::fmt::Write;
+
+/// Renders the FSM's regions, states and transitions as the body of a Graphviz `digraph`. Submachines are
+/// rendered as clusters, filled in at runtime from the submachine's own info struct.
+pub fn to_dot(fsm: &FinnyFsm) -> Result<(String, TokenStream), std::fmt::Error> {
+    let mut output = String::new();
+    let mut subs = TokenStream::new();
+
+    let node = |state_id: &str| format!("\"{}::{}\"", fsm.fsm_id, state_id);
+
+    let mut regions: Vec<_> = fsm.regions.values().collect();
+    regions.sort_by_key(|r| r.region_id);
+
+    for region in regions {
+        let start_node = format!("\"{}::Start{}\"", fsm.fsm_id, region.region_id);
+        writeln!(&mut output, "{} [shape=point];", start_node)?;
+
+        let mut states: Vec<_> = region.states.values().collect();
+        states.sort_by_key(|s| s.get_state_id());
+
+        for state in states {
+            match state {
+                super::FinnyStateKind::Stopped => {
+
+                }
+                super::FinnyStateKind::State(state) => {
+                    let mut label = state.name.clone();
+                    for timer in &state.timers {
+                        write!(&mut label, "\\nTimer {}", timer.timer_id)?;
+                    }
+
+                    writeln!(&mut output, "{} [label=\"{}\"];", node(&state.state_id), label)?;
+                },
+                super::FinnyStateKind::SubMachine(sub_id) => {
+                    writeln!(&mut output, "{} [label=\"{}\", shape=box3d];", node(sub_id), sub_id)?;
+                    writeln!(&mut output, "{} -> \"{}::Start0\" [style=dotted, arrowhead=none];", node(sub_id), sub_id)?;
+
+                    let p = syn::parse_str::<syn::Type>(&format!("{}Info", sub_id)).unwrap();
+                    let cluster = format!("cluster_{}", sub_id);
+
+                    subs.append_all(quote! {
+                        let _ = writeln!(&mut output, "subgraph \"{}\" {{", #cluster);
+                        let _ = writeln!(&mut output, "label=\"{}\";", #sub_id);
+                        let _ = write!(&mut output, "{}", < #p > :: dot_inner() );
+                        let _ = writeln!(&mut output, "}}");
+                    });
+                }
+            }
+        }
+
+        let mut transitions: Vec<_> = region.transitions.values().collect();
+        transitions.sort_by(|a, b| a.transition_id.cmp(&b.transition_id));
+
+        for transition in transitions {
+            let mut event = match transition.event {
+                super::FinnyEvent::Start => "Start".to_string(),
+                super::FinnyEvent::Stop => "Stop".to_string(),
+                super::FinnyEvent::Event(ref ev) => ev.clone()
+            };
+
+            if let Some(guard) = transition.guard_label() {
+                event.push_str(&format!(" {}", guard));
+            }
+            if let Some(ref action) = transition.action {
+                event.push_str(&format!(" / {}", action));
+            }
+
+            match &transition.transition {
+                super::FinnyTransitionKind::SelfTransition { state_id } => {
+                    writeln!(&mut output, "{state} -> {state} [label=\"{event} (Self)\"];", state = node(state_id), event = event)?;
+                }
+                super::FinnyTransitionKind::InternalTransition { state_id } => {
+                    writeln!(&mut output, "{state} -> {state} [label=\"{event} (Internal)\", style=dashed];", state = node(state_id), event = event)?;
+                }
+                super::FinnyTransitionKind::NormalTransition(t) => {
+                    let state_from = match t.from_state.as_str() {
+                        "Stopped" => start_node.clone(),
+                        _ => node(&t.from_state)
+                    };
+
+                    writeln!(&mut output, "{state_from} -> {state_to} [label=\"{event}\"];", state_from = state_from, state_to = node(&t.to_state), event = event)?;
+                }
+            }
+        }
+    }
+
+    Ok((output, subs))
+}
@@ -0,0 +1,94 @@
+use proc_macro2::TokenStream;
+use quote::{quote, TokenStreamExt};
+
+use super::{FinnyEvent, FinnyFsm, FinnyStateKind, FinnyTransitionKind};
+use std::fmt::Write;
+
+/// Renders the FSM's regions, states and transitions as the body of a Mermaid `stateDiagram-v2`. The regions
+/// are separated with `--`, the submachines are composite states with a placeholder for their body, replaced
+/// at runtime with the submachine's own diagram.
+pub fn to_mermaid(fsm: &FinnyFsm) -> Result<(String, TokenStream), std::fmt::Error> {
+    let mut output = String::new();
+    let mut subs = TokenStream::new();
+
+    // the state ids are global in the diagram, prefix them with the machine
+    let node = |state_id: &str| format!("{}_{}", fsm.fsm_id, state_id);
+
+    let mut regions: Vec<_> = fsm.regions.values().collect();
+    regions.sort_by_key(|r| r.region_id);
+
+    for (i, region) in regions.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(&mut output, "--")?;
+        }
+
+        let mut states: Vec<_> = region.states.values().collect();
+        states.sort_by_key(|s| s.get_state_id());
+
+        for state in states {
+            match state {
+                FinnyStateKind::Stopped => (),
+                FinnyStateKind::State(state) => {
+                    writeln!(&mut output, "state \"{}\" as {}", state.name, node(&state.state_id))?;
+                    for timer in &state.timers {
+                        writeln!(&mut output, "{} : Timer {}", node(&state.state_id), timer.timer_id)?;
+                    }
+                    if state.is_final {
+                        writeln!(&mut output, "{} --> [*]", node(&state.state_id))?;
+                    }
+                },
+                FinnyStateKind::SubMachine(sub_id) => {
+                    let placeholder = format!("%% finny_sub {}", sub_id);
+                    writeln!(&mut output, "state \"{}\" as {} {{", sub_id, node(sub_id))?;
+                    writeln!(&mut output, "{}", placeholder)?;
+                    writeln!(&mut output, "}}")?;
+
+                    let p = syn::parse_str::<syn::Type>(&format!("{}Info", sub_id)).unwrap();
+                    subs.append_all(quote! {
+                        output = output.replace(#placeholder, < #p > :: mermaid_inner().trim_end());
+                    });
+                }
+            }
+        }
+
+        let mut transitions: Vec<_> = region.transitions.values().collect();
+        transitions.sort_by_key(|t| t.order);
+
+        for transition in transitions {
+            let mut event = match transition.event {
+                FinnyEvent::Start => "Start".to_string(),
+                FinnyEvent::Stop => "Stop".to_string(),
+                FinnyEvent::Event(ref ev) => ev.clone()
+            };
+
+            if let Some(guard) = transition.guard_label() {
+                event.push_str(&format!(" {}", guard));
+            }
+            if let Some(ref action) = transition.action {
+                event.push_str(&format!(" / {}", action));
+            }
+
+            match &transition.transition {
+                FinnyTransitionKind::SelfTransition { state_id } => {
+                    writeln!(&mut output, "{state} --> {state} : {event} (Self)", state = node(state_id), event = event)?;
+                }
+                FinnyTransitionKind::InternalTransition { state_id } => {
+                    writeln!(&mut output, "{state} --> {state} : {event} (Internal)", state = node(state_id), event = event)?;
+                }
+                FinnyTransitionKind::NormalTransition(t) if t.from_state == "Stopped" && !transition.guarded => {
+                    writeln!(&mut output, "[*] --> {}", node(&t.to_state))?;
+                }
+                FinnyTransitionKind::NormalTransition(t) => {
+                    let state_from = match t.from_state.as_str() {
+                        "Stopped" => "[*]".to_string(),
+                        _ => node(&t.from_state)
+                    };
+
+                    writeln!(&mut output, "{} --> {} : {}", state_from, node(&t.to_state), event)?;
+                }
+            }
+        }
+    }
+
+    Ok((output, subs))
+}
@@ -4,6 +4,9 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
 pub mod plantuml;
+pub mod dot;
+pub mod xstate;
+pub mod mermaid;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FinnyFsm {
@@ -41,14 +44,68 @@ impl FinnyStateKind {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FinnyState {
     pub state_id: String,
-    pub timers: Vec<FinnyTimer>
+    /// The label of the state in the diagrams, declared with `named`.
+    #[serde(default)]
+    pub name: String,
+    pub timers: Vec<FinnyTimer>,
+    pub is_final: bool
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FinnyTransition {
     pub transition_id: String,
     pub event: FinnyEvent,
-    pub transition: FinnyTransitionKind
+    pub transition: FinnyTransitionKind,
+    pub guarded: bool,
+    /// The name of the guard's function, the closures aren't named.
+    #[serde(default)]
+    pub guard: Option<String>,
+    /// The terms of a combined guard.
+    #[serde(default)]
+    pub guard_expr: Option<FinnyGuard>,
+    /// The name of the action's function.
+    #[serde(default)]
+    pub action: Option<String>,
+    /// The position of the transition in the region, the transitions for the same event are evaluated in this order.
+    pub order: usize
+}
+
+impl FinnyTransition {
+    /// The guard's label in the diagrams, the terms of a combined guard are labeled separately.
+    pub fn guard_label(&self) -> Option<String> {
+        match (&self.guard_expr, &self.guard) {
+            (Some(expr), _) => Some(expr.label()),
+            (None, Some(guard)) => Some(format!("[{}]", guard)),
+            (None, None) if self.guarded => Some("[guard]".to_string()),
+            (None, None) => None
+        }
+    }
+}
+
+/// A guard combined with `and_guard`, `or_guard` and `not`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum FinnyGuard {
+    Guard(String),
+    Not(Box<FinnyGuard>),
+    And(Box<FinnyGuard>, Box<FinnyGuard>),
+    Or(Box<FinnyGuard>, Box<FinnyGuard>)
+}
+
+impl FinnyGuard {
+    /// The terms in brackets, joined by the operators, like `![overloaded] && [door_closed]`.
+    pub fn label(&self) -> String {
+        let operand = |g: &FinnyGuard| match g {
+            FinnyGuard::And(..) | FinnyGuard::Or(..) => format!("({})", g.label()),
+            _ => g.label()
+        };
+
+        match self {
+            FinnyGuard::Guard(name) => format!("[{}]", name),
+            FinnyGuard::Not(g) => format!("!{}", operand(g)),
+            FinnyGuard::And(a, b) => format!("{} && {}", operand(a), operand(b)),
+            FinnyGuard::Or(a, b) => format!("{} || {}", operand(a), operand(b))
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
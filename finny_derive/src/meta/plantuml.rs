@@ -1,24 +1,40 @@
 use proc_macro2:: TokenStream;
 use quote::{quote, TokenStreamExt};
-use syn::{PathSegment, TypePath, parse::Parse};
 
 use super::FinnyFsm;
 use std::fmt::Write;
 
+/// Generates the statements that write the PlantUML state diagram into the `output` string. Regions
+/// are separated as concurrent states and submachines are rendered as composite states.
+pub fn to_plant_uml(fsm: &FinnyFsm) -> Result<TokenStream, std::fmt::Error> {
+    let mut q = TokenStream::new();
 
-pub fn to_plant_uml(fsm: &FinnyFsm) -> Result<(String, TokenStream), std::fmt::Error> {
-    let mut output = String::new();
-    let mut subs = TokenStream::new();
+    let mut regions: Vec<_> = fsm.regions.values().collect();
+    regions.sort_by_key(|r| r.region_id);
 
-    for region in fsm.regions.values() {
-        for state in region.states.values() {
+    for (i, region) in regions.iter().enumerate() {
+        let mut output = String::new();
+        let mut subs = TokenStream::new();
+
+        if i > 0 {
+            writeln!(&mut output, "--")?;
+        }
+
+        let mut states: Vec<_> = region.states.values().collect();
+        states.sort_by_key(|s| s.get_state_id());
+
+        for state in states {
             match state {
                 super::FinnyStateKind::Stopped => {
 
                 }
                 super::FinnyStateKind::State(state) => {
 
-                    writeln!(&mut output, "state {} {{", state.state_id)?;
+                    if state.name == state.state_id {
+                        writeln!(&mut output, "state {} {{", state.state_id)?;
+                    } else {
+                        writeln!(&mut output, "state \"{}\" as {} {{", state.name, state.state_id)?;
+                    }
                     writeln!(&mut output, "}}")?;
 
                     for timer in &state.timers {
@@ -31,31 +47,42 @@ pub fn to_plant_uml(fsm: &FinnyFsm) -> Result<(String, TokenStream), std::fmt::E
                     
 
                     subs.append_all(quote! {
-                        writeln!(&mut output, "state {} {{", #sub_id);
-                        writeln!(&mut output, "{}", < #p > :: plantuml_inner() );
-                        writeln!(&mut output, "}}");
+                        let _ = writeln!(&mut output, "state {} {{", #sub_id);
+                        let _ = writeln!(&mut output, "{}", < #p > :: plantuml_inner() );
+                        let _ = writeln!(&mut output, "}}");
                     });
 
                 }
             }
         }
 
-        for transition in region.transitions.values() {
+        let mut transitions_output = String::new();
+
+        let mut transitions: Vec<_> = region.transitions.values().collect();
+        transitions.sort_by(|a, b| a.transition_id.cmp(&b.transition_id));
 
-            let event = match transition.event {
+        for transition in transitions {
+
+            let mut event = match transition.event {
                 super::FinnyEvent::Start => "Start".to_string(),
                 super::FinnyEvent::Stop => "Stop".to_string(),
                 super::FinnyEvent::Event(ref ev) => ev.clone()
             };
 
+            if let Some(guard) = transition.guard_label() {
+                event.push_str(&format!(" {}", guard));
+            }
+
+            let output = &mut transitions_output;
+
             match &transition.transition {
                 super::FinnyTransitionKind::SelfTransition { state_id } => {
-                    writeln!(&mut output, "{state} --> {state} : {event} (Self)", state = state_id, event = event)?;
-                    writeln!(&mut output, "note on link: {}", transition.transition_id)?;
+                    writeln!(output, "{state} --> {state} : {event} (Self)", state = state_id, event = event)?;
+                    writeln!(output, "note on link: {}", transition.transition_id)?;
                 }
                 super::FinnyTransitionKind::InternalTransition { state_id } => {
-                    writeln!(&mut output, "{state} --> {state} : {event} (Internal)", state = state_id, event = event)?;
-                    writeln!(&mut output, "note on link: {}", transition.transition_id)?;
+                    writeln!(output, "{state} --> {state} : {event} (Internal)", state = state_id, event = event)?;
+                    writeln!(output, "note on link: {}", transition.transition_id)?;
                 }
                 super::FinnyTransitionKind::NormalTransition(t) => {
                     let state_from = match t.from_state.as_str() {
@@ -63,12 +90,18 @@ pub fn to_plant_uml(fsm: &FinnyFsm) -> Result<(String, TokenStream), std::fmt::E
                         _ => &t.from_state
                     };
 
-                    writeln!(&mut output, "{state_from} --> {state_to} : {event}", state_from = state_from, state_to = t.to_state, event = event)?;
-                    writeln!(&mut output, "note on link: {}", transition.transition_id)?;
+                    writeln!(output, "{state_from} --> {state_to} : {event}", state_from = state_from, state_to = t.to_state, event = event)?;
+                    writeln!(output, "note on link: {}", transition.transition_id)?;
                 }
             }
         }
+
+        q.append_all(quote! {
+            output.push_str( #output );
+            #subs
+            output.push_str( #transitions_output );
+        });
     }
 
-    Ok((output, subs))
-}
\ No newline at end of file
+    Ok(q)
+}
@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream;
+use quote::{quote, TokenStreamExt};
+use serde_json::{Map, Value, json};
+
+use super::{FinnyEvent, FinnyFsm, FinnyRegion, FinnyStateKind, FinnyTransitionKind};
+
+/// Renders the members of the machine's node in the xstate JSON machine format, the configuration syntax of
+/// xstate 4. Multiple regions become a parallel node. The nodes of the submachines are marked with a placeholder
+/// member, replaced at runtime with the submachine's own members.
+pub fn to_xstate(fsm: &FinnyFsm) -> Result<(String, TokenStream), serde_json::Error> {
+    let mut subs = TokenStream::new();
+
+    let mut regions: Vec<_> = fsm.regions.values().collect();
+    regions.sort_by_key(|r| r.region_id);
+
+    let node = match regions.as_slice() {
+        [region] => region_node(region, &mut subs),
+        regions => {
+            let mut states = Map::new();
+            for region in regions {
+                states.insert(format!("Region{}", region.region_id), Value::Object(region_node(region, &mut subs)));
+            }
+
+            let mut node = Map::new();
+            node.insert("type".into(), json!("parallel"));
+            node.insert("states".into(), Value::Object(states));
+            node
+        }
+    };
+
+    let output = serde_json::to_string(&Value::Object(node))?;
+    let output = output[1..output.len() - 1].to_string();
+
+    Ok((output, subs))
+}
+
+fn region_node(region: &FinnyRegion, subs: &mut TokenStream) -> Map<String, Value> {
+    let mut node = Map::new();
+
+    let mut transitions: Vec<_> = region.transitions.values().collect();
+    transitions.sort_by_key(|t| t.order);
+
+    let initial = transitions.iter().find_map(|t| match (&t.event, &t.transition) {
+        (FinnyEvent::Start, FinnyTransitionKind::NormalTransition(t)) if t.from_state == "Stopped" => Some(t.to_state.clone()),
+        _ => None
+    });
+    if let Some(initial) = initial {
+        node.insert("initial".into(), json!(initial));
+    }
+
+    let mut states = Map::new();
+    for state in region.states.values() {
+        let state_id = state.get_state_id();
+        let mut state_node = Map::new();
+
+        match state {
+            FinnyStateKind::Stopped => continue,
+            FinnyStateKind::State(s) => {
+                if s.is_final {
+                    state_node.insert("type".into(), json!("final"));
+                }
+                if !s.timers.is_empty() {
+                    let timers: Vec<_> = s.timers.iter().map(|t| t.timer_id.clone()).collect();
+                    state_node.insert("meta".into(), json!({ "timers": timers }));
+                }
+            },
+            FinnyStateKind::SubMachine(sub_id) => {
+                let placeholder = format!("__finny_sub_{}", sub_id);
+                let p = syn::parse_str::<syn::Type>(&format!("{}Info", sub_id)).unwrap();
+                let replaced = format!("\"{}\":null", placeholder);
+
+                subs.append_all(quote! {
+                    output = output.replace(#replaced, &< #p > :: xstate_inner());
+                });
+
+                state_node.insert(placeholder, Value::Null);
+            }
+        }
+
+        let mut on = Map::new();
+        for transition in &transitions {
+            let (from, target) = match &transition.transition {
+                FinnyTransitionKind::SelfTransition { state_id } => (state_id, Some(state_id.clone())),
+                FinnyTransitionKind::InternalTransition { state_id } => (state_id, None),
+                FinnyTransitionKind::NormalTransition(t) => (&t.from_state, Some(t.to_state.clone()))
+            };
+
+            if *from != state_id {
+                continue;
+            }
+
+            let event = match transition.event {
+                FinnyEvent::Start => continue,
+                FinnyEvent::Stop => "Fsm::Stop".to_string(),
+                FinnyEvent::Event(ref ev) => ev.clone()
+            };
+
+            let mut t = Map::new();
+            match target {
+                Some(target) => { t.insert("target".into(), json!(target)); },
+                None => { t.insert("internal".into(), json!(true)); }
+            }
+            if transition.guarded {
+                t.insert("cond".into(), json!(transition.guard.as_ref().unwrap_or(&transition.transition_id)));
+            }
+            if let Some(ref action) = transition.action {
+                t.insert("actions".into(), json!([action]));
+            }
+
+            if let Value::Array(event_transitions) = on.entry(event).or_insert(Value::Array(vec![])) {
+                event_transitions.push(Value::Object(t));
+            }
+        }
+
+        if !on.is_empty() {
+            state_node.insert("on".into(), Value::Object(on));
+        }
+
+        states.insert(state_id, Value::Object(state_node));
+    }
+
+    node.insert("states".into(), Value::Object(states));
+    node
+}
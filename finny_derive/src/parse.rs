@@ -1,9 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
 use proc_macro2::{Span, TokenStream};
+use quote::quote;
 use syn::{Error, Expr, ExprMethodCall, GenericArgument, ItemFn, parse::{self, Parse, ParseStream}, spanned::Spanned};
 
-use crate::{parse_blocks::{FsmBlock, decode_blocks, get_generics, get_method_receiver_ident}, parse_fsm::{FsmCodegenOptions, FsmParser}, utils::{assert_no_generics, get_closure, to_field_name, ty_append}};
+use crate::{parse_blocks::{FsmBlock, decode_blocks, get_generics, get_method_receiver_ident}, parse_fsm::{FsmCodegenOptions, FsmParser}, utils::{assert_no_generics, get_closure, remap_closure_inputs, to_field_name, ty_append}};
 
 
 pub struct FsmFnInput {
@@ -131,7 +132,20 @@ pub struct FsmDeclarations {
     pub initial_states: Vec<syn::Type>,
     pub states: HashMap<syn::Type, FsmState>,
     pub events: HashMap<syn::Type, FsmEvent>,
-    pub transitions: Vec<FsmTransition>
+    pub transitions: Vec<FsmTransition>,
+    pub unhandled_event: FsmUnhandledEvent,
+    pub error_state: Option<syn::Type>,
+    pub resources: Option<syn::Type>,
+    pub outputs: Option<syn::Type>,
+    /// The priorities of the regions, by their initial states.
+    pub region_priorities: Vec<(syn::Type, u8)>
+}
+
+/// The handling of the events without a transition in any of the regions.
+#[derive(Debug, Clone, Default)]
+pub struct FsmUnhandledEvent {
+    pub handler: Option<syn::ExprClosure>,
+    pub policy: Option<syn::Expr>
 }
 
 #[derive(Debug)]
@@ -139,12 +153,38 @@ pub struct ValidatedFsm {
     pub codegen_options: FsmCodegenOptions,
     pub regions: Vec<FsmRegion>,
     pub states: HashMap<syn::Type, FsmState>,
-    pub events: HashMap<syn::Type, FsmEvent>
+    pub events: HashMap<syn::Type, FsmEvent>,
+    pub unhandled_event: FsmUnhandledEvent,
+    /// The state entered when a fallible guard or action fails.
+    pub error_state: Option<syn::Type>,
+    /// The external resources lent to the guards and actions for a dispatch, `()` if not declared.
+    pub resources: Option<syn::Type>,
+    /// The commands that the actions produce for the caller, `()` if not declared.
+    pub outputs: Option<syn::Type>
+}
+
+impl ValidatedFsm {
+    /// The regions in the order in which they see the dispatched events, by their priority and then
+    /// by their declaration.
+    pub fn regions_by_priority(&self) -> Vec<&FsmRegion> {
+        let mut regions: Vec<_> = self.regions.iter().collect();
+        regions.sort_by_key(|r| core::cmp::Reverse(r.priority));
+        regions
+    }
+
+    /// The state and the region that it belongs to.
+    pub fn find_state(&self, ty: &syn::Type) -> syn::Result<(&FsmRegion, &FsmState)> {
+        self.regions.iter()
+            .find_map(|r| r.states.iter().find(|s| &s.ty == ty).map(|s| (r, s)))
+            .ok_or_else(|| syn::Error::new(ty.span(), "State not found."))
+    }
 }
 
 #[derive(Debug)]
 pub struct FsmRegion {
     pub region_id: usize,
+    /// The regions with a higher priority see the dispatched events first.
+    pub priority: u8,
     pub initial_state: syn::Type,
     pub transitions: Vec<FsmTransition>,
     pub states: Vec<FsmState>
@@ -209,6 +249,13 @@ impl FsmTransitionType {
 
         ret
     }
+
+    pub fn get_action(&self) -> &EventGuardAction {
+        match self {
+            FsmTransitionType::InternalTransition(s) | FsmTransitionType::SelfTransition(s) => &s.action,
+            FsmTransitionType::StateTransition(s) => &s.action
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -234,7 +281,24 @@ pub enum FsmStateKind {
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct FsmSubMachineOptions {
-    pub context_constructor: Option<syn::ExprClosure>
+    pub context_constructor: Option<syn::ExprClosure>,
+    /// Creates the event for the parent machine, once the submachine completes.
+    pub completion_event: Option<syn::ExprClosure>,
+    /// Create the events for the parent machine, once the submachine enters their states.
+    pub state_events: Vec<FsmSubMachineStateEvent>,
+    /// The parent's events that are converted into the submachine's events and dispatched to it.
+    pub mapped_events: Vec<(syn::Type, syn::ExprClosure)>,
+    /// The submachine's events that are converted into the parent's events once the submachine enqueues them.
+    pub lifted_events: Vec<(syn::Type, syn::ExprClosure)>
+}
+
+/// Notifies the parent machine that the submachine entered a state, declared with `on_exit_point` or `on_child_state`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsmSubMachineStateEvent {
+    pub state: syn::Type,
+    pub closure: syn::ExprClosure,
+    /// The closure also receives the entered state.
+    pub with_state: bool
 }
 
 #[derive(Debug, Clone)]
@@ -244,7 +308,45 @@ pub struct FsmState {
     pub state_storage_field: syn::Ident,
     pub on_entry_closure: Option<syn::ExprClosure>,
     pub on_exit_closure: Option<syn::ExprClosure>,
-    pub timers: Vec<FsmTimer>
+    pub on_entry_async_closure: Option<syn::ExprClosure>,
+    pub on_exit_async_closure: Option<syn::ExprClosure>,
+    pub on_entry_event_closure: Option<syn::ExprClosure>,
+    pub on_exit_event_closure: Option<syn::ExprClosure>,
+    pub timers: Vec<FsmTimer>,
+    pub history: FsmStateHistory,
+    pub deferred_events: Vec<syn::Type>,
+    pub is_final: bool,
+    /// The parent machine can enter this submachine at this state, instead of the initial state.
+    pub is_entry_point: bool,
+    /// The id declared with `fsm.state_id`.
+    pub id: Option<u32>,
+    /// The name declared with `named`.
+    pub display_name: Option<String>
+}
+
+impl FsmState {
+    /// The name of the state, without its type arguments.
+    pub fn name(&self) -> String {
+        crate::utils::tokens_to_string(&crate::utils::strip_generics(self.ty.clone()))
+    }
+
+    /// The declared id, or the hash of the name.
+    pub fn stable_id(&self) -> u32 {
+        self.id.unwrap_or_else(|| crate::utils::stable_id(&self.name()))
+    }
+
+    /// The declared name for the inspection and the diagrams, or the name of the type.
+    pub fn display_name(&self) -> String {
+        self.display_name.clone().unwrap_or_else(|| self.name())
+    }
+}
+
+/// What happens with the previously active states of a submachine when it is re-entered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FsmStateHistory {
+    None,
+    Shallow,
+    Deep
 }
 
 #[derive(Debug, Clone)]
@@ -272,24 +374,191 @@ impl FsmTimer {
 #[derive(Debug, Clone)]
 pub struct FsmEvent {
     pub ty: syn::Type,
-    pub transitions: Vec<FsmEventTransition>
+    pub transitions: Vec<FsmEventTransition>,
+    pub priority: Option<syn::Expr>,
+    pub trace_context: Option<syn::ExprClosure>,
+    /// The id declared with `fsm.event_id`.
+    pub id: Option<u32>,
+    /// The name declared with `fsm.event_name`.
+    pub display_name: Option<String>
+}
+
+impl FsmEvent {
+    /// The event borrows its data, so it can only be dispatched directly and never queued.
+    pub fn is_borrowed(&self) -> bool {
+        crate::utils::is_borrowed_ty(&self.ty)
+    }
+
+    /// The variant of the events enum.
+    pub fn variant(&self) -> syn::Ident {
+        crate::utils::event_variant(&self.ty)
+    }
+
+    pub fn name(&self) -> String {
+        crate::utils::event_name(&self.ty)
+    }
+
+    /// The declared id, or the hash of the name.
+    pub fn stable_id(&self) -> u32 {
+        self.id.unwrap_or_else(|| crate::utils::stable_id(&self.name()))
+    }
+
+    /// The declared name for the inspection and the diagrams, or the name of the type.
+    pub fn display_name(&self) -> String {
+        self.display_name.clone().unwrap_or_else(|| self.name())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum FsmEventTransition {
     /// A transition from one state to another.
-    State(syn::Type, syn::Type, EventGuardAction),
+    State(syn::Type, syn::Type, EventGuardAction, Span),
     /// Triggers the state's exit/enter actions
-    InternalTransition(syn::Type, EventGuardAction),
+    InternalTransition(syn::Type, EventGuardAction, Span),
     /// Triggers the state's exit/enter actions
-    SelfTransition(syn::Type, EventGuardAction)
+    SelfTransition(syn::Type, EventGuardAction, Span)
+}
+
+impl FsmEventTransition {
+    pub fn get_state_from(&self) -> &syn::Type {
+        match self {
+            FsmEventTransition::State(from, _, _, _) |
+            FsmEventTransition::InternalTransition(from, _, _) |
+            FsmEventTransition::SelfTransition(from, _, _) => from
+        }
+    }
+
+    pub fn get_action(&self) -> &EventGuardAction {
+        match self {
+            FsmEventTransition::State(_, _, action, _) |
+            FsmEventTransition::InternalTransition(_, action, _) |
+            FsmEventTransition::SelfTransition(_, action, _) => action
+        }
+    }
+
+    /// The same transition, from a different state.
+    pub fn with_state_from(&self, state: &syn::Type) -> Self {
+        match self {
+            FsmEventTransition::State(_, to, action, span) => FsmEventTransition::State(state.clone(), to.clone(), action.clone(), *span),
+            FsmEventTransition::InternalTransition(_, action, span) => FsmEventTransition::InternalTransition(state.clone(), action.clone(), *span),
+            FsmEventTransition::SelfTransition(_, action, span) => FsmEventTransition::SelfTransition(state.clone(), action.clone(), *span)
+        }
+    }
+
+    /// The builder call that declared the transition.
+    pub fn get_span(&self) -> Span {
+        match self {
+            FsmEventTransition::State(_, _, _, span) |
+            FsmEventTransition::InternalTransition(_, _, span) |
+            FsmEventTransition::SelfTransition(_, _, span) => *span
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct EventGuardAction{
     pub guard: Option<syn::ExprClosure>,
     pub action: Option<syn::ExprClosure>,
-    pub type_hint: Option<syn::Type>
+    pub guard_async: Option<syn::ExprClosure>,
+    pub action_async: Option<syn::ExprClosure>,
+    /// The guard was declared with `try_guard` and returns a result.
+    pub guard_fallible: bool,
+    /// The action was declared with `try_action` and returns a result.
+    pub action_fallible: bool,
+    /// The name of the function that was given as the guard, shared with the other transitions.
+    pub guard_name: Option<String>,
+    /// The terms of a guard combined with `and_guard`, `or_guard` and `not`. The combined `guard` is kept for
+    /// the dispatch without inspection.
+    pub guard_expr: Option<FsmGuardExpr>,
+    /// The name of the function that was given as the action.
+    pub action_name: Option<String>,
+    pub type_hint: Option<syn::Type>,
+    /// The state of the target submachine that is entered instead of its initial state.
+    pub entry_point: Option<syn::Type>,
+    /// The states of the other regions that are entered along with the target state.
+    pub forks: Vec<syn::Type>,
+    /// The states of the other regions that have to be active for the transition to fire.
+    pub joins: Vec<syn::Type>,
+    /// Filters the rapid repetitions of the event, declared with `debounce` or `throttle`.
+    pub rate_limit: Option<FsmTransitionRateLimit>,
+    /// The maximum number of the retries, declared with `retry`.
+    pub retry: Option<syn::Expr>
+}
+
+impl EventGuardAction {
+    pub fn has_guard(&self) -> bool {
+        self.guard.is_some() || self.guard_async.is_some()
+    }
+
+    /// Can the transition be skipped, so that the following transitions on the event are evaluated?
+    pub fn is_conditional(&self) -> bool {
+        self.has_guard() || self.rate_limit.is_some() || self.retry.is_some()
+    }
+}
+
+/// A guard combined from terms, evaluated from the left with short-circuiting.
+#[derive(Debug, Clone)]
+pub enum FsmGuardExpr {
+    /// The anonymous closures are named after their position in the expression, like `guard_2`.
+    Term { closure: syn::ExprClosure, name: String },
+    Not(Box<FsmGuardExpr>),
+    And(Box<FsmGuardExpr>, Box<FsmGuardExpr>),
+    Or(Box<FsmGuardExpr>, Box<FsmGuardExpr>)
+}
+
+impl FsmGuardExpr {
+    pub fn terms(&self) -> usize {
+        match self {
+            FsmGuardExpr::Term { .. } => 1,
+            FsmGuardExpr::Not(e) => e.terms(),
+            FsmGuardExpr::And(a, b) | FsmGuardExpr::Or(a, b) => a.terms() + b.terms()
+        }
+    }
+
+    /// The expression of the terms' names, like `!overloaded && door_closed`. The compound operands are parenthesized.
+    pub fn label(&self) -> String {
+        let operand = |e: &FsmGuardExpr| match e {
+            FsmGuardExpr::And(..) | FsmGuardExpr::Or(..) => format!("({})", e.label()),
+            _ => e.label()
+        };
+
+        match self {
+            FsmGuardExpr::Term { name, .. } => name.clone(),
+            FsmGuardExpr::Not(e) => format!("!{}", operand(e)),
+            FsmGuardExpr::And(a, b) => format!("{} && {}", operand(a), operand(b)),
+            FsmGuardExpr::Or(a, b) => format!("{} || {}", operand(a), operand(b))
+        }
+    }
+
+    /// The expression that evaluates the terms with the closure inputs remapped to the `args`. The evaluated
+    /// body of every term is passed through `term`, with its name.
+    pub fn expand(&self, args: &[TokenStream], term: &dyn Fn(&str, TokenStream) -> TokenStream) -> syn::Result<TokenStream> {
+        Ok(match self {
+            FsmGuardExpr::Term { closure, name } => {
+                let remap = remap_closure_inputs(&closure.inputs, args)?;
+                let body = &closure.body;
+                term(name, quote! { { #remap #body } })
+            },
+            FsmGuardExpr::Not(e) => {
+                let e = e.expand(args, term)?;
+                quote! { !(#e) }
+            },
+            FsmGuardExpr::And(a, b) => {
+                let (a, b) = (a.expand(args, term)?, b.expand(args, term)?);
+                quote! { (#a) && (#b) }
+            },
+            FsmGuardExpr::Or(a, b) => {
+                let (a, b) = (a.expand(args, term)?, b.expand(args, term)?);
+                quote! { (#a) || (#b) }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FsmTransitionRateLimit {
+    Debounce(syn::Expr),
+    Throttle(syn::Expr)
 }
 
 impl FsmDeclarations {
@@ -1,30 +1,78 @@
 use std::collections::HashMap;
 
 use proc_macro2::Span;
+use quote::quote;
 use syn::{ExprMethodCall, ItemFn, Type, spanned::Spanned};
 
-use crate::{parse::{EventGuardAction, FsmDeclarations, FsmEvent, FsmEventTransition, FsmFnBase, FsmState, FsmStateAction, FsmStateKind, FsmStateTransition, FsmSubMachineOptions, FsmTimer, FsmTransition, FsmTransitionEvent, FsmTransitionState, FsmTransitionType, ValidatedFsm}, parse_blocks::{FsmBlock, get_generics}, utils::{assert_no_generics, to_field_name, get_closure}, validation::create_regions};
+use crate::{parse::{EventGuardAction, FsmDeclarations, FsmEvent, FsmEventTransition, FsmFnBase, FsmGuardExpr, FsmState, FsmStateAction, FsmStateHistory, FsmStateKind, FsmStateTransition, FsmSubMachineOptions, FsmSubMachineStateEvent, FsmTimer, FsmTransition, FsmTransitionEvent, FsmTransitionRateLimit, FsmTransitionState, FsmTransitionType, FsmUnhandledEvent, ValidatedFsm}, parse_blocks::{FsmBlock, get_generics}, utils::{assert_event_ty, assert_no_generics, is_borrowed_ty, to_field_name, get_closure, get_closure_or_fn, remap_closure_inputs, tokens_to_string}, validation::create_regions};
 
 #[derive(Copy, Clone, Debug)]
 pub struct FsmCodegenOptions {
-    pub event_debug: bool
+    pub event_debug: bool,
+    /// Implement `Debug` for the states struct.
+    pub states_debug: bool,
+    pub derive_serde: bool,
+    /// Derive the serde traits only for the events enum.
+    pub event_serde: bool,
+    /// Implement `Arbitrary` for the events enum, for the fuzzers.
+    pub event_arbitrary: bool,
+    /// Derive `Clone` for the events enum, required by the deferred events and the submachines.
+    pub event_clone: bool,
+    /// Fail the dispatch of an event that isn't handled in the current state of any of the regions.
+    pub strict_events: bool,
+    /// Roll back the context and the states when the dispatch of an event fails.
+    pub transactional: bool,
+    /// Generate the `extern "C"` API of the machine.
+    pub ffi: bool
 }
 
 impl FsmCodegenOptions {
     pub fn new() -> Self {
         Self {
-            event_debug: false
+            event_debug: false,
+            states_debug: false,
+            derive_serde: false,
+            event_serde: false,
+            event_arbitrary: false,
+            event_clone: true,
+            strict_events: false,
+            transactional: false,
+            ffi: false
         }
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+enum GuardCombinator {
+    And,
+    Or,
+    Not
+}
+
 pub struct FsmParser {
     initial_states: Vec<syn::Type>,
     states: HashMap<Type, FsmState>,
     events: HashMap<Type, FsmEvent>,
     options: FsmCodegenOptions,
+    unhandled_event: FsmUnhandledEvent,
+    error_state: Option<syn::Type>,
+    resources: Option<syn::Type>,
+    outputs: Option<syn::Type>,
+    region_priorities: Vec<(syn::Type, u8)>,
+    state_ids: Vec<(syn::Type, u32)>,
     base: FsmFnBase,
-    timer_id: usize
+    timer_id: usize,
+    any_state_events: Vec<FsmAnyStateEvent>
+}
+
+/// The transitions declared using `any_state` or `state_group`, expanded into transitions from each
+/// of the states once the whole builder is parsed.
+struct FsmAnyStateEvent {
+    /// The states of the group, or all of the declared states.
+    states: Option<Vec<syn::Type>>,
+    except: Vec<syn::Type>,
+    event: FsmEvent,
+    span: Span
 }
 
 impl FsmParser {
@@ -34,8 +82,15 @@ impl FsmParser {
             states: HashMap::new(),
             events: HashMap::new(),
             options: FsmCodegenOptions::new(),
+            unhandled_event: FsmUnhandledEvent::default(),
+            error_state: None,
+            resources: None,
+            outputs: None,
+            region_priorities: vec![],
+            state_ids: vec![],
             base,
-            timer_id: 1
+            timer_id: 1,
+            any_state_events: vec![]
         }
     }
 
@@ -58,6 +113,160 @@ impl FsmParser {
                         [MethodOverviewRef { name: "events_debug", generics: [], .. }] => {
                             self.options.event_debug = true;
                         },
+                        [MethodOverviewRef { name: "states_debug", generics: [], .. }] => {
+                            self.options.states_debug = true;
+                        },
+                        [MethodOverviewRef { name: "derive_serde", generics: [], .. }] => {
+                            self.options.derive_serde = true;
+                        },
+                        [MethodOverviewRef { name: "events_serde", generics: [], .. }] => {
+                            self.options.event_serde = true;
+                        },
+                        [MethodOverviewRef { name: "events_arbitrary", generics: [], .. }] => {
+                            self.options.event_arbitrary = true;
+                        },
+                        [MethodOverviewRef { name: "events_without_clone", generics: [], .. }] => {
+                            self.options.event_clone = false;
+                        },
+                        [MethodOverviewRef { name: "strict_events", generics: [], .. }] => {
+                            self.options.strict_events = true;
+                        },
+                        [MethodOverviewRef { name: "transactional", generics: [], .. }] => {
+                            self.options.transactional = true;
+                        },
+                        [MethodOverviewRef { name: "ffi", generics: [], .. }] => {
+                            self.options.ffi = true;
+                        },
+                        [MethodOverviewRef { name: "on_unhandled_event", generics: [], call }] => {
+                            let closure = get_closure(call)?;
+
+                            if self.unhandled_event.handler.is_some() {
+                                return Err(syn::Error::new(closure.span(), "Duplicate 'on_unhandled_event'!"));
+                            }
+                            self.unhandled_event.handler = Some(closure.clone());
+                        },
+                        [MethodOverviewRef { name: "unhandled_event_policy", generics: [], call }] => {
+                            let policy = match call.args.first() {
+                                Some(expr) if call.args.len() == 1 => expr.clone(),
+                                _ => { return Err(syn::Error::new(call.span(), "Expected the unhandled event policy.")); }
+                            };
+
+                            if self.unhandled_event.policy.is_some() {
+                                return Err(syn::Error::new(call.span(), "Duplicate 'unhandled_event_policy'!"));
+                            }
+                            self.unhandled_event.policy = Some(policy);
+                        },
+                        [MethodOverviewRef { name: "on_error", generics: [], .. }, MethodOverviewRef { name: "transition_to", generics: [ty_state], .. }] => {
+                            if self.error_state.is_some() {
+                                return Err(syn::Error::new(ty_state.span(), "Duplicate 'on_error'!"));
+                            }
+
+                            self.state_builder_parser(ty_state, &[], false)?;
+                            self.error_state = Some(ty_state.clone());
+                        },
+                        [MethodOverviewRef { name: "resources", generics: [ty_resources], .. }] => {
+                            if self.resources.is_some() {
+                                return Err(syn::Error::new(ty_resources.span(), "Duplicate 'resources'!"));
+                            }
+                            self.resources = Some(ty_resources.clone());
+                        },
+                        [MethodOverviewRef { name: "outputs", generics: [ty_outputs], .. }] => {
+                            if self.outputs.is_some() {
+                                return Err(syn::Error::new(ty_outputs.span(), "Duplicate 'outputs'!"));
+                            }
+                            self.outputs = Some(ty_outputs.clone());
+                        },
+                        [MethodOverviewRef { name: "event", generics: [ty_event], .. }] => {
+                            assert_event_ty(ty_event)?;
+
+                            self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+                        },
+                        [MethodOverviewRef { name: "event_priority", generics: [ty_event], call }] => {
+                            if is_borrowed_ty(ty_event) {
+                                return Err(syn::Error::new(ty_event.span(), "The borrowed events can't be queued!"));
+                            }
+                            assert_event_ty(ty_event)?;
+
+                            let priority = match call.args.first() {
+                                Some(expr) if call.args.len() == 1 => expr.clone(),
+                                _ => { return Err(syn::Error::new(call.span(), "Expected the priority of the event.")); }
+                            };
+
+                            let event = self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+                            if event.priority.is_some() {
+                                return Err(syn::Error::new(ty_event.span(), "Duplicate event priority!"));
+                            }
+                            event.priority = Some(priority);
+                        },
+                        [MethodOverviewRef { name: "region_priority", generics: [ty_state], call }] => {
+                            // the regions are ordered in the generated dispatch, the priority has to be known
+                            let priority = match call.args.first() {
+                                Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. })) if call.args.len() == 1 => lit.base10_parse::<u8>()?,
+                                _ => { return Err(syn::Error::new(call.span(), "Expected the priority of the region, as an integer literal.")); }
+                            };
+
+                            if self.region_priorities.iter().any(|(ty, _)| ty == ty_state) {
+                                return Err(syn::Error::new(ty_state.span(), "Duplicate region priority!"));
+                            }
+                            self.region_priorities.push((ty_state.clone(), priority));
+                        },
+                        [MethodOverviewRef { name: "event_id", generics: [ty_event], call }] => {
+                            assert_event_ty(ty_event)?;
+                            let id = parse_stable_id(call)?;
+
+                            let event = self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+                            if event.id.is_some() {
+                                return Err(syn::Error::new(ty_event.span(), "Duplicate event id!"));
+                            }
+                            event.id = Some(id);
+                        },
+                        [MethodOverviewRef { name: "event_name", generics: [ty_event], call }] => {
+                            assert_event_ty(ty_event)?;
+                            let name = parse_display_name(call)?;
+
+                            let event = self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+                            if event.display_name.is_some() {
+                                return Err(syn::Error::new(ty_event.span(), "Duplicate event name!"));
+                            }
+                            event.display_name = Some(name);
+                        },
+                        [MethodOverviewRef { name: "state_id", generics: [ty_state], call }] => {
+                            // the states can be declared after their ids
+                            let id = parse_stable_id(call)?;
+
+                            if self.state_ids.iter().any(|(ty, _)| ty == ty_state) {
+                                return Err(syn::Error::new(ty_state.span(), "Duplicate state id!"));
+                            }
+                            self.state_ids.push((ty_state.clone(), id));
+                        },
+                        [MethodOverviewRef { name: "event_trace_context", generics: [ty_event], call }] => {
+                            if is_borrowed_ty(ty_event) {
+                                return Err(syn::Error::new(ty_event.span(), "The borrowed events can't be queued!"));
+                            }
+                            assert_event_ty(ty_event)?;
+
+                            let closure = get_closure(call)?;
+
+                            let event = self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+                            if event.trace_context.is_some() {
+                                return Err(syn::Error::new(ty_event.span(), "Duplicate event trace context!"));
+                            }
+                            event.trace_context = Some(closure.clone());
+                        },
                         [MethodOverviewRef { name: "initial_state", generics: [ty], .. }] => {
                             assert_no_generics(ty)?;
                             if self.initial_states.len() > 0 { return Err(syn::Error::new(ty.span(), "Duplicate initial_state!")); }
@@ -90,8 +299,18 @@ impl FsmParser {
                                     state_storage_field: field_name,
                                     on_entry_closure: None,
                                     on_exit_closure: None,
+                                    on_entry_async_closure: None,
+                                    on_exit_async_closure: None,
+                                    on_entry_event_closure: None,
+                                    on_exit_event_closure: None,
                                     kind: FsmStateKind::SubMachine(FsmSubMachineOptions::default()),
-                                    timers: vec![]
+                                    timers: vec![],
+                                    history: FsmStateHistory::None,
+                                    deferred_events: vec![],
+                                    is_final: false,
+                                    is_entry_point: false,
+                                    id: None,
+                                    display_name: None
                                 });
                             let mut sub_options = match state.kind {                                
                                 FsmStateKind::SubMachine(ref sub) => sub.clone(),
@@ -116,11 +335,25 @@ impl FsmParser {
 
                             // update the options
                             self.states.entry(ty_sub_fsm.clone()).and_modify(|s| {
-                                s.kind = FsmStateKind::SubMachine(sub_options);
+                                if let FsmStateKind::SubMachine(ref mut sub) = s.kind {
+                                    sub.context_constructor = sub_options.context_constructor;
+                                }
                             });
                             
                         },
 
+                        [MethodOverviewRef { name: "any_state", generics: [], call }, st @ .. ] => {
+
+                            self.any_state_parser(call, st)?;
+
+                        },
+
+                        [MethodOverviewRef { name: "state_group", generics: [ty_states], call }, st @ .. ] => {
+
+                            self.state_group_parser(call, ty_states, st)?;
+
+                        },
+
                         [MethodOverviewRef { name: "state", generics: [ty_state], .. }, st @ .. ] => {
 
                             self.state_builder_parser(ty_state, st, false)?;
@@ -138,28 +371,158 @@ impl FsmParser {
         Ok(())
     }
 
-    fn parse_event_guard_action(event_method_calls: &[MethodOverviewRef]) -> syn::Result<EventGuardAction> {
-        let mut guard_action = EventGuardAction { guard: None, action: None, type_hint: None };
+    /// Combine the declared guard with another one, or negate it. The terms are kept as a tree, so that each
+    /// of them is inspected on its own, and folded from the left into a single closure for the other uses.
+    fn combine_guard(guard_action: &mut EventGuardAction, call: &ExprMethodCall, op: GuardCombinator, other: Option<(syn::ExprClosure, Option<String>)>) -> syn::Result<()> {
+        let left = match (guard_action.guard_expr.take(), &guard_action.guard) {
+            (Some(expr), _) => expr,
+            (None, Some(guard)) if !guard_action.guard_fallible => FsmGuardExpr::Term {
+                closure: guard.clone(),
+                name: guard_action.guard_name.take().unwrap_or_else(|| "guard_1".to_string())
+            },
+            _ => { return Err(syn::Error::new(call.span(), "The guards can only be combined with a preceding 'guard'.")); }
+        };
+
+        let expr = match (op, other) {
+            (GuardCombinator::Not, _) => FsmGuardExpr::Not(Box::new(left)),
+            (op, Some((closure, name))) => {
+                let name = name.unwrap_or_else(|| format!("guard_{}", left.terms() + 1));
+                let right = Box::new(FsmGuardExpr::Term { closure, name });
+                match op {
+                    GuardCombinator::And => FsmGuardExpr::And(Box::new(left), right),
+                    _ => FsmGuardExpr::Or(Box::new(left), right)
+                }
+            },
+            (_, None) => { return Err(syn::Error::new(call.span(), "Missing guard!")); }
+        };
+
+        let args = vec![quote! { arg0 }, quote! { arg1 }, quote! { arg2 }];
+        let body = expr.expand(&args, &|_name, term| term)?;
+        guard_action.guard = Some(syn::parse2(quote! { |arg0, arg1, arg2| #body })?);
+        guard_action.guard_name = Some(expr.label());
+        guard_action.guard_expr = Some(expr);
+        Ok(())
+    }
+
+    fn parse_event_guard_action(event_method_calls: &[MethodOverviewRef], action_inputs: usize) -> syn::Result<EventGuardAction> {
+        let mut guard_action = EventGuardAction::default();
         
         for method in event_method_calls {
             match method {
                 MethodOverviewRef { name: "guard", .. } => {
-                    let closure = get_closure(method.call)?;
+                    let (closure, name) = get_closure_or_fn(method.call, 3)?;
+
+                    if guard_action.has_guard() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'guard'!"));
+                    }
 
-                    if guard_action.guard.is_some() {
+                    guard_action.guard = Some(closure.clone());
+                    guard_action.guard_name = name;
+                },
+                MethodOverviewRef { name: op @ ("and_guard" | "or_guard"), .. } => {
+                    let (closure, name) = get_closure_or_fn(method.call, 3)?;
+                    let op = if *op == "and_guard" { GuardCombinator::And } else { GuardCombinator::Or };
+                    Self::combine_guard(&mut guard_action, method.call, op, Some((closure, name)))?;
+                },
+                MethodOverviewRef { name: "not", generics: [], .. } => {
+                    Self::combine_guard(&mut guard_action, method.call, GuardCombinator::Not, None)?;
+                },
+                MethodOverviewRef { name: "try_guard", .. } => {
+                    let (closure, name) = get_closure_or_fn(method.call, 3)?;
+
+                    if guard_action.has_guard() {
                         return Err(syn::Error::new(closure.span(), "Duplicate 'guard'!"));
                     }
 
                     guard_action.guard = Some(closure.clone());
+                    guard_action.guard_name = name;
+                    guard_action.guard_fallible = true;
+                },
+                MethodOverviewRef { name: "guard_async", .. } => {
+                    let (closure, name) = get_closure_or_fn(method.call, 3)?;
+
+                    if guard_action.has_guard() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'guard'!"));
+                    }
+
+                    guard_action.guard_async = Some(closure.clone());
+                    guard_action.guard_name = name;
+                },
+                MethodOverviewRef { name: "action_async", .. } => {
+                    let (closure, name) = get_closure_or_fn(method.call, action_inputs)?;
+
+                    if guard_action.action_async.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'action_async'!"));
+                    }
+
+                    guard_action.action_async = Some(closure.clone());
+                    guard_action.action_name = name;
                 },
                 MethodOverviewRef { name: "action", .. } => {
-                    let closure = get_closure(method.call)?;
+                    let (closure, name) = get_closure_or_fn(method.call, action_inputs)?;
+
+                    if guard_action.action.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'action'!"));
+                    }
+
+                    guard_action.action = Some(closure.clone());
+                    guard_action.action_name = name;
+                },
+                MethodOverviewRef { name: "try_action", .. } => {
+                    let (closure, name) = get_closure_or_fn(method.call, action_inputs)?;
 
                     if guard_action.action.is_some() {
                         return Err(syn::Error::new(closure.span(), "Duplicate 'action'!"));
                     }
 
                     guard_action.action = Some(closure.clone());
+                    guard_action.action_name = name;
+                    guard_action.action_fallible = true;
+                },
+                MethodOverviewRef { name: "entry_point", generics: [ty_state], .. } => {
+                    if guard_action.entry_point.is_some() {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'entry_point'!"));
+                    }
+
+                    guard_action.entry_point = Some(ty_state.clone());
+                },
+                MethodOverviewRef { name: "fork", generics: [ty_state], .. } => {
+                    if guard_action.forks.contains(ty_state) {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'fork'!"));
+                    }
+
+                    guard_action.forks.push(ty_state.clone());
+                },
+                MethodOverviewRef { name: "join", generics: [ty_state], .. } => {
+                    if guard_action.joins.contains(ty_state) {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'join'!"));
+                    }
+
+                    guard_action.joins.push(ty_state.clone());
+                },
+                MethodOverviewRef { name: kind @ ("debounce" | "throttle"), generics: [], call } => {
+                    let window = match call.args.first() {
+                        Some(expr) if call.args.len() == 1 => expr.clone(),
+                        _ => { return Err(syn::Error::new(call.span(), "Expected the duration of the window.")); }
+                    };
+
+                    if guard_action.rate_limit.is_some() {
+                        return Err(syn::Error::new(call.span(), "Duplicate 'debounce' or 'throttle'!"));
+                    }
+
+                    guard_action.rate_limit = Some(if *kind == "debounce" { FsmTransitionRateLimit::Debounce(window) } else { FsmTransitionRateLimit::Throttle(window) });
+                },
+                MethodOverviewRef { name: "retry", generics: [], call } => {
+                    let max = match call.args.first() {
+                        Some(expr) if call.args.len() == 1 => expr.clone(),
+                        _ => { return Err(syn::Error::new(call.span(), "Expected the maximum number of the retries.")); }
+                    };
+
+                    if guard_action.retry.is_some() {
+                        return Err(syn::Error::new(call.span(), "Duplicate 'retry'!"));
+                    }
+
+                    guard_action.retry = Some(max);
                 },
                 MethodOverviewRef { name: "with_transition_ty", generics: [transition_ty], ..}  => {
 
@@ -177,16 +540,30 @@ impl FsmParser {
         Ok(guard_action)
     }
 
-    fn parse_state_on_event(state: &FsmState, event: &mut FsmEvent, method_calls: &[MethodOverviewRef]) -> syn::Result<()> {
+    /// The internal and self transitions don't enter another state, they can't have an entry point
+    /// or synchronize the regions.
+    fn parse_state_guard_action(event_method_calls: &[MethodOverviewRef]) -> syn::Result<EventGuardAction> {
+        let guard_action = Self::parse_event_guard_action(event_method_calls, 3)?;
+        if let Some(ref entry_point) = guard_action.entry_point {
+            return Err(syn::Error::new(entry_point.span(), "Only the submachines have entry points!"));
+        }
+        if let Some(ty) = guard_action.forks.iter().chain(guard_action.joins.iter()).next() {
+            return Err(syn::Error::new(ty.span(), "Only the transitions to another state can fork or join the regions!"));
+        }
+
+        Ok(guard_action)
+    }
+
+    fn parse_state_on_event(state: &syn::Type, event: &mut FsmEvent, method_calls: &[MethodOverviewRef]) -> syn::Result<()> {
         match method_calls {
-            [MethodOverviewRef { name: "transition_to", generics: [ty_to], .. }, ev @ .. ] => {
-                event.transitions.push(FsmEventTransition::State(state.ty.clone(), ty_to.clone(), Self::parse_event_guard_action(ev)?));                
+            [MethodOverviewRef { name: "transition_to", generics: [ty_to], call }, ev @ .. ] => {
+                Self::parse_state_choice(state, event, ty_to, call.method.span(), ev, false)?;
             },
-            [MethodOverviewRef { name: "internal_transition", generics: [], ..}, ev @ ..] => {
-                event.transitions.push(FsmEventTransition::InternalTransition(state.ty.clone(), Self::parse_event_guard_action(ev)?));
+            [MethodOverviewRef { name: "internal_transition", generics: [], call }, ev @ ..] => {
+                event.transitions.push(FsmEventTransition::InternalTransition(state.clone(), Self::parse_state_guard_action(ev)?, call.method.span()));
             },
-            [MethodOverviewRef { name: "self_transition", generics: [], ..}, ev @ ..] => {
-                event.transitions.push(FsmEventTransition::SelfTransition(state.ty.clone(), Self::parse_event_guard_action(ev)?));
+            [MethodOverviewRef { name: "self_transition", generics: [], call }, ev @ ..] => {
+                event.transitions.push(FsmEventTransition::SelfTransition(state.clone(), Self::parse_state_guard_action(ev)?, call.method.span()));
             },
             [] => (),
             _ => { return Err(syn::Error::new(method_calls.first().map(|m| m.call.span()).unwrap_or(Span::call_site()), "Unsupported methods.")); }
@@ -195,12 +572,214 @@ impl FsmParser {
         Ok(())
     }
 
+    fn any_state_parser(&mut self, call: &ExprMethodCall, st: &[MethodOverviewRef]) -> syn::Result<()> {
+        let mut except = vec![];
+
+        for (i, method) in st.iter().enumerate() {
+            match method {
+                MethodOverviewRef { name: "except", generics: [ty_state], .. } => {
+                    except.extend(Self::tuple_types(ty_state));
+                },
+                MethodOverviewRef { name: "on_event", generics: [ty_event], .. } => {
+                    return self.any_state_on_event(None, except, call, ty_event, &st[(i+1)..]);
+                },
+                _ => { return Err(syn::Error::new(method.call.span(), "Unsupported method.")); }
+            }
+        }
+
+        Err(syn::Error::new(call.span(), "Missing the event, use 'on_event' after 'any_state'."))
+    }
+
+    fn state_group_parser(&mut self, call: &ExprMethodCall, ty_states: &syn::Type, st: &[MethodOverviewRef]) -> syn::Result<()> {
+        match st {
+            [MethodOverviewRef { name: "on_event", generics: [ty_event], .. }, ev @ ..] => {
+                self.any_state_on_event(Some(Self::tuple_types(ty_states)), vec![], call, ty_event, ev)
+            },
+            [m, ..] => Err(syn::Error::new(m.call.span(), "Unsupported method.")),
+            [] => Err(syn::Error::new(call.span(), "Missing the event, use 'on_event' after 'state_group'."))
+        }
+    }
+
+    fn tuple_types(ty: &syn::Type) -> Vec<syn::Type> {
+        match ty {
+            syn::Type::Tuple(tuple) => tuple.elems.iter().cloned().collect(),
+            ty => vec![ty.clone()]
+        }
+    }
+
+    fn any_state_on_event(&mut self, states: Option<Vec<syn::Type>>, except: Vec<syn::Type>, call: &ExprMethodCall, ty_event: &syn::Type, method_calls: &[MethodOverviewRef]) -> syn::Result<()> {
+        assert_event_ty(ty_event)?;
+
+        self.events
+            .entry(ty_event.clone())
+            .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+        let mut event = FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None };
+        let any_state: syn::Type = syn::parse_quote! { finny::decl::FsmAnyState };
+        Self::parse_state_on_event(&any_state, &mut event, method_calls)?;
+
+        if let Some(t) = event.transitions.iter().find(|t| t.get_action().type_hint.is_some()) {
+            return Err(syn::Error::new(t.get_span(), "The transitions from multiple states can't have a transition type, one is generated for each of the states."));
+        }
+
+        self.any_state_events.push(FsmAnyStateEvent { states, except, event, span: call.method.span() });
+
+        Ok(())
+    }
+
+    /// Adds the transitions declared using `any_state` to every declared state, after the state's own transitions
+    /// on the same event. A state is skipped if it's excluded, if it's the target of the transition, if it defers the
+    /// event or if its own transition on the event has no guard. The states of a `state_group` are never skipped,
+    /// the transitions are validated as if they were declared on each of them.
+    fn expand_any_state_events(&mut self) -> syn::Result<()> {
+        for any in self.any_state_events.drain(..) {
+            for ty in any.except.iter().chain(any.states.iter().flatten()) {
+                if !self.states.contains_key(ty) {
+                    return Err(syn::Error::new(ty.span(), "State not found."));
+                }
+            }
+
+            let event = self.events.get_mut(&any.event.ty).ok_or(syn::Error::new(any.span, "Event not found."))?;
+
+            match any.states {
+                Some(ref states) => {
+                    for ty in states {
+                        for t in &any.event.transitions {
+                            if let FsmEventTransition::State(_, to, _, _) = t {
+                                if to == ty {
+                                    return Err(syn::Error::new(ty.span(), "The target of the transition can't be in the group of its source states, use a self transition for it."));
+                                }
+                            }
+
+                            event.transitions.push(t.with_state_from(ty));
+                        }
+                    }
+                },
+                None => {
+                    for (ty, state) in &self.states {
+                        if any.except.contains(ty) || state.deferred_events.contains(&event.ty) {
+                            continue;
+                        }
+
+                        if event.transitions.iter().any(|t| t.get_state_from() == ty && !t.get_action().is_conditional()) {
+                            continue;
+                        }
+
+                        for t in &any.event.transitions {
+                            if let FsmEventTransition::State(_, to, _, _) = t {
+                                if to == ty { continue; }
+                            }
+
+                            event.transitions.push(t.with_state_from(ty));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A chain of guarded transitions on the same event, evaluated in the declaration order,
+    /// optionally ending with an `otherwise` fallback.
+    fn parse_state_choice(state: &syn::Type, event: &mut FsmEvent, ty_to: &syn::Type, span: Span, method_calls: &[MethodOverviewRef], is_otherwise: bool) -> syn::Result<()> {
+        let next = method_calls.iter().position(|m| m.name == "transition_to" || m.name == "otherwise");
+        let (ev, rest) = method_calls.split_at(next.unwrap_or(method_calls.len()));
+
+        let guard_action = Self::parse_event_guard_action(ev, 4)?;
+
+        match (rest, is_otherwise) {
+            ([], _) => (),
+            ([next, ..], true) => {
+                return Err(syn::Error::new(next.call.span(), "The 'otherwise' transition has to be the last one!"));
+            },
+            ([next, ..], false) if !guard_action.is_conditional() => {
+                return Err(syn::Error::new(next.call.span(), "Only the last transition of the choice can be without a guard, use 'otherwise' for it."));
+            },
+            _ => ()
+        }
+
+        if is_otherwise && guard_action.is_conditional() {
+            return Err(syn::Error::new(ty_to.span(), "The 'otherwise' transition can't have a guard, a rate limit or retries!"));
+        }
+
+        event.transitions.push(FsmEventTransition::State(state.clone(), ty_to.clone(), guard_action, span));
+
+        match rest {
+            [MethodOverviewRef { name, generics: [ty_to], call }, ev @ .. ] => {
+                Self::parse_state_choice(state, event, ty_to, call.method.span(), ev, *name == "otherwise")
+            },
+            [m, ..] => Err(syn::Error::new(m.call.span(), "Expected the target state of the transition.")),
+            [] => Ok(())
+        }
+    }
+
+    /// The transitions from the same state on the same event are evaluated in their declaration order, so
+    /// the ones declared after a transition without a guard can never be taken.
+    fn validate_shadowed_transitions(event: &FsmEvent) -> syn::Result<()> {
+        let mut errors: Option<syn::Error> = None;
+
+        for (i, unguarded) in event.transitions.iter().enumerate() {
+            if unguarded.get_action().is_conditional() { continue; }
+
+            let from = unguarded.get_state_from();
+            let shadowed = event.transitions.iter().skip(i + 1).find(|t| t.get_state_from() == from);
+            if let Some(shadowed) = shadowed {
+                let mut err = syn::Error::new(shadowed.get_span(), format!("Conflicting transitions from the state '{}' on the event '{}', this transition is never taken!",
+                    tokens_to_string(from), tokens_to_string(&event.ty)));
+                err.combine(syn::Error::new(unguarded.get_span(), "The transition that is always taken instead, it has no guard. Add guards to the transitions or use 'otherwise' for the fallback."));
+
+                match errors {
+                    Some(ref mut e) => e.combine(err),
+                    None => errors = Some(err)
+                }
+            }
+        }
+
+        match errors {
+            Some(e) => Err(e),
+            None => Ok(())
+        }
+    }
+
     pub fn validate(mut self, input_fn: &ItemFn) -> syn::Result<ValidatedFsm> {
         let mut transitions = vec![];
 
+        self.expand_any_state_events()?;
+
+        for (ty, id) in self.state_ids.drain(..) {
+            let state = self.states.get_mut(&ty).ok_or_else(|| syn::Error::new(ty.span(), "The state isn't declared!"))?;
+            state.id = Some(id);
+        }
+
+        // the ids are streamed and stored, they have to be unique
+        let mut state_ids: HashMap<u32, &FsmState> = HashMap::new();
+        for state in self.states.values() {
+            if let Some(other) = state_ids.insert(state.stable_id(), state) {
+                return Err(syn::Error::new(state.ty.span(), format!("The states '{}' and '{}' have the same id, declare their ids with 'state_id'.", other.name(), state.name())));
+            }
+        }
+        let mut event_ids: HashMap<u32, &FsmEvent> = HashMap::new();
+        for event in self.events.values() {
+            if let Some(other) = event_ids.insert(event.stable_id(), event) {
+                return Err(syn::Error::new(event.ty.span(), format!("The events '{}' and '{}' have the same id, declare their ids with 'event_id'.", other.name(), event.name())));
+            }
+        }
+
         if self.initial_states.len() == 0 {
             return Err(syn::Error::new(input_fn.span(), "Missing the initial state declaration! Use the method 'initial_state' or 'initial_states'."));
         }
+
+        if !self.options.event_clone {
+            for (ty, state) in &self.states {
+                if let FsmStateKind::SubMachine(_) = state.kind {
+                    return Err(syn::Error::new(ty.span(), "The events are forwarded to the submachines by cloning them, submachines require the events to be cloneable!"));
+                }
+                if let Some(ev) = state.deferred_events.first() {
+                    return Err(syn::Error::new(ev.span(), "The deferred events are stored by cloning them, deferring requires the events to be cloneable!"));
+                }
+            }
+        }
         
         // build and validate the transitions table
         {
@@ -231,12 +810,32 @@ impl FsmParser {
             }
 
             for (ty, ev) in self.events.iter() {
+                Self::validate_shadowed_transitions(ev)?;
+
                 for t in &ev.transitions {
                     match t {
-                        FsmEventTransition::State(from, to, action) => {
+                        FsmEventTransition::State(from, to, action, _) => {
 
                             let from = self.states.get(from).ok_or(syn::Error::new(from.span(), "State not found."))?;
+                            if from.deferred_events.contains(ty) {
+                                return Err(syn::Error::new(ty.span(), "This event is deferred in the state, it can't also trigger a transition from it!"));
+                            }
                             let to = self.states.get(to).ok_or(syn::Error::new(to.span(), "State not found."))?;
+                            if let (Some(entry_point), FsmStateKind::Normal) = (&action.entry_point, &to.kind) {
+                                return Err(syn::Error::new(entry_point.span(), "Only the submachines have entry points!"));
+                            }
+                            for fork in &action.forks {
+                                match self.states.get(fork) {
+                                    Some(FsmState { kind: FsmStateKind::SubMachine(_), .. }) => {
+                                        return Err(syn::Error::new(fork.span(), "A fork can't enter a submachine!"));
+                                    },
+                                    Some(_) => (),
+                                    None => { return Err(syn::Error::new(fork.span(), "State not found.")); }
+                                }
+                            }
+                            if let Some(join) = action.joins.iter().find(|j| !self.states.contains_key(*j)) {
+                                return Err(syn::Error::new(join.span(), "State not found."));
+                            }
 
                             transitions.push(FsmTransition {
                                 transition_ty: generate_transition_ty(&self.base, &mut i, &action.type_hint),
@@ -248,9 +847,12 @@ impl FsmParser {
                                 })
                             });
                         }
-                        FsmEventTransition::InternalTransition(state, action) => {
+                        FsmEventTransition::InternalTransition(state, action, _) => {
                             // todo: code duplication!
                             let state = self.states.get(state).ok_or(syn::Error::new(state.span(), "State not found."))?;
+                            if state.deferred_events.contains(ty) {
+                                return Err(syn::Error::new(ty.span(), "This event is deferred in the state, it can't also trigger a transition from it!"));
+                            }
                             transitions.push(FsmTransition {
                                 transition_ty: generate_transition_ty(&self.base, &mut i, &action.type_hint),
                                 ty: FsmTransitionType::InternalTransition(FsmStateAction {
@@ -260,9 +862,12 @@ impl FsmParser {
                                 })
                             });
                         }
-                        FsmEventTransition::SelfTransition(state, action) => {
+                        FsmEventTransition::SelfTransition(state, action, _) => {
                             // todo: code duplication!
                             let state = self.states.get(state).ok_or(syn::Error::new(state.span(), "State not found."))?;
+                            if state.deferred_events.contains(ty) {
+                                return Err(syn::Error::new(ty.span(), "This event is deferred in the state, it can't also trigger a transition from it!"));
+                            }
                             transitions.push(FsmTransition {
                                 transition_ty: generate_transition_ty(&self.base, &mut i, &action.type_hint),
                                 ty: FsmTransitionType::SelfTransition(FsmStateAction {
@@ -281,7 +886,12 @@ impl FsmParser {
             initial_states: self.initial_states,
             states: self.states,
             events: self.events,
-            transitions
+            transitions,
+            unhandled_event: self.unhandled_event,
+            error_state: self.error_state,
+            resources: self.resources,
+            outputs: self.outputs,
+            region_priorities: self.region_priorities
         };
 
         let regions = create_regions(dec, self.options)?;
@@ -298,9 +908,19 @@ impl FsmParser {
                 ty: ty_state.clone(),
                 on_entry_closure: None,
                 on_exit_closure: None,
+                on_entry_async_closure: None,
+                on_exit_async_closure: None,
+                on_entry_event_closure: None,
+                on_exit_event_closure: None,
                 state_storage_field: field_name,
                 kind: FsmStateKind::Normal,
-                timers: vec![]
+                timers: vec![],
+                history: FsmStateHistory::None,
+                deferred_events: vec![],
+                is_final: false,
+                is_entry_point: false,
+                id: None,
+                display_name: None
             });
 
             
@@ -324,15 +944,158 @@ impl FsmParser {
                     }
                     state.on_exit_closure = Some(closure.clone());
                 },
+                MethodOverviewRef { name: "on_entry_async", .. } => {
+                    let closure = get_closure(&method.call)?;
+
+                    if state.on_entry_async_closure.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'on_entry_async'!"));
+                    }
+                    state.on_entry_async_closure = Some(closure.clone());
+                },
+                MethodOverviewRef { name: "on_exit_async", .. } => {
+                    let closure = get_closure(&method.call)?;
+
+                    if state.on_exit_async_closure.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'on_exit_async'!"));
+                    }
+                    state.on_exit_async_closure = Some(closure.clone());
+                },
+                MethodOverviewRef { name: "on_entry_with_event", .. } => {
+                    let closure = get_closure(&method.call)?;
+
+                    if state.on_entry_event_closure.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'on_entry_with_event'!"));
+                    }
+                    state.on_entry_event_closure = Some(closure.clone());
+                },
+                MethodOverviewRef { name: "on_exit_with_event", .. } => {
+                    let closure = get_closure(&method.call)?;
+
+                    if state.on_exit_event_closure.is_some() {
+                        return Err(syn::Error::new(closure.span(), "Duplicate 'on_exit_with_event'!"));
+                    }
+                    state.on_exit_event_closure = Some(closure.clone());
+                },
+                MethodOverviewRef { name: "history_shallow", generics: [], .. } => {
+                    if state.history != FsmStateHistory::None {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate history!"));
+                    }
+                    state.history = FsmStateHistory::Shallow;
+                },
+                MethodOverviewRef { name: "final_state", generics: [], .. } => {
+                    if state.is_final {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'final_state'!"));
+                    }
+                    state.is_final = true;
+                },
+                MethodOverviewRef { name: "named", generics: [], .. } => {
+                    if state.display_name.is_some() {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'named'!"));
+                    }
+                    state.display_name = Some(parse_display_name(method.call)?);
+                },
+                MethodOverviewRef { name: "entry_point", generics: [], .. } => {
+                    if state.is_entry_point {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate 'entry_point'!"));
+                    }
+                    state.is_entry_point = true;
+                },
+                MethodOverviewRef { name: "on_completion", generics: [], .. } if is_sub_fsm => {
+                    let closure = get_closure(method.call)?;
+
+                    match state.kind {
+                        FsmStateKind::SubMachine(ref mut sub) => {
+                            if sub.completion_event.is_some() {
+                                return Err(syn::Error::new(closure.span(), "Duplicate 'on_completion'!"));
+                            }
+                            sub.completion_event = Some(closure.clone());
+                        },
+                        FsmStateKind::Normal => { return Err(syn::Error::new(closure.span(), "Only submachines can complete.")); }
+                    }
+                },
+                MethodOverviewRef { name: name @ ("on_exit_point" | "on_child_state"), generics: [ty_state, ..], .. } if is_sub_fsm => {
+                    let closure = get_closure(method.call)?;
+
+                    match state.kind {
+                        FsmStateKind::SubMachine(ref mut sub) => {
+                            if sub.state_events.iter().any(|ev| &ev.state == ty_state) {
+                                return Err(syn::Error::new(ty_state.span(), "Duplicate notification of this submachine's state!"));
+                            }
+                            sub.state_events.push(FsmSubMachineStateEvent {
+                                state: ty_state.clone(),
+                                closure: closure.clone(),
+                                with_state: *name == "on_child_state"
+                            });
+                        },
+                        FsmStateKind::Normal => { return Err(syn::Error::new(closure.span(), "Only submachines can notify their states.")); }
+                    }
+                },
+                MethodOverviewRef { name: "map_event", generics: [ty_event, ..], .. } if is_sub_fsm => {
+                    if is_borrowed_ty(ty_event) {
+                        return Err(syn::Error::new(ty_event.span(), "The borrowed events can't be dispatched to the submachines!"));
+                    }
+                    assert_event_ty(ty_event)?;
+                    let closure = get_closure(method.call)?;
+
+                    match state.kind {
+                        FsmStateKind::SubMachine(ref mut sub) => {
+                            if sub.mapped_events.iter().any(|(ty, _)| ty == ty_event) {
+                                return Err(syn::Error::new(ty_event.span(), "Duplicate mapping of this event!"));
+                            }
+                            sub.mapped_events.push((ty_event.clone(), closure.clone()));
+                        },
+                        FsmStateKind::Normal => { return Err(syn::Error::new(closure.span(), "Only the events of the submachines can be mapped.")); }
+                    }
+
+                    self.events
+                        .entry(ty_event.clone())
+                        .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+                },
+                MethodOverviewRef { name: "lift_event", generics: [ty_sub_event, ..], .. } if is_sub_fsm => {
+                    assert_event_ty(ty_sub_event)?;
+                    let closure = get_closure(method.call)?;
+
+                    match state.kind {
+                        FsmStateKind::SubMachine(ref mut sub) => {
+                            if sub.lifted_events.iter().any(|(ty, _)| ty == ty_sub_event) {
+                                return Err(syn::Error::new(ty_sub_event.span(), "Duplicate lifting of this event!"));
+                            }
+                            sub.lifted_events.push((ty_sub_event.clone(), closure.clone()));
+                        },
+                        FsmStateKind::Normal => { return Err(syn::Error::new(closure.span(), "Only the events of the submachines can be lifted.")); }
+                    }
+                },
+                MethodOverviewRef { name: "history_deep", generics: [], .. } => {
+                    if state.history != FsmStateHistory::None {
+                        return Err(syn::Error::new(method.call.span(), "Duplicate history!"));
+                    }
+                    state.history = FsmStateHistory::Deep;
+                },
+                MethodOverviewRef { name: "defer", generics: [ty_event], .. } => {
+                    if is_borrowed_ty(ty_event) {
+                        return Err(syn::Error::new(ty_event.span(), "The borrowed events can't be deferred!"));
+                    }
+                    assert_event_ty(ty_event)?;
+
+                    if state.deferred_events.contains(ty_event) {
+                        return Err(syn::Error::new(ty_event.span(), "Duplicate deferred event!"));
+                    }
+
+                    self.events
+                        .entry(ty_event.clone())
+                        .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+
+                    state.deferred_events.push(ty_event.clone());
+                },
                 MethodOverviewRef { name: "on_event", generics: [ty_event], .. } => {
-                    assert_no_generics(ty_event)?;
+                    assert_event_ty(ty_event)?;
 
                     let event = self.events
                         .entry(ty_event.clone())
-                        .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![] });
+                        .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
 
                     let other_method_calls = &st[(i+1)..];
-                    Self::parse_state_on_event(state, event, other_method_calls)?;
+                    Self::parse_state_on_event(&state.ty, event, other_method_calls)?;
 
                     break;
                 },
@@ -360,6 +1123,135 @@ impl FsmParser {
                     }
                 },
 
+                MethodOverviewRef { name: "on_timer", generics: [], .. } => {
+
+                    let call_args: Vec<_> = method.call.args.iter().collect();
+                    match call_args.as_slice() {
+                        [syn::Expr::Closure(ref timeout), syn::Expr::Closure(ref trigger)] => {
+
+                            if timer.is_some() { panic!("double timer bug!"); }
+
+                            let timeout_inputs = remap_closure_inputs(&timeout.inputs, &[quote! { &*ctx }, quote! { state }])?;
+                            let timeout_body = &timeout.body;
+
+                            let setup: syn::ExprClosure = syn::parse_quote! {
+                                |ctx, state, settings| {
+                                    settings.timeout = {
+                                        #timeout_inputs
+                                        #timeout_body
+                                    };
+                                    settings.renew = false;
+                                    settings.cancel_on_state_exit = true;
+                                }
+                            };
+
+                            timer = Some(FsmTimer {
+                                setup,
+                                trigger: trigger.clone(),
+                                id: self.timer_id,
+                                type_hint: None
+                            });
+
+                            self.timer_id += 1;
+
+                        },
+                        _ => {
+                            return Err(syn::Error::new(method.call.span(), "Unexpected arguments to the timer method."));
+                        }
+                    }
+                },
+
+                MethodOverviewRef { name: "on_timer_event", generics: [ty_event], .. } => {
+                    assert_event_ty(ty_event)?;
+                    if is_borrowed_ty(ty_event) {
+                        return Err(syn::Error::new(ty_event.span(), "The borrowed events can't be queued!"));
+                    }
+
+                    let call_args: Vec<_> = method.call.args.iter().collect();
+                    match call_args.as_slice() {
+                        [syn::Expr::Closure(ref timeout), syn::Expr::Closure(ref event)] => {
+
+                            if timer.is_some() { panic!("double timer bug!"); }
+
+                            let timeout_inputs = remap_closure_inputs(&timeout.inputs, &[quote! { &*ctx }, quote! { state }])?;
+                            let timeout_body = &timeout.body;
+
+                            let setup: syn::ExprClosure = syn::parse_quote! {
+                                |ctx, state, settings| {
+                                    settings.timeout = {
+                                        #timeout_inputs
+                                        #timeout_body
+                                    };
+                                    settings.renew = false;
+                                    settings.cancel_on_state_exit = true;
+                                }
+                            };
+
+                            let event_inputs = remap_closure_inputs(&event.inputs, &[quote! { ctx }, quote! { state }])?;
+                            let event_body = &event.body;
+
+                            let trigger: syn::ExprClosure = syn::parse_quote! {
+                                |ctx, state| {
+                                    let event: #ty_event = {
+                                        #event_inputs
+                                        #event_body
+                                    };
+                                    Some(event.into())
+                                }
+                            };
+
+                            timer = Some(FsmTimer {
+                                setup,
+                                trigger,
+                                id: self.timer_id,
+                                type_hint: None
+                            });
+
+                            self.timer_id += 1;
+
+                            // the event might not be handled by any of the transitions yet
+                            self.events
+                                .entry(ty_event.clone())
+                                .or_insert(FsmEvent { ty: ty_event.clone(), transitions: vec![], priority: None, trace_context: None, id: None, display_name: None });
+                        },
+                        _ => {
+                            return Err(syn::Error::new(method.call.span(), "Unexpected arguments to the timer method."));
+                        }
+                    }
+                },
+
+                MethodOverviewRef { name: "on_timer_interval", generics: [], .. } => {
+
+                    let call_args: Vec<_> = method.call.args.iter().collect();
+                    match call_args.as_slice() {
+                        [interval, syn::Expr::Closure(ref trigger)] => {
+
+                            if timer.is_some() { panic!("double timer bug!"); }
+
+                            let setup: syn::ExprClosure = syn::parse_quote! {
+                                |_ctx, settings| {
+                                    settings.timeout = #interval;
+                                    settings.renew = true;
+                                    settings.cancel_on_state_exit = true;
+                                }
+                            };
+
+                            timer = Some(FsmTimer {
+                                setup,
+                                trigger: trigger.clone(),
+                                id: self.timer_id,
+                                type_hint: None
+                            });
+
+                            self.timer_id += 1;
+
+                        },
+                        _ => {
+                            return Err(syn::Error::new(method.call.span(), "Unexpected arguments to the timer interval method."));
+                        }
+                    }
+                },
+
                 MethodOverviewRef { name: "with_timer_ty", generics: [timer_ty], .. } => {
 
                     if let Some(ref mut timer) = timer {
@@ -414,4 +1306,51 @@ struct MethodOverviewRef<'a> {
     name: &'a str,
     generics: &'a [syn::Type],
     call: &'a ExprMethodCall
-}
\ No newline at end of file
+}
+
+/// The ids of the states and events are the discriminants of their enums, they have to be integer literals.
+fn parse_display_name(call: &ExprMethodCall) -> syn::Result<String> {
+    match call.args.first() {
+        Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. })) if call.args.len() == 1 && !lit.value().is_empty() => Ok(lit.value()),
+        _ => Err(syn::Error::new(call.span(), "Expected the name, as a string literal."))
+    }
+}
+
+fn parse_stable_id(call: &ExprMethodCall) -> syn::Result<u32> {
+    match call.args.first() {
+        Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. })) if call.args.len() == 1 => lit.base10_parse::<u32>(),
+        _ => Err(syn::Error::new(call.span(), "Expected the id, as an integer literal."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::FsmFnInput;
+
+    #[test]
+    fn test_shadowed_transitions() {
+        let input = r#"
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.state::<Closed>()
+        .on_event::<Open>()
+        .transition_to::<Opened>();
+    fsm.state::<Closed>()
+        .on_event::<Open>()
+        .transition_to::<Jammed>()
+        .guard(|_ev, ctx, _| ctx.stuck);
+    fsm.state::<Opened>();
+    fsm.state::<Jammed>();
+    fsm.build()
+}"#;
+
+        let err = FsmFnInput::parse(Default::default(), input.parse().unwrap()).err().expect("the shadowed transition is rejected");
+        let errors: Vec<_> = err.into_iter().map(|e| (e.to_string(), e.span().start().line)).collect();
+
+        assert_eq!(2, errors.len());
+        assert_eq!("Conflicting transitions from the state 'Closed' on the event 'Open', this transition is never taken!", errors[0].0);
+        assert_eq!(9, errors[0].1);
+        assert!(errors[1].0.starts_with("The transition that is always taken instead, it has no guard."));
+        assert_eq!(6, errors[1].1);
+    }
+}
@@ -0,0 +1,401 @@
+//! Converts an SCXML statechart document into the builder function of a Finny machine, which is then
+//! generated just like the functions annotated with `#[finny_fsm]`.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Error, LitStr, parse::Parse};
+
+use crate::{codegen::generate_fsm_code, parse::FsmFnInput, utils::to_snake_case};
+
+use self::xml::{XmlElement, parse_xml};
+
+mod xml;
+
+/// The arguments of the `finny_scxml!` macro: the machine's type, the context's type and the
+/// path of the document, relative to the crate's manifest.
+pub struct ScxmlInput {
+    fsm_ty: syn::Ident,
+    context_ty: syn::Type,
+    path: LitStr
+}
+
+impl Parse for ScxmlInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let fsm_ty = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let context_ty = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let path = input.parse()?;
+        let _ = input.parse::<Option<syn::Token![,]>>()?;
+
+        Ok(Self { fsm_ty, context_ty, path })
+    }
+}
+
+struct ScxmlTimer {
+    event: syn::Ident,
+    timeout_ms: u64,
+    timer_ty: Option<syn::Ident>
+}
+
+struct ScxmlTransition {
+    events: Vec<syn::Ident>,
+    /// `None` for the targetless, internal transitions.
+    target: Option<syn::Ident>,
+    guard: Option<syn::Ident>,
+    cancel_timers: Vec<syn::Ident>
+}
+
+struct ScxmlState {
+    ty: syn::Ident,
+    id: String,
+    is_final: bool,
+    timers: Vec<ScxmlTimer>,
+    transitions: Vec<ScxmlTransition>
+}
+
+struct ScxmlRegion {
+    initial: syn::Ident,
+    states: Vec<ScxmlState>
+}
+
+pub fn generate_scxml_fsm(input: ScxmlInput) -> syn::Result<TokenStream> {
+    let span = input.path.span();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = std::path::Path::new(&manifest_dir).join(input.path.value());
+    let path_str = path.to_string_lossy().to_string();
+
+    let document = std::fs::read_to_string(&path)
+        .map_err(|e| Error::new(span, format!("Failed to read the SCXML document '{}': {}", path_str, e)))?;
+    let root = parse_xml(&document).map_err(|e| Error::new(span, format!("Invalid SCXML document: {}", e)))?;
+    if root.name != "scxml" {
+        return Err(Error::new(span, "The root element of the document has to be <scxml>."));
+    }
+
+    let regions = parse_regions(&root, span)?;
+
+    let all_states: Vec<_> = regions.iter().flat_map(|r| r.states.iter()).collect();
+    let timer_tys: Vec<_> = all_states.iter().flat_map(|s| s.timers.iter()).filter_map(|t| t.timer_ty.as_ref()).collect();
+
+    let mut events: Vec<&syn::Ident> = vec![];
+    let mut guards: Vec<&syn::Ident> = vec![];
+    for state in &all_states {
+        for transition in &state.transitions {
+            for event in &transition.events {
+                if !events.contains(&event) {
+                    events.push(event);
+                }
+            }
+            if let Some(ref guard) = transition.guard {
+                if !guards.contains(&guard) {
+                    guards.push(guard);
+                }
+            }
+            for cancel in &transition.cancel_timers {
+                if !timer_tys.contains(&cancel) {
+                    return Err(Error::new(span, format!("The transition of the state '{}' cancels an unknown timer '{}'.", state.id, cancel)));
+                }
+            }
+        }
+        for timer in &state.timers {
+            if !events.contains(&&timer.event) {
+                return Err(Error::new(span, format!("The event '{}' of the timer in the state '{}' isn't handled by any transition.", timer.event, state.id)));
+            }
+        }
+    }
+
+    let fsm_ty = &input.fsm_ty;
+    let ctx_ty = &input.context_ty;
+
+    let mut body = TokenStream::new();
+
+    let initial_states: Vec<_> = regions.iter().map(|r| &r.initial).collect();
+    if let [initial] = initial_states.as_slice() {
+        body.extend(quote! { fsm.initial_state::< #initial >(); });
+    } else {
+        body.extend(quote! { fsm.initial_states::<( #( #initial_states ),* )>(); });
+    }
+
+    for state in &all_states {
+        let ty = &state.ty;
+        body.extend(quote! { fsm.state::< #ty >(); });
+
+        if state.is_final {
+            body.extend(quote! { fsm.state::< #ty >().final_state(); });
+        }
+
+        for timer in &state.timers {
+            let event = &timer.event;
+            let timeout_ms = timer.timeout_ms;
+            let timer_ty = match timer.timer_ty {
+                Some(ref t) => quote! { .with_timer_ty::< #t >() },
+                None => TokenStream::new()
+            };
+
+            body.extend(quote! {
+                fsm.state::< #ty >()
+                    .on_entry_start_timer(|_ctx, settings| {
+                        settings.timeout = core::time::Duration::from_millis( #timeout_ms );
+                    }, |_ctx, _state| {
+                        Some( #event.into() )
+                    })
+                    #timer_ty;
+            });
+        }
+
+        for transition in &state.transitions {
+            let cancel = &transition.cancel_timers;
+            let (kind, action) = match transition.target {
+                Some(ref target) if *target == state.ty => (quote! { self_transition() }, quote! { |_ev, ctx, _state| }),
+                Some(ref target) => (quote! { transition_to::< #target >() }, quote! { |_ev, ctx, _from, _to| }),
+                None => (quote! { internal_transition() }, quote! { |_ev, ctx, _state| })
+            };
+
+            let guard = match transition.guard {
+                Some(ref guard) => quote! { .guard(|_ev, ctx, _states| { ctx. #guard () }) },
+                None => TokenStream::new()
+            };
+
+            let action = if cancel.is_empty() {
+                TokenStream::new()
+            } else {
+                quote! {
+                    .action( #action {
+                        #( let _ = ctx.timers.cancel::< #cancel >(); )*
+                    })
+                }
+            };
+
+            for event in &transition.events {
+                body.extend(quote! {
+                    fsm.state::< #ty >().on_event::< #event >(). #kind #guard #action;
+                });
+            }
+        }
+    }
+
+    let builder_fn = quote! {
+        fn build_fsm(mut fsm: FsmBuilder< #fsm_ty, #ctx_ty >) -> BuiltFsm {
+            #body
+            fsm.build()
+        }
+    };
+
+    let parsed = FsmFnInput::parse(TokenStream::new(), builder_fn.clone())?;
+    let fsm_code = generate_fsm_code(&parsed, TokenStream::new(), builder_fn)?;
+
+    let states = all_states.iter().map(|s| {
+        let ty = &s.ty;
+        let doc = format!("The SCXML state `{}`.", s.id);
+        quote! {
+            #[doc = #doc]
+            #[derive(Default)]
+            pub struct #ty;
+        }
+    });
+
+    let event_structs = events.iter().map(|ev| {
+        quote! {
+            #[derive(Clone, Debug, PartialEq)]
+            pub struct #ev;
+        }
+    });
+
+    let guards_trait = if guards.is_empty() {
+        TokenStream::new()
+    } else {
+        let guards_ty = syn::Ident::new(&format!("{}Guards", fsm_ty), fsm_ty.span());
+        let doc = format!("The conditions of the SCXML transitions, to be implemented by the context of `{}`.", fsm_ty);
+        quote! {
+            #[doc = #doc]
+            pub trait #guards_ty {
+                #( fn #guards (&self) -> bool; )*
+            }
+        }
+    };
+
+    Ok(quote! {
+        // rebuild the machine once the document changes
+        const _: &[u8] = include_bytes!( #path_str );
+
+        #( #states )*
+        #( #event_structs )*
+        #guards_trait
+
+        #fsm_code
+    })
+}
+
+/// The regions are either the direct child states of the document, or the compound child states
+/// of a top level `<parallel>` element.
+fn parse_regions(root: &XmlElement, span: Span) -> syn::Result<Vec<ScxmlRegion>> {
+    let parallel: Vec<_> = root.children_named("parallel").collect();
+
+    match parallel.as_slice() {
+        [] => Ok(vec![parse_region(root, span)?]),
+        [parallel] if !root.children.iter().any(is_state) => {
+            let regions = parallel.children.iter()
+                .filter(|c| c.name == "state")
+                .map(|c| parse_region(c, span))
+                .collect::<syn::Result<Vec<_>>>()?;
+            if regions.is_empty() {
+                return Err(Error::new(span, format!("The <parallel> element at line {} has no regions.", parallel.line)));
+            }
+            Ok(regions)
+        },
+        _ => Err(Error::new(span, "Only a single top level <parallel> element, without sibling states, is supported."))
+    }
+}
+
+fn is_state(element: &XmlElement) -> bool {
+    element.name == "state" || element.name == "final"
+}
+
+fn parse_region(node: &XmlElement, span: Span) -> syn::Result<ScxmlRegion> {
+    let state_elements: Vec<_> = node.children.iter().filter(|c| is_state(c)).collect();
+
+    let mut states = vec![];
+    for element in &state_elements {
+        states.push(parse_state(element, span)?);
+    }
+
+    let initial_id = match node.attr("initial") {
+        Some(initial) => Some(initial.to_string()),
+        None => node.children_named("initial")
+            .flat_map(|i| i.children_named("transition"))
+            .find_map(|t| t.attr("target").map(|t| t.to_string()))
+            .or_else(|| states.first().map(|s| s.id.clone()))
+    };
+
+    let initial = match initial_id {
+        Some(id) => states.iter().find(|s| s.id == id).map(|s| s.ty.clone())
+            .ok_or_else(|| Error::new(span, format!("The initial state '{}' at line {} isn't a child state.", id, node.line)))?,
+        None => return Err(Error::new(span, format!("The element <{}> at line {} has no states.", node.name, node.line)))
+    };
+
+    for state in &states {
+        for transition in &state.transitions {
+            if let Some(ref target) = transition.target {
+                if !states.iter().any(|s| s.ty == *target) {
+                    return Err(Error::new(span, format!("The target '{}' of the transition from the state '{}' isn't a state of the same region.", target, state.id)));
+                }
+            }
+        }
+    }
+
+    Ok(ScxmlRegion { initial, states })
+}
+
+fn parse_state(element: &XmlElement, span: Span) -> syn::Result<ScxmlState> {
+    let id = element.attr("id")
+        .ok_or_else(|| Error::new(span, format!("The state at line {} is missing its id.", element.line)))?
+        .to_string();
+
+    if element.children.iter().any(|c| is_state(c) || c.name == "parallel") {
+        return Err(Error::new(span, format!("The state '{}' is a compound state. Only the atomic states are supported, use a submachine for the nested states.", id)));
+    }
+
+    let mut timers = vec![];
+    for send in element.children_named("onentry").flat_map(|e| e.children_named("send")) {
+        let delay = match send.attr("delay") {
+            Some(delay) => delay,
+            None => continue
+        };
+        let event = send.attr("event")
+            .ok_or_else(|| Error::new(span, format!("The <send> element at line {} is missing its event.", send.line)))?;
+
+        timers.push(ScxmlTimer {
+            event: to_type_ident(event, send.line, span)?,
+            timeout_ms: parse_delay(delay).ok_or_else(|| Error::new(span, format!("Unsupported delay '{}' at line {}, expected a duration like '5s' or '250ms'.", delay, send.line)))?,
+            timer_ty: send.attr("id").map(|id| to_type_ident(id, send.line, span)).transpose()?
+        });
+    }
+
+    let mut transitions = vec![];
+    for transition in element.children_named("transition") {
+        let line = transition.line;
+        let events = transition.attr("event")
+            .ok_or_else(|| Error::new(span, format!("The transition at line {} has no event, the eventless transitions aren't supported.", line)))?
+            .split_whitespace()
+            .map(|ev| {
+                if ev.contains('*') {
+                    Err(Error::new(span, format!("The wildcard event '{}' at line {} isn't supported.", ev, line)))
+                } else {
+                    to_type_ident(ev, line, span)
+                }
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let target = match transition.attr("target").map(|t| t.split_whitespace().collect::<Vec<_>>()) {
+            None => None,
+            Some(targets) => match targets.as_slice() {
+                [] => None,
+                [target] => Some(to_type_ident(target, line, span)?),
+                _ => return Err(Error::new(span, format!("The transition at line {} has multiple targets.", line)))
+            }
+        };
+
+        let guard = match transition.attr("cond") {
+            Some(cond) => {
+                let name = to_snake_case(cond.trim());
+                Some(syn::parse_str::<syn::Ident>(&name)
+                    .map_err(|_| Error::new(span, format!("Unsupported condition '{}' at line {}, only the names of the guards are supported.", cond, line)))?)
+            },
+            None => None
+        };
+
+        let cancel_timers = transition.children_named("cancel")
+            .map(|c| {
+                let send_id = c.attr("sendid")
+                    .ok_or_else(|| Error::new(span, format!("The <cancel> element at line {} is missing its sendid.", c.line)))?;
+                to_type_ident(send_id, c.line, span)
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        transitions.push(ScxmlTransition { events, target, guard, cancel_timers });
+    }
+
+    Ok(ScxmlState {
+        ty: to_type_ident(&id, element.line, span)?,
+        id,
+        is_final: element.name == "final",
+        timers,
+        transitions
+    })
+}
+
+/// The SCXML names of the states and events as Rust types, `door.open` becomes `DoorOpen`.
+fn to_type_ident(name: &str, line: usize, span: Span) -> syn::Result<syn::Ident> {
+    let ty: String = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let mut chars = p.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new()
+            }
+        })
+        .collect();
+
+    syn::parse_str::<syn::Ident>(&ty)
+        .map_err(|_| Error::new(span, format!("Can't convert the name '{}' at line {} into a Rust type.", name, line)))
+}
+
+/// The CSS2 durations of the SCXML delays, in milliseconds.
+fn parse_delay(delay: &str) -> Option<u64> {
+    let delay = delay.trim();
+    let (value, multiplier) = if let Some(ms) = delay.strip_suffix("ms") {
+        (ms, 1.0)
+    } else if let Some(s) = delay.strip_suffix('s') {
+        (s, 1000.0)
+    } else {
+        return None;
+    };
+
+    let value: f64 = value.trim().parse().ok()?;
+    if value < 0.0 {
+        return None;
+    }
+    Some((value * multiplier).round() as u64)
+}
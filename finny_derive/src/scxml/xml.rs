@@ -0,0 +1,177 @@
+//! A minimal XML reader, just enough for the SCXML documents. Builds the tree of the elements and
+//! their attributes, the text content, comments, processing instructions and the DTD are skipped.
+
+#[derive(Debug, Clone)]
+pub struct XmlElement {
+    /// The local name of the element, without the namespace prefix.
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    /// The line of the element's start tag, for the error messages.
+    pub line: usize
+}
+
+impl XmlElement {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+pub fn parse_xml(input: &str) -> Result<XmlElement, String> {
+    let mut reader = XmlReader { input, pos: 0 };
+    reader.skip_misc()?;
+    let root = reader.element()?;
+    reader.skip_misc()?;
+    if reader.pos < input.len() {
+        return Err(reader.error("Unexpected content after the root element"));
+    }
+    Ok(root)
+}
+
+struct XmlReader<'a> {
+    input: &'a str,
+    pos: usize
+}
+
+impl<'a> XmlReader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn line(&self) -> usize {
+        self.input[..self.pos].matches('\n').count() + 1
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{}, at line {}.", msg, self.line())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_until(&mut self, end: &str) -> Result<(), String> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            },
+            None => Err(self.error(&format!("Missing the closing '{}'", end)))
+        }
+    }
+
+    /// Skips the whitespace, comments, processing instructions and the DTD between the elements.
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if rest.starts_with("<!") && !rest.starts_with("<![CDATA[") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), String> {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", s)))
+        }
+    }
+
+    fn element(&mut self) -> Result<XmlElement, String> {
+        let line = self.line();
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut attributes = vec![];
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(XmlElement { name: local_name(&name), attributes, children: vec![], line });
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+
+            let attr_name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q @ '"') | Some(q @ '\'') => q,
+                _ => return Err(self.error("Expected a quoted attribute value"))
+            };
+            self.pos += 1;
+            let end = self.rest().find(quote).ok_or_else(|| self.error("Unterminated attribute value"))?;
+            let value = unescape(&self.rest()[..end]);
+            self.pos += end + 1;
+            attributes.push((local_name(&attr_name), value));
+        }
+
+        let mut children = vec![];
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                let end_name = self.name()?;
+                if end_name != name {
+                    return Err(self.error(&format!("Expected the closing tag of '{}', found '{}'", name, end_name)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(XmlElement { name: local_name(&name), attributes, children, line });
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_until("]]>")?;
+            } else if rest.starts_with("<!--") || rest.starts_with("<?") {
+                self.skip_misc()?;
+            } else if rest.starts_with('<') {
+                children.push(self.element()?);
+            } else if rest.is_empty() {
+                return Err(self.error(&format!("Missing the closing tag of '{}'", name)));
+            } else {
+                // text content
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+            }
+        }
+    }
+}
+
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
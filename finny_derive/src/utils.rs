@@ -38,6 +38,24 @@ pub fn get_closure(call: &syn::ExprMethodCall) -> syn::Result<&syn::ExprClosure>
     }
 }
 
+/// A closure, or a named function that is shared between the transitions. The function is wrapped
+/// in a closure with the given number of arguments and its name is returned.
+pub fn get_closure_or_fn(call: &syn::ExprMethodCall, inputs: usize) -> syn::Result<(syn::ExprClosure, Option<String>)> {
+    match call.args.first() {
+        Some(syn::Expr::Closure(closure)) => Ok((closure.clone(), None)),
+        Some(syn::Expr::Path(path)) => {
+            let name = match path.path.segments.last() {
+                Some(segment) => segment.ident.to_string(),
+                None => return Err(syn::Error::new(path.span(), "Expected a function!"))
+            };
+            let args: Vec<_> = (0..inputs).map(|i| syn::Ident::new(&format!("arg{}", i), Span::call_site())).collect();
+            let closure = syn::parse2(quote! { | #(#args),* | #path ( #(#args),* ) })?;
+            Ok((closure, Some(name)))
+        },
+        _ => Err(syn::Error::new(call.span(), "Missing closure!"))
+    }
+}
+
 pub fn strip_generics(mut ty: syn::Type) -> syn::Type {
     match ty {
         syn::Type::Path(ref mut tp) => {
@@ -133,6 +151,117 @@ pub fn assert_no_generics(ty: &syn::Type) -> syn::Result<()> {
     Ok(())
 }
 
+/// The event types can have concrete type arguments, one variant of the events is generated for each of
+/// the instantiations, and lifetime arguments for the events that borrow their data.
+pub fn assert_event_ty(ty: &syn::Type) -> syn::Result<()> {
+    match ty {
+        syn::Type::Path(ref tp) if tp.qself.is_none() => {
+            for seg in &tp.path.segments {
+                match seg.arguments {
+                    syn::PathArguments::None => {},
+                    syn::PathArguments::AngleBracketed(ref args) => {
+                        for arg in &args.args {
+                            match arg {
+                                syn::GenericArgument::Lifetime(_) => {},
+                                syn::GenericArgument::Type(ty) => { event_variant_name(ty)?; },
+                                _ => { return Err(syn::Error::new(arg.span(), "Only the type and lifetime arguments are supported for the event types!")); }
+                            }
+                        }
+                    },
+                    _ => { return Err(syn::Error::new(ty.span(), "Generics aren't supported for state or event types!")); }
+                }
+            }
+            Ok(())
+        },
+        _ => Err(syn::Error::new(ty.span(), "Unsupported type."))
+    }
+}
+
+/// Has lifetime arguments, like `Packet<'_>`.
+pub fn is_borrowed_ty(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(ref tp) => tp.path.segments.iter().any(|seg| match seg.arguments {
+            syn::PathArguments::AngleBracketed(ref args) => args.args.iter().any(|a| matches!(a, syn::GenericArgument::Lifetime(_))),
+            _ => false
+        }),
+        _ => false
+    }
+}
+
+fn event_variant_name(ty: &syn::Type) -> syn::Result<String> {
+    let tp = match ty {
+        syn::Type::Path(ref tp) if tp.qself.is_none() => tp,
+        _ => { return Err(syn::Error::new(ty.span(), "Only the named types are supported as the arguments of the event types!")); }
+    };
+
+    let mut name = String::new();
+    if let Some(seg) = tp.path.segments.last() {
+        let ident = seg.ident.to_string();
+        let mut chars = ident.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+
+        if let syn::PathArguments::AngleBracketed(ref args) = seg.arguments {
+            for arg in &args.args {
+                if let syn::GenericArgument::Type(ty) = arg {
+                    name.push_str(&event_variant_name(ty)?);
+                }
+            }
+        }
+    }
+
+    Ok(name)
+}
+
+/// The variant of the events enum, the type arguments of generic events are appended to their name,
+/// like `ReceivedU32` for `Received<u32>`.
+pub fn event_variant(ty: &syn::Type) -> syn::Ident {
+    let name = event_variant_name(ty).unwrap_or_else(|_| tokens_to_string(&strip_generics(ty.clone())));
+    syn::Ident::new(&name, ty.span())
+}
+
+/// The name of the event, with its type arguments and without its lifetimes, like `Received<u32>`.
+pub fn event_name(ty: &syn::Type) -> String {
+    let mut ty = ty.clone();
+    if let syn::Type::Path(ref mut tp) = ty {
+        for seg in &mut tp.path.segments {
+            if let syn::PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+                args.args = args.args.iter().filter(|a| !matches!(a, syn::GenericArgument::Lifetime(_))).cloned().collect();
+                if args.args.is_empty() {
+                    seg.arguments = syn::PathArguments::None;
+                }
+            }
+        }
+    }
+
+    tokens_to_string(&ty).replace(' ', "")
+}
+
+/// The id of a state or an event that wasn't declared explicitly, the FNV-1a hash of its name. It doesn't
+/// change when the builder is reordered.
+pub fn stable_id(name: &str) -> u32 {
+    name.bytes().fold(0x811c9dc5u32, |hash, b| (hash ^ b as u32).wrapping_mul(0x01000193))
+}
+
+/// Replaces all of the type's lifetime arguments with this lifetime.
+pub fn with_lifetime(mut ty: syn::Type, lifetime: &syn::Lifetime) -> syn::Type {
+    if let syn::Type::Path(ref mut tp) = ty {
+        for seg in &mut tp.path.segments {
+            if let syn::PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+                for arg in &mut args.args {
+                    if let syn::GenericArgument::Lifetime(ref mut l) = arg {
+                        *l = lifetime.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    ty
+}
+
 pub fn get_ty_ident(ty: &syn::Type) -> syn::Result<&syn::Ident> {
     match ty {
         syn::Type::Path(syn::TypePath { path, .. }) if path.segments.len() == 1 => {
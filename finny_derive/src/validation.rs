@@ -4,7 +4,7 @@ use petgraph::{Graph, graph::NodeIndex, visit::Dfs};
 use proc_macro2::Span;
 use syn::spanned::Spanned;
 
-use crate::{parse::{FsmDeclarations, FsmRegion, ValidatedFsm}, parse_fsm::FsmCodegenOptions, utils::tokens_to_string};
+use crate::{parse::{FsmDeclarations, FsmRegion, FsmStateHistory, FsmStateKind, FsmStateTransition, FsmTransitionType, ValidatedFsm}, parse_fsm::FsmCodegenOptions, utils::tokens_to_string};
 
 #[derive(Debug)]
 struct TypeNode {
@@ -24,7 +24,17 @@ pub fn create_regions(decl: FsmDeclarations, options: FsmCodegenOptions) -> syn:
             })
     }
 
-    for (ty, _) in &decl.states {
+    for (ty, state) in &decl.states {
+        if state.history != FsmStateHistory::None && state.kind == FsmStateKind::Normal {
+            return Err(syn::Error::new(ty.span(), "History is only supported on submachine states!"));
+        }
+        if options.transactional && state.kind != FsmStateKind::Normal {
+            return Err(syn::Error::new(ty.span(), "A transactional machine can't have submachines!"));
+        }
+        if decl.resources.is_some() && state.kind != FsmStateKind::Normal {
+            return Err(syn::Error::new(ty.span(), "A machine with resources can't have submachines, they don't receive the resources!"));
+        }
+
         get_or_add_node(&mut nodes, &mut graph, ty);
     }
 
@@ -57,9 +67,106 @@ pub fn create_regions(decl: FsmDeclarations, options: FsmCodegenOptions) -> syn:
         }
     }
 
+    // the fault state is entered on errors, it belongs to the region of the states it transitions to
+    if let Some(ref error_state) = decl.error_state {
+        if let Some(FsmStateKind::SubMachine(_)) = decl.states.get(error_state).map(|s| &s.kind) {
+            return Err(syn::Error::new(error_state.span(), "The fault state can't be a submachine!"));
+        }
+    }
+
+    // as are the entry points, entered by the parent machine, and the states entered by the forks
+    let mut entry_points: Vec<_> = decl.states.values().filter(|s| s.is_entry_point).map(|s| s.ty.clone()).collect();
+    entry_points.sort_by_key(tokens_to_string);
+
+    let mut forked_states: Vec<_> = decl.transitions.iter().filter_map(|t| match &t.ty {
+        FsmTransitionType::StateTransition(FsmStateTransition { action, .. }) => Some(action.forks.iter().cloned()),
+        _ => None
+    }).flatten().collect();
+    forked_states.sort_by_key(tokens_to_string);
+    forked_states.dedup();
+
+    for entered_state in decl.error_state.iter().chain(entry_points.iter()).chain(forked_states.iter()) {
+        let entered_node = get_or_add_node(&mut nodes, &mut graph, entered_state);
+        if graph[entered_node].region.is_none() {
+            let mut dfs = Dfs::new(&graph, entered_node);
+            let region_id = {
+                let mut region_id = None;
+                while let Some(idx) = dfs.next(&graph) {
+                    if graph[idx].region.is_some() {
+                        region_id = graph[idx].region;
+                        break;
+                    }
+                }
+                match region_id {
+                    Some(region_id) => region_id,
+                    None if forked_states.contains(entered_state) => {
+                        return Err(syn::Error::new(entered_state.span(), format!("The region of the forked state '{}' is unknown, add a transition from it into the states of its region!",
+                            tokens_to_string(entered_state))));
+                    },
+                    None => 0
+                }
+            };
+
+            let mut dfs = Dfs::new(&graph, entered_node);
+            while let Some(idx) = dfs.next(&graph) {
+                match graph[idx].region {
+                    None => graph[idx].region = Some(region_id),
+                    Some(r) if r != region_id => {
+                        let s = &graph[idx].state;
+                        return Err(syn::Error::new(s.span(), format!("The state '{}' was already matched into another region, check the transition graph of the states!",
+                        tokens_to_string(s))));
+                    },
+                    _ => ()
+                }
+            }
+        }
+    }
+
+    // report all of the dead states at once
+    let mut unreachable: Option<syn::Error> = None;
     for node in graph.raw_nodes() {
         if node.weight.region == None {
-            return Err(syn::Error::new(node.weight.state.span(), "Unreachable state! Add some transitions that will make this state reachable!"));
+            let s = &node.weight.state;
+            let err = syn::Error::new(s.span(), format!("Unreachable state '{}'! It can't be entered from the initial states using any of the transitions, add some transitions that will make this state reachable!",
+                tokens_to_string(s)));
+            match unreachable {
+                Some(ref mut e) => e.combine(err),
+                None => unreachable = Some(err)
+            }
+        }
+    }
+
+    if let Some(err) = unreachable {
+        return Err(err);
+    }
+
+    // the forks and the joins synchronize the transition's region with the other ones
+    for transition in &decl.transitions {
+        if let FsmTransitionType::StateTransition(FsmStateTransition { action, state_from, .. }) = &transition.ty {
+            let region_of = |ty: &syn::Type| graph[nodes[ty]].region;
+            let own_region = match state_from.get_fsm_state() {
+                Ok(from) => region_of(&from.ty),
+                Err(_) => continue
+            };
+
+            for synchronized in [&action.forks, &action.joins] {
+                let mut regions = HashSet::new();
+                for ty in synchronized.iter() {
+                    let region = region_of(ty);
+                    if region == own_region {
+                        return Err(syn::Error::new(ty.span(), "The forks and the joins have to refer to the states of the other regions!"));
+                    }
+                    if !regions.insert(region) {
+                        return Err(syn::Error::new(ty.span(), "Only one state of each region can be forked into or joined!"));
+                    }
+                }
+            }
+        }
+    }
+
+    for (ty, _) in &decl.region_priorities {
+        if !decl.initial_states.contains(ty) {
+            return Err(syn::Error::new(ty.span(), "The regions are referred to by their initial states!"));
         }
     }
 
@@ -94,6 +201,7 @@ pub fn create_regions(decl: FsmDeclarations, options: FsmCodegenOptions) -> syn:
         regions.push(FsmRegion {
             initial_state: initial_state.clone(),
             region_id,
+            priority: decl.region_priorities.iter().find(|(ty, _)| ty == initial_state).map(|(_, p)| *p).unwrap_or(0),
             transitions,
             states: states.into_iter().map(|ty| decl.states.get(&ty).unwrap()).cloned().collect()
         });
@@ -103,6 +211,37 @@ pub fn create_regions(decl: FsmDeclarations, options: FsmCodegenOptions) -> syn:
         events: decl.events,
         states: decl.states,
         regions,
-        codegen_options: options
+        codegen_options: options,
+        unhandled_event: decl.unhandled_event,
+        error_state: decl.error_state,
+        resources: decl.resources,
+        outputs: decl.outputs
     })
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::parse::FsmFnInput;
+
+    #[test]
+    fn test_unreachable_states() {
+        let input = r#"
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.state::<Closed>().on_event::<Open>().transition_to::<Opened>();
+    fsm.state::<Opened>();
+    fsm.state::<Broken>();
+    fsm.state::<Jammed>();
+    fsm.build()
+}"#;
+
+        let err = FsmFnInput::parse(Default::default(), input.parse().unwrap()).err().expect("the dead states are rejected");
+        let mut errors: Vec<_> = err.into_iter().map(|e| (e.to_string(), e.span().start().line)).collect();
+        errors.sort();
+
+        assert_eq!(2, errors.len());
+        assert!(errors[0].0.starts_with("Unreachable state 'Broken'!"));
+        assert_eq!(6, errors[0].1);
+        assert!(errors[1].0.starts_with("Unreachable state 'Jammed'!"));
+        assert_eq!(7, errors[1].1);
+    }
+}
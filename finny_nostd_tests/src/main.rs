@@ -1,7 +1,11 @@
 // disabling until no_std + alloc becomes stable
 // #![no_std]
 
-use finny::{finny_fsm, inspect::null::InspectNull, FsmEventQueueArray, FsmFactory, FsmTimersNull};
+use finny::{finny_fsm, inspect::null::InspectNull, spsc::FsmEventQueueSpsc, FsmEventQueueArray, FsmFactory, FsmFrontend, FsmStatic, FsmStaticQueue, FsmTimersNull};
+
+// the snapshots of the machines, built with the allocator
+#[cfg(feature = "alloc")]
+mod pump;
 
 pub fn main() {
     // Since we are passing a C string the final null character is mandatory
@@ -18,6 +22,28 @@ pub fn main() {
         let mut fsm = StateMachine::new_with(ctx, queue, inspect, timers).unwrap();
         fsm.start().unwrap();
     }
+
+    #[cfg(feature = "alloc")]
+    {
+        let ctx = StateMachineContext::default();
+        let queue = finny::FsmEventQueueVec::new();
+        let inspect = InspectNull::new();
+        let timers = FsmTimersNull;
+        let mut fsm = StateMachine::new_with(ctx, queue, inspect, timers).unwrap();
+        fsm.start().unwrap();
+    }
+
+    {
+        static QUEUE: FsmStaticQueue<StateMachine, 16> = FsmStatic::new();
+        static FSM: FsmStatic<FsmFrontend<StateMachine, FsmEventQueueSpsc<'static, StateMachine, 16, 4>, InspectNull, FsmTimersNull>> = FsmStatic::new();
+
+        let (_producer, queue) = QUEUE.split::<StateMachine, 4>().unwrap();
+        let fsm = FSM.init(StateMachine::new_with(StateMachineContext::default(), queue, InspectNull::new(), FsmTimersNull).unwrap());
+        fsm.start().unwrap();
+    }
+
+    #[cfg(feature = "alloc")]
+    pump::run();
 }
 
 ///////////////////////////////////////////////////
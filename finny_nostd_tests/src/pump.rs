@@ -0,0 +1,68 @@
+use finny::{bundled::serde::{Deserialize, Serialize}, finny_fsm, fsm_snapshot_from_slice, inspect::null::InspectNull, FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmSnapshot, FsmTimersNull};
+
+pub fn run() {
+    // the deferred events survive the power loss
+    let mut fsm = Pump::new_with(PumpContext, FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull).unwrap();
+    fsm.start().unwrap();
+    fsm.dispatch(Prime).unwrap();
+    fsm.dispatch(Dose(3)).unwrap();
+
+    let mut flash = [0xffu8; 64];
+    let len = fsm.snapshot_to_slice(&mut flash).unwrap().len();
+    let snapshot: FsmSnapshot<Pump, FsmEventQueueVec<Pump>> = fsm_snapshot_from_slice(&flash[..len]).unwrap();
+    assert_eq!(1, snapshot.deferred.len());
+
+    let mut fsm = Pump::restore_with(PumpContext, snapshot, InspectNull::new(), FsmTimersNull).unwrap();
+    fsm.dispatch(Primed).unwrap();
+    assert_eq!(FsmCurrentState::State(PumpCurrentState::Ready), fsm.get_current_states()[0]);
+    assert_eq!(3, fsm.get_state::<Ready>().dosed);
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct PumpContext;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct Priming;
+#[derive(Default, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct Ready {
+    dosed: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct Prime;
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct Primed;
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(crate = "finny::bundled::serde")]
+pub struct Dose(usize);
+
+#[finny_fsm]
+fn build_pump_fsm(mut fsm: FsmBuilder<Pump, PumpContext>) -> BuiltFsm {
+    fsm.derive_serde();
+    fsm.initial_state::<Ready>();
+
+    fsm.state::<Ready>()
+        .on_event::<Prime>()
+        .transition_to::<Priming>();
+
+    fsm.state::<Ready>()
+        .on_event::<Dose>()
+        .internal_transition()
+        .action(|ev, _ctx, ready| {
+            ready.dosed += ev.0;
+        });
+
+    fsm.state::<Priming>()
+        .defer::<Dose>();
+
+    fsm.state::<Priming>()
+        .on_event::<Primed>()
+        .transition_to::<Ready>();
+
+    fsm.build()
+}
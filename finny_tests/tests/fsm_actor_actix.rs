@@ -0,0 +1,64 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, actor::{FsmGetCurrentStates, FsmMessage}, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct LampContext {
+    switched: usize
+}
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct On;
+
+#[derive(Clone, Debug)]
+pub struct Switch;
+#[derive(Clone, Debug)]
+pub struct Expire;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Lamp, LampContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+
+    fsm.state::<Off>()
+        .on_event::<Switch>()
+        .transition_to::<On>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.switched += 1;
+        });
+
+    fsm.state::<On>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_millis(30);
+        }, |_ctx, _state| {
+            Some( Expire.into() )
+        })
+        .with_timer_ty::<ExpireTimer>();
+
+    fsm.state::<On>()
+        .on_event::<Expire>()
+        .transition_to::<Off>();
+
+    fsm.build()
+}
+
+#[actix::test]
+async fn test_actix_actor() -> FsmResult<()> {
+    let addr = Lamp::new(LampContext::default())?.start_actor();
+
+    addr.send(FsmMessage(Switch)).await.unwrap()?;
+    let states = addr.send(FsmGetCurrentStates::new()).await.unwrap();
+    assert_eq!(FsmCurrentState::State(LampCurrentState::On), states[0]);
+
+    assert!(matches!(addr.send(FsmMessage(Switch)).await.unwrap(), Err(FsmError::NoTransition { .. })));
+
+    // switched off by the timer, polled by the actor
+    actix::clock::sleep(Duration::from_millis(150)).await;
+    let states = addr.send(FsmGetCurrentStates::new()).await.unwrap();
+    assert_eq!(FsmCurrentState::State(LampCurrentState::Off), states[0]);
+
+    Ok(())
+}
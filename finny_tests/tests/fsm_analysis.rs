@@ -0,0 +1,99 @@
+extern crate finny;
+
+use finny::{FsmAnalysis, FsmInfoEvent, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct OrderContext {
+    paid: bool
+}
+
+#[derive(Default)]
+pub struct Created;
+#[derive(Default)]
+pub struct Paid;
+#[derive(Default)]
+pub struct Shipped;
+#[derive(Default)]
+pub struct Cancelled;
+#[derive(Default)]
+pub struct Archived;
+
+#[derive(Clone)]
+pub struct Pay;
+#[derive(Clone)]
+pub struct Ship;
+#[derive(Clone)]
+pub struct Cancel;
+#[derive(Clone)]
+pub struct Touch;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Order, OrderContext>) -> BuiltFsm {
+    fsm.initial_state::<Created>();
+
+    fsm.state::<Created>()
+        .on_event::<Pay>()
+        .transition_to::<Paid>();
+
+    fsm.state::<Created>()
+        .on_event::<Cancel>()
+        .transition_to::<Cancelled>();
+
+    fsm.state::<Created>()
+        .on_event::<Touch>()
+        .self_transition();
+
+    fsm.state::<Paid>()
+        .on_event::<Ship>()
+        .transition_to::<Shipped>()
+        .guard(|_ev, ctx, _| ctx.paid);
+
+    fsm.state::<Paid>()
+        .on_event::<Cancel>()
+        .transition_to::<Cancelled>();
+
+    fsm.state::<Shipped>()
+        .final_state();
+
+    fsm.state::<Cancelled>()
+        .on_event::<Touch>()
+        .transition_to::<Archived>();
+
+    fsm.state::<Archived>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_reachability() {
+    let analysis = FsmAnalysis::new(Order::fsm_info());
+
+    assert!(analysis.is_reachable("Created", "Shipped"));
+    assert!(analysis.is_reachable("Paid", "Cancelled"));
+    assert!(!analysis.is_reachable("Shipped", "Created"));
+    assert!(!analysis.is_reachable("Cancelled", "Paid"));
+
+    assert_eq!(vec!["Archived", "Cancelled", "Paid", "Shipped"], analysis.reachable_states("Paid"));
+    assert_eq!(vec!["Archived"], analysis.dead_end_states());
+}
+
+#[test]
+fn test_shortest_path() {
+    let analysis = FsmAnalysis::new(Order::fsm_info());
+
+    let path = analysis.shortest_path_from_start("Shipped").unwrap();
+    assert_eq!(vec!["Pay", "Ship"], path.events());
+    assert_eq!(vec!["Stopped", "Created", "Paid", "Shipped"], path.states());
+    assert_eq!(FsmInfoEvent::Start, path.steps[0].event);
+    assert!(path.is_guarded());
+
+    let path = analysis.shortest_path("Created", "Cancelled").unwrap();
+    assert_eq!(vec!["Cancel"], path.events());
+    assert!(!path.is_guarded());
+
+    let path = analysis.shortest_path("Created", "Archived").unwrap();
+    assert_eq!(vec!["Cancel", "Touch"], path.events());
+
+    assert!(analysis.shortest_path("Shipped", "Paid").is_none());
+    assert!(analysis.shortest_path("Created", "Created").unwrap().steps.is_empty());
+}
@@ -0,0 +1,66 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct MachineContext {
+    aborts: usize
+}
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Heating;
+#[derive(Default)]
+pub struct Maintenance;
+
+#[derive(Clone)]
+pub struct Heat;
+#[derive(Clone)]
+pub struct Service;
+#[derive(Clone)]
+pub struct Shutdown;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Machine, MachineContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>().on_event::<Heat>().transition_to::<Heating>();
+    fsm.state::<Idle>().on_event::<Service>().transition_to::<Maintenance>();
+    fsm.state::<Heating>();
+    fsm.state::<Maintenance>();
+    fsm.state::<Off>();
+
+    fsm.any_state()
+        .except::<Maintenance>()
+        .on_event::<Shutdown>()
+        .transition_to::<Off>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.aborts += 1;
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_any_state() -> FsmResult<()> {
+    let mut fsm = Machine::new(MachineContext::default())?;
+
+    fsm.start()?;
+    fsm.dispatch(Heat)?;
+    fsm.dispatch(Shutdown)?;
+    assert_eq!(FsmCurrentState::State(MachineCurrentState::Off), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.aborts);
+
+    // the target state doesn't get the transition
+    assert!(fsm.dispatch(Shutdown).is_err());
+
+    let mut fsm = Machine::new(MachineContext::default())?;
+    fsm.start()?;
+    fsm.dispatch(Service)?;
+    assert!(fsm.dispatch(Shutdown).is_err());
+    assert_eq!(FsmCurrentState::State(MachineCurrentState::Maintenance), fsm.get_current_states()[0]);
+
+    Ok(())
+}
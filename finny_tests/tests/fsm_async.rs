@@ -0,0 +1,120 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct AsyncContext {
+    log: Vec<&'static str>,
+    limit: usize
+}
+
+#[derive(Default)]
+pub struct StateA;
+#[derive(Default)]
+pub struct StateB {
+    value: usize
+}
+#[derive(Clone)]
+pub struct EventGo { n: usize }
+#[derive(Clone)]
+pub struct EventPing;
+
+async fn write_to_db(ctx: &mut AsyncContext, msg: &'static str) {
+    tokio::task::yield_now().await;
+    ctx.log.push(msg);
+}
+
+async fn read_limit(ctx: &AsyncContext) -> usize {
+    tokio::task::yield_now().await;
+    ctx.limit
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<AsyncMachine, AsyncContext>) -> BuiltFsm {
+    fsm.initial_state::<StateA>();
+
+    fsm.state::<StateA>()
+        .on_entry(|_, ctx| {
+            ctx.log.push("enter a");
+        })
+        .on_entry_async(|_, ctx| async move {
+            write_to_db(ctx, "enter a async").await;
+        })
+        .on_exit_async(async |_, ctx| {
+            write_to_db(ctx, "exit a async").await;
+        })
+        .on_event::<EventGo>()
+        .transition_to::<StateB>()
+        .guard_async(|ev, ctx, _| async move {
+            ev.n > read_limit(ctx).await
+        })
+        .action_async(|ev, ctx, _, state_b| async move {
+            write_to_db(ctx, "action").await;
+            state_b.value = ev.n;
+        });
+
+    fsm.state::<StateB>()
+        .on_event::<EventPing>()
+        .internal_transition()
+        .action_async(|_, ctx, state| async move {
+            write_to_db(ctx, "ping").await;
+            state.value += 1;
+        });
+
+    fsm.build()
+}
+
+#[tokio::test]
+async fn test_async_dispatch() -> FsmResult<()> {
+    let mut fsm = AsyncMachine::new(AsyncContext { log: vec![], limit: 10 })?;
+
+    fsm.start_async().await?;
+    assert_eq!(&["enter a", "enter a async"], fsm.log.as_slice());
+
+    let res = fsm.dispatch_async(EventGo { n: 5 }).await;
+    assert_eq!(Err(FsmError::NoTransition { state: "StateA", event: "EventGo" }), res);
+    assert_eq!(FsmCurrentState::State(AsyncMachineCurrentState::StateA), fsm.get_current_states()[0]);
+
+    fsm.dispatch_async(EventGo { n: 42 }).await?;
+    assert_eq!(FsmCurrentState::State(AsyncMachineCurrentState::StateB), fsm.get_current_states()[0]);
+    assert_eq!(&["enter a", "enter a async", "exit a async", "action"], fsm.log.as_slice());
+    let state_b: &StateB = fsm.get_state();
+    assert_eq!(42, state_b.value);
+
+    fsm.dispatch_async(EventPing).await?;
+    let state_b: &StateB = fsm.get_state();
+    assert_eq!(43, state_b.value);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_async_machine_fails_sync_dispatch() -> FsmResult<()> {
+    let mut fsm = AsyncMachine::new(AsyncContext { log: vec![], limit: 10 })?;
+
+    assert_eq!(Err(FsmError::AsyncRequired), fsm.start());
+    assert!(fsm.log.is_empty());
+
+    fsm.start_async().await?;
+    assert_eq!(Err(FsmError::AsyncRequired), fsm.dispatch(EventGo { n: 42 }));
+    assert_eq!(FsmCurrentState::State(AsyncMachineCurrentState::StateA), fsm.get_current_states()[0]);
+    assert_eq!(&["enter a", "enter a async"], fsm.log.as_slice());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_async_dispatch_spawned() -> FsmResult<()> {
+    let mut fsm = AsyncMachine::new(AsyncContext { log: vec![], limit: 0 })?;
+
+    let handle = tokio::spawn(async move {
+        fsm.start_async().await?;
+        fsm.dispatch_async(EventGo { n: 1 }).await?;
+        Ok::<_, FsmError>(fsm.get_current_states()[0])
+    });
+
+    let state = handle.await.unwrap()?;
+    assert_eq!(FsmCurrentState::State(AsyncMachineCurrentState::StateB), state);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct ParserContext {
+    line: Vec<u8>,
+    lines: usize
+}
+
+#[derive(Default)]
+pub struct Reading;
+#[derive(Default)]
+pub struct Done;
+
+#[derive(Clone)]
+pub struct Byte(u8);
+#[derive(Clone)]
+pub struct LineEnd;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<LineParser, ParserContext>) -> BuiltFsm {
+    fsm.initial_state::<Reading>();
+
+    fsm.state::<Reading>()
+        .on_event::<Byte>()
+        .internal_transition()
+        .guard(|ev, _, _| ev.0 != b'\n')
+        .action(|ev, ctx, _| {
+            ctx.line.push(ev.0);
+        });
+
+    fsm.state::<Reading>()
+        .on_event::<Byte>()
+        .transition_to::<Done>()
+        .guard(|ev, _, _| ev.0 == b'\n')
+        .action(|_, ctx, _, _| {
+            ctx.queue.enqueue(LineEnd).unwrap();
+        });
+
+    fsm.state::<Done>()
+        .on_event::<LineEnd>()
+        .internal_transition()
+        .action(|_, ctx, _| {
+            ctx.lines += 1;
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_dispatch_all() -> FsmResult<()> {
+    let mut fsm = LineParser::new(ParserContext::default())?;
+    fsm.start()?;
+
+    let results: Vec<_> = fsm.dispatch_all(b"ab\nc".iter().map(|b| Byte(*b)));
+
+    assert_eq!(4, results.len());
+    assert!(results[..3].iter().all(|r| r.is_ok()));
+    assert_eq!(Err(FsmError::NoTransition { state: "Done", event: "Byte" }), results[3]);
+
+    // the enqueued event was dispatched after the batch
+    assert_eq!(FsmCurrentState::State(LineParserCurrentState::Done), fsm.get_current_states()[0]);
+    assert_eq!(b"ab".to_vec(), fsm.line);
+    assert_eq!(1, fsm.lines);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+extern crate finny;
+
+use finny::{FsmBackendImpl, FsmCurrentState, FsmError, FsmEvent, FsmFactory, FsmMiddleware, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct ModemContext {
+    received: usize,
+    dropped: usize
+}
+
+#[derive(Default)]
+pub struct Listening;
+#[derive(Default)]
+pub struct Overflow;
+
+/// Borrows the receive buffer, only lives for its dispatch.
+pub struct PacketReceived<'a> {
+    pub data: &'a [u8]
+}
+
+#[derive(Clone)]
+pub struct Reset;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Modem, ModemContext>) -> BuiltFsm {
+    fsm.initial_state::<Listening>();
+
+    fsm.state::<Listening>()
+        .on_event::<PacketReceived<'_>>()
+        .internal_transition()
+        .guard(|ev, _ctx, _states| ev.data.len() <= 4)
+        .action(|ev, ctx, _state| {
+            ctx.received += ev.data.len();
+        });
+
+    fsm.state::<Listening>()
+        .on_event::<PacketReceived<'_>>()
+        .transition_to::<Overflow>()
+        .action(|ev, ctx, _from, _to| {
+            ctx.dropped += ev.data.len();
+            ctx.queue.enqueue(Reset).unwrap();
+        });
+
+    fsm.state::<Overflow>()
+        .on_event::<Reset>()
+        .transition_to::<Listening>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_borrowed_events() -> FsmResult<()> {
+    let mut fsm = Modem::new(ModemContext::default())?;
+    fsm.start()?;
+
+    let buffer = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    fsm.dispatch_borrowed(PacketReceived { data: &buffer[..3] })?;
+    assert_eq!(3, fsm.received);
+
+    // the owned reset event is enqueued by the action and dispatched to completition
+    fsm.dispatch_borrowed(PacketReceived { data: &buffer })?;
+    assert_eq!(8, fsm.dropped);
+    assert_eq!(FsmCurrentState::State(ModemCurrentState::Listening), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_borrowed_event_not_handled() -> FsmResult<()> {
+    let mut fsm = Modem::new(ModemContext::default())?;
+    assert_eq!(Err(FsmError::NoTransition { state: "Stopped", event: "PacketReceived" }), fsm.dispatch_borrowed(PacketReceived { data: &[1] }));
+
+    Ok(())
+}
+
+struct Firewall;
+
+impl FsmMiddleware<Modem> for Firewall {
+    fn before_dispatch(&mut self, event: FsmEvent<ModemEvents, ModemTimers>, _backend: &FsmBackendImpl<Modem>) -> FsmResult<Option<FsmEvent<ModemEvents, ModemTimers>>> {
+        Ok(Some(event))
+    }
+}
+
+#[test]
+fn test_borrowed_event_with_middlewares() -> FsmResult<()> {
+    let mut fsm = Modem::new(ModemContext::default())?;
+    fsm.start()?;
+    fsm.middlewares.add(Firewall);
+
+    // the firewall can't inspect the borrowed packet, so it isn't let through
+    assert_eq!(Err(FsmError::NotSupported), fsm.dispatch_borrowed(PacketReceived { data: &[1, 2] }));
+    assert_eq!(0, fsm.received);
+
+    fsm.middlewares.clear();
+    fsm.dispatch_borrowed(PacketReceived { data: &[1, 2] })?;
+    assert_eq!(2, fsm.received);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct VendingContext {
+    price: usize,
+    credit: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Vending;
+#[derive(Default)]
+pub struct Collecting;
+#[derive(Default)]
+pub struct Rejected;
+
+#[derive(Debug, Clone)]
+pub struct Coin(usize);
+#[derive(Debug, Clone)]
+pub struct Reset;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<VendingMachine, VendingContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Coin>()
+        .transition_to::<Vending>()
+        .guard(|ev, ctx, _| ev.0 >= ctx.price)
+        .transition_to::<Collecting>()
+        .guard(|ev, _, _| ev.0 > 0)
+        .action(|ev, ctx, _, _| {
+            ctx.credit += ev.0;
+        })
+        .otherwise::<Rejected>();
+
+    fsm.state::<Vending>();
+    fsm.state::<Collecting>();
+    fsm.state::<Rejected>();
+
+    fsm.state::<Vending>().on_event::<Reset>().transition_to::<Idle>();
+    fsm.state::<Collecting>().on_event::<Reset>().transition_to::<Idle>();
+    fsm.state::<Rejected>().on_event::<Reset>().transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_choice() -> FsmResult<()> {
+    let mut fsm = VendingMachine::new(VendingContext { price: 10, credit: 0 })?;
+    fsm.start()?;
+
+    fsm.dispatch(Coin(20))?;
+    assert_eq!(FsmCurrentState::State(VendingMachineCurrentState::Vending), fsm.get_current_states()[0]);
+    assert_eq!(0, fsm.credit);
+    fsm.dispatch(Reset)?;
+
+    fsm.dispatch(Coin(5))?;
+    assert_eq!(FsmCurrentState::State(VendingMachineCurrentState::Collecting), fsm.get_current_states()[0]);
+    assert_eq!(5, fsm.credit);
+    fsm.dispatch(Reset)?;
+
+    fsm.dispatch(Coin(0))?;
+    assert_eq!(FsmCurrentState::State(VendingMachineCurrentState::Rejected), fsm.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+extern crate finny;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmInfoEvent, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::coverage::InspectCoverage};
+
+#[derive(Debug, Default)]
+pub struct TurnstileContext {
+    coins: usize
+}
+
+#[derive(Default)]
+pub struct Locked;
+#[derive(Default)]
+pub struct Unlocked;
+
+#[derive(Clone)]
+pub struct Coin;
+#[derive(Clone)]
+pub struct Push;
+#[derive(Clone)]
+pub struct Kick;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Turnstile, TurnstileContext>) -> BuiltFsm {
+    fsm.initial_state::<Locked>();
+
+    fsm.state::<Locked>()
+        .on_event::<Coin>()
+        .transition_to::<Unlocked>()
+        .guard(|_, ctx, _| ctx.coins > 0)
+        .action(|_, ctx, _, _| {
+            ctx.coins -= 1;
+        });
+
+    fsm.state::<Unlocked>()
+        .on_event::<Push>()
+        .transition_to::<Locked>();
+
+    fsm.state::<Unlocked>()
+        .on_event::<Kick>()
+        .transition_to::<Locked>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_transition_coverage() -> FsmResult<()> {
+    let coverage = InspectCoverage::new();
+    let mut fsm = Turnstile::new_with(TurnstileContext { coins: 1 }, FsmEventQueueVec::new(), coverage.clone(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Coin)?;
+    fsm.dispatch(Push)?;
+
+    let report = coverage.report(Turnstile::fsm_info());
+    assert!(!report.is_complete());
+
+    let unexercised: Vec<_> = report.unexercised().map(|t| t.transition.event).collect();
+    assert_eq!(vec![FsmInfoEvent::Event("Kick")], unexercised);
+    let guards: Vec<_> = report.unexercised_guards().map(|t| t.transition.event).collect();
+    assert_eq!(vec![FsmInfoEvent::Event("Coin")], guards);
+
+    // the rejected branch of the guard and the remaining transition
+    assert!(fsm.dispatch(Coin).is_err());
+    fsm.coins += 1;
+    fsm.dispatch(Coin)?;
+    fsm.dispatch(Kick)?;
+
+    let report = coverage.report(Turnstile::fsm_info());
+    assert!(report.is_complete(), "{}", report);
+
+    Ok(())
+}
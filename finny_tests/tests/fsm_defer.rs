@@ -0,0 +1,102 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct WorkerContext {
+    log: Vec<String>
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Busy;
+
+#[derive(Debug, Clone)]
+pub struct Request(usize);
+#[derive(Debug, Clone)]
+pub struct Done;
+#[derive(Debug, Clone)]
+pub struct Ping;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Worker, WorkerContext>) -> BuiltFsm {
+    fsm.events_debug();
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Request>()
+        .transition_to::<Busy>()
+        .try_action(|ev, ctx, _, _| {
+            if ev.0 == 0 {
+                return Err(FsmError::ActionFailed("empty request"));
+            }
+            ctx.log.push(format!("request {}", ev.0));
+            Ok(())
+        });
+
+    fsm.state::<Idle>()
+        .on_event::<Ping>()
+        .internal_transition()
+        .action(|_, ctx, _| {
+            ctx.log.push("ping".into());
+        });
+
+    fsm.state::<Busy>()
+        .defer::<Request>();
+
+    fsm.state::<Busy>()
+        .on_event::<Done>()
+        .transition_to::<Idle>()
+        .action(|_, ctx, _, _| {
+            ctx.log.push("done".into());
+            ctx.queue.enqueue(Ping).unwrap();
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_deferred_events() -> FsmResult<()> {
+    let mut fsm = Worker::new(WorkerContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Request(1))?;
+    fsm.dispatch(Request(2))?;
+    fsm.dispatch(Request(3))?;
+    assert_eq!(FsmCurrentState::State(WorkerCurrentState::Busy), fsm.get_current_states()[0]);
+    assert_eq!(2, fsm.deferred.len());
+    assert_eq!(vec!["request 1"], fsm.log);
+
+    // the deferred request is handled before the queued ping, the last one stays deferred
+    fsm.dispatch(Done)?;
+    assert_eq!(FsmCurrentState::State(WorkerCurrentState::Busy), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.deferred.len());
+    assert_eq!(Err(FsmError::NoTransition { state: "Busy", event: "Ping" }), fsm.dispatch(Ping));
+    assert_eq!(vec!["request 1", "done", "request 2"], fsm.log);
+
+    fsm.dispatch(Done)?;
+    fsm.dispatch(Done)?;
+    assert_eq!(FsmCurrentState::State(WorkerCurrentState::Idle), fsm.get_current_states()[0]);
+    assert!(fsm.deferred.is_empty());
+    assert_eq!(vec!["request 1", "done", "request 2", "done", "request 3", "done", "ping"], fsm.log);
+
+    Ok(())
+}
+
+#[test]
+fn test_deferred_event_error() -> FsmResult<()> {
+    let mut fsm = Worker::new(WorkerContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Request(1))?;
+    fsm.dispatch(Request(0))?;
+
+    // the released request fails after the event that released it was handled
+    assert_eq!(Err(FsmError::ActionFailed("empty request")), fsm.dispatch(Done));
+    assert_eq!(FsmCurrentState::State(WorkerCurrentState::Idle), fsm.get_current_states()[0]);
+    assert!(fsm.deferred.is_empty());
+    assert_eq!(vec!["request 1", "done"], fsm.log);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::json::{InspectJson, InspectJsonKind, InspectJsonRecord}};
+
+#[derive(Default)]
+pub struct PumpContext;
+
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone)]
+pub struct Prime;
+#[derive(Clone)]
+pub struct Fill;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Pump, PumpContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>()
+        .on_event::<Prime>()
+        .transition_to::<Tank>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.queue.enqueue(TankEvents::from(Fill)).unwrap();
+        });
+
+    fsm.sub_machine::<Tank>()
+        .with_context(|_| TankContext);
+
+    fsm.build()
+}
+
+pub struct TankContext;
+
+#[derive(Default)]
+pub struct Empty;
+#[derive(Default)]
+pub struct Full;
+
+#[finny_fsm]
+fn build_tank_fsm(mut fsm: FsmBuilder<Tank, TankContext>) -> BuiltFsm {
+    fsm.initial_state::<Empty>();
+    fsm.state::<Empty>().on_event::<Fill>().transition_to::<Full>();
+    fsm.state::<Full>();
+    fsm.build()
+}
+
+#[test]
+fn test_dispatch_ids() -> FsmResult<()> {
+    let records = Arc::new(Mutex::new(Vec::<InspectJsonRecord>::new()));
+    let sink = records.clone();
+    let inspect = InspectJson::with_sink(move |record| sink.lock().unwrap().push(record.clone()));
+
+    let mut fsm = Pump::new_with(PumpContext, FsmEventQueueVec::new(), inspect, FsmTimersNull)?;
+    fsm.start()?;
+    let start = fsm.dispatch_id;
+    fsm.dispatch(Prime)?;
+    let prime = fsm.dispatch_id;
+    assert!(prime > start);
+
+    // the queued event and the submachine are a part of the same dispatch
+    let records = records.lock().unwrap();
+    let of = |event: &str, fsm: &str| records.iter().filter(|r| r.event == event && r.fsm == fsm).map(|r| r.dispatch_id).collect::<Vec<_>>();
+    let dispatches = of("Prime", "Pump").into_iter().chain(of("TankEvents", "Pump")).chain(of("Fill", "Tank")).collect::<Vec<_>>();
+    assert!(!of("Fill", "Tank").is_empty());
+    assert!(dispatches.iter().all(|id| *id == prime));
+    assert!(records.iter().any(|r| r.dispatch_id == prime && r.kind == InspectJsonKind::StateEnter { state: "Full".into() }));
+    assert!(of("Fsm::Start", "Pump").iter().all(|id| *id == start));
+    assert!(of("Fsm::Start", "Tank").iter().all(|id| *id == prime));
+
+    Ok(())
+}
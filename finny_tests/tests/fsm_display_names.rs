@@ -0,0 +1,65 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::json::{InspectJson, InspectJsonKind, InspectJsonRecord}, short_type_name};
+
+#[derive(Default)]
+pub struct KioskContext;
+
+#[derive(Default)]
+pub struct WaitingForCard;
+#[derive(Default)]
+pub struct ReadingCard;
+
+#[derive(Clone)]
+pub struct CardInsertedEvent;
+#[derive(Clone)]
+pub struct CardRemovedEvent;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Kiosk, KioskContext>) -> BuiltFsm {
+    fsm.event_name::<CardInsertedEvent>("Insert");
+    fsm.initial_state::<WaitingForCard>();
+
+    fsm.state::<WaitingForCard>()
+        .named("Idle")
+        .on_event::<CardInsertedEvent>()
+        .transition_to::<ReadingCard>();
+
+    fsm.state::<ReadingCard>()
+        .on_event::<CardRemovedEvent>()
+        .transition_to::<WaitingForCard>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_display_names() -> FsmResult<()> {
+    let records = Arc::new(Mutex::new(Vec::<InspectJsonRecord>::new()));
+    let sink = records.clone();
+    let inspect = InspectJson::with_sink(move |record| sink.lock().unwrap().push(record.clone()));
+
+    let mut fsm = Kiosk::new_with(KioskContext, FsmEventQueueVec::new(), inspect, FsmTimersNull)?;
+    fsm.start()?;
+    assert_eq!("Idle", fsm.current_state_name());
+    assert_eq!(Err(FsmError::NoTransition { state: "Idle", event: "CardRemovedEvent" }), fsm.dispatch(CardRemovedEvent));
+
+    fsm.dispatch(CardInsertedEvent)?;
+    assert_eq!("ReadingCard", fsm.current_state_name());
+    assert_eq!("Insert", KioskEvents::from(CardInsertedEvent).event_name());
+
+    // the inspectors see the declared names, the rest without their module paths
+    let records = records.lock().unwrap();
+    assert!(records.iter().all(|r| r.fsm == "Kiosk"));
+    assert!(records.iter().any(|r| r.event == "Insert" && r.kind == InspectJsonKind::StateExit { state: "Idle".into() }));
+    assert!(records.iter().any(|r| r.event == "Insert" && r.kind == InspectJsonKind::StateEnter { state: "ReadingCard".into() }));
+
+    let info = Kiosk::fsm_info();
+    assert_eq!("Idle", info.get_state("WaitingForCard").unwrap().name);
+    assert!(info.events.contains(&"Insert"));
+
+    assert_eq!("Vec<u8>", short_type_name::<std::vec::Vec<u8>>());
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+extern crate finny;
+
+use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct DotContext;
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Running;
+
+#[derive(Debug, Clone)]
+pub struct Start;
+#[derive(Debug, Clone)]
+pub struct Tick;
+#[derive(Debug, Clone)]
+pub struct Stop;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<DotMachine, DotContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Start>()
+        .transition_to::<Running>()
+        .guard(|_, _, _| true);
+
+    fsm.state::<Running>()
+        .on_event::<Tick>()
+        .internal_transition()
+        .action(|_, _, _| {});
+
+    fsm.state::<Running>()
+        .on_event::<Stop>()
+        .transition_to::<DotSubMachine>();
+
+    fsm.sub_machine::<DotSubMachine>()
+        .with_context(|_| DotContext);
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct SubIdle;
+
+#[finny_fsm]
+fn build_sub_fsm(mut fsm: FsmBuilder<DotSubMachine, DotContext>) -> BuiltFsm {
+    fsm.initial_state::<SubIdle>();
+    fsm.state::<SubIdle>();
+    fsm.build()
+}
+
+#[test]
+fn test_dot() {
+    let dot = DotMachineInfo::dot();
+    assert!(dot.starts_with("digraph \"DotMachine\" {"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("\"DotMachine::Start0\" -> \"DotMachine::Idle\" [label=\"Start\"];"));
+    assert!(dot.contains("\"DotMachine::Idle\" -> \"DotMachine::Running\" [label=\"Start [guard]\"];"));
+    assert!(dot.contains("\"DotMachine::Running\" -> \"DotMachine::Running\" [label=\"Tick (Internal)\", style=dashed];"));
+    assert!(dot.contains("\"DotMachine::Running\" -> \"DotMachine::DotSubMachine\" [label=\"Stop\"];"));
+    assert!(dot.contains("\"DotMachine::DotSubMachine\" [label=\"DotSubMachine\", shape=box3d];"));
+    assert!(dot.contains("subgraph \"cluster_DotSubMachine\" {"));
+    assert!(dot.contains("\"DotSubMachine::Start0\" -> \"DotSubMachine::SubIdle\" [label=\"Start\"];"));
+}
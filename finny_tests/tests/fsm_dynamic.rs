@@ -0,0 +1,83 @@
+extern crate finny;
+
+use finny::{FsmError, dynamic::{FsmDynBuilder, FsmDynError, FsmDynResult}};
+
+#[derive(Default)]
+pub struct WorkflowContext {
+    approvals: usize,
+    journal: Vec<String>
+}
+
+fn build_workflow(required_approvals: usize) -> FsmDynBuilder<WorkflowContext, String> {
+    let mut fsm = FsmDynBuilder::<WorkflowContext, String>::new();
+    fsm.initial_state("Draft");
+
+    fsm.state("Draft")
+        .on_exit(|ctx| ctx.journal.push("draft exited".into()))
+        .on_event("Submit")
+        .transition_to("Review");
+
+    fsm.state("Review")
+        .on_entry(|ctx| ctx.journal.push("review entered".into()))
+        .on_event("Approve")
+        .internal_transition()
+        .guard(move |_ev, ctx| ctx.approvals + 1 < required_approvals)
+        .action(|_ev, ctx| ctx.approvals += 1);
+
+    fsm.state("Review")
+        .on_event("Approve")
+        .transition_to("Published")
+        .action(|_ev, ctx| {
+            ctx.approvals += 1;
+            ctx.queue.push_back("Notify".to_string());
+        });
+
+    fsm.state("Review")
+        .on_event("Reject")
+        .transition_to("Draft")
+        .try_action(|_ev, ctx| {
+            if ctx.approvals > 0 {
+                return Err(FsmError::ActionFailed("already approved"));
+            }
+            Ok(())
+        });
+
+    fsm.state("Published")
+        .on_event("Notify")
+        .self_transition()
+        .action(|_ev, ctx| ctx.journal.push("notified".into()));
+
+    fsm
+}
+
+#[test]
+fn test_dynamic_workflow() -> FsmDynResult<()> {
+    let mut fsm = build_workflow(2).build(WorkflowContext::default())?;
+    assert_eq!(None, fsm.current_state());
+    fsm.start()?;
+    assert_eq!(Some("Draft"), fsm.current_state());
+
+    assert_eq!(Err(FsmDynError::NoTransition { state: "Draft".into(), event: "Approve".into() }), fsm.dispatch("Approve".into()));
+
+    fsm.dispatch("Submit".into())?;
+    fsm.dispatch("Approve".into())?;
+    assert_eq!(Some("Review"), fsm.current_state());
+    assert_eq!(Err(FsmDynError::Fsm(FsmError::ActionFailed("already approved"))), fsm.dispatch("Reject".into()));
+
+    fsm.dispatch("Approve".into())?;
+    assert_eq!(Some("Published"), fsm.current_state());
+    assert_eq!(2, fsm.approvals);
+    assert_eq!(vec!["draft exited", "review entered", "notified"], fsm.journal);
+
+    Ok(())
+}
+
+#[test]
+fn test_dynamic_validation() {
+    let mut fsm = build_workflow(1);
+    fsm.state("Published").on_event("Archive").transition_to("Archived");
+    assert_eq!(Some(FsmDynError::StateNotFound("Archived".into())), fsm.build(WorkflowContext::default()).err());
+
+    let fsm = FsmDynBuilder::<WorkflowContext, &str>::new();
+    assert_eq!(Some(FsmDynError::NoInitialState), fsm.build(WorkflowContext::default()).err());
+}
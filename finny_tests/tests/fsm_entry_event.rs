@@ -0,0 +1,62 @@
+extern crate finny;
+
+use finny::{FsmEvent, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct DoorContext {
+    log: Vec<String>
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open {
+    opened_by: Option<String>
+}
+
+#[derive(Clone)]
+pub struct Unlock {
+    user: String
+}
+#[derive(Clone)]
+pub struct Close;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.state::<Closed>()
+        .on_entry_with_event(|_state, ev, ctx| {
+            let name = ev.map(|ev| ev.as_ref().to_string()).unwrap_or_default();
+            ctx.log.push(format!("Closed by {}", name));
+        })
+        .on_event::<Unlock>().transition_to::<Open>();
+
+    fsm.state::<Open>()
+        .on_entry_with_event(|state, ev, _ctx| {
+            if let Some(FsmEvent::Event(DoorEvents::Unlock(unlock))) = ev {
+                state.opened_by = Some(unlock.user.clone());
+            }
+        })
+        .on_exit_with_event(|state, ev, ctx| {
+            let name = ev.map(|ev| ev.as_ref().to_string()).unwrap_or_default();
+            ctx.log.push(format!("{} left open by {}", state.opened_by.as_deref().unwrap_or_default(), name));
+        })
+        .on_event::<Close>().transition_to::<Closed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_entry_exit_with_event() -> FsmResult<()> {
+    let mut fsm = Door::new(DoorContext::default())?;
+
+    fsm.start()?;
+    fsm.dispatch(Unlock { user: "alice".into() })?;
+    let open: &Open = fsm.get_state();
+    assert_eq!(Some("alice"), open.opened_by.as_deref());
+
+    fsm.dispatch(Close)?;
+    assert_eq!(vec!["Closed by Fsm::Start", "alice left open by Close", "Closed by Close"], fsm.log);
+
+    Ok(())
+}
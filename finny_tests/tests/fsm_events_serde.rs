@@ -0,0 +1,58 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+use serde::{Deserialize, Serialize};
+
+// neither the context nor the states are serializable
+pub struct ValveContext {
+    opened: Vec<u8>
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenValve { percent: u8 }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CloseValve;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Valve, ValveContext>) -> BuiltFsm {
+    fsm.events_serde();
+    fsm.initial_state::<Closed>();
+
+    fsm.state::<Closed>()
+        .on_event::<OpenValve>()
+        .transition_to::<Open>()
+        .action(|ev, ctx, _from, _to| {
+            ctx.opened.push(ev.percent);
+        });
+
+    fsm.state::<Open>()
+        .on_event::<CloseValve>()
+        .transition_to::<Closed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_events_from_json() -> FsmResult<()> {
+    let mut fsm = Valve::new(ValveContext { opened: vec![] })?;
+    fsm.start()?;
+
+    let wire = r#"[{"OpenValve":{"percent":40}},{"CloseValve":null},{"OpenValve":{"percent":75}}]"#;
+    let events: Vec<ValveEvents> = serde_json::from_str(wire).unwrap();
+    for ev in events {
+        fsm.dispatch(ev)?;
+    }
+
+    assert_eq!(FsmCurrentState::State(ValveCurrentState::Open), fsm.get_current_states()[0]);
+    assert_eq!(vec![40, 75], fsm.opened);
+
+    let json = serde_json::to_string(&ValveEvents::from(CloseValve)).unwrap();
+    assert_eq!(r#"{"CloseValve":null}"#, json);
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::{clock::FsmClockManual, std::TimersStd}};
+
+#[derive(Debug)]
+pub enum HardwareError {
+    Timeout
+}
+
+impl From<HardwareError> for FsmError {
+    fn from(_: HardwareError) -> Self {
+        FsmError::ActionFailed("hardware")
+    }
+}
+
+#[derive(Default)]
+pub struct ValveContext {
+    fail_motor: bool,
+    fail_sensor: bool,
+    opened: usize,
+    closed: usize,
+    ticks: usize
+}
+
+impl ValveContext {
+    fn drive_motor(&self) -> Result<(), HardwareError> {
+        if self.fail_motor { Err(HardwareError::Timeout) } else { Ok(()) }
+    }
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone, Debug)]
+pub struct OpenValve;
+#[derive(Clone, Debug)]
+pub struct Calibrate;
+#[derive(Clone, Debug)]
+pub struct Tick;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Valve, ValveContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+
+    fsm.state::<Closed>()
+        .on_entry(|_state, ctx| {
+            ctx.closed += 1;
+        })
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(1);
+            settings.renew = true;
+        }, |_ctx, _state| {
+            Some( Tick.into() )
+        })
+        .with_timer_ty::<TickTimer>();
+
+    fsm.state::<Closed>()
+        .on_event::<Tick>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.ticks += 1;
+        });
+
+    fsm.state::<Closed>()
+        .on_event::<OpenValve>()
+        .transition_to::<Open>()
+        .try_guard(|_ev, ctx, _states| {
+            if ctx.fail_sensor {
+                return Err(FsmError::ActionFailed("sensor"));
+            }
+            Ok(true)
+        })
+        .try_action(|_ev, ctx, _from, _to| {
+            ctx.drive_motor()?;
+            ctx.opened += 1;
+            Ok(())
+        });
+
+    fsm.state::<Closed>()
+        .on_event::<Calibrate>()
+        .internal_transition()
+        .try_action(|_ev, ctx, _state| {
+            ctx.drive_motor()?;
+            Ok(())
+        });
+
+    fsm.state::<Open>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_fallible_action() -> FsmResult<()> {
+    let ctx = ValveContext { fail_motor: true, ..Default::default() };
+    let mut fsm = Valve::new_with(ctx, FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    assert_eq!(Err(FsmError::ActionFailed("hardware")), fsm.dispatch(Calibrate));
+    assert_eq!(Err(FsmError::ActionFailed("hardware")), fsm.dispatch(OpenValve));
+    assert_eq!([FsmCurrentState::State(ValveCurrentState::Closed)], fsm.get_current_states());
+    assert_eq!(0, fsm.opened);
+
+    fsm.fail_motor = false;
+    fsm.dispatch(OpenValve)?;
+    assert_eq!([FsmCurrentState::State(ValveCurrentState::Open)], fsm.get_current_states());
+    assert_eq!(1, fsm.opened);
+
+    Ok(())
+}
+
+#[test]
+fn test_fallible_guard() -> FsmResult<()> {
+    let ctx = ValveContext { fail_sensor: true, ..Default::default() };
+    let mut fsm = Valve::new_with(ctx, FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    assert_eq!(Err(FsmError::ActionFailed("sensor")), fsm.dispatch(OpenValve));
+    assert_eq!([FsmCurrentState::State(ValveCurrentState::Closed)], fsm.get_current_states());
+
+    Ok(())
+}
+
+#[test]
+fn test_fallible_action_enters_again() -> FsmResult<()> {
+    let ctx = ValveContext { fail_motor: true, ..Default::default() };
+    let clock = FsmClockManual::new();
+    let mut fsm = Valve::new_with(ctx, FsmEventQueueVec::new(), InspectNull::new(), TimersStd::with_clock(clock.clone()))?;
+    fsm.start()?;
+    assert_eq!(1, fsm.closed);
+
+    // the failed action exited the first state, it's entered again
+    assert_eq!(Err(FsmError::ActionFailed("hardware")), fsm.dispatch(OpenValve));
+    assert_eq!([FsmCurrentState::State(ValveCurrentState::Closed)], fsm.get_current_states());
+    assert_eq!(2, fsm.closed);
+
+    // and its timer keeps running
+    clock.advance(Duration::from_secs(1));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(1, fsm.ticks);
+
+    // the next dispatch is handled by it
+    fsm.dispatch(Tick)?;
+    assert_eq!(2, fsm.ticks);
+    fsm.fail_motor = false;
+    fsm.dispatch(OpenValve)?;
+    assert_eq!([FsmCurrentState::State(ValveCurrentState::Open)], fsm.get_current_states());
+    assert_eq!(1, fsm.opened);
+
+    // the timer was cancelled by the exit
+    clock.advance(Duration::from_secs(2));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(2, fsm.ticks);
+
+    Ok(())
+}
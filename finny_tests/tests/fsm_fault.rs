@@ -0,0 +1,152 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull};
+
+#[derive(Default)]
+pub struct PumpContext {
+    motor_fails: bool,
+    sensor_fails: bool,
+    motor_stopped: usize,
+    alarms: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Pumping;
+#[derive(Default)]
+pub struct Fault;
+
+#[derive(Clone, Debug)]
+pub struct Start;
+#[derive(Clone, Debug)]
+pub struct Measure;
+#[derive(Clone, Debug)]
+pub struct Reset;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Pump, PumpContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.on_error().transition_to::<Fault>();
+
+    fsm.state::<Idle>()
+        .on_event::<Start>()
+        .transition_to::<Pumping>()
+        .try_action(|_ev, ctx, _from, _to| {
+            if ctx.motor_fails {
+                return Err(FsmError::ActionFailed("motor"));
+            }
+            Ok(())
+        });
+
+    fsm.state::<Pumping>()
+        .on_exit(|_state, ctx| {
+            ctx.motor_stopped += 1;
+        })
+        .on_event::<Measure>()
+        .internal_transition()
+        .try_guard(|_ev, ctx, _states| {
+            if ctx.sensor_fails {
+                return Err(FsmError::ActionFailed("sensor"));
+            }
+            Ok(true)
+        });
+
+    fsm.state::<Fault>()
+        .on_entry(|_state, ctx| {
+            ctx.alarms += 1;
+        })
+        .on_event::<Reset>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_fault_on_action() -> FsmResult<()> {
+    let ctx = PumpContext { motor_fails: true, ..Default::default() };
+    let mut fsm = Pump::new_with(ctx, FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    fsm.dispatch(Start)?;
+    assert_eq!([FsmCurrentState::State(PumpCurrentState::Fault)], fsm.get_current_states());
+    assert_eq!(Some(&FsmError::ActionFailed("motor")), fsm.backend.get_error());
+    assert_eq!(1, fsm.alarms);
+
+    fsm.dispatch(Reset)?;
+    assert_eq!([FsmCurrentState::State(PumpCurrentState::Idle)], fsm.get_current_states());
+    assert_eq!(Some(FsmError::ActionFailed("motor")), fsm.backend.take_error());
+
+    Ok(())
+}
+
+#[test]
+fn test_fault_on_guard() -> FsmResult<()> {
+    let mut fsm = Pump::new_with(PumpContext::default(), FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Start)?;
+    fsm.dispatch(Measure)?;
+    assert_eq!(0, fsm.motor_stopped);
+
+    fsm.sensor_fails = true;
+    fsm.dispatch(Measure)?;
+    assert_eq!([FsmCurrentState::State(PumpCurrentState::Fault)], fsm.get_current_states());
+    assert_eq!(1, fsm.motor_stopped);
+    assert_eq!(Some(&FsmError::ActionFailed("sensor")), fsm.backend.get_error());
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct StationContext;
+
+#[derive(Default)]
+pub struct Parked;
+#[derive(Default)]
+pub struct StationFault;
+
+#[derive(Clone, Debug)]
+pub struct Plug;
+
+#[finny_fsm]
+fn build_station_fsm(mut fsm: FsmBuilder<Station, StationContext>) -> BuiltFsm {
+    fsm.initial_state::<Parked>();
+    fsm.on_error().transition_to::<StationFault>();
+
+    fsm.state::<Parked>()
+        .on_event::<Plug>()
+        .transition_to::<Charger>();
+
+    fsm.sub_machine::<Charger>()
+        .with_context(|_ctx| ChargerContext);
+
+    fsm.state::<StationFault>();
+
+    fsm.build()
+}
+
+pub struct ChargerContext;
+
+#[derive(Default)]
+pub struct Charging;
+
+#[finny_fsm]
+fn build_charger_fsm(mut fsm: FsmBuilder<Charger, ChargerContext>) -> BuiltFsm {
+    fsm.initial_state::<Charging>();
+    fsm.state::<Charging>()
+        .on_entry_async(|_, _ctx| async move { });
+    fsm.build()
+}
+
+#[test]
+fn test_fault_on_submachine_start() -> FsmResult<()> {
+    let mut fsm = Station::new_with(StationContext, FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    // the submachine can't be started on the sync dispatch
+    fsm.dispatch(Plug)?;
+    assert_eq!([FsmCurrentState::State(StationCurrentState::StationFault)], fsm.get_current_states());
+    assert_eq!(Some(&FsmError::AsyncRequired), fsm.backend.get_error());
+
+    Ok(())
+}
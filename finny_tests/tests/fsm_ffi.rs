@@ -0,0 +1,84 @@
+extern crate finny;
+
+use finny::{FsmFfiEvent, FsmFfiStatus, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct MotorContext {
+    speed: u8
+}
+
+#[derive(Default)]
+pub struct Stopped;
+#[derive(Default)]
+pub struct Running;
+
+#[derive(Clone)]
+pub struct SetSpeed(u8);
+#[derive(Clone)]
+pub struct Halt;
+
+impl FsmFfiEvent for SetSpeed {
+    fn from_ffi_bytes(payload: &[u8]) -> Option<Self> {
+        payload.first().map(|speed| SetSpeed(*speed))
+    }
+}
+
+impl FsmFfiEvent for Halt {
+    fn from_ffi_bytes(_payload: &[u8]) -> Option<Self> {
+        Some(Halt)
+    }
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Motor, MotorContext>) -> BuiltFsm {
+    fsm.ffi();
+    fsm.event_id::<SetSpeed>(1);
+    fsm.event_id::<Halt>(2);
+    fsm.state_id::<Stopped>(10);
+    fsm.state_id::<Running>(11);
+    fsm.initial_state::<Stopped>();
+
+    fsm.state::<Stopped>()
+        .on_event::<SetSpeed>()
+        .transition_to::<Running>()
+        .action(|ev, ctx, _from, _to| {
+            ctx.speed = ev.0;
+        });
+
+    fsm.state::<Running>()
+        .on_event::<Halt>()
+        .transition_to::<Stopped>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_ffi() {
+    unsafe {
+        let fsm = motor_create();
+        assert!(!fsm.is_null());
+
+        let mut state = 0;
+        assert_eq!(FsmFfiStatus::Ok as i32, motor_current_state(fsm, 0, &mut state));
+        assert_eq!(10, state);
+
+        // the payload is decoded, the unknown ids and the invalid payloads are rejected
+        assert_eq!(FsmFfiStatus::UnknownEvent as i32, motor_dispatch(fsm, 3, core::ptr::null(), 0));
+        assert_eq!(FsmFfiStatus::InvalidPayload as i32, motor_dispatch(fsm, 1, core::ptr::null(), 0));
+        let speed = [42u8];
+        assert_eq!(FsmFfiStatus::Ok as i32, motor_dispatch(fsm, 1, speed.as_ptr(), speed.len()));
+        assert_eq!(FsmFfiStatus::Ok as i32, motor_current_state(fsm, 0, &mut state));
+        assert_eq!(11, state);
+        assert_eq!(42, (&*fsm).speed);
+
+        assert_eq!(FsmFfiStatus::NoTransition as i32, motor_dispatch(fsm, 1, speed.as_ptr(), speed.len()));
+        assert_eq!(FsmFfiStatus::Stopped as i32, motor_current_state(fsm, 1, &mut state));
+
+        motor_destroy(fsm);
+        assert_eq!(FsmFfiStatus::NullPointer as i32, motor_dispatch(core::ptr::null_mut(), 2, core::ptr::null(), 0));
+    }
+
+    assert!(Motor::FFI_HEADER.contains("#define MOTOR_EVENT_SET_SPEED 1u"));
+    assert!(Motor::FFI_HEADER.contains("#define MOTOR_STATE_RUNNING 11u"));
+    assert!(Motor::FFI_HEADER.contains("int32_t motor_dispatch(motor_t *fsm, uint32_t event_id, const uint8_t *payload, size_t payload_len);"));
+}
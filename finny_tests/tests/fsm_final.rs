@@ -0,0 +1,97 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct InstallerContext;
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Installed;
+
+#[derive(Debug, Clone)]
+pub struct Install;
+#[derive(Debug, Clone)]
+pub struct Downloaded {
+    pub bytes: usize
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Installer, InstallerContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>()
+        .on_event::<Install>()
+        .transition_to::<Download>();
+
+    fsm.sub_machine::<Download>()
+        .with_context(|_| DownloadContext::default())
+        .on_completion(|ctx| Downloaded { bytes: ctx.bytes })
+        .on_event::<Downloaded>()
+        .transition_to::<Installed>();
+
+    fsm.state::<Installed>()
+        .final_state();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct DownloadContext {
+    bytes: usize
+}
+
+#[derive(Default)]
+pub struct Fetching;
+#[derive(Default)]
+pub struct Done;
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub last: bool
+}
+
+#[finny_fsm]
+fn build_download_fsm(mut fsm: FsmBuilder<Download, DownloadContext>) -> BuiltFsm {
+    fsm.initial_state::<Fetching>();
+
+    fsm.state::<Fetching>()
+        .on_event::<Chunk>()
+        .internal_transition()
+        .guard(|ev, _ctx, _states| !ev.last)
+        .action(|_ev, ctx, _state| {
+            ctx.bytes += 1024;
+        });
+
+    fsm.state::<Fetching>()
+        .on_event::<Chunk>()
+        .transition_to::<Done>()
+        .guard(|ev, _ctx, _states| ev.last);
+
+    fsm.state::<Done>()
+        .final_state();
+
+    fsm.build()
+}
+
+#[test]
+fn test_final_states() -> FsmResult<()> {
+    let mut fsm = Installer::new(InstallerContext)?;
+    fsm.start()?;
+    assert!(!fsm.is_completed());
+
+    fsm.dispatch(Install)?;
+    fsm.dispatch(DownloadEvents::Chunk(Chunk { last: false }))?;
+
+    let download: &Download = fsm.get_state();
+    assert!(!download.is_completed());
+
+    fsm.dispatch(DownloadEvents::Chunk(Chunk { last: true }))?;
+
+    let download: &Download = fsm.get_state();
+    assert!(download.is_completed());
+    assert_eq!([FsmCurrentState::State(InstallerCurrentState::Installed)], fsm.get_current_states());
+    assert!(fsm.is_completed());
+
+    Ok(())
+}
@@ -92,7 +92,8 @@ fn test_fsm() -> FsmResult<()> {
     assert_eq!(1, state.enter);
 
     let ret = fsm.dispatch(EventClick { time: 99 });
-    assert_eq!(Err(FsmError::NoTransition), ret);
+    assert_eq!(Err(FsmError::NoTransition { state: "StateA", event: "EventClick" }), ret);
+    assert_eq!("The event 'EventClick' isn't handled in the state 'StateA'", ret.unwrap_err().to_string());
     
     fsm.dispatch(EventClick { time: 123 })?;
 
@@ -103,7 +104,7 @@ fn test_fsm() -> FsmResult<()> {
     assert_eq!(1, state_b.counter);
 
     let ret = fsm.dispatch(EventEnter { shift: true });
-    assert_eq!(Err(FsmError::NoTransition), ret);
+    assert_eq!(Err(FsmError::NoTransition { state: "StateB", event: "EventEnter" }), ret);
     
     fsm.dispatch(EventEnter { shift: false })?;
     let state_b: &StateB = fsm.get_state();
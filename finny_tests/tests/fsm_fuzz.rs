@@ -0,0 +1,104 @@
+extern crate finny;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmFrontend, FsmFuzz, FsmFuzzRng, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::std::TimersStd};
+
+#[derive(Default)]
+pub struct LinkContext {
+    sent: usize,
+    bytes: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Connected;
+
+#[derive(Clone)]
+pub struct Connect;
+#[derive(Clone)]
+pub struct Ack;
+#[derive(Clone)]
+pub struct Send { len: usize }
+#[derive(Clone)]
+pub struct Reset;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, LinkContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Connect>()
+        .transition_to::<Connecting>();
+
+    fsm.state::<Connecting>()
+        .on_event::<Ack>()
+        .transition_to::<Connected>();
+
+    fsm.state::<Connected>()
+        .on_event::<Send>()
+        .internal_transition()
+        .action(|ev, ctx, _state| {
+            ctx.sent += 1;
+            ctx.bytes += ev.len;
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<Reset>()
+        .transition_to::<Idle>();
+
+    fsm.state::<Connected>()
+        .on_event::<Reset>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+type LinkFrontend = FsmFrontend<Link, FsmEventQueueVec<Link>, InspectNull, TimersStd<Link>>;
+type LinkFuzz = FsmFuzz<Link, FsmEventQueueVec<Link>, InspectNull, TimersStd<Link>, fn() -> FsmResult<LinkFrontend>>;
+
+fn new_link() -> FsmResult<LinkFrontend> {
+    Link::new(LinkContext::default())
+}
+
+fn fuzz_link() -> LinkFuzz {
+    FsmFuzz::new(new_link as fn() -> FsmResult<LinkFrontend>)
+        .event(|_| Connect)
+        .event(|_| Ack)
+        .event(|rng| Send { len: 1 + rng.below(64) })
+        .event(|_| Reset)
+}
+
+#[test]
+fn test_fuzz_invariants_hold() {
+    fuzz_link()
+        .invariant::<Connected>("the payloads are limited", |fsm| fsm.bytes <= 64 * fsm.sent)
+        .invariant_all("every message has a payload", |fsm| fsm.bytes >= fsm.sent)
+        .runs(50)
+        .check();
+}
+
+#[test]
+fn test_fuzz_shrinks_the_failure() {
+    let failure = fuzz_link()
+        .invariant::<Connected>("a single message per link", |fsm| fsm.sent < 2)
+        .seed(7)
+        .runs(50)
+        .run()
+        .unwrap_err();
+
+    assert_eq!(vec!["Connect", "Ack", "Send", "Send"], failure.events);
+    assert!(failure.reason.contains("a single message per link"));
+}
+
+#[test]
+fn test_fuzz_rng_zero_bound() {
+    let mut rng = FsmFuzzRng::new(3);
+    assert_eq!(0, rng.below(0));
+    assert!(rng.below(4) < 4);
+
+    let mut bytes = [0; 13];
+    rng.fill_bytes(&mut bytes);
+    assert!(bytes.iter().any(|b| *b != 0));
+}
@@ -0,0 +1,73 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct ChannelContext {
+    numbers: Vec<u32>,
+    texts: Vec<String>
+}
+
+#[derive(Default)]
+pub struct Open;
+#[derive(Default)]
+pub struct Closed;
+
+#[derive(Clone)]
+pub struct Received<T> {
+    value: T
+}
+#[derive(Clone)]
+pub struct Close;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Channel, ChannelContext>) -> BuiltFsm {
+    fsm.initial_state::<Open>();
+
+    fsm.state::<Open>()
+        .on_event::<Received<u32>>()
+        .internal_transition()
+        .action(|ev, ctx, _state| {
+            ctx.numbers.push(ev.value);
+        });
+
+    fsm.state::<Open>()
+        .on_event::<Received<String>>()
+        .internal_transition()
+        .action(|ev, ctx, _state| {
+            ctx.texts.push(ev.value.clone());
+        });
+
+    fsm.state::<Open>()
+        .on_event::<Close>()
+        .transition_to::<Closed>();
+
+    fsm.state::<Closed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_generic_events() -> FsmResult<()> {
+    let mut fsm = Channel::new(ChannelContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Received { value: 42u32 })?;
+    fsm.dispatch(Received { value: "hello".to_string() })?;
+    fsm.dispatch(ChannelEvents::ReceivedU32(Received { value: 7 }))?;
+    assert_eq!(vec![42, 7], fsm.numbers);
+    assert_eq!(vec!["hello".to_string()], fsm.texts);
+
+    fsm.dispatch(Close)?;
+    assert_eq!(FsmCurrentState::State(ChannelCurrentState::Closed), fsm.get_current_states()[0]);
+    assert_eq!(Err(FsmError::NoTransition { state: "Closed", event: "Received<u32>" }), fsm.dispatch(Received { value: 1u32 }));
+
+    Ok(())
+}
+
+#[test]
+fn test_generic_events_in_the_description() {
+    let mut events = Channel::fsm_info().events.to_vec();
+    events.sort_unstable();
+    assert_eq!(vec!["Close", "Received<String>", "Received<u32>"], events);
+}
@@ -0,0 +1,121 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{EventContext, FsmCurrentState, FsmEventQueue, FsmEventQueueVec, FsmFactory, FsmInfoGuard, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::json::{InspectJson, InspectJsonKind}};
+
+#[derive(Default)]
+pub struct ElevatorContext {
+    door_closed: bool,
+    load: usize,
+    maintenance: bool
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Moving;
+#[derive(Default)]
+pub struct Inspection;
+
+#[derive(Clone)]
+pub struct Call;
+#[derive(Clone)]
+pub struct Arrived;
+#[derive(Clone)]
+pub struct Inspect;
+
+fn door_closed<E, Q: FsmEventQueue<Elevator>>(_ev: &E, ctx: &EventContext<Elevator, Q>, _states: &ElevatorStates) -> bool {
+    ctx.door_closed
+}
+
+fn overloaded<E, Q: FsmEventQueue<Elevator>>(_ev: &E, ctx: &EventContext<Elevator, Q>, _states: &ElevatorStates) -> bool {
+    ctx.load > 8
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Elevator, ElevatorContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Call>()
+        .transition_to::<Moving>()
+        .guard(overloaded)
+        .not()
+        .and_guard(door_closed);
+
+    fsm.state::<Idle>()
+        .on_event::<Inspect>()
+        .transition_to::<Inspection>()
+        .guard(door_closed)
+        .or_guard(|_ev, ctx, _states| ctx.maintenance);
+
+    fsm.state::<Moving>()
+        .on_event::<Arrived>()
+        .transition_to::<Idle>();
+
+    fsm.state::<Inspection>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_guard_combinators() -> FsmResult<()> {
+    let mut fsm = Elevator::new(ElevatorContext { door_closed: false, load: 12, maintenance: false })?;
+    fsm.start()?;
+
+    assert!(fsm.dispatch(Call).is_err());
+    fsm.door_closed = true;
+    assert!(fsm.dispatch(Call).is_err());
+    fsm.load = 4;
+    fsm.dispatch(Call)?;
+    assert_eq!(FsmCurrentState::State(ElevatorCurrentState::Moving), fsm.get_current_states()[0]);
+
+    fsm.dispatch(Arrived)?;
+    fsm.door_closed = false;
+    assert!(fsm.dispatch(Inspect).is_err());
+    fsm.maintenance = true;
+    fsm.dispatch(Inspect)?;
+    assert_eq!(FsmCurrentState::State(ElevatorCurrentState::Inspection), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_guard_combinators_in_the_description() {
+    let mut guards: Vec<_> = Elevator::fsm_info().transitions().filter_map(|t| t.guard).collect();
+    guards.sort_unstable();
+    assert_eq!(vec!["!overloaded && door_closed", "door_closed || guard_2"], guards);
+
+    let call = Elevator::fsm_info().transitions().find(|t| t.guard == Some("!overloaded && door_closed")).unwrap();
+    assert_eq!(Some(FsmInfoGuard::And(&FsmInfoGuard::Not(&FsmInfoGuard::Guard("overloaded")), &FsmInfoGuard::Guard("door_closed"))), call.guard_expr);
+
+    // the terms are labeled separately in the diagrams
+    let mermaid = Elevator::to_mermaid();
+    assert!(mermaid.contains("Elevator_Idle --> Elevator_Moving : Call ![overloaded] && [door_closed]\n"));
+    assert!(mermaid.contains("Elevator_Idle --> Elevator_Inspection : Inspect [door_closed] || [guard_2]\n"));
+    assert!(ElevatorInfo::dot().contains("[label=\"Call ![overloaded] && [door_closed]\"]"));
+}
+
+#[test]
+fn test_guard_combinators_inspected_by_term() -> FsmResult<()> {
+    let guards = Arc::new(Mutex::new(vec![]));
+    let inspect = {
+        let guards = guards.clone();
+        InspectJson::with_sink(move |record| if let InspectJsonKind::Guard { guard, result } = &record.kind {
+            guards.lock().unwrap().push((guard.clone(), *result));
+        })
+    };
+    let mut fsm = Elevator::new_with(ElevatorContext { door_closed: true, load: 12, maintenance: false }, FsmEventQueueVec::new(), inspect, FsmTimersNull)?;
+    fsm.start()?;
+
+    // the rejected term short-circuits the rest of the guard
+    assert!(fsm.dispatch(Call).is_err());
+    assert_eq!(vec![("overloaded".to_string(), true)], guards.lock().unwrap().drain(..).collect::<Vec<_>>());
+
+    fsm.load = 4;
+    fsm.dispatch(Call)?;
+    assert_eq!(vec![("overloaded".to_string(), false), ("door_closed".to_string(), true)], guards.lock().unwrap().drain(..).collect::<Vec<_>>());
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct ConnectionContext;
+
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Failed;
+#[derive(Default)]
+pub struct Connected;
+
+#[derive(Clone, Debug)]
+pub struct Retry;
+#[derive(Clone, Debug)]
+pub struct Ack;
+#[derive(Clone, Debug)]
+pub struct ConnectTimeout;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Connection, ConnectionContext>) -> BuiltFsm {
+    fsm.initial_state::<Connecting>();
+
+    fsm.state::<Connecting>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(10);
+        }, |_ctx, _state| {
+            Some( ConnectTimeout.into() )
+        })
+        .with_timer_ty::<ConnectTimer>();
+
+    fsm.state::<Connecting>()
+        .on_event::<Retry>()
+        .self_transition()
+        .guard(|_ev, ctx, _states| {
+            ctx.time_in_state().map(|t| t < Duration::from_secs(5)).unwrap_or(false)
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<Ack>()
+        .transition_to::<Connected>()
+        .guard(|_ev, ctx, _states| {
+            ctx.timers.is_running::<ConnectTimer>()
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<ConnectTimeout>()
+        .internal_transition();
+
+    fsm.state::<Connecting>()
+        .on_event::<Ack>()
+        .transition_to::<Failed>();
+
+    fsm.state::<Failed>();
+    fsm.state::<Connected>();
+
+    fsm.build()
+}
+
+type Timers = TimersCore<Connection, ConnectionTimersStorage<CoreTimer>, [ConnectionTimers; 16]>;
+
+fn new_connection() -> FsmResult<FsmFrontend<Connection, FsmEventQueueVec<Connection>, InspectNull, Timers>> {
+    let mut fsm = Connection::new_with(ConnectionContext, FsmEventQueueVec::new(), InspectNull::new(), TimersCore::new(Default::default()))?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_guard_time_in_state() -> FsmResult<()> {
+    let mut fsm = new_connection()?;
+
+    fsm.timers.tick(Duration::from_secs(3));
+    fsm.dispatch(Retry)?;
+
+    // the self transition restarted the clock of the state
+    fsm.timers.tick(Duration::from_secs(3));
+    fsm.dispatch(Retry)?;
+
+    fsm.timers.tick(Duration::from_secs(6));
+    assert!(fsm.dispatch(Retry).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_guard_timer_running() -> FsmResult<()> {
+    let mut fsm = new_connection()?;
+    fsm.timers.tick(Duration::from_secs(9));
+    fsm.dispatch(Ack)?;
+    assert_eq!(FsmCurrentState::State(ConnectionCurrentState::Connected), fsm.get_current_states()[0]);
+
+    let mut fsm = new_connection()?;
+    fsm.timers.tick(Duration::from_secs(11));
+    fsm.dispatch_timer_events()?;
+    fsm.dispatch(Ack)?;
+    assert_eq!(FsmCurrentState::State(ConnectionCurrentState::Failed), fsm.get_current_states()[0]);
+
+    Ok(())
+}
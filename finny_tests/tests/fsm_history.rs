@@ -0,0 +1,169 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct MainContext;
+
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Debug, Clone)]
+pub struct Play;
+#[derive(Debug, Clone)]
+pub struct Pause;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Jukebox, MainContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>()
+        .on_event::<Play>()
+        .transition_to::<PlayerMachine>();
+
+    fsm.sub_machine::<PlayerMachine>()
+        .with_context(|_| PlayerContext::default())
+        .history_shallow()
+        .on_event::<Pause>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[finny_fsm]
+fn build_deep_fsm(mut fsm: FsmBuilder<DeepJukebox, MainContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>()
+        .on_event::<Play>()
+        .transition_to::<PlayerMachine>();
+
+    fsm.sub_machine::<PlayerMachine>()
+        .with_context(|_| PlayerContext::default())
+        .history_deep()
+        .on_event::<Pause>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct PlayerContext {
+    track_entries: usize
+}
+
+#[derive(Default)]
+pub struct TrackOne;
+#[derive(Default)]
+pub struct TrackTwo;
+
+#[derive(Debug, Clone)]
+pub struct Next;
+
+#[finny_fsm]
+fn build_player_fsm(mut fsm: FsmBuilder<PlayerMachine, PlayerContext>) -> BuiltFsm {
+    fsm.initial_state::<TrackOne>();
+
+    fsm.state::<TrackOne>()
+        .on_entry(|_, ctx| {
+            ctx.track_entries += 1;
+        })
+        .on_event::<Next>()
+        .transition_to::<TrackTwo>();
+
+    fsm.state::<TrackTwo>()
+        .on_entry(|_, ctx| {
+            ctx.track_entries += 1;
+        })
+        .on_event::<Next>()
+        .transition_to::<BufferingMachine>();
+
+    fsm.sub_machine::<BufferingMachine>()
+        .with_context(|_| BufferingContext);
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct BufferingContext;
+
+#[derive(Default)]
+pub struct Loading;
+#[derive(Default)]
+pub struct Ready;
+
+#[derive(Debug, Clone)]
+pub struct Loaded;
+
+#[finny_fsm]
+fn build_buffering_fsm(mut fsm: FsmBuilder<BufferingMachine, BufferingContext>) -> BuiltFsm {
+    fsm.initial_state::<Loading>();
+    fsm.state::<Loading>()
+        .on_event::<Loaded>()
+        .transition_to::<Ready>();
+    fsm.state::<Ready>();
+    fsm.build()
+}
+
+#[test]
+fn test_shallow_history() -> FsmResult<()> {
+    let mut fsm = Jukebox::new(MainContext)?;
+    assert_eq!([FsmCurrentState::Stopped], fsm.get_sub_current_states::<PlayerMachine>());
+
+    fsm.start()?;
+    fsm.dispatch(Play)?;
+    fsm.dispatch(PlayerMachineEvents::from(Next))?;
+
+    let player: &PlayerMachine = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(PlayerMachineCurrentState::TrackTwo), player.get_current_states()[0]);
+    assert_eq!(2, player.track_entries);
+
+    fsm.dispatch(Pause)?;
+    assert_eq!(FsmCurrentState::State(JukeboxCurrentState::Idle), fsm.get_current_states()[0]);
+    // the exited submachine keeps its history
+    assert_eq!([FsmCurrentState::State(PlayerMachineCurrentState::TrackTwo)], fsm.get_sub_current_states::<PlayerMachine>());
+
+    // resumes the last active state and executes its entry action
+    fsm.dispatch(Play)?;
+    let player: &PlayerMachine = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(PlayerMachineCurrentState::TrackTwo), player.get_current_states()[0]);
+    assert_eq!(3, player.track_entries);
+
+    fsm.dispatch(PlayerMachineEvents::from(Next))?;
+    fsm.dispatch(PlayerMachineEvents::from(BufferingMachineEvents::from(Loaded)))?;
+    let player: &PlayerMachine = fsm.get_state();
+    let buffering: &BufferingMachine = player.get_state();
+    assert_eq!(FsmCurrentState::State(BufferingMachineCurrentState::Ready), buffering.get_current_states()[0]);
+
+    fsm.dispatch(Pause)?;
+    fsm.dispatch(Play)?;
+
+    // the nested submachine is resumed, but only shallowly: it restarts from its initial state
+    let player: &PlayerMachine = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(PlayerMachineCurrentState::BufferingMachine), player.get_current_states()[0]);
+    let buffering: &BufferingMachine = player.get_state();
+    assert_eq!(FsmCurrentState::State(BufferingMachineCurrentState::Loading), buffering.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_deep_history() -> FsmResult<()> {
+    let mut fsm = DeepJukebox::new(MainContext)?;
+
+    fsm.start()?;
+    fsm.dispatch(Play)?;
+    fsm.dispatch(PlayerMachineEvents::from(Next))?;
+    fsm.dispatch(PlayerMachineEvents::from(Next))?;
+    fsm.dispatch(PlayerMachineEvents::from(BufferingMachineEvents::from(Loaded)))?;
+
+    fsm.dispatch(Pause)?;
+    assert_eq!(FsmCurrentState::State(DeepJukeboxCurrentState::Idle), fsm.get_current_states()[0]);
+    fsm.dispatch(Play)?;
+
+    // the entire nested configuration is restored
+    let player: &PlayerMachine = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(PlayerMachineCurrentState::BufferingMachine), player.get_current_states()[0]);
+    let buffering: &BufferingMachine = player.get_state();
+    assert_eq!(FsmCurrentState::State(BufferingMachineCurrentState::Ready), buffering.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmId, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct Disconnected;
+#[derive(Default)]
+pub struct Connected;
+
+#[derive(Clone)]
+pub struct Connect { address: u32 }
+#[derive(Clone)]
+pub struct Heartbeat;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, ()>) -> BuiltFsm {
+    fsm.state_id::<Connected>(7);
+    fsm.event_id::<Connect>(1);
+    fsm.initial_state::<Disconnected>();
+
+    fsm.state::<Disconnected>()
+        .on_event::<Connect>()
+        .transition_to::<Connected>()
+        .guard(|ev, _ctx, _| ev.address > 0);
+
+    fsm.state::<Connected>()
+        .on_event::<Heartbeat>()
+        .internal_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_stable_ids() -> FsmResult<()> {
+    assert_eq!(7, LinkCurrentState::Connected as u32);
+    assert_eq!(Some(LinkCurrentState::Connected), LinkCurrentState::from_id(7));
+    assert_eq!(None, LinkCurrentState::from_id(8));
+
+    // the undeclared ids are the hashes of the names, they don't depend on the order of the builder
+    let disconnected = LinkCurrentState::Disconnected.to_id();
+    assert_ne!(7, disconnected);
+    assert_eq!(Some(LinkCurrentState::Disconnected), LinkCurrentState::from_id(disconnected));
+
+    assert_eq!(1, LinkEvents::from(Connect { address: 1 }).event_id());
+    let heartbeat = LinkEvents::from(Heartbeat);
+    assert_eq!(LinkEventKind::Heartbeat, heartbeat.event_kind());
+    assert_eq!(Some(LinkEventKind::Heartbeat), LinkEventKind::from_id(heartbeat.event_id()));
+    assert_eq!("Heartbeat", LinkEventKind::Heartbeat.event_name());
+
+    let mut fsm = Link::new(())?;
+    assert_eq!(None, fsm.get_current_states()[0].id());
+    fsm.start()?;
+    fsm.dispatch(Connect { address: 1 })?;
+    assert_eq!(FsmCurrentState::State(LinkCurrentState::Connected), fsm.get_current_states()[0]);
+    assert_eq!(Some(7), fsm.get_current_states()[0].id());
+
+    Ok(())
+}
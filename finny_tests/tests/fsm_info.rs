@@ -0,0 +1,70 @@
+extern crate finny;
+
+use finny::{FsmInfoEvent, FsmInfoStateKind, FsmInfoTransitionKind, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct DoorContext {
+    locked: bool
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+#[derive(Default)]
+pub struct Alarm;
+
+#[derive(Clone)]
+pub struct Push;
+#[derive(Clone)]
+pub struct Ring;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_states::<(Closed, Alarm)>();
+
+    fsm.state::<Closed>()
+        .on_event::<Push>()
+        .transition_to::<Open>()
+        .guard(|_ev, ctx, _| !ctx.locked);
+
+    fsm.state::<Open>()
+        .on_event::<Push>()
+        .transition_to::<Closed>();
+
+    fsm.state::<Alarm>()
+        .defer::<Push>();
+
+    fsm.state::<Alarm>()
+        .on_event::<Ring>()
+        .self_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_fsm_info() {
+    let info = Door::fsm_info();
+
+    assert_eq!("Door", info.fsm_id);
+    assert_eq!("DoorContext", info.context_id);
+    assert_eq!(&["Push", "Ring"], info.events);
+    assert_eq!(2, info.regions.len());
+
+    let doors = &info.regions[0];
+    assert_eq!("Closed", doors.initial_state);
+    let states: Vec<_> = doors.states.iter().map(|s| s.state_id).collect();
+    assert_eq!(vec!["Closed", "Open"], states);
+    assert_eq!(FsmInfoEvent::Start, doors.transitions[0].event);
+
+    let open = doors.transitions.iter()
+        .find(|t| t.kind == FsmInfoTransitionKind::NormalTransition { from_state: "Closed", to_state: "Open" })
+        .unwrap();
+    assert_eq!(FsmInfoEvent::Event("Push"), open.event);
+    assert!(open.guard.unwrap().contains("ctx.locked"));
+
+    let alarm = info.get_state("Alarm").unwrap();
+    assert_eq!(FsmInfoStateKind::State, alarm.kind);
+    assert_eq!(&["Push"], alarm.deferred_events);
+    assert!(info.transitions().any(|t| t.kind == FsmInfoTransitionKind::SelfTransition { state_id: "Alarm" }));
+}
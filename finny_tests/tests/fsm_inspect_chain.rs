@@ -0,0 +1,68 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, InspectEvent, InspectFsmEvent, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::{chain::InspectChain, events::EventInspector, log::InspectLog}};
+
+#[derive(Default)]
+pub struct LampContext;
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct On;
+
+#[derive(Clone)]
+pub struct Toggle;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Lamp, LampContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+    fsm.state::<Off>().on_event::<Toggle>().transition_to::<On>();
+    fsm.state::<On>().on_event::<Toggle>().transition_to::<Off>();
+    fsm.build()
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>
+}
+
+impl InspectEvent for Recorder {
+    fn on_event<S: std::any::Any + std::fmt::Debug + Clone>(&self, event: &InspectFsmEvent<S>) {
+        self.events.lock().unwrap().push(format!("{:?}", event));
+    }
+}
+
+#[test]
+fn test_inspect_tuple() -> FsmResult<()> {
+    let a = Recorder::default();
+    let b = Recorder::default();
+
+    let mut fsm = Lamp::new_with(LampContext, FsmEventQueueVec::new(), (EventInspector::new(a.clone()), (InspectLog::new(), EventInspector::new(b.clone()))), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Toggle)?;
+
+    let expected = vec!["StateEnter(Off)", "StateExit(Off)", "StateEnter(On)"];
+    assert_eq!(expected, *a.events.lock().unwrap());
+    assert_eq!(expected, *b.events.lock().unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_inspect_chain() -> FsmResult<()> {
+    let a = Recorder::default();
+    let b = Recorder::default();
+
+    let inspect = InspectChain::new_chain(EventInspector::new(a.clone())).add_inspect(EventInspector::new(b.clone()));
+    let mut fsm = Lamp::new_with(LampContext, FsmEventQueueVec::new(), inspect, FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Toggle)?;
+    fsm.dispatch(Toggle)?;
+
+    assert_eq!(5, a.events.lock().unwrap().len());
+    assert_eq!(*a.events.lock().unwrap(), *b.events.lock().unwrap());
+
+    Ok(())
+}
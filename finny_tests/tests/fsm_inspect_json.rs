@@ -0,0 +1,70 @@
+extern crate finny;
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::json::{InspectJson, InspectJsonKind, InspectJsonRecord}};
+
+#[derive(Default)]
+pub struct DoorContext {
+    opened: usize
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone)]
+pub struct Push;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.state::<Closed>().on_event::<Push>().transition_to::<Open>();
+    fsm.state::<Open>().on_entry(|_state, ctx| {
+        ctx.opened += 1;
+    });
+    fsm.build()
+}
+
+#[derive(Clone, Default)]
+struct Lines(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Lines {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_inspect_json() -> FsmResult<()> {
+    let lines = Lines::default();
+    let mut fsm = Door::new_with(DoorContext::default(), FsmEventQueueVec::new(), InspectJson::new(lines.clone()), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Push)?;
+    assert!(fsm.dispatch(Push).is_err());
+
+    let output = String::from_utf8(lines.0.lock().unwrap().clone()).unwrap();
+    let records: Vec<InspectJsonRecord> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+
+    let push: Vec<_> = records.iter().filter(|r| r.event == "Push").collect();
+    assert!(push.iter().all(|r| r.fsm == "Door" && r.timestamp_us > 0));
+    assert_eq!(InspectJsonKind::Dispatch { current_states: "[Closed]".into() }, push[0].kind);
+
+    // the records of a dispatch share its id, and the ids increase with every dispatch
+    let first = push[0].dispatch_id;
+    let last = push.last().unwrap().dispatch_id;
+    assert!(push.iter().any(|r| r.dispatch_id == first && r.kind == InspectJsonKind::StateEnter { state: "Open".into() }));
+    assert!(push.iter().any(|r| r.dispatch_id == last && r.kind == InspectJsonKind::Unhandled));
+    assert!(first > 0 && last > first);
+    assert!(push.iter().all(|r| r.dispatch_id == first || r.dispatch_id == last));
+
+    assert!(output.lines().next().unwrap().contains(r#""kind":"dispatch""#));
+
+    Ok(())
+}
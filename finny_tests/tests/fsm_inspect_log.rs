@@ -0,0 +1,62 @@
+extern crate finny;
+
+use std::sync::Mutex;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::log::{InspectLog, InspectLogLevels}};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+#[derive(Default)]
+pub struct LampContext;
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct On;
+
+#[derive(Clone)]
+pub struct Toggle;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Lamp, LampContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+    fsm.state::<Off>().on_event::<Toggle>().transition_to::<On>();
+    fsm.state::<On>();
+    fsm.build()
+}
+
+struct Recorder {
+    lines: Mutex<Vec<(Level, String)>>
+}
+
+impl Log for Recorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder { lines: Mutex::new(Vec::new()) };
+
+#[test]
+fn test_inspect_log() -> FsmResult<()> {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let levels = InspectLogLevels { unhandled: Level::Error, ..Default::default() };
+    let mut fsm = Lamp::new_with(LampContext, FsmEventQueueVec::new(), InspectLog::with_levels(levels), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Toggle)?;
+    assert!(fsm.dispatch(Toggle).is_err());
+
+    let lines = RECORDER.lines.lock().unwrap();
+    assert!(lines.contains(&(Level::Info, "[Lamp] #2 Toggle: Matched transition LampTransition2".into())));
+    assert!(lines.contains(&(Level::Debug, "[Lamp] #2 Toggle: Dispatch done, the current states are [On]".into())));
+    assert!(lines.contains(&(Level::Error, "[Lamp] #3 Toggle: No transition for the event".into())));
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::opentelemetry::InspectOpenTelemetry};
+use tracing::{Event, Span, Subscriber, field::{Field, Visit}, info_span, span};
+use tracing_subscriber::{Layer, layer::{Context, SubscriberExt}, registry::LookupSpan};
+
+#[derive(Default)]
+pub struct OrderContext;
+
+#[derive(Default)]
+pub struct Created;
+#[derive(Default)]
+pub struct Paid;
+
+#[derive(Clone)]
+pub struct Pay {
+    span: Span
+}
+#[derive(Clone)]
+pub struct Refund;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Order, OrderContext>) -> BuiltFsm {
+    fsm.initial_state::<Created>();
+    fsm.event_trace_context::<Pay>(|ev| &ev.span);
+    fsm.state::<Created>().on_event::<Pay>().transition_to::<Paid>();
+    fsm.state::<Paid>()
+        .on_event::<Refund>()
+        .transition_to::<Created>()
+        .guard(|_ev, _ctx, _states| true)
+        .try_action(|_ev, _ctx, _from, _to| Err(FsmError::ActionFailed("declined")));
+    fsm.build()
+}
+
+/// The name of the span and of its parent.
+type SpanParent = (String, Option<String>);
+
+/// Records the new spans with the name of their parent, the messages of the events with the name of
+/// their span and the recorded statuses of the spans.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<SpanParent>>>,
+    events: Arc<Mutex<Vec<SpanParent>>>,
+    statuses: Arc<Mutex<Vec<SpanParent>>>
+}
+
+/// The message of an event, or the status code of a span.
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    status_code: Option<String>
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "otel.status_code" {
+            self.status_code = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<S> Layer<S> for Recorder where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let parent = span.parent().map(|p| p.name().to_string());
+        self.spans.lock().unwrap().push((span.name().to_string(), parent));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let span = ctx.event_span(event).map(|s| s.name().to_string());
+        self.events.lock().unwrap().push((fields.message.unwrap_or_default(), span));
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if let Some(status_code) = fields.status_code {
+            self.statuses.lock().unwrap().push((ctx.span(id).unwrap().name().to_string(), Some(status_code)));
+        }
+    }
+}
+
+#[test]
+fn test_inspect_opentelemetry() -> FsmResult<()> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let mut fsm = Order::new_with(OrderContext, FsmEventQueueVec::new(), InspectOpenTelemetry::new(), FsmTimersNull)?;
+        fsm.start()?;
+
+        // the event leaves the request's scope before it's dispatched
+        let pay = {
+            let request = info_span!("http_request");
+            let _entered = request.enter();
+            Pay { span: Span::current() }
+        };
+        fsm.dispatch(pay)?;
+
+        Ok::<_, finny::FsmError>(())
+    })?;
+
+    let spans = recorder.spans.lock().unwrap();
+    let parent_of_dispatch: Vec<_> = spans.iter().filter(|s| s.0 == "fsm_dispatch").map(|s| s.1.as_deref()).collect();
+    assert_eq!(vec![None, Some("http_request")], parent_of_dispatch);
+    let parent_of_transition: Vec<_> = spans.iter().filter(|s| s.0 == "fsm_transition").map(|s| s.1.as_deref()).collect();
+    assert_eq!(vec![Some("fsm_dispatch"), Some("fsm_dispatch")], parent_of_transition);
+
+    Ok(())
+}
+
+#[test]
+fn test_inspect_opentelemetry_span_events() -> FsmResult<()> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let mut fsm = Order::new_with(OrderContext, FsmEventQueueVec::new(), InspectOpenTelemetry::new(), FsmTimersNull)?;
+        fsm.start()?;
+        fsm.dispatch(Pay { span: Span::none() })?;
+        recorder.events.lock().unwrap().clear();
+
+        assert_eq!(Err(FsmError::ActionFailed("declined")), fsm.dispatch(Refund));
+        Ok::<_, FsmError>(())
+    })?;
+
+    let events = recorder.events.lock().unwrap();
+    let in_span = |message: &str, span: &str| events.contains(&(message.to_string(), Some(span.to_string())));
+    assert!(in_span("Guard evaluated", "fsm_dispatch"));
+    assert!(in_span("Exiting state", "fsm_transition"));
+    assert!(in_span("Executing action", "fsm_transition"));
+    assert!(in_span("Entering state", "fsm_transition"));
+
+    // both the transition and its dispatch failed
+    let statuses = recorder.statuses.lock().unwrap();
+    assert!(statuses.contains(&("fsm_transition".to_string(), Some("ERROR".to_string()))));
+    assert!(statuses.contains(&("fsm_dispatch".to_string(), Some("ERROR".to_string()))));
+
+    Ok(())
+}
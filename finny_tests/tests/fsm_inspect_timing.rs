@@ -0,0 +1,74 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmInfoEvent, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::timing::InspectTiming};
+
+#[derive(Default)]
+pub struct LoopContext {
+    slow: bool
+}
+
+#[derive(Default)]
+pub struct Stopped;
+#[derive(Default)]
+pub struct Running;
+
+#[derive(Clone)]
+pub struct Enable;
+#[derive(Clone)]
+pub struct Tick;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<ControlLoop, LoopContext>) -> BuiltFsm {
+    fsm.initial_state::<Stopped>();
+
+    fsm.state::<Stopped>()
+        .on_event::<Enable>()
+        .transition_to::<Running>();
+
+    fsm.state::<Running>()
+        .on_event::<Tick>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            // every other tick blows the budget
+            ctx.slow = !ctx.slow;
+            if ctx.slow {
+                std::thread::sleep(Duration::from_millis(2));
+            }
+        });
+
+    fsm.build()
+}
+
+fn transition_id(event: &'static str) -> &'static str {
+    ControlLoop::fsm_info().transitions().find(|t| t.event == FsmInfoEvent::Event(event)).unwrap().transition_id
+}
+
+#[test]
+fn test_inspect_timing() -> FsmResult<()> {
+    let timing = InspectTiming::new();
+    let mut fsm = ControlLoop::new_with(LoopContext::default(), FsmEventQueueVec::new(), timing.clone(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Enable)?;
+    for _ in 0..4 {
+        fsm.dispatch(Tick)?;
+    }
+
+    let budget = Duration::from_millis(1);
+    let tick = timing.action_time("ControlLoop", transition_id("Tick")).unwrap();
+    assert_eq!(4, tick.count);
+    assert_eq!(2, tick.over_budget(budget));
+    assert!(tick.max >= Duration::from_millis(2) && tick.sum >= Duration::from_millis(4));
+
+    let dispatch = timing.dispatch_time("ControlLoop", transition_id("Tick")).unwrap();
+    assert_eq!(4, dispatch.count);
+    assert!(dispatch.max >= tick.max);
+
+    // the transitions that blow the budget
+    let slow: Vec<_> = timing.actions().into_iter().filter(|t| t.histogram.over_budget(budget) > 0).map(|t| t.transition).collect();
+    assert_eq!(vec![transition_id("Tick").to_string()], slow);
+    assert_eq!(1, timing.action_time("ControlLoop", transition_id("Enable")).unwrap().count);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::tracing::InspectTracing};
+use tracing::{Subscriber, field::{Field, Visit}, span};
+use tracing_subscriber::{Layer, layer::{Context, SubscriberExt}, registry::LookupSpan};
+
+#[derive(Default)]
+pub struct LampContext;
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct On;
+
+#[derive(Clone)]
+pub struct Toggle;
+#[derive(Clone)]
+pub struct Dim;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Lamp, LampContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+    fsm.state::<Off>().on_event::<Toggle>().transition_to::<On>();
+    fsm.state::<On>().on_event::<Dim>().internal_transition().guard(|_, _, _| false);
+    fsm.build()
+}
+
+/// Records the messages of the events, prefixed with the fields of their span.
+#[derive(Clone, Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>
+}
+
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() { self.0.push(' '); }
+        if field.name() == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S> Layer<S> for Recorder where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let span = ctx.event_span(event).map(|s| {
+            let ext = s.extensions();
+            format!("[{}] ", ext.get::<Fields>().map(|f| f.0.clone()).unwrap_or_default())
+        }).unwrap_or_default();
+
+        self.lines.lock().unwrap().push(format!("{}{}", span, fields.0));
+    }
+}
+
+#[test]
+fn test_inspect_tracing() -> FsmResult<()> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let mut fsm = Lamp::new_with(LampContext, FsmEventQueueVec::new(), InspectTracing::new(), FsmTimersNull)?;
+        fsm.start()?;
+        fsm.dispatch(Toggle)?;
+        let _ = fsm.dispatch(Dim);
+        Ok::<_, finny::FsmError>(())
+    })?;
+
+    let lines = recorder.lines.lock().unwrap();
+
+    assert!(lines.iter().any(|l| l.starts_with("[fsm=\"Lamp\" event=Toggle start_state=[Off]") && l.contains("Matched transition transition=\"LampTransition")));
+    assert!(lines.iter().any(|l| l.contains("Entering state state=\"On\"")));
+    assert!(lines.iter().any(|l| l.contains("event=Dim start_state=[On]") && l.contains("Guard rejected")));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+extern crate finny;
+
+use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct MermaidContext;
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Running;
+#[derive(Default)]
+pub struct Done;
+#[derive(Default)]
+pub struct LightOff;
+
+#[derive(Debug, Clone)]
+pub struct Go;
+#[derive(Debug, Clone)]
+pub struct Tick;
+#[derive(Debug, Clone)]
+pub struct Finish;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<MermaidMachine, MermaidContext>) -> BuiltFsm {
+    fsm.initial_states::<(Idle, LightOff)>();
+
+    fsm.state::<Idle>()
+        .on_event::<Go>()
+        .transition_to::<Running>()
+        .guard(|_, _, _| true);
+
+    fsm.state::<Running>()
+        .on_event::<Tick>()
+        .internal_transition();
+
+    fsm.state::<Running>()
+        .on_event::<Go>()
+        .transition_to::<MermaidSubMachine>();
+
+    fsm.sub_machine::<MermaidSubMachine>()
+        .with_context(|_| MermaidContext)
+        .on_event::<Finish>()
+        .transition_to::<Done>();
+
+    fsm.state::<Done>().final_state();
+    fsm.state::<LightOff>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct SubIdle;
+
+#[finny_fsm]
+fn build_sub_fsm(mut fsm: FsmBuilder<MermaidSubMachine, MermaidContext>) -> BuiltFsm {
+    fsm.initial_state::<SubIdle>();
+    fsm.state::<SubIdle>();
+    fsm.build()
+}
+
+#[test]
+fn test_mermaid() {
+    let mermaid = MermaidMachine::to_mermaid();
+    assert!(mermaid.starts_with("stateDiagram-v2\nstate \"MermaidMachine\" as MermaidMachine {\n"));
+    assert!(mermaid.trim_end().ends_with('}'));
+    assert!(mermaid.contains("\n--\n"));
+    assert!(mermaid.contains("[*] --> MermaidMachine_Idle\n"));
+    assert!(mermaid.contains("[*] --> MermaidMachine_LightOff\n"));
+    assert!(mermaid.contains("MermaidMachine_Idle --> MermaidMachine_Running : Go [guard]\n"));
+    assert!(mermaid.contains("MermaidMachine_Running --> MermaidMachine_Running : Tick (Internal)\n"));
+    assert!(mermaid.contains("MermaidMachine_MermaidSubMachine --> MermaidMachine_Done : Finish\n"));
+    assert!(mermaid.contains("MermaidMachine_Done --> [*]\n"));
+    assert!(mermaid.contains("state \"MermaidSubMachine\" as MermaidMachine_MermaidSubMachine {\nstate \"SubIdle\" as MermaidSubMachine_SubIdle\n[*] --> MermaidSubMachine_SubIdle\n}\n"));
+}
@@ -0,0 +1,105 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::metrics::{FsmMetricsRecorder, InspectMetrics}};
+
+#[derive(Debug, Default)]
+pub struct TurnstileContext {
+    coins: usize
+}
+
+#[derive(Default)]
+pub struct Locked;
+#[derive(Default)]
+pub struct Unlocked;
+
+#[derive(Clone)]
+pub struct Coin;
+#[derive(Clone)]
+pub struct Push;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Turnstile, TurnstileContext>) -> BuiltFsm {
+    fsm.initial_state::<Locked>();
+
+    fsm.state::<Locked>()
+        .on_event::<Coin>()
+        .transition_to::<Unlocked>()
+        .guard(|_, ctx, _| ctx.coins > 0);
+
+    fsm.state::<Unlocked>()
+        .on_event::<Push>()
+        .transition_to::<Locked>();
+
+    fsm.state::<Unlocked>()
+        .on_event::<Coin>()
+        .internal_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_metrics() -> FsmResult<()> {
+    let metrics = InspectMetrics::new().with_info(Turnstile::fsm_info());
+
+    for _ in 0..2 {
+        let mut fsm = Turnstile::new_with(TurnstileContext { coins: 1 }, FsmEventQueueVec::new(), metrics.clone(), FsmTimersNull)?;
+        fsm.start()?;
+        fsm.dispatch(Coin)?;
+        fsm.dispatch(Coin)?;
+        fsm.dispatch(Push)?;
+        assert!(fsm.dispatch(Push).is_err());
+    }
+
+    assert_eq!(4, metrics.events_dispatched("Turnstile", "Push"));
+    assert_eq!(2, metrics.events_unhandled("Turnstile", "Push"));
+    assert_eq!(2, metrics.transitions("Turnstile", "Locked", "Unlocked", "Coin"));
+    assert_eq!(2, metrics.transitions("Turnstile", "Unlocked", "Unlocked", "Coin"));
+    assert_eq!(2, metrics.transitions("Turnstile", "Unlocked", "Locked", "Push"));
+
+    let latency = metrics.dispatch_latency("Turnstile").unwrap();
+    assert_eq!(10, latency.count);
+    assert_eq!(Some(&(1.0, 10)), latency.buckets.last());
+
+    let rendered = metrics.render();
+    assert!(rendered.contains("# TYPE finny_transitions_total counter\n"));
+    assert!(rendered.contains("finny_transitions_total{fsm=\"Turnstile\",from=\"Unlocked\",to=\"Locked\",event=\"Push\"} 2\n"));
+    assert!(rendered.contains("finny_events_unhandled_total{fsm=\"Turnstile\",event=\"Push\"} 2\n"));
+    assert!(rendered.contains("finny_dispatch_duration_seconds_count{fsm=\"Turnstile\"} 10\n"));
+
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct Recorded(Arc<Mutex<Vec<String>>>);
+
+impl FsmMetricsRecorder for Recorded {
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)]) {
+        self.0.lock().unwrap().push(format!("{} {:?}", name, labels));
+    }
+
+    fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], _value: f64) {
+        self.0.lock().unwrap().push(format!("{} {:?}", name, labels));
+    }
+}
+
+#[test]
+fn test_metrics_recorder() -> FsmResult<()> {
+    let recorded = Recorded::default();
+    // the transitions are labeled without registering the description
+    let metrics = InspectMetrics::new().with_recorder(recorded.clone());
+
+    let mut fsm = Turnstile::new_with(TurnstileContext { coins: 1 }, FsmEventQueueVec::new(), metrics.clone(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Coin)?;
+    assert_eq!(1, metrics.transitions("Turnstile", "Locked", "Unlocked", "Coin"));
+    assert!(metrics.render().contains("finny_transitions_total{fsm=\"Turnstile\",from=\"Locked\",to=\"Unlocked\",event=\"Coin\"} 1\n"));
+
+    let recorded = recorded.0.lock().unwrap();
+    assert!(recorded.contains(&r#"finny_events_dispatched_total [("fsm", "Turnstile"), ("event", "Coin")]"#.to_string()));
+    assert!(recorded.contains(&r#"finny_transitions_total [("fsm", "Turnstile"), ("from", "Locked"), ("to", "Unlocked"), ("event", "Coin")]"#.to_string()));
+    assert_eq!(2, recorded.iter().filter(|r| r.starts_with("finny_dispatch_duration_seconds ")).count());
+
+    Ok(())
+}
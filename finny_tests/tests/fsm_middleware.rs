@@ -0,0 +1,103 @@
+extern crate finny;
+
+use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+use finny::{FsmBackendImpl, FsmCurrentState, FsmError, FsmEvent, FsmFactory, FsmMiddleware, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct SafeContext {
+    knocks: usize
+}
+
+#[derive(Default)]
+pub struct Locked;
+#[derive(Default)]
+pub struct Unlocked;
+
+#[derive(Clone)]
+pub struct Unlock {
+    pin: u16
+}
+#[derive(Clone)]
+pub struct Knock;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Safe, SafeContext>) -> BuiltFsm {
+    fsm.initial_state::<Locked>();
+
+    fsm.state::<Locked>()
+        .on_event::<Unlock>()
+        .transition_to::<Unlocked>();
+
+    fsm.state::<Locked>()
+        .on_event::<Knock>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.knocks += 1;
+        });
+
+    fsm.state::<Unlocked>();
+
+    fsm.build()
+}
+
+struct Authenticator;
+
+impl FsmMiddleware<Safe> for Authenticator {
+    fn before_dispatch(&mut self, event: FsmEvent<SafeEvents, SafeTimers>, _backend: &FsmBackendImpl<Safe>) -> FsmResult<Option<FsmEvent<SafeEvents, SafeTimers>>> {
+        match event {
+            FsmEvent::Event(SafeEvents::Unlock(Unlock { pin })) if pin != 1234 => Err(FsmError::EventRejected("wrong pin")),
+            event => Ok(Some(event))
+        }
+    }
+}
+
+struct RateLimiter {
+    remaining: usize,
+    dispatched: Arc<AtomicUsize>
+}
+
+impl FsmMiddleware<Safe> for RateLimiter {
+    fn before_dispatch(&mut self, event: FsmEvent<SafeEvents, SafeTimers>, backend: &FsmBackendImpl<Safe>) -> FsmResult<Option<FsmEvent<SafeEvents, SafeTimers>>> {
+        match event {
+            FsmEvent::Event(SafeEvents::Knock(_)) if self.remaining == 0 => Ok(None),
+            FsmEvent::Event(SafeEvents::Knock(_)) => {
+                assert_eq!(2 - self.remaining, backend.knocks);
+                self.remaining -= 1;
+                Ok(Some(event))
+            },
+            event => Ok(Some(event))
+        }
+    }
+
+    fn after_dispatch(&mut self, result: &FsmResult<()>, _backend: &FsmBackendImpl<Safe>) {
+        if result.is_ok() {
+            self.dispatched.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn test_middleware() -> FsmResult<()> {
+    let mut fsm = Safe::new(SafeContext::default())?;
+    fsm.middlewares.add(Authenticator);
+    let dispatched = Arc::new(AtomicUsize::new(0));
+    fsm.middlewares.add(RateLimiter { remaining: 2, dispatched: dispatched.clone() });
+    fsm.start()?;
+
+    // dropped after the second one
+    for _ in 0..5 {
+        fsm.dispatch(Knock)?;
+    }
+    assert_eq!(2, fsm.knocks);
+    // the start and the knocks that got through
+    assert_eq!(3, dispatched.load(Ordering::SeqCst));
+
+    assert_eq!(Err(FsmError::EventRejected("wrong pin")), fsm.dispatch(Unlock { pin: 1111 }));
+    assert_eq!(FsmCurrentState::State(SafeCurrentState::Locked), fsm.get_current_states()[0]);
+
+    fsm.dispatch(Unlock { pin: 1234 })?;
+    assert_eq!(FsmCurrentState::State(SafeCurrentState::Unlocked), fsm.get_current_states()[0]);
+
+    Ok(())
+}
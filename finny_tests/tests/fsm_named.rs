@@ -0,0 +1,115 @@
+extern crate finny;
+
+use std::sync::Mutex;
+
+use finny::{EventContext, FsmEventQueue, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::log::InspectLog};
+use log::{LevelFilter, Log, Metadata, Record};
+
+#[derive(Default)]
+pub struct ParkingContext {
+    credit: usize,
+    charged: usize
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+#[derive(Default)]
+pub struct Service;
+
+#[derive(Clone)]
+pub struct Ticket;
+#[derive(Clone)]
+pub struct Card;
+#[derive(Clone)]
+pub struct Passed;
+
+fn has_credit<E, Q: FsmEventQueue<Parking>>(_ev: &E, ctx: &EventContext<Parking, Q>, _states: &ParkingStates) -> bool {
+    ctx.credit > 0
+}
+
+fn charge<E, Q: FsmEventQueue<Parking>>(_ev: &E, ctx: &mut EventContext<Parking, Q>, _from: &mut Closed, _to: &mut Open) {
+    ctx.credit -= 1;
+    ctx.charged += 1;
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Parking, ParkingContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+
+    fsm.state::<Closed>()
+        .on_event::<Ticket>()
+        .transition_to::<Open>()
+        .guard(has_credit)
+        .action(charge);
+
+    fsm.state::<Closed>()
+        .on_event::<Card>()
+        .transition_to::<Open>()
+        .guard(has_credit)
+        .action(charge);
+
+    fsm.state::<Closed>()
+        .on_event::<Card>()
+        .transition_to::<Service>();
+
+    fsm.state::<Open>()
+        .on_event::<Passed>()
+        .transition_to::<Closed>();
+
+    fsm.state::<Service>();
+
+    fsm.build()
+}
+
+struct Recorder {
+    lines: Mutex<Vec<String>>
+}
+
+impl Log for Recorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder { lines: Mutex::new(Vec::new()) };
+
+#[test]
+fn test_named_guards_and_actions() -> FsmResult<()> {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut fsm = Parking::new_with(ParkingContext { credit: 1, charged: 0 }, FsmEventQueueVec::new(), InspectLog::new(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Ticket)?;
+    fsm.dispatch(Passed)?;
+    assert_eq!(1, fsm.charged);
+
+    // out of credit
+    fsm.dispatch(Card)?;
+    assert_eq!(finny::FsmCurrentState::State(ParkingCurrentState::Service), fsm.get_current_states()[0]);
+
+    let lines = RECORDER.lines.lock().unwrap();
+    assert!(lines.contains(&"[Parking] #2 Ticket: Guard has_credit evaluated to true".into()));
+    assert!(lines.contains(&"[Parking] #2 Ticket: Executing charge".into()));
+    assert!(lines.contains(&"[Parking] #4 Card: Guard has_credit evaluated to false".into()));
+
+    Ok(())
+}
+
+#[test]
+fn test_named_guards_in_the_description() {
+    let guarded: Vec<_> = Parking::fsm_info().transitions().filter_map(|t| t.guard).collect();
+    assert_eq!(vec!["has_credit", "has_credit"], guarded);
+    assert_eq!(2, Parking::fsm_info().transitions().filter(|t| t.action == Some("charge")).count());
+
+    let mermaid = Parking::to_mermaid();
+    assert!(mermaid.contains("Parking_Closed --> Parking_Open : Ticket [has_credit] / charge\n"));
+}
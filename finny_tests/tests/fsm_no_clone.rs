@@ -0,0 +1,60 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct LinkContext {
+    received: usize
+}
+
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Connected;
+
+/// Not cloneable, the payload is only ever borrowed by the machine.
+pub struct Packet {
+    payload: Vec<u8>
+}
+pub struct Handshake;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, LinkContext>) -> BuiltFsm {
+    fsm.events_without_clone();
+    fsm.initial_state::<Connecting>();
+
+    fsm.state::<Connecting>()
+        .on_event::<Packet>()
+        .transition_to::<Connected>()
+        .action(|ev, ctx, _, _| {
+            ctx.received += ev.payload.len();
+            ctx.queue.enqueue(Handshake).unwrap();
+        });
+
+    fsm.state::<Connected>()
+        .on_event::<Packet>()
+        .internal_transition()
+        .action(|ev, ctx, _| {
+            ctx.received += ev.payload.len();
+        });
+
+    fsm.state::<Connected>()
+        .on_event::<Handshake>()
+        .internal_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_events_without_clone() -> FsmResult<()> {
+    let mut fsm = Link::new(LinkContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Packet { payload: vec![0; 4096] })?;
+    fsm.dispatch(Packet { payload: vec![0; 1024] })?;
+
+    assert_eq!(FsmCurrentState::State(LinkCurrentState::Connected), fsm.get_current_states()[0]);
+    assert_eq!(5120, fsm.received);
+
+    Ok(())
+}
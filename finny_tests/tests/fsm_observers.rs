@@ -0,0 +1,57 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex};
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct PlayerContext;
+
+#[derive(Default)]
+pub struct Stopped;
+#[derive(Default)]
+pub struct Playing;
+
+#[derive(Clone)]
+pub struct Play;
+#[derive(Clone)]
+pub struct Seek;
+#[derive(Clone)]
+pub struct Stop;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Player, PlayerContext>) -> BuiltFsm {
+    fsm.initial_state::<Stopped>();
+    fsm.state::<Stopped>().on_event::<Play>().transition_to::<Playing>();
+    fsm.state::<Playing>().on_event::<Seek>().internal_transition();
+    fsm.state::<Playing>().on_event::<Stop>().transition_to::<Stopped>();
+    fsm.build()
+}
+
+#[test]
+fn test_observers() -> FsmResult<()> {
+    let mut fsm = Player::new(PlayerContext)?;
+
+    let events = Arc::new(Mutex::new(vec![]));
+    {
+        let events = events.clone();
+        fsm.observers.subscribe(move |change| events.lock().unwrap().push(change.event.clone()));
+    }
+    let changes = fsm.observers.subscribe_channel();
+
+    fsm.start()?;
+    fsm.dispatch(Play)?;
+    fsm.dispatch(Seek)?;
+    fsm.dispatch(Stop)?;
+
+    // the internal transition doesn't change the state
+    assert_eq!(vec!["Fsm::Start", "Play", "Stop"], *events.lock().unwrap());
+
+    let changes: Vec<_> = changes.try_iter().collect();
+    assert_eq!(3, changes.len());
+    assert_eq!(FsmCurrentState::Stopped, changes[0].from);
+    assert_eq!(FsmCurrentState::State(PlayerCurrentState::Stopped), changes[1].from);
+    assert_eq!(FsmCurrentState::State(PlayerCurrentState::Playing), changes[1].to);
+
+    Ok(())
+}
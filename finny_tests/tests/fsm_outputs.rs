@@ -0,0 +1,72 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct LinkContext {
+    sequence: u8
+}
+
+#[derive(Default)]
+pub struct Down;
+#[derive(Default)]
+pub struct Up;
+
+#[derive(Clone)]
+pub struct Connect;
+#[derive(Clone)]
+pub struct Send {
+    payload: u8
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    SetLed(bool),
+    SendFrame { sequence: u8, payload: u8 }
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, LinkContext>) -> BuiltFsm {
+    fsm.initial_state::<Down>();
+    fsm.outputs::<Command>();
+
+    fsm.state::<Down>()
+        .on_event::<Connect>()
+        .transition_to::<Up>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.outputs.push(Command::SetLed(true)).unwrap();
+        });
+
+    fsm.state::<Up>()
+        .on_event::<Send>()
+        .internal_transition()
+        .action(|ev, ctx, _state| {
+            ctx.sequence += 1;
+            let frame = Command::SendFrame { sequence: ctx.sequence, payload: ev.payload };
+            ctx.outputs.push(frame).unwrap();
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_outputs() -> FsmResult<()> {
+    let mut fsm = Link::new(LinkContext::default())?;
+    fsm.start()?;
+    assert_eq!(0, fsm.drain_outputs().count());
+
+    fsm.dispatch(Connect)?;
+    fsm.dispatch(Send { payload: 7 })?;
+    fsm.dispatch(Send { payload: 9 })?;
+    assert_eq!(FsmCurrentState::State(LinkCurrentState::Up), fsm.get_current_states()[0]);
+
+    let commands: Vec<_> = fsm.drain_outputs().collect();
+    assert_eq!(vec![
+        Command::SetLed(true),
+        Command::SendFrame { sequence: 1, payload: 7 },
+        Command::SendFrame { sequence: 2, payload: 9 }
+    ], commands);
+    assert!(fsm.backend.outputs.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmInfoTransitionKind, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct WorkerContext {
+    capacity: usize,
+    started: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Busy;
+
+#[derive(Clone)]
+pub struct Job { size: usize }
+#[derive(Clone)]
+pub struct Done;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Worker, WorkerContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Job>()
+        .transition_to::<Busy>()
+        .guard(|ev, ctx, _| ev.size <= ctx.capacity)
+        .action(|_ev, ctx, _from, _to| { ctx.started += 1; });
+
+    fsm.state::<Busy>()
+        .on_event::<Done>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_peek_dispatch() -> FsmResult<()> {
+    let mut fsm = Worker::new(WorkerContext { capacity: 4, started: 0 })?;
+    fsm.start()?;
+
+    assert!(fsm.peek_dispatch(&Job { size: 8 })?.is_none());
+    assert!(fsm.peek_dispatch(&Done)?.is_none());
+
+    let transition = fsm.peek_dispatch(&Job { size: 2 })?.expect("The job should be accepted");
+    assert_eq!(FsmInfoTransitionKind::NormalTransition { from_state: "Idle", to_state: "Busy" }, transition.kind);
+
+    // nothing was committed
+    assert_eq!(0, fsm.started);
+    assert_eq!(FsmCurrentState::State(WorkerCurrentState::Idle), fsm.get_current_states()[0]);
+
+    fsm.dispatch(Job { size: 2 })?;
+    assert_eq!(1, fsm.started);
+    assert!(fsm.peek_dispatch(&Job { size: 2 })?.is_none());
+    assert!(fsm.peek_dispatch(&Done)?.is_some());
+
+    Ok(())
+}
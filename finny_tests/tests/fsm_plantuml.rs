@@ -0,0 +1,65 @@
+#![cfg(feature = "generate_plantuml")]
+
+extern crate finny;
+
+use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct DiagramContext;
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Running;
+#[derive(Default)]
+pub struct LightOff;
+
+#[derive(Debug, Clone)]
+pub struct Go;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<DiagramMachine, DiagramContext>) -> BuiltFsm {
+    fsm.initial_states::<(Idle, LightOff)>();
+
+    fsm.state::<Idle>()
+        .on_event::<Go>()
+        .transition_to::<Running>();
+
+    fsm.state::<Running>()
+        .on_event::<Go>()
+        .transition_to::<DiagramSubMachine>();
+
+    fsm.sub_machine::<DiagramSubMachine>()
+        .with_context(|_| DiagramContext);
+
+    fsm.state::<LightOff>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct SubIdle;
+
+#[finny_fsm]
+fn build_sub_fsm(mut fsm: FsmBuilder<DiagramSubMachine, DiagramContext>) -> BuiltFsm {
+    fsm.initial_state::<SubIdle>();
+    fsm.state::<SubIdle>();
+    fsm.build()
+}
+
+#[test]
+fn test_plantuml_regions_and_submachines() {
+    let uml = DiagramMachineInfo::plantuml();
+
+    assert!(uml.starts_with("@startuml DiagramMachine\nstate DiagramMachine {\n"));
+    assert!(uml.trim_end().ends_with("@enduml"));
+
+    // the regions are separated as concurrent states
+    let (first_region, second_region) = uml.split_at(uml.find("\n--\n").expect("Missing the region separator"));
+    assert!(first_region.contains("[*] --> Idle : Start"));
+    assert!(first_region.contains("Running --> DiagramSubMachine : Go"));
+    assert!(second_region.contains("[*] --> LightOff : Start"));
+
+    // the submachine is a composite state within its region
+    assert!(first_region.contains("state DiagramSubMachine {\nstate SubIdle {\n}\n[*] --> SubIdle : Start"));
+}
@@ -0,0 +1,70 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmInlineFrontend, FsmPool, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct ConnectionContext {
+    received: usize
+}
+
+#[derive(Default)]
+pub struct Handshake;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone)]
+pub struct Hello;
+#[derive(Clone)]
+pub struct Data;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Connection, ConnectionContext>) -> BuiltFsm {
+    fsm.initial_state::<Handshake>();
+
+    fsm.state::<Handshake>()
+        .on_event::<Hello>()
+        .transition_to::<Open>();
+
+    fsm.state::<Open>()
+        .on_event::<Data>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| { ctx.received += 1; });
+
+    fsm.build()
+}
+
+type ConnectionPool = FsmPool<Connection, finny::FsmEventQueueBounded<Connection, 4>, finny::inspect::null::InspectNull, finny::timers::std::TimersStd<Connection>>;
+
+fn spawn(pool: &mut ConnectionPool) -> FsmResult<finny::FsmPoolId> {
+    let mut fsm: FsmInlineFrontend<Connection, 4> = Connection::new_with_capacity(ConnectionContext::default())?;
+    fsm.start()?;
+    Ok(pool.spawn(fsm))
+}
+
+#[test]
+fn test_pool() -> FsmResult<()> {
+    let mut pool = ConnectionPool::with_capacity(1000);
+
+    let ids = (0..1000).map(|_| spawn(&mut pool)).collect::<FsmResult<Vec<_>>>()?;
+    assert_eq!(1000, pool.len());
+
+    pool.dispatch(ids[7], Hello)?;
+    pool.dispatch(ids[7], Data)?;
+    assert_eq!(1, pool.get(ids[7]).unwrap().received);
+    assert_eq!(FsmCurrentState::State(ConnectionCurrentState::Open), pool.get(ids[7]).unwrap().get_current_states()[0]);
+    assert_eq!(0, pool.get(ids[8]).unwrap().received);
+
+    let despawned = pool.despawn(ids[7]).expect("The connection should be in the pool");
+    assert_eq!(1, despawned.received);
+    assert_eq!(999, pool.len());
+    assert_eq!(Err(FsmError::PoolInstanceNotFound), pool.dispatch(ids[7], Data));
+
+    // the slot is reused, the old id doesn't refer to the new machine
+    let id = spawn(&mut pool)?;
+    assert_eq!(ids[7].index(), id.index());
+    assert!(!pool.contains(ids[7]));
+    assert!(pool.despawn(ids[7]).is_none());
+    assert_eq!(1000, pool.iter().count());
+
+    Ok(())
+}
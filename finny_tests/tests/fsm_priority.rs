@@ -0,0 +1,57 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueuePriority, FsmEventQueueSender, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull};
+
+#[derive(Default)]
+pub struct MotorContext {
+    telemetry: usize
+}
+
+#[derive(Default)]
+pub struct Running;
+#[derive(Default)]
+pub struct Stopped;
+
+#[derive(Clone, Debug)]
+pub struct Telemetry;
+#[derive(Clone, Debug)]
+pub struct EmergencyStop;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Motor, MotorContext>) -> BuiltFsm {
+    fsm.initial_state::<Running>();
+    fsm.event_priority::<EmergencyStop>(10);
+
+    fsm.state::<Running>()
+        .on_event::<Telemetry>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.telemetry += 1;
+        });
+
+    fsm.state::<Running>()
+        .on_event::<EmergencyStop>()
+        .transition_to::<Stopped>();
+
+    fsm.state::<Stopped>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_priority_queue() -> FsmResult<()> {
+    let mut fsm = Motor::new_with(MotorContext::default(), FsmEventQueuePriority::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    for _ in 0..5 {
+        fsm.queue.enqueue(Telemetry)?;
+    }
+    fsm.queue.enqueue(EmergencyStop)?;
+
+    // the stop preempts the telemetry, which is no longer handled afterwards
+    fsm.dispatch_queue()?;
+    assert_eq!(0, fsm.telemetry);
+    assert_eq!(FsmCurrentState::State(MotorCurrentState::Stopped), fsm.get_current_states()[0]);
+
+    Ok(())
+}
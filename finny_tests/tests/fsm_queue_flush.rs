@@ -0,0 +1,77 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueue, FsmEventQueueIter, FsmEventQueueSender, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct PumpContext {
+    commands: usize
+}
+
+#[derive(Default)]
+pub struct Running;
+#[derive(Default)]
+pub struct Faulted;
+
+#[derive(Clone)]
+pub struct Command;
+#[derive(Clone)]
+pub struct Failure;
+#[derive(Clone)]
+pub struct Reset;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Pump, PumpContext>) -> BuiltFsm {
+    fsm.initial_state::<Running>();
+
+    fsm.state::<Running>()
+        .on_event::<Command>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.commands += 1;
+        });
+
+    fsm.state::<Running>()
+        .on_event::<Failure>()
+        .transition_to::<Faulted>();
+
+    fsm.state::<Faulted>()
+        .on_entry(|_state, ctx| {
+            // the commands were meant for the running pump
+            ctx.queue.retain(|ev| !matches!(ev, PumpEvents::Command(_)));
+        })
+        .on_event::<Reset>()
+        .transition_to::<Running>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_flush_the_queue_in_the_fault_state() -> FsmResult<()> {
+    let mut fsm = Pump::new(PumpContext::default())?;
+    fsm.start()?;
+
+    fsm.queue_mut().enqueue(Command)?;
+    fsm.queue_mut().enqueue(Failure)?;
+    fsm.queue_mut().enqueue(Command)?;
+    fsm.queue_mut().enqueue(Reset)?;
+    fsm.queue_mut().enqueue(Command)?;
+
+    let queued: Vec<_> = fsm.queue().iter().map(|ev| ev.as_ref().to_string()).collect();
+    assert_eq!(vec!["Command", "Failure", "Command", "Reset", "Command"], queued);
+
+    fsm.process_one()?;
+    fsm.process_one()?;
+    assert_eq!(FsmCurrentState::State(PumpCurrentState::Faulted), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.queue().len());
+
+    fsm.process()?;
+    assert_eq!(FsmCurrentState::State(PumpCurrentState::Running), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.commands);
+
+    fsm.queue_mut().enqueue(Command)?;
+    fsm.queue_mut().clear();
+    fsm.process()?;
+    assert_eq!(1, fsm.commands);
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+extern crate finny;
+
+use std::sync::{Arc, Mutex, mpsc::{channel, Receiver, Sender}};
+
+use finny::{FsmError, FsmEventQueue, FsmEventQueueBounded, FsmFactory, FsmQueueDropped, FsmQueueOverflowPolicy, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::json::{InspectJson, InspectJsonKind, InspectJsonRecord}};
+
+pub struct WorkerContext {
+    started: Sender<()>,
+    release: Receiver<()>
+}
+
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone)]
+pub struct Job;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Worker, WorkerContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>()
+        .on_event::<Job>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            let _ = ctx.started.send(());
+            let _ = ctx.release.recv();
+        });
+    fsm.build()
+}
+
+fn records() -> (InspectJson, Arc<Mutex<Vec<InspectJsonRecord>>>) {
+    let records = Arc::new(Mutex::new(Vec::<InspectJsonRecord>::new()));
+    let sink = records.clone();
+    (InspectJson::with_sink(move |record| sink.lock().unwrap().push(record.clone())), records)
+}
+
+#[test]
+fn test_queue_full() -> FsmResult<()> {
+    let (started, _) = channel();
+    let (_, release) = channel();
+    let (inspect, records) = records();
+
+    let mut fsm = Worker::new_with(WorkerContext { started, release }, FsmEventQueueBounded::<Worker, 1>::new(), inspect, FsmTimersNull)?;
+    fsm.enqueue(Job)?;
+    assert_eq!(Err(FsmError::QueueFull { capacity: 1, dropped: FsmQueueDropped::Newest }), fsm.enqueue(Job));
+    assert_eq!(1, fsm.queue_len());
+
+    let full = InspectJsonKind::QueueFull { capacity: 1, dropped: "Newest".into() };
+    assert_eq!(1, records.lock().unwrap().iter().filter(|r| r.kind == full).count());
+
+    Ok(())
+}
+
+#[test]
+fn test_queue_drop_oldest() -> FsmResult<()> {
+    let (started, _) = channel();
+    let (_, release) = channel();
+    let (inspect, records) = records();
+
+    let queue = FsmEventQueueBounded::<Worker, 1>::with_policy(FsmQueueOverflowPolicy::DropOldest);
+    let mut fsm = Worker::new_with(WorkerContext { started, release }, queue, inspect, FsmTimersNull)?;
+    fsm.enqueue(Job)?;
+    fsm.enqueue(Job)?;
+    assert_eq!(1, fsm.queue_len());
+
+    let full = InspectJsonKind::QueueFull { capacity: 1, dropped: "Oldest".into() };
+    assert_eq!(1, records.lock().unwrap().iter().filter(|r| r.kind == full).count());
+
+    Ok(())
+}
+
+#[test]
+fn test_queue_drop_newest() -> FsmResult<()> {
+    let (started, _) = channel();
+    let (_, release) = channel();
+    let (inspect, records) = records();
+
+    let queue = FsmEventQueueBounded::<Worker, 1>::with_policy(FsmQueueOverflowPolicy::DropNewest);
+    let mut fsm = Worker::new_with(WorkerContext { started, release }, queue, inspect, FsmTimersNull)?;
+    fsm.enqueue(Job)?;
+    fsm.enqueue(Job)?;
+    assert_eq!(1, fsm.queue_len());
+
+    let full = InspectJsonKind::QueueFull { capacity: 1, dropped: "Newest".into() };
+    assert_eq!(1, records.lock().unwrap().iter().filter(|r| r.kind == full).count());
+
+    Ok(())
+}
+
+#[test]
+fn test_try_dispatch() -> FsmResult<()> {
+    let (started, wait_started) = channel();
+    let (_, release) = channel();
+    let (inspect, records) = records();
+
+    let mut fsm = Worker::new_with(WorkerContext { started, release }, FsmEventQueueBounded::<Worker, 1>::new(), inspect, FsmTimersNull)?;
+    fsm.start()?;
+    fsm.try_dispatch(Job)?;
+    assert!(wait_started.try_recv().is_ok());
+
+    // busy with the pending job, that has the only place in the queue
+    fsm.enqueue(Job)?;
+    assert_eq!(Err(FsmError::QueueFull { capacity: 1, dropped: FsmQueueDropped::Newest }), fsm.try_dispatch(Job));
+    assert_eq!(1, records.lock().unwrap().iter().filter(|r| matches!(r.kind, InspectJsonKind::QueueFull { .. })).count());
+
+    fsm.process()?;
+    assert_eq!(0, fsm.queue.len());
+    assert!(wait_started.try_recv().is_ok());
+    assert!(wait_started.try_recv().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_runner_try_dispatch() -> FsmResult<()> {
+    let (started, wait_started) = channel();
+    let (release_job, release) = channel();
+    let (inspect, records) = records();
+
+    let fsm = Worker::new_with(WorkerContext { started, release }, FsmEventQueueBounded::<Worker, 1>::new(), inspect, FsmTimersNull)?;
+    let handle = fsm.spawn_runner_bounded(1)?;
+
+    // the machine is busy with the first job, there's room for only one more
+    handle.try_dispatch(Job)?;
+    wait_started.recv().unwrap();
+    handle.try_dispatch(Job)?;
+    assert_eq!(Err(FsmError::QueueFull { capacity: 1, dropped: FsmQueueDropped::Newest }), handle.try_dispatch(Job));
+
+    release_job.send(()).unwrap();
+    release_job.send(()).unwrap();
+    handle.shutdown()?;
+
+    assert_eq!(1, records.lock().unwrap().iter().filter(|r| matches!(r.kind, InspectJsonKind::QueueFull { .. })).count());
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 extern crate finny;
 
-use finny::{FsmCurrentState, FsmError, FsmEvent, FsmFrontend, FsmEventQueue, FsmEventQueueSender, FsmResult, FsmFactory, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+use finny::{FsmCurrentState, FsmError, FsmEvent, FsmFrontend, FsmEventQueue, FsmEventQueueSender, FsmQueueDropped, FsmResult, FsmFactory, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
 
 #[derive(Default)]
 pub struct StateA {
@@ -54,4 +54,19 @@ fn test_queues() -> FsmResult<()> {
     assert_eq!(1, state_b.value);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_queue_capacity() -> FsmResult<()> {
+    let mut fsm = StateMachine::new_with_capacity::<1>(())?;
+    assert_eq!(0, fsm.queue.len());
+
+    fsm.start()?;
+    fsm.dispatch(Event { n: 42 })?;
+    assert_eq!(FsmCurrentState::State(StateMachineCurrentState::StateB), fsm.get_current_states()[0]);
+
+    fsm.queue.enqueue(Event { n: 1 })?;
+    assert_eq!(Err(FsmError::QueueFull { capacity: 1, dropped: FsmQueueDropped::Newest }), fsm.queue.enqueue(Event { n: 2 }));
+
+    Ok(())
+}
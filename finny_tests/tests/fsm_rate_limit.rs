@@ -0,0 +1,83 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, FsmTimersNull, FsmTimersWithClock, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::clock::FsmClockManual};
+
+#[derive(Default)]
+pub struct PanelContext {
+    presses: usize,
+    samples: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone, Debug)]
+pub struct Press;
+#[derive(Clone, Debug)]
+pub struct Sample;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Panel, PanelContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Press>()
+        .internal_transition()
+        .debounce(Duration::from_millis(20))
+        .action(|_ev, ctx, _state| {
+            ctx.presses += 1;
+        });
+
+    fsm.state::<Idle>()
+        .on_event::<Sample>()
+        .internal_transition()
+        .throttle(Duration::from_millis(20))
+        .action(|_ev, ctx, _state| {
+            ctx.samples += 1;
+        });
+
+    fsm.build()
+}
+
+type PanelFsm = FsmFrontend<Panel, FsmEventQueueVec<Panel>, InspectNull, FsmTimersWithClock<FsmTimersNull, FsmClockManual>>;
+
+fn new_panel() -> FsmResult<(PanelFsm, FsmClockManual)> {
+    let clock = FsmClockManual::new();
+    let mut fsm = Panel::new_with(PanelContext::default(), FsmEventQueueVec::new(), InspectNull::new(), FsmTimersWithClock::new(FsmTimersNull, clock.clone()))?;
+    fsm.start()?;
+    Ok((fsm, clock))
+}
+
+#[test]
+fn test_debounce() -> FsmResult<()> {
+    let (mut fsm, clock) = new_panel()?;
+
+    // the bounces within the window are ignored, and extend it
+    for elapsed in [0, 10, 15, 15, 100] {
+        clock.advance(Duration::from_millis(elapsed));
+        let _ = fsm.dispatch(Press);
+    }
+    assert_eq!(2, fsm.get_context().presses);
+
+    Ok(())
+}
+
+#[test]
+fn test_throttle() -> FsmResult<()> {
+    let (mut fsm, clock) = new_panel()?;
+
+    for elapsed in [0, 10, 15, 5, 10] {
+        clock.advance(Duration::from_millis(elapsed));
+        let _ = fsm.dispatch(Sample);
+    }
+    assert_eq!(2, fsm.get_context().samples);
+    assert!(fsm.peek_dispatch(&Sample)?.is_none());
+
+    clock.advance(Duration::from_millis(20));
+    fsm.dispatch(Sample)?;
+    assert_eq!(3, fsm.get_context().samples);
+
+    Ok(())
+}
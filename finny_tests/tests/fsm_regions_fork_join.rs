@@ -0,0 +1,105 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct OvenContext {
+    heatings: usize,
+    boosts: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Cooking;
+#[derive(Default)]
+pub struct Finished;
+#[derive(Default)]
+pub struct HeaterOff;
+#[derive(Default)]
+pub struct HeaterOn;
+#[derive(Default)]
+pub struct FanOff;
+#[derive(Default)]
+pub struct FanOn;
+
+#[derive(Clone)]
+pub struct Cook;
+#[derive(Clone)]
+pub struct Heated;
+#[derive(Clone)]
+pub struct Done;
+#[derive(Clone)]
+pub struct Stop;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Oven, OvenContext>) -> BuiltFsm {
+    fsm.initial_states::<(Idle, HeaterOff, FanOff)>();
+
+    fsm.state::<Idle>()
+        .on_event::<Cook>()
+        .transition_to::<Cooking>()
+        .fork::<HeaterOn>()
+        .fork::<FanOn>();
+
+    fsm.state::<Cooking>()
+        .on_event::<Done>()
+        .transition_to::<Finished>()
+        .join::<HeaterOff>()
+        .fork::<FanOff>();
+
+    fsm.state::<Finished>();
+
+    fsm.state::<HeaterOn>()
+        .on_entry(|_state, ctx| {
+            ctx.heatings += 1;
+        })
+        .on_event::<Heated>()
+        .transition_to::<HeaterOff>();
+
+    fsm.state::<HeaterOff>();
+
+    fsm.state::<FanOn>()
+        .on_event::<Cook>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.boosts += 1;
+        });
+
+    fsm.state::<FanOn>()
+        .on_event::<Stop>()
+        .transition_to::<FanOff>();
+
+    fsm.state::<FanOff>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_regions_fork_join() -> FsmResult<()> {
+    let mut fsm = Oven::new(OvenContext::default())?;
+    fsm.start()?;
+
+    // the forked regions don't handle the forking event
+    fsm.dispatch(Cook)?;
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::Cooking), fsm.get_current_states()[0]);
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::HeaterOn), fsm.get_current_states()[1]);
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::FanOn), fsm.get_current_states()[2]);
+    assert_eq!(1, fsm.heatings);
+    assert_eq!(0, fsm.boosts);
+
+    fsm.dispatch(Cook)?;
+    assert_eq!(1, fsm.boosts);
+
+    // the heater hasn't joined yet
+    assert!(fsm.dispatch(Done).is_err());
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::Cooking), fsm.get_current_states()[0]);
+
+    fsm.dispatch(Heated)?;
+    fsm.dispatch(Done)?;
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::Finished), fsm.get_current_states()[0]);
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::HeaterOff), fsm.get_current_states()[1]);
+    assert_eq!(FsmCurrentState::State(OvenCurrentState::FanOff), fsm.get_current_states()[2]);
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct RocketContext;
+
+#[derive(Default)]
+pub struct OnPad;
+#[derive(Default)]
+pub struct Launched;
+#[derive(Default)]
+pub struct Fueling {
+    percent: usize
+}
+
+#[derive(Clone)]
+pub struct Launch;
+#[derive(Clone)]
+pub struct Fuel;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Rocket, RocketContext>) -> BuiltFsm {
+    fsm.initial_states::<(OnPad, Fueling)>();
+
+    // guarded on the progress of the other region
+    fsm.state::<OnPad>()
+        .on_event::<Launch>()
+        .transition_to::<Launched>()
+        .guard(|_ev, _ctx, states| states.get::<Fueling>().percent == 100);
+
+    fsm.state::<Launched>();
+
+    fsm.state::<Fueling>()
+        .on_event::<Fuel>()
+        .internal_transition()
+        .action(|_ev, _ctx, state| {
+            state.percent += 50;
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_guard_on_other_region_state() -> FsmResult<()> {
+    let mut fsm = Rocket::new(RocketContext)?;
+    fsm.start()?;
+
+    fsm.dispatch(Fuel)?;
+    assert!(fsm.dispatch(Launch).is_err());
+
+    fsm.dispatch(Fuel)?;
+    fsm.dispatch(Launch)?;
+    assert_eq!(FsmCurrentState::State(RocketCurrentState::Launched), fsm.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+extern crate finny;
+
+use finny::{FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct StationContext {
+    reading: usize,
+    shown: usize,
+    order: Vec<&'static str>
+}
+
+#[derive(Default)]
+pub struct Display;
+#[derive(Default)]
+pub struct Sensor;
+#[derive(Default)]
+pub struct Logger;
+
+#[derive(Clone)]
+pub struct Sample {
+    value: usize
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Station, StationContext>) -> BuiltFsm {
+    fsm.initial_states::<(Display, Sensor, Logger)>();
+    // the display shows the reading of the same sample
+    fsm.region_priority::<Sensor>(10);
+
+    fsm.state::<Display>()
+        .on_event::<Sample>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.shown = ctx.reading;
+            ctx.order.push("display");
+        });
+
+    fsm.state::<Sensor>()
+        .on_event::<Sample>()
+        .internal_transition()
+        .action(|ev, ctx, _state| {
+            ctx.reading = ev.value;
+            ctx.order.push("sensor");
+        });
+
+    fsm.state::<Logger>()
+        .on_event::<Sample>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.order.push("logger");
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_region_priority() -> FsmResult<()> {
+    let mut fsm = Station::new(StationContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Sample { value: 42 })?;
+    assert_eq!(42, fsm.shown);
+    assert_eq!(vec!["sensor", "display", "logger"], fsm.order);
+
+    let priorities: Vec<_> = Station::fsm_info().regions.iter().map(|r| (r.initial_state, r.priority)).collect();
+    assert_eq!(vec![("Display", 0), ("Sensor", 10), ("Logger", 0)], priorities);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::recorder::{FsmEventLog, InspectRecorder}};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PumpContext {
+    strokes: usize
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Idle;
+#[derive(Default, Serialize, Deserialize)]
+pub struct Pumping;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Start;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stroke;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stop;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Pump, PumpContext>) -> BuiltFsm {
+    fsm.derive_serde();
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Start>()
+        .transition_to::<Pumping>()
+        .action(|_, ctx, _, _| {
+            ctx.queue.enqueue(Stroke).unwrap();
+        });
+
+    fsm.state::<Pumping>()
+        .on_event::<Stroke>()
+        .internal_transition()
+        .action(|_, ctx, _| {
+            ctx.strokes += 1;
+        });
+
+    fsm.state::<Pumping>()
+        .on_event::<Stop>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_record_replay() -> FsmResult<()> {
+    let recorder: InspectRecorder<Pump> = InspectRecorder::new();
+    let mut fsm = Pump::new_with(PumpContext::default(), FsmEventQueueVec::new(), recorder.clone(), FsmTimersNull)?;
+    fsm.start()?;
+    fsm.dispatch(Start)?;
+    fsm.dispatch(Stroke)?;
+    fsm.dispatch(Stop)?;
+    assert!(fsm.dispatch(Stop).is_err());
+    fsm.dispatch(Start)?;
+
+    let log = recorder.log();
+    // the enqueued strokes are recorded as well
+    assert_eq!(8, log.events.len());
+
+    let json = serde_json::to_string(&log).unwrap();
+    let log: FsmEventLog<Pump> = serde_json::from_str(&json).unwrap();
+    let replayed = Pump::replay(PumpContext::default(), &log)?;
+
+    assert_eq!(fsm.get_current_states(), replayed.get_current_states());
+    assert_eq!(FsmCurrentState::State(PumpCurrentState::Pumping), replayed.get_current_states()[0]);
+    assert_eq!(fsm.strokes, replayed.strokes);
+    assert_eq!(3, replayed.strokes);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResetStates, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct TallyContext {
+    log: Vec<&'static str>
+}
+
+#[derive(Default)]
+pub struct Counting {
+    count: u32
+}
+#[derive(Default)]
+pub struct Done;
+
+#[derive(Clone, Debug)]
+pub struct Increment;
+#[derive(Clone, Debug)]
+pub struct Finish;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Tally, TallyContext>) -> BuiltFsm {
+    fsm.initial_state::<Counting>();
+
+    fsm.state::<Counting>()
+        .on_entry(|_state, ctx| {
+            ctx.log.push("enter Counting");
+        })
+        .on_event::<Increment>()
+        .internal_transition()
+        .action(|_ev, _ctx, state| {
+            state.count += 1;
+        });
+
+    fsm.state::<Counting>()
+        .on_event::<Finish>()
+        .transition_to::<Done>();
+
+    fsm.state::<Done>()
+        .on_exit(|_state, ctx| {
+            ctx.log.push("exit Done");
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_reset() -> FsmResult<()> {
+    let mut fsm = Tally::new(TallyContext::default())?;
+    fsm.start()?;
+    fsm.dispatch(Increment)?;
+    fsm.dispatch(Increment)?;
+    fsm.dispatch(Finish)?;
+
+    fsm.reset(FsmResetStates::Keep)?;
+    assert_eq!([FsmCurrentState::State(TallyCurrentState::Counting)], fsm.get_current_states());
+    assert_eq!(vec!["enter Counting", "exit Done", "enter Counting"], fsm.get_context().log);
+    assert_eq!(2, fsm.get_state::<Counting>().count);
+
+    fsm.reset(FsmResetStates::Recreate)?;
+    assert_eq!(0, fsm.get_state::<Counting>().count);
+    assert_eq!(4, fsm.get_context().log.len());
+
+    Ok(())
+}
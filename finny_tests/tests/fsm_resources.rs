@@ -0,0 +1,76 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct HeaterContext {
+    setpoint: i32
+}
+
+/// Owned by the application, lent to the machine for every dispatch.
+pub struct Board {
+    temperature: i32,
+    relay: bool
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Heating;
+
+#[derive(Clone)]
+pub struct Tick;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Heater, HeaterContext>) -> BuiltFsm {
+    fsm.resources::<Board>();
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Tick>()
+        .transition_to::<Heating>()
+        .guard(|_ev, ctx, _states| {
+            ctx.resources.as_deref().map(|board| board.temperature < ctx.setpoint).unwrap_or(false)
+        })
+        .action(|_ev, ctx, _from, _to| {
+            if let Some(board) = ctx.resources.as_deref_mut() {
+                board.relay = true;
+            }
+        });
+
+    fsm.state::<Heating>()
+        .on_event::<Tick>()
+        .transition_to::<Idle>()
+        .guard(|_ev, ctx, _states| {
+            ctx.resources.as_deref().map(|board| board.temperature >= ctx.setpoint).unwrap_or(false)
+        })
+        .action(|_ev, ctx, _from, _to| {
+            if let Some(board) = ctx.resources.as_deref_mut() {
+                board.relay = false;
+            }
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_resources() -> FsmResult<()> {
+    let mut board = Board { temperature: 15, relay: false };
+
+    let mut fsm = Heater::new(HeaterContext { setpoint: 20 })?;
+    fsm.start()?;
+
+    // without the resources the guards don't pass
+    assert!(fsm.dispatch(Tick).is_err());
+
+    fsm.dispatch_with(&mut board, Tick)?;
+    assert_eq!(FsmCurrentState::State(HeaterCurrentState::Heating), fsm.get_current_states()[0]);
+    assert!(board.relay);
+
+    board.temperature = 21;
+    fsm.dispatch_with(&mut board, Tick)?;
+    assert_eq!(FsmCurrentState::State(HeaterCurrentState::Idle), fsm.get_current_states()[0]);
+    assert!(!board.relay);
+
+    Ok(())
+}
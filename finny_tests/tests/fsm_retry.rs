@@ -0,0 +1,166 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmInfoTransitionKind, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct LinkContext {
+    backoffs: Vec<u32>,
+    gave_up_after: Option<u32>
+}
+
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Backoff;
+#[derive(Default)]
+pub struct Online;
+#[derive(Default)]
+pub struct Offline;
+
+#[derive(Clone, Debug)]
+pub struct ConnectFailed;
+#[derive(Clone, Debug)]
+pub struct Reconnect;
+#[derive(Clone, Debug)]
+pub struct Connected;
+#[derive(Clone, Debug)]
+pub struct Dropped;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, LinkContext>) -> BuiltFsm {
+    fsm.initial_state::<Connecting>();
+
+    fsm.state::<Connecting>()
+        .on_event::<ConnectFailed>()
+        .transition_to::<Backoff>()
+        .retry(3)
+        .transition_to::<Offline>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.gave_up_after = Some(ctx.attempts());
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<Connected>()
+        .transition_to::<Online>();
+
+    fsm.state::<Backoff>()
+        .on_entry(|_state, ctx| {
+            let attempts = ctx.attempts();
+            ctx.backoffs.push(attempts);
+        })
+        .on_event::<Reconnect>()
+        .transition_to::<Connecting>();
+
+    fsm.state::<Online>()
+        .on_event::<Dropped>()
+        .transition_to::<Connecting>();
+
+    fsm.state::<Offline>();
+
+    fsm.build()
+}
+
+#[derive(Default, Clone)]
+pub struct UplinkContext {
+    busy: bool,
+    dialed: Vec<u32>
+}
+
+#[derive(Default, Clone)]
+pub struct Dialing;
+#[derive(Default, Clone)]
+pub struct Waiting;
+#[derive(Default, Clone)]
+pub struct GaveUp;
+
+#[derive(Clone, Debug)]
+pub struct DialFailed;
+#[derive(Clone, Debug)]
+pub struct Redial;
+
+#[finny_fsm]
+fn build_uplink_fsm(mut fsm: FsmBuilder<Uplink, UplinkContext>) -> BuiltFsm {
+    fsm.transactional();
+    fsm.initial_state::<Dialing>();
+
+    fsm.state::<Dialing>()
+        .on_event::<DialFailed>()
+        .transition_to::<Waiting>()
+        .retry(2)
+        .try_action(|_ev, ctx, _from, _to| {
+            if ctx.busy {
+                return Err(FsmError::ActionFailed("line busy"));
+            }
+            let attempts = ctx.attempts();
+            ctx.dialed.push(attempts);
+            Ok(())
+        })
+        .transition_to::<GaveUp>();
+
+    fsm.state::<Waiting>()
+        .on_event::<Redial>()
+        .transition_to::<Dialing>();
+
+    fsm.state::<GaveUp>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_retry_fallback() -> FsmResult<()> {
+    let mut fsm = Link::new(LinkContext::default())?;
+    fsm.start()?;
+
+    for _ in 0..3 {
+        fsm.dispatch(ConnectFailed)?;
+        fsm.dispatch(Reconnect)?;
+    }
+    let fallback = FsmInfoTransitionKind::NormalTransition { from_state: "Connecting", to_state: "Offline" };
+    assert_eq!(Some(fallback), fsm.peek_dispatch(&ConnectFailed)?.map(|t| t.kind));
+
+    fsm.dispatch(ConnectFailed)?;
+    assert_eq!([FsmCurrentState::State(LinkCurrentState::Offline)], fsm.get_current_states());
+    assert_eq!(vec![1, 2, 3], fsm.get_context().backoffs);
+    assert_eq!(Some(3), fsm.get_context().gave_up_after);
+
+    Ok(())
+}
+
+#[test]
+fn test_retry_resets_outside_of_the_loop() -> FsmResult<()> {
+    let mut fsm = Link::new(LinkContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(ConnectFailed)?;
+    fsm.dispatch(Reconnect)?;
+    fsm.dispatch(ConnectFailed)?;
+    fsm.dispatch(Reconnect)?;
+    fsm.dispatch(Connected)?;
+
+    fsm.dispatch(Dropped)?;
+    fsm.dispatch(ConnectFailed)?;
+    assert_eq!(vec![1, 2, 1], fsm.get_context().backoffs);
+
+    Ok(())
+}
+
+#[test]
+fn test_retry_rolled_back() -> FsmResult<()> {
+    let mut fsm = Uplink::new(UplinkContext { busy: true, ..Default::default() })?;
+    fsm.start()?;
+
+    // the failed retry is rolled back with the rest of the dispatch
+    assert_eq!(Err(FsmError::ActionFailed("line busy")), fsm.dispatch(DialFailed));
+    fsm.busy = false;
+
+    fsm.dispatch(DialFailed)?;
+    fsm.dispatch(Redial)?;
+    fsm.dispatch(DialFailed)?;
+    assert_eq!(vec![1, 2], fsm.get_context().dialed);
+
+    fsm.dispatch(Redial)?;
+    fsm.dispatch(DialFailed)?;
+    assert_eq!([FsmCurrentState::State(UplinkCurrentState::GaveUp)], fsm.get_current_states());
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+extern crate finny;
+
+use std::time::{Duration, Instant};
+
+use finny::{FsmBackendImpl, FsmCurrentState, FsmError, FsmEvent, FsmFactory, FsmHandle, FsmMiddleware, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct DoorContext;
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone, Debug)]
+pub struct Push;
+#[derive(Clone, Debug)]
+pub struct AutoClose;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+
+    fsm.state::<Closed>()
+        .on_event::<Push>()
+        .transition_to::<Open>();
+
+    fsm.state::<Open>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_millis(50);
+        }, |_ctx, _state| {
+            Some( AutoClose.into() )
+        })
+        .with_timer_ty::<AutoCloseTimer>();
+
+    fsm.state::<Open>()
+        .on_event::<AutoClose>()
+        .transition_to::<Closed>();
+
+    fsm.build()
+}
+
+fn wait_for(handle: &FsmHandle<Door>, state: DoorCurrentState) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if handle.current_state()[0] == FsmCurrentState::State(state) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+#[test]
+fn test_runner() -> FsmResult<()> {
+    let handle = Door::new(DoorContext)?.spawn_runner()?;
+    assert_eq!(FsmCurrentState::State(DoorCurrentState::Closed), handle.current_state()[0]);
+
+    // the handles can be sent to other threads
+    let sender = handle.clone();
+    std::thread::spawn(move || sender.send(Push)).join().unwrap()?;
+    assert!(wait_for(&handle, DoorCurrentState::Open));
+
+    // closed by the timer
+    assert!(wait_for(&handle, DoorCurrentState::Closed));
+
+    handle.shutdown()?;
+    assert!(!handle.is_running());
+    assert_eq!(Err(FsmError::RunnerStopped), handle.send(Push));
+
+    Ok(())
+}
+
+struct Locked;
+
+impl FsmMiddleware<Door> for Locked {
+    fn before_dispatch(&mut self, _event: FsmEvent<DoorEvents, DoorTimers>, _backend: &FsmBackendImpl<Door>) -> FsmResult<Option<FsmEvent<DoorEvents, DoorTimers>>> {
+        Err(FsmError::EventRejected("locked"))
+    }
+}
+
+#[test]
+fn test_runner_start_failure() -> FsmResult<()> {
+    let mut fsm = Door::new(DoorContext)?;
+    fsm.middlewares.add(Locked);
+
+    assert!(matches!(fsm.spawn_runner(), Err(FsmError::EventRejected("locked"))));
+
+    Ok(())
+}
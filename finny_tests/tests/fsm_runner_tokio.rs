@@ -0,0 +1,119 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmBackendImpl, FsmCurrentState, FsmError, FsmEvent, FsmEventQueueVec, FsmFactory, FsmMiddleware, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::tokio::TimersTokio};
+
+#[derive(Default)]
+pub struct GateContext {
+    passed: usize
+}
+
+#[derive(Default)]
+pub struct Locked;
+#[derive(Default)]
+pub struct Unlocked;
+
+#[derive(Clone, Debug)]
+pub struct Coin;
+#[derive(Clone, Debug)]
+pub struct Relock;
+#[derive(Clone, Debug)]
+pub struct CoinLater;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Gate, GateContext>) -> BuiltFsm {
+    fsm.initial_state::<Locked>();
+
+    fsm.state::<Locked>()
+        .on_event::<Coin>()
+        .transition_to::<Unlocked>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.passed += 1;
+        });
+
+    fsm.state::<Locked>()
+        .on_event::<CoinLater>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            let _ = ctx.timers.enqueue_after(Coin, Duration::from_millis(20));
+        });
+
+    fsm.state::<Unlocked>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_millis(30);
+        }, |_ctx, _state| {
+            Some( Relock.into() )
+        })
+        .with_timer_ty::<RelockTimer>();
+
+    fsm.state::<Unlocked>()
+        .on_event::<Relock>()
+        .transition_to::<Locked>();
+
+    fsm.build()
+}
+
+#[tokio::test]
+async fn test_tokio_runner() -> FsmResult<()> {
+    let handle = Gate::new_with(GateContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?.spawn_tokio_runner()?;
+
+    handle.dispatch(Coin).await?;
+    assert_eq!(FsmCurrentState::State(GateCurrentState::Unlocked), handle.current_state()[0]);
+
+    // the unhandled events report their error
+    assert!(matches!(handle.dispatch(Coin).await, Err(FsmError::NoTransition { .. })));
+
+    // relocked by the timer
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(FsmCurrentState::State(GateCurrentState::Locked), handle.current_state()[0]);
+
+    handle.shutdown().await?;
+    assert_eq!(Err(FsmError::RunnerStopped), handle.dispatch(Coin).await);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tokio_runner_wait_for_state() -> FsmResult<()> {
+    let handle = Gate::new_with(GateContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?.spawn_tokio_runner()?;
+    handle.wait_for_state::<Locked>(Duration::from_millis(10)).await?;
+
+    let sender = handle.clone();
+    tokio::spawn(async move { sender.dispatch(Coin).await });
+    handle.wait_for_state::<Unlocked>(Duration::from_secs(5)).await?;
+    handle.wait_for_state::<Locked>(Duration::from_secs(5)).await?;
+
+    assert_eq!(Err(FsmError::WaitTimeout), handle.wait_for_state::<Unlocked>(Duration::from_millis(50)).await);
+
+    handle.shutdown().await
+}
+
+#[tokio::test]
+async fn test_tokio_runner_scheduled_event() -> FsmResult<()> {
+    let handle = Gate::new_with(GateContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?.spawn_tokio_runner()?;
+
+    // no timers are running, the task wakes up for the scheduled event
+    handle.dispatch(CoinLater).await?;
+    handle.wait_for_state::<Unlocked>(Duration::from_secs(1)).await?;
+
+    handle.shutdown().await
+}
+
+struct Maintenance;
+
+impl FsmMiddleware<Gate> for Maintenance {
+    fn before_dispatch(&mut self, _event: FsmEvent<GateEvents, GateTimers>, _backend: &FsmBackendImpl<Gate>) -> FsmResult<Option<FsmEvent<GateEvents, GateTimers>>> {
+        Err(FsmError::EventRejected("maintenance"))
+    }
+}
+
+#[tokio::test]
+async fn test_tokio_runner_start_failure() -> FsmResult<()> {
+    let mut fsm = Gate::new_with(GateContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?;
+    fsm.middlewares.add(Maintenance);
+
+    assert!(matches!(fsm.spawn_tokio_runner(), Err(FsmError::EventRejected("maintenance"))));
+
+    Ok(())
+}
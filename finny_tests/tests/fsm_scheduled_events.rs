@@ -0,0 +1,114 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, FsmScheduledEventId, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::{test::TimersTest, tokio::TimersTokio}};
+
+#[derive(Default)]
+pub struct ClientContext {
+    attempts: usize,
+    retry: Option<FsmScheduledEventId>
+}
+
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Connected;
+
+#[derive(Clone, Debug)]
+pub struct Failed;
+#[derive(Clone, Debug)]
+pub struct Retry;
+#[derive(Clone, Debug)]
+pub struct Success;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Client, ClientContext>) -> BuiltFsm {
+    fsm.initial_state::<Connecting>();
+
+    fsm.state::<Connecting>()
+        .on_event::<Failed>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.retry = ctx.timers.enqueue_after(Retry, Duration::from_secs(5)).ok();
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<Retry>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.attempts += 1;
+        });
+
+    fsm.state::<Connecting>()
+        .on_event::<Success>()
+        .transition_to::<Connected>()
+        .action(|_ev, ctx, _from, _to| {
+            if let Some(retry) = ctx.retry.take() {
+                ctx.timers.cancel_scheduled(retry);
+            }
+        });
+
+    fsm.state::<Connected>();
+
+    fsm.build()
+}
+
+fn new_client() -> FsmResult<FsmFrontend<Client, FsmEventQueueVec<Client>, InspectNull, TimersTest<Client>>> {
+    let mut fsm = Client::new_with(ClientContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTest::new())?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_scheduled_event() -> FsmResult<()> {
+    let mut fsm = new_client()?;
+
+    fsm.dispatch(Failed)?;
+    fsm.advance(Duration::from_secs(4))?;
+    assert_eq!(0, fsm.attempts);
+
+    fsm.advance(Duration::from_secs(1))?;
+    assert_eq!(1, fsm.attempts);
+
+    // scheduled from outside of the machine
+    fsm.enqueue_after(Retry, Duration::from_secs(2))?;
+    fsm.advance(Duration::from_secs(2))?;
+    assert_eq!(2, fsm.attempts);
+
+    Ok(())
+}
+
+#[test]
+fn test_cancel_scheduled_event() -> FsmResult<()> {
+    let mut fsm = new_client()?;
+
+    fsm.dispatch(Failed)?;
+    fsm.dispatch(Success)?;
+    fsm.advance(Duration::from_secs(10))?;
+    assert_eq!(0, fsm.attempts);
+    assert_eq!(FsmCurrentState::State(ClientCurrentState::Connected), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scheduled_event_async() -> FsmResult<()> {
+    let mut fsm = new_client()?;
+
+    fsm.dispatch_async(Failed).await?;
+    fsm.timers.advance(Duration::from_secs(5));
+    fsm.dispatch_timer_events_async().await?;
+    assert_eq!(1, fsm.attempts);
+
+    // the tokio driver wakes up for the scheduled event, without any running timers
+    let mut fsm = Client::new_with(ClientContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?;
+    fsm.start()?;
+    fsm.enqueue_after(Retry, Duration::from_millis(20))?;
+    assert!(fsm.next_scheduled_in().is_some());
+    tokio::time::timeout(Duration::from_secs(1), fsm.wait_and_dispatch_timer_events()).await.expect("the scheduled event")?;
+    assert_eq!(1, fsm.attempts);
+    assert_eq!(None, fsm.next_scheduled_in());
+
+    Ok(())
+}
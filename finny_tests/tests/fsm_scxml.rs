@@ -0,0 +1,49 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, finny_scxml, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct DoorContext {
+    powered: bool
+}
+
+impl DoorGuards for DoorContext {
+    fn is_powered(&self) -> bool {
+        self.powered
+    }
+}
+
+finny_scxml!(Door, DoorContext, "tests/door.scxml");
+
+type Timers = TimersCore<Door, DoorTimersStorage<CoreTimer>, [DoorTimers; 4]>;
+
+#[test]
+fn test_scxml() -> FsmResult<()> {
+    let mut fsm = Door::new_with(DoorContext { powered: true }, FsmEventQueueVec::new(), InspectNull::new(), Timers::new(Default::default()))?;
+    fsm.start()?;
+
+    fsm.dispatch(DoorOpen)?;
+    assert_eq!(FsmCurrentState::State(DoorCurrentState::Opened), fsm.get_current_states()[0]);
+
+    // the auto close timer
+    fsm.timers.tick(Duration::from_secs(3));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(DoorCurrentState::Closed), fsm.get_current_states()[0]);
+
+    // a blocked door stays opened
+    fsm.dispatch(DoorOpen)?;
+    fsm.dispatch(DoorBlock)?;
+    fsm.timers.tick(Duration::from_secs(3));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(DoorCurrentState::Opened), fsm.get_current_states()[0]);
+
+    fsm.dispatch(DoorClose)?;
+    fsm.powered = false;
+    assert!(fsm.dispatch(DoorOpen).is_err());
+    fsm.dispatch(PowerOff)?;
+    assert!(fsm.is_completed());
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+extern crate finny;
+
+use std::thread;
+
+use finny::{FsmEventQueueVecShared, FsmFactory, FsmResult, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull};
+
+#[derive(Debug, Default)]
+pub struct CounterContext {
+    total: usize
+}
+
+#[derive(Default)]
+pub struct Counting;
+
+#[derive(Clone)]
+pub struct Add(usize);
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Counter, CounterContext>) -> BuiltFsm {
+    fsm.initial_state::<Counting>();
+    fsm.state::<Counting>()
+        .on_event::<Add>()
+        .internal_transition()
+        .action(|ev, ctx, _| {
+            ctx.total += ev.0;
+        });
+    fsm.build()
+}
+
+#[test]
+fn test_event_sender() -> FsmResult<()> {
+    let mut fsm = Counter::new_with(CounterContext::default(), FsmEventQueueVecShared::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    let producers: Vec<_> = (1..=4).map(|n| {
+        let mut sender = fsm.event_sender();
+        thread::spawn(move || {
+            for _ in 0..10 {
+                sender.send(Add(n)).unwrap();
+            }
+        })
+    }).collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    assert_eq!(0, fsm.total);
+    fsm.process()?;
+    assert_eq!(100, fsm.total);
+
+    Ok(())
+}
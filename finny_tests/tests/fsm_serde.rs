@@ -0,0 +1,106 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueueSender, FsmEventQueueVec, FsmFactory, FsmResult, FsmSnapshot, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkflowContext {
+    approvals: usize
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Draft {
+    edits: usize
+}
+#[derive(Default, Serialize, Deserialize)]
+pub struct Review;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Edit;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Submit;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Approve;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Workflow, WorkflowContext>) -> BuiltFsm {
+    fsm.derive_serde();
+    fsm.initial_state::<Draft>();
+
+    fsm.state::<Draft>()
+        .on_event::<Edit>()
+        .internal_transition()
+        .action(|_, _, state| {
+            state.edits += 1;
+        });
+
+    fsm.state::<Draft>()
+        .on_event::<Submit>()
+        .transition_to::<ApprovalMachine>();
+
+    fsm.sub_machine::<ApprovalMachine>()
+        .with_context(|_| ApprovalContext::default());
+
+    fsm.state::<Review>();
+
+    fsm.sub_machine::<ApprovalMachine>()
+        .on_event::<Submit>()
+        .transition_to::<Review>()
+        .action(|_, ctx, from, _| {
+            ctx.approvals = from.approvals;
+        });
+
+    fsm.build()
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ApprovalContext {
+    approvals: usize
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Pending;
+
+#[finny_fsm]
+fn build_approval_fsm(mut fsm: FsmBuilder<ApprovalMachine, ApprovalContext>) -> BuiltFsm {
+    fsm.derive_serde();
+    fsm.initial_state::<Pending>();
+    fsm.state::<Pending>()
+        .on_event::<Approve>()
+        .internal_transition()
+        .action(|_, ctx, _| {
+            ctx.approvals += 1;
+        });
+    fsm.build()
+}
+
+#[test]
+fn test_snapshot_restore() -> FsmResult<()> {
+    let mut fsm = Workflow::new(WorkflowContext::default())?;
+    fsm.start()?;
+    fsm.dispatch(Edit)?;
+    fsm.dispatch(Edit)?;
+    fsm.dispatch(Submit)?;
+    fsm.dispatch(ApprovalMachineEvents::from(Approve))?;
+    fsm.queue.enqueue(Submit)?;
+
+    let json = serde_json::to_string(&fsm.snapshot()).unwrap();
+    drop(fsm);
+
+    let snapshot: FsmSnapshot<Workflow, FsmEventQueueVec<Workflow>> = serde_json::from_str(&json).unwrap();
+    let mut fsm = Workflow::restore(WorkflowContext::default(), snapshot)?;
+
+    assert_eq!(FsmCurrentState::State(WorkflowCurrentState::ApprovalMachine), fsm.get_current_states()[0]);
+    let draft: &Draft = fsm.get_state();
+    assert_eq!(2, draft.edits);
+    let approval: &ApprovalMachine = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(ApprovalMachineCurrentState::Pending), approval.get_current_states()[0]);
+    assert_eq!(1, approval.approvals);
+
+    // the restored machine keeps running, starting with the pending events
+    fsm.dispatch_queue()?;
+    assert_eq!(FsmCurrentState::State(WorkflowCurrentState::Review), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.approvals);
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmSnapshot, FsmSnapshotMigrations, FsmSnapshotVersion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OrderContext;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddItem;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pay;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ship;
+
+mod v1 {
+    use super::*;
+    use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+    #[derive(Default, Serialize, Deserialize)]
+    pub struct Cart {
+        pub items: usize
+    }
+    #[derive(Default, Serialize, Deserialize)]
+    pub struct Paid;
+
+    #[finny_fsm]
+    fn build_fsm(mut fsm: FsmBuilder<Order, OrderContext>) -> BuiltFsm {
+        fsm.derive_serde();
+        fsm.initial_state::<Cart>();
+
+        fsm.state::<Cart>()
+            .on_event::<AddItem>()
+            .internal_transition()
+            .action(|_, _, cart| { cart.items += 1; });
+
+        fsm.state::<Cart>()
+            .on_event::<Pay>()
+            .transition_to::<Paid>();
+
+        fsm.state::<Paid>();
+
+        fsm.build()
+    }
+}
+
+mod v2 {
+    use super::*;
+    use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+    #[derive(Default, Serialize, Deserialize)]
+    pub struct Basket {
+        pub items: usize
+    }
+    #[derive(Default, Serialize, Deserialize)]
+    pub struct Paid;
+    #[derive(Default, Serialize, Deserialize)]
+    pub struct Shipped;
+
+    #[finny_fsm]
+    fn build_fsm(mut fsm: FsmBuilder<Order, OrderContext>) -> BuiltFsm {
+        fsm.derive_serde();
+        fsm.initial_state::<Basket>();
+
+        fsm.state::<Basket>()
+            .on_event::<AddItem>()
+            .internal_transition()
+            .action(|_, _, basket| { basket.items += 1; });
+
+        fsm.state::<Basket>()
+            .on_event::<Pay>()
+            .transition_to::<Paid>();
+
+        fsm.state::<Paid>()
+            .on_event::<Ship>()
+            .transition_to::<Shipped>();
+
+        fsm.state::<Shipped>();
+
+        fsm.build()
+    }
+}
+
+fn rename_cart(snapshot: &mut serde_json::Value) -> FsmResult<()> {
+    let states = snapshot["states"].as_object_mut().ok_or(FsmError::SnapshotInvalid("no states"))?;
+    let cart = states.remove("cart").ok_or(FsmError::SnapshotInvalid("no cart"))?;
+    states.insert("basket".into(), cart);
+    states.insert("shipped".into(), serde_json::Value::Null);
+
+    for state in snapshot["current_states"].as_array_mut().ok_or(FsmError::SnapshotInvalid("no current states"))? {
+        if state["State"] == "Cart" {
+            state["State"] = "Basket".into();
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_migration() -> FsmResult<()> {
+    let mut fsm = v1::Order::new(OrderContext)?;
+    fsm.start()?;
+    fsm.dispatch(AddItem)?;
+    fsm.dispatch(AddItem)?;
+    let json = serde_json::to_value(fsm.snapshot()).unwrap();
+    assert_eq!(v1::Order::SNAPSHOT_VERSION, json["version"].as_u64().unwrap());
+
+    let mut migrations = FsmSnapshotMigrations::new();
+    let migrated: FsmResult<FsmSnapshot<v2::Order, FsmEventQueueVec<v2::Order>>> = migrations.migrate(json.clone());
+    assert_eq!(Some(FsmError::SnapshotVersion { expected: v2::Order::SNAPSHOT_VERSION, found: v1::Order::SNAPSHOT_VERSION }), migrated.err());
+
+    migrations.add(v1::Order::SNAPSHOT_VERSION, v2::Order::SNAPSHOT_VERSION, rename_cart);
+    let snapshot: FsmSnapshot<v2::Order, FsmEventQueueVec<v2::Order>> = migrations.migrate(json)?;
+    let mut fsm = v2::Order::restore(OrderContext, snapshot)?;
+
+    assert_eq!(FsmCurrentState::State(v2::OrderCurrentState::Basket), fsm.get_current_states()[0]);
+    let basket: &v2::Basket = fsm.get_state();
+    assert_eq!(2, basket.items);
+
+    fsm.dispatch(Pay)?;
+    fsm.dispatch(Ship)?;
+    assert_eq!(FsmCurrentState::State(v2::OrderCurrentState::Shipped), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_version_mismatch() {
+    let mut snapshot: serde_json::Value = serde_json::to_value(v2::Order::new(OrderContext).unwrap().snapshot()).unwrap();
+    snapshot["version"] = 7.into();
+
+    let snapshot: FsmSnapshot<v2::Order, FsmEventQueueVec<v2::Order>> = serde_json::from_value(snapshot).unwrap();
+    assert_eq!(Some(FsmError::SnapshotVersion { expected: v2::Order::SNAPSHOT_VERSION, found: 7 }), v2::Order::restore(OrderContext, snapshot).err());
+}
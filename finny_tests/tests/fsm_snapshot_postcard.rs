@@ -0,0 +1,65 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmSnapshot, decl::{BuiltFsm, FsmBuilder}, finny_fsm, fsm_snapshot_from_slice, inspect::null::InspectNull, timers::std::TimersStd};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ChargerContext;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Idle;
+#[derive(Default, Serialize, Deserialize)]
+pub struct Charging {
+    cycles: u32
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Plug;
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Cycle;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Charger, ChargerContext>) -> BuiltFsm {
+    fsm.derive_serde();
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Plug>()
+        .transition_to::<Charging>();
+
+    fsm.state::<Charging>()
+        .on_event::<Cycle>()
+        .internal_transition()
+        .action(|_, _, state| {
+            state.cycles += 1;
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_postcard_snapshot() -> FsmResult<()> {
+    let mut fsm = Charger::new(ChargerContext)?;
+    fsm.start()?;
+    fsm.dispatch(Plug)?;
+    fsm.dispatch(Cycle)?;
+    fsm.dispatch(Cycle)?;
+
+    let mut flash = [0xffu8; 64];
+    let len = fsm.snapshot_to_slice(&mut flash)?.len();
+    assert!(len < 32);
+    assert!(matches!(fsm.snapshot_to_slice(&mut [0; 8]), Err(FsmError::SnapshotInvalid(_))));
+    drop(fsm);
+
+    let snapshot: FsmSnapshot<Charger, FsmEventQueueVec<Charger>> = fsm_snapshot_from_slice(&flash[..len])?;
+    let fsm = Charger::restore_with(ChargerContext, snapshot, InspectNull::new(), TimersStd::new())?;
+    assert_eq!(FsmCurrentState::State(ChargerCurrentState::Charging), fsm.get_current_states()[0]);
+    let charging: &Charging = fsm.get_state();
+    assert_eq!(2, charging.cycles);
+
+    // a write that was torn by a power loss
+    flash[1] ^= 0x01;
+    assert_eq!(Err(FsmError::SnapshotInvalid("The checksum of the snapshot doesn't match")), fsm_snapshot_from_slice::<Charger, FsmEventQueueVec<Charger>>(&flash[..len]).map(|_| ()));
+
+    Ok(())
+}
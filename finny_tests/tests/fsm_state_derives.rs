@@ -0,0 +1,53 @@
+extern crate finny;
+
+use std::collections::HashMap;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default, Debug)]
+pub struct Red { cycles: usize }
+#[derive(Default, Debug)]
+pub struct Green;
+
+#[derive(Clone)]
+pub struct Next;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Light, ()>) -> BuiltFsm {
+    fsm.states_debug();
+    fsm.initial_state::<Red>();
+
+    fsm.state::<Red>()
+        .on_exit(|state, _ctx| { state.cycles += 1; })
+        .on_event::<Next>()
+        .transition_to::<Green>();
+
+    fsm.state::<Green>()
+        .on_event::<Next>()
+        .transition_to::<Red>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_state_derives() -> FsmResult<()> {
+    let mut fsm = Light::new(())?;
+    assert_eq!("Stopped", fsm.get_current_states()[0].to_string());
+
+    let mut visits: HashMap<FsmCurrentState<LightCurrentState>, usize> = HashMap::new();
+    fsm.start()?;
+    for _ in 0..3 {
+        *visits.entry(fsm.get_current_states()[0]).or_default() += 1;
+        fsm.dispatch(Next)?;
+    }
+
+    assert_eq!(2, visits[&FsmCurrentState::State(LightCurrentState::Red)]);
+    assert_eq!(1, visits[&FsmCurrentState::State(LightCurrentState::Green)]);
+    assert_eq!("Green", LightCurrentState::Green.to_string());
+
+    let states = format!("{:?}", fsm.states);
+    assert!(states.starts_with("LightStates"));
+    assert!(states.contains("Red { cycles: 2 }"));
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Debug, Default)]
+pub struct LinkContext;
+
+#[derive(Default)]
+pub struct Connecting;
+#[derive(Default)]
+pub struct Connected;
+#[derive(Default)]
+pub struct Degraded;
+#[derive(Default)]
+pub struct Offline;
+
+#[derive(Clone)]
+pub struct Established;
+#[derive(Clone)]
+pub struct PacketLoss;
+#[derive(Clone)]
+pub struct LinkDown;
+#[derive(Clone)]
+pub struct Reconnect;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Link, LinkContext>) -> BuiltFsm {
+    fsm.initial_state::<Connecting>();
+    fsm.state::<Connecting>().on_event::<Established>().transition_to::<Connected>();
+    fsm.state::<Connected>().on_event::<PacketLoss>().transition_to::<Degraded>();
+    fsm.state::<Degraded>();
+    fsm.state::<Offline>().on_event::<Reconnect>().transition_to::<Connecting>();
+
+    fsm.state_group::<(Connecting, Connected, Degraded)>()
+        .on_event::<LinkDown>()
+        .transition_to::<Offline>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_state_group() -> FsmResult<()> {
+    let mut fsm = Link::new(LinkContext)?;
+
+    fsm.start()?;
+    fsm.dispatch(LinkDown)?;
+    assert_eq!(FsmCurrentState::State(LinkCurrentState::Offline), fsm.get_current_states()[0]);
+
+    fsm.dispatch(Reconnect)?;
+    fsm.dispatch(Established)?;
+    fsm.dispatch(PacketLoss)?;
+    fsm.dispatch(LinkDown)?;
+    assert_eq!(FsmCurrentState::State(LinkCurrentState::Offline), fsm.get_current_states()[0]);
+
+    assert!(fsm.dispatch(LinkDown).is_err());
+
+    Ok(())
+}
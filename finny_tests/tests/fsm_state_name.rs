@@ -0,0 +1,65 @@
+extern crate finny;
+
+use finny::{FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct Disconnected;
+#[derive(Default)]
+pub struct LedOff;
+#[derive(Default)]
+pub struct Authenticating;
+#[derive(Default)]
+pub struct Ready;
+
+#[derive(Clone)]
+pub struct Connect;
+#[derive(Clone)]
+pub struct Authenticated;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Session, ()>) -> BuiltFsm {
+    fsm.initial_states::<(Disconnected, LedOff)>();
+
+    fsm.state::<Disconnected>()
+        .on_event::<Connect>()
+        .transition_to::<Connected>();
+
+    fsm.sub_machine::<Connected>();
+    fsm.state::<LedOff>();
+
+    fsm.build()
+}
+
+#[finny_fsm]
+fn build_connected(mut fsm: FsmBuilder<Connected, ()>) -> BuiltFsm {
+    fsm.initial_state::<Authenticating>();
+
+    fsm.state::<Authenticating>()
+        .on_event::<Authenticated>()
+        .transition_to::<Ready>();
+
+    fsm.state::<Ready>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_current_state_name() -> FsmResult<()> {
+    let mut fsm = Session::new(())?;
+    assert_eq!("Stopped, Stopped", fsm.current_state_name());
+
+    fsm.start()?;
+    assert_eq!("Disconnected, LedOff", fsm.current_state_name());
+
+    fsm.dispatch(Connect)?;
+    assert_eq!("Connected/Authenticating, LedOff", fsm.current_state_name());
+
+    let ev: ConnectedEvents = Authenticated.into();
+    fsm.dispatch(ev)?;
+
+    let mut name = String::with_capacity(64);
+    fsm.write_current_state_name(&mut name).unwrap();
+    assert_eq!("Connected/Ready, LedOff", name);
+
+    Ok(())
+}
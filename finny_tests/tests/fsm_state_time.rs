@@ -0,0 +1,60 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::metrics::InspectMetrics, timers::{clock::FsmClockManual, std::TimersStd}};
+
+#[derive(Default)]
+pub struct ServiceContext;
+
+#[derive(Default)]
+pub struct Healthy;
+#[derive(Default)]
+pub struct Degraded;
+
+#[derive(Clone, Debug)]
+pub struct CheckFailed;
+#[derive(Clone, Debug)]
+pub struct CheckPassed;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Service, ServiceContext>) -> BuiltFsm {
+    fsm.initial_state::<Healthy>();
+
+    fsm.state::<Healthy>()
+        .on_event::<CheckFailed>()
+        .transition_to::<Degraded>();
+
+    fsm.state::<Degraded>()
+        .on_event::<CheckPassed>()
+        .transition_to::<Healthy>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_time_in_state() -> FsmResult<()> {
+    let clock = FsmClockManual::new();
+    let metrics = InspectMetrics::new();
+    let mut fsm = Service::new_with(ServiceContext, FsmEventQueueVec::new(), metrics.clone(), TimersStd::with_clock(clock.clone()))?;
+    fsm.start()?;
+
+    clock.advance(Duration::from_secs(10));
+    fsm.dispatch(CheckFailed)?;
+    clock.advance(Duration::from_secs(3));
+    fsm.dispatch(CheckPassed)?;
+    clock.advance(Duration::from_secs(5));
+    fsm.dispatch(CheckFailed)?;
+    clock.advance(Duration::from_secs(2));
+
+    // the current visit of the degraded state is included
+    assert_eq!(Some(Duration::from_secs(5)), fsm.time_in_state::<Degraded>());
+    assert_eq!(Some(Duration::from_secs(15)), fsm.time_in_state::<Healthy>());
+
+    // the inspector only sees the completed visits
+    assert_eq!(Duration::from_secs(3), metrics.time_in_state("Service", "Degraded"));
+    assert_eq!(Duration::from_secs(15), metrics.time_in_state("Service", "Healthy"));
+    assert!(metrics.render().contains("finny_state_duration_seconds_total{fsm=\"Service\",state=\"Degraded\"} 3"));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueueSender, FsmFactory, FsmFrontend, FsmResult, FsmStatic, FsmStaticQueue, FsmTimersNull, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, spsc::FsmEventQueueSpsc};
+
+#[derive(Default)]
+pub struct SensorContext {
+    samples: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Sampling;
+
+#[derive(Clone, Debug)]
+pub struct Trigger;
+#[derive(Clone, Debug)]
+pub struct Sample;
+#[derive(Clone, Debug)]
+pub struct Done;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Sensor, SensorContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Trigger>()
+        .transition_to::<Sampling>();
+
+    fsm.state::<Sampling>()
+        .on_event::<Sample>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.samples += 1;
+        });
+
+    fsm.state::<Sampling>()
+        .on_event::<Done>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+type SensorFrontend = FsmFrontend<Sensor, FsmEventQueueSpsc<'static, Sensor, 8, 4>, InspectNull, FsmTimersNull>;
+
+static QUEUE: FsmStaticQueue<Sensor, 8> = FsmStatic::new();
+static SENSOR: FsmStatic<SensorFrontend> = FsmStatic::new();
+
+#[test]
+fn test_static_fsm() -> FsmResult<()> {
+    let (mut producer, consumer) = QUEUE.split::<Sensor, 4>().unwrap();
+    assert!(QUEUE.split::<Sensor, 4>().is_none());
+
+    let fsm = SENSOR.init(Sensor::new_with(SensorContext::default(), consumer, InspectNull::new(), FsmTimersNull)?);
+    assert!(SENSOR.is_initialized());
+    fsm.start()?;
+
+    // enqueued from another task, like an interrupt handler
+    std::thread::spawn(move || {
+        producer.enqueue(Trigger).unwrap();
+        producer.enqueue(Sample).unwrap();
+        producer.enqueue(Sample).unwrap();
+    }).join().unwrap();
+
+    fsm.dispatch_queue()?;
+    assert_eq!(FsmCurrentState::State(SensorCurrentState::Sampling), fsm.get_current_states()[0]);
+    assert_eq!(2, fsm.samples);
+
+    Ok(())
+}
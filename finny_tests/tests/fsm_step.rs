@@ -0,0 +1,76 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueSender, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct BootContext;
+
+#[derive(Default)]
+pub struct Off;
+#[derive(Default)]
+pub struct Loading;
+#[derive(Default)]
+pub struct Checking;
+#[derive(Default)]
+pub struct Ready;
+
+#[derive(Clone)]
+pub struct PowerOn;
+#[derive(Clone)]
+pub struct Loaded;
+#[derive(Clone)]
+pub struct Checked;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Boot, BootContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+
+    fsm.state::<Off>()
+        .on_event::<PowerOn>()
+        .transition_to::<Loading>();
+
+    fsm.state::<Loading>()
+        .on_entry(|_state, ctx| {
+            ctx.queue.enqueue(Loaded).unwrap();
+        })
+        .on_event::<Loaded>()
+        .transition_to::<Checking>();
+
+    fsm.state::<Checking>()
+        .on_entry(|_state, ctx| {
+            ctx.queue.enqueue(Checked).unwrap();
+        })
+        .on_event::<Checked>()
+        .transition_to::<Ready>();
+
+    fsm.state::<Ready>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_single_steps() -> FsmResult<()> {
+    let mut fsm = Boot::new(BootContext)?;
+    fsm.start()?;
+    assert!(!fsm.process_one()?);
+
+    fsm.dispatch_single(PowerOn)?;
+    assert_eq!(FsmCurrentState::State(BootCurrentState::Loading), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.queue_len());
+
+    assert!(fsm.process_one()?);
+    assert_eq!(FsmCurrentState::State(BootCurrentState::Checking), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.queue_len());
+
+    assert!(fsm.process_one()?);
+    assert_eq!(FsmCurrentState::State(BootCurrentState::Ready), fsm.get_current_states()[0]);
+    assert_eq!(0, fsm.queue_len());
+    assert!(!fsm.process_one()?);
+
+    // the unhandled event is consumed
+    fsm.queue.enqueue(PowerOn)?;
+    assert_eq!(Err(FsmError::NoTransition { state: "Ready", event: "PowerOn" }), fsm.process_one());
+    assert_eq!(0, fsm.queue_len());
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+extern crate finny;
+
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::{clock::FsmClockManual, std::TimersStd}};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+pub struct DeviceContext {
+    log: Log
+}
+
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone, Debug)]
+pub struct Attach;
+#[derive(Clone, Debug)]
+pub struct Wake;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Device, DeviceContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(1);
+        }, |_ctx, _state| {
+            Some( Wake.into() )
+        })
+        .with_timer_ty::<WakeTimer>();
+
+    fsm.state::<Idle>()
+        .on_event::<Wake>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.log.lock().unwrap().push("wake");
+        });
+
+    fsm.state::<Idle>()
+        .on_event::<Attach>()
+        .transition_to::<Port>();
+
+    fsm.sub_machine::<Port>()
+        .with_context(|ctx| PortContext { log: ctx.log.clone() })
+        .on_entry(|_port, ctx| {
+            ctx.log.lock().unwrap().push("attach");
+        })
+        .on_exit(|_port, ctx| {
+            ctx.log.lock().unwrap().push("detach");
+        });
+
+    fsm.build()
+}
+
+pub struct PortContext {
+    log: Log
+}
+
+#[derive(Default)]
+pub struct Open;
+
+#[finny_fsm]
+fn build_port_fsm(mut fsm: FsmBuilder<Port, PortContext>) -> BuiltFsm {
+    fsm.initial_state::<Open>();
+    fsm.state::<Open>()
+        .on_entry(|_state, ctx| {
+            ctx.log.lock().unwrap().push("open");
+        })
+        .on_exit(|_state, ctx| {
+            ctx.log.lock().unwrap().push("close");
+        });
+    fsm.build()
+}
+
+#[test]
+fn test_stop_exits_bottom_up() -> FsmResult<()> {
+    let log = Log::default();
+    let mut fsm = Device::new(DeviceContext { log: log.clone() })?;
+    fsm.start()?;
+    fsm.dispatch(Attach)?;
+    assert_eq!(vec!["attach", "open"], *log.lock().unwrap());
+
+    fsm.stop();
+    assert_eq!(vec!["attach", "open", "close", "detach"], *log.lock().unwrap());
+    assert!(fsm.is_stopped());
+    assert_eq!([FsmCurrentState::Stopped], fsm.get_sub_current_states::<Port>());
+
+    // stopping again doesn't exit anything
+    fsm.stop();
+    assert_eq!(4, log.lock().unwrap().len());
+
+    fsm.start()?;
+    assert_eq!([FsmCurrentState::State(DeviceCurrentState::Idle)], fsm.get_current_states());
+
+    Ok(())
+}
+
+#[test]
+fn test_stop_cancels_timers() -> FsmResult<()> {
+    let log = Log::default();
+    let clock = FsmClockManual::new();
+    let mut fsm = Device::new_with(DeviceContext { log: log.clone() }, FsmEventQueueVec::new(), InspectNull::new(), TimersStd::with_clock(clock.clone()))?;
+    fsm.start()?;
+
+    fsm.stop();
+    clock.advance(Duration::from_secs(2));
+    fsm.dispatch_timer_events()?;
+    assert!(log.lock().unwrap().is_empty());
+
+    // the restart starts the timer of the initial state again
+    fsm.restart()?;
+    clock.advance(Duration::from_secs(2));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(vec!["wake"], *log.lock().unwrap());
+
+    Ok(())
+}
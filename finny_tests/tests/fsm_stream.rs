@@ -0,0 +1,66 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+use futures::{StreamExt, stream};
+
+#[derive(Debug, Default)]
+pub struct OrderContext {
+    items: usize
+}
+
+#[derive(Default)]
+pub struct Cart;
+#[derive(Default)]
+pub struct Paid;
+
+#[derive(Clone)]
+pub struct AddItem;
+#[derive(Clone)]
+pub struct Pay;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Order, OrderContext>) -> BuiltFsm {
+    fsm.initial_state::<Cart>();
+
+    fsm.state::<Cart>()
+        .on_event::<AddItem>()
+        .internal_transition()
+        .action(|_, ctx, _| {
+            ctx.items += 1;
+        });
+
+    fsm.state::<Cart>()
+        .on_event::<Pay>()
+        .transition_to::<Paid>()
+        .guard(|_, ctx, _| ctx.items > 0);
+
+    fsm.state::<Paid>()
+        .final_state();
+
+    fsm.build()
+}
+
+#[tokio::test]
+async fn test_stream_driver() -> FsmResult<()> {
+    let mut fsm = Order::new(OrderContext::default())?;
+    fsm.start()?;
+
+    let events = stream::iter(vec![
+        OrderEvents::from(Pay),
+        OrderEvents::from(AddItem),
+        OrderEvents::from(Pay),
+        // not dispatched, the machine is completed
+        OrderEvents::from(AddItem)
+    ]);
+
+    let transitions: Vec<_> = fsm.drive_stream(events).collect().await;
+    assert_eq!(3, transitions.len());
+
+    assert_eq!("Pay", transitions[0].event);
+    assert_eq!(Err(FsmError::NoTransition { state: "Cart", event: "Pay" }), transitions[0].result);
+    assert!(!transitions[1].is_state_changed());
+    assert!(transitions[2].is_state_changed());
+    assert_eq!(FsmCurrentState::State(OrderCurrentState::Paid), transitions[2].states_after[0]);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct VendingContext {
+    coins: usize
+}
+
+#[derive(Default)]
+pub struct Waiting;
+#[derive(Default)]
+pub struct Paid;
+#[derive(Default)]
+pub struct LightOff;
+#[derive(Default)]
+pub struct LightOn;
+
+#[derive(Clone)]
+pub struct Coin;
+#[derive(Clone)]
+pub struct Vend;
+#[derive(Clone)]
+pub struct Motion;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Vending, VendingContext>) -> BuiltFsm {
+    fsm.strict_events();
+    fsm.initial_states::<(Waiting, LightOff)>();
+
+    fsm.state::<Waiting>()
+        .on_event::<Coin>()
+        .transition_to::<Paid>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.coins += 1;
+        });
+
+    fsm.state::<Paid>()
+        .on_event::<Vend>()
+        .transition_to::<Waiting>();
+
+    fsm.state::<LightOff>()
+        .on_event::<Motion>()
+        .transition_to::<LightOn>();
+
+    fsm.state::<LightOn>()
+        .on_event::<Coin>()
+        .self_transition();
+
+    fsm.state::<LightOn>()
+        .on_event::<Vend>()
+        .self_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_strict_events() -> FsmResult<()> {
+    let mut fsm = Vending::new(VendingContext::default())?;
+    assert_eq!(Err(FsmError::NoTransition { state: "Stopped", event: "Coin" }), fsm.dispatch(Coin));
+    fsm.start()?;
+
+    // not handled by either of the regions
+    assert_eq!(Err(FsmError::NoTransition { state: "Waiting", event: "Vend" }), fsm.dispatch(Vend));
+
+    // handled by the first region only, the transition still happens
+    assert_eq!(Err(FsmError::NoTransition { state: "LightOff", event: "Coin" }), fsm.dispatch(Coin));
+    assert_eq!(FsmCurrentState::State(VendingCurrentState::Paid), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.coins);
+
+    assert_eq!(Err(FsmError::NoTransition { state: "Paid", event: "Motion" }), fsm.dispatch(Motion));
+    assert_eq!(FsmCurrentState::State(VendingCurrentState::LightOn), fsm.get_current_states()[1]);
+
+    fsm.dispatch(Vend)?;
+    fsm.dispatch(Coin)?;
+    assert_eq!(2, fsm.coins);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct PrinterContext {
+    printed: usize
+}
+
+#[derive(Default)]
+pub struct Ready;
+
+#[derive(Clone)]
+pub struct Print;
+#[derive(Clone)]
+pub struct JobFinished {
+    pages: usize
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Printer, PrinterContext>) -> BuiltFsm {
+    fsm.initial_state::<Ready>();
+
+    fsm.state::<Ready>()
+        .on_event::<Print>()
+        .transition_to::<PrintJob>();
+
+    fsm.sub_machine::<PrintJob>()
+        .on_child_state::<Done, _, _>(|_ctx, done| JobFinished { pages: done.pages })
+        .on_event::<JobFinished>()
+        .transition_to::<Ready>()
+        .action(|ev, ctx, _from, _to| {
+            ctx.printed += ev.pages;
+        });
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct PrintJobContext;
+
+#[derive(Default)]
+pub struct Printing {
+    pages: usize
+}
+#[derive(Default)]
+pub struct Done {
+    pages: usize
+}
+
+#[derive(Clone)]
+pub struct Page;
+#[derive(Clone)]
+pub struct Eject;
+
+#[finny_fsm]
+fn build_print_job_fsm(mut fsm: FsmBuilder<PrintJob, PrintJobContext>) -> BuiltFsm {
+    fsm.initial_state::<Printing>();
+
+    fsm.state::<Printing>()
+        .on_event::<Page>()
+        .internal_transition()
+        .action(|_ev, _ctx, state| {
+            state.pages += 1;
+        });
+
+    fsm.state::<Printing>()
+        .on_event::<Eject>()
+        .transition_to::<Done>()
+        .action(|_ev, _ctx, printing, done| {
+            done.pages = printing.pages;
+        });
+
+    fsm.state::<Done>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_sub_child_state() -> FsmResult<()> {
+    let mut fsm = Printer::new(PrinterContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Print)?;
+    fsm.dispatch(PrintJobEvents::Page(Page))?;
+    fsm.dispatch(PrintJobEvents::Page(Page))?;
+    assert_eq!(FsmCurrentState::State(PrinterCurrentState::PrintJob), fsm.get_current_states()[0]);
+
+    fsm.dispatch(PrintJobEvents::Eject(Eject))?;
+    assert_eq!(FsmCurrentState::State(PrinterCurrentState::Ready), fsm.get_current_states()[0]);
+    assert_eq!(2, fsm.printed);
+
+    Ok(())
+}
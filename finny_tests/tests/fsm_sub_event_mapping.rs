@@ -0,0 +1,101 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct KioskContext {
+    paid: usize
+}
+
+#[derive(Default)]
+pub struct Browsing;
+#[derive(Default)]
+pub struct Done;
+
+#[derive(Clone)]
+pub struct Checkout {
+    total: usize
+}
+#[derive(Clone)]
+pub struct OrderPaid {
+    amount: usize
+}
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Kiosk, KioskContext>) -> BuiltFsm {
+    fsm.initial_state::<Browsing>();
+
+    fsm.state::<Browsing>()
+        .on_event::<Checkout>()
+        .transition_to::<Payment>();
+
+    fsm.sub_machine::<Payment>()
+        .with_context(|_ctx| PaymentContext { limit: 50 })
+        .map_event::<Checkout, _, _>(|ev| Charge { amount: ev.total })
+        .lift_event::<Approved, _, _>(|ev| OrderPaid { amount: ev.amount })
+        .on_event::<OrderPaid>()
+        .transition_to::<Done>()
+        .action(|ev, ctx, _from, _to| {
+            ctx.paid += ev.amount;
+        });
+
+    fsm.state::<Done>();
+
+    fsm.build()
+}
+
+pub struct PaymentContext {
+    limit: usize
+}
+
+#[derive(Default)]
+pub struct Waiting;
+#[derive(Default)]
+pub struct Authorized;
+
+#[derive(Clone)]
+pub struct Charge {
+    amount: usize
+}
+#[derive(Clone)]
+pub struct Approved {
+    amount: usize
+}
+
+#[finny_fsm]
+fn build_payment_fsm(mut fsm: FsmBuilder<Payment, PaymentContext>) -> BuiltFsm {
+    fsm.initial_state::<Waiting>();
+    fsm.event::<Approved>();
+
+    fsm.state::<Waiting>()
+        .on_event::<Charge>()
+        .transition_to::<Authorized>()
+        .guard(|ev, ctx, _states| ev.amount <= ctx.limit)
+        .action(|ev, ctx, _from, _to| {
+            ctx.queue.enqueue(Approved { amount: ev.amount }).unwrap();
+        });
+
+    fsm.state::<Authorized>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_sub_event_mapping() -> FsmResult<()> {
+    let mut fsm = Kiosk::new(KioskContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Checkout { total: 10 })?;
+    assert_eq!(FsmCurrentState::State(KioskCurrentState::Payment), fsm.get_current_states()[0]);
+
+    // no longer handled by the parent, mapped into the payment's charge
+    assert!(fsm.dispatch(Checkout { total: 80 }).is_err());
+    fsm.dispatch(Checkout { total: 10 })?;
+    assert_eq!(FsmCurrentState::State(KioskCurrentState::Done), fsm.get_current_states()[0]);
+    assert_eq!(10, fsm.paid);
+
+    let payment: &Payment = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(PaymentCurrentState::Authorized), payment.get_current_states()[0]);
+
+    Ok(())
+}
@@ -135,7 +135,7 @@ fn test_sub_generics() -> FsmResult<()> {
     assert_eq!(2, state.value);
 
     let res = fsm.dispatch(EventSub { n: 0 });
-    assert_eq!(Err(FsmError::NoTransition), res);
+    assert_eq!(Err(FsmError::NoTransition { state: "SubStateMachine", event: "EventSub" }), res);
 
     fsm.dispatch(EventSub { n: 1 })?;
 
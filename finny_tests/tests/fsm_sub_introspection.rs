@@ -0,0 +1,85 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct WasherContext;
+
+#[derive(Default)]
+pub struct Off;
+
+#[derive(Clone)]
+pub struct PowerOn;
+#[derive(Clone)]
+pub struct PowerOff;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Washer, WasherContext>) -> BuiltFsm {
+    fsm.initial_state::<Off>();
+
+    fsm.state::<Off>()
+        .on_event::<PowerOn>()
+        .transition_to::<Cycle>();
+
+    fsm.sub_machine::<Cycle>()
+        .on_event::<PowerOff>()
+        .transition_to::<Off>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct CycleContext;
+
+#[derive(Default)]
+pub struct Filling {
+    liters: usize
+}
+#[derive(Default)]
+pub struct Spinning;
+
+#[derive(Clone)]
+pub struct Water;
+#[derive(Clone)]
+pub struct Spin;
+
+#[finny_fsm]
+fn build_cycle_fsm(mut fsm: FsmBuilder<Cycle, CycleContext>) -> BuiltFsm {
+    fsm.initial_state::<Filling>();
+
+    fsm.state::<Filling>()
+        .on_event::<Water>()
+        .internal_transition()
+        .action(|_ev, _ctx, state| {
+            state.liters += 5;
+        });
+
+    fsm.state::<Filling>()
+        .on_event::<Spin>()
+        .transition_to::<Spinning>();
+
+    fsm.state::<Spinning>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_sub_introspection() -> FsmResult<()> {
+    let mut fsm = Washer::new(WasherContext)?;
+    fsm.start()?;
+    assert_eq!(FsmCurrentState::Stopped, fsm.get_sub_current_states::<Cycle>()[0]);
+
+    fsm.dispatch(PowerOn)?;
+    fsm.dispatch(CycleEvents::Water(Water))?;
+    fsm.dispatch(CycleEvents::Water(Water))?;
+    assert_eq!(FsmCurrentState::State(CycleCurrentState::Filling), fsm.get_sub_current_states::<Cycle>()[0]);
+    assert_eq!(10, fsm.get_sub_state::<Cycle, Filling>().liters);
+
+    fsm.dispatch(CycleEvents::Spin(Spin))?;
+    assert_eq!(FsmCurrentState::State(CycleCurrentState::Spinning), fsm.get_sub_current_states::<Cycle>()[0]);
+
+    fsm.dispatch(PowerOff)?;
+    assert_eq!(FsmCurrentState::State(WasherCurrentState::Off), fsm.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+extern crate finny;
+
+use std::{sync::Mutex, time::Duration};
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimers, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::log::InspectLog, timers::test::TimersTest};
+use log::{LevelFilter, Log, Metadata, Record};
+
+#[derive(Clone)]
+pub struct Descend;
+#[derive(Clone)]
+pub struct Ping;
+#[derive(Clone)]
+pub struct Pong;
+
+#[derive(Default)]
+pub struct Context1;
+#[derive(Default)]
+pub struct Idle1;
+
+#[finny_fsm]
+fn build_level1_fsm(mut fsm: FsmBuilder<Level1, Context1>) -> BuiltFsm {
+    fsm.initial_state::<Idle1>();
+    fsm.state::<Idle1>()
+        .on_event::<Descend>()
+        .transition_to::<Level2>();
+    fsm.sub_machine::<Level2>();
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Context2;
+#[derive(Default)]
+pub struct Idle2;
+
+#[finny_fsm]
+fn build_level2_fsm(mut fsm: FsmBuilder<Level2, Context2>) -> BuiltFsm {
+    fsm.initial_state::<Idle2>();
+    fsm.state::<Idle2>()
+        .on_event::<Descend>()
+        .transition_to::<Level3>();
+    fsm.sub_machine::<Level3>();
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Context3;
+#[derive(Default)]
+pub struct Idle3;
+
+#[finny_fsm]
+fn build_level3_fsm(mut fsm: FsmBuilder<Level3, Context3>) -> BuiltFsm {
+    fsm.initial_state::<Idle3>();
+    fsm.state::<Idle3>()
+        .on_event::<Descend>()
+        .transition_to::<Level4>();
+    fsm.sub_machine::<Level4>();
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Context4;
+#[derive(Default)]
+pub struct Idle4;
+
+#[finny_fsm]
+fn build_level4_fsm(mut fsm: FsmBuilder<Level4, Context4>) -> BuiltFsm {
+    fsm.initial_state::<Idle4>();
+    fsm.state::<Idle4>()
+        .on_event::<Descend>()
+        .transition_to::<Level5>();
+    fsm.sub_machine::<Level5>();
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Context5 {
+    pings: usize,
+    pongs: usize
+}
+#[derive(Default)]
+pub struct Waiting5;
+#[derive(Default)]
+pub struct Done5;
+
+#[finny_fsm]
+fn build_level5_fsm(mut fsm: FsmBuilder<Level5, Context5>) -> BuiltFsm {
+    fsm.initial_state::<Waiting5>();
+
+    fsm.state::<Waiting5>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(10);
+        }, |_ctx, _state| {
+            Some( Ping.into() )
+        })
+        .with_timer_ty::<PingTimer>();
+
+    // the action's event goes through the queues of all the levels and back
+    fsm.state::<Waiting5>()
+        .on_event::<Ping>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.pings += 1;
+            ctx.queue.enqueue(Pong).unwrap();
+        });
+
+    fsm.state::<Waiting5>()
+        .on_event::<Pong>()
+        .transition_to::<Done5>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.pongs += 1;
+        });
+
+    fsm.state::<Done5>();
+
+    fsm.build()
+}
+
+struct Recorder {
+    lines: Mutex<Vec<String>>
+}
+
+impl Log for Recorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder { lines: Mutex::new(Vec::new()) };
+
+fn level5_event(ev: Level5Events) -> Level1Events {
+    Level1Events::Level2(Level2Events::Level3(Level3Events::Level4(Level4Events::Level5(ev))))
+}
+
+#[test]
+fn test_sub_nested_five_levels() -> FsmResult<()> {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut fsm = Level1::new_with(Context1, FsmEventQueueVec::new(), InspectLog::new(), TimersTest::new())?;
+    fsm.start()?;
+
+    fsm.dispatch(Descend)?;
+    fsm.dispatch(Level2Events::Descend(Descend))?;
+    fsm.dispatch(Level2Events::Level3(Level3Events::Descend(Descend)))?;
+    fsm.dispatch(Level2Events::Level3(Level3Events::Level4(Level4Events::Descend(Descend))))?;
+
+    let level2: &Level2 = fsm.get_state();
+    let level3: &Level3 = level2.get_state();
+    let level4: &Level4 = level3.get_state();
+    assert_eq!(FsmCurrentState::State(Level4CurrentState::Level5), level4.get_current_states()[0]);
+    let level5: &Level5 = level4.get_state();
+    assert_eq!(FsmCurrentState::State(Level5CurrentState::Waiting5), level5.get_current_states()[0]);
+
+    // the timer of the deepest level is scoped to it
+    assert!(fsm.timers.is_running(Level1Timers::Level2(Level2Timers::Level3(Level3Timers::Level4(Level4Timers::Level5(Level5Timers::PingTimer))))));
+    fsm.advance(Duration::from_secs(10))?;
+
+    let level5: &Level5 = fsm.get_state::<Level2>().get_state::<Level3>().get_state::<Level4>().get_state();
+    assert_eq!(FsmCurrentState::State(Level5CurrentState::Done5), level5.get_current_states()[0]);
+    assert_eq!(1, level5.pings);
+    assert_eq!(1, level5.pongs);
+    assert_eq!(0, fsm.queue_len());
+
+    // the inspection of each level is nested in its parent's
+    let lines = RECORDER.lines.lock().unwrap();
+    assert!(lines.iter().any(|l| l.contains("[Level4] Level5Events: [Level5] Pong: Matched transition")));
+
+    Ok(())
+}
+
+#[test]
+fn test_sub_nested_events() -> FsmResult<()> {
+    let mut fsm = Level1::new(Context1)?;
+    fsm.start()?;
+
+    // not in the deepest level yet
+    assert!(fsm.dispatch(level5_event(Ping.into())).is_err());
+
+    for ev in [Descend.into(), Level2Events::Descend(Descend).into(), Level2Events::Level3(Level3Events::Descend(Descend)).into(),
+        Level2Events::Level3(Level3Events::Level4(Level4Events::Descend(Descend))).into()] {
+        fsm.dispatch::<Level1Events>(ev)?;
+    }
+
+    fsm.dispatch(level5_event(Ping.into()))?;
+    let level5: &Level5 = fsm.get_state::<Level2>().get_state::<Level3>().get_state::<Level4>().get_state();
+    assert_eq!(FsmCurrentState::State(Level5CurrentState::Done5), level5.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default)]
+pub struct PhoneContext {
+    calls: usize
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct InCall;
+
+#[derive(Clone)]
+pub struct Dial;
+#[derive(Clone)]
+pub struct IncomingCall;
+#[derive(Clone)]
+pub struct HangUp;
+#[derive(Clone)]
+pub struct CallConnected;
+#[derive(Clone)]
+pub struct CallRejected;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Phone, PhoneContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Dial>()
+        .transition_to::<CallSetup>();
+
+    fsm.state::<Idle>()
+        .on_event::<IncomingCall>()
+        .transition_to::<CallSetup>()
+        .entry_point::<Ringing>();
+
+    fsm.sub_machine::<CallSetup>()
+        .with_context(|ctx| CallSetupContext { attempt: ctx.calls })
+        .on_exit_point::<Connected, _, _>(|_ctx| CallConnected)
+        .on_exit_point::<Rejected, _, _>(|_ctx| CallRejected)
+        .on_event::<CallConnected>()
+        .transition_to::<InCall>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.calls += 1;
+        });
+
+    fsm.sub_machine::<CallSetup>()
+        .on_event::<CallRejected>()
+        .transition_to::<Idle>();
+
+    fsm.state::<InCall>()
+        .on_event::<HangUp>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+pub struct CallSetupContext {
+    attempt: usize
+}
+
+#[derive(Default)]
+pub struct Dialing {
+    entered: usize
+}
+#[derive(Default)]
+pub struct Ringing {
+    entered: usize
+}
+#[derive(Default)]
+pub struct Connected;
+#[derive(Default)]
+pub struct Rejected;
+
+#[derive(Clone)]
+pub struct Answer;
+#[derive(Clone)]
+pub struct Decline;
+
+#[finny_fsm]
+fn build_call_setup_fsm(mut fsm: FsmBuilder<CallSetup, CallSetupContext>) -> BuiltFsm {
+    fsm.initial_state::<Dialing>();
+
+    fsm.state::<Dialing>()
+        .on_entry(|state, _ctx| {
+            state.entered += 1;
+        })
+        .on_event::<Answer>()
+        .transition_to::<Connected>();
+
+    fsm.state::<Ringing>()
+        .entry_point()
+        .on_entry(|state, _ctx| {
+            state.entered += 1;
+        })
+        .on_event::<Answer>()
+        .transition_to::<Connected>();
+
+    fsm.state::<Ringing>()
+        .on_event::<Decline>()
+        .transition_to::<Rejected>();
+
+    fsm.state::<Connected>();
+    fsm.state::<Rejected>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_sub_entry_point() -> FsmResult<()> {
+    let mut fsm = Phone::new(PhoneContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(IncomingCall)?;
+    assert_eq!(FsmCurrentState::State(PhoneCurrentState::CallSetup), fsm.get_current_states()[0]);
+    let sub: &CallSetup = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(CallSetupCurrentState::Ringing), sub.get_current_states()[0]);
+    let ringing: &Ringing = sub.get_state();
+    assert_eq!(1, ringing.entered);
+    let dialing: &Dialing = sub.get_state();
+    assert_eq!(0, dialing.entered);
+
+    // the rejected exit leads back to idle
+    fsm.dispatch(CallSetupEvents::Decline(Decline))?;
+    assert_eq!(FsmCurrentState::State(PhoneCurrentState::Idle), fsm.get_current_states()[0]);
+    assert_eq!(0, fsm.calls);
+
+    Ok(())
+}
+
+#[test]
+fn test_sub_exit_points() -> FsmResult<()> {
+    let mut fsm = Phone::new(PhoneContext::default())?;
+    fsm.start()?;
+
+    fsm.dispatch(Dial)?;
+    let sub: &CallSetup = fsm.get_state();
+    assert_eq!(FsmCurrentState::State(CallSetupCurrentState::Dialing), sub.get_current_states()[0]);
+    assert_eq!(0, sub.attempt);
+
+    fsm.dispatch(CallSetupEvents::Answer(Answer))?;
+    assert_eq!(FsmCurrentState::State(PhoneCurrentState::InCall), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.calls);
+
+    fsm.dispatch(HangUp)?;
+    fsm.dispatch(IncomingCall)?;
+    fsm.dispatch(CallSetupEvents::Answer(Answer))?;
+    assert_eq!(FsmCurrentState::State(PhoneCurrentState::InCall), fsm.get_current_states()[0]);
+    assert_eq!(2, fsm.calls);
+
+    Ok(())
+}
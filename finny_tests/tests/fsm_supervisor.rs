@@ -0,0 +1,105 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueueVec, FsmFactory, FsmResult, FsmSupervisor, FsmSupervisorPolicy, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::{clock::FsmClockManual, std::TimersStd}};
+
+#[derive(Default)]
+pub struct SensorContext {
+    fails: bool,
+    panics: bool
+}
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Warm;
+#[derive(Default)]
+pub struct Failed;
+
+#[derive(Clone, Debug)]
+pub struct WarmUp;
+#[derive(Clone, Debug)]
+pub struct Measure;
+#[derive(Clone, Debug)]
+pub struct Trip;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Sensor, SensorContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<WarmUp>()
+        .transition_to::<Warm>();
+
+    fsm.state::<Warm>()
+        .on_event::<Measure>()
+        .internal_transition()
+        .try_action(|_ev, ctx, _state| {
+            if ctx.panics {
+                panic!("the sensor's driver crashed");
+            }
+            if ctx.fails {
+                return Err(FsmError::ActionFailed("sensor"));
+            }
+            Ok(())
+        });
+
+    fsm.state::<Failed>();
+    fsm.any_state()
+        .on_event::<Trip>()
+        .transition_to::<Failed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_supervisor_restarts() -> FsmResult<()> {
+    let clock = FsmClockManual::new();
+    let fsm = Sensor::new_with(SensorContext { fails: true, ..Default::default() }, FsmEventQueueVec::new(), InspectNull::new(), TimersStd::with_clock(clock.clone()))?;
+    let mut supervisor = FsmSupervisor::new(fsm, FsmSupervisorPolicy::Restart)
+        .with_max_restarts(2, Duration::from_secs(60))
+        .with_backoff(Duration::from_secs(1), Duration::from_secs(10));
+    supervisor.start()?;
+
+    supervisor.dispatch(WarmUp)?;
+    supervisor.dispatch(Measure)?;
+    assert_eq!(1, supervisor.restarts());
+    assert_eq!([FsmCurrentState::State(SensorCurrentState::Idle)], supervisor.frontend().get_current_states());
+    assert_eq!(Err(FsmError::SupervisorBackoff { remaining: Duration::from_secs(1) }), supervisor.dispatch(WarmUp));
+
+    // the backoff doubles with every restart
+    clock.advance(Duration::from_secs(1));
+    supervisor.dispatch(WarmUp)?;
+    supervisor.dispatch(Measure)?;
+    assert_eq!(Err(FsmError::SupervisorBackoff { remaining: Duration::from_secs(2) }), supervisor.dispatch(WarmUp));
+
+    // restarted too many times within the period
+    clock.advance(Duration::from_secs(2));
+    supervisor.dispatch(WarmUp)?;
+    assert_eq!(Err(FsmError::ActionFailed("sensor")), supervisor.dispatch(Measure));
+    assert_eq!((2, 3), (supervisor.restarts(), supervisor.failures()));
+
+    // the restarts are forgotten after the period
+    clock.advance(Duration::from_secs(60));
+    supervisor.dispatch(Measure)?;
+    assert_eq!(3, supervisor.restarts());
+    assert_eq!(Some(&FsmError::ActionFailed("sensor")), supervisor.last_error());
+
+    Ok(())
+}
+
+#[test]
+fn test_supervisor_fault_on_panic() -> FsmResult<()> {
+    let fsm = Sensor::new(SensorContext { panics: true, ..Default::default() })?;
+    let mut supervisor = FsmSupervisor::new(fsm, FsmSupervisorPolicy::Fault(|| Trip.into()))
+        .catch_panics();
+    supervisor.start()?;
+    supervisor.dispatch(WarmUp)?;
+
+    supervisor.dispatch(Measure)?;
+    assert_eq!(Some(&FsmError::Panicked), supervisor.last_error());
+    assert_eq!([FsmCurrentState::State(SensorCurrentState::Failed)], supervisor.into_inner().get_current_states());
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+extern crate finny;
+
+use std::{sync::atomic::{AtomicU64, Ordering}, time::Duration};
+
+use finny::{FsmClock, FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResetStates, FsmResult, FsmTimersNull, FsmTimersWithClock, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull};
+
+/// A tick counter, like the one of a microcontroller.
+pub struct TickClock(&'static AtomicU64);
+
+impl FsmClock for TickClock {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+#[derive(Default)]
+pub struct HeaterContext {
+    heated: Vec<Option<Duration>>,
+    exited: Vec<Option<Duration>>
+}
+
+#[derive(Default)]
+pub struct Heating;
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone, Debug)]
+pub struct Check;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Heater, HeaterContext>) -> BuiltFsm {
+    fsm.initial_state::<Heating>();
+
+    fsm.state::<Heating>()
+        .on_exit(|_state, ctx| {
+            let elapsed = ctx.time_in_state();
+            ctx.exited.push(elapsed);
+        })
+        .on_event::<Check>()
+        .transition_to::<Idle>()
+        .guard(|_ev, ctx, _states| {
+            ctx.time_in_state().map(|t| t >= Duration::from_secs(5)).unwrap_or(false)
+        })
+        .action(|_ev, ctx, _from, _to| {
+            let elapsed = ctx.time_in_state();
+            ctx.heated.push(elapsed);
+        });
+
+    fsm.state::<Idle>()
+        .on_event::<Check>()
+        .internal_transition();
+
+    fsm.build()
+}
+
+#[test]
+fn test_time_in_state_with_user_clock() -> FsmResult<()> {
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+    let timers = FsmTimersWithClock::new(FsmTimersNull, TickClock(&TICKS));
+    let mut fsm = Heater::new_with(HeaterContext::default(), FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+
+    TICKS.store(3_000, Ordering::SeqCst);
+    assert!(fsm.dispatch(Check).is_err());
+
+    TICKS.store(7_000, Ordering::SeqCst);
+    fsm.dispatch(Check)?;
+    assert_eq!([FsmCurrentState::State(HeaterCurrentState::Idle)], fsm.get_current_states());
+    assert_eq!(vec![Some(Duration::from_secs(7))], fsm.get_context().heated);
+    assert_eq!(vec![Some(Duration::from_secs(7))], fsm.get_context().exited);
+
+    // the exit actions of the stopped machine see the time up to the stop
+    fsm.reset(FsmResetStates::Keep)?;
+    TICKS.store(9_500, Ordering::SeqCst);
+    fsm.stop();
+    assert_eq!(Some(Duration::from_millis(2_500)), fsm.get_context().exited[1]);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct RetryContext {
+    backoff_ms: u64,
+    attempts: usize
+}
+
+#[derive(Default)]
+pub struct Retrying;
+
+#[derive(Clone, Debug)]
+pub struct RetryTimeout;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Retry, RetryContext>) -> BuiltFsm {
+    fsm.initial_state::<Retrying>();
+
+    fsm.state::<Retrying>()
+        .on_timer(|ctx, _state| {
+            Duration::from_millis(ctx.backoff_ms)
+        }, |_ctx, _state| {
+            Some( RetryTimeout.into() )
+        })
+        .with_timer_ty::<BackoffTimer>();
+
+    fsm.state::<Retrying>()
+        .on_event::<RetryTimeout>()
+        .self_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.attempts += 1;
+            ctx.backoff_ms *= 2;
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_timer_computed_timeout() -> FsmResult<()> {
+    let timers: TimersCore<Retry, RetryTimersStorage<CoreTimer>, [RetryTimers; 16]> = TimersCore::new(Default::default());
+    let mut fsm = Retry::new_with(RetryContext { backoff_ms: 100, attempts: 0 }, FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+
+    let mut tick = || -> FsmResult<usize> {
+        fsm.timers.tick(Duration::from_millis(100));
+        fsm.dispatch_timer_events()?;
+        Ok(fsm.attempts)
+    };
+
+    assert_eq!(1, tick()?);
+    assert_eq!(1, tick()?);
+    assert_eq!(2, tick()?);
+    assert_eq!(2, tick()?);
+    assert_eq!(2, tick()?);
+    assert_eq!(2, tick()?);
+    assert_eq!(3, tick()?);
+
+    Ok(())
+}
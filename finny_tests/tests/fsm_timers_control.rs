@@ -0,0 +1,94 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct SessionContext {
+    timeout_ms: u64
+}
+
+#[derive(Default)]
+pub struct Active;
+#[derive(Default)]
+pub struct Idle;
+
+#[derive(Clone, Debug)]
+pub struct UserActivity;
+#[derive(Clone, Debug)]
+pub struct KeepAlive;
+#[derive(Clone, Debug)]
+pub struct InactivityTimeout;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Session, SessionContext>) -> BuiltFsm {
+    fsm.initial_state::<Active>();
+
+    fsm.state::<Active>()
+        .on_entry_start_timer(|ctx, settings| {
+            settings.timeout = Duration::from_millis(ctx.timeout_ms);
+        }, |_ctx, _state| {
+            Some( InactivityTimeout.into() )
+        })
+        .with_timer_ty::<InactivityTimer>();
+
+    fsm.state::<Active>()
+        .on_event::<UserActivity>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.timers.restart::<InactivityTimer>().unwrap();
+        });
+
+    fsm.state::<Active>()
+        .on_event::<KeepAlive>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.timers.cancel::<InactivityTimer>().unwrap();
+        });
+
+    fsm.state::<Active>()
+        .on_event::<InactivityTimeout>()
+        .transition_to::<Idle>();
+
+    fsm.state::<Idle>();
+
+    fsm.build()
+}
+
+type Timers = TimersCore<Session, SessionTimersStorage<CoreTimer>, [SessionTimers; 16]>;
+
+fn new_session() -> FsmResult<FsmFrontend<Session, FsmEventQueueVec<Session>, InspectNull, Timers>> {
+    let mut fsm = Session::new_with(SessionContext { timeout_ms: 100 }, FsmEventQueueVec::new(), InspectNull::new(), TimersCore::new(Default::default()))?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_timer_restart() -> FsmResult<()> {
+    let mut fsm = new_session()?;
+
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch(UserActivity)?;
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(SessionCurrentState::Active), fsm.get_current_states()[0]);
+
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(SessionCurrentState::Idle), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_timer_cancel() -> FsmResult<()> {
+    let mut fsm = new_session()?;
+
+    fsm.dispatch(KeepAlive)?;
+    fsm.timers.tick(Duration::from_millis(200));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(SessionCurrentState::Active), fsm.get_current_states()[0]);
+
+    Ok(())
+}
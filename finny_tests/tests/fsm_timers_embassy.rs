@@ -0,0 +1,74 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use embassy_futures::{block_on, select::select};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
+use embassy_time::Timer;
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::embassy::TimersEmbassy};
+
+#[derive(Default)]
+pub struct ValveContext {
+    opened: usize
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone, Debug)]
+pub struct Request;
+#[derive(Clone, Debug)]
+pub struct Close;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Valve, ValveContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+
+    fsm.state::<Closed>()
+        .on_event::<Request>()
+        .transition_to::<Open>()
+        .action(|_ev, ctx, _from, _to| {
+            ctx.opened += 1;
+        });
+
+    fsm.state::<Open>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_millis(20);
+        }, |_ctx, _state| {
+            Some( Close.into() )
+        })
+        .with_timer_ty::<CloseTimer>();
+
+    fsm.state::<Open>()
+        .on_event::<Close>()
+        .transition_to::<Closed>();
+
+    fsm.build()
+}
+
+static EVENTS: Channel<CriticalSectionRawMutex, Request, 4> = Channel::new();
+
+#[test]
+fn test_embassy_runner() -> FsmResult<()> {
+    let timers = TimersEmbassy::new(ValveTimersStorage::default());
+    let mut fsm = Valve::new_with(ValveContext::default(), FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+
+    block_on(async {
+        let events = async {
+            EVENTS.send(Request).await;
+            Timer::after_millis(100).await;
+            EVENTS.send(Request).await;
+            Timer::after_millis(5).await;
+        };
+        select(fsm.run_embassy(EVENTS.receiver()), events).await;
+    });
+
+    // opened twice, closed by the timer in between
+    assert_eq!(2, fsm.opened);
+    assert_eq!(FsmCurrentState::State(ValveCurrentState::Open), fsm.get_current_states()[0]);
+
+    Ok(())
+}
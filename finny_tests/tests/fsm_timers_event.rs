@@ -0,0 +1,69 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct RequestContext {
+    timeout_ms: u64,
+    timeouts: usize
+}
+
+#[derive(Default)]
+pub struct Waiting;
+#[derive(Default)]
+pub struct Failed;
+
+#[derive(Clone, Debug)]
+pub struct Timeout { after_ms: u64 }
+#[derive(Clone, Debug)]
+pub struct Response;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Request, RequestContext>) -> BuiltFsm {
+    fsm.initial_state::<Waiting>();
+
+    fsm.state::<Waiting>()
+        .on_timer_event::<Timeout>(|ctx, _state| {
+            Duration::from_millis(ctx.timeout_ms)
+        }, |ctx, _state| {
+            Timeout { after_ms: ctx.timeout_ms }
+        })
+        .with_timer_ty::<ResponseTimer>();
+
+    fsm.state::<Waiting>()
+        .on_event::<Timeout>()
+        .transition_to::<Failed>()
+        .guard(|ev, ctx, _| ev.after_ms == ctx.timeout_ms)
+        .action(|_ev, ctx, _from, _to| { ctx.timeouts += 1; });
+
+    fsm.state::<Waiting>()
+        .on_event::<Response>()
+        .self_transition();
+
+    fsm.state::<Failed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_timer_event() -> FsmResult<()> {
+    let timers: TimersCore<Request, RequestTimersStorage<CoreTimer>, [RequestTimers; 16]> = TimersCore::new(Default::default());
+    let mut fsm = Request::new_with(RequestContext { timeout_ms: 100, timeouts: 0 }, FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+
+    // the responses restart the timer
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch(Response)?;
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(RequestCurrentState::Waiting), fsm.get_current_states()[0]);
+
+    fsm.timers.tick(Duration::from_millis(60));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(FsmCurrentState::State(RequestCurrentState::Failed), fsm.get_current_states()[0]);
+    assert_eq!(1, fsm.timeouts);
+
+    Ok(())
+}
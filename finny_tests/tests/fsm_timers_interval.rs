@@ -0,0 +1,68 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::core::{CoreTimer, TimersCore}};
+
+#[derive(Default)]
+pub struct HeartbeatContext {
+    beats: usize
+}
+
+#[derive(Default)]
+pub struct Connected;
+#[derive(Default)]
+pub struct Disconnected;
+
+#[derive(Clone, Debug)]
+pub struct Beat;
+#[derive(Clone, Debug)]
+pub struct Disconnect;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Heartbeat, HeartbeatContext>) -> BuiltFsm {
+    fsm.initial_state::<Connected>();
+
+    fsm.state::<Connected>()
+        .on_timer_interval(Duration::from_millis(100), |_ctx, _state| {
+            Some( Beat.into() )
+        })
+        .with_timer_ty::<HeartbeatTimer>();
+
+    fsm.state::<Connected>()
+        .on_event::<Beat>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.beats += 1;
+        });
+
+    fsm.state::<Connected>()
+        .on_event::<Disconnect>()
+        .transition_to::<Disconnected>();
+
+    fsm.state::<Disconnected>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_timer_interval() -> FsmResult<()> {
+    let timers: TimersCore<Heartbeat, HeartbeatTimersStorage<CoreTimer>, [HeartbeatTimers; 16]> = TimersCore::new(Default::default());
+    let mut fsm = Heartbeat::new_with(HeartbeatContext::default(), FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+
+    for _ in 0..3 {
+        fsm.timers.tick(Duration::from_millis(100));
+        fsm.dispatch_timer_events()?;
+    }
+    assert_eq!(3, fsm.beats);
+
+    fsm.dispatch(Disconnect)?;
+    assert_eq!(FsmCurrentState::State(HeartbeatCurrentState::Disconnected), fsm.get_current_states()[0]);
+
+    fsm.timers.tick(Duration::from_millis(100));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(3, fsm.beats);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, FsmTimers, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::test::TimersTest};
+
+#[derive(Clone, Debug)]
+pub struct Begin;
+#[derive(Clone, Debug)]
+pub struct Abort;
+#[derive(Clone, Debug)]
+pub struct Tick;
+
+#[derive(Default)]
+pub struct JobContext;
+#[derive(Default)]
+pub struct Idle;
+
+#[finny_fsm]
+fn build_job_fsm(mut fsm: FsmBuilder<Job, JobContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_event::<Begin>()
+        .transition_to::<Worker>();
+
+    fsm.sub_machine::<Worker>()
+        .on_event::<Abort>()
+        .transition_to::<Idle>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct WorkerContext {
+    ticks: usize
+}
+#[derive(Default)]
+pub struct Working;
+
+#[finny_fsm]
+fn build_worker_fsm(mut fsm: FsmBuilder<Worker, WorkerContext>) -> BuiltFsm {
+    fsm.initial_state::<Working>();
+
+    fsm.state::<Working>()
+        .on_timer_interval(Duration::from_secs(1), |_ctx, _state| {
+            Some( Tick.into() )
+        })
+        .with_timer_ty::<TickTimer>();
+
+    fsm.state::<Working>()
+        .on_event::<Tick>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.ticks += 1;
+        });
+
+    fsm.build()
+}
+
+fn new_job() -> FsmResult<FsmFrontend<Job, FsmEventQueueVec<Job>, InspectNull, TimersTest<Job>>> {
+    let mut fsm = Job::new_with(JobContext, FsmEventQueueVec::new(), InspectNull::new(), TimersTest::new())?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_sub_timers_cancelled_on_exit() -> FsmResult<()> {
+    let mut fsm = new_job()?;
+    let tick_timer = JobTimers::Worker(WorkerTimers::TickTimer);
+
+    fsm.dispatch(Begin)?;
+    fsm.advance(Duration::from_secs(2))?;
+    let worker: &Worker = fsm.get_state();
+    assert_eq!(2, worker.ticks);
+    assert!(fsm.timers.is_running(tick_timer));
+
+    fsm.dispatch(Abort)?;
+    assert_eq!(FsmCurrentState::State(JobCurrentState::Idle), fsm.get_current_states()[0]);
+    assert!(!fsm.timers.is_running(tick_timer));
+
+    fsm.advance(Duration::from_secs(5))?;
+    let worker: &Worker = fsm.get_state();
+    assert_eq!(2, worker.ticks);
+
+    // the submachine is started again, with its timers
+    fsm.dispatch(Begin)?;
+    assert!(fsm.timers.is_running(tick_timer));
+
+    Ok(())
+}
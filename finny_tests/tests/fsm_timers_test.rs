@@ -0,0 +1,118 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, FsmTimers, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::test::TimersTest};
+
+#[derive(Default)]
+pub struct LightContext {
+    blinks: usize
+}
+
+#[derive(Default)]
+pub struct Red;
+#[derive(Default)]
+pub struct Green;
+#[derive(Default)]
+pub struct Yellow;
+
+#[derive(Clone, Debug)]
+pub struct Next;
+#[derive(Clone, Debug)]
+pub struct Blink;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Light, LightContext>) -> BuiltFsm {
+    fsm.initial_state::<Red>();
+
+    fsm.state::<Red>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(30);
+        }, |_ctx, _state| {
+            Some( Next.into() )
+        })
+        .with_timer_ty::<RedTimer>();
+
+    fsm.state::<Red>()
+        .on_event::<Next>()
+        .transition_to::<Green>();
+
+    fsm.state::<Green>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(25);
+        }, |_ctx, _state| {
+            Some( Next.into() )
+        })
+        .with_timer_ty::<GreenTimer>();
+
+    fsm.state::<Green>()
+        .on_event::<Next>()
+        .transition_to::<Yellow>();
+
+    fsm.state::<Yellow>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(5);
+        }, |_ctx, _state| {
+            Some( Next.into() )
+        })
+        .with_timer_ty::<YellowTimer>();
+
+    fsm.state::<Yellow>()
+        .on_timer_interval(Duration::from_secs(1), |_ctx, _state| {
+            Some( Blink.into() )
+        })
+        .with_timer_ty::<BlinkTimer>();
+
+    fsm.state::<Yellow>()
+        .on_event::<Blink>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.blinks += 1;
+        });
+
+    fsm.state::<Yellow>()
+        .on_event::<Next>()
+        .transition_to::<Red>();
+
+    fsm.build()
+}
+
+fn new_light() -> FsmResult<FsmFrontend<Light, FsmEventQueueVec<Light>, InspectNull, TimersTest<Light>>> {
+    let mut fsm = Light::new_with(LightContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTest::new())?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_advance_fires_the_timers() -> FsmResult<()> {
+    let mut fsm = new_light()?;
+
+    fsm.advance(Duration::from_secs(29))?;
+    assert_eq!(FsmCurrentState::State(LightCurrentState::Red), fsm.get_current_states()[0]);
+
+    fsm.advance(Duration::from_secs(1))?;
+    assert_eq!(FsmCurrentState::State(LightCurrentState::Green), fsm.get_current_states()[0]);
+
+    fsm.advance(Duration::from_secs(27))?;
+    assert_eq!(FsmCurrentState::State(LightCurrentState::Yellow), fsm.get_current_states()[0]);
+    assert_eq!(2, fsm.blinks);
+
+    Ok(())
+}
+
+#[test]
+fn test_advance_through_the_cycle() -> FsmResult<()> {
+    let mut fsm = new_light()?;
+
+    // the timers started by the transitions fire within the same advance
+    fsm.advance(Duration::from_secs(61))?;
+    assert_eq!(FsmCurrentState::State(LightCurrentState::Red), fsm.get_current_states()[0]);
+    assert_eq!(Duration::from_secs(61), fsm.timers.elapsed());
+
+    // the yellow state was exited before its fifth blink
+    assert_eq!(4, fsm.blinks);
+    assert!(fsm.timers.is_running(LightTimers::RedTimer));
+    assert!(!fsm.timers.is_running(LightTimers::BlinkTimer));
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmBackendImpl, FsmCurrentState, FsmError, FsmEvent, FsmEventQueueVec, FsmFactory, FsmMiddleware, FsmResult, FsmTimers, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::tokio::TimersTokio};
+
+#[derive(Debug, Default)]
+pub struct TokioTimersContext {
+    exit_a: bool
+}
+
+#[derive(Default)]
+pub struct StateA {
+    ticks: usize
+}
+#[derive(Default)]
+pub struct StateB;
+#[derive(Clone, Debug)]
+pub struct EventTick;
+#[derive(Clone, Debug)]
+pub struct EventTimeout;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<TokioTimersMachine, TokioTimersContext>) -> BuiltFsm {
+    fsm.initial_state::<StateA>();
+
+    fsm.state::<StateA>()
+        .on_exit(|_, ctx| {
+            ctx.exit_a = true;
+        })
+        .on_event::<EventTick>()
+        .internal_transition()
+        .action(|_, _, state| {
+            state.ticks += 1;
+        });
+
+    fsm.state::<StateA>()
+        .on_event::<EventTimeout>()
+        .transition_to::<StateB>();
+
+    fsm.state::<StateA>()
+        .on_entry_start_timer(|_ctx, timer| {
+            timer.timeout = Duration::from_millis(20);
+            timer.renew = true;
+        }, |_ctx, _state| {
+            Some( EventTick.into() )
+        })
+        .with_timer_ty::<TickTimer>();
+
+    fsm.state::<StateA>()
+        .on_entry_start_timer(|_ctx, timer| {
+            timer.timeout = Duration::from_millis(110);
+        }, |_ctx, _state| {
+            Some( EventTimeout.into() )
+        })
+        .with_timer_ty::<TimeoutTimer>();
+
+    fsm.state::<StateB>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Blinking;
+#[derive(Default)]
+pub struct Dark;
+#[derive(Clone, Debug)]
+pub struct Flash;
+#[derive(Clone, Debug)]
+pub struct PowerDown;
+
+#[finny_fsm]
+fn build_beacon_fsm(mut fsm: FsmBuilder<Beacon, ()>) -> BuiltFsm {
+    fsm.initial_state::<Blinking>();
+
+    // the flashes aren't handled while blinking
+    fsm.state::<Blinking>()
+        .on_entry_start_timer(|_ctx, timer| {
+            timer.timeout = Duration::from_millis(10);
+            timer.renew = true;
+        }, |_ctx, _state| {
+            Some( Flash.into() )
+        })
+        .with_timer_ty::<FlashTimer>();
+
+    fsm.state::<Blinking>()
+        .on_entry_start_timer(|_ctx, timer| {
+            timer.timeout = Duration::from_millis(50);
+        }, |_ctx, _state| {
+            Some( PowerDown.into() )
+        })
+        .with_timer_ty::<PowerDownTimer>();
+
+    fsm.state::<Blinking>()
+        .on_event::<PowerDown>()
+        .transition_to::<Dark>();
+
+    fsm.state::<Dark>()
+        .on_event::<Flash>()
+        .internal_transition();
+
+    fsm.build()
+}
+
+#[tokio::test]
+async fn test_tokio_timers() -> FsmResult<()> {
+    let mut fsm = TokioTimersMachine::new_with(TokioTimersContext::default(), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?;
+
+    fsm.start()?;
+
+    let state = loop {
+        fsm.wait_and_dispatch_timer_events().await?;
+        let state = fsm.get_current_states()[0];
+        if state != FsmCurrentState::State(TokioTimersMachineCurrentState::StateA) {
+            break state;
+        }
+    };
+
+    assert_eq!(FsmCurrentState::State(TokioTimersMachineCurrentState::StateB), state);
+    assert!(fsm.exit_a);
+    let state_a: &StateA = fsm.get_state();
+    assert!(state_a.ticks >= 3);
+
+    // the tick timer was cancelled when leaving the state
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(None, fsm.timers.get_triggered_timer());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tokio_timers_cancel() -> FsmResult<()> {
+    let mut timers = TimersTokio::<TokioTimersMachine>::new();
+    let settings = finny::TimerSettings { enabled: true, timeout: Duration::from_millis(10), renew: false };
+
+    timers.create(TokioTimersMachineTimers::TickTimer, &settings)?;
+    timers.create(TokioTimersMachineTimers::TimeoutTimer, &settings)?;
+    timers.cancel(TokioTimersMachineTimers::TickTimer)?;
+
+    let triggered = timers.wait_for_triggered_timer().await;
+    assert_eq!(Some(TokioTimersMachineTimers::TimeoutTimer), triggered);
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(None, timers.get_triggered_timer());
+
+    Ok(())
+}
+
+/// Dispatches the flashes directly, instead of enqueueing them from the flash timer's trigger.
+struct FlashRelay;
+
+impl FsmMiddleware<Beacon> for FlashRelay {
+    fn before_dispatch(&mut self, event: FsmEvent<BeaconEvents, BeaconTimers>, _backend: &FsmBackendImpl<Beacon>) -> FsmResult<Option<FsmEvent<BeaconEvents, BeaconTimers>>> {
+        match event {
+            FsmEvent::Timer(BeaconTimers::FlashTimer) => Ok(Some(FsmEvent::Event(Flash.into()))),
+            event => Ok(Some(event))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_tokio_run_timers_past_unhandled_events() -> FsmResult<()> {
+    let mut fsm = Beacon::new_with((), FsmEventQueueVec::new(), InspectNull::new(), TimersTokio::new())?;
+    fsm.start()?;
+    fsm.middlewares.add(FlashRelay);
+    assert!(matches!(fsm.dispatch(Flash), Err(FsmError::NoTransition { .. })));
+
+    let running = tokio::time::timeout(Duration::from_millis(150), fsm.run_timers()).await;
+    assert!(running.is_err());
+    assert_eq!(FsmCurrentState::State(BeaconCurrentState::Dark), fsm.get_current_states()[0]);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+extern crate finny;
+
+use std::time::Duration;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmFrontend, FsmResult, FsmTimers, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull, timers::{clock::FsmClockManual, wheel::TimersWheel}};
+
+#[derive(Default)]
+pub struct SessionContext {
+    heartbeats: usize
+}
+
+#[derive(Default)]
+pub struct Active;
+#[derive(Default)]
+pub struct Expired;
+
+#[derive(Clone, Debug)]
+pub struct Timeout;
+#[derive(Clone, Debug)]
+pub struct Heartbeat;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Session, SessionContext>) -> BuiltFsm {
+    fsm.initial_state::<Active>();
+
+    fsm.state::<Active>()
+        .on_entry_start_timer(|_ctx, settings| {
+            settings.timeout = Duration::from_secs(35);
+        }, |_ctx, _state| {
+            Some( Timeout.into() )
+        })
+        .with_timer_ty::<SessionTimer>();
+
+    fsm.state::<Active>()
+        .on_timer_interval(Duration::from_secs(10), |_ctx, _state| {
+            Some( Heartbeat.into() )
+        })
+        .with_timer_ty::<HeartbeatTimer>();
+
+    fsm.state::<Active>()
+        .on_event::<Heartbeat>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.heartbeats += 1;
+        });
+
+    fsm.state::<Active>()
+        .on_event::<Timeout>()
+        .transition_to::<Expired>();
+
+    fsm.state::<Expired>();
+
+    fsm.build()
+}
+
+type SessionFsm = FsmFrontend<Session, FsmEventQueueVec<Session>, InspectNull, TimersWheel<Session, FsmClockManual>>;
+
+fn new_session(clock: &FsmClockManual) -> FsmResult<SessionFsm> {
+    // a turn of the wheel is shorter than the session's timeout
+    let timers = TimersWheel::with_resolution(clock.clone(), Duration::from_secs(1), 16);
+    let mut fsm = Session::new_with(SessionContext::default(), FsmEventQueueVec::new(), InspectNull::new(), timers)?;
+    fsm.start()?;
+    Ok(fsm)
+}
+
+#[test]
+fn test_wheel_timers() -> FsmResult<()> {
+    let clock = FsmClockManual::new();
+    let mut fsm = new_session(&clock)?;
+    assert!(fsm.timers.is_running(SessionTimers::SessionTimer));
+
+    clock.advance(Duration::from_secs(25));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(2, fsm.heartbeats);
+    assert_eq!(FsmCurrentState::State(SessionCurrentState::Active), fsm.get_current_states()[0]);
+
+    // the heartbeat is due before the timeout
+    clock.advance(Duration::from_secs(10));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(3, fsm.heartbeats);
+    assert_eq!(FsmCurrentState::State(SessionCurrentState::Expired), fsm.get_current_states()[0]);
+    assert!(!fsm.timers.is_running(SessionTimers::HeartbeatTimer));
+
+    // the cancelled interval never triggers again
+    clock.advance(Duration::from_secs(60));
+    fsm.dispatch_timer_events()?;
+    assert_eq!(3, fsm.heartbeats);
+    assert!(fsm.timers.is_empty());
+
+    Ok(())
+}
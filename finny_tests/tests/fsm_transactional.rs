@@ -0,0 +1,123 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmError, FsmEventQueue, FsmFactory, FsmResult, decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+
+#[derive(Default, Clone)]
+pub struct LedgerContext {
+    balance: i64,
+    journal: Vec<&'static str>
+}
+
+#[derive(Default, Clone)]
+pub struct Idle {
+    exits: usize
+}
+#[derive(Default, Clone)]
+pub struct Settled;
+
+#[derive(Clone)]
+pub struct Transfer { amount: i64 }
+#[derive(Clone)]
+pub struct Notify;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Ledger, LedgerContext>) -> BuiltFsm {
+    fsm.transactional();
+    fsm.initial_state::<Idle>();
+
+    fsm.state::<Idle>()
+        .on_exit(|state, ctx| {
+            state.exits += 1;
+            ctx.journal.push("idle exited");
+        })
+        .on_event::<Transfer>()
+        .transition_to::<Settled>()
+        .try_action(|ev, ctx, _from, _to| {
+            ctx.balance -= ev.amount;
+            ctx.queue.enqueue(Notify)?;
+            if ctx.balance < 0 {
+                return Err(FsmError::ActionFailed("overdrawn"));
+            }
+            Ok(())
+        });
+
+    fsm.state::<Settled>()
+        .on_event::<Notify>()
+        .internal_transition()
+        .action(|_ev, ctx, _state| {
+            ctx.journal.push("notified");
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_transactional_rollback() -> FsmResult<()> {
+    let mut fsm = Ledger::new(LedgerContext { balance: 100, journal: vec![] })?;
+    fsm.start()?;
+
+    assert_eq!(Err(FsmError::ActionFailed("overdrawn")), fsm.dispatch(Transfer { amount: 150 }));
+    assert_eq!(100, fsm.balance);
+    assert!(fsm.journal.is_empty());
+    assert_eq!(0, fsm.queue.len());
+    assert_eq!(FsmCurrentState::State(LedgerCurrentState::Idle), fsm.get_current_states()[0]);
+    let idle: &Idle = fsm.get_state();
+    assert_eq!(0, idle.exits);
+
+    fsm.dispatch(Transfer { amount: 50 })?;
+    assert_eq!(50, fsm.balance);
+    assert_eq!(vec!["idle exited", "notified"], fsm.journal);
+    assert_eq!(FsmCurrentState::State(LedgerCurrentState::Settled), fsm.get_current_states()[0]);
+
+    Ok(())
+}
+
+#[derive(Default, Clone)]
+pub struct Fault;
+
+#[finny_fsm]
+fn build_guarded_fsm(mut fsm: FsmBuilder<FaultyLedger, LedgerContext>) -> BuiltFsm {
+    fsm.transactional();
+    fsm.initial_state::<Idle>();
+    fsm.on_error().transition_to::<Fault>();
+
+    fsm.state::<Idle>()
+        .on_exit(|state, ctx| {
+            state.exits += 1;
+            ctx.journal.push("idle exited");
+        })
+        .on_event::<Transfer>()
+        .transition_to::<Settled>()
+        .try_action(|ev, ctx, _from, _to| {
+            ctx.balance -= ev.amount;
+            if ctx.balance < 0 {
+                return Err(FsmError::ActionFailed("overdrawn"));
+            }
+            Ok(())
+        });
+
+    fsm.state::<Settled>();
+
+    fsm.state::<Fault>()
+        .on_entry(|_state, ctx| {
+            ctx.journal.push("fault");
+        });
+
+    fsm.build()
+}
+
+#[test]
+fn test_transactional_rollback_into_fault() -> FsmResult<()> {
+    let mut fsm = FaultyLedger::new(LedgerContext { balance: 100, journal: vec![] })?;
+    fsm.start()?;
+
+    fsm.dispatch(Transfer { amount: 150 })?;
+    assert_eq!(FsmCurrentState::State(FaultyLedgerCurrentState::Fault), fsm.get_current_states()[0]);
+    assert_eq!(Some(FsmError::ActionFailed("overdrawn")), fsm.backend.take_error());
+    assert_eq!(100, fsm.balance);
+    assert_eq!(vec!["idle exited", "fault"], fsm.journal);
+    let idle: &Idle = fsm.get_state();
+    assert_eq!(1, idle.exits);
+
+    Ok(())
+}
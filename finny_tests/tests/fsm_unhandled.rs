@@ -0,0 +1,79 @@
+extern crate finny;
+
+use finny::{FsmCurrentState, FsmEventQueueVec, FsmFactory, FsmResult, FsmTimersNull, FsmUnhandledEventPolicy, decl::{BuiltFsm, FsmBuilder}, finny_fsm, inspect::null::InspectNull};
+
+#[derive(Default)]
+pub struct DoorContext {
+    unhandled: Vec<String>
+}
+
+#[derive(Default)]
+pub struct Closed;
+#[derive(Default)]
+pub struct Open;
+
+#[derive(Clone, Debug)]
+pub struct OpenDoor;
+#[derive(Clone, Debug)]
+pub struct CloseDoor;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Door, DoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.on_unhandled_event(|ev, ctx| {
+        ctx.context.unhandled.push(ev.as_ref().to_string());
+    });
+    fsm.unhandled_event_policy(FsmUnhandledEventPolicy::Ignore);
+
+    fsm.state::<Closed>().on_event::<OpenDoor>().transition_to::<Open>();
+    fsm.state::<Open>().on_event::<CloseDoor>().transition_to::<Closed>();
+
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct StrictDoorContext {
+    unhandled: usize
+}
+
+#[finny_fsm]
+fn build_strict_fsm(mut fsm: FsmBuilder<StrictDoor, StrictDoorContext>) -> BuiltFsm {
+    fsm.initial_state::<Closed>();
+    fsm.on_unhandled_event(|_ev, ctx| {
+        ctx.unhandled += 1;
+    });
+
+    fsm.state::<Closed>().on_event::<OpenDoor>().transition_to::<Open>();
+    fsm.state::<Open>().on_event::<CloseDoor>().transition_to::<Closed>();
+
+    fsm.build()
+}
+
+#[test]
+fn test_unhandled_ignored() -> FsmResult<()> {
+    let mut fsm = Door::new_with(DoorContext::default(), FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    fsm.dispatch(CloseDoor)?;
+    fsm.dispatch(OpenDoor)?;
+    fsm.dispatch(OpenDoor)?;
+
+    assert_eq!([FsmCurrentState::State(DoorCurrentState::Open)], fsm.get_current_states());
+    assert_eq!(vec!["CloseDoor".to_string(), "OpenDoor".to_string()], fsm.unhandled);
+
+    Ok(())
+}
+
+#[test]
+fn test_unhandled_error() -> FsmResult<()> {
+    let mut fsm = StrictDoor::new_with(StrictDoorContext::default(), FsmEventQueueVec::new(), InspectNull::new(), FsmTimersNull)?;
+    fsm.start()?;
+
+    assert!(fsm.dispatch(CloseDoor).is_err());
+    fsm.dispatch(OpenDoor)?;
+
+    assert_eq!([FsmCurrentState::State(StrictDoorCurrentState::Open)], fsm.get_current_states());
+    assert_eq!(1, fsm.unhandled);
+
+    Ok(())
+}
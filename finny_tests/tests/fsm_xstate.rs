@@ -0,0 +1,77 @@
+extern crate finny;
+
+use finny::{decl::{BuiltFsm, FsmBuilder}, finny_fsm};
+use serde_json::{Value, json};
+
+#[derive(Default)]
+pub struct PlayerContext;
+
+#[derive(Default)]
+pub struct Idle;
+#[derive(Default)]
+pub struct Playing;
+#[derive(Default)]
+pub struct Ejected;
+
+#[derive(Debug, Clone)]
+pub struct Play;
+#[derive(Debug, Clone)]
+pub struct Seek;
+#[derive(Debug, Clone)]
+pub struct Eject;
+
+#[finny_fsm]
+fn build_fsm(mut fsm: FsmBuilder<Player, PlayerContext>) -> BuiltFsm {
+    fsm.initial_state::<Idle>();
+    fsm.state::<Idle>().on_event::<Play>().transition_to::<Playing>().guard(|_, _, _| true);
+    fsm.state::<Idle>().on_event::<Eject>().transition_to::<Ejected>();
+    fsm.state::<Playing>().on_event::<Seek>().internal_transition();
+    fsm.state::<Playing>().on_event::<Eject>().transition_to::<PlayerSub>();
+    fsm.state::<Ejected>().final_state();
+    fsm.sub_machine::<PlayerSub>()
+        .with_context(|_| PlayerContext);
+    fsm.build()
+}
+
+#[derive(Default)]
+pub struct Spinning;
+
+#[finny_fsm]
+fn build_sub_fsm(mut fsm: FsmBuilder<PlayerSub, PlayerContext>) -> BuiltFsm {
+    fsm.initial_state::<Spinning>();
+    fsm.state::<Spinning>();
+    fsm.build()
+}
+
+#[test]
+fn test_xstate() {
+    let mut machine: Value = serde_json::from_str(&PlayerInfo::xstate()).unwrap();
+
+    // the guards are named after the generated transition types
+    let cond = machine["states"]["Idle"]["on"]["Play"][0].as_object_mut().and_then(|t| t.remove("cond"));
+    assert!(cond.and_then(|c| c.as_str().map(|c| c.starts_with("PlayerTransition"))).unwrap_or(false));
+
+    assert_eq!(json!({
+        "id": "Player",
+        "initial": "Idle",
+        "states": {
+            "Idle": {
+                "on": {
+                    "Play": [{ "target": "Playing" }],
+                    "Eject": [{ "target": "Ejected" }]
+                }
+            },
+            "Playing": {
+                "on": {
+                    "Seek": [{ "internal": true }],
+                    "Eject": [{ "target": "PlayerSub" }]
+                }
+            },
+            "Ejected": { "type": "final" },
+            "PlayerSub": {
+                "initial": "Spinning",
+                "states": { "Spinning": {} }
+            }
+        }
+    }), machine);
+}